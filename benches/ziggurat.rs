@@ -0,0 +1,81 @@
+use criterion::Criterion;
+use criterion::{criterion_group, criterion_main};
+use rv::traits::{InverseCdf, Rv};
+
+// Gaussian and Exponential draw with the Ziggurat samplers in rand_distr.
+// These compare them to the textbook alternatives.
+
+fn draw_box_muller<R: rand::Rng>(rng: &mut R) -> f64 {
+    let u1: f64 = rng.gen();
+    let u2: f64 = rng.gen();
+    (-2.0 * (1.0 - u1).ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+fn draw_exp_inversion<R: rand::Rng>(rng: &mut R) -> f64 {
+    let u: f64 = rng.gen();
+    -(1.0 - u).ln()
+}
+
+fn bench_gaussian_draw(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gaussian_draw");
+    group.bench_function("ziggurat", |b| {
+        let mut rng = rand::thread_rng();
+        let gauss = rv::dist::Gaussian::standard();
+        b.iter(|| {
+            let _x: f64 = gauss.draw(&mut rng);
+        })
+    });
+    group.bench_function("box_muller", |b| {
+        let mut rng = rand::thread_rng();
+        b.iter(|| draw_box_muller(&mut rng))
+    });
+    group.bench_function("inverse_cdf", |b| {
+        let mut rng = rand::thread_rng();
+        let gauss = rv::dist::Gaussian::standard();
+        b.iter(|| {
+            let _x: f64 = gauss.invcdf(rand::Rng::gen(&mut rng));
+        })
+    });
+}
+
+fn bench_exponential_draw(c: &mut Criterion) {
+    let mut group = c.benchmark_group("exponential_draw");
+    group.bench_function("ziggurat", |b| {
+        let mut rng = rand::thread_rng();
+        let expon = rv::dist::Exponential::new(1.0).unwrap();
+        b.iter(|| {
+            let _x: f64 = expon.draw(&mut rng);
+        })
+    });
+    group.bench_function("inversion", |b| {
+        let mut rng = rand::thread_rng();
+        b.iter(|| draw_exp_inversion(&mut rng))
+    });
+}
+
+fn bench_gaussian_sample(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gaussian_sample_1000");
+    group.bench_function("ziggurat", |b| {
+        let mut rng = rand::thread_rng();
+        let gauss = rv::dist::Gaussian::new(1.0, 2.0).unwrap();
+        b.iter(|| {
+            let _xs: Vec<f64> = gauss.sample(1000, &mut rng);
+        })
+    });
+    group.bench_function("box_muller", |b| {
+        let mut rng = rand::thread_rng();
+        b.iter(|| {
+            let _xs: Vec<f64> = (0..1000)
+                .map(|_| 2.0_f64.mul_add(draw_box_muller(&mut rng), 1.0))
+                .collect();
+        })
+    });
+}
+
+criterion_group!(
+    ziggurat_benches,
+    bench_gaussian_draw,
+    bench_exponential_draw,
+    bench_gaussian_sample,
+);
+criterion_main!(ziggurat_benches);
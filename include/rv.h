@@ -0,0 +1,65 @@
+/*
+ * C interface to rv. Build with the `ffi` feature:
+ *
+ *     cargo rustc --release --features ffi --crate-type cdylib
+ *
+ * Constructors return NULL if the parameters are invalid. Every handle must
+ * be freed exactly once with the matching `_free` function; freeing NULL does
+ * nothing. Functions returning a double return NaN if a handle is NULL or an
+ * argument is out of range. No function unwinds. Discrete distributions take
+ * and return their values as doubles, and have zero mass at non-integers.
+ */
+#ifndef RV_H
+#define RV_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Opaque handle to a univariate distribution */
+typedef struct RvDist RvDist;
+
+/* Opaque handle to a seeded random number generator. Not thread safe. */
+typedef struct RvRng RvRng;
+
+/* Constructors */
+RvDist *rv_gaussian_new(double mu, double sigma);
+RvDist *rv_lognormal_new(double mu, double sigma);
+RvDist *rv_gamma_new(double shape, double rate);
+RvDist *rv_beta_new(double alpha, double beta);
+RvDist *rv_exponential_new(double rate);
+RvDist *rv_uniform_new(double a, double b);
+RvDist *rv_cauchy_new(double loc, double scale);
+RvDist *rv_weibull_new(double shape, double scale);
+RvDist *rv_poisson_new(double rate);
+
+void rv_dist_free(RvDist *dist);
+
+/* Density, or mass for discrete distributions, and its log */
+double rv_pdf(const RvDist *dist, double x);
+double rv_ln_pdf(const RvDist *dist, double x);
+
+/* Cumulative distribution function */
+double rv_cdf(const RvDist *dist, double x);
+
+/* Quantile function. For discrete distributions, the smallest value whose
+ * CDF is at least p. NaN if p is not in [0, 1]. */
+double rv_quantile(const RvDist *dist, double p);
+
+/* Random number generation */
+RvRng *rv_rng_new(uint64_t seed);
+void rv_rng_free(RvRng *rng);
+
+double rv_draw(const RvDist *dist, RvRng *rng);
+
+/* Draw n values into out. Returns 0 on success or -1 if a handle is NULL. */
+int32_t rv_sample(const RvDist *dist, RvRng *rng, size_t n, double *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* RV_H */
@@ -0,0 +1,174 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// A bidirectional map between string labels and the indices 0, ..., k-1
+///
+/// Labels are assigned indices in the order they are added. The map is
+/// serialized as its list of labels.
+///
+/// # Example
+///
+/// ```
+/// use rv::data::LabelMap;
+///
+/// let mut labels = LabelMap::new(vec!["noun", "verb"]).unwrap();
+/// assert_eq!(labels.index("verb"), Some(1));
+/// assert_eq!(labels.label(0), Some("noun"));
+///
+/// // Inserting a new label gives it the next index
+/// assert_eq!(labels.insert("adjective"), 2);
+/// assert_eq!(labels.insert("noun"), 0);
+/// assert_eq!(labels.len(), 3);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde1",
+    serde(try_from = "Vec<String>", into = "Vec<String>")
+)]
+pub struct LabelMap {
+    /// The label of each index
+    labels: Vec<String>,
+    /// The index of each label
+    indices: HashMap<String, usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum LabelMapError {
+    /// A label appears more than once
+    DuplicateLabel { label: String },
+}
+
+impl LabelMap {
+    /// Create a map assigning `labels` the indices 0, ..., k-1 in order
+    pub fn new<S: Into<String>>(
+        labels: impl IntoIterator<Item = S>,
+    ) -> Result<Self, LabelMapError> {
+        let mut map = LabelMap::default();
+        for label in labels {
+            let label = label.into();
+            if map.indices.contains_key(&label) {
+                return Err(LabelMapError::DuplicateLabel { label });
+            }
+            map.insert(label);
+        }
+        Ok(map)
+    }
+
+    /// The number of labels
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Returns `true` if there are no labels
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// The labels in index order
+    #[inline]
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// The index of `label`, if it is in the map
+    #[inline]
+    pub fn index(&self, label: &str) -> Option<usize> {
+        self.indices.get(label).copied()
+    }
+
+    /// The label at index `ix`, if there is one
+    #[inline]
+    pub fn label(&self, ix: usize) -> Option<&str> {
+        self.labels.get(ix).map(String::as_str)
+    }
+
+    /// Returns `true` if `label` is in the map
+    #[inline]
+    pub fn contains(&self, label: &str) -> bool {
+        self.indices.contains_key(label)
+    }
+
+    /// The index of `label`, adding it with the next index if it is new
+    pub fn insert<S: Into<String>>(&mut self, label: S) -> usize {
+        let label = label.into();
+        if let Some(&ix) = self.indices.get(&label) {
+            ix
+        } else {
+            let ix = self.labels.len();
+            self.indices.insert(label.clone(), ix);
+            self.labels.push(label);
+            ix
+        }
+    }
+}
+
+impl TryFrom<Vec<String>> for LabelMap {
+    type Error = LabelMapError;
+
+    fn try_from(labels: Vec<String>) -> Result<Self, Self::Error> {
+        LabelMap::new(labels)
+    }
+}
+
+impl From<LabelMap> for Vec<String> {
+    fn from(map: LabelMap) -> Self {
+        map.labels
+    }
+}
+
+impl std::error::Error for LabelMapError {}
+
+impl fmt::Display for LabelMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateLabel { label } => {
+                write!(f, "duplicate label: {}", label)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_rejects_duplicates() {
+        assert_eq!(
+            LabelMap::new(["a", "b", "a"]),
+            Err(LabelMapError::DuplicateLabel {
+                label: String::from("a")
+            })
+        );
+    }
+
+    #[test]
+    fn index_and_label_are_inverse() {
+        let map = LabelMap::new(["x", "y", "z"]).unwrap();
+        for (ix, label) in map.labels().iter().enumerate() {
+            assert_eq!(map.index(label), Some(ix));
+            assert_eq!(map.label(ix), Some(label.as_str()));
+        }
+        assert_eq!(map.index("w"), None);
+        assert_eq!(map.label(3), None);
+    }
+
+    #[cfg(feature = "serde1")]
+    #[test]
+    fn serde_round_trip() {
+        let map = LabelMap::new(["x", "y"]).unwrap();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"["x","y"]"#);
+        let map_de: LabelMap = serde_json::from_str(&json).unwrap();
+        assert_eq!(map_de, map);
+        assert!(serde_json::from_str::<LabelMap>(r#"["x","x"]"#).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 //! Data utilities
+mod label_map;
 mod partition;
 mod stat;
 
@@ -8,16 +9,25 @@ mod datum;
 #[cfg(feature = "datum")]
 pub use datum::Datum;
 
+pub use label_map::{LabelMap, LabelMapError};
 pub use partition::Partition;
 pub use stat::BernoulliSuffStat;
 pub use stat::BetaSuffStat;
+pub use stat::BinomialSuffStat;
 pub use stat::CategoricalSuffStat;
+pub use stat::ExponentialSuffStat;
+pub use stat::GammaSuffStat;
 pub use stat::GaussianSuffStat;
+pub use stat::GeometricSuffStat;
 pub use stat::InvGammaSuffStat;
 pub use stat::InvGaussianSuffStat;
+pub use stat::MarkovChainSuffStat;
 #[cfg(feature = "arraydist")]
 pub use stat::MvGaussianSuffStat;
+pub use stat::ParetoSuffStat;
 pub use stat::PoissonSuffStat;
+pub use stat::UniformSuffStat;
+pub use stat::ZeroInflatedSuffStat;
 
 use crate::dist::{
     Bernoulli, Categorical, Gaussian, InvGamma, InvGaussian, Poisson,
@@ -121,6 +121,20 @@ impl<X: Booleable> SuffStat<X> for BernoulliSuffStat {
             self.k -= 1
         }
     }
+
+    fn observe_count(&mut self, x: &X, count: usize) {
+        self.n += count;
+        if x.into_bool() {
+            self.k += count
+        }
+    }
+
+    fn forget_count(&mut self, x: &X, count: usize) {
+        self.n -= count;
+        if x.into_bool() {
+            self.k -= count
+        }
+    }
 }
 
 #[cfg(test)]
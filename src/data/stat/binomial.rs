@@ -0,0 +1,182 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::DataOrSuffStat;
+use crate::dist::Binomial;
+use crate::misc::ln_binom;
+use crate::traits::SuffStat;
+
+/// Sufficient statistic for the Binomial distribution over `(k, n)` data,
+/// where each observation is `k` successes out of `n` trials.
+///
+/// Contains the number of observations, the total number of successes and
+/// trials, and the sum of the log binomial coefficients.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct BinomialSuffStat {
+    /// Number of observations
+    n: usize,
+    /// Total number of successes
+    k: u64,
+    /// Total number of trials
+    trials: u64,
+    /// Sum of `ln(n choose k)`
+    sum_ln_binom: f64,
+}
+
+impl BinomialSuffStat {
+    /// Create a new empty Binomial sufficient statistic
+    #[inline]
+    pub fn new() -> Self {
+        BinomialSuffStat {
+            n: 0,
+            k: 0,
+            trials: 0,
+            sum_ln_binom: 0.0,
+        }
+    }
+
+    /// Create a sufficient statistic from components without checking whether
+    /// they are valid.
+    #[inline]
+    pub fn from_parts_unchecked(
+        n: usize,
+        k: u64,
+        trials: u64,
+        sum_ln_binom: f64,
+    ) -> Self {
+        BinomialSuffStat {
+            n,
+            k,
+            trials,
+            sum_ln_binom,
+        }
+    }
+
+    /// Get the number of observations
+    #[inline]
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Get the total number of successes, Σk.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rv::data::BinomialSuffStat;
+    /// # use rv::traits::SuffStat;
+    /// let mut stat = BinomialSuffStat::new();
+    ///
+    /// stat.observe(&(3_u32, 10_u32));
+    /// stat.observe(&(1_u32, 4_u32));
+    ///
+    /// assert_eq!(stat.k(), 4);
+    /// assert_eq!(stat.trials(), 14);
+    /// ```
+    #[inline]
+    pub fn k(&self) -> u64 {
+        self.k
+    }
+
+    /// Get the total number of trials, Σn
+    #[inline]
+    pub fn trials(&self) -> u64 {
+        self.trials
+    }
+
+    /// Get the sum of the log binomial coefficients, Σ ln(n choose k)
+    #[inline]
+    pub fn sum_ln_binom(&self) -> f64 {
+        self.sum_ln_binom
+    }
+}
+
+impl Default for BinomialSuffStat {
+    fn default() -> Self {
+        BinomialSuffStat::new()
+    }
+}
+
+macro_rules! impl_binomial_suffstat {
+    ($kind:ty) => {
+        impl<'a> From<&'a BinomialSuffStat>
+            for DataOrSuffStat<'a, ($kind, $kind), Binomial>
+        {
+            fn from(stat: &'a BinomialSuffStat) -> Self {
+                DataOrSuffStat::SuffStat(stat)
+            }
+        }
+
+        impl<'a> From<&'a Vec<($kind, $kind)>>
+            for DataOrSuffStat<'a, ($kind, $kind), Binomial>
+        {
+            fn from(xs: &'a Vec<($kind, $kind)>) -> Self {
+                DataOrSuffStat::Data(xs)
+            }
+        }
+
+        impl<'a> From<&'a [($kind, $kind)]>
+            for DataOrSuffStat<'a, ($kind, $kind), Binomial>
+        {
+            fn from(xs: &'a [($kind, $kind)]) -> Self {
+                DataOrSuffStat::Data(xs)
+            }
+        }
+
+        impl SuffStat<($kind, $kind)> for BinomialSuffStat {
+            fn n(&self) -> usize {
+                self.n
+            }
+
+            fn observe(&mut self, x: &($kind, $kind)) {
+                let (k, n) = *x;
+                self.n += 1;
+                self.k += k as u64;
+                self.trials += n as u64;
+                self.sum_ln_binom += ln_binom(n as f64, k as f64);
+            }
+
+            fn forget(&mut self, x: &($kind, $kind)) {
+                if self.n > 1 {
+                    let (k, n) = *x;
+                    self.n -= 1;
+                    self.k -= k as u64;
+                    self.trials -= n as u64;
+                    self.sum_ln_binom -= ln_binom(n as f64, k as f64);
+                } else {
+                    *self = BinomialSuffStat::new();
+                }
+            }
+        }
+    };
+}
+
+impl_binomial_suffstat!(u8);
+impl_binomial_suffstat!(u16);
+impl_binomial_suffstat!(u32);
+impl_binomial_suffstat!(u64);
+impl_binomial_suffstat!(usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn observe_and_forget() {
+        let mut stat = BinomialSuffStat::new();
+        stat.observe(&(2_u8, 5_u8));
+        stat.observe(&(0_u8, 3_u8));
+        assert_eq!(stat.n(), 2);
+        assert_eq!(stat.k(), 2);
+        assert_eq!(stat.trials(), 8);
+        assert::close(stat.sum_ln_binom(), 10_f64.ln(), 1E-12);
+
+        stat.forget(&(2_u8, 5_u8));
+        assert_eq!(stat.k(), 0);
+        assert_eq!(stat.trials(), 3);
+        stat.forget(&(0_u8, 3_u8));
+        assert_eq!(stat, BinomialSuffStat::new());
+    }
+}
@@ -3,19 +3,27 @@ use serde::{Deserialize, Serialize};
 
 use crate::data::CategoricalDatum;
 use crate::data::DataOrSuffStat;
-use crate::dist::Categorical;
+use crate::dist::{Categorical, Dpd};
 use crate::traits::SuffStat;
 
 /// Categorical distribution sufficient statistic.
 ///
 /// Store the number of observations and the count of observations of each
 /// instance.
+///
+/// A statistic created with
+/// [`with_capacity_growing`](CategoricalSuffStat::with_capacity_growing)
+/// extends its counts whenever it observes a category index beyond the
+/// current number of categories, which is useful for open category sets.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
 pub struct CategoricalSuffStat {
     n: usize,
     counts: Vec<f64>,
+    /// Whether to grow `counts` when observing an unseen category index
+    #[cfg_attr(feature = "serde1", serde(default))]
+    growing: bool,
 }
 
 impl CategoricalSuffStat {
@@ -24,6 +32,32 @@ impl CategoricalSuffStat {
         CategoricalSuffStat {
             n: 0,
             counts: vec![0.0; k],
+            growing: false,
+        }
+    }
+
+    /// Create a sufficient statistic that starts with `k` categories and
+    /// grows as larger category indices are observed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rv::data::CategoricalSuffStat;
+    /// # use rv::traits::SuffStat;
+    /// let mut stat = CategoricalSuffStat::with_capacity_growing(0);
+    ///
+    /// stat.observe(&1_usize);
+    /// stat.observe(&4_usize);
+    ///
+    /// assert_eq!(stat.n(), 2);
+    /// assert_eq!(*stat.counts(), vec![0.0, 1.0, 0.0, 0.0, 1.0]);
+    /// ```
+    #[inline]
+    pub fn with_capacity_growing(k: usize) -> Self {
+        CategoricalSuffStat {
+            n: 0,
+            counts: vec![0.0; k],
+            growing: true,
         }
     }
 
@@ -31,7 +65,23 @@ impl CategoricalSuffStat {
     /// they are valid.
     #[inline]
     pub fn from_parts_unchecked(n: usize, counts: Vec<f64>) -> Self {
-        CategoricalSuffStat { n, counts }
+        CategoricalSuffStat {
+            n,
+            counts,
+            growing: false,
+        }
+    }
+
+    /// Returns `true` if the statistic grows to fit unseen categories
+    #[inline]
+    pub fn is_growing(&self) -> bool {
+        self.growing
+    }
+
+    /// The number of categories the statistic currently tracks
+    #[inline]
+    pub fn n_cats(&self) -> usize {
+        self.counts.len()
     }
 
     /// Get the total number of trials
@@ -102,6 +152,24 @@ where
     }
 }
 
+impl<'a, X> From<&'a CategoricalSuffStat> for DataOrSuffStat<'a, X, Dpd>
+where
+    X: CategoricalDatum,
+{
+    fn from(stat: &'a CategoricalSuffStat) -> Self {
+        DataOrSuffStat::SuffStat(stat)
+    }
+}
+
+impl<'a, X> From<&'a Vec<X>> for DataOrSuffStat<'a, X, Dpd>
+where
+    X: CategoricalDatum,
+{
+    fn from(xs: &'a Vec<X>) -> Self {
+        DataOrSuffStat::Data(xs.as_slice())
+    }
+}
+
 impl<X: CategoricalDatum> SuffStat<X> for CategoricalSuffStat {
     fn n(&self) -> usize {
         self.n
@@ -109,6 +177,9 @@ impl<X: CategoricalDatum> SuffStat<X> for CategoricalSuffStat {
 
     fn observe(&mut self, x: &X) {
         let ix = x.into_usize();
+        if self.growing && ix >= self.counts.len() {
+            self.counts.resize(ix + 1, 0.0);
+        }
         self.n += 1;
         self.counts[ix] += 1.0;
     }
@@ -118,6 +189,21 @@ impl<X: CategoricalDatum> SuffStat<X> for CategoricalSuffStat {
         self.n -= 1;
         self.counts[ix] -= 1.0;
     }
+
+    fn observe_count(&mut self, x: &X, count: usize) {
+        let ix = x.into_usize();
+        if self.growing && ix >= self.counts.len() {
+            self.counts.resize(ix + 1, 0.0);
+        }
+        self.n += count;
+        self.counts[ix] += count as f64;
+    }
+
+    fn forget_count(&mut self, x: &X, count: usize) {
+        let ix = x.into_usize();
+        self.n -= count;
+        self.counts[ix] -= count as f64;
+    }
 }
 
 #[cfg(test)]
@@ -145,4 +231,38 @@ mod tests {
         assert_eq!(stat.counts()[2], 3.0);
         assert_eq!(stat.counts()[3], 4.0);
     }
+
+    #[test]
+    fn growing_extends_counts() {
+        let mut stat = CategoricalSuffStat::with_capacity_growing(2);
+        stat.observe(&0_u8);
+        stat.observe(&3_u8);
+        stat.observe(&3_u8);
+        assert_eq!(stat.n_cats(), 4);
+        assert_eq!(*stat.counts(), vec![1.0, 0.0, 0.0, 2.0]);
+
+        stat.forget(&3_u8);
+        assert_eq!(stat.n(), 2);
+        assert_eq!(*stat.counts(), vec![1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn observe_count_grows_counts() {
+        let mut stat = CategoricalSuffStat::with_capacity_growing(2);
+        stat.observe_count(&3_u8, 4);
+        stat.observe_count(&0_u8, 2);
+        assert_eq!(stat.n(), 6);
+        assert_eq!(*stat.counts(), vec![2.0, 0.0, 0.0, 4.0]);
+
+        stat.forget_count(&3_u8, 3);
+        assert_eq!(stat.n(), 3);
+        assert_eq!(*stat.counts(), vec![2.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn fixed_panics_on_unseen_category() {
+        let mut stat = CategoricalSuffStat::new(2);
+        stat.observe(&2_u8);
+    }
 }
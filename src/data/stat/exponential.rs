@@ -0,0 +1,119 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::DataOrSuffStat;
+use crate::dist::Exponential;
+use crate::traits::SuffStat;
+
+/// Exponential sufficient statistic.
+///
+/// Holds the number of observations and their sum.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct ExponentialSuffStat {
+    /// Number of observations
+    n: usize,
+    /// Sum of `x`
+    sum_x: f64,
+}
+
+impl ExponentialSuffStat {
+    /// Create a new empty SuffStat
+    #[inline]
+    pub fn new() -> Self {
+        Self { n: 0, sum_x: 0.0 }
+    }
+
+    /// Create a sufficient statistic from components without checking whether
+    /// they are valid.
+    #[inline]
+    pub fn from_parts_unchecked(n: usize, sum_x: f64) -> Self {
+        Self { n, sum_x }
+    }
+
+    /// Get the number of observations
+    #[inline]
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Get the sum of `x`
+    #[inline]
+    pub fn sum_x(&self) -> f64 {
+        self.sum_x
+    }
+}
+
+impl Default for ExponentialSuffStat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+macro_rules! impl_exponential_suffstat {
+    ($kind:ty) => {
+        impl<'a> From<&'a ExponentialSuffStat>
+            for DataOrSuffStat<'a, $kind, Exponential>
+        {
+            fn from(stat: &'a ExponentialSuffStat) -> Self {
+                DataOrSuffStat::SuffStat(stat)
+            }
+        }
+
+        impl<'a> From<&'a Vec<$kind>>
+            for DataOrSuffStat<'a, $kind, Exponential>
+        {
+            fn from(xs: &'a Vec<$kind>) -> Self {
+                DataOrSuffStat::Data(xs)
+            }
+        }
+
+        impl<'a> From<&'a [$kind]> for DataOrSuffStat<'a, $kind, Exponential> {
+            fn from(xs: &'a [$kind]) -> Self {
+                DataOrSuffStat::Data(xs)
+            }
+        }
+
+        impl SuffStat<$kind> for ExponentialSuffStat {
+            fn n(&self) -> usize {
+                self.n
+            }
+
+            fn observe(&mut self, x: &$kind) {
+                self.n += 1;
+                self.sum_x += f64::from(*x);
+            }
+
+            fn forget(&mut self, x: &$kind) {
+                if self.n > 1 {
+                    self.n -= 1;
+                    self.sum_x -= f64::from(*x);
+                } else {
+                    self.n = 0;
+                    self.sum_x = 0.0;
+                }
+            }
+        }
+    };
+}
+
+impl_exponential_suffstat!(f32);
+impl_exponential_suffstat!(f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forget_last_resets() {
+        let mut stat = ExponentialSuffStat::new();
+        stat.observe(&2.0_f64);
+        stat.observe(&3.0_f64);
+        stat.forget(&2.0_f64);
+        assert_eq!(stat.n(), 1);
+        assert::close(stat.sum_x(), 3.0, 1E-12);
+        stat.forget(&3.0_f64);
+        assert_eq!(stat, ExponentialSuffStat::new());
+    }
+}
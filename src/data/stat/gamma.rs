@@ -0,0 +1,131 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::DataOrSuffStat;
+use crate::dist::Gamma;
+use crate::traits::SuffStat;
+
+/// Gamma sufficient statistic.
+///
+/// Holds the number of observations, their sum, and the sum of their logs.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct GammaSuffStat {
+    /// Number of observations
+    n: usize,
+    /// Sum of `x`
+    sum_x: f64,
+    /// Sum of `ln(x)`
+    sum_ln_x: f64,
+}
+
+impl GammaSuffStat {
+    /// Create a new empty SuffStat
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            n: 0,
+            sum_x: 0.0,
+            sum_ln_x: 0.0,
+        }
+    }
+
+    /// Create a sufficient statistic from components without checking whether
+    /// they are valid.
+    #[inline]
+    pub fn from_parts_unchecked(n: usize, sum_x: f64, sum_ln_x: f64) -> Self {
+        Self { n, sum_x, sum_ln_x }
+    }
+
+    /// Get the number of observations
+    #[inline]
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Get the sum of `x`
+    #[inline]
+    pub fn sum_x(&self) -> f64 {
+        self.sum_x
+    }
+
+    /// Get the sum of `ln(x)`
+    #[inline]
+    pub fn sum_ln_x(&self) -> f64 {
+        self.sum_ln_x
+    }
+}
+
+impl Default for GammaSuffStat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+macro_rules! impl_gamma_suffstat {
+    ($kind:ty) => {
+        impl<'a> From<&'a GammaSuffStat> for DataOrSuffStat<'a, $kind, Gamma> {
+            fn from(stat: &'a GammaSuffStat) -> Self {
+                DataOrSuffStat::SuffStat(stat)
+            }
+        }
+
+        impl<'a> From<&'a Vec<$kind>> for DataOrSuffStat<'a, $kind, Gamma> {
+            fn from(xs: &'a Vec<$kind>) -> Self {
+                DataOrSuffStat::Data(xs)
+            }
+        }
+
+        impl<'a> From<&'a [$kind]> for DataOrSuffStat<'a, $kind, Gamma> {
+            fn from(xs: &'a [$kind]) -> Self {
+                DataOrSuffStat::Data(xs)
+            }
+        }
+
+        impl SuffStat<$kind> for GammaSuffStat {
+            fn n(&self) -> usize {
+                self.n
+            }
+
+            fn observe(&mut self, x: &$kind) {
+                let xf = f64::from(*x);
+                self.n += 1;
+                self.sum_x += xf;
+                self.sum_ln_x += xf.ln();
+            }
+
+            fn forget(&mut self, x: &$kind) {
+                if self.n > 1 {
+                    let xf = f64::from(*x);
+                    self.n -= 1;
+                    self.sum_x -= xf;
+                    self.sum_ln_x -= xf.ln();
+                } else {
+                    *self = Self::new();
+                }
+            }
+        }
+    };
+}
+
+impl_gamma_suffstat!(f32);
+impl_gamma_suffstat!(f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forget_last_resets() {
+        let mut stat = GammaSuffStat::new();
+        stat.observe(&2.0_f64);
+        stat.observe(&3.0_f64);
+        stat.forget(&2.0_f64);
+        assert_eq!(stat.n(), 1);
+        assert::close(stat.sum_x(), 3.0, 1E-12);
+        assert::close(stat.sum_ln_x(), 3.0_f64.ln(), 1E-12);
+        stat.forget(&3.0_f64);
+        assert_eq!(stat, GammaSuffStat::new());
+    }
+}
@@ -7,8 +7,9 @@ use crate::traits::SuffStat;
 
 /// Gaussian sufficient statistic.
 ///
-/// Holds the number of observations, their sum, and the sum of their squared
-/// values.
+/// Holds the number of observations, their mean, and the sum of their squared
+/// deviations from the mean. These are updated with Welford's algorithm, which
+/// remains accurate for data with large means or very many observations.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
@@ -50,6 +51,36 @@ impl GaussianSuffStat {
         self.mean
     }
 
+    /// Get the sum of the squared deviations from the mean
+    #[inline]
+    pub fn scatter(&self) -> f64 {
+        self.sx
+    }
+
+    /// Get the unbiased sample variance. Returns NaN if there are fewer than
+    /// two observations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::data::GaussianSuffStat;
+    /// use rv::traits::SuffStat;
+    ///
+    /// let mut stat = GaussianSuffStat::new();
+    /// stat.observe_many(&[1E9 + 4.0, 1E9 + 7.0, 1E9 + 13.0, 1E9 + 16.0]);
+    ///
+    /// assert!((stat.mean() - (1E9 + 10.0)).abs() < 1E-6);
+    /// assert!((stat.sample_var() - 30.0).abs() < 1E-6);
+    /// ```
+    #[inline]
+    pub fn sample_var(&self) -> f64 {
+        if self.n < 2 {
+            f64::NAN
+        } else {
+            self.sx / (self.n - 1) as f64
+        }
+    }
+
     /// Sum of `x`
     #[inline]
     pub fn sum_x(&self) -> f64 {
@@ -142,6 +173,47 @@ macro_rules! impl_gaussian_suffstat {
                     self.sx = 0.0;
                 }
             }
+
+            // Merges a group of `count` identical observations, whose mean
+            // is `x` and whose squared deviations sum to zero, as in
+            // Welford's parallel update.
+            fn observe_count(&mut self, x: &$kind, count: usize) {
+                if count == 0 {
+                    return;
+                }
+                let xf = f64::from(*x);
+                let n_old = self.n as f64;
+                let cf = count as f64;
+
+                self.n += count;
+
+                let nf = self.n as f64;
+                let delta = xf - self.mean;
+                self.mean = (delta * cf).mul_add(nf.recip(), self.mean);
+                self.sx = (delta * delta).mul_add(n_old * cf / nf, self.sx);
+            }
+
+            fn forget_count(&mut self, x: &$kind, count: usize) {
+                if self.n > count {
+                    let xf = f64::from(*x);
+                    let nf = self.n as f64;
+                    let cf = count as f64;
+                    let n_old = (self.n - count) as f64;
+
+                    let old_mean =
+                        (nf / n_old).mul_add(self.mean, -xf * cf / n_old);
+                    let delta = xf - old_mean;
+
+                    self.sx =
+                        (delta * delta).mul_add(-n_old * cf / nf, self.sx);
+                    self.mean = old_mean;
+                    self.n -= count;
+                } else {
+                    self.n = 0;
+                    self.mean = 0.0;
+                    self.sx = 0.0;
+                }
+            }
         }
     };
 }
@@ -176,6 +248,25 @@ mod tests {
         assert::close(suffstat.sum_x_sq(), 27.889_999_999_999_993, 1e-14);
     }
 
+    #[test]
+    fn observe_count_matches_repeated_observe() {
+        let mut counted = GaussianSuffStat::new();
+        let mut repeated = GaussianSuffStat::new();
+        for (x, count) in [(1.5_f64, 3), (-0.5, 1), (4.0, 0), (2.25, 5)] {
+            counted.observe_count(&x, count);
+            (0..count).for_each(|_| repeated.observe(&x));
+        }
+
+        assert_eq!(counted.n(), 9);
+        assert::close(counted.mean(), repeated.mean(), 1e-14);
+        assert::close(counted.sample_var(), repeated.sample_var(), 1e-13);
+
+        counted.forget_count(&2.25, 5);
+        assert_eq!(counted.n(), 4);
+        assert::close(counted.mean(), 1.0, 1e-14);
+        assert::close(counted.sum_x_sq(), 7.0, 1e-13);
+    }
+
     #[test]
     fn suffstat_decrements_correctly() {
         let xs: Vec<f64> = vec![0.0, 1.2, 2.3, 4.6];
@@ -193,4 +284,28 @@ mod tests {
         assert::close(suffstat.sum_x(), 8.1, 1e-14);
         assert::close(suffstat.sum_x_sq(), 27.889_999_999_999_993, 1e-13);
     }
+
+    #[test]
+    fn sample_var() {
+        let mut stat = GaussianSuffStat::new();
+        assert!(stat.sample_var().is_nan());
+        stat.observe(&1.0_f64);
+        assert!(stat.sample_var().is_nan());
+        stat.observe_many(&[2.0_f64, 3.0, 6.0]);
+        assert::close(stat.sample_var(), 14.0 / 3.0, 1e-14);
+    }
+
+    #[test]
+    fn stable_with_large_mean() {
+        let offset = 1E9;
+        let xs: Vec<f64> =
+            (0..1000).map(|i| offset + (i % 10) as f64).collect();
+        let mut stat = GaussianSuffStat::new();
+        stat.observe_many(&xs);
+        stat.forget(&xs[0]);
+        stat.observe(&xs[0]);
+
+        assert::close(stat.mean(), offset + 4.5, 1e-3);
+        assert::close(stat.sample_var(), 8.25 * 1000.0 / 999.0, 1e-6);
+    }
 }
@@ -0,0 +1,118 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::DataOrSuffStat;
+use crate::dist::Geometric;
+use crate::traits::SuffStat;
+
+/// Geometric sufficient statistic.
+///
+/// Holds the number of observations and the sum of the failure counts.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct GeometricSuffStat {
+    /// Number of observations
+    n: usize,
+    /// Sum of the failure counts, `k`
+    sum_k: f64,
+}
+
+impl GeometricSuffStat {
+    /// Create a new empty SuffStat
+    #[inline]
+    pub fn new() -> Self {
+        Self { n: 0, sum_k: 0.0 }
+    }
+
+    /// Create a sufficient statistic from components without checking whether
+    /// they are valid.
+    #[inline]
+    pub fn from_parts_unchecked(n: usize, sum_k: f64) -> Self {
+        Self { n, sum_k }
+    }
+
+    /// Get the number of observations
+    #[inline]
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Get the sum of the failure counts
+    #[inline]
+    pub fn sum_k(&self) -> f64 {
+        self.sum_k
+    }
+}
+
+impl Default for GeometricSuffStat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+macro_rules! impl_geometric_suffstat {
+    ($kind:ty) => {
+        impl<'a> From<&'a GeometricSuffStat>
+            for DataOrSuffStat<'a, $kind, Geometric>
+        {
+            fn from(stat: &'a GeometricSuffStat) -> Self {
+                DataOrSuffStat::SuffStat(stat)
+            }
+        }
+
+        impl<'a> From<&'a Vec<$kind>> for DataOrSuffStat<'a, $kind, Geometric> {
+            fn from(xs: &'a Vec<$kind>) -> Self {
+                DataOrSuffStat::Data(xs)
+            }
+        }
+
+        impl<'a> From<&'a [$kind]> for DataOrSuffStat<'a, $kind, Geometric> {
+            fn from(xs: &'a [$kind]) -> Self {
+                DataOrSuffStat::Data(xs)
+            }
+        }
+
+        impl SuffStat<$kind> for GeometricSuffStat {
+            fn n(&self) -> usize {
+                self.n
+            }
+
+            fn observe(&mut self, x: &$kind) {
+                self.n += 1;
+                self.sum_k += f64::from(*x);
+            }
+
+            fn forget(&mut self, x: &$kind) {
+                if self.n > 1 {
+                    self.n -= 1;
+                    self.sum_k -= f64::from(*x);
+                } else {
+                    self.n = 0;
+                    self.sum_k = 0.0;
+                }
+            }
+        }
+    };
+}
+
+impl_geometric_suffstat!(u8);
+impl_geometric_suffstat!(u16);
+impl_geometric_suffstat!(u32);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forget_last_resets() {
+        let mut stat = GeometricSuffStat::new();
+        stat.observe(&2_u32);
+        stat.observe(&3_u32);
+        stat.forget(&2_u32);
+        assert_eq!(stat.n(), 1);
+        assert::close(stat.sum_k(), 3.0, 1E-12);
+        stat.forget(&3_u32);
+        assert_eq!(stat, GeometricSuffStat::new());
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::{CategoricalDatum, DataOrSuffStat};
+use crate::dist::MarkovChain;
+use crate::traits::SuffStat;
+
+/// Markov chain sufficient statistic.
+///
+/// Stores the number of paths, the count of each initial state, and the
+/// count of each transition between consecutive states.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct MarkovChainSuffStat {
+    n: usize,
+    initial_counts: Vec<f64>,
+    /// `transition_counts[i][j]` is the number of moves from `i` to `j`
+    transition_counts: Vec<Vec<f64>>,
+}
+
+impl MarkovChainSuffStat {
+    /// Create an empty statistic for a chain with `k` states
+    #[inline]
+    pub fn new(k: usize) -> Self {
+        MarkovChainSuffStat {
+            n: 0,
+            initial_counts: vec![0.0; k],
+            transition_counts: vec![vec![0.0; k]; k],
+        }
+    }
+
+    /// The number of paths observed
+    #[inline]
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// The number of states
+    #[inline]
+    pub fn n_states(&self) -> usize {
+        self.initial_counts.len()
+    }
+
+    /// The number of times each state started a path
+    #[inline]
+    pub fn initial_counts(&self) -> &[f64] {
+        &self.initial_counts
+    }
+
+    /// The number of moves between each pair of states, by row of the
+    /// state moved from
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rv::data::MarkovChainSuffStat;
+    /// # use rv::traits::SuffStat;
+    /// let mut stat = MarkovChainSuffStat::new(2);
+    /// stat.observe(&vec![0_usize, 0, 1, 0]);
+    ///
+    /// assert_eq!(stat.n(), 1);
+    /// assert_eq!(stat.initial_counts(), &[1.0, 0.0]);
+    /// assert_eq!(stat.transition_counts()[0], vec![1.0, 1.0]);
+    /// assert_eq!(stat.transition_counts()[1], vec![1.0, 0.0]);
+    /// ```
+    #[inline]
+    pub fn transition_counts(&self) -> &[Vec<f64>] {
+        &self.transition_counts
+    }
+
+    fn update<X: CategoricalDatum>(&mut self, path: &[X], sign: f64) {
+        if let Some(x0) = path.first() {
+            self.initial_counts[x0.into_usize()] += sign;
+        }
+        path.windows(2).for_each(|w| {
+            self.transition_counts[w[0].into_usize()][w[1].into_usize()] +=
+                sign;
+        });
+    }
+}
+
+impl<'a, X> From<&'a MarkovChainSuffStat>
+    for DataOrSuffStat<'a, Vec<X>, MarkovChain>
+where
+    X: CategoricalDatum,
+{
+    fn from(stat: &'a MarkovChainSuffStat) -> Self {
+        DataOrSuffStat::SuffStat(stat)
+    }
+}
+
+impl<'a, X> From<&'a Vec<Vec<X>>> for DataOrSuffStat<'a, Vec<X>, MarkovChain>
+where
+    X: CategoricalDatum,
+{
+    fn from(xs: &'a Vec<Vec<X>>) -> Self {
+        DataOrSuffStat::Data(xs.as_slice())
+    }
+}
+
+impl<X: CategoricalDatum> SuffStat<Vec<X>> for MarkovChainSuffStat {
+    fn n(&self) -> usize {
+        self.n
+    }
+
+    fn observe(&mut self, x: &Vec<X>) {
+        self.n += 1;
+        self.update(x, 1.0);
+    }
+
+    fn forget(&mut self, x: &Vec<X>) {
+        self.n -= 1;
+        self.update(x, -1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forget_undoes_observe() {
+        let mut stat = MarkovChainSuffStat::new(3);
+        stat.observe(&vec![2_u8, 1, 1]);
+        stat.observe(&vec![0_u8]);
+        assert_eq!(stat.n(), 2);
+        assert_eq!(stat.initial_counts(), &[1.0, 0.0, 1.0]);
+        assert_eq!(stat.transition_counts()[2][1], 1.0);
+        assert_eq!(stat.transition_counts()[1][1], 1.0);
+
+        stat.forget(&vec![2_u8, 1, 1]);
+        stat.forget(&vec![0_u8]);
+        assert_eq!(stat, MarkovChainSuffStat::new(3));
+    }
+}
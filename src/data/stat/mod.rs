@@ -1,19 +1,35 @@
 mod bernoulli;
 mod beta;
+mod binomial;
 mod categorical;
+mod exponential;
+mod gamma;
 mod gaussian;
+mod geometric;
 mod invgamma;
 mod invgaussian;
+mod markov_chain;
 #[cfg(feature = "arraydist")]
 mod mvg;
+mod pareto;
 mod poisson;
+mod uniform;
+mod zero_inflated;
 
 pub use bernoulli::*;
 pub use beta::*;
+pub use binomial::*;
 pub use categorical::*;
+pub use exponential::*;
+pub use gamma::*;
 pub use gaussian::*;
+pub use geometric::*;
 pub use invgamma::*;
 pub use invgaussian::*;
+pub use markov_chain::*;
 #[cfg(feature = "arraydist")]
 pub use mvg::*;
+pub use pareto::*;
 pub use poisson::*;
+pub use uniform::*;
+pub use zero_inflated::*;
@@ -2,15 +2,38 @@
 use serde::{Deserialize, Serialize};
 
 use crate::traits::SuffStat;
-use nalgebra::{DMatrix, DVector};
+use nalgebra::linalg::Cholesky;
+use nalgebra::{DMatrix, DVector, Dyn};
+use std::sync::OnceLock;
 
-#[derive(Debug, Clone, PartialEq)]
+/// Multivariate Gaussian sufficient statistic.
+///
+/// Holds the number of observations, their mean, and the sum of the outer
+/// products of their deviations from the mean (the scatter matrix). These are
+/// updated with Welford's algorithm, which remains accurate for data with
+/// large means or very many observations.
+///
+/// Once requested, the Cholesky factor of the scatter matrix is kept current
+/// through `observe` and `forget` with O(k²) rank-1 updates and downdates.
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
 pub struct MvGaussianSuffStat {
+    /// Number of observations
     n: usize,
-    sum_x: DVector<f64>,
-    sum_x_sq: DMatrix<f64>,
+    /// Mean of `x`
+    mean: DVector<f64>,
+    /// Sum of `(x - mean)(x - mean)^T`
+    sx: DMatrix<f64>,
+    /// Cholesky factor of `sx`, or `None` if it is not positive definite
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    sx_chol: OnceLock<Option<Cholesky<f64, Dyn>>>,
+}
+
+impl PartialEq for MvGaussianSuffStat {
+    fn eq(&self, other: &Self) -> bool {
+        self.n == other.n && self.mean == other.mean && self.sx == other.sx
+    }
 }
 
 impl MvGaussianSuffStat {
@@ -18,20 +41,49 @@ impl MvGaussianSuffStat {
     pub fn new(dims: usize) -> Self {
         MvGaussianSuffStat {
             n: 0,
-            sum_x: DVector::zeros(dims),
-            sum_x_sq: DMatrix::zeros(dims, dims),
+            mean: DVector::zeros(dims),
+            sx: DMatrix::zeros(dims, dims),
+            sx_chol: OnceLock::new(),
         }
     }
 
-    /// Create a sufficient statistic from components without checking whether
+    /// Create a sufficient statistic from the number of observations, their
+    /// sum, and the sum of their outer products without checking whether
     /// they are valid.
+    ///
+    /// The mean and scatter matrix are recovered from the sums, which loses
+    /// precision for data with large means. Prefer
+    /// [`MvGaussianSuffStat::from_mean_scatter_unchecked`].
     #[inline]
     pub fn from_parts_unchecked(
         n: usize,
         sum_x: DVector<f64>,
         sum_x_sq: DMatrix<f64>,
     ) -> Self {
-        MvGaussianSuffStat { n, sum_x, sum_x_sq }
+        if n == 0 {
+            let dims = sum_x.len();
+            return Self::new(dims);
+        }
+        let mean = sum_x / n as f64;
+        let sx = sum_x_sq - &mean * mean.transpose() * n as f64;
+        Self::from_mean_scatter_unchecked(n, mean, sx)
+    }
+
+    /// Create a sufficient statistic from the number of observations, their
+    /// mean, and their scatter matrix (see [`MvGaussianSuffStat::scatter`])
+    /// without checking whether they are valid.
+    #[inline]
+    pub fn from_mean_scatter_unchecked(
+        n: usize,
+        mean: DVector<f64>,
+        sx: DMatrix<f64>,
+    ) -> Self {
+        MvGaussianSuffStat {
+            n,
+            mean,
+            sx,
+            sx_chol: OnceLock::new(),
+        }
     }
 
     /// Get the number of observations
@@ -40,16 +92,124 @@ impl MvGaussianSuffStat {
         self.n
     }
 
+    /// Get the sample mean
+    #[inline]
+    pub fn mean(&self) -> &DVector<f64> {
+        &self.mean
+    }
+
+    /// Get the scatter matrix, the sum of the outer products of the
+    /// deviations from the mean
+    #[inline]
+    pub fn scatter(&self) -> &DMatrix<f64> {
+        &self.sx
+    }
+
+    /// Get the Cholesky factor of the scatter matrix, or `None` if the
+    /// scatter matrix is not positive definite, as when there are no more
+    /// observations than dimensions.
+    ///
+    /// The factor is computed on the first call and then updated, rather
+    /// than recomputed, as observations are added and removed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nalgebra::dvector;
+    /// use rv::data::MvGaussianSuffStat;
+    /// use rv::traits::SuffStat;
+    ///
+    /// let mut stat = MvGaussianSuffStat::new(2);
+    /// stat.observe(&dvector![1.0, 0.0]);
+    /// stat.observe(&dvector![3.0, 2.0]);
+    /// assert!(stat.scatter_cholesky().is_none());
+    ///
+    /// stat.observe(&dvector![5.0, 1.0]);
+    /// stat.observe(&dvector![0.0, 4.0]);
+    /// assert!(stat.scatter_cholesky().is_some());
+    ///
+    /// // Downdated rather than refactorized
+    /// stat.forget(&dvector![0.0, 4.0]);
+    /// let l = stat.scatter_cholesky().unwrap().l();
+    /// assert!((&l * l.transpose()).relative_eq(stat.scatter(), 1E-10, 1E-10));
+    /// ```
+    pub fn scatter_cholesky(&self) -> Option<&Cholesky<f64, Dyn>> {
+        // The scatter of k-dimensional data has rank at most n - 1
+        if self.n <= self.mean.len() {
+            return None;
+        }
+        self.sx_chol
+            .get_or_init(|| self.sx.clone().cholesky())
+            .as_ref()
+    }
+
+    /// Apply `sx += sigma * delta * delta^T` to the cached Cholesky factor,
+    /// after `n` has been updated, dropping it if the result is not positive
+    /// definite
+    fn update_chol(&mut self, delta: &DVector<f64>, sigma: f64) {
+        if self.n <= self.mean.len() {
+            self.sx_chol = OnceLock::new();
+            return;
+        }
+        match self.sx_chol.get_mut() {
+            Some(Some(chol)) => {
+                chol.rank_one_update(delta, sigma);
+                let valid = chol
+                    .l_dirty()
+                    .diagonal()
+                    .iter()
+                    .all(|&d| d.is_finite() && d > 0.0);
+                if !valid {
+                    self.sx_chol = OnceLock::new();
+                }
+            }
+            // The scatter was singular, but may not be now
+            Some(None) => self.sx_chol = OnceLock::new(),
+            None => (),
+        }
+    }
+
+    /// Get the unbiased sample covariance. Every entry is NaN if there are
+    /// fewer than two observations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nalgebra::{dmatrix, dvector};
+    /// use rv::data::MvGaussianSuffStat;
+    /// use rv::traits::SuffStat;
+    ///
+    /// let mut stat = MvGaussianSuffStat::new(2);
+    /// stat.observe(&dvector![1.0, 0.0]);
+    /// stat.observe(&dvector![3.0, 2.0]);
+    /// stat.observe(&dvector![5.0, 1.0]);
+    ///
+    /// assert!(stat.mean().relative_eq(&dvector![3.0, 1.0], 1E-12, 1E-12));
+    /// assert!(stat.sample_cov().relative_eq(
+    ///     &dmatrix![4.0, 1.0; 1.0, 1.0],
+    ///     1E-12,
+    ///     1E-12
+    /// ));
+    /// ```
+    #[inline]
+    pub fn sample_cov(&self) -> DMatrix<f64> {
+        if self.n < 2 {
+            self.sx.map(|_| f64::NAN)
+        } else {
+            &self.sx / (self.n - 1) as f64
+        }
+    }
+
     /// Get the sum of observations
     #[inline]
-    pub fn sum_x(&self) -> &DVector<f64> {
-        &self.sum_x
+    pub fn sum_x(&self) -> DVector<f64> {
+        &self.mean * self.n as f64
     }
 
     /// Get the sum of X^2
     #[inline]
-    pub fn sum_x_sq(&self) -> &DMatrix<f64> {
-        &self.sum_x_sq
+    pub fn sum_x_sq(&self) -> DMatrix<f64> {
+        &self.sx + &self.mean * self.mean.transpose() * self.n as f64
     }
 }
 
@@ -61,23 +221,158 @@ impl SuffStat<DVector<f64>> for MvGaussianSuffStat {
     fn observe(&mut self, x: &DVector<f64>) {
         self.n += 1;
         if self.n == 1 {
-            self.sum_x = x.clone();
-            self.sum_x_sq = x * x.transpose();
+            let dims = x.len();
+            self.mean = x.clone();
+            self.sx = DMatrix::zeros(dims, dims);
+            self.sx_chol = OnceLock::new();
         } else {
-            self.sum_x += x;
-            self.sum_x_sq += x * x.transpose();
+            let n = self.n as f64;
+            let delta = x - &self.mean;
+            self.mean += &delta / n;
+            self.sx += &delta * delta.transpose() * ((n - 1.0) / n);
+            self.update_chol(&delta, (n - 1.0) / n);
         }
     }
 
     fn forget(&mut self, x: &DVector<f64>) {
-        self.n -= 1;
-        if self.n > 0 {
-            self.sum_x -= x;
-            self.sum_x_sq -= x * x.transpose();
+        if self.n > 1 {
+            let n = self.n as f64;
+            let old_mean = (&self.mean * n - x) / (n - 1.0);
+            let delta = x - &old_mean;
+            self.sx -= &delta * delta.transpose() * ((n - 1.0) / n);
+            self.mean = old_mean;
+            self.n -= 1;
+            self.update_chol(&delta, -(n - 1.0) / n);
         } else {
-            let dims = self.sum_x.len();
-            self.sum_x = DVector::zeros(dims);
-            self.sum_x_sq = DMatrix::zeros(dims, dims);
+            let dims = self.mean.len();
+            self.n = 0;
+            self.mean = DVector::zeros(dims);
+            self.sx = DMatrix::zeros(dims, dims);
+            self.sx_chol = OnceLock::new();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::dvector;
+
+    fn data() -> Vec<DVector<f64>> {
+        vec![
+            dvector![0.0, 1.2],
+            dvector![1.2, -0.4],
+            dvector![2.3, 0.8],
+            dvector![4.6, 3.1],
+        ]
+    }
+
+    #[test]
+    fn sums_match_raw_sums() {
+        let xs = data();
+        let mut stat = MvGaussianSuffStat::new(2);
+        stat.observe_many(&xs);
+
+        let sum_x = xs.iter().fold(DVector::zeros(2), |acc, x| acc + x);
+        let sum_x_sq = xs
+            .iter()
+            .fold(DMatrix::zeros(2, 2), |acc, x| acc + x * x.transpose());
+
+        assert_eq!(stat.n(), 4);
+        assert!(stat.sum_x().relative_eq(&sum_x, 1E-12, 1E-12));
+        assert!(stat.sum_x_sq().relative_eq(&sum_x_sq, 1E-12, 1E-12));
+    }
+
+    #[test]
+    fn from_parts() {
+        let xs = data();
+        let mut stat = MvGaussianSuffStat::new(2);
+        stat.observe_many(&xs);
+
+        let from_sums = MvGaussianSuffStat::from_parts_unchecked(
+            4,
+            stat.sum_x(),
+            stat.sum_x_sq(),
+        );
+        assert_eq!(from_sums.n(), 4);
+        assert!(from_sums.mean().relative_eq(stat.mean(), 1E-12, 1E-12));
+        assert!(from_sums
+            .scatter()
+            .relative_eq(stat.scatter(), 1E-12, 1E-12));
+
+        let from_moments = MvGaussianSuffStat::from_mean_scatter_unchecked(
+            4,
+            stat.mean().clone(),
+            stat.scatter().clone(),
+        );
+        assert_eq!(from_moments, stat);
+
+        let empty = MvGaussianSuffStat::from_parts_unchecked(
+            0,
+            DVector::zeros(2),
+            DMatrix::zeros(2, 2),
+        );
+        assert_eq!(empty, MvGaussianSuffStat::new(2));
+    }
+
+    #[test]
+    fn forget_undoes_observe() {
+        let xs = data();
+        let mut stat = MvGaussianSuffStat::new(2);
+        stat.observe_many(&xs);
+        let before = stat.clone();
+
+        stat.observe(&dvector![-3.0, 7.5]);
+        stat.forget(&dvector![-3.0, 7.5]);
+
+        assert_eq!(stat.n(), 4);
+        assert!(stat.mean().relative_eq(before.mean(), 1E-12, 1E-12));
+        assert!(stat.scatter().relative_eq(before.scatter(), 1E-12, 1E-12));
+
+        stat.forget_many(&xs);
+        assert_eq!(stat, MvGaussianSuffStat::new(2));
+    }
+
+    #[test]
+    fn stable_with_large_mean() {
+        let offset = 1E9;
+        let mut stat = MvGaussianSuffStat::new(2);
+        for i in 0..1000 {
+            let k = (i % 10) as f64;
+            stat.observe(&dvector![offset + k, (-2.0_f64).mul_add(k, offset)]);
+        }
+
+        let var = 8.25 * 1000.0 / 999.0;
+        let cov = stat.sample_cov();
+        assert::close(cov[(0, 0)], var, 1E-6);
+        assert::close(cov[(0, 1)], -2.0 * var, 1E-6);
+        assert::close(cov[(1, 1)], 4.0 * var, 1E-6);
+    }
+
+    #[test]
+    fn scatter_cholesky_tracks_observe_and_forget() {
+        let xs = data();
+        let mut stat = MvGaussianSuffStat::new(2);
+        stat.observe_many(&xs);
+        assert!(stat.scatter_cholesky().is_some());
+
+        let extra = vec![dvector![-3.0, 7.5], dvector![0.5, 0.5]];
+        stat.observe_many(&extra);
+        stat.forget(&xs[1]);
+
+        let fresh = stat.scatter().clone().cholesky().unwrap();
+        let updated = stat.scatter_cholesky().unwrap();
+        assert!(updated.l().relative_eq(&fresh.l(), 1E-10, 1E-10));
+
+        // Downdating to a singular scatter drops the factor
+        stat.forget_many(&extra);
+        stat.forget(&xs[0]);
+        assert_eq!(stat.n(), 2);
+        assert!(stat.scatter_cholesky().is_none());
+
+        // ... which is refactorized once it is positive definite again
+        stat.observe(&xs[0]);
+        let l = stat.scatter_cholesky().unwrap().l();
+        assert!((&l * l.transpose()).relative_eq(stat.scatter(), 1E-10, 1E-10));
+    }
+}
@@ -113,6 +113,28 @@ macro_rules! impl_poisson_suffstat {
                     self.sum_ln_fact = 0.0;
                 }
             }
+
+            fn observe_count(&mut self, x: &$kind, count: usize) {
+                let cf = count as f64;
+                self.n += count;
+                self.sum = cf.mul_add(*x as f64, self.sum);
+                self.sum_ln_fact =
+                    cf.mul_add(ln_fact(*x as usize), self.sum_ln_fact);
+            }
+
+            fn forget_count(&mut self, x: &$kind, count: usize) {
+                if self.n > count {
+                    let cf = count as f64;
+                    self.n -= count;
+                    self.sum = cf.mul_add(-(*x as f64), self.sum);
+                    self.sum_ln_fact =
+                        cf.mul_add(-ln_fact(*x as usize), self.sum_ln_fact);
+                } else {
+                    self.n = 0;
+                    self.sum = 0.0;
+                    self.sum_ln_fact = 0.0;
+                }
+            }
         }
     };
 }
@@ -0,0 +1,142 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::DataOrSuffStat;
+use crate::dist::Uniform;
+use crate::traits::SuffStat;
+
+/// Uniform sufficient statistic.
+///
+/// Holds the number of observations and the smallest and largest
+/// observations. The extremes of an empty statistic are `f64::INFINITY` and
+/// `f64::NEG_INFINITY`.
+///
+/// The extremes cannot be recovered once an observation is forgotten, so
+/// `forget` only resets them when the last observation is forgotten.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct UniformSuffStat {
+    /// Number of observations
+    n: usize,
+    /// Smallest observation
+    min: f64,
+    /// Largest observation
+    max: f64,
+}
+
+impl UniformSuffStat {
+    /// Create a new empty SuffStat
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            n: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Create a sufficient statistic from components without checking whether
+    /// they are valid.
+    #[inline]
+    pub fn from_parts_unchecked(n: usize, min: f64, max: f64) -> Self {
+        Self { n, min, max }
+    }
+
+    /// Get the number of observations
+    #[inline]
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Get the smallest observation
+    #[inline]
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Get the largest observation
+    #[inline]
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+}
+
+impl Default for UniformSuffStat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+macro_rules! impl_uniform_suffstat {
+    ($kind:ty) => {
+        impl<'a> From<&'a UniformSuffStat>
+            for DataOrSuffStat<'a, $kind, Uniform>
+        {
+            fn from(stat: &'a UniformSuffStat) -> Self {
+                DataOrSuffStat::SuffStat(stat)
+            }
+        }
+
+        impl<'a> From<&'a Vec<$kind>> for DataOrSuffStat<'a, $kind, Uniform> {
+            fn from(xs: &'a Vec<$kind>) -> Self {
+                DataOrSuffStat::Data(xs)
+            }
+        }
+
+        impl<'a> From<&'a [$kind]> for DataOrSuffStat<'a, $kind, Uniform> {
+            fn from(xs: &'a [$kind]) -> Self {
+                DataOrSuffStat::Data(xs)
+            }
+        }
+
+        impl SuffStat<$kind> for UniformSuffStat {
+            fn n(&self) -> usize {
+                self.n
+            }
+
+            fn observe(&mut self, x: &$kind) {
+                let xf = f64::from(*x);
+                self.n += 1;
+                self.min = self.min.min(xf);
+                self.max = self.max.max(xf);
+            }
+
+            fn forget(&mut self, _x: &$kind) {
+                if self.n > 1 {
+                    self.n -= 1;
+                } else {
+                    *self = Self::new();
+                }
+            }
+        }
+    };
+}
+
+impl_uniform_suffstat!(f32);
+impl_uniform_suffstat!(f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn observe_tracks_extremes() {
+        let mut stat = UniformSuffStat::new();
+        stat.observe_many(&[0.5_f64, 2.0, 0.1, 1.0]);
+        assert_eq!(stat.n(), 4);
+        assert_eq!(stat.min(), 0.1);
+        assert_eq!(stat.max(), 2.0);
+    }
+
+    #[test]
+    fn forget_last_resets() {
+        let mut stat = UniformSuffStat::new();
+        stat.observe(&2.0_f64);
+        stat.observe(&3.0_f64);
+        stat.forget(&3.0_f64);
+        assert_eq!(stat.n(), 1);
+        stat.forget(&2.0_f64);
+        assert_eq!(stat, UniformSuffStat::new());
+    }
+}
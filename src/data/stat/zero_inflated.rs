@@ -0,0 +1,89 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::traits::SuffStat;
+use num::Zero;
+
+/// Zero-inflated sufficient statistic.
+///
+/// Holds the number of zeros and the sufficient statistic of the base
+/// distribution for the non-zero observations.
+///
+/// # Example
+///
+/// ```
+/// use rv::data::{PoissonSuffStat, ZeroInflatedSuffStat};
+/// use rv::traits::SuffStat;
+///
+/// let mut stat = ZeroInflatedSuffStat::new(PoissonSuffStat::new());
+/// stat.observe_many(&[0_u32, 3, 0, 1]);
+///
+/// assert_eq!(stat.n_zeros(), 2);
+/// assert_eq!(stat.nonzero().n(), 2);
+/// assert_eq!(stat.nonzero().sum(), 4.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct ZeroInflatedSuffStat<S> {
+    /// Number of zeros observed
+    n_zeros: usize,
+    /// Sufficient statistic of the non-zero observations
+    nonzero: S,
+}
+
+impl<S> ZeroInflatedSuffStat<S> {
+    /// Create a new statistic from an empty base statistic
+    #[inline]
+    pub fn new(nonzero: S) -> Self {
+        Self {
+            n_zeros: 0,
+            nonzero,
+        }
+    }
+
+    /// Create a sufficient statistic from components without checking whether
+    /// they are valid.
+    #[inline]
+    pub fn from_parts_unchecked(n_zeros: usize, nonzero: S) -> Self {
+        Self { n_zeros, nonzero }
+    }
+
+    /// Get the number of zeros observed
+    #[inline]
+    pub fn n_zeros(&self) -> usize {
+        self.n_zeros
+    }
+
+    /// Get the sufficient statistic of the non-zero observations
+    #[inline]
+    pub fn nonzero(&self) -> &S {
+        &self.nonzero
+    }
+}
+
+impl<X, S> SuffStat<X> for ZeroInflatedSuffStat<S>
+where
+    X: Zero,
+    S: SuffStat<X>,
+{
+    fn n(&self) -> usize {
+        self.n_zeros + self.nonzero.n()
+    }
+
+    fn observe(&mut self, x: &X) {
+        if x.is_zero() {
+            self.n_zeros += 1;
+        } else {
+            self.nonzero.observe(x);
+        }
+    }
+
+    fn forget(&mut self, x: &X) {
+        if x.is_zero() {
+            self.n_zeros = self.n_zeros.saturating_sub(1);
+        } else {
+            self.nonzero.forget(x);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Affine transformations of location-scale distributions
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::dist::{Cauchy, Gaussian, Laplace, ScaledBeta, Uniform};
+use crate::traits::Affine;
+use std::fmt;
+
+/// Errors from [`Affine`] transformations
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum AffineError {
+    /// The shift is infinite or NaN
+    ShiftNotFinite { shift: f64 },
+    /// The scale factor is zero, which leaves a point mass
+    ScaleZero,
+    /// The scale factor is infinite or NaN
+    ScaleNotFinite { scale: f64 },
+    /// The transformed parameters are not valid, e.g., because they overflow
+    InvalidResult,
+}
+
+fn check_shift(shift: f64) -> Result<(), AffineError> {
+    if shift.is_finite() {
+        Ok(())
+    } else {
+        Err(AffineError::ShiftNotFinite { shift })
+    }
+}
+
+fn check_scale(scale: f64) -> Result<(), AffineError> {
+    if scale == 0.0 {
+        Err(AffineError::ScaleZero)
+    } else if !scale.is_finite() {
+        Err(AffineError::ScaleNotFinite { scale })
+    } else {
+        Ok(())
+    }
+}
+
+// Symmetric families, where reflection only negates the location
+macro_rules! impl_symmetric {
+    ($fx: ident, $loc: ident, $scale: ident) => {
+        impl Affine for $fx {
+            fn shift(&self, shift: f64) -> Result<Self, AffineError> {
+                check_shift(shift)?;
+                $fx::new(self.$loc() + shift, self.$scale())
+                    .map_err(|_| AffineError::InvalidResult)
+            }
+
+            fn rescale(&self, scale: f64) -> Result<Self, AffineError> {
+                check_scale(scale)?;
+                $fx::new(self.$loc() * scale, self.$scale() * scale.abs())
+                    .map_err(|_| AffineError::InvalidResult)
+            }
+        }
+    };
+}
+
+impl_symmetric!(Gaussian, mu, sigma);
+impl_symmetric!(Cauchy, loc, scale);
+impl_symmetric!(Laplace, mu, b);
+
+impl Affine for Uniform {
+    fn shift(&self, shift: f64) -> Result<Self, AffineError> {
+        check_shift(shift)?;
+        Uniform::new(self.a() + shift, self.b() + shift)
+            .map_err(|_| AffineError::InvalidResult)
+    }
+
+    fn rescale(&self, scale: f64) -> Result<Self, AffineError> {
+        check_scale(scale)?;
+        let (a, b) = (self.a() * scale, self.b() * scale);
+        Uniform::new(a.min(b), a.max(b)).map_err(|_| AffineError::InvalidResult)
+    }
+}
+
+impl Affine for ScaledBeta {
+    fn shift(&self, shift: f64) -> Result<Self, AffineError> {
+        check_shift(shift)?;
+        ScaledBeta::from_beta(
+            self.beta_dist().clone(),
+            self.a() + shift,
+            self.b() + shift,
+        )
+        .map_err(|_| AffineError::InvalidResult)
+    }
+
+    // Reflection swaps the shape parameters
+    fn rescale(&self, scale: f64) -> Result<Self, AffineError> {
+        check_scale(scale)?;
+        let (a, b) = (self.a() * scale, self.b() * scale);
+        if scale > 0.0 {
+            ScaledBeta::from_beta(self.beta_dist().clone(), a, b)
+        } else {
+            ScaledBeta::new(self.beta(), self.alpha(), b, a)
+        }
+        .map_err(|_| AffineError::InvalidResult)
+    }
+}
+
+impl std::error::Error for AffineError {}
+
+impl fmt::Display for AffineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ShiftNotFinite { shift } => {
+                write!(f, "non-finite shift: {}", shift)
+            }
+            Self::ScaleZero => write!(f, "scale must not be zero"),
+            Self::ScaleNotFinite { scale } => {
+                write!(f, "non-finite scale: {}", scale)
+            }
+            Self::InvalidResult => {
+                write!(f, "the transformed parameters are invalid")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{Cdf, ContinuousDistr};
+
+    const TOL: f64 = 1E-12;
+
+    // The density of cX + d at y is f((y - d) / c) / |c|
+    fn check_density<Fx>(fx: &Fx, scale: f64, shift: f64)
+    where
+        Fx: Affine + ContinuousDistr<f64>,
+    {
+        let gx = fx.affine(scale, shift).unwrap();
+        for y in [-3.0_f64, -0.5, 0.2, 1.0, 4.5] {
+            let x = (y - shift) / scale;
+            assert::close(gx.pdf(&y), fx.pdf(&x) / scale.abs(), TOL);
+        }
+    }
+
+    #[test]
+    fn transformed_densities() {
+        for (scale, shift) in [(2.0, 1.0), (-0.5, 0.3), (1.0, -2.0)] {
+            check_density(&Gaussian::new(0.5, 1.5).unwrap(), scale, shift);
+            check_density(&Cauchy::new(-0.2, 0.7).unwrap(), scale, shift);
+            check_density(&Laplace::new(1.0, 0.4).unwrap(), scale, shift);
+            check_density(&Uniform::new(-1.0, 0.8).unwrap(), scale, shift);
+            let sbeta = ScaledBeta::new(2.0, 3.0, -1.0, 0.8).unwrap();
+            check_density(&sbeta, scale, shift);
+        }
+    }
+
+    #[test]
+    fn reflected_uniform_cdf() {
+        let unif = Uniform::new(1.0, 3.0).unwrap().rescale(-1.0).unwrap();
+        assert::close(unif.cdf(&-2.5), 0.25, TOL);
+    }
+
+    #[test]
+    fn invalid_transforms() {
+        let gauss = Gaussian::standard();
+        assert_eq!(gauss.rescale(0.0), Err(AffineError::ScaleZero));
+        assert!(matches!(
+            gauss.rescale(f64::NAN),
+            Err(AffineError::ScaleNotFinite { .. })
+        ));
+        assert_eq!(
+            gauss.shift(f64::INFINITY),
+            Err(AffineError::ShiftNotFinite {
+                shift: f64::INFINITY
+            })
+        );
+        let wide = Gaussian::new(0.0, 1E300).unwrap();
+        assert_eq!(wide.rescale(1E10), Err(AffineError::InvalidResult));
+    }
+}
@@ -3,6 +3,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data::{BernoulliSuffStat, Booleable};
+use crate::dist::categorical::js_term;
 use crate::impl_display;
 use crate::traits::*;
 use rand::Rng;
@@ -299,6 +300,19 @@ impl KlDivergence for Bernoulli {
     }
 }
 
+impl JsDivergence for Bernoulli {
+    fn js(&self, other: &Self) -> f64 {
+        js_term(self.p, other.p) + js_term(self.q(), other.q())
+    }
+}
+
+impl HellingerDistance for Bernoulli {
+    fn hellinger(&self, other: &Self) -> f64 {
+        let bc = (self.p * other.p).sqrt() + (self.q() * other.q()).sqrt();
+        (1.0 - bc).max(0.0).sqrt()
+    }
+}
+
 impl Entropy for Bernoulli {
     fn entropy(&self) -> f64 {
         let q = self.q();
@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data::BetaSuffStat;
+use crate::dist::quantile_match::{self, QuantileMatchError};
+use crate::dist::Gaussian;
 use crate::impl_display;
 use crate::traits::*;
 use rand::Rng;
@@ -13,6 +15,8 @@ use std::fmt;
 use std::sync::OnceLock;
 
 pub mod bernoulli_prior;
+mod binomial_prior;
+mod geometric_prior;
 
 /// [Beta distribution](https://en.wikipedia.org/wiki/Beta_distribution),
 /// Beta(α, β) over x in (0, 1).
@@ -41,6 +45,9 @@ pub mod bernoulli_prior;
 /// let p_pred_heads = beta.pp(&true, &DataOrSuffStat::Data(&flips)); // 9/15
 /// assert!((p_pred_heads - 3.0/5.0).abs() < 1E-12);
 /// ```
+///
+/// Quantiles from [`InverseCdf`] invert the incomplete beta function and are
+/// refined with Newton steps to a relative error of about 1E-12.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
@@ -156,6 +163,53 @@ impl Beta {
         }
     }
 
+    /// Create the Beta whose quantiles best match `quantiles`, given as
+    /// `(p, x)` pairs where `x` is the `p`-quantile.
+    ///
+    /// The parameters are found numerically by minimizing the squared error
+    /// between the requested and fitted quantiles on the standard normal
+    /// scale.
+    ///
+    /// # Example
+    ///
+    /// A prior on a success rate that is probably between 20% and 40%
+    ///
+    /// ```rust
+    /// # use rv::dist::Beta;
+    /// use rv::traits::Cdf;
+    ///
+    /// let beta = Beta::from_quantiles(&[(0.05, 0.2), (0.95, 0.4)]).unwrap();
+    ///
+    /// assert!((beta.cdf(&0.2_f64) - 0.05).abs() < 1E-6);
+    /// assert!((beta.cdf(&0.4_f64) - 0.95).abs() < 1E-6);
+    /// ```
+    pub fn from_quantiles(
+        quantiles: &[(f64, f64)],
+    ) -> Result<Self, QuantileMatchError> {
+        let quantiles =
+            quantile_match::validate(quantiles, 2, |x| 0.0 < x && x < 1.0)?;
+
+        // Start from the method of moments using the matching Gaussian
+        let init = {
+            let (mean, var) = Gaussian::from_quantiles(&quantiles)
+                .map(|g| (g.mu().clamp(0.01, 0.99), g.sigma().powi(2)))
+                .unwrap_or((0.5, 1.0 / 12.0));
+            let concentration =
+                (mean * (1.0 - mean) / var - 1.0).clamp(0.5, 1E4);
+            [
+                (mean * concentration).ln(),
+                ((1.0 - mean) * concentration).ln(),
+            ]
+        };
+
+        let params = quantile_match::fit(&quantiles, &init, |params, x| {
+            let (alpha, beta) = (params[0].exp(), params[1].exp());
+            x.inc_beta(alpha, beta, alpha.ln_beta(beta))
+        });
+        Self::new(params[0].exp(), params[1].exp())
+            .map_err(|_| QuantileMatchError::NoSolution)
+    }
+
     /// Get the alpha parameter
     ///
     /// # Example
@@ -289,6 +343,57 @@ impl From<&Beta> for String {
 
 impl_display!(Beta);
 
+/// Maximum number of Newton steps used to polish the inverse CDF
+const INVCDF_MAX_ITER: usize = 16;
+
+/// Quantile of Beta(α, β) at `p`.
+///
+/// Starts from the inverse of the regularized incomplete beta function
+/// (AS 109), which is accurate to roughly 1E-8, then polishes with Newton
+/// steps on ln CDF against ln x, reaching a relative error near 1E-13. Upper
+/// tail quantiles are found as lower tail quantiles of Beta(β, α).
+fn invcdf(p: f64, alpha: f64, beta: f64, ln_beta: f64) -> f64 {
+    assert!((0.0..=1.0).contains(&p), "P out of range");
+    if p == 0.0 || p == 1.0 {
+        p
+    } else if p > 0.5 {
+        1.0 - invcdf_lower(1.0 - p, beta, alpha, ln_beta)
+    } else {
+        invcdf_lower(p, alpha, beta, ln_beta)
+    }
+}
+
+// Newton steps on ln x converge quickly in the power law lower tail, where
+// the CDF is approximately x^α / (α B(α, β)).
+fn invcdf_lower(p: f64, alpha: f64, beta: f64, ln_beta: f64) -> f64 {
+    let ln_p = p.ln();
+    let mut x = p.inv_inc_beta(alpha, beta, ln_beta);
+    if !(x > 0.0 && x < 1.0) {
+        x = ((ln_p + alpha.ln() + ln_beta) / alpha).exp().min(0.5);
+    }
+
+    for _ in 0..INVCDF_MAX_ITER {
+        let cdf = x.inc_beta(alpha, beta, ln_beta);
+        let ln_f = (alpha - 1.0).mul_add(x.ln(), (beta - 1.0) * (-x).ln_1p())
+            - ln_beta;
+        // d ln F / d ln x = x f(x) / F(x)
+        let slope = (x.ln() + ln_f).exp() / cdf;
+        let step = (cdf.ln() - ln_p) / slope;
+        if !step.is_finite() {
+            break;
+        }
+        // Halve the distance to one rather than leave the support
+        let x_new = (x.ln() - step).exp();
+        let x_new = if x_new < 1.0 { x_new } else { (1.0 + x) / 2.0 };
+        let converged = (x_new - x).abs() <= 1E-15 * x;
+        x = x_new;
+        if converged {
+            break;
+        }
+    }
+    x
+}
+
 macro_rules! impl_traits {
     ($kind:ty) => {
         impl Rv<$kind> for Beta {
@@ -299,6 +404,21 @@ macro_rules! impl_traits {
                 ) - self.ln_beta_ab()
             }
 
+            fn ln_f_many(&self, xs: &[$kind], out: &mut [f64]) {
+                assert_eq!(
+                    xs.len(),
+                    out.len(),
+                    "xs and out must be the same length"
+                );
+                let ln_beta = self.ln_beta_ab();
+                let (alpha_m1, beta_m1) = (self.alpha - 1.0, self.beta - 1.0);
+                xs.iter().zip(out.iter_mut()).for_each(|(&x, y)| {
+                    let xf = f64::from(x);
+                    *y = alpha_m1.mul_add(xf.ln(), beta_m1 * (1.0 - xf).ln())
+                        - ln_beta;
+                });
+            }
+
             fn draw<R: Rng>(&self, rng: &mut R) -> $kind {
                 let b = rand_distr::Beta::new(self.alpha, self.beta).unwrap();
                 rng.sample(b) as $kind
@@ -326,6 +446,12 @@ macro_rules! impl_traits {
             }
         }
 
+        impl InverseCdf<$kind> for Beta {
+            fn invcdf(&self, p: f64) -> $kind {
+                invcdf(p, self.alpha, self.beta, self.ln_beta_ab()) as $kind
+            }
+        }
+
         impl Mean<$kind> for Beta {
             fn mean(&self) -> Option<$kind> {
                 Some((self.alpha / (self.alpha + self.beta)) as $kind)
@@ -418,6 +544,22 @@ impl Kurtosis for Beta {
 impl_traits!(f32);
 impl_traits!(f64);
 
+impl QuadBounds for Beta {
+    fn quad_bounds(&self) -> (f64, f64) {
+        (0.0, 1.0)
+    }
+}
+
+impl CacheControl for Beta {
+    fn precompute(&self) {
+        self.ln_beta_ab();
+    }
+
+    fn invalidate(&mut self) {
+        self.ln_beta_ab = OnceLock::new();
+    }
+}
+
 impl std::error::Error for BetaError {}
 
 impl fmt::Display for BetaError {
@@ -573,6 +715,53 @@ mod tests {
         assert::close(cdfs, true_cdfs, TOL);
     }
 
+    #[test]
+    fn invcdf_reference_values() {
+        // Computed with mpmath at 50 digits
+        let refs = [
+            (0.5, 0.5, 1E-6, 2.467_401_100_270_31E-12),
+            (0.5, 0.5, 0.01, 2.467_198_171_342_215E-4),
+            (0.5, 0.5, 0.99, 0.999_753_280_182_865_8),
+            (2.0, 5.0, 1E-6, 2.582_878_293_540_636E-4),
+            (2.0, 5.0, 0.5, 0.264_449_983_295_659_96),
+            (2.0, 5.0, 0.99, 0.705_686_328_319_707_5),
+            (0.1, 3.0, 1E-6, 2.366_901_351_944_922E-61),
+            (0.1, 3.0, 0.5, 2.312_399_075_001_179_5E-4),
+            (0.1, 3.0, 0.99, 0.458_666_134_880_689_5),
+            (50.0, 20.0, 1E-6, 0.434_423_136_542_258_05),
+            (50.0, 20.0, 0.01, 0.581_776_693_096_904_8),
+            (50.0, 20.0, 0.99, 0.828_938_531_139_546_1),
+        ];
+        for (alpha, beta, p, x_ref) in refs {
+            let x: f64 = Beta::new(alpha, beta).unwrap().invcdf(p);
+            assert::close(x / x_ref, 1.0, 1E-12);
+        }
+    }
+
+    #[test]
+    fn invcdf_inverts_cdf() {
+        let beta = Beta::new(0.7, 3.2).unwrap();
+        for p in [0.001, 0.1, 0.3, 0.5, 0.7, 0.9, 0.999] {
+            let x: f64 = beta.invcdf(p);
+            assert::close(beta.cdf(&x), p, TOL);
+        }
+        let x: f64 = Beta::uniform().invcdf(0.3);
+        assert::close(x, 0.3, TOL);
+        assert_eq!(InverseCdf::<f64>::invcdf(&beta, 0.0), 0.0);
+        assert_eq!(InverseCdf::<f64>::invcdf(&beta, 1.0), 1.0);
+    }
+
+    #[test]
+    fn hdi_is_shorter_than_equal_tailed_interval() {
+        let beta = Beta::new(2.0, 8.0).unwrap();
+        let (a, b) = beta.hdi(0.9);
+        let (c, d): (f64, f64) = beta.interval(0.9);
+        assert::close(beta.cdf(&b) - beta.cdf(&a), 0.9, 1E-10);
+        assert::close(beta.pdf(&a), beta.pdf(&b), 1E-5);
+        assert!(b - a < d - c);
+        assert!(a < c && b < d);
+    }
+
     #[test]
     fn draw_should_resturn_values_within_0_to_1() {
         let mut rng = rand::thread_rng();
@@ -745,4 +934,24 @@ mod tests {
 
         assert::close(ln_f_base, ln_f_stat, 1e-12);
     }
+
+    #[test]
+    fn from_quantiles_recovers_params() {
+        let beta = Beta::new(2.0, 7.0).unwrap();
+        let qs: Vec<(f64, f64)> = [0.05, 0.2, 0.5]
+            .iter()
+            .map(|&x| (beta.cdf(&x), x))
+            .collect();
+        let fit = Beta::from_quantiles(&qs).unwrap();
+        assert::close(fit.alpha(), 2.0, 1E-4);
+        assert::close(fit.beta(), 7.0, 1E-4);
+    }
+
+    #[test]
+    fn from_quantiles_rejects_values_outside_unit_interval() {
+        assert_eq!(
+            Beta::from_quantiles(&[(0.1, 0.5), (0.9, 1.0)]),
+            Err(QuantileMatchError::ValueOutOfSupport { x: 1.0 })
+        );
+    }
 }
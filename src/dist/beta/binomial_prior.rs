@@ -0,0 +1,158 @@
+use rand::Rng;
+use special::Beta as SBeta;
+
+use crate::data::{extract_stat, BinomialSuffStat, DataOrSuffStat};
+use crate::dist::{Beta, Binomial};
+use crate::misc::ln_binom;
+use crate::traits::*;
+
+/// The prior is on `p` only. Drawn Binomials have a single trial; the number
+/// of trials of `(k, n)` data comes from the data.
+impl Rv<Binomial> for Beta {
+    fn ln_f(&self, x: &Binomial) -> f64 {
+        self.ln_f(&x.p())
+    }
+
+    fn draw<R: Rng>(&self, mut rng: &mut R) -> Binomial {
+        let p: f64 = self.draw(&mut rng);
+        Binomial::new(1, p).expect("Failed to draw valid p")
+    }
+}
+
+impl Support<Binomial> for Beta {
+    fn supports(&self, x: &Binomial) -> bool {
+        0.0 < x.p() && x.p() < 1.0
+    }
+}
+
+impl ContinuousDistr<Binomial> for Beta {}
+
+macro_rules! impl_traits {
+    ($kind: ty) => {
+        /// Conjugate prior on the success probability of `(k, n)` data
+        impl ConjugatePrior<($kind, $kind), Binomial> for Beta {
+            type Posterior = Self;
+            type LnMCache = f64;
+            type LnPpCache = (f64, f64, f64);
+
+            fn posterior(
+                &self,
+                x: &DataOrSuffStat<($kind, $kind), Binomial>,
+            ) -> Self {
+                let stat = extract_stat(x, BinomialSuffStat::new);
+                let k = stat.k() as f64;
+                let n = stat.trials() as f64;
+
+                let a = self.alpha() + k;
+                let b = self.beta() + (n - k);
+
+                Beta::new(a, b).expect("Invalid posterior parameters")
+            }
+
+            #[inline]
+            fn ln_m_cache(&self) -> Self::LnMCache {
+                self.alpha().ln_beta(self.beta())
+            }
+
+            fn ln_m_with_cache(
+                &self,
+                cache: &Self::LnMCache,
+                x: &DataOrSuffStat<($kind, $kind), Binomial>,
+            ) -> f64 {
+                let stat = extract_stat(x, BinomialSuffStat::new);
+                let stat_data = DataOrSuffStat::SuffStat(&stat);
+                let post = <Beta as ConjugatePrior<($kind, $kind), Binomial>>::posterior(self, &stat_data);
+                stat.sum_ln_binom() + post.alpha().ln_beta(post.beta()) - cache
+            }
+
+            #[inline]
+            fn ln_pp_cache(
+                &self,
+                x: &DataOrSuffStat<($kind, $kind), Binomial>,
+            ) -> Self::LnPpCache {
+                let post = self.posterior(x);
+                let (a, b) = (post.alpha(), post.beta());
+                (a, b, a.ln_beta(b))
+            }
+
+            fn ln_pp_with_cache(
+                &self,
+                cache: &Self::LnPpCache,
+                y: &($kind, $kind),
+            ) -> f64 {
+                // Beta-binomial with the trials of y
+                let (a, b, ln_beta_ab) = *cache;
+                let (k, n) = *y;
+                if k > n {
+                    return f64::NEG_INFINITY;
+                }
+                let kf = k as f64;
+                let nf = n as f64;
+                ln_binom(nf, kf) + (kf + a).ln_beta(nf - kf + b) - ln_beta_ab
+            }
+        }
+    };
+}
+
+impl_traits!(u8);
+impl_traits!(u16);
+impl_traits!(u32);
+impl_traits!(u64);
+impl_traits!(usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Bernoulli, BetaBinomial};
+
+    const TOL: f64 = 1E-12;
+
+    #[test]
+    fn posterior_from_data() {
+        let data: Vec<(u32, u32)> = vec![(2, 5), (0, 1), (3, 3)];
+        let xs = DataOrSuffStat::Data::<(u32, u32), Binomial>(&data);
+        let posterior = Beta::new(1.0, 2.0).unwrap().posterior(&xs);
+
+        assert::close(posterior.alpha(), 6.0, TOL);
+        assert::close(posterior.beta(), 6.0, TOL);
+    }
+
+    #[test]
+    fn single_trials_match_bernoulli() {
+        let prior = Beta::new(1.5, 0.7).unwrap();
+        let bools = vec![true, false, true, true];
+        let pairs: Vec<(u8, u8)> =
+            bools.iter().map(|&x| (u8::from(x), 1)).collect();
+
+        let ln_m_bern = <Beta as ConjugatePrior<bool, Bernoulli>>::ln_m(
+            &prior,
+            &DataOrSuffStat::Data(&bools),
+        );
+        let ln_m_binom = prior.ln_m(&DataOrSuffStat::Data(&pairs));
+        assert::close(ln_m_binom, ln_m_bern, TOL);
+    }
+
+    #[test]
+    fn ln_pp_is_beta_binomial() {
+        let prior = Beta::new(1.5, 0.7).unwrap();
+        let data: Vec<(u32, u32)> = vec![(2, 5), (0, 1), (3, 3)];
+        let xs = DataOrSuffStat::Data(&data);
+        let post = prior.posterior(&xs);
+        let bb = BetaBinomial::new(8, post.alpha(), post.beta()).unwrap();
+        for k in 0..=8_u32 {
+            assert::close(prior.ln_pp(&(k, 8), &xs), bb.ln_f(&k), TOL);
+        }
+        assert_eq!(prior.ln_pp(&(9_u32, 8_u32), &xs), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn ln_m_is_sum_of_sequential_ln_pp() {
+        let prior = Beta::new(2.0, 3.0).unwrap();
+        let data: Vec<(u16, u16)> = vec![(2, 5), (0, 1), (3, 3), (4, 10)];
+        let ln_m = prior.ln_m(&DataOrSuffStat::Data(&data));
+        let ln_pp_sum: f64 = (0..data.len())
+            .map(|i| prior.ln_pp(&data[i], &DataOrSuffStat::Data(&data[..i])))
+            .sum();
+        assert::close(ln_m, ln_pp_sum, TOL);
+    }
+}
@@ -0,0 +1,147 @@
+use rand::Rng;
+use special::Beta as SBeta;
+
+use crate::data::{extract_stat, DataOrSuffStat, GeometricSuffStat};
+use crate::dist::{Beta, Geometric};
+use crate::traits::*;
+
+impl Rv<Geometric> for Beta {
+    fn ln_f(&self, x: &Geometric) -> f64 {
+        self.ln_f(&x.p())
+    }
+
+    fn draw<R: Rng>(&self, mut rng: &mut R) -> Geometric {
+        let p: f64 = self.draw(&mut rng);
+        Geometric::new(p).expect("Failed to draw valid p")
+    }
+}
+
+impl Support<Geometric> for Beta {
+    fn supports(&self, x: &Geometric) -> bool {
+        0.0 < x.p() && x.p() <= 1.0
+    }
+}
+
+impl ContinuousDistr<Geometric> for Beta {}
+
+macro_rules! impl_traits {
+    ($kind: ty) => {
+        impl ConjugatePrior<$kind, Geometric> for Beta {
+            type Posterior = Self;
+            type LnMCache = f64;
+            type LnPpCache = (f64, f64, f64);
+
+            fn posterior(&self, x: &DataOrSuffStat<$kind, Geometric>) -> Self {
+                let stat = extract_stat(x, GeometricSuffStat::new);
+                let a = self.alpha() + stat.n() as f64;
+                let b = self.beta() + stat.sum_k();
+                Beta::new(a, b).expect("Invalid posterior parameters")
+            }
+
+            #[inline]
+            fn ln_m_cache(&self) -> Self::LnMCache {
+                self.alpha().ln_beta(self.beta())
+            }
+
+            fn ln_m_with_cache(
+                &self,
+                cache: &Self::LnMCache,
+                x: &DataOrSuffStat<$kind, Geometric>,
+            ) -> f64 {
+                let post = self.posterior(x);
+                post.alpha().ln_beta(post.beta()) - cache
+            }
+
+            #[inline]
+            fn ln_pp_cache(
+                &self,
+                x: &DataOrSuffStat<$kind, Geometric>,
+            ) -> Self::LnPpCache {
+                let post = self.posterior(x);
+                let (a, b) = (post.alpha(), post.beta());
+                (a, b, a.ln_beta(b))
+            }
+
+            fn ln_pp_with_cache(
+                &self,
+                cache: &Self::LnPpCache,
+                y: &$kind,
+            ) -> f64 {
+                // f(k) = B(a + 1, b + k) / B(a, b)
+                let (a, b, ln_beta_ab) = *cache;
+                (a + 1.0).ln_beta(b + f64::from(*y)) - ln_beta_ab
+            }
+        }
+    };
+}
+
+impl_traits!(u8);
+impl_traits!(u16);
+impl_traits!(u32);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOL: f64 = 1E-12;
+
+    #[test]
+    fn posterior_from_data() {
+        let data: Vec<u32> = vec![0, 3, 1, 4];
+        let xs = DataOrSuffStat::Data::<u32, Geometric>(&data);
+        let posterior = Beta::new(1.0, 2.0).unwrap().posterior(&xs);
+
+        assert::close(posterior.alpha(), 5.0, TOL);
+        assert::close(posterior.beta(), 10.0, TOL);
+    }
+
+    #[test]
+    fn ln_m_no_data() {
+        let prior = Beta::new(1.5, 0.7).unwrap();
+        let data: DataOrSuffStat<u32, Geometric> = DataOrSuffStat::None;
+        assert::close(prior.ln_m(&data), 0.0, TOL);
+    }
+
+    #[test]
+    fn ln_pp_sums_to_one() {
+        let prior = Beta::new(3.0, 2.0).unwrap();
+        let data: Vec<u16> = vec![2, 0, 5];
+        let xs = DataOrSuffStat::<u16, Geometric>::Data(&data);
+        let total: f64 =
+            (0..2000_u16).map(|k| prior.ln_pp(&k, &xs).exp()).sum();
+        assert::close(total, 1.0, 1E-6);
+    }
+
+    #[test]
+    fn ln_pp_matches_quadrature() {
+        // E[p (1 - p)^k] under the prior
+        let prior = Beta::new(2.0, 3.0).unwrap();
+        let n = 100_000;
+        let k = 2;
+        let mean: f64 = (0..n)
+            .map(|i| {
+                let p = (i as f64 + 0.5) / n as f64;
+                prior.f(&p) * p * (1.0 - p).powi(k)
+            })
+            .sum::<f64>()
+            / n as f64;
+        let ln_pp =
+            prior.ln_pp(&(k as u32), &DataOrSuffStat::<u32, Geometric>::None);
+        assert::close(ln_pp.exp(), mean, 1E-8);
+    }
+
+    #[test]
+    fn ln_m_is_sum_of_sequential_ln_pp() {
+        let prior = Beta::new(2.0, 3.0).unwrap();
+        let data: Vec<u8> = vec![1, 0, 6, 2, 2];
+        let mut stat = GeometricSuffStat::new();
+        let mut total = 0.0;
+        for x in data.iter() {
+            total += prior
+                .ln_pp(x, &DataOrSuffStat::<u8, Geometric>::SuffStat(&stat));
+            stat.observe(x);
+        }
+        let ln_m = prior.ln_m(&DataOrSuffStat::<u8, Geometric>::Data(&data));
+        assert::close(ln_m, total, TOL);
+    }
+}
@@ -396,6 +396,16 @@ impl_int_traits!(i16);
 impl_int_traits!(i32);
 impl_int_traits!(i64);
 
+impl CacheControl for BetaBinomial {
+    fn precompute(&self) {
+        self.ln_beta_ab();
+    }
+
+    fn invalidate(&mut self) {
+        self.ln_beta_ab = OnceLock::new();
+    }
+}
+
 impl std::error::Error for BetaBinomialError {}
 
 impl fmt::Display for BetaBinomialError {
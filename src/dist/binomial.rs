@@ -2,6 +2,7 @@
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
 
+use crate::data::BinomialSuffStat;
 use crate::impl_display;
 use crate::misc::ln_binom;
 use crate::traits::*;
@@ -41,6 +42,17 @@ use std::fmt;
 /// let f = binom.pmf(&5_u8);
 /// assert_eq!(f, 0.0);
 /// ```
+///
+/// Data can also be `(k, n)` pairs of successes and trials, in which case
+/// the number of trials comes from the datum rather than the distribution.
+/// This allows observations with different numbers of trials to share `p`.
+///
+/// ```
+/// # use rv::prelude::*;
+/// let binom = Binomial::new(4, 0.3).unwrap();
+/// let other = Binomial::new(7, 0.3).unwrap();
+/// assert_eq!(binom.pmf(&(2_u32, 7_u32)), other.pmf(&2_u32));
+/// ```
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
@@ -277,6 +289,53 @@ macro_rules! impl_int_traits {
     };
 }
 
+macro_rules! impl_pair_traits {
+    ($kind:ty) => {
+        impl Rv<($kind, $kind)> for Binomial {
+            fn ln_f(&self, x: &($kind, $kind)) -> f64 {
+                let (k, n) = *x;
+                if k > n {
+                    return f64::NEG_INFINITY;
+                }
+                let nf = n as f64;
+                let kf = k as f64;
+                self.q()
+                    .ln()
+                    .mul_add(nf - kf, self.p.ln().mul_add(kf, ln_binom(nf, kf)))
+            }
+
+            fn draw<R: Rng>(&self, rng: &mut R) -> ($kind, $kind) {
+                let b = rand_distr::Binomial::new(self.n, self.p).unwrap();
+                (rng.sample(b) as $kind, self.n as $kind)
+            }
+        }
+
+        impl Support<($kind, $kind)> for Binomial {
+            fn supports(&self, x: &($kind, $kind)) -> bool {
+                x.0 <= x.1
+            }
+        }
+
+        impl DiscreteDistr<($kind, $kind)> for Binomial {}
+
+        impl HasSuffStat<($kind, $kind)> for Binomial {
+            type Stat = BinomialSuffStat;
+
+            fn empty_suffstat(&self) -> Self::Stat {
+                BinomialSuffStat::new()
+            }
+
+            fn ln_f_stat(&self, stat: &Self::Stat) -> f64 {
+                let k = stat.k() as f64;
+                let n = stat.trials() as f64;
+                self.q()
+                    .ln()
+                    .mul_add(n - k, self.p.ln().mul_add(k, stat.sum_ln_binom()))
+            }
+        }
+    };
+}
+
 impl Skewness for Binomial {
     fn skewness(&self) -> Option<f64> {
         let nf = self.n as f64;
@@ -315,6 +374,12 @@ impl_int_traits!(i16);
 impl_int_traits!(i32);
 impl_int_traits!(i64);
 
+impl_pair_traits!(u8);
+impl_pair_traits!(u16);
+impl_pair_traits!(u32);
+impl_pair_traits!(u64);
+impl_pair_traits!(usize);
+
 impl std::error::Error for BinomialError {}
 
 impl fmt::Display for BinomialError {
@@ -507,4 +572,23 @@ mod tests {
         });
         assert!(passes > 0);
     }
+
+    #[test]
+    fn pair_data_use_their_own_trials() {
+        let binom = Binomial::new(5, 0.6).unwrap();
+        let data: Vec<(u32, u32)> = vec![(2, 3), (0, 1), (7, 12), (3, 3)];
+        for &(k, n) in &data {
+            let other = Binomial::new(u64::from(n), 0.6).unwrap();
+            assert::close(binom.ln_f(&(k, n)), other.ln_f(&k), TOL);
+        }
+        assert!(!binom.supports(&(4_u32, 3_u32)));
+        assert_eq!(binom.ln_f(&(4_u32, 3_u32)), f64::NEG_INFINITY);
+
+        let mut stat = BinomialSuffStat::new();
+        stat.observe_many(&data);
+        let ln_f_sum: f64 = data.iter().map(|x| binom.ln_f(x)).sum();
+        let ln_f_stat =
+            <Binomial as HasSuffStat<(u32, u32)>>::ln_f_stat(&binom, &stat);
+        assert::close(ln_f_stat, ln_f_sum, TOL);
+    }
 }
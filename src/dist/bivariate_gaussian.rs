@@ -0,0 +1,318 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::consts::{LN_2PI, LN_2PI_E};
+use crate::dist::{ConditionError, Gaussian};
+use crate::impl_display;
+use crate::traits::*;
+use rand::Rng;
+use std::fmt;
+
+/// Bivariate Gaussian distribution over `(x, y)` with means, standard
+/// deviations, and correlation, ρ.
+///
+/// A lightweight alternative to the two-dimensional
+/// [`MvGaussian`](crate::dist::MvGaussian) that works with tuples and
+/// conditions to a univariate [`Gaussian`].
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{BivariateGaussian, Gaussian};
+/// use rv::traits::*;
+///
+/// let bvg = BivariateGaussian::new((1.0, -1.0), (2.0, 0.5), -0.8).unwrap();
+///
+/// // The marginals are Gaussian
+/// let x_marginal: Gaussian = bvg.marginal_x();
+/// assert_eq!(x_marginal, Gaussian::new(1.0, 2.0).unwrap());
+///
+/// // Independent when ρ = 0
+/// let indep = BivariateGaussian::new((1.0, -1.0), (2.0, 0.5), 0.0).unwrap();
+/// let y_marginal = Gaussian::new(-1.0, 0.5).unwrap();
+/// let xy = (0.3, 0.2);
+/// assert::close(
+///     indep.ln_f(&xy),
+///     x_marginal.ln_f(&xy.0) + y_marginal.ln_f(&xy.1),
+///     1E-12,
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct BivariateGaussian {
+    mu: (f64, f64),
+    sigma: (f64, f64),
+    rho: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum BivariateGaussianError {
+    /// A mean is infinite or NaN
+    MuNotFinite { mu: f64 },
+    /// A standard deviation is less than or equal to zero
+    SigmaTooLow { sigma: f64 },
+    /// A standard deviation is infinite or NaN
+    SigmaNotFinite { sigma: f64 },
+    /// The correlation is not in (-1, 1)
+    RhoOutOfRange { rho: f64 },
+}
+
+impl BivariateGaussian {
+    /// Create a new bivariate Gaussian with means `mu`, standard deviations
+    /// `sigma`, and correlation `rho`
+    pub fn new(
+        mu: (f64, f64),
+        sigma: (f64, f64),
+        rho: f64,
+    ) -> Result<Self, BivariateGaussianError> {
+        for m in [mu.0, mu.1] {
+            if !m.is_finite() {
+                return Err(BivariateGaussianError::MuNotFinite { mu: m });
+            }
+        }
+        for s in [sigma.0, sigma.1] {
+            if s <= 0.0 {
+                return Err(BivariateGaussianError::SigmaTooLow { sigma: s });
+            } else if !s.is_finite() {
+                return Err(BivariateGaussianError::SigmaNotFinite {
+                    sigma: s,
+                });
+            }
+        }
+        if !(rho > -1.0 && rho < 1.0) {
+            return Err(BivariateGaussianError::RhoOutOfRange { rho });
+        }
+        Ok(BivariateGaussian { mu, sigma, rho })
+    }
+
+    /// Creates a new BivariateGaussian without checking whether the
+    /// parameters are valid.
+    #[inline]
+    pub fn new_unchecked(mu: (f64, f64), sigma: (f64, f64), rho: f64) -> Self {
+        BivariateGaussian { mu, sigma, rho }
+    }
+
+    /// Get the means
+    #[inline]
+    pub fn mu(&self) -> (f64, f64) {
+        self.mu
+    }
+
+    /// Get the standard deviations
+    #[inline]
+    pub fn sigma(&self) -> (f64, f64) {
+        self.sigma
+    }
+
+    /// Get the correlation
+    #[inline]
+    pub fn rho(&self) -> f64 {
+        self.rho
+    }
+
+    /// The covariance of `x` and `y`
+    #[inline]
+    pub fn covariance(&self) -> f64 {
+        self.rho * self.sigma.0 * self.sigma.1
+    }
+
+    /// The marginal distribution of `x`
+    #[inline]
+    pub fn marginal_x(&self) -> Gaussian {
+        Gaussian::new_unchecked(self.mu.0, self.sigma.0)
+    }
+
+    /// The marginal distribution of `y`
+    #[inline]
+    pub fn marginal_y(&self) -> Gaussian {
+        Gaussian::new_unchecked(self.mu.1, self.sigma.1)
+    }
+}
+
+impl From<&BivariateGaussian> for String {
+    fn from(bvg: &BivariateGaussian) -> String {
+        format!(
+            "N₂(μ: ({}, {}), σ: ({}, {}), ρ: {})",
+            bvg.mu.0, bvg.mu.1, bvg.sigma.0, bvg.sigma.1, bvg.rho
+        )
+    }
+}
+
+impl_display!(BivariateGaussian);
+
+impl Rv<(f64, f64)> for BivariateGaussian {
+    fn ln_f(&self, x: &(f64, f64)) -> f64 {
+        let zx = (x.0 - self.mu.0) / self.sigma.0;
+        let zy = (x.1 - self.mu.1) / self.sigma.1;
+        let one_m_rho2 = self.rho.mul_add(-self.rho, 1.0);
+        let q = (2.0 * self.rho * zx).mul_add(-zy, zx.mul_add(zx, zy * zy));
+        let ln_norm = -LN_2PI - self.sigma.0.ln() - self.sigma.1.ln();
+        (-0.5_f64).mul_add(one_m_rho2.ln() + q / one_m_rho2, ln_norm)
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> (f64, f64) {
+        let z0: f64 = rng.sample(rand_distr::StandardNormal);
+        let z1: f64 = rng.sample(rand_distr::StandardNormal);
+        let zy = self
+            .rho
+            .mul_add(z0, self.rho.mul_add(-self.rho, 1.0).sqrt() * z1);
+        (
+            self.sigma.0.mul_add(z0, self.mu.0),
+            self.sigma.1.mul_add(zy, self.mu.1),
+        )
+    }
+}
+
+impl Support<(f64, f64)> for BivariateGaussian {
+    fn supports(&self, x: &(f64, f64)) -> bool {
+        x.0.is_finite() && x.1.is_finite()
+    }
+}
+
+impl ContinuousDistr<(f64, f64)> for BivariateGaussian {}
+
+impl Mean<(f64, f64)> for BivariateGaussian {
+    fn mean(&self) -> Option<(f64, f64)> {
+        Some(self.mu)
+    }
+}
+
+impl Mode<(f64, f64)> for BivariateGaussian {
+    fn mode(&self) -> Option<(f64, f64)> {
+        Some(self.mu)
+    }
+}
+
+impl Entropy for BivariateGaussian {
+    fn entropy(&self) -> f64 {
+        let one_m_rho2 = self.rho.mul_add(-self.rho, 1.0);
+        0.5_f64.mul_add(one_m_rho2.ln(), LN_2PI_E)
+            + self.sigma.0.ln()
+            + self.sigma.1.ln()
+    }
+}
+
+/// Condition on one of the variables, given as `(index, value)` where index
+/// 0 is `x` and 1 is `y`, to get the Gaussian of the other
+impl Condition<(usize, f64)> for BivariateGaussian {
+    type Conditional = Gaussian;
+
+    fn condition(
+        &self,
+        evidence: &(usize, f64),
+    ) -> Result<Gaussian, ConditionError> {
+        let (ix, value) = *evidence;
+        let (mu_obs, sigma_obs, mu_free, sigma_free) = match ix {
+            0 => (self.mu.0, self.sigma.0, self.mu.1, self.sigma.1),
+            1 => (self.mu.1, self.sigma.1, self.mu.0, self.sigma.0),
+            _ => {
+                return Err(ConditionError::IndexOutOfBounds { ix, n_vars: 2 })
+            }
+        };
+        if !value.is_finite() {
+            return Err(ConditionError::ImpossibleEvidence);
+        }
+        let z = (value - mu_obs) / sigma_obs;
+        let mu = (self.rho * sigma_free).mul_add(z, mu_free);
+        let sigma = sigma_free * self.rho.mul_add(-self.rho, 1.0).sqrt();
+        Gaussian::new(mu, sigma).map_err(|_| ConditionError::Degenerate)
+    }
+}
+
+impl std::error::Error for BivariateGaussianError {}
+
+impl fmt::Display for BivariateGaussianError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MuNotFinite { mu } => write!(f, "non-finite mu: {}", mu),
+            Self::SigmaTooLow { sigma } => {
+                write!(f, "sigma ({}) must be greater than zero", sigma)
+            }
+            Self::SigmaNotFinite { sigma } => {
+                write!(f, "non-finite sigma: {}", sigma)
+            }
+            Self::RhoOutOfRange { rho } => {
+                write!(f, "rho ({}) must be in (-1, 1)", rho)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::misc::ks_test;
+    use crate::test_basic_impls;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    test_basic_impls!(
+        BivariateGaussian::new((0.0, 1.0), (1.0, 2.0), 0.3).unwrap(),
+        (0.5_f64, 0.5_f64)
+    );
+
+    #[test]
+    fn new_validates_params() {
+        assert!(BivariateGaussian::new((0.0, 0.0), (1.0, 1.0), 0.99).is_ok());
+        assert!(
+            BivariateGaussian::new((f64::NAN, 0.0), (1.0, 1.0), 0.0).is_err()
+        );
+        assert!(BivariateGaussian::new((0.0, 0.0), (1.0, 0.0), 0.0).is_err());
+        assert!(BivariateGaussian::new((0.0, 0.0), (1.0, 1.0), 1.0).is_err());
+        assert!(
+            BivariateGaussian::new((0.0, 0.0), (1.0, 1.0), f64::NAN).is_err()
+        );
+    }
+
+    #[test]
+    fn joint_is_marginal_times_conditional() {
+        let bvg = BivariateGaussian::new((1.0, -2.0), (1.5, 0.7), 0.6).unwrap();
+        for &(x, y) in &[(0.0, 0.0), (1.3, -2.5), (-1.0, 1.0)] {
+            let y_given_x = bvg.condition(&(0, x)).unwrap();
+            let x_given_y = bvg.condition(&(1, y)).unwrap();
+            let ln_f = bvg.ln_f(&(x, y));
+            assert::close(
+                ln_f,
+                bvg.marginal_x().ln_f(&x) + y_given_x.ln_f(&y),
+                TOL,
+            );
+            assert::close(
+                ln_f,
+                bvg.marginal_y().ln_f(&y) + x_given_y.ln_f(&x),
+                TOL,
+            );
+        }
+    }
+
+    #[test]
+    fn condition_validates_evidence() {
+        let bvg = BivariateGaussian::new((0.0, 0.0), (1.0, 1.0), 0.5).unwrap();
+        assert_eq!(
+            bvg.condition(&(2, 1.0)),
+            Err(ConditionError::IndexOutOfBounds { ix: 2, n_vars: 2 })
+        );
+        assert_eq!(
+            bvg.condition(&(0, f64::INFINITY)),
+            Err(ConditionError::ImpossibleEvidence)
+        );
+    }
+
+    #[test]
+    fn draws_have_the_right_correlation() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(1);
+        let bvg = BivariateGaussian::new((1.0, 2.0), (2.0, 3.0), 0.7).unwrap();
+        let xys: Vec<(f64, f64)> = bvg.sample(2000, &mut rng);
+
+        // x + y is Gaussian with variance σx² + σy² + 2ρσxσy
+        let sd = 2.0_f64.mul_add(bvg.covariance(), 13.0).sqrt();
+        let sum = Gaussian::new(3.0, sd).unwrap();
+        let sums: Vec<f64> = xys.iter().map(|(x, y)| x + y).collect();
+        let (_, p) = ks_test(&sums, |s| sum.cdf(&s));
+        assert!(p > 0.01);
+    }
+}
@@ -4,19 +4,55 @@ use serde::{Deserialize, Serialize};
 
 use crate::data::{CategoricalDatum, CategoricalSuffStat};
 use crate::impl_display;
-use crate::misc::{argmax, ln_pflip, logsumexp, vec_to_string};
+use crate::misc::{argmax, logsumexp, vec_to_string, AliasTable};
 use crate::traits::*;
 use rand::Rng;
+use std::cmp::Ordering;
 use std::fmt;
+use std::sync::OnceLock;
 
 /// [Categorical distribution](https://en.wikipedia.org/wiki/Categorical_distribution)
 /// over unordered values in [0, k).
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+///
+/// Draws use an alias table, built on the first draw, so that each draw
+/// takes O(1) time regardless of k. Call [`CacheControl::precompute`] to
+/// build it up front.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::Categorical;
+/// use rv::traits::*;
+///
+/// let weights: Vec<f64> = (1..=5_000).map(f64::from).collect();
+/// let cat = Categorical::new(&weights).unwrap();
+/// cat.precompute();
+///
+/// let mut rng = rand::thread_rng();
+/// let xs: Vec<usize> = cat.sample(1_000, &mut rng);
+/// assert!(xs.iter().all(|&x| x < 5_000));
+/// ```
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
 pub struct Categorical {
     // Use log weights instead to optimize for computation of ln_f
     ln_weights: Vec<f64>,
+    /// Cached alias table for drawing
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    alias: OnceLock<AliasTable>,
+}
+
+impl PartialEq for Categorical {
+    fn eq(&self, other: &Categorical) -> bool {
+        self.ln_weights == other.ln_weights
+    }
+}
+
+impl PartialOrd for Categorical {
+    fn partial_cmp(&self, other: &Categorical) -> Option<Ordering> {
+        self.ln_weights.partial_cmp(&other.ln_weights)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -132,7 +168,7 @@ impl Categorical {
 
         let sum = logsumexp(&ln_weights).abs();
         if sum < 10E-12 {
-            Ok(Categorical { ln_weights })
+            Ok(Categorical::new_unchecked(ln_weights))
         } else {
             Err(CategoricalError::WeightsDoNotSumToOne { ln: true, sum })
         }
@@ -142,7 +178,10 @@ impl Categorical {
     /// valid.
     #[inline]
     pub fn new_unchecked(ln_weights: Vec<f64>) -> Self {
-        Categorical { ln_weights }
+        Categorical {
+            ln_weights,
+            alias: OnceLock::new(),
+        }
     }
 
     /// Creates a Categorical distribution over [0, k) with uniform weights
@@ -177,6 +216,14 @@ impl Categorical {
     pub fn ln_weights(&self) -> &Vec<f64> {
         &self.ln_weights
     }
+
+    #[inline]
+    fn alias(&self) -> &AliasTable {
+        self.alias.get_or_init(|| {
+            AliasTable::from_ln_weights(&self.ln_weights)
+                .expect("Categorical weights must be valid")
+        })
+    }
 }
 
 impl From<&Categorical> for String {
@@ -194,15 +241,22 @@ impl<X: CategoricalDatum> Rv<X> for Categorical {
         self.ln_weights[ix]
     }
 
-    fn draw<R: Rng>(&self, mut rng: &mut R) -> X {
-        let ix = ln_pflip(&self.ln_weights, 1, true, &mut rng)[0];
-        CategoricalDatum::from_usize(ix)
+    fn ln_f_many(&self, xs: &[X], out: &mut [f64]) {
+        assert_eq!(xs.len(), out.len(), "xs and out must be the same length");
+        let ln_weights = self.ln_weights.as_slice();
+        xs.iter()
+            .zip(out.iter_mut())
+            .for_each(|(x, y)| *y = ln_weights[x.into_usize()]);
     }
 
-    fn sample<R: Rng>(&self, n: usize, mut rng: &mut R) -> Vec<X> {
-        ln_pflip(&self.ln_weights, n, true, &mut rng)
-            .iter()
-            .map(|&ix| CategoricalDatum::from_usize(ix))
+    fn draw<R: Rng>(&self, rng: &mut R) -> X {
+        CategoricalDatum::from_usize(self.alias().draw(rng))
+    }
+
+    fn sample<R: Rng>(&self, n: usize, rng: &mut R) -> Vec<X> {
+        let alias = self.alias();
+        (0..n)
+            .map(|_| CategoricalDatum::from_usize(alias.draw(rng)))
             .collect()
     }
 }
@@ -271,6 +325,36 @@ impl KlDivergence for Categorical {
     }
 }
 
+impl JsDivergence for Categorical {
+    fn js(&self, other: &Self) -> f64 {
+        self.ln_weights
+            .iter()
+            .zip(other.ln_weights.iter())
+            .map(|(&ws, &wo)| js_term(ws.exp(), wo.exp()))
+            .sum()
+    }
+}
+
+impl HellingerDistance for Categorical {
+    fn hellinger(&self, other: &Self) -> f64 {
+        let bc: f64 = self
+            .ln_weights
+            .iter()
+            .zip(other.ln_weights.iter())
+            .map(|(&ws, &wo)| (0.5 * (ws + wo)).exp())
+            .sum();
+        (1.0 - bc).max(0.0).sqrt()
+    }
+}
+
+/// The contribution of an outcome with probabilities `p` and `q` to the JS
+/// divergence
+pub(crate) fn js_term(p: f64, q: f64) -> f64 {
+    let m = p + q;
+    let term = |w: f64| if w > 0.0 { w * (2.0 * w / m).ln() } else { 0.0 };
+    0.5 * (term(p) + term(q))
+}
+
 impl fmt::Display for CategoricalError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -294,6 +378,16 @@ impl fmt::Display for CategoricalError {
     }
 }
 
+impl CacheControl for Categorical {
+    fn precompute(&self) {
+        self.alias();
+    }
+
+    fn invalidate(&mut self) {
+        self.alias = OnceLock::new();
+    }
+}
+
 impl std::error::Error for CategoricalError {}
 
 #[cfg(test)]
@@ -301,6 +395,8 @@ mod tests {
     use super::*;
     use crate::misc::x2_test;
     use crate::test_basic_impls;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
     use std::f64::consts::LN_2;
     use std::f64::NEG_INFINITY;
 
@@ -422,6 +518,42 @@ mod tests {
         assert!(passes > 0);
     }
 
+    #[test]
+    fn draws_follow_many_small_weights() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xCA7);
+        let k = 2_000;
+        let weights: Vec<f64> = (0..k).map(|i| (i % 7) as f64).collect();
+        let cat = Categorical::new(&weights).unwrap();
+        let xs: Vec<usize> = cat.sample(100_000, &mut rng);
+        assert!(xs.iter().all(|&x| x % 7 != 0));
+
+        // Pool categories by weight so the counts are large enough to test
+        let mut f_obs: Vec<u32> = vec![0; 6];
+        xs.iter().for_each(|&x| f_obs[x % 7 - 1] += 1);
+        let total: f64 = weights.iter().sum();
+        let ps: Vec<f64> = (1..7)
+            .map(|w| {
+                let n_w = (0..k).filter(|i| i % 7 == w).count();
+                (w * n_w) as f64 / total
+            })
+            .collect();
+        let (_, p) = x2_test(&f_obs, &ps);
+        assert!(p > 0.01);
+    }
+
+    #[test]
+    fn cache_is_ignored_by_eq_and_rebuilt_after_invalidate() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xCA7);
+        let mut cat = Categorical::new(&[1.0, 0.0, 3.0]).unwrap();
+        let fresh = cat.clone();
+        cat.precompute();
+        assert_eq!(cat, fresh);
+
+        cat.invalidate();
+        let xs: Vec<u8> = cat.sample(100, &mut rng);
+        assert!(xs.iter().all(|&x| x != 1));
+    }
+
     #[test]
     fn kl() {
         let cat1 = Categorical::new(&[
@@ -446,6 +578,33 @@ mod tests {
         assert::close(cat2.kl(&cat1), 0.188_144_081_986_255_82, 1E-7);
     }
 
+    #[test]
+    fn js_and_hellinger_are_symmetric_and_bounded() {
+        let cat1 = Categorical::new(&[0.2, 0.5, 0.3]).unwrap();
+        let cat2 = Categorical::new(&[0.6, 0.1, 0.3]).unwrap();
+        let cat3 = Categorical::new(&[0.0, 0.0, 1.0]).unwrap();
+
+        assert::close(cat1.js(&cat2), cat2.js(&cat1), TOL);
+        assert::close(cat1.hellinger(&cat2), cat2.hellinger(&cat1), TOL);
+        assert::close(cat1.js(&cat1), 0.0, TOL);
+        assert::close(cat1.hellinger(&cat1), 0.0, 1E-7);
+
+        // JS is finite where KL is not
+        assert_eq!(cat1.kl(&cat3), f64::INFINITY);
+        let js = cat1.js(&cat3);
+        assert!(js > 0.0 && js < std::f64::consts::LN_2);
+
+        let m = [0.4_f64, 0.3, 0.3];
+        let js_direct = 0.5
+            * [0.2_f64, 0.5, 0.3]
+                .iter()
+                .chain([0.6_f64, 0.1, 0.3].iter())
+                .zip(m.iter().chain(m.iter()))
+                .map(|(p, m)| p * (p / m).ln())
+                .sum::<f64>();
+        assert::close(cat1.js(&cat2), js_direct, 1E-12);
+    }
+
     #[test]
     fn cdf() {
         let cat = Categorical::new(&[1.0, 2.0, 4.0, 3.0]).unwrap();
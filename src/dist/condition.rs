@@ -0,0 +1,83 @@
+use std::fmt;
+
+/// Errors from conditioning a joint distribution with
+/// [`Condition`](crate::traits::Condition)
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConditionError {
+    /// A variable index in the evidence is out of bounds
+    IndexOutOfBounds {
+        /// The offending index
+        ix: usize,
+        /// The number of variables in the joint distribution
+        n_vars: usize,
+    },
+    /// A variable appears more than once in the evidence
+    DuplicateIndex {
+        /// The repeated index
+        ix: usize,
+    },
+    /// Every variable is in the evidence, so there is nothing left to
+    /// distribute
+    NoFreeVariables,
+    /// The evidence has zero probability or is outside the support
+    ImpossibleEvidence,
+    /// The conditional distribution could not be constructed, e.g., because
+    /// its parameters are numerically degenerate
+    Degenerate,
+}
+
+/// Validates the indices of evidence on `n_vars` variables and returns the
+/// indices of the variables that are not in the evidence
+pub(crate) fn free_indices<'a, I>(
+    evidence_ixs: I,
+    n_vars: usize,
+) -> Result<Vec<usize>, ConditionError>
+where
+    I: IntoIterator<Item = &'a usize>,
+{
+    let mut observed = vec![false; n_vars];
+    for &ix in evidence_ixs {
+        if ix >= n_vars {
+            return Err(ConditionError::IndexOutOfBounds { ix, n_vars });
+        } else if observed[ix] {
+            return Err(ConditionError::DuplicateIndex { ix });
+        }
+        observed[ix] = true;
+    }
+    let free: Vec<usize> = (0..n_vars).filter(|&ix| !observed[ix]).collect();
+    if free.is_empty() {
+        Err(ConditionError::NoFreeVariables)
+    } else {
+        Ok(free)
+    }
+}
+
+impl std::error::Error for ConditionError {}
+
+impl fmt::Display for ConditionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IndexOutOfBounds { ix, n_vars } => write!(
+                f,
+                "evidence index {} is out of bounds for {} variables",
+                ix, n_vars
+            ),
+            Self::DuplicateIndex { ix } => {
+                write!(
+                    f,
+                    "variable {} appears more than once in the evidence",
+                    ix
+                )
+            }
+            Self::NoFreeVariables => {
+                write!(f, "every variable is in the evidence")
+            }
+            Self::ImpossibleEvidence => {
+                write!(f, "the evidence has zero probability")
+            }
+            Self::Degenerate => {
+                write!(f, "the conditional distribution is degenerate")
+            }
+        }
+    }
+}
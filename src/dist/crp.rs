@@ -11,6 +11,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::data::Partition;
+use crate::dist::{Beta, Gamma};
 use crate::impl_display;
 use crate::misc::pflip;
 use crate::traits::*;
@@ -185,6 +186,71 @@ impl Crp {
     pub fn set_n_unchecked(&mut self, n: usize) {
         self.n = n;
     }
+
+    /// Resample alpha given `partition` under a Gamma prior on alpha
+    ///
+    /// Uses the auxiliary variable Gibbs step of Escobar and West (1995), so
+    /// repeated calls alternated with updates to the partition sample from
+    /// the joint posterior. Returns the new alpha.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rv::dist::{Crp, Gamma};
+    /// # use rv::data::Partition;
+    /// let mut rng = rand::thread_rng();
+    /// let mut crp = Crp::new(1.0, 6).unwrap();
+    /// let partition = Partition::from_z(vec![0, 0, 1, 1, 2, 3]).unwrap();
+    ///
+    /// let prior = Gamma::new(1.0, 1.0).unwrap();
+    /// let alpha = crp.resample_alpha(&partition, &prior, &mut rng);
+    ///
+    /// assert_eq!(crp.alpha(), alpha);
+    /// assert!(alpha > 0.0);
+    /// ```
+    pub fn resample_alpha<R: Rng>(
+        &mut self,
+        partition: &Partition,
+        prior: &Gamma,
+        rng: &mut R,
+    ) -> f64 {
+        let alpha = draw_alpha_posterior(
+            self.alpha,
+            partition.len(),
+            partition.k(),
+            prior,
+            rng,
+        );
+        self.set_alpha_unchecked(alpha);
+        alpha
+    }
+}
+
+/// One Escobar-West auxiliary variable Gibbs step for the CRP alpha given
+/// `n` items in `k` categories, under a Gamma prior.
+pub(crate) fn draw_alpha_posterior<R: Rng>(
+    alpha: f64,
+    n: usize,
+    k: usize,
+    prior: &Gamma,
+    rng: &mut R,
+) -> f64 {
+    if n == 0 {
+        return prior.draw(rng);
+    }
+    let nf = n as f64;
+    let kf = k as f64;
+    let eta: f64 = Beta::new_unchecked(alpha + 1.0, nf).draw(rng);
+    let rate = prior.rate() - eta.ln();
+    // Odds of the k-shape component against the (k - 1)-shape component
+    let odds = (prior.shape() + kf - 1.0) / (nf * rate);
+    let shape = if rng.gen::<f64>() < odds / (1.0 + odds) {
+        prior.shape() + kf
+    } else {
+        prior.shape() + kf - 1.0
+    };
+    let alpha: f64 = Gamma::new_unchecked(shape, rate).draw(rng);
+    alpha.max(f64::MIN_POSITIVE)
 }
 
 impl From<&Crp> for String {
@@ -277,5 +343,35 @@ mod tests {
         assert_eq!(crp.n, 808);
     }
 
-    // TODO: More tests!
+    #[test]
+    fn resampled_alpha_matches_posterior() {
+        use rand::SeedableRng;
+        let mut rng = rand_xoshiro::Xoshiro256Plus::seed_from_u64(17);
+        let prior = Gamma::new(2.0, 1.5).unwrap();
+        let partition =
+            Partition::from_z(vec![0, 0, 0, 1, 1, 2, 0, 3, 1, 0, 4, 0])
+                .unwrap();
+        let (n, k) = (partition.len() as f64, partition.k() as f64);
+
+        // p(α | k, n) ∝ p(α) α^k Γ(α) / Γ(α + n)
+        let ln_post = |a: f64| {
+            let ln_prior: f64 = prior.ln_f(&a);
+            k.mul_add(a.ln(), ln_prior) + a.ln_gamma().0 - (a + n).ln_gamma().0
+        };
+        let da = 1E-3;
+        let (z, m) = (1..20_000).fold((0.0, 0.0), |(z, m), i| {
+            let a = i as f64 * da;
+            let f = ln_post(a).exp();
+            (z + f, a.mul_add(f, m))
+        });
+        let post_mean = m / z;
+
+        let mut crp = Crp::new(1.0, partition.len()).unwrap();
+        let n_samples = 20_000;
+        let mean = (0..n_samples)
+            .map(|_| crp.resample_alpha(&partition, &prior, &mut rng))
+            .sum::<f64>()
+            / n_samples as f64;
+        assert!((mean - post_mean).abs() < 0.03 * post_mean);
+    }
 }
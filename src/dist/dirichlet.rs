@@ -2,6 +2,9 @@
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
 
+use crate::dist::condition::free_indices;
+use crate::dist::marginalize::dropped_indices;
+use crate::dist::{ConditionError, MarginalizeError};
 use crate::impl_display;
 use crate::misc::vec_to_string;
 use crate::traits::*;
@@ -422,6 +425,71 @@ impl Support<Vec<f64>> for Dirichlet {
     }
 }
 
+/// Condition on some of the components, given as `(index, value)` pairs.
+///
+/// The remaining components, rescaled to sum to one, lie on a sub-simplex
+/// and are independent of the observed components. The conditional is their
+/// Dirichlet, with the concentrations of the remaining components.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::Dirichlet;
+/// use rv::traits::Condition;
+///
+/// let dir = Dirichlet::new(vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+///
+/// // (x₀, x₂) / (1 - x₁ - x₃) given x₁ = 0.2 and x₃ = 0.3
+/// let cond = dir.condition(&[(1, 0.2), (3, 0.3)]).unwrap();
+/// assert_eq!(*cond.alphas(), vec![1.0, 3.0]);
+/// ```
+impl Condition<[(usize, f64)]> for Dirichlet {
+    type Conditional = Dirichlet;
+
+    fn condition(
+        &self,
+        evidence: &[(usize, f64)],
+    ) -> Result<Dirichlet, ConditionError> {
+        let free = free_indices(evidence.iter().map(|(ix, _)| ix), self.k())?;
+        let total: f64 = evidence.iter().map(|&(_, x)| x).sum();
+        if evidence.iter().any(|&(_, x)| !(x > 0.0 && x < 1.0)) || total >= 1.0
+        {
+            return Err(ConditionError::ImpossibleEvidence);
+        }
+        let alphas = free.iter().map(|&ix| self.alphas[ix]).collect();
+        Ok(Dirichlet::new_unchecked(alphas))
+    }
+}
+
+/// The marginal of the selected components, with the remaining components
+/// lumped into a final component, `1 - Σ x_ixs`, if there are any
+impl Marginalize for Dirichlet {
+    type Marginal = Dirichlet;
+
+    fn marginalize(
+        &self,
+        ixs: &[usize],
+    ) -> Result<Dirichlet, MarginalizeError> {
+        let dropped = dropped_indices(ixs, self.k())?;
+        let mut alphas: Vec<f64> =
+            ixs.iter().map(|&ix| self.alphas[ix]).collect();
+        if !dropped.is_empty() {
+            alphas.push(dropped.iter().map(|&ix| self.alphas[ix]).sum());
+        }
+        Ok(Dirichlet::new_unchecked(alphas))
+    }
+}
+
+impl CacheControl for SymmetricDirichlet {
+    fn precompute(&self) {
+        self.ln_gamma_alpha();
+    }
+
+    fn invalidate(&mut self) {
+        self.ln_gamma_alpha = OnceLock::new();
+    }
+}
+
 impl std::error::Error for SymmetricDirichletError {}
 impl std::error::Error for DirichletError {}
 
@@ -536,6 +604,78 @@ mod tests {
                 TOL,
             );
         }
+
+        #[test]
+        fn joint_is_marginal_times_conditional() {
+            use crate::dist::Beta;
+
+            let dir = Dirichlet::new(vec![1.5, 2.0, 3.0]).unwrap();
+            let x = vec![0.2, 0.3, 0.5];
+            let cond = dir.condition(&[(1, x[1])]).unwrap();
+            let marginal = Beta::new(2.0, 4.5).unwrap();
+
+            // The density of x₀ given x₁ is that of x₀ / (1 - x₁), scaled
+            let u = x[0] / (1.0 - x[1]);
+            let ln_f = marginal.ln_f(&x[1]) + cond.ln_f(&vec![u, 1.0 - u])
+                - (1.0 - x[1]).ln();
+            assert::close(dir.ln_f(&x), ln_f, TOL);
+        }
+
+        #[test]
+        fn condition_validates_evidence() {
+            let dir = Dirichlet::new(vec![1.0, 2.0, 3.0]).unwrap();
+            assert_eq!(
+                dir.condition(&[(0, 0.6), (1, 0.5)]),
+                Err(ConditionError::ImpossibleEvidence)
+            );
+            assert_eq!(
+                dir.condition(&[(0, 0.1), (0, 0.2)]),
+                Err(ConditionError::DuplicateIndex { ix: 0 })
+            );
+            assert_eq!(
+                dir.condition(&[(0, 0.1), (1, 0.2), (2, 0.7)]),
+                Err(ConditionError::NoFreeVariables)
+            );
+        }
+
+        #[test]
+        fn marginal_components_are_beta() {
+            use crate::dist::Beta;
+
+            let dir = Dirichlet::new(vec![1.5, 2.0, 3.0, 0.5]).unwrap();
+            let marginal = dir.marginalize(&[2]).unwrap();
+            assert_eq!(marginal.alphas(), &vec![3.0, 4.0]);
+
+            let beta = Beta::new(3.0, 4.0).unwrap();
+            for x in [0.1, 0.5, 0.8] {
+                assert::close(
+                    marginal.ln_f(&vec![x, 1.0 - x]),
+                    beta.ln_f(&x),
+                    TOL,
+                );
+            }
+        }
+
+        #[test]
+        fn marginalize_reorders_and_validates() {
+            let dir = Dirichlet::new(vec![1.0, 2.0, 3.0]).unwrap();
+            assert_eq!(
+                dir.marginalize(&[2, 0, 1]).unwrap().alphas(),
+                &vec![3.0, 1.0, 2.0]
+            );
+            assert_eq!(
+                dir.marginalize(&[1, 3]),
+                Err(MarginalizeError::IndexOutOfBounds { ix: 3, n_vars: 3 })
+            );
+            assert_eq!(
+                dir.marginalize(&[1, 1]),
+                Err(MarginalizeError::DuplicateIndex { ix: 1 })
+            );
+            assert_eq!(
+                dir.marginalize(&[]),
+                Err(MarginalizeError::NoVariables)
+            );
+        }
     }
 
     mod symdir {
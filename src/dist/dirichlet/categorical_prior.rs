@@ -1,7 +1,9 @@
 use rand::Rng;
 use special::Gamma as SGamma;
 
-use crate::data::{extract_stat_then, CategoricalDatum, CategoricalSuffStat};
+use crate::data::{
+    extract_stat_then, CategoricalDatum, CategoricalSuffStat, DataOrSuffStat,
+};
 use crate::dist::{Categorical, Dirichlet, SymmetricDirichlet};
 use crate::prelude::CategoricalData;
 use crate::traits::*;
@@ -17,20 +19,65 @@ impl Rv<Categorical> for SymmetricDirichlet {
     }
 }
 
+/// The number of categories implied by the prior and a statistic, which may
+/// have grown beyond `k` if it was created with
+/// [`CategoricalSuffStat::with_capacity_growing`].
+///
+/// # Panics
+/// If a fixed statistic has more than `k` categories
+#[inline]
+fn realized_k(
+    symdir: &SymmetricDirichlet,
+    stat: &CategoricalSuffStat,
+) -> usize {
+    if stat.is_growing() {
+        symdir.k().max(stat.n_cats())
+    } else {
+        assert!(
+            stat.n_cats() <= symdir.k(),
+            "statistic has {} categories, but k is {}",
+            stat.n_cats(),
+            symdir.k()
+        );
+        symdir.k()
+    }
+}
+
+/// `ln Γ(kα) - k ln Γ(α)`
+#[inline]
+fn ln_norm(alpha: f64, k: usize) -> f64 {
+    let kf = k as f64;
+    alpha.ln_gamma().0.mul_add(-kf, (alpha * kf).ln_gamma().0)
+}
+
+/// The support of the `SymmetricDirichlet` is fixed at `k` categories, and
+/// data with a category index of `k` or more panic.
+///
+/// Open category sets are opted into by passing a statistic created with
+/// [`CategoricalSuffStat::with_capacity_growing`]. `k` is then the minimum
+/// number of categories, and the posterior and marginal likelihood are over
+/// the realized number of categories, `max(k, stat.n_cats())`, so they depend
+/// on which categories have been seen. The predictive is normalized over the
+/// realized categories, but predicting a category beyond them extends the
+/// support to include it, giving it the prior weight, `α`. Summed over every
+/// category index, the predictive of a growing statistic is thus improper.
 impl<X: CategoricalDatum> ConjugatePrior<X, Categorical>
     for SymmetricDirichlet
 {
     type Posterior = Dirichlet;
     type LnMCache = f64;
-    type LnPpCache = (Vec<f64>, f64);
+    /// The posterior alphas, the log of their sum, and whether the support
+    /// is open
+    type LnPpCache = (Vec<f64>, f64, bool);
 
     fn posterior(&self, x: &CategoricalData<X>) -> Self::Posterior {
         extract_stat_then(
             x,
             || CategoricalSuffStat::new(self.k()),
             |stat: CategoricalSuffStat| {
-                let alphas: Vec<f64> =
+                let mut alphas: Vec<f64> =
                     stat.counts().iter().map(|&ct| self.alpha() + ct).collect();
+                alphas.resize(realized_k(self, &stat), self.alpha());
 
                 Dirichlet::new(alphas).unwrap()
             },
@@ -39,10 +86,7 @@ impl<X: CategoricalDatum> ConjugatePrior<X, Categorical>
 
     #[inline]
     fn ln_m_cache(&self) -> Self::LnMCache {
-        let sum_alpha = self.alpha() * self.k() as f64;
-        let a = sum_alpha.ln_gamma().0;
-        let d = self.alpha().ln_gamma().0 * self.k() as f64;
-        a - d
+        ln_norm(self.alpha(), self.k())
     }
 
     fn ln_m_with_cache(
@@ -50,33 +94,58 @@ impl<X: CategoricalDatum> ConjugatePrior<X, Categorical>
         cache: &Self::LnMCache,
         x: &CategoricalData<X>,
     ) -> f64 {
-        let sum_alpha = self.alpha() * self.k() as f64;
-
         extract_stat_then(
             x,
             || CategoricalSuffStat::new(self.k()),
             |stat: CategoricalSuffStat| {
+                let k = realized_k(self, &stat);
+                let norm = if k == self.k() {
+                    *cache
+                } else {
+                    ln_norm(self.alpha(), k)
+                };
+                let sum_alpha = self.alpha() * k as f64;
+
+                // Categories past the end of the counts have zero counts
+                let n_unseen = (k - stat.n_cats()) as f64;
+
                 // terms
                 let b = (sum_alpha + stat.n() as f64).ln_gamma().0;
-                let c = stat.counts().iter().fold(0.0, |acc, &ct| {
-                    acc + (self.alpha() + ct).ln_gamma().0
-                });
+                let c =
+                    stat.counts().iter().fold(
+                        n_unseen * self.alpha().ln_gamma().0,
+                        |acc, &ct| acc + (self.alpha() + ct).ln_gamma().0,
+                    );
 
-                -b + c + cache
+                -b + c + norm
             },
         )
     }
 
     #[inline]
     fn ln_pp_cache(&self, x: &CategoricalData<X>) -> Self::LnPpCache {
+        let open =
+            matches!(x, DataOrSuffStat::SuffStat(stat) if stat.is_growing());
         let post = self.posterior(x);
         let norm = post.alphas().iter().fold(0.0, |acc, &a| acc + a);
-        (post.alphas, norm.ln())
+        (post.alphas, norm.ln(), open)
     }
 
     fn ln_pp_with_cache(&self, cache: &Self::LnPpCache, y: &X) -> f64 {
         let ix = y.into_usize();
-        cache.0[ix].ln() - cache.1
+        match cache.0.get(ix) {
+            Some(alpha) => alpha.ln() - cache.1,
+            None if !cache.2 => panic!(
+                "category index {} is out of range for {} categories",
+                ix,
+                cache.0.len()
+            ),
+            None => {
+                let n_new = (ix + 1 - cache.0.len()) as f64;
+                let norm = self.alpha().mul_add(n_new, cache.1.exp());
+                self.alpha().ln() - norm.ln()
+            }
+        }
     }
 }
 
@@ -335,5 +404,100 @@ mod test {
             let lf = csd.ln_f(&cat);
             assert::close(lf, -0.084_598_117_749_354_22, TOL);
         }
+
+        #[test]
+        fn growing_stat_ln_m_matches_fixed_stat() {
+            let csd = SymmetricDirichlet::new(0.8, 5).unwrap();
+            let xs: Vec<u8> = vec![0, 1, 1, 2, 2, 2];
+
+            let mut fixed = CategoricalSuffStat::new(5);
+            let mut growing = CategoricalSuffStat::with_capacity_growing(0);
+            fixed.observe_many(&xs);
+            growing.observe_many(&xs);
+            assert_eq!(growing.n_cats(), 3);
+
+            let m_fixed = csd.ln_m(&DataOrSuffStat::<u8, _>::SuffStat(&fixed));
+            let m_growing =
+                csd.ln_m(&DataOrSuffStat::<u8, _>::SuffStat(&growing));
+            assert::close(m_fixed, m_growing, TOL);
+
+            let lp_fixed =
+                csd.ln_pp(&4, &DataOrSuffStat::<u8, _>::SuffStat(&fixed));
+            let lp_growing =
+                csd.ln_pp(&4, &DataOrSuffStat::<u8, _>::SuffStat(&growing));
+            assert::close(lp_fixed, lp_growing, TOL);
+        }
+
+        #[test]
+        fn growing_stat_extends_support_beyond_k() {
+            let xs: Vec<u8> = vec![0, 1, 1, 3, 4, 4];
+            let mut stat = CategoricalSuffStat::with_capacity_growing(0);
+            stat.observe_many(&xs);
+            let data = DataOrSuffStat::<u8, _>::SuffStat(&stat);
+
+            let small = SymmetricDirichlet::new(1.5, 2).unwrap();
+            let realized = SymmetricDirichlet::new(1.5, 5).unwrap();
+
+            assert::close(small.ln_m(&data), realized.ln_m(&data), TOL);
+            assert_eq!(small.posterior(&data), realized.posterior(&data));
+        }
+
+        #[test]
+        #[should_panic]
+        fn raw_data_beyond_k_panics() {
+            let xs: Vec<u8> = vec![0, 1, 1, 3, 4, 4];
+            let csd = SymmetricDirichlet::new(1.5, 2).unwrap();
+            csd.ln_m(&DataOrSuffStat::Data(&xs));
+        }
+
+        #[test]
+        #[should_panic]
+        fn fixed_stat_ln_pp_beyond_k_panics() {
+            let xs: Vec<u8> = vec![0, 1, 1];
+            let csd = SymmetricDirichlet::new(1.5, 2).unwrap();
+            csd.ln_pp(&2, &DataOrSuffStat::Data(&xs));
+        }
+
+        #[test]
+        fn growing_stat_ln_pp_is_normalized_over_realized_support() {
+            let xs: Vec<u8> = vec![0, 1, 1, 3, 6, 6];
+            let mut stat = CategoricalSuffStat::with_capacity_growing(0);
+            stat.observe_many(&xs);
+            let data = DataOrSuffStat::<u8, _>::SuffStat(&stat);
+
+            for k in [2, 7, 9] {
+                let csd = SymmetricDirichlet::new(0.7, k).unwrap();
+                let total: f64 = (0..k.max(7) as u8)
+                    .map(|y| csd.ln_pp(&y, &data).exp())
+                    .sum();
+                assert::close(total, 1.0, TOL);
+            }
+        }
+
+        #[test]
+        fn ln_pp_of_unseen_category_beyond_k() {
+            let xs: Vec<u8> = vec![0, 1, 1, 3];
+            let mut stat = CategoricalSuffStat::with_capacity_growing(2);
+            stat.observe_many(&xs);
+            let data = DataOrSuffStat::<u8, _>::SuffStat(&stat);
+
+            // Predicting 6 extends the support to seven categories
+            let small = SymmetricDirichlet::new(1.5, 2).unwrap();
+            let extended = SymmetricDirichlet::new(1.5, 7).unwrap();
+            let lp = small.ln_pp(&6, &data);
+            // α / (7α + n)
+            assert::close(lp, (1.5_f64 / 14.5).ln(), TOL);
+            assert::close(lp, extended.ln_pp(&6, &data), TOL);
+
+            // ...which agrees with the ratio of marginal likelihoods
+            let mut with_y = stat.clone();
+            with_y.observe(&6_u8);
+            let m_with_y =
+                small.ln_m(&DataOrSuffStat::<u8, _>::SuffStat(&with_y));
+            assert::close(lp, m_with_y - extended.ln_m(&data), TOL);
+
+            // Categories within the realized support are unchanged
+            assert::close(small.ln_pp(&2, &data), (1.5_f64 / 10.0).ln(), TOL);
+        }
     }
 }
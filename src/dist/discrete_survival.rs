@@ -0,0 +1,724 @@
+//! Discrete-time survival and competing risks from per-period hazards
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::impl_display;
+use crate::misc::vec_to_string;
+use crate::traits::*;
+use num::{FromPrimitive, Integer, ToPrimitive, Unsigned};
+use rand::Rng;
+use std::fmt;
+
+/// Distribution of the period, t in {0, 1, 2, ...}, in which an event
+/// happens, given the hazard of each period: the probability of the event
+/// in period t given that it has not happened before.
+///
+/// The last hazard applies to every later period, so the tail is
+/// geometric. It must be greater than zero for the event to happen
+/// eventually.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::DiscreteSurvival;
+/// use rv::traits::*;
+///
+/// // Lapse rates of a policy by policy year
+/// let lapse = DiscreteSurvival::new(vec![0.2, 0.1, 0.05]).unwrap();
+///
+/// // In force through the first two years
+/// assert::close(lapse.sf(&1_u32), 0.8 * 0.9, 1E-12);
+///
+/// // Lapses in year 5, after two years at the ultimate rate
+/// let p = lapse.f(&4_u32);
+/// assert::close(p, 0.8 * 0.9 * 0.95 * 0.95 * 0.05, 1E-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct DiscreteSurvival {
+    hazards: Vec<f64>,
+}
+
+/// Discrete-time competing risks: the period of the first event and which
+/// of several causes it was, given the hazard of each cause in each period.
+///
+/// The hazards of the causes in a period sum to the overall hazard of that
+/// period. As with [`DiscreteSurvival`], the hazards of the last period
+/// apply to every later period.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::CompetingRisks;
+/// use rv::traits::*;
+///
+/// // Death (0) and lapse (1) by policy year
+/// let risks = CompetingRisks::new(vec![
+///     vec![0.01, 0.2],
+///     vec![0.02, 0.1],
+/// ]).unwrap();
+///
+/// // Chance of lapsing in the first two years
+/// let lapsed = risks.cumulative_incidence(1, 1);
+/// assert::close(lapsed, 0.2 + 0.79 * 0.1, 1E-12);
+///
+/// // Every policy eventually ends by one cause or the other
+/// let total = risks.cause_probability(0) + risks.cause_probability(1);
+/// assert::close(total, 1.0, 1E-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct CompetingRisks {
+    /// `cause_hazards[t][c]` is the hazard of cause `c` in period `t`
+    cause_hazards: Vec<Vec<f64>>,
+    /// Time to the first event of any cause
+    overall: DiscreteSurvival,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum DiscreteSurvivalError {
+    /// There are no periods
+    NoPeriods,
+    /// There are no causes
+    NoCauses,
+    /// A hazard is not in [0, 1]
+    HazardOutOfRange { ix: usize, hazard: f64 },
+    /// The hazard of a cause is negative, infinite, or NaN
+    CauseHazardInvalid {
+        ix: usize,
+        cause: usize,
+        hazard: f64,
+    },
+    /// The hazards of the causes in a period sum to more than one
+    TotalHazardTooHigh { ix: usize, total: f64 },
+    /// A period has a different number of causes than the first
+    CauseCountMismatch {
+        ix: usize,
+        n_causes: usize,
+        expected: usize,
+    },
+    /// The hazard of the last period is zero, so the event may never
+    /// happen
+    FinalHazardZero,
+    /// The event and censoring tables have different numbers of periods
+    TableLengthMismatch { n_events: usize, n_censored: usize },
+    /// A count is negative, infinite, or NaN
+    InvalidCount { ix: usize, count: f64 },
+    /// The tables have no subjects
+    NoSubjects,
+}
+
+impl DiscreteSurvival {
+    /// Create a discrete survival distribution from per-period hazards
+    pub fn new(hazards: Vec<f64>) -> Result<Self, DiscreteSurvivalError> {
+        if hazards.is_empty() {
+            return Err(DiscreteSurvivalError::NoPeriods);
+        }
+        hazards.iter().enumerate().try_for_each(|(ix, &hazard)| {
+            if (0.0..=1.0).contains(&hazard) {
+                Ok(())
+            } else {
+                Err(DiscreteSurvivalError::HazardOutOfRange { ix, hazard })
+            }
+        })?;
+        if hazards[hazards.len() - 1] == 0.0 {
+            return Err(DiscreteSurvivalError::FinalHazardZero);
+        }
+        Ok(DiscreteSurvival { hazards })
+    }
+
+    /// Creates a new DiscreteSurvival without checking whether the hazards
+    /// are valid
+    #[inline]
+    pub fn new_unchecked(hazards: Vec<f64>) -> Self {
+        DiscreteSurvival { hazards }
+    }
+
+    /// Estimate the hazards from a censored event table.
+    ///
+    /// `events[t]` subjects had the event in period `t` and `censored[t]`
+    /// left the study in period `t` without it. Subjects censored in a
+    /// period count as at risk for all of it, so the survival function is
+    /// the Kaplan-Meier estimate. Periods after everyone has left are
+    /// dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::DiscreteSurvival;
+    /// use rv::traits::*;
+    ///
+    /// // 100 subjects: 10 events and 20 withdrawals in the first period,
+    /// // then 35 events among the 70 left
+    /// let surv = DiscreteSurvival::from_event_table(
+    ///     &[10.0, 35.0],
+    ///     &[20.0, 35.0],
+    /// ).unwrap();
+    /// assert_eq!(surv.hazards(), &[0.1, 0.5]);
+    /// ```
+    pub fn from_event_table(
+        events: &[f64],
+        censored: &[f64],
+    ) -> Result<Self, DiscreteSurvivalError> {
+        let events: Vec<Vec<f64>> = events.iter().map(|&d| vec![d]).collect();
+        let hazards = table_hazards(&events, censored)?
+            .into_iter()
+            .map(|h| h[0])
+            .collect();
+        Self::new(hazards)
+    }
+
+    /// The hazards of the given periods
+    #[inline]
+    pub fn hazards(&self) -> &[f64] {
+        &self.hazards
+    }
+
+    /// The hazard of period `t`
+    #[inline]
+    pub fn hazard(&self, t: usize) -> f64 {
+        self.hazards[t.min(self.hazards.len() - 1)]
+    }
+
+    /// ln P(T > t)
+    fn ln_surv(&self, t: usize) -> f64 {
+        let m = self.hazards.len();
+        let given: f64 = self.hazards[..=t.min(m - 1)]
+            .iter()
+            .map(|h| (-h).ln_1p())
+            .sum();
+        if t < m {
+            given
+        } else {
+            ((t - m + 1) as f64).mul_add((-self.hazards[m - 1]).ln_1p(), given)
+        }
+    }
+
+    /// P(T > t - 1), the probability of reaching period `t`
+    fn surv_before(&self, t: usize) -> f64 {
+        if t == 0 {
+            1.0
+        } else {
+            self.ln_surv(t - 1).exp()
+        }
+    }
+
+    fn draw_usize<R: Rng>(&self, rng: &mut R) -> usize {
+        // The first period whose survival drops below a uniform draw
+        let v: f64 = 1.0 - rng.gen::<f64>();
+        let mut surv = 1.0;
+        for (t, h) in self.hazards.iter().enumerate() {
+            surv *= 1.0 - h;
+            if surv < v {
+                return t;
+            }
+        }
+        // Geometric tail
+        let m = self.hazards.len();
+        let ln_q = (-self.hazards[m - 1]).ln_1p();
+        m + ((v / surv).ln() / ln_q).floor() as usize
+    }
+}
+
+impl CompetingRisks {
+    /// Create a competing risks distribution from the hazard of each cause
+    /// in each period, `cause_hazards[t][c]`
+    pub fn new(
+        cause_hazards: Vec<Vec<f64>>,
+    ) -> Result<Self, DiscreteSurvivalError> {
+        if cause_hazards.is_empty() {
+            return Err(DiscreteSurvivalError::NoPeriods);
+        }
+        let n_causes = cause_hazards[0].len();
+        if n_causes == 0 {
+            return Err(DiscreteSurvivalError::NoCauses);
+        }
+        let hazards = cause_hazards
+            .iter()
+            .enumerate()
+            .map(|(ix, hs)| {
+                if hs.len() != n_causes {
+                    return Err(DiscreteSurvivalError::CauseCountMismatch {
+                        ix,
+                        n_causes: hs.len(),
+                        expected: n_causes,
+                    });
+                }
+                hs.iter().enumerate().try_for_each(|(cause, &hazard)| {
+                    if hazard >= 0.0 && hazard.is_finite() {
+                        Ok(())
+                    } else {
+                        Err(DiscreteSurvivalError::CauseHazardInvalid {
+                            ix,
+                            cause,
+                            hazard,
+                        })
+                    }
+                })?;
+                let total: f64 = hs.iter().sum();
+                if total > 1.0 {
+                    Err(DiscreteSurvivalError::TotalHazardTooHigh { ix, total })
+                } else {
+                    Ok(total)
+                }
+            })
+            .collect::<Result<Vec<f64>, _>>()?;
+        let overall = DiscreteSurvival::new(hazards)?;
+        Ok(CompetingRisks {
+            cause_hazards,
+            overall,
+        })
+    }
+
+    /// Estimate the cause-specific hazards from a censored event table.
+    ///
+    /// `events[t][c]` subjects had an event of cause `c` in period `t` and
+    /// `censored[t]` left the study in period `t` without an event.
+    /// Subjects censored in a period count as at risk for all of it, so the
+    /// cumulative incidences are the Aalen-Johansen estimates. Periods
+    /// after everyone has left are dropped.
+    pub fn from_event_table(
+        events: &[Vec<f64>],
+        censored: &[f64],
+    ) -> Result<Self, DiscreteSurvivalError> {
+        if events.first().is_some_and(Vec::is_empty) {
+            return Err(DiscreteSurvivalError::NoCauses);
+        }
+        Self::new(table_hazards(events, censored)?)
+    }
+
+    /// The number of causes
+    #[inline]
+    pub fn n_causes(&self) -> usize {
+        self.cause_hazards[0].len()
+    }
+
+    /// The hazard of each cause in each of the given periods
+    #[inline]
+    pub fn cause_hazards(&self) -> &[Vec<f64>] {
+        &self.cause_hazards
+    }
+
+    /// The hazard of `cause` in period `t`
+    #[inline]
+    pub fn cause_hazard(&self, cause: usize, t: usize) -> f64 {
+        self.cause_hazards[t.min(self.cause_hazards.len() - 1)][cause]
+    }
+
+    /// The distribution of the period of the first event of any cause
+    #[inline]
+    pub fn overall(&self) -> &DiscreteSurvival {
+        &self.overall
+    }
+
+    /// The cumulative incidence (sub-distribution function) of `cause`,
+    /// the probability that the first event happens by period `t` and is
+    /// of `cause`
+    pub fn cumulative_incidence(&self, cause: usize, t: usize) -> f64 {
+        let m = self.cause_hazards.len();
+        let given: f64 = (0..=t.min(m - 1))
+            .map(|s| self.overall.surv_before(s) * self.cause_hazard(cause, s))
+            .sum();
+        if t < m {
+            given
+        } else {
+            // Geometric sum over periods m..=t at the final hazards
+            let q = 1.0 - self.overall.hazard(m);
+            let n_tail = (t - m + 1) as i32;
+            self.overall.surv_before(m)
+                * self.cause_hazard(cause, m)
+                * (1.0 - q.powi(n_tail))
+                / self.overall.hazard(m)
+                + given
+        }
+    }
+
+    /// The probability that the first event is of `cause`, the cumulative
+    /// incidence as `t → ∞`
+    pub fn cause_probability(&self, cause: usize) -> f64 {
+        let m = self.cause_hazards.len();
+        let given: f64 = (0..m - 1)
+            .map(|s| self.overall.surv_before(s) * self.cause_hazard(cause, s))
+            .sum();
+        self.overall.surv_before(m - 1) * self.cause_hazard(cause, m - 1)
+            / self.overall.hazard(m - 1)
+            + given
+    }
+}
+
+/// The hazard of each cause in each period of an event table, stopping
+/// when no one is left at risk
+fn table_hazards(
+    events: &[Vec<f64>],
+    censored: &[f64],
+) -> Result<Vec<Vec<f64>>, DiscreteSurvivalError> {
+    if events.len() != censored.len() {
+        return Err(DiscreteSurvivalError::TableLengthMismatch {
+            n_events: events.len(),
+            n_censored: censored.len(),
+        });
+    }
+    let n_causes = events.first().map_or(0, Vec::len);
+    let check_count = |ix: usize, count: f64| {
+        if count >= 0.0 && count.is_finite() {
+            Ok(count)
+        } else {
+            Err(DiscreteSurvivalError::InvalidCount { ix, count })
+        }
+    };
+    let mut at_risk = 0.0;
+    for (ix, (ds, &c)) in events.iter().zip(censored.iter()).enumerate() {
+        if ds.len() != n_causes {
+            return Err(DiscreteSurvivalError::CauseCountMismatch {
+                ix,
+                n_causes: ds.len(),
+                expected: n_causes,
+            });
+        }
+        at_risk += check_count(ix, c)?;
+        for &d in ds {
+            at_risk += check_count(ix, d)?;
+        }
+    }
+    if at_risk <= 0.0 {
+        return Err(DiscreteSurvivalError::NoSubjects);
+    }
+
+    let mut hazards = Vec::with_capacity(events.len());
+    for (ds, c) in events.iter().zip(censored.iter()) {
+        if at_risk <= 0.0 {
+            break;
+        }
+        hazards.push(ds.iter().map(|d| d / at_risk).collect());
+        at_risk -= ds.iter().sum::<f64>() + c;
+    }
+    Ok(hazards)
+}
+
+impl From<&DiscreteSurvival> for String {
+    fn from(surv: &DiscreteSurvival) -> String {
+        format!(
+            "DiscreteSurvival(hazards: {})",
+            vec_to_string(&surv.hazards, 5)
+        )
+    }
+}
+
+impl_display!(DiscreteSurvival);
+
+impl From<&CompetingRisks> for String {
+    fn from(risks: &CompetingRisks) -> String {
+        format!(
+            "CompetingRisks(periods: {}, causes: {})",
+            risks.cause_hazards.len(),
+            risks.n_causes()
+        )
+    }
+}
+
+impl_display!(CompetingRisks);
+
+impl<X> Rv<X> for DiscreteSurvival
+where
+    X: Unsigned + Integer + FromPrimitive + ToPrimitive,
+{
+    fn ln_f(&self, t: &X) -> f64 {
+        let t = t.to_usize().unwrap();
+        let ln_reach = if t == 0 { 0.0 } else { self.ln_surv(t - 1) };
+        self.hazard(t).ln() + ln_reach
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> X {
+        X::from_usize(self.draw_usize(rng)).unwrap()
+    }
+}
+
+impl<X> Support<X> for DiscreteSurvival
+where
+    X: Unsigned + Integer + FromPrimitive + ToPrimitive,
+{
+    fn supports(&self, _t: &X) -> bool {
+        true
+    }
+}
+
+impl<X> DiscreteDistr<X> for DiscreteSurvival where
+    X: Unsigned + Integer + FromPrimitive + ToPrimitive
+{
+}
+
+impl<X> Cdf<X> for DiscreteSurvival
+where
+    X: Unsigned + Integer + FromPrimitive + ToPrimitive,
+{
+    fn cdf(&self, t: &X) -> f64 {
+        -self.ln_surv(t.to_usize().unwrap()).exp_m1()
+    }
+
+    fn sf(&self, t: &X) -> f64 {
+        self.ln_surv(t.to_usize().unwrap()).exp()
+    }
+}
+
+impl Mean<f64> for DiscreteSurvival {
+    fn mean(&self) -> Option<f64> {
+        // E[T] = Σ P(T > t), with a geometric sum over the tail
+        let m = self.hazards.len();
+        let given: f64 = (0..m - 1).map(|t| self.ln_surv(t).exp()).sum();
+        Some(self.ln_surv(m - 1).exp() / self.hazards[m - 1] + given)
+    }
+}
+
+/// The first event as `(period, cause)`
+impl Rv<(usize, usize)> for CompetingRisks {
+    fn ln_f(&self, x: &(usize, usize)) -> f64 {
+        let (t, cause) = *x;
+        let ln_reach = if t == 0 {
+            0.0
+        } else {
+            self.overall.ln_surv(t - 1)
+        };
+        self.cause_hazard(cause, t).ln() + ln_reach
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> (usize, usize) {
+        let t = self.overall.draw_usize(rng);
+        let row = &self.cause_hazards[t.min(self.cause_hazards.len() - 1)];
+        let total: f64 = row.iter().sum();
+        let mut u = rng.gen::<f64>() * total;
+        let cause = row
+            .iter()
+            .position(|&h| {
+                u -= h;
+                u < 0.0
+            })
+            .unwrap_or(row.len() - 1);
+        (t, cause)
+    }
+}
+
+impl Support<(usize, usize)> for CompetingRisks {
+    fn supports(&self, x: &(usize, usize)) -> bool {
+        x.1 < self.n_causes()
+    }
+}
+
+impl DiscreteDistr<(usize, usize)> for CompetingRisks {}
+
+impl std::error::Error for DiscreteSurvivalError {}
+
+impl fmt::Display for DiscreteSurvivalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoPeriods => write!(f, "there must be at least one period"),
+            Self::NoCauses => write!(f, "there must be at least one cause"),
+            Self::HazardOutOfRange { ix, hazard } => write!(
+                f,
+                "hazard of period {} ({}) must be in [0, 1]",
+                ix, hazard
+            ),
+            Self::CauseHazardInvalid { ix, cause, hazard } => write!(
+                f,
+                "hazard of cause {} in period {} ({}) must be finite and \
+                non-negative",
+                cause, ix, hazard
+            ),
+            Self::TotalHazardTooHigh { ix, total } => write!(
+                f,
+                "hazards of the causes in period {} sum to {}, more than one",
+                ix, total
+            ),
+            Self::CauseCountMismatch {
+                ix,
+                n_causes,
+                expected,
+            } => write!(
+                f,
+                "period {} has {} causes but the first has {}",
+                ix, n_causes, expected
+            ),
+            Self::FinalHazardZero => write!(
+                f,
+                "the hazard of the last period must be greater than zero"
+            ),
+            Self::TableLengthMismatch {
+                n_events,
+                n_censored,
+            } => write!(
+                f,
+                "{} periods of events but {} of censoring",
+                n_events, n_censored
+            ),
+            Self::InvalidCount { ix, count } => write!(
+                f,
+                "count in period {} ({}) must be finite and non-negative",
+                ix, count
+            ),
+            Self::NoSubjects => write!(f, "the tables have no subjects"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::Geometric;
+    use crate::test_basic_impls;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    fn surv() -> DiscreteSurvival {
+        DiscreteSurvival::new(vec![0.3, 0.0, 0.5, 0.2]).unwrap()
+    }
+
+    test_basic_impls!(surv(), 3_u32);
+
+    #[test]
+    fn constant_hazard_is_geometric() {
+        let surv = DiscreteSurvival::new(vec![0.25]).unwrap();
+        let geom = Geometric::new(0.25).unwrap();
+        for t in [0_u32, 1, 5, 30] {
+            assert::close(surv.ln_f(&t), geom.ln_f(&t), TOL);
+            assert::close(surv.cdf(&t), geom.cdf(&t), TOL);
+        }
+        assert::close(surv.mean().unwrap(), geom.mean().unwrap(), TOL);
+    }
+
+    #[test]
+    fn pmf_sums_to_one_and_matches_mean() {
+        let surv = surv();
+        let ps: Vec<f64> = (0..200_u32).map(|t| surv.f(&t)).collect();
+        assert::close(ps.iter().sum::<f64>(), 1.0, TOL);
+        let mean: f64 = ps.iter().enumerate().map(|(t, p)| t as f64 * p).sum();
+        assert::close(surv.mean().unwrap(), mean, 1E-10);
+        assert_eq!(surv.f(&1_u32), 0.0);
+        assert::close(surv.cdf(&10_u32) + surv.sf(&10_u32), 1.0, TOL);
+    }
+
+    #[test]
+    fn draws_match_pmf() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x87);
+        let surv = surv();
+        let n = 50_000;
+        let ts: Vec<usize> = surv.sample(n, &mut rng);
+        for t in [0_usize, 1, 2, 3, 6] {
+            let freq = ts.iter().filter(|&&s| s == t).count() as f64 / n as f64;
+            assert::close(freq, surv.f(&t), 0.01);
+        }
+    }
+
+    #[test]
+    fn table_estimate_is_kaplan_meier() {
+        // Times of 10 subjects; censored ones marked false
+        let times = [0, 0, 1, 1, 1, 2, 2, 3, 3, 3];
+        let observed = [
+            true, false, true, true, false, true, false, true, true, true,
+        ];
+        let mut events = vec![0.0; 4];
+        let mut censored = vec![0.0; 4];
+        for (&t, &obs) in times.iter().zip(observed.iter()) {
+            if obs {
+                events[t] += 1.0;
+            } else {
+                censored[t] += 1.0;
+            }
+        }
+        let surv =
+            DiscreteSurvival::from_event_table(&events, &censored).unwrap();
+        let km = [0.9, 0.9 * 6.0 / 8.0, 0.9 * 0.75 * 4.0 / 5.0, 0.0];
+        for (t, s) in km.iter().enumerate() {
+            assert::close(surv.sf(&t), *s, TOL);
+        }
+    }
+
+    #[test]
+    fn competing_risks_match_empirical_incidence() {
+        // Without censoring, cumulative incidence is the empirical fraction
+        let events = vec![
+            vec![2.0, 3.0, 0.0],
+            vec![1.0, 0.0, 4.0],
+            vec![5.0, 1.0, 4.0],
+        ];
+        let risks =
+            CompetingRisks::from_event_table(&events, &[0.0; 3]).unwrap();
+        assert_eq!(risks.n_causes(), 3);
+        assert::close(risks.cumulative_incidence(0, 1), 3.0 / 20.0, TOL);
+        assert::close(risks.cumulative_incidence(2, 2), 8.0 / 20.0, TOL);
+        assert::close(risks.cause_probability(1), 4.0 / 20.0, TOL);
+        assert::close(risks.overall().sf(&1_u32), 10.0 / 20.0, TOL);
+
+        let p: f64 = (0..3)
+            .flat_map(|t| (0..3).map(move |c| (t, c)))
+            .map(|x| risks.f(&x))
+            .sum();
+        assert::close(p, 1.0, TOL);
+    }
+
+    #[test]
+    fn competing_risks_tail() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x88);
+        let risks = CompetingRisks::new(vec![vec![0.1, 0.1], vec![0.05, 0.15]])
+            .unwrap();
+        // Incidence through period 50 by summing the pmf
+        let by_sum: f64 = (0..=50).map(|t| risks.f(&(t, 1))).sum();
+        assert::close(risks.cumulative_incidence(1, 50), by_sum, TOL);
+        assert::close(
+            risks.cause_probability(0) + risks.cause_probability(1),
+            1.0,
+            TOL,
+        );
+
+        let n = 50_000;
+        let draws: Vec<(usize, usize)> = risks.sample(n, &mut rng);
+        let freq = draws.iter().filter(|x| x.1 == 0).count() as f64 / n as f64;
+        assert::close(freq, risks.cause_probability(0), 0.01);
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        assert_eq!(
+            DiscreteSurvival::new(vec![0.5, 1.5]),
+            Err(DiscreteSurvivalError::HazardOutOfRange { ix: 1, hazard: 1.5 })
+        );
+        assert_eq!(
+            DiscreteSurvival::new(vec![0.5, 0.0]),
+            Err(DiscreteSurvivalError::FinalHazardZero)
+        );
+        assert_eq!(
+            CompetingRisks::new(vec![vec![0.5, 0.6]]),
+            Err(DiscreteSurvivalError::TotalHazardTooHigh {
+                ix: 0,
+                total: 1.1
+            })
+        );
+        assert_eq!(
+            CompetingRisks::new(vec![vec![0.5, 0.1], vec![0.5]]),
+            Err(DiscreteSurvivalError::CauseCountMismatch {
+                ix: 1,
+                n_causes: 1,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            DiscreteSurvival::from_event_table(&[1.0], &[1.0, 2.0]),
+            Err(DiscreteSurvivalError::TableLengthMismatch {
+                n_events: 1,
+                n_censored: 2
+            })
+        );
+        assert_eq!(
+            DiscreteSurvival::from_event_table(&[1.0, -1.0], &[0.0, 0.0]),
+            Err(DiscreteSurvivalError::InvalidCount { ix: 1, count: -1.0 })
+        );
+        assert_eq!(
+            DiscreteSurvival::from_event_table(&[0.0], &[0.0]),
+            Err(DiscreteSurvivalError::NoSubjects)
+        );
+    }
+}
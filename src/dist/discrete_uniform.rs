@@ -1,4 +1,4 @@
-//! Continuous uniform distribution, U(a, b) on the interval x in [a, b]
+//! Discrete uniform distribution, U(a, b) on the integers x in [a, b]
 use crate::traits::*;
 use num::{FromPrimitive, Integer, ToPrimitive};
 use rand::Rng;
@@ -9,11 +9,29 @@ use std::fmt;
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
 
-pub trait DuParam: Integer + Copy {}
-impl<T> DuParam for T where T: Integer + Copy {}
+pub trait DuParam: Integer + Copy + ToPrimitive {}
+impl<T> DuParam for T where T: Integer + Copy + ToPrimitive {}
 
 /// [Discrete uniform distribution](https://en.wikipedia.org/wiki/Discrete_uniform_distribution),
-/// U(a, b) on the interval x in [a, b]
+/// U(a, b) on the integers x in [a, b]
+///
+/// Each of the n = b - a + 1 integers has probability 1/n.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::DiscreteUniform;
+/// use rv::traits::*;
+///
+/// let du = DiscreteUniform::new(1_u32, 6).unwrap();
+///
+/// assert!((du.pmf(&3_u32) - 1.0 / 6.0).abs() < 1E-12);
+/// assert!((du.cdf(&2_u32) - 2.0 / 6.0).abs() < 1E-12);
+///
+/// // The quantile is the smallest value with at least that much mass below
+/// let x: u32 = du.invcdf(0.5);
+/// assert_eq!(x, 3);
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
@@ -79,6 +97,12 @@ impl<T: DuParam> DiscreteUniform<T> {
     pub fn b(&self) -> T {
         self.b
     }
+
+    /// The number of values in the support, b - a + 1
+    #[inline]
+    fn n(&self) -> f64 {
+        self.b.to_f64().unwrap() - self.a.to_f64().unwrap() + 1.0
+    }
 }
 
 impl<T> From<&DiscreteUniform<T>> for String
@@ -106,7 +130,7 @@ where
 {
     fn ln_f(&self, x: &X) -> f64 {
         if *x >= X::from(self.a) && *x <= X::from(self.b) {
-            0.0
+            -self.n().ln()
         } else {
             f64::NEG_INFINITY
         }
@@ -145,8 +169,7 @@ where
     T: DuParam + Into<f64>,
 {
     fn entropy(&self) -> f64 {
-        let diff: f64 = (self.b - self.a).into();
-        diff.ln()
+        self.n().ln()
     }
 }
 
@@ -155,7 +178,7 @@ where
     T: DuParam + SampleUniform + Into<f64>,
 {
     fn mean(&self) -> Option<f64> {
-        let m = ((self.b + self.a).into()) / 2.0;
+        let m = (self.b.into() + self.a.into()) / 2.0;
         Some(m)
     }
 }
@@ -165,7 +188,7 @@ where
     T: DuParam + SampleUniform + Into<f64>,
 {
     fn median(&self) -> Option<f64> {
-        let m: f64 = (self.b + self.a).into() / 2.0;
+        let m = (self.b.into() + self.a.into()) / 2.0;
         Some(m)
     }
 }
@@ -175,10 +198,8 @@ where
     T: DuParam + SampleUniform + Into<f64>,
 {
     fn variance(&self) -> Option<f64> {
-        let v = (self.b - self.a + T::one()).into()
-            * (self.b - self.a + T::one()).into()
-            / 12.0;
-        Some(v)
+        let n = self.n();
+        Some(n.mul_add(n, -1.0) / 12.0)
     }
 }
 
@@ -207,9 +228,17 @@ where
     X: Integer + From<T> + FromPrimitive,
     T: DuParam + SampleUniform + ToPrimitive,
 {
+    /// The smallest x with cdf(x) >= p
     fn invcdf(&self, p: f64) -> X {
-        let diff: f64 = (self.b - self.a).to_f64().unwrap();
-        X::from_f64(p * diff).unwrap() + X::from(self.a)
+        let n = self.n();
+        // The number of values at or below x, corrected for rounding in p * n
+        let mut k = (p * n).ceil().clamp(1.0, n);
+        if k > 1.0 && (k - 1.0) / n >= p {
+            k -= 1.0;
+        } else if k < n && k / n < p {
+            k += 1.0;
+        }
+        X::from_f64(k - 1.0).unwrap() + X::from(self.a)
     }
 }
 
@@ -221,7 +250,8 @@ impl<T: DuParam> Skewness for DiscreteUniform<T> {
 
 impl<T: DuParam> Kurtosis for DiscreteUniform<T> {
     fn kurtosis(&self) -> Option<f64> {
-        Some(-1.2)
+        let n2 = self.n() * self.n();
+        Some(-6.0 * (n2 + 1.0) / (5.0 * (n2 - 1.0)))
     }
 }
 
@@ -281,19 +311,41 @@ mod tests {
     #[test]
     fn variance() {
         let v: f64 = DiscreteUniform::new(0, 10).unwrap().variance().unwrap();
-        assert::close(v, (11.0 * 11.0) / 12.0, TOL);
+        assert::close(v, 120.0 / 12.0, TOL);
     }
 
     #[test]
     fn entropy() {
         let h: f64 = DiscreteUniform::new(2, 4).unwrap().entropy();
-        assert::close(h, f64::consts::LN_2, TOL);
+        assert::close(h, 3.0_f64.ln(), TOL);
     }
 
     #[test]
     fn ln_pmf() {
         let u = DiscreteUniform::new(0, 10).unwrap();
-        assert::close(u.ln_pmf(&2_u8), 0.0, TOL);
+        assert::close(u.ln_pmf(&2_u8), -11.0_f64.ln(), TOL);
+        assert_eq!(u.ln_pmf(&11_u8), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn pmf_sums_to_one_over_full_range() {
+        // n = 256 does not fit in a u8
+        let u = DiscreteUniform::new(0_u8, 255_u8).unwrap();
+        let total: f64 = (0..=255_u8).map(|x| u.pmf(&x)).sum();
+        assert::close(total, 1.0, TOL);
+        let m: f64 = u.mean().unwrap();
+        assert::close(m, 127.5, TOL);
+    }
+
+    #[test]
+    fn moments_match_pmf() {
+        let u = DiscreteUniform::new(-3_i32, 4_i32).unwrap();
+        let xs: Vec<f64> = (-3..=4).map(f64::from).collect();
+        let central =
+            |k: i32| xs.iter().map(|x| (x - 0.5).powi(k)).sum::<f64>() / 8.0;
+        let v = u.variance().unwrap();
+        assert::close(v, central(2), TOL);
+        assert::close(u.kurtosis().unwrap(), central(4) / (v * v) - 3.0, TOL);
     }
     #[test]
     fn cdf() {
@@ -303,6 +355,20 @@ mod tests {
         assert::close(u.cdf(&10_u32), 1.0, TOL);
     }
 
+    #[test]
+    fn invcdf_is_smallest_x_with_cdf_at_least_p() {
+        let u = DiscreteUniform::new(3_u32, 9_u32).unwrap();
+        for p in [0.0, 1E-9, 0.1, 1.0 / 7.0, 0.5, 6.0 / 7.0, 0.9, 1.0] {
+            let x: u32 = u.invcdf(p);
+            assert!(u.cdf(&x) >= p, "p = {}", p);
+            if x > 3 {
+                assert!(u.cdf(&(x - 1)) < p, "p = {}", p);
+            }
+        }
+        let x: u32 = u.invcdf(1.0);
+        assert_eq!(x, 9);
+    }
+
     #[test]
     fn cdf_inv_cdf_ident() {
         let mut rng = rand::thread_rng();
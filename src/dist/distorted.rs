@@ -0,0 +1,522 @@
+//! Distortion risk measures and the distributions they price under
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::dist::risk::tail_average;
+use crate::dist::Gaussian;
+use crate::traits::*;
+use rand::Rng;
+use std::fmt;
+
+/// A distortion of tail probabilities, g: \[0, 1\] → \[0, 1\], increasing with
+/// g(0) = 0 and g(1) = 1.
+///
+/// A loss exceeds x with probability g(S(x)) under the distortion, where S
+/// is the survival function of the loss. The mean of the distorted loss is
+/// the distortion risk measure. Concave distortions, like all of these with
+/// loading parameters in the documented ranges, weight the tail up and give
+/// coherent, spectral risk measures.
+///
+/// Build with the constructors, which validate the parameters. Variants
+/// built directly are not checked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum Distortion {
+    /// Wang transform, g(s) = Φ(Φ⁻¹(s) + lambda). Shifts a Gaussian loss by
+    /// `lambda` standard deviations; concave for `lambda ≥ 0`.
+    Wang { lambda: f64 },
+    /// Proportional hazard transform, g(s) = s<sup>power</sup>. Multiplies
+    /// the hazard rate by `power`; concave for `power` in (0, 1].
+    ProportionalHazard { power: f64 },
+    /// Dual power transform, g(s) = 1 - (1 - s)<sup>power</sup>. The loss is
+    /// distributed as the largest of `power` draws when `power` is a whole
+    /// number; concave for `power ≥ 1`.
+    DualPower { power: f64 },
+    /// Expected shortfall at level `p`, g(s) = min(s / (1 - p), 1). The
+    /// distorted loss is the loss conditioned on its worst `1 - p` of
+    /// outcomes.
+    ExpectedShortfall { p: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum DistortionError {
+    /// The Wang transform shift is infinite or NaN
+    LambdaNotFinite { lambda: f64 },
+    /// The power of a power transform is less than or equal to zero
+    PowerTooLow { power: f64 },
+    /// The power of a power transform is infinite or NaN
+    PowerNotFinite { power: f64 },
+    /// The expected shortfall level is not in (0, 1)
+    POutOfRange { p: f64 },
+}
+
+/// A distribution with its tail probabilities distorted: the loss
+/// distribution a [`Distortion`] prices under.
+///
+/// Its mean is the distortion risk measure of the base distribution, and it
+/// can be used anywhere else a continuous distribution can.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{Distorted, Distortion, Exponential};
+/// use rv::traits::*;
+///
+/// let loss = Exponential::new(1.0).unwrap();
+///
+/// // The proportional hazard transform of an Exponential is Exponential
+/// // with the hazard rate scaled, so the premium is loaded by 1 / 0.8
+/// let ph = Distortion::proportional_hazard(0.8).unwrap();
+/// let priced = Distorted::new(loss.clone(), ph).unwrap();
+/// let premium: f64 = priced.mean().unwrap();
+/// assert::close(premium, 1.25, 1E-8);
+/// assert::close(loss.distortion_risk(&ph), premium, 1E-8);
+///
+/// let mut rng = rand::thread_rng();
+/// let x: f64 = priced.draw(&mut rng);
+/// assert!(x > 0.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct Distorted<Fx> {
+    base: Fx,
+    distortion: Distortion,
+}
+
+impl Distortion {
+    /// The Wang transform shifting by `lambda` standard normal quantiles
+    pub fn wang(lambda: f64) -> Result<Self, DistortionError> {
+        if lambda.is_finite() {
+            Ok(Distortion::Wang { lambda })
+        } else {
+            Err(DistortionError::LambdaNotFinite { lambda })
+        }
+    }
+
+    /// The proportional hazard transform with hazard multiplier `power`
+    pub fn proportional_hazard(power: f64) -> Result<Self, DistortionError> {
+        check_power(power)?;
+        Ok(Distortion::ProportionalHazard { power })
+    }
+
+    /// The dual power transform with exponent `power`
+    pub fn dual_power(power: f64) -> Result<Self, DistortionError> {
+        check_power(power)?;
+        Ok(Distortion::DualPower { power })
+    }
+
+    /// The expected shortfall at level `p`
+    pub fn expected_shortfall(p: f64) -> Result<Self, DistortionError> {
+        if 0.0 < p && p < 1.0 {
+            Ok(Distortion::ExpectedShortfall { p })
+        } else {
+            Err(DistortionError::POutOfRange { p })
+        }
+    }
+
+    /// Check the parameters of a distortion built directly
+    pub fn validate(&self) -> Result<(), DistortionError> {
+        match *self {
+            Self::Wang { lambda } => Self::wang(lambda).map(|_| ()),
+            Self::ProportionalHazard { power } | Self::DualPower { power } => {
+                check_power(power)
+            }
+            Self::ExpectedShortfall { p } => {
+                Self::expected_shortfall(p).map(|_| ())
+            }
+        }
+    }
+
+    /// The distorted tail probability g(s)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Distortion;
+    ///
+    /// let es = Distortion::expected_shortfall(0.9).unwrap();
+    /// assert!((es.g(0.05) - 0.5).abs() < 1E-12);
+    /// assert_eq!(es.g(0.2), 1.0);
+    /// ```
+    pub fn g(&self, s: f64) -> f64 {
+        match *self {
+            Self::Wang { lambda } => {
+                let std = Gaussian::standard();
+                let z: f64 = std.invcdf(s);
+                std.cdf(&(z + lambda))
+            }
+            Self::ProportionalHazard { power } => s.powf(power),
+            Self::DualPower { power } => -(power * (-s).ln_1p()).exp_m1(),
+            Self::ExpectedShortfall { p } => (s / (1.0 - p)).min(1.0),
+        }
+    }
+
+    /// The log of the derivative of g at `s`, the log of the factor the
+    /// density is weighted by where the survival function is `s`
+    pub fn ln_g_prime(&self, s: f64) -> f64 {
+        match *self {
+            Self::Wang { lambda } => {
+                let z: f64 = Gaussian::standard().invcdf(s);
+                (-0.5 * lambda).mul_add(lambda, -lambda * z)
+            }
+            Self::ProportionalHazard { power } => {
+                (power - 1.0).mul_add(s.ln(), power.ln())
+            }
+            Self::DualPower { power } => {
+                (power - 1.0).mul_add((-s).ln_1p(), power.ln())
+            }
+            Self::ExpectedShortfall { p } => {
+                if s < 1.0 - p {
+                    -(-p).ln_1p()
+                } else {
+                    f64::NEG_INFINITY
+                }
+            }
+        }
+    }
+
+    /// The base CDF at the point where the distorted CDF is `u`,
+    /// 1 - g⁻¹(1 - u), in a form accurate for small `u`
+    fn base_cdf_level(&self, u: f64) -> f64 {
+        match *self {
+            Self::Wang { lambda } => {
+                let std = Gaussian::standard();
+                let z: f64 = std.invcdf(u);
+                std.cdf(&(z + lambda))
+            }
+            Self::ProportionalHazard { power } => {
+                -((-u).ln_1p() / power).exp_m1()
+            }
+            Self::DualPower { power } => u.powf(power.recip()),
+            Self::ExpectedShortfall { p } => u.mul_add(1.0 - p, p),
+        }
+    }
+
+    /// The base survival function at the point where the distorted one is
+    /// `s`, g⁻¹(s)
+    fn base_sf_level(&self, s: f64) -> f64 {
+        match *self {
+            Self::Wang { lambda } => {
+                let std = Gaussian::standard();
+                let z: f64 = std.invcdf(s);
+                std.cdf(&(z - lambda))
+            }
+            Self::ProportionalHazard { power } => s.powf(power.recip()),
+            Self::DualPower { power } => -((-s).ln_1p() / power).exp_m1(),
+            Self::ExpectedShortfall { p } => s * (1.0 - p),
+        }
+    }
+}
+
+fn check_power(power: f64) -> Result<(), DistortionError> {
+    if power <= 0.0 {
+        Err(DistortionError::PowerTooLow { power })
+    } else if !power.is_finite() {
+        Err(DistortionError::PowerNotFinite { power })
+    } else {
+        Ok(())
+    }
+}
+
+/// The mean of `fx` distorted by `distortion`, from the upper and lower
+/// halves of its quantile function.
+///
+/// Base tail probabilities are kept at least `f64::EPSILON` away from 0 and
+/// 1, which can bias the mean by about 1E-7 for heavy-tailed distortions.
+pub(crate) fn distorted_mean<Fx>(fx: &Fx, distortion: &Distortion) -> f64
+where
+    Fx: InverseCdf<f64> + ?Sized,
+{
+    let upper = tail_average(
+        |s| {
+            let sf = distortion.base_sf_level(s).max(f64::EPSILON);
+            fx.invcdf(1.0 - sf)
+        },
+        0.5,
+    );
+    let lower = tail_average(
+        |u| fx.invcdf(distortion.base_cdf_level(u).max(f64::EPSILON)),
+        0.5,
+    );
+    0.5 * (upper + lower)
+}
+
+impl<Fx> Distorted<Fx> {
+    /// Distort the tail probabilities of `base` by `distortion`
+    pub fn new(
+        base: Fx,
+        distortion: Distortion,
+    ) -> Result<Self, DistortionError> {
+        distortion.validate()?;
+        Ok(Distorted { base, distortion })
+    }
+
+    /// Creates a new Distorted without checking the distortion
+    #[inline]
+    pub fn new_unchecked(base: Fx, distortion: Distortion) -> Self {
+        Distorted { base, distortion }
+    }
+
+    /// The undistorted distribution
+    #[inline]
+    pub fn base(&self) -> &Fx {
+        &self.base
+    }
+
+    /// The distortion
+    #[inline]
+    pub fn distortion(&self) -> &Distortion {
+        &self.distortion
+    }
+}
+
+impl From<&Distortion> for String {
+    fn from(distortion: &Distortion) -> String {
+        match distortion {
+            Distortion::Wang { lambda } => format!("Wang(λ: {})", lambda),
+            Distortion::ProportionalHazard { power } => {
+                format!("ProportionalHazard(power: {})", power)
+            }
+            Distortion::DualPower { power } => {
+                format!("DualPower(power: {})", power)
+            }
+            Distortion::ExpectedShortfall { p } => {
+                format!("ExpectedShortfall(p: {})", p)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Distortion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from(self))
+    }
+}
+
+impl<Fx: fmt::Display> From<&Distorted<Fx>> for String {
+    fn from(distorted: &Distorted<Fx>) -> String {
+        format!(
+            "Distorted(base: {}, distortion: {})",
+            distorted.base, distorted.distortion
+        )
+    }
+}
+
+impl<Fx: fmt::Display> fmt::Display for Distorted<Fx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from(self))
+    }
+}
+
+impl<Fx> Rv<f64> for Distorted<Fx>
+where
+    Fx: Cdf<f64> + InverseCdf<f64>,
+{
+    fn ln_f(&self, x: &f64) -> f64 {
+        self.base.ln_f(x) + self.distortion.ln_g_prime(self.base.sf(x))
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> f64 {
+        self.invcdf(rng.gen())
+    }
+}
+
+impl<Fx> Support<f64> for Distorted<Fx>
+where
+    Fx: Cdf<f64> + InverseCdf<f64>,
+{
+    fn supports(&self, x: &f64) -> bool {
+        self.base.supports(x)
+    }
+}
+
+impl<Fx> ContinuousDistr<f64> for Distorted<Fx> where
+    Fx: Cdf<f64> + InverseCdf<f64>
+{
+}
+
+impl<Fx> Cdf<f64> for Distorted<Fx>
+where
+    Fx: Cdf<f64> + InverseCdf<f64>,
+{
+    fn cdf(&self, x: &f64) -> f64 {
+        1.0 - self.sf(x)
+    }
+
+    fn sf(&self, x: &f64) -> f64 {
+        self.distortion.g(self.base.sf(x))
+    }
+}
+
+impl<Fx> InverseCdf<f64> for Distorted<Fx>
+where
+    Fx: Cdf<f64> + InverseCdf<f64>,
+{
+    fn invcdf(&self, p: f64) -> f64 {
+        self.base.invcdf(self.distortion.base_cdf_level(p))
+    }
+}
+
+impl<Fx> Mean<f64> for Distorted<Fx>
+where
+    Fx: Cdf<f64> + InverseCdf<f64>,
+{
+    /// The distortion risk measure of the base distribution
+    fn mean(&self) -> Option<f64> {
+        let mean = distorted_mean(&self.base, &self.distortion);
+        if mean.is_nan() {
+            None
+        } else {
+            Some(mean)
+        }
+    }
+}
+
+impl<Fx> RiskMeasure for Distorted<Fx> where Fx: Cdf<f64> + InverseCdf<f64> {}
+
+impl std::error::Error for DistortionError {}
+
+impl fmt::Display for DistortionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LambdaNotFinite { lambda } => {
+                write!(f, "non-finite lambda: {}", lambda)
+            }
+            Self::PowerTooLow { power } => {
+                write!(f, "power ({}) must be greater than zero", power)
+            }
+            Self::PowerNotFinite { power } => {
+                write!(f, "non-finite power: {}", power)
+            }
+            Self::POutOfRange { p } => {
+                write!(f, "p ({}) must be in (0, 1)", p)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Beta, Exponential, Gamma, Uniform};
+    use crate::test_basic_impls;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-9;
+
+    test_basic_impls!(
+        Distorted::new(Gaussian::standard(), Distortion::Wang { lambda: 0.5 })
+            .unwrap(),
+        0.3_f64
+    );
+
+    #[test]
+    fn wang_transform_shifts_gaussian() {
+        let (mu, sigma, lambda) = (1.0, 2.0, 0.7);
+        let base = Gaussian::new(mu, sigma).unwrap();
+        let wang =
+            Distorted::new(base, Distortion::wang(lambda).unwrap()).unwrap();
+        let shifted = Gaussian::new(lambda.mul_add(sigma, mu), sigma).unwrap();
+        for x in [-2.0, 0.5, 3.0, 6.0] {
+            assert::close(wang.ln_f(&x), shifted.ln_f(&x), TOL);
+            assert::close(wang.cdf(&x), shifted.cdf(&x), TOL);
+        }
+        let q: f64 = shifted.invcdf(0.3);
+        assert::close(wang.invcdf(0.3), q, TOL);
+        assert::close(wang.mean().unwrap(), lambda.mul_add(sigma, mu), 1E-8);
+    }
+
+    #[test]
+    fn proportional_hazard_scales_exponential_rate() {
+        let base = Exponential::new(2.0).unwrap();
+        let ph = Distortion::proportional_hazard(0.5).unwrap();
+        let priced = Distorted::new(base, ph).unwrap();
+        let scaled = Exponential::new(1.0).unwrap();
+        for x in [0.1, 1.0, 4.0] {
+            assert::close(priced.ln_f(&x), scaled.ln_f(&x), TOL);
+            assert::close(priced.sf(&x), scaled.sf(&x), TOL);
+        }
+        assert::close(priced.mean().unwrap(), 1.0, 1E-7);
+    }
+
+    #[test]
+    fn dual_power_is_maximum_of_draws() {
+        // The largest of 3 uniforms is Beta(3, 1)
+        let priced = Distorted::new(
+            Uniform::new(0.0, 1.0).unwrap(),
+            Distortion::dual_power(3.0).unwrap(),
+        )
+        .unwrap();
+        let max = Beta::new(3.0, 1.0).unwrap();
+        for x in [0.1, 0.5, 0.9] {
+            assert::close(priced.ln_f(&x), max.ln_f(&x), TOL);
+            assert::close(priced.cdf(&x), max.cdf(&x), TOL);
+        }
+        assert::close(priced.mean().unwrap(), 0.75, 1E-8);
+    }
+
+    #[test]
+    fn expected_shortfall_distortion_matches_risk_measure() {
+        let gamma = Gamma::new(2.0, 1.5).unwrap();
+        let es = Distortion::expected_shortfall(0.95).unwrap();
+        assert::close(
+            gamma.distortion_risk(&es),
+            gamma.expected_shortfall(0.95),
+            1E-7,
+        );
+
+        // The distorted loss is the worst 5% of outcomes
+        let tail = Distorted::new(gamma.clone(), es).unwrap();
+        let var = gamma.value_at_risk(0.95);
+        assert::close(tail.cdf(&var), 0.0, TOL);
+        assert_eq!(tail.f(&(var / 2.0)), 0.0);
+    }
+
+    #[test]
+    fn draws_match_cdf() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x92);
+        let priced = Distorted::new(
+            Gamma::new(2.0, 1.0).unwrap(),
+            Distortion::wang(0.5).unwrap(),
+        )
+        .unwrap();
+        let n = 20_000;
+        let xs: Vec<f64> = priced.sample(n, &mut rng);
+        for x in [1.0, 2.0, 4.0] {
+            let freq = xs.iter().filter(|&&s| s <= x).count() as f64 / n as f64;
+            assert::close(priced.cdf(&x), freq, 0.015);
+        }
+    }
+
+    #[test]
+    fn constructors_validate() {
+        assert_eq!(
+            Distortion::wang(f64::NAN).map_err(|e| e.to_string()),
+            Err("non-finite lambda: NaN".to_string())
+        );
+        assert_eq!(
+            Distortion::proportional_hazard(0.0),
+            Err(DistortionError::PowerTooLow { power: 0.0 })
+        );
+        assert_eq!(
+            Distortion::expected_shortfall(1.0),
+            Err(DistortionError::POutOfRange { p: 1.0 })
+        );
+        assert_eq!(
+            Distorted::new(
+                Gaussian::standard(),
+                Distortion::DualPower {
+                    power: f64::INFINITY
+                }
+            ),
+            Err(DistortionError::PowerNotFinite {
+                power: f64::INFINITY
+            })
+        );
+    }
+}
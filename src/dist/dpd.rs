@@ -0,0 +1,231 @@
+//! Truncated Dirichlet process discrete distribution
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::{CategoricalDatum, CategoricalSuffStat};
+use crate::impl_display;
+use crate::misc::{ln_pflip, vec_to_string};
+use crate::traits::*;
+use rand::Rng;
+use std::fmt;
+
+/// Discrete distribution drawn from a truncated Dirichlet process
+///
+/// A `Dpd` assigns explicit weights to the first `k` atoms, `0..k`, and puts
+/// the rest of the probability mass on a single "new atom" with index `k`.
+/// It is what a [`StickBreaking`](crate::dist::StickBreaking) process
+/// draws.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::Dpd;
+/// use rv::traits::*;
+///
+/// let dpd = Dpd::new(vec![0.5, 0.3], 0.2).unwrap();
+/// assert_eq!(dpd.k(), 2);
+///
+/// assert::close(dpd.pmf(&1_usize), 0.3, 1E-12);
+/// // The remaining mass lives on the new atom at index k
+/// assert::close(dpd.pmf(&2_usize), 0.2, 1E-12);
+/// assert!(!dpd.supports(&3_usize));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct Dpd {
+    /// Log weights of the atoms followed by the log remaining mass
+    ln_weights: Vec<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum DpdError {
+    /// One of the weights or the remaining mass is infinite or NaN
+    NonFiniteWeight { ix: usize, weight: f64 },
+    /// One of the weights or the remaining mass is less than zero
+    NegativeWeight { ix: usize, weight: f64 },
+    /// The weights and remaining mass do not sum to 1
+    WeightsDoNotSumToOne { sum: f64 },
+}
+
+impl Dpd {
+    /// Create a new `Dpd` from atom weights and the remaining mass
+    ///
+    /// # Arguments
+    /// - weights: Weights of atoms `0..k`
+    /// - rest: Mass of the new atom, `k`. `rest` and the weights must sum
+    ///   to 1.
+    pub fn new(weights: Vec<f64>, rest: f64) -> Result<Self, DpdError> {
+        let mut sum = 0.0;
+        for (ix, &weight) in weights.iter().chain(Some(&rest)).enumerate() {
+            if !weight.is_finite() {
+                return Err(DpdError::NonFiniteWeight { ix, weight });
+            } else if weight < 0.0 {
+                return Err(DpdError::NegativeWeight { ix, weight });
+            }
+            sum += weight;
+        }
+
+        if (sum - 1.0).abs() > 1E-10 {
+            Err(DpdError::WeightsDoNotSumToOne { sum })
+        } else {
+            Ok(Self::new_unchecked(weights, rest))
+        }
+    }
+
+    /// Create a new `Dpd` without checking whether the weights are valid
+    #[inline]
+    pub fn new_unchecked(weights: Vec<f64>, rest: f64) -> Self {
+        let ln_weights = weights.iter().chain(Some(&rest)).map(|w| w.ln());
+        Dpd {
+            ln_weights: ln_weights.collect(),
+        }
+    }
+
+    /// The number of explicit atoms. The new atom has index `k`.
+    #[inline]
+    pub fn k(&self) -> usize {
+        self.ln_weights.len() - 1
+    }
+
+    /// Weights of the explicit atoms
+    #[inline]
+    pub fn weights(&self) -> Vec<f64> {
+        self.ln_weights[..self.k()]
+            .iter()
+            .map(|w| w.exp())
+            .collect()
+    }
+
+    /// Mass of the new atom
+    #[inline]
+    pub fn rest(&self) -> f64 {
+        self.ln_weights[self.k()].exp()
+    }
+
+    /// Log weights of the explicit atoms followed by the log remaining mass
+    #[inline]
+    pub fn ln_weights(&self) -> &Vec<f64> {
+        &self.ln_weights
+    }
+}
+
+impl From<&Dpd> for String {
+    fn from(dpd: &Dpd) -> String {
+        let weights = vec_to_string(&dpd.weights(), 5);
+        format!("Dpd({}; {}, rest: {})", dpd.k(), weights, dpd.rest())
+    }
+}
+
+impl_display!(Dpd);
+
+impl<X: CategoricalDatum> Rv<X> for Dpd {
+    fn ln_f(&self, x: &X) -> f64 {
+        self.ln_weights[x.into_usize()]
+    }
+
+    fn draw<R: Rng>(&self, mut rng: &mut R) -> X {
+        let ix = ln_pflip(&self.ln_weights, 1, true, &mut rng)[0];
+        CategoricalDatum::from_usize(ix)
+    }
+
+    fn sample<R: Rng>(&self, n: usize, mut rng: &mut R) -> Vec<X> {
+        ln_pflip(&self.ln_weights, n, true, &mut rng)
+            .iter()
+            .map(|&ix| CategoricalDatum::from_usize(ix))
+            .collect()
+    }
+}
+
+impl<X: CategoricalDatum> Support<X> for Dpd {
+    fn supports(&self, x: &X) -> bool {
+        x.into_usize() < self.ln_weights.len()
+    }
+}
+
+impl<X: CategoricalDatum> DiscreteDistr<X> for Dpd {}
+
+impl<X: CategoricalDatum> HasSuffStat<X> for Dpd {
+    type Stat = CategoricalSuffStat;
+
+    fn empty_suffstat(&self) -> Self::Stat {
+        CategoricalSuffStat::with_capacity_growing(self.k())
+    }
+
+    fn ln_f_stat(&self, stat: &Self::Stat) -> f64 {
+        self.ln_weights
+            .iter()
+            .zip(stat.counts().iter())
+            .filter(|(_, &ct)| ct > 0.0)
+            .map(|(&w, &ct)| ct * w)
+            .sum()
+    }
+}
+
+impl std::error::Error for DpdError {}
+
+impl fmt::Display for DpdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonFiniteWeight { ix, weight } => {
+                write!(f, "non-finite weight at index {}: {}", ix, weight)
+            }
+            Self::NegativeWeight { ix, weight } => {
+                write!(f, "negative weight at index {}: {}", ix, weight)
+            }
+            Self::WeightsDoNotSumToOne { sum } => {
+                write!(f, "weights sum to {}, should sum to one", sum)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_basic_impls;
+
+    test_basic_impls!([categorical] Dpd::new(vec![0.6, 0.3], 0.1).unwrap());
+
+    #[test]
+    fn new_validates_weights() {
+        assert!(Dpd::new(vec![0.5, 0.5], 0.0).is_ok());
+        assert!(matches!(
+            Dpd::new(vec![0.5, 0.4], 0.2),
+            Err(DpdError::WeightsDoNotSumToOne { .. })
+        ));
+        assert!(matches!(
+            Dpd::new(vec![0.5, -0.1], 0.6),
+            Err(DpdError::NegativeWeight { ix: 1, .. })
+        ));
+        assert!(matches!(
+            Dpd::new(vec![0.5], f64::NAN),
+            Err(DpdError::NonFiniteWeight { ix: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn ln_f_stat_matches_ln_f() {
+        let dpd = Dpd::new(vec![0.6, 0.3], 0.1).unwrap();
+        let xs: Vec<usize> = vec![0, 0, 2, 1];
+        let mut stat = <Dpd as HasSuffStat<usize>>::empty_suffstat(&dpd);
+        stat.observe_many(&xs);
+        let expected: f64 = xs.iter().map(|x| dpd.ln_f(x)).sum();
+        assert::close(
+            <Dpd as HasSuffStat<usize>>::ln_f_stat(&dpd, &stat),
+            expected,
+            1E-12,
+        );
+    }
+
+    #[test]
+    fn draws_stay_in_support() {
+        let mut rng = rand::thread_rng();
+        let dpd = Dpd::new(vec![0.2, 0.3], 0.5).unwrap();
+        let xs: Vec<usize> = dpd.sample(100, &mut rng);
+        assert!(xs.iter().all(|x| dpd.supports(x)));
+        assert!(xs.contains(&2));
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+/// What a sampler does when no proposal is accepted within its budget in
+/// [`TryDraw::try_draw`](crate::traits::TryDraw::try_draw)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum DrawPolicy {
+    /// Keep proposing until a proposal is accepted, as `Rv::draw` does. This
+    /// never fails, but may never return.
+    Retry,
+    /// Return a draw from an approximate sampler that always succeeds
+    Fallback,
+    /// Return [`DrawError::ProposalLimitReached`]
+    Error,
+}
+
+/// Errors from [`TryDraw::try_draw`](crate::traits::TryDraw::try_draw)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum DrawError {
+    /// No proposal was accepted
+    ProposalLimitReached {
+        /// The number of proposals rejected
+        n_proposals: usize,
+    },
+}
+
+impl std::error::Error for DrawError {}
+
+impl fmt::Display for DrawError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ProposalLimitReached { n_proposals } => write!(
+                f,
+                "no proposal accepted after {} proposals",
+                n_proposals
+            ),
+        }
+    }
+}
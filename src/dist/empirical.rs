@@ -1,7 +1,7 @@
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
 
-use crate::traits::{Cdf, Mean, Rv, Variance};
+use crate::traits::{Cdf, Entropy, InverseCdf, Mean, Rv, Support, Variance};
 use rand::Rng;
 
 /// An empirical distribution derived from samples.
@@ -9,6 +9,9 @@ use rand::Rng;
 /// __WARNING__: The `ln_f` and `f` methods are poor approximations.
 /// They both are likely be have unbound errors.
 ///
+/// The CDF, quantiles, mean, and variance are those of the samples. Draws
+/// resample the original values with replacement.
+///
 /// ```rust
 /// use rv::dist::{Gaussian, Empirical};
 /// use rv::prelude::Rv;
@@ -119,6 +122,16 @@ impl Empirical {
     pub fn range(&self) -> &(f64, f64) {
         &self.range
     }
+
+    /// The sorted samples underlying this distribution
+    pub fn xs(&self) -> &[f64] {
+        &self.xs
+    }
+
+    /// The number of samples
+    pub fn n(&self) -> usize {
+        self.xs.len()
+    }
 }
 
 impl Rv<f64> for Empirical {
@@ -163,6 +176,73 @@ impl Cdf<f64> for Empirical {
     }
 }
 
+impl Support<f64> for Empirical {
+    fn supports(&self, x: &f64) -> bool {
+        self.range.0 <= *x && *x <= self.range.1
+    }
+}
+
+impl InverseCdf<f64> for Empirical {
+    /// The `p` quantile of the samples: the smallest order statistic,
+    /// x<sub>(k)</sub>, with k/n ≥ p.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Empirical;
+    /// use rv::traits::InverseCdf;
+    ///
+    /// let emp = Empirical::new(vec![4.0, 1.0, 3.0, 2.0]);
+    ///
+    /// assert_eq!(emp.invcdf(0.25), 1.0);
+    /// assert_eq!(emp.invcdf(0.5), 2.0);
+    /// assert_eq!(emp.invcdf(0.6), 3.0);
+    /// assert_eq!(emp.invcdf(1.0), 4.0);
+    /// ```
+    fn invcdf(&self, p: f64) -> f64 {
+        assert!((0.0..=1.0).contains(&p), "P out of range");
+        let n = self.xs.len();
+        let k = (p * n as f64).ceil() as usize;
+        self.xs[k.clamp(1, n) - 1]
+    }
+}
+
+impl Entropy for Empirical {
+    /// Vasicek's m-spacing estimate of the differential entropy, with
+    /// m = round(√n / 2).
+    ///
+    /// The estimate is `-inf` if more than m samples share the same value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::{Empirical, Gaussian};
+    /// use rv::traits::{Entropy, Rv};
+    ///
+    /// let mut rng = rand::thread_rng();
+    /// let g = Gaussian::standard();
+    /// let emp = Empirical::new(g.sample(10_000, &mut rng));
+    ///
+    /// assert!((emp.entropy() - g.entropy()).abs() < 0.05);
+    /// ```
+    fn entropy(&self) -> f64 {
+        let n = self.xs.len();
+        if n < 2 {
+            return f64::NEG_INFINITY;
+        }
+        let nf = n as f64;
+        let m = ((nf.sqrt() / 2.0).round() as usize).max(1);
+        let sum_ln_spacings = (0..n)
+            .map(|i| {
+                let hi = self.xs[(i + m).min(n - 1)];
+                let lo = self.xs[i.saturating_sub(m)];
+                (nf / (2 * m) as f64 * (hi - lo)).ln()
+            })
+            .sum::<f64>();
+        sum_ln_spacings / nf
+    }
+}
+
 impl Mean<f64> for Empirical {
     fn mean(&self) -> Option<f64> {
         let n = self.xs.len() as f64;
@@ -223,6 +303,43 @@ mod tests {
         assert!(max_f_err < 1E-5);
     }
 
+    #[test]
+    fn invcdf_returns_order_statistics() {
+        let emp = Empirical::new(vec![0.5, -1.0, 3.0, 2.0, 0.0]);
+        let n = emp.n() as f64;
+        for (i, &x) in emp.xs().iter().enumerate() {
+            assert_eq!(emp.invcdf((i as f64 + 0.5) / n), x);
+            assert_eq!(emp.invcdf((i as f64 + 1.0) / n), x);
+        }
+        assert_eq!(emp.invcdf(0.0), -1.0);
+        assert_eq!(emp.invcdf(1.0), 3.0);
+    }
+
+    #[test]
+    fn supports_range() {
+        let emp = Empirical::new(vec![1.0, 2.0, 3.0]);
+        assert!(emp.supports(&1.0));
+        assert!(emp.supports(&2.5));
+        assert!(emp.supports(&3.0));
+        assert!(!emp.supports(&0.9));
+        assert!(!emp.supports(&3.1));
+    }
+
+    #[test]
+    fn entropy_of_uniform_sample() {
+        use crate::dist::Uniform;
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xABCD);
+        let u = Uniform::new(0.0, 4.0).unwrap();
+        let emp = Empirical::new(u.sample(10_000, &mut rng));
+        assert::close(emp.entropy(), 4.0_f64.ln(), 0.05);
+    }
+
+    #[test]
+    fn entropy_of_single_value_is_neg_inf() {
+        let emp = Empirical::new(vec![1.0]);
+        assert_eq!(emp.entropy(), f64::NEG_INFINITY);
+    }
+
     #[test]
     fn draw_smoke() {
         let mut rng = rand::thread_rng();
@@ -2,6 +2,7 @@
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
 
+use crate::data::ExponentialSuffStat;
 use crate::impl_display;
 use crate::traits::*;
 use rand::Rng;
@@ -13,6 +14,9 @@ use std::fmt;
 /// [Exponential distribution](https://en.wikipedia.org/wiki/Exponential_distribution),
 /// Exp(λ) over x in [0, ∞).
 ///
+/// Draws use the Ziggurat sampler from `rand_distr`, which is faster than
+/// inverting the CDF (see `benches/ziggurat.rs`).
+///
 /// # Examples
 ///
 /// Compute 50% confidence interval
@@ -198,6 +202,19 @@ macro_rules! impl_traits {
                 Some((std * std) as $kind)
             }
         }
+
+        impl HasSuffStat<$kind> for Exponential {
+            type Stat = ExponentialSuffStat;
+
+            fn empty_suffstat(&self) -> Self::Stat {
+                ExponentialSuffStat::new()
+            }
+
+            fn ln_f_stat(&self, stat: &Self::Stat) -> f64 {
+                let n = stat.n() as f64;
+                self.rate.mul_add(-stat.sum_x(), n * self.rate.ln())
+            }
+        }
     };
 }
 
@@ -370,4 +387,17 @@ mod tests {
         });
         assert!(passes > 0);
     }
+
+    #[test]
+    fn ln_f_stat() {
+        let expon = Exponential::new(1.5).unwrap();
+        let xs: Vec<f64> = vec![0.2, 1.2, 4.4];
+        let mut stat = ExponentialSuffStat::new();
+        stat.observe_many(&xs);
+
+        let ln_f_sum: f64 = xs.iter().map(|x| expon.ln_f(x)).sum();
+        let ln_f_stat =
+            <Exponential as HasSuffStat<f64>>::ln_f_stat(&expon, &stat);
+        assert::close(ln_f_stat, ln_f_sum, TOL);
+    }
 }
@@ -0,0 +1,496 @@
+//! Maximum likelihood fitting and ranking of candidate families
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::dist::{
+    Beta, Cauchy, Distribution, Exponential, Gamma, Gaussian, InvGamma,
+    InvGaussian, Laplace, LogNormal, Pareto, Uniform,
+};
+use crate::misc::ks_test;
+use crate::misc::optimize::nelder_mead;
+use crate::traits::*;
+use std::fmt;
+
+/// A univariate real family that [`fit_best`] can fit by maximum likelihood
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum Family {
+    Beta,
+    Cauchy,
+    Exponential,
+    Gamma,
+    Gaussian,
+    InvGamma,
+    InvGaussian,
+    Laplace,
+    LogNormal,
+    Pareto,
+    Uniform,
+}
+
+impl Family {
+    /// Every family, for fitting against the whole catalog
+    pub const ALL: [Family; 11] = [
+        Family::Beta,
+        Family::Cauchy,
+        Family::Exponential,
+        Family::Gamma,
+        Family::Gaussian,
+        Family::InvGamma,
+        Family::InvGaussian,
+        Family::Laplace,
+        Family::LogNormal,
+        Family::Pareto,
+        Family::Uniform,
+    ];
+
+    /// The number of free parameters, which the information criteria
+    /// penalize
+    pub fn n_params(self) -> usize {
+        match self {
+            Family::Exponential => 1,
+            _ => 2,
+        }
+    }
+
+    /// Whether `x` can be observed from some member of the family
+    fn supports(self, x: f64) -> bool {
+        match self {
+            Family::Beta => 0.0 < x && x < 1.0,
+            Family::Exponential => x >= 0.0,
+            Family::Gamma
+            | Family::InvGamma
+            | Family::InvGaussian
+            | Family::LogNormal
+            | Family::Pareto => x > 0.0,
+            Family::Cauchy
+            | Family::Gaussian
+            | Family::Laplace
+            | Family::Uniform => true,
+        }
+    }
+
+    /// The maximum likelihood fit of the family to `xs` with its
+    /// diagnostics.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::{Distribution, Family};
+    ///
+    /// let xs = [1.2, 0.4, 2.2, 0.9, 1.6, 0.3];
+    /// let fit = Family::Exponential.fit(&xs).unwrap();
+    ///
+    /// // The MLE of the rate is one over the mean
+    /// match fit.distribution {
+    ///     Distribution::Exponential(expon) => {
+    ///         assert!((expon.rate() - 1.0 / 1.1).abs() < 1E-12);
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn fit(self, xs: &[f64]) -> Result<FamilyFit, FitError> {
+        validate(xs)?;
+        if let Some(ix) = xs.iter().position(|&x| !self.supports(x)) {
+            return Err(FitError::OutOfSupport {
+                family: self,
+                ix,
+                x: xs[ix],
+            });
+        }
+
+        let no_fit = FitError::NoFit { family: self };
+        let n = xs.len() as f64;
+        let (mean, var) = mean_var(xs);
+        match self {
+            Family::Beta => {
+                let init = <Beta as ProjectionFamily>::from_mean_var(mean, var)
+                    .ok_or(no_fit.clone())?;
+                let beta = mle(xs, &init.unconstrained_params(), |p| {
+                    Beta::new(p[0].exp(), p[1].exp()).ok()
+                })
+                .ok_or(no_fit)?;
+                Ok(diagnose(self, xs, beta, Distribution::Beta))
+            }
+            Family::Cauchy => {
+                let quartiles = quantiles(xs, &[0.25, 0.5, 0.75]);
+                let half_iqr = ((quartiles[2] - quartiles[0]) / 2.0)
+                    .max(var.sqrt() * 1E-3);
+                let cauchy = mle(xs, &[quartiles[1], half_iqr.ln()], |p| {
+                    Cauchy::new(p[0], p[1].exp()).ok()
+                })
+                .ok_or(no_fit)?;
+                Ok(diagnose(self, xs, cauchy, Distribution::Cauchy))
+            }
+            Family::Exponential => {
+                let expon = Exponential::new(mean.recip()).or(Err(no_fit))?;
+                Ok(diagnose(self, xs, expon, Distribution::Exponential))
+            }
+            Family::Gamma => {
+                let init =
+                    <Gamma as ProjectionFamily>::from_mean_var(mean, var)
+                        .ok_or(no_fit.clone())?;
+                let gamma = mle(xs, &init.unconstrained_params(), |p| {
+                    Gamma::new(p[0].exp(), p[1].exp()).ok()
+                })
+                .ok_or(no_fit)?;
+                Ok(diagnose(self, xs, gamma, Distribution::Gamma))
+            }
+            Family::Gaussian => {
+                let gauss = Gaussian::new(mean, var.sqrt()).or(Err(no_fit))?;
+                Ok(diagnose(self, xs, gauss, Distribution::Gaussian))
+            }
+            Family::InvGamma => {
+                // The method of moments, which needs a shape above two
+                let shape = mean * mean / var + 2.0;
+                let init = [shape.ln(), (mean * (shape - 1.0)).ln()];
+                let inv_gamma = mle(xs, &init, |p| {
+                    InvGamma::new(p[0].exp(), p[1].exp()).ok()
+                })
+                .ok_or(no_fit)?;
+                Ok(diagnose(self, xs, inv_gamma, Distribution::InvGamma))
+            }
+            Family::InvGaussian => {
+                let excess: f64 =
+                    xs.iter().map(|&x| x.recip() - mean.recip()).sum();
+                let inv_gauss =
+                    InvGaussian::new(mean, n / excess).or(Err(no_fit))?;
+                Ok(diagnose(self, xs, inv_gauss, Distribution::InvGaussian))
+            }
+            Family::Laplace => {
+                let median = quantiles(xs, &[0.5])[0];
+                let b = xs.iter().map(|&x| (x - median).abs()).sum::<f64>() / n;
+                let laplace = Laplace::new(median, b).or(Err(no_fit))?;
+                Ok(diagnose(self, xs, laplace, Distribution::Laplace))
+            }
+            Family::LogNormal => {
+                let ln_xs: Vec<f64> = xs.iter().map(|x| x.ln()).collect();
+                let (mu, sigma2) = mean_var(&ln_xs);
+                let lognormal =
+                    LogNormal::new(mu, sigma2.sqrt()).or(Err(no_fit))?;
+                Ok(diagnose(self, xs, lognormal, Distribution::LogNormal))
+            }
+            Family::Pareto => {
+                let scale = xs.iter().copied().fold(f64::INFINITY, f64::min);
+                let sum_ln: f64 = xs.iter().map(|x| (x / scale).ln()).sum();
+                let pareto = Pareto::new(n / sum_ln, scale).or(Err(no_fit))?;
+                Ok(diagnose(self, xs, pareto, Distribution::Pareto))
+            }
+            Family::Uniform => {
+                let (a, b) = xs
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(a, b), &x| {
+                        (a.min(x), b.max(x))
+                    });
+                let uniform = Uniform::new(a, b).or(Err(no_fit))?;
+                Ok(diagnose(self, xs, uniform, Distribution::Uniform))
+            }
+        }
+    }
+}
+
+/// How [`fit_best`] ranks the fitted families
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum FitCriterion {
+    /// Akaike information criterion, lower is better
+    Aic,
+    /// Bayesian information criterion, lower is better
+    Bic,
+    /// Kolmogorov-Smirnov statistic, lower is better
+    Ks,
+}
+
+/// A family fit to data by maximum likelihood, with diagnostics
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct FamilyFit {
+    /// The family that was fit
+    pub family: Family,
+    /// The maximum likelihood member of the family
+    pub distribution: Distribution,
+    /// The log likelihood of the data under `distribution`
+    pub ln_likelihood: f64,
+    /// Akaike information criterion, 2k - 2 ln L
+    pub aic: f64,
+    /// Bayesian information criterion, k ln n - 2 ln L
+    pub bic: f64,
+    /// Kolmogorov-Smirnov statistic of the data against `distribution`
+    pub ks_stat: f64,
+    /// Kolmogorov-Smirnov p-value. The parameters were fit to the same
+    /// data, so this is too large and is best used to rule families out.
+    pub ks_p_value: f64,
+}
+
+impl FamilyFit {
+    /// The score of the fit by `criterion`, where lower is better
+    pub fn score(&self, criterion: FitCriterion) -> f64 {
+        match criterion {
+            FitCriterion::Aic => self.aic,
+            FitCriterion::Bic => self.bic,
+            FitCriterion::Ks => self.ks_stat,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum FitError {
+    /// There is no data to fit
+    NoData,
+    /// A datum is infinite or NaN
+    DatumNotFinite { ix: usize },
+    /// A datum cannot be observed from any member of the family
+    OutOfSupport { family: Family, ix: usize, x: f64 },
+    /// The family has no valid maximum likelihood fit to the data, e.g.,
+    /// because every datum is the same
+    NoFit { family: Family },
+    /// None of the candidate families could be fit to the data
+    NoFamilyFits,
+}
+
+/// Fit every family in `catalog` to `xs` by maximum likelihood and rank the
+/// fits by `criterion`, best first.
+///
+/// Families that cannot be fit, e.g., because the data are outside their
+/// support, are left out; call [`Family::fit`] to see why.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{fit_best, Family, FitCriterion, Gamma};
+/// use rv::traits::*;
+///
+/// let mut rng = rand::thread_rng();
+/// let xs: Vec<f64> = Gamma::new(3.0, 2.0).unwrap().sample(500, &mut rng);
+///
+/// let catalog = [Family::Gaussian, Family::Gamma, Family::Uniform];
+/// let fits = fit_best(&xs, &catalog, FitCriterion::Aic).unwrap();
+/// assert_eq!(fits.len(), 3);
+/// assert_eq!(fits[0].family, Family::Gamma);
+/// assert!(fits[0].aic <= fits[1].aic);
+/// ```
+pub fn fit_best(
+    xs: &[f64],
+    catalog: &[Family],
+    criterion: FitCriterion,
+) -> Result<Vec<FamilyFit>, FitError> {
+    validate(xs)?;
+    let mut fits: Vec<FamilyFit> = catalog
+        .iter()
+        .filter_map(|family| family.fit(xs).ok())
+        .collect();
+    if fits.is_empty() {
+        return Err(FitError::NoFamilyFits);
+    }
+    fits.sort_by(|a, b| a.score(criterion).total_cmp(&b.score(criterion)));
+    Ok(fits)
+}
+
+fn validate(xs: &[f64]) -> Result<(), FitError> {
+    if xs.is_empty() {
+        Err(FitError::NoData)
+    } else if let Some(ix) = xs.iter().position(|x| !x.is_finite()) {
+        Err(FitError::DatumNotFinite { ix })
+    } else {
+        Ok(())
+    }
+}
+
+/// The mean and maximum likelihood variance
+fn mean_var(xs: &[f64]) -> (f64, f64) {
+    let n = xs.len() as f64;
+    let mean = xs.iter().sum::<f64>() / n;
+    let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+    (mean, var)
+}
+
+/// Empirical quantiles by linear interpolation of the order statistics
+fn quantiles(xs: &[f64], ps: &[f64]) -> Vec<f64> {
+    let mut sorted = xs.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let last = (sorted.len() - 1) as f64;
+    ps.iter()
+        .map(|p| {
+            let h = p * last;
+            let (lo, hi) = (h.floor() as usize, h.ceil() as usize);
+            (h - h.floor()).mul_add(sorted[hi] - sorted[lo], sorted[lo])
+        })
+        .collect()
+}
+
+/// Maximize the likelihood over unconstrained parameters, starting from
+/// `init`, with `build` returning `None` for invalid parameters
+fn mle<D, B>(xs: &[f64], init: &[f64], build: B) -> Option<D>
+where
+    D: ContinuousDistr<f64>,
+    B: Fn(&[f64]) -> Option<D>,
+{
+    let neg_ln_lik = |params: &[f64]| {
+        build(params).map_or(f64::INFINITY, |fx| {
+            -xs.iter().map(|x| fx.ln_pdf(x)).sum::<f64>()
+        })
+    };
+
+    // Restart from the best point found so that a collapsed simplex does not
+    // end the search early.
+    let mut best = nelder_mead(neg_ln_lik, init, 0.5, 1E-12, 2_000);
+    for _ in 0..3 {
+        let next = nelder_mead(neg_ln_lik, &best.0, 0.1, 1E-12, 2_000);
+        if next.1 >= best.1 {
+            break;
+        }
+        best = next;
+    }
+    if best.1.is_finite() {
+        build(&best.0)
+    } else {
+        None
+    }
+}
+
+fn diagnose<D, W>(family: Family, xs: &[f64], fx: D, wrap: W) -> FamilyFit
+where
+    D: ContinuousDistr<f64> + Cdf<f64>,
+    W: Fn(D) -> Distribution,
+{
+    let n = xs.len() as f64;
+    let k = family.n_params() as f64;
+    let ln_likelihood: f64 = xs.iter().map(|x| fx.ln_pdf(x)).sum();
+    let (ks_stat, ks_p_value) = ks_test(xs, |x| fx.cdf(&x));
+    FamilyFit {
+        family,
+        distribution: wrap(fx),
+        ln_likelihood,
+        aic: 2.0_f64.mul_add(k, -2.0 * ln_likelihood),
+        bic: k.mul_add(n.ln(), -2.0 * ln_likelihood),
+        ks_stat,
+        ks_p_value,
+    }
+}
+
+impl std::error::Error for FitError {}
+
+impl fmt::Display for FitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoData => write!(f, "there is no data to fit"),
+            Self::DatumNotFinite { ix } => {
+                write!(f, "the datum at index {} is not finite", ix)
+            }
+            Self::OutOfSupport { family, ix, x } => write!(
+                f,
+                "the datum {} at index {} is outside the support of {:?}",
+                x, ix, family
+            ),
+            Self::NoFit { family } => {
+                write!(
+                    f,
+                    "{:?} has no maximum likelihood fit to the data",
+                    family
+                )
+            }
+            Self::NoFamilyFits => {
+                write!(f, "none of the candidate families fit the data")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    #[test]
+    fn numeric_mle_matches_gradient_conditions() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x95);
+        let xs: Vec<f64> = Gamma::new(2.5, 0.5).unwrap().sample(400, &mut rng);
+        let fit = Family::Gamma.fit(&xs).unwrap();
+        let gamma = match fit.distribution {
+            Distribution::Gamma(gamma) => gamma,
+            _ => panic!("expected a Gamma"),
+        };
+
+        // The score equations: shape / rate = mean and
+        // ψ(shape) - ln(rate) = mean of ln x
+        let (mean, _) = mean_var(&xs);
+        let ln_mean = xs.iter().map(|x| x.ln()).sum::<f64>() / xs.len() as f64;
+        assert::close(gamma.shape() / gamma.rate(), mean, 1E-5);
+        assert::close(
+            special::Gamma::digamma(gamma.shape()) - gamma.rate().ln(),
+            ln_mean,
+            1E-5,
+        );
+    }
+
+    #[test]
+    fn ranks_generating_family_first() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x96);
+        let xs: Vec<f64> =
+            LogNormal::new(0.0, 1.5).unwrap().sample(2_000, &mut rng);
+        let fits = fit_best(&xs, &Family::ALL, FitCriterion::Bic).unwrap();
+
+        // Beta cannot fit data above one, so it is left out
+        assert_eq!(fits.len(), Family::ALL.len() - 1);
+        assert!(fits.iter().all(|fit| fit.family != Family::Beta));
+        assert_eq!(fits[0].family, Family::LogNormal);
+        assert!(fits[0].ks_p_value > 0.01);
+        assert!(fits.windows(2).all(|w| w[0].bic <= w[1].bic));
+
+        let by_ks = fit_best(&xs, &Family::ALL, FitCriterion::Ks).unwrap();
+        assert!(by_ks.windows(2).all(|w| w[0].ks_stat <= w[1].ks_stat));
+    }
+
+    #[test]
+    fn information_criteria() {
+        let xs = [0.5, 1.5, 2.0, 3.5, 2.5];
+        let fit = Family::Gaussian.fit(&xs).unwrap();
+        let k = 2.0;
+        assert::close(
+            fit.aic,
+            2.0_f64.mul_add(k, -2.0 * fit.ln_likelihood),
+            1E-12,
+        );
+        assert::close(
+            fit.bic,
+            k.mul_add(5.0_f64.ln(), -2.0 * fit.ln_likelihood),
+            1E-12,
+        );
+    }
+
+    #[test]
+    fn fit_errors() {
+        assert_eq!(
+            fit_best(&[], &Family::ALL, FitCriterion::Aic),
+            Err(FitError::NoData)
+        );
+        assert_eq!(
+            fit_best(&[1.0, f64::NAN], &Family::ALL, FitCriterion::Aic),
+            Err(FitError::DatumNotFinite { ix: 1 })
+        );
+        assert_eq!(
+            Family::Gamma.fit(&[1.0, -2.0]),
+            Err(FitError::OutOfSupport {
+                family: Family::Gamma,
+                ix: 1,
+                x: -2.0
+            })
+        );
+        assert_eq!(
+            Family::Gaussian.fit(&[2.0, 2.0]),
+            Err(FitError::NoFit {
+                family: Family::Gaussian
+            })
+        );
+        assert_eq!(
+            fit_best(&[-1.0, 2.0], &[Family::Gamma], FitCriterion::Aic),
+            Err(FitError::NoFamilyFits)
+        );
+    }
+}
@@ -2,6 +2,9 @@
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
 
+use crate::data::GammaSuffStat;
+use crate::dist::quantile_match::{self, QuantileMatchError};
+use crate::dist::Gaussian;
 use crate::impl_display;
 use crate::traits::*;
 use rand::Rng;
@@ -9,6 +12,7 @@ use special::Gamma as _;
 use std::fmt;
 use std::sync::OnceLock;
 
+mod exponential_prior;
 mod poisson_prior;
 
 /// [Gamma distribution](https://en.wikipedia.org/wiki/Gamma_distribution) G(α, β)
@@ -22,6 +26,9 @@ mod poisson_prior;
 /// f(x|α, β) = ----  x^(α-1) e^(-βx)
 ///             Γ(α)
 /// ```
+///
+/// Quantiles from [`InverseCdf`] start from the Wilson–Hilferty approximation
+/// and are refined with Halley steps to a relative error of about 1E-12.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
@@ -34,6 +41,9 @@ pub struct Gamma {
     // ln(rate)
     #[cfg_attr(feature = "serde1", serde(skip))]
     ln_rate: OnceLock<f64>,
+    // shape * ln(rate) - ln(gamma(shape))
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    ln_z: OnceLock<f64>,
 }
 
 impl PartialEq for Gamma {
@@ -80,9 +90,54 @@ impl Gamma {
             rate,
             ln_gamma_shape: OnceLock::new(),
             ln_rate: OnceLock::new(),
+            ln_z: OnceLock::new(),
         }
     }
 
+    /// Create the Gamma whose quantiles best match `quantiles`, given as
+    /// `(p, x)` pairs where `x` is the `p`-quantile.
+    ///
+    /// The parameters are found numerically by minimizing the squared error
+    /// between the requested and fitted quantiles on the standard normal
+    /// scale.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rv::dist::Gamma;
+    /// use rv::traits::Cdf;
+    ///
+    /// let gam = Gamma::from_quantiles(&[(0.1, 0.5), (0.9, 4.0)]).unwrap();
+    ///
+    /// assert!((gam.cdf(&0.5_f64) - 0.1).abs() < 1E-6);
+    /// assert!((gam.cdf(&4.0_f64) - 0.9).abs() < 1E-6);
+    /// ```
+    pub fn from_quantiles(
+        quantiles: &[(f64, f64)],
+    ) -> Result<Self, QuantileMatchError> {
+        let quantiles = quantile_match::validate(quantiles, 2, |x| x > 0.0)?;
+
+        // Start from the moments of the matching Gaussian if it sits on the
+        // positive reals, otherwise from the exponential with the middle
+        // quantile as its median.
+        let init = match Gaussian::from_quantiles(&quantiles) {
+            Ok(g) if g.mu() > 0.0 => {
+                let shape = (g.mu() / g.sigma()).powi(2);
+                [shape.ln(), (g.mu() / (g.sigma() * g.sigma())).ln()]
+            }
+            _ => {
+                let median = quantiles[quantiles.len() / 2].1;
+                [0.0, (std::f64::consts::LN_2 / median).ln()]
+            }
+        };
+
+        let params = quantile_match::fit(&quantiles, &init, |params, x| {
+            (params[1].exp() * x).inc_gamma(params[0].exp())
+        });
+        Self::new(params[0].exp(), params[1].exp())
+            .map_err(|_| QuantileMatchError::NoSolution)
+    }
+
     /// Get ln(rate)
     #[inline]
     fn ln_rate(&self) -> f64 {
@@ -95,6 +150,14 @@ impl Gamma {
         *self.ln_gamma_shape.get_or_init(|| self.shape.ln_gamma().0)
     }
 
+    /// Get the log normalizing constant, shape * ln(rate) - ln(gamma(shape))
+    #[inline]
+    fn ln_z(&self) -> f64 {
+        *self.ln_z.get_or_init(|| {
+            self.shape.mul_add(self.ln_rate(), -self.ln_gamma_shape())
+        })
+    }
+
     /// Get the shape parameter
     ///
     /// # Example
@@ -151,6 +214,7 @@ impl Gamma {
     pub fn set_shape_unchecked(&mut self, shape: f64) {
         self.shape = shape;
         self.ln_gamma_shape = OnceLock::new();
+        self.ln_z = OnceLock::new();
     }
 
     /// Get the rate parameter
@@ -209,6 +273,7 @@ impl Gamma {
     pub fn set_rate_unchecked(&mut self, rate: f64) {
         self.rate = rate;
         self.ln_rate = OnceLock::new();
+        self.ln_z = OnceLock::new();
     }
 }
 
@@ -226,17 +291,81 @@ impl From<&Gamma> for String {
 
 impl_display!(Gamma);
 
+/// Maximum number of Halley steps used to compute the inverse CDF
+const INVCDF_MAX_ITER: usize = 32;
+
+/// Quantile of Gamma(shape, 1) at `p`.
+///
+/// The initial guess is the Wilson–Hilferty approximation for shape ≥ 1 and
+/// the small-x expansion of the incomplete gamma function otherwise. Halley
+/// steps on the CDF then bring the relative error to about 1E-12.
+fn invcdf_std(p: f64, shape: f64, ln_gamma_shape: f64) -> f64 {
+    assert!((0.0..=1.0).contains(&p), "P out of range");
+    if p == 0.0 {
+        return 0.0;
+    } else if p == 1.0 {
+        return f64::INFINITY;
+    }
+
+    let mut x = if shape >= 1.0 {
+        let z: f64 = Gaussian::standard().invcdf(p);
+        let t = 1.0 / (9.0 * shape);
+        let wh = shape * (z.mul_add(t.sqrt(), 1.0) - t).powi(3);
+        wh.max(1E-3)
+    } else {
+        let t = shape.mul_add(-0.12_f64.mul_add(shape, 0.253), 1.0);
+        if p < t {
+            (p / t).powf(shape.recip())
+        } else {
+            1.0 - (-(p - t) / (1.0 - t)).ln_1p()
+        }
+    };
+
+    let shape_m1 = shape - 1.0;
+    for _ in 0..INVCDF_MAX_ITER {
+        let ln_f = shape_m1.mul_add(x.ln(), -x) - ln_gamma_shape;
+        let err = x.inc_gamma(shape) - p;
+        let t = err / ln_f.exp();
+        if !t.is_finite() {
+            break;
+        }
+        let step =
+            t / (-0.5_f64).mul_add((t * (shape_m1 / x - 1.0)).min(1.0), 1.0);
+        let x_new = if x - step <= 0.0 { x / 2.0 } else { x - step };
+        let converged = (x_new - x).abs() <= 1E-14 * x;
+        x = x_new;
+        if converged {
+            break;
+        }
+    }
+    x
+}
+
 macro_rules! impl_traits {
     ($kind:ty) => {
         impl Rv<$kind> for Gamma {
             fn ln_f(&self, x: &$kind) -> f64 {
-                self.shape.mul_add(self.ln_rate(), -self.ln_gamma_shape())
+                self.ln_z()
                     + (self.shape - 1.0).mul_add(
                         f64::from(*x).ln(),
                         -(self.rate * f64::from(*x)),
                     )
             }
 
+            fn ln_f_many(&self, xs: &[$kind], out: &mut [f64]) {
+                assert_eq!(
+                    xs.len(),
+                    out.len(),
+                    "xs and out must be the same length"
+                );
+                let ln_z = self.ln_z();
+                let shape_m1 = self.shape - 1.0;
+                xs.iter().zip(out.iter_mut()).for_each(|(&x, y)| {
+                    let xf = f64::from(x);
+                    *y = ln_z + shape_m1.mul_add(xf.ln(), -(self.rate * xf));
+                });
+            }
+
             fn draw<R: Rng>(&self, rng: &mut R) -> $kind {
                 let g = rand_distr::Gamma::new(self.shape, 1.0 / self.rate)
                     .unwrap();
@@ -258,12 +387,36 @@ macro_rules! impl_traits {
             }
         }
 
+        impl HasSuffStat<$kind> for Gamma {
+            type Stat = GammaSuffStat;
+
+            fn empty_suffstat(&self) -> Self::Stat {
+                GammaSuffStat::new()
+            }
+
+            fn ln_f_stat(&self, stat: &Self::Stat) -> f64 {
+                let n = stat.n() as f64;
+                let ln_z = self.ln_z();
+                (self.shape - 1.0).mul_add(
+                    stat.sum_ln_x(),
+                    n.mul_add(ln_z, -self.rate * stat.sum_x()),
+                )
+            }
+        }
+
         impl Cdf<$kind> for Gamma {
             fn cdf(&self, x: &$kind) -> f64 {
                 (self.rate * f64::from(*x)).inc_gamma(self.shape)
             }
         }
 
+        impl InverseCdf<$kind> for Gamma {
+            fn invcdf(&self, p: f64) -> $kind {
+                let x = invcdf_std(p, self.shape, self.ln_gamma_shape());
+                (x / self.rate) as $kind
+            }
+        }
+
         impl Mean<$kind> for Gamma {
             fn mean(&self) -> Option<$kind> {
                 Some((self.shape / self.rate) as $kind)
@@ -312,6 +465,26 @@ impl Kurtosis for Gamma {
 impl_traits!(f32);
 impl_traits!(f64);
 
+impl QuadBounds for Gamma {
+    fn quad_bounds(&self) -> (f64, f64) {
+        self.interval(0.999_999_999_999)
+    }
+}
+
+impl CacheControl for Gamma {
+    fn precompute(&self) {
+        self.ln_rate();
+        self.ln_gamma_shape();
+        self.ln_z();
+    }
+
+    fn invalidate(&mut self) {
+        self.ln_gamma_shape = OnceLock::new();
+        self.ln_rate = OnceLock::new();
+        self.ln_z = OnceLock::new();
+    }
+}
+
 impl std::error::Error for GammaError {}
 
 impl fmt::Display for GammaError {
@@ -377,6 +550,57 @@ mod tests {
         assert::close(gam.cdf(&100.0_f64), 1.0, TOL);
     }
 
+    #[test]
+    fn invcdf_reference_values() {
+        // Computed with mpmath at 50 digits
+        let refs = [
+            (0.1, 1.0, 1E-6, 6.073_048_362_407_926E-61),
+            (0.1, 1.0, 0.5, 5.933_911_044_602_262E-4),
+            (0.1, 1.0, 0.99, 1.588_477_817_929_504_6),
+            (0.5, 2.0, 1E-6, 3.926_990_816_989_297E-13),
+            (0.5, 2.0, 0.01, 3.927_196_447_742_549_6E-5),
+            (0.5, 2.0, 0.99, 1.658_724_150_255_303_4),
+            (3.5, 0.5, 1E-6, 0.078_523_600_990_369_92),
+            (3.5, 0.5, 0.5, 6.345_811_195_521_517_5),
+            (3.5, 0.5, 0.99, 18.475_306_906_582_36),
+            (100.0, 1.0, 1E-6, 59.436_320_698_122_89),
+            (100.0, 1.0, 0.01, 78.215_983_053_795_83),
+            (100.0, 1.0, 0.99, 124.722_561_490_720_8),
+        ];
+        for (shape, rate, p, x_ref) in refs {
+            let x: f64 = Gamma::new(shape, rate).unwrap().invcdf(p);
+            assert::close(x / x_ref, 1.0, 1E-12);
+        }
+    }
+
+    #[test]
+    fn invcdf_inverts_cdf() {
+        let gam = Gamma::new(1.2, 3.4).unwrap();
+        for p in [0.001, 0.1, 0.3, 0.5, 0.7, 0.9, 0.999] {
+            let x: f64 = gam.invcdf(p);
+            assert::close(gam.cdf(&x), p, TOL);
+        }
+        // Gamma(1, rate) is Exponential(rate)
+        let x: f64 = Gamma::new(1.0, 2.0).unwrap().invcdf(0.75);
+        assert::close(x, -(0.25_f64.ln()) / 2.0, TOL);
+        assert_eq!(InverseCdf::<f64>::invcdf(&gam, 0.0), 0.0);
+        assert_eq!(InverseCdf::<f64>::invcdf(&gam, 1.0), f64::INFINITY);
+    }
+
+    #[test]
+    fn hdi_has_equal_density_ends() {
+        let gam = Gamma::new(3.0, 2.0).unwrap();
+        let (a, b) = gam.hdi(0.95);
+        assert::close(gam.cdf(&b) - gam.cdf(&a), 0.95, 1E-10);
+        assert::close(gam.pdf(&a), gam.pdf(&b), 1E-6);
+
+        // The density of Gamma(1, rate) decreases, so the HDI starts at zero
+        let expon = Gamma::new(1.0, 2.0).unwrap();
+        let (a, b) = expon.hdi(0.9);
+        assert!(a < 1E-6);
+        assert::close(b, -(0.1_f64.ln()) / 2.0, 1E-6);
+    }
+
     #[test]
     fn ln_pdf_hight_value() {
         let gam = Gamma::new(1.2, 3.4).unwrap();
@@ -500,4 +724,61 @@ mod tests {
 
         assert!(passes > 0);
     }
+
+    #[test]
+    fn from_quantiles_recovers_params() {
+        let gam = Gamma::new(3.5, 0.8).unwrap();
+        let qs: Vec<(f64, f64)> =
+            [1.5, 4.0, 8.0].iter().map(|&x| (gam.cdf(&x), x)).collect();
+        let fit = Gamma::from_quantiles(&qs).unwrap();
+        assert::close(fit.shape(), 3.5, 1E-4);
+        assert::close(fit.rate(), 0.8, 1E-4);
+    }
+
+    #[test]
+    fn from_quantiles_skewed() {
+        let fit = Gamma::from_quantiles(&[(0.5, 0.1), (0.99, 10.0)]).unwrap();
+        assert::close(fit.cdf(&0.1_f64), 0.5, 1E-6);
+        assert::close(fit.cdf(&10.0_f64), 0.99, 1E-6);
+    }
+
+    #[test]
+    fn from_quantiles_rejects_nonpositive_values() {
+        assert_eq!(
+            Gamma::from_quantiles(&[(0.1, -1.0), (0.9, 1.0)]),
+            Err(QuantileMatchError::ValueOutOfSupport { x: -1.0 })
+        );
+    }
+
+    #[test]
+    fn ln_f_stat() {
+        let gam = Gamma::new(2.5, 1.5).unwrap();
+        let xs: Vec<f64> = vec![0.3, 1.2, 4.0, 2.2];
+        let mut stat = GammaSuffStat::new();
+        stat.observe_many(&xs);
+
+        let ln_f_sum: f64 = xs.iter().map(|x| gam.ln_f(x)).sum();
+        let ln_f_stat = <Gamma as HasSuffStat<f64>>::ln_f_stat(&gam, &stat);
+        assert::close(ln_f_stat, ln_f_sum, 1E-12);
+    }
+
+    #[test]
+    fn ln_f_after_setters_works() {
+        let mut gam = Gamma::new(1.0, 1.0).unwrap();
+        assert::close(gam.ln_pdf(&0.1_f64), -0.1, TOL);
+
+        gam.set_shape(1.2).unwrap();
+        gam.set_rate(3.4).unwrap();
+        assert::close(gam.ln_pdf(&0.1_f64), 0.753_387_589_351_045_6, TOL);
+    }
+
+    #[test]
+    fn precompute_and_invalidate() {
+        let mut gam = Gamma::new(2.0, 3.0).unwrap();
+        assert!(gam.ln_z.get().is_none());
+        gam.precompute();
+        assert::close(*gam.ln_z.get().unwrap(), 2.0 * 3.0_f64.ln(), TOL);
+        gam.invalidate();
+        assert!(gam.ln_z.get().is_none());
+    }
 }
@@ -0,0 +1,144 @@
+use rand::Rng;
+
+use crate::data::{extract_stat, DataOrSuffStat, ExponentialSuffStat};
+use crate::dist::{Exponential, ExponentialError, Gamma, Lomax};
+use crate::traits::*;
+
+impl Rv<Exponential> for Gamma {
+    fn ln_f(&self, x: &Exponential) -> f64 {
+        self.ln_f(&x.rate())
+    }
+
+    fn draw<R: Rng>(&self, mut rng: &mut R) -> Exponential {
+        let rate: f64 = self.draw(&mut rng);
+        match Exponential::new(rate) {
+            Ok(expon) => expon,
+            Err(ExponentialError::RateTooLow { .. }) => {
+                Exponential::new_unchecked(f64::EPSILON)
+            }
+            Err(err) => panic!("Failed to draw Exponential: {}", err),
+        }
+    }
+}
+
+impl Support<Exponential> for Gamma {
+    fn supports(&self, x: &Exponential) -> bool {
+        x.rate() > 0.0 && x.rate().is_finite()
+    }
+}
+
+impl ContinuousDistr<Exponential> for Gamma {}
+
+macro_rules! impl_traits {
+    ($kind: ty) => {
+        impl ConjugatePrior<$kind, Exponential> for Gamma {
+            type Posterior = Self;
+            type LnMCache = f64;
+            // The posterior predictive is a Lomax
+            type LnPpCache = Lomax;
+
+            fn posterior(
+                &self,
+                x: &DataOrSuffStat<$kind, Exponential>,
+            ) -> Self {
+                let stat = extract_stat(x, ExponentialSuffStat::new);
+                let a = self.shape() + stat.n() as f64;
+                let b = self.rate() + stat.sum_x();
+                Self::new(a, b).expect("Invalid posterior parameters")
+            }
+
+            #[inline]
+            fn ln_m_cache(&self) -> Self::LnMCache {
+                self.shape().mul_add(-self.ln_rate(), self.ln_gamma_shape())
+            }
+
+            fn ln_m_with_cache(
+                &self,
+                cache: &Self::LnMCache,
+                x: &DataOrSuffStat<$kind, Exponential>,
+            ) -> f64 {
+                let post = self.posterior(x);
+                let zn = post
+                    .shape()
+                    .mul_add(-post.ln_rate(), post.ln_gamma_shape());
+                zn - cache
+            }
+
+            #[inline]
+            fn ln_pp_cache(
+                &self,
+                x: &DataOrSuffStat<$kind, Exponential>,
+            ) -> Self::LnPpCache {
+                let post = self.posterior(x);
+                Lomax::new_unchecked(post.shape(), post.rate())
+            }
+
+            fn ln_pp_with_cache(
+                &self,
+                cache: &Self::LnPpCache,
+                y: &$kind,
+            ) -> f64 {
+                cache.ln_f(&f64::from(*y))
+            }
+        }
+    };
+}
+
+impl_traits!(f32);
+impl_traits!(f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const TOL: f64 = 1E-12;
+
+    #[test]
+    fn posterior_from_data() {
+        let data: Vec<f64> = vec![0.5, 1.0, 1.5, 2.0];
+        let xs = DataOrSuffStat::Data::<f64, Exponential>(&data);
+        let posterior = Gamma::new(2.0, 1.0).unwrap().posterior(&xs);
+
+        assert::close(posterior.shape(), 6.0, TOL);
+        assert::close(posterior.rate(), 6.0, TOL);
+    }
+
+    #[test]
+    fn ln_m_no_data() {
+        let dist = Gamma::new(2.0, 3.0).unwrap();
+        let data: DataOrSuffStat<f64, Exponential> = DataOrSuffStat::None;
+        assert::close(dist.ln_m(&data), 0.0, TOL);
+    }
+
+    #[test]
+    fn ln_m_single_datum_is_lomax() {
+        // f(x) = a b^a / (b + x)^(a + 1)
+        let dist = Gamma::new(2.5, 1.5).unwrap();
+        let x: f64 = 0.8;
+        let expected = 3.5_f64.mul_add(
+            -2.3_f64.ln(),
+            2.5_f64.mul_add(1.5_f64.ln(), 2.5_f64.ln()),
+        );
+        let data = vec![x];
+        let ln_m = dist.ln_m(&DataOrSuffStat::<f64, Exponential>::Data(&data));
+        assert::close(ln_m, expected, TOL);
+        assert::close(
+            dist.ln_pp(&x, &DataOrSuffStat::<f64, Exponential>::None),
+            expected,
+            TOL,
+        );
+    }
+
+    #[test]
+    fn ln_m_is_sum_of_sequential_ln_pp() {
+        let dist = Gamma::new(1.2, 0.7).unwrap();
+        let data: Vec<f32> = vec![0.3, 2.2, 0.1, 1.4, 5.0];
+        let mut stat = ExponentialSuffStat::new();
+        let mut total = 0.0;
+        for x in data.iter() {
+            total += dist.ln_pp(x, &DataOrSuffStat::SuffStat(&stat));
+            stat.observe(x);
+        }
+        let ln_m = dist.ln_m(&DataOrSuffStat::<f32, Exponential>::Data(&data));
+        assert::close(ln_m, total, 1E-10);
+    }
+}
@@ -181,6 +181,57 @@ mod tests {
             });
     }
 
+    #[test]
+    fn ln_m_stream_matches_ln_m_of_prefixes() {
+        let dist = Gamma::new(2.0, 1.5).unwrap();
+        let fx = Poisson::new(1.0).unwrap();
+        let data: Vec<u16> = vec![0, 3, 1, 4, 2, 2, 7];
+        let batches: Vec<&[u16]> = vec![&data[..2], &data[2..2], &data[2..]];
+        let partials = dist.ln_m_stream(&fx, batches);
+
+        assert_eq!(partials.len(), 3);
+        for (partial, end) in partials.iter().zip([2, 2, 7]) {
+            let prefix = DataOrSuffStat::<u16, Poisson>::Data(&data[..end]);
+            assert::close(*partial, dist.ln_m(&prefix), TOL);
+        }
+    }
+
+    #[test]
+    fn ln_pp_many_matches_ln_pp() {
+        let dist = Gamma::new(2.0, 1.5).unwrap();
+        let xs: Vec<u16> = vec![0, 3, 1];
+        let data = DataOrSuffStat::<u16, Poisson>::Data(&xs);
+        let ys: Vec<u16> = vec![4, 0, 2];
+        let ln_pps = dist.ln_pp_many(&ys, &data);
+        for (ln_pp, y) in ln_pps.iter().zip(ys.iter()) {
+            assert::close(*ln_pp, dist.ln_pp(y, &data), TOL);
+        }
+    }
+
+    #[test]
+    fn ln_pp_joint_is_ratio_of_marginals() {
+        let dist = Gamma::new(2.0, 1.5).unwrap();
+        let fx = Poisson::new(1.0).unwrap();
+        let xys: Vec<u16> = vec![0, 3, 1, 4, 0, 2];
+        let (xs, ys) = xys.split_at(3);
+        let data = DataOrSuffStat::<u16, Poisson>::Data(xs);
+        let ln_m_x = dist.ln_m(&data);
+        let ln_m_xy = dist.ln_m(&DataOrSuffStat::<u16, Poisson>::Data(&xys));
+        assert::close(dist.ln_pp_joint(&fx, ys, &data), ln_m_xy - ln_m_x, TOL);
+
+        // A single y is predicted as by ln_pp
+        let mut stat = PoissonSuffStat::new();
+        stat.observe_many(xs);
+        let ln_pp = dist.ln_pp(&ys[0], &DataOrSuffStat::SuffStat(&stat));
+        let ln_joint =
+            dist.ln_pp_joint(&fx, &ys[..1], &DataOrSuffStat::SuffStat(&stat));
+        assert::close(ln_joint, ln_pp, TOL);
+
+        // No ys have probability one
+        let none = DataOrSuffStat::<u16, Poisson>::None;
+        assert_eq!(dist.ln_pp_joint(&fx, &[], &none), 0.0);
+    }
+
     #[test]
     fn ln_pp_no_data() {
         let dist = Gamma::new(1.0, 1.0).unwrap();
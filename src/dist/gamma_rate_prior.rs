@@ -0,0 +1,283 @@
+//! Gamma prior on the rate of a Gamma with known shape
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::{extract_stat, DataOrSuffStat, GammaSuffStat};
+use crate::dist::Gamma;
+use crate::impl_display;
+use crate::traits::*;
+use rand::Rng;
+use special::Gamma as _;
+use std::fmt;
+
+/// Conjugate prior on the rate, β, of a [`Gamma`] likelihood with known
+/// shape, α, where β ~ Gamma(a, b).
+///
+/// The likelihood depends on the rate only through n and Σx, so the
+/// posterior is Gamma(a + nα, b + Σx).
+///
+/// # Example
+///
+/// ```
+/// use rv::data::DataOrSuffStat;
+/// use rv::dist::{Gamma, GammaRatePrior};
+/// use rv::traits::*;
+///
+/// let prior = GammaRatePrior::new(Gamma::new(2.0, 1.0).unwrap(), 3.0).unwrap();
+///
+/// let xs: Vec<f64> = vec![1.5, 2.0, 0.5, 4.0];
+/// let data: DataOrSuffStat<f64, Gamma> = DataOrSuffStat::Data(&xs);
+/// let post = prior.posterior(&data);
+///
+/// assert::close(post.gamma().shape(), 2.0 + 4.0 * 3.0, 1E-12);
+/// assert::close(post.gamma().rate(), 1.0 + 8.0, 1E-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct GammaRatePrior {
+    gamma: Gamma,
+    shape: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum GammaRatePriorError {
+    /// Shape parameter is less than or equal to zero
+    ShapeTooLow { shape: f64 },
+    /// Shape parameter is infinite or NaN
+    ShapeNotFinite { shape: f64 },
+}
+
+impl GammaRatePrior {
+    /// Create a new prior from the Gamma on the rate and the known shape
+    pub fn new(gamma: Gamma, shape: f64) -> Result<Self, GammaRatePriorError> {
+        if shape <= 0.0 {
+            Err(GammaRatePriorError::ShapeTooLow { shape })
+        } else if !shape.is_finite() {
+            Err(GammaRatePriorError::ShapeNotFinite { shape })
+        } else {
+            Ok(GammaRatePrior { gamma, shape })
+        }
+    }
+
+    /// Creates a new GammaRatePrior without checking whether the shape is
+    /// valid.
+    #[inline]
+    pub fn new_unchecked(gamma: Gamma, shape: f64) -> Self {
+        GammaRatePrior { gamma, shape }
+    }
+
+    /// The Gamma distribution on the rate
+    #[inline]
+    pub fn gamma(&self) -> &Gamma {
+        &self.gamma
+    }
+
+    /// The known shape, α
+    #[inline]
+    pub fn shape(&self) -> f64 {
+        self.shape
+    }
+
+    /// a ln(b) - ln Γ(a) for the Gamma on the rate
+    fn ln_norm(gamma: &Gamma) -> f64 {
+        let a = gamma.shape();
+        a.mul_add(gamma.rate().ln(), -a.ln_gamma().0)
+    }
+}
+
+impl From<&GammaRatePrior> for String {
+    fn from(prior: &GammaRatePrior) -> String {
+        format!(
+            "GammaRatePrior(a: {}, b: {}, α: {})",
+            prior.gamma.shape(),
+            prior.gamma.rate(),
+            prior.shape
+        )
+    }
+}
+
+impl_display!(GammaRatePrior);
+
+impl Rv<Gamma> for GammaRatePrior {
+    fn ln_f(&self, x: &Gamma) -> f64 {
+        if x.shape() == self.shape {
+            self.gamma.ln_f(&x.rate())
+        } else {
+            f64::NEG_INFINITY
+        }
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> Gamma {
+        let rate: f64 = self.gamma.draw(rng);
+        Gamma::new_unchecked(self.shape, rate.max(f64::MIN_POSITIVE))
+    }
+}
+
+impl Support<Gamma> for GammaRatePrior {
+    fn supports(&self, x: &Gamma) -> bool {
+        x.shape() == self.shape && x.rate() > 0.0 && x.rate().is_finite()
+    }
+}
+
+impl ContinuousDistr<Gamma> for GammaRatePrior {}
+
+macro_rules! impl_traits {
+    ($kind: ty) => {
+        impl ConjugatePrior<$kind, Gamma> for GammaRatePrior {
+            type Posterior = Self;
+            type LnMCache = f64;
+            // (a + α, b, a ln(b) + ln Γ(a + α) - ln Γ(a) - ln Γ(α)) of
+            // the posterior
+            type LnPpCache = (f64, f64, f64);
+
+            fn posterior(&self, x: &DataOrSuffStat<$kind, Gamma>) -> Self {
+                let stat = extract_stat(x, GammaSuffStat::new);
+                let a =
+                    (stat.n() as f64).mul_add(self.shape, self.gamma.shape());
+                let b = self.gamma.rate() + stat.sum_x();
+                let gamma = Gamma::new(a, b).expect("Invalid posterior params");
+                GammaRatePrior::new_unchecked(gamma, self.shape)
+            }
+
+            #[inline]
+            fn ln_m_cache(&self) -> Self::LnMCache {
+                Self::ln_norm(&self.gamma)
+            }
+
+            fn ln_m_with_cache(
+                &self,
+                cache: &Self::LnMCache,
+                x: &DataOrSuffStat<$kind, Gamma>,
+            ) -> f64 {
+                let stat = extract_stat(x, GammaSuffStat::new);
+                let n = stat.n() as f64;
+                let post = self.posterior(x);
+                let ln_lik = (self.shape - 1.0)
+                    .mul_add(stat.sum_ln_x(), -n * self.shape.ln_gamma().0);
+                ln_lik + cache - Self::ln_norm(&post.gamma)
+            }
+
+            #[inline]
+            fn ln_pp_cache(
+                &self,
+                x: &DataOrSuffStat<$kind, Gamma>,
+            ) -> Self::LnPpCache {
+                let post = self.posterior(x);
+                let a = post.gamma.shape();
+                let ln_b = post.gamma.rate().ln();
+                let a_total = a + self.shape;
+                let ln_z = a.mul_add(ln_b, a_total.ln_gamma().0)
+                    - a.ln_gamma().0
+                    - self.shape.ln_gamma().0;
+                (a_total, post.gamma.rate(), ln_z)
+            }
+
+            fn ln_pp_with_cache(
+                &self,
+                cache: &Self::LnPpCache,
+                y: &$kind,
+            ) -> f64 {
+                // f(y) = Γ(a + α) / (Γ(a) Γ(α)) b^a y^(α - 1) / (b + y)^(a + α)
+                let (a_total, b, ln_z) = *cache;
+                let y = f64::from(*y);
+                if y <= 0.0 {
+                    f64::NEG_INFINITY
+                } else {
+                    (self.shape - 1.0)
+                        .mul_add(y.ln(), a_total.mul_add(-(b + y).ln(), ln_z))
+                }
+            }
+        }
+    };
+}
+
+impl_traits!(f32);
+impl_traits!(f64);
+
+impl std::error::Error for GammaRatePriorError {}
+
+impl fmt::Display for GammaRatePriorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ShapeTooLow { shape } => {
+                write!(f, "shape ({}) must be greater than zero", shape)
+            }
+            Self::ShapeNotFinite { shape } => {
+                write!(f, "non-finite shape: {}", shape)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::misc::adaptive_gauss_legendre_quadrature;
+
+    const TOL: f64 = 1E-10;
+
+    fn prior() -> GammaRatePrior {
+        GammaRatePrior::new(Gamma::new(2.5, 1.5).unwrap(), 1.7).unwrap()
+    }
+
+    #[test]
+    fn new_validates_shape() {
+        let gamma = Gamma::default();
+        assert!(GammaRatePrior::new(gamma.clone(), 1.0).is_ok());
+        assert!(GammaRatePrior::new(gamma.clone(), 0.0).is_err());
+        assert!(GammaRatePrior::new(gamma, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn posterior_params() {
+        let xs: Vec<f64> = vec![0.6, 1.2, 4.0];
+        let data: DataOrSuffStat<f64, Gamma> = DataOrSuffStat::Data(&xs);
+        let post = prior().posterior(&data);
+        assert::close(post.gamma().shape(), 3.0_f64.mul_add(1.7, 2.5), TOL);
+        assert::close(post.gamma().rate(), 1.5 + 5.8, TOL);
+        assert_eq!(post.shape(), 1.7);
+    }
+
+    #[test]
+    fn ln_m_matches_quadrature() {
+        let pr = prior();
+        let xs: Vec<f64> = vec![0.6, 1.2, 4.0];
+        let data: DataOrSuffStat<f64, Gamma> = DataOrSuffStat::Data(&xs);
+        let m = adaptive_gauss_legendre_quadrature(
+            |rate| {
+                let gam = Gamma::new_unchecked(1.7, rate);
+                let ln_lik: f64 = xs.iter().map(|x| gam.ln_f(x)).sum();
+                (ln_lik + pr.gamma().ln_f(&rate)).exp()
+            },
+            (1E-12, 60.0),
+            1E-14,
+            30,
+        );
+        assert::close(pr.ln_m(&data), m.ln(), 1E-8);
+    }
+
+    #[test]
+    fn ln_m_no_data_is_zero() {
+        let data: DataOrSuffStat<f64, Gamma> = DataOrSuffStat::None;
+        assert::close(prior().ln_m(&data), 0.0, TOL);
+    }
+
+    #[test]
+    fn ln_pp_is_ratio_of_marginals() {
+        let pr = prior();
+        let xs: Vec<f64> = vec![0.6, 1.2, 4.0];
+        let mut xys = xs.clone();
+        xys.push(2.2);
+        let ln_pp = pr.ln_pp(&2.2, &DataOrSuffStat::Data(&xs));
+        let ln_m_x = pr.ln_m(&DataOrSuffStat::Data(&xs));
+        let ln_m_xy = pr.ln_m(&DataOrSuffStat::Data(&xys));
+        assert::close(ln_pp, ln_m_xy - ln_m_x, TOL);
+        assert_eq!(
+            pr.ln_pp(&-0.4, &DataOrSuffStat::Data(&xs)),
+            f64::NEG_INFINITY
+        );
+    }
+}
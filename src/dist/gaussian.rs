@@ -11,12 +11,16 @@ use std::sync::OnceLock;
 
 use crate::consts::*;
 use crate::data::GaussianSuffStat;
+use crate::dist::quantile_match::{self, QuantileMatchError};
 use crate::impl_display;
 use crate::traits::*;
 
 /// Gaussian / [Normal distribution](https://en.wikipedia.org/wiki/Normal_distribution),
 /// N(μ, σ) over real values.
 ///
+/// Draws use the Ziggurat sampler from `rand_distr`, which is several times
+/// faster than Box-Muller or inverting the CDF (see `benches/ziggurat.rs`).
+///
 /// # Examples
 ///
 /// Compute the [KL Divergence](https://en.wikipedia.org/wiki/Kullback%E2%80%93Leibler_divergence)
@@ -47,6 +51,9 @@ pub struct Gaussian {
     /// Cached log(sigma)
     #[cfg_attr(feature = "serde1", serde(skip))]
     ln_sigma: OnceLock<f64>,
+    /// Cached 1 / σ
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    inv_sigma: OnceLock<f64>,
 }
 
 impl PartialEq for Gaussian {
@@ -85,6 +92,7 @@ impl Gaussian {
                 mu,
                 sigma,
                 ln_sigma: OnceLock::new(),
+                inv_sigma: OnceLock::new(),
             })
         }
     }
@@ -97,6 +105,7 @@ impl Gaussian {
             mu,
             sigma,
             ln_sigma: OnceLock::new(),
+            inv_sigma: OnceLock::new(),
         }
     }
 
@@ -116,9 +125,56 @@ impl Gaussian {
             mu: 0.0,
             sigma: 1.0,
             ln_sigma: OnceLock::from(0.0),
+            inv_sigma: OnceLock::from(1.0),
         }
     }
 
+    /// Create the Gaussian whose quantiles best match `quantiles`, given as
+    /// `(p, x)` pairs where `x` is the `p`-quantile.
+    ///
+    /// Two quantiles are matched exactly. With more, `μ` and `σ` are the
+    /// least-squares fit of `x = μ + σ Φ⁻¹(p)`.
+    ///
+    /// # Example
+    ///
+    /// An expert believes a value is between 10 and 30 with 90% certainty.
+    ///
+    /// ```rust
+    /// # use rv::dist::Gaussian;
+    /// use rv::traits::InverseCdf;
+    ///
+    /// let gauss = Gaussian::from_quantiles(&[(0.05, 10.0), (0.95, 30.0)])
+    ///     .unwrap();
+    ///
+    /// assert!((gauss.mu() - 20.0).abs() < 1E-10);
+    /// let x: f64 = gauss.invcdf(0.95);
+    /// assert!((x - 30.0).abs() < 1E-8);
+    /// ```
+    pub fn from_quantiles(
+        quantiles: &[(f64, f64)],
+    ) -> Result<Self, QuantileMatchError> {
+        let quantiles = quantile_match::validate(quantiles, 2, |_| true)?;
+        let n = quantiles.len() as f64;
+        let zs: Vec<f64> = quantiles
+            .iter()
+            .map(|&(p, _)| quantile_match::probit(p))
+            .collect();
+        let z_mean = zs.iter().sum::<f64>() / n;
+        let x_mean = quantiles.iter().map(|&(_, x)| x).sum::<f64>() / n;
+
+        let (cov_zx, var_z) = zs.iter().zip(quantiles.iter()).fold(
+            (0.0, 0.0),
+            |(cov, var), (&z, &(_, x))| {
+                let dz = z - z_mean;
+                (dz.mul_add(x - x_mean, cov), dz.mul_add(dz, var))
+            },
+        );
+
+        let sigma = cov_zx / var_z;
+        let mu = sigma.mul_add(-z_mean, x_mean);
+        Self::new(mu, sigma).map_err(|_| QuantileMatchError::NoSolution)
+    }
+
     /// Get mu parameter
     ///
     /// # Example
@@ -230,6 +286,7 @@ impl Gaussian {
     pub fn set_sigma_unchecked(&mut self, sigma: f64) {
         self.sigma = sigma;
         self.ln_sigma = OnceLock::new();
+        self.inv_sigma = OnceLock::new();
     }
 
     /// Evaluate or fetch cached log sigma
@@ -237,6 +294,12 @@ impl Gaussian {
     fn ln_sigma(&self) -> f64 {
         *self.ln_sigma.get_or_init(|| self.sigma.ln())
     }
+
+    /// Evaluate or fetch cached 1 / σ
+    #[inline]
+    fn inv_sigma(&self) -> f64 {
+        *self.inv_sigma.get_or_init(|| self.sigma.recip())
+    }
 }
 
 impl Default for Gaussian {
@@ -257,10 +320,24 @@ macro_rules! impl_traits {
     ($kind:ty) => {
         impl Rv<$kind> for Gaussian {
             fn ln_f(&self, x: &$kind) -> f64 {
-                let k = (f64::from(*x) - self.mu) / self.sigma;
+                let k = (f64::from(*x) - self.mu) * self.inv_sigma();
                 (0.5 * k).mul_add(-k, -self.ln_sigma()) - HALF_LN_2PI
             }
 
+            fn ln_f_many(&self, xs: &[$kind], out: &mut [f64]) {
+                assert_eq!(
+                    xs.len(),
+                    out.len(),
+                    "xs and out must be the same length"
+                );
+                let ln_z = -self.ln_sigma() - HALF_LN_2PI;
+                let inv_sigma = self.inv_sigma();
+                xs.iter().zip(out.iter_mut()).for_each(|(&x, y)| {
+                    let k = (f64::from(x) - self.mu) * inv_sigma;
+                    *y = (0.5 * k).mul_add(-k, ln_z);
+                });
+            }
+
             fn draw<R: Rng>(&self, rng: &mut R) -> $kind {
                 let g = Normal::new(self.mu, self.sigma).unwrap();
                 rng.sample(g) as $kind
@@ -324,15 +401,14 @@ macro_rules! impl_traits {
             }
 
             fn ln_f_stat(&self, stat: &Self::Stat) -> f64 {
-                // let k = (f64::from(*x) - self.mu) / self.sigma;
-                // (0.5 * k).mul_add(-k, -self.ln_sigma()) - HALF_LN_2PI
-                let z = (2.0 * self.sigma * self.sigma).recip();
+                // Σ((x - μ) / σ)² = (√sx / σ)² + n ((x̄ - μ) / σ)², in the
+                // standardized form so that it neither over- nor underflows
                 let n = stat.n() as f64;
-                let expterm = stat.sum_x_sq()
-                    + self
-                        .mu
-                        .mul_add(-2.0 * stat.sum_x(), n * self.mu * self.mu);
-                -n.mul_add(self.ln_sigma() + HALF_LN_2PI, z * expterm)
+                let k_sx = stat.scatter().sqrt() * self.inv_sigma();
+                let k_mean = (stat.mean() - self.mu) * self.inv_sigma();
+                let sum_k_sq = (n * k_mean).mul_add(k_mean, k_sx * k_sx);
+                (-0.5_f64)
+                    .mul_add(sum_k_sq, -n * (self.ln_sigma() + HALF_LN_2PI))
             }
         }
     };
@@ -378,6 +454,19 @@ impl KlDivergence for Gaussian {
     }
 }
 
+impl HellingerDistance for Gaussian {
+    fn hellinger(&self, other: &Self) -> f64 {
+        let s1 = self.sigma;
+        let s2 = other.sigma;
+        let var_sum = s1.mul_add(s1, s2 * s2);
+        let dmu = self.mu - other.mu;
+
+        let bc = (2.0 * s1 * s2 / var_sum).sqrt()
+            * (-dmu * dmu / (4.0 * var_sum)).exp();
+        (1.0 - bc).max(0.0).sqrt()
+    }
+}
+
 impl QuadBounds for Gaussian {
     fn quad_bounds(&self) -> (f64, f64) {
         self.interval(0.999_999_999_999)
@@ -387,6 +476,18 @@ impl QuadBounds for Gaussian {
 impl_traits!(f32);
 impl_traits!(f64);
 
+impl CacheControl for Gaussian {
+    fn precompute(&self) {
+        self.ln_sigma();
+        self.inv_sigma();
+    }
+
+    fn invalidate(&mut self) {
+        self.ln_sigma = OnceLock::new();
+        self.inv_sigma = OnceLock::new();
+    }
+}
+
 impl std::error::Error for GaussianError {}
 
 impl fmt::Display for GaussianError {
@@ -631,6 +732,29 @@ mod tests {
         assert::close(g1.kl(&g2), kl, TOL);
     }
 
+    #[test]
+    fn hellinger_matches_quadrature() {
+        let g1 = Gaussian::new(1.0, 2.0).unwrap();
+        let g2 = Gaussian::new(-0.5, 0.7).unwrap();
+        assert::close(g1.hellinger(&g2), g1.hellinger_quad(&g2), 1E-8);
+        assert::close(g1.hellinger(&g1), 0.0, TOL);
+    }
+
+    #[test]
+    fn js_quad_and_mc_agree() {
+        let mut rng = rand::thread_rng();
+        let g1 = Gaussian::new(1.0, 2.0).unwrap();
+        let g2 = Gaussian::new(-0.5, 0.7).unwrap();
+        let js = g1.js_quad(&g2);
+        assert::close(js, g2.js_quad(&g1), 1E-10);
+        assert!(js > 0.0 && js < std::f64::consts::LN_2);
+        let js_mc = McDivergence::<f64>::js_mc(&g1, &g2, 200_000, &mut rng);
+        assert::close(js_mc, js, 1E-2);
+        let h_mc =
+            McDivergence::<f64>::hellinger_mc(&g1, &g2, 200_000, &mut rng);
+        assert::close(h_mc, g1.hellinger(&g2), 1E-2);
+    }
+
     #[test]
     fn ln_f_after_set_mu_works() {
         let mut gauss = Gaussian::standard();
@@ -663,4 +787,81 @@ mod tests {
 
         assert::close(ln_f_base, ln_f_stat, TOL);
     }
+
+    #[test]
+    fn ln_f_extreme_sigma() {
+        let wide = Gaussian::new(0.0, 1E200).unwrap();
+        let expected = -0.5 - 1E200_f64.ln() - HALF_LN_2PI;
+        assert::close(wide.ln_f(&1E200_f64), expected, TOL);
+
+        let narrow = Gaussian::new(0.0, 1E-170).unwrap();
+        let expected = -(1E-170_f64.ln()) - HALF_LN_2PI;
+        assert::close(narrow.ln_f(&0.0_f64), expected, TOL);
+        assert::close(narrow.ln_f(&1E-170_f64), expected - 0.5, TOL);
+
+        let mut out = [0.0; 2];
+        narrow.ln_f_many(&[0.0_f64, 1E-170], &mut out);
+        assert::close(&out[..], &[expected, expected - 0.5], TOL);
+    }
+
+    #[test]
+    fn ln_f_stat_extreme_sigma() {
+        // The data are small enough that their scatter is finite
+        for (sigma, data) in [
+            (1E200, vec![-1E150, 0.0, 2E150]),
+            (1E-150, vec![-1E-150, 0.0, 2E-150]),
+        ] {
+            let gauss = Gaussian::new(0.0, sigma).unwrap();
+            let mut stat = GaussianSuffStat::new();
+            stat.observe_many(&data);
+
+            let ln_f_base: f64 = data.iter().map(|x| gauss.ln_f(x)).sum();
+            let ln_f_stat: f64 =
+                <Gaussian as HasSuffStat<f64>>::ln_f_stat(&gauss, &stat);
+            assert!(ln_f_stat.is_finite());
+            assert::close(ln_f_base, ln_f_stat, 1E-9);
+        }
+    }
+
+    #[test]
+    fn from_quantiles_matches_two_quantiles() {
+        let gauss = Gaussian::new(-1.5, 2.5).unwrap();
+        let qs: Vec<(f64, f64)> =
+            [0.2, 0.7].iter().map(|&p| (p, gauss.invcdf(p))).collect();
+        let fit = Gaussian::from_quantiles(&qs).unwrap();
+        assert::close(fit.mu(), -1.5, 1E-10);
+        assert::close(fit.sigma(), 2.5, 1E-10);
+    }
+
+    #[test]
+    fn from_quantiles_least_squares_recovers_params() {
+        let gauss = Gaussian::new(3.0, 0.5).unwrap();
+        let qs: Vec<(f64, f64)> = [0.9, 0.05, 0.5, 0.25]
+            .iter()
+            .map(|&p| (p, gauss.invcdf(p)))
+            .collect();
+        let fit = Gaussian::from_quantiles(&qs).unwrap();
+        assert::close(fit.mu(), 3.0, 1E-10);
+        assert::close(fit.sigma(), 0.5, 1E-10);
+    }
+
+    #[test]
+    fn from_quantiles_rejects_decreasing_values() {
+        assert_eq!(
+            Gaussian::from_quantiles(&[(0.1, 2.0), (0.9, 1.0)]),
+            Err(QuantileMatchError::NotIncreasing)
+        );
+    }
+
+    #[test]
+    fn precompute_and_invalidate() {
+        let mut gauss = Gaussian::new(1.0, 2.0).unwrap();
+        assert!(gauss.ln_sigma.get().is_none());
+        gauss.precompute();
+        assert_eq!(gauss.ln_sigma.get(), Some(&2.0_f64.ln()));
+        assert_eq!(gauss.inv_sigma.get(), Some(&0.5));
+        gauss.invalidate();
+        assert!(gauss.ln_sigma.get().is_none());
+        assert!(gauss.inv_sigma.get().is_none());
+    }
 }
@@ -2,6 +2,7 @@
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
 
+use crate::data::GeometricSuffStat;
 use crate::dist::Uniform;
 use crate::impl_display;
 use crate::traits::*;
@@ -250,6 +251,27 @@ where
     }
 }
 
+macro_rules! impl_suffstat {
+    ($kind: ty) => {
+        impl HasSuffStat<$kind> for Geometric {
+            type Stat = GeometricSuffStat;
+
+            fn empty_suffstat(&self) -> Self::Stat {
+                GeometricSuffStat::new()
+            }
+
+            fn ln_f_stat(&self, stat: &Self::Stat) -> f64 {
+                let n = stat.n() as f64;
+                stat.sum_k().mul_add(self.ln_1mp(), n * self.ln_p())
+            }
+        }
+    };
+}
+
+impl_suffstat!(u8);
+impl_suffstat!(u16);
+impl_suffstat!(u32);
+
 impl Mean<f64> for Geometric {
     fn mean(&self) -> Option<f64> {
         Some((1.0 - self.p) / self.p)
@@ -282,6 +304,18 @@ impl Entropy for Geometric {
     }
 }
 
+impl CacheControl for Geometric {
+    fn precompute(&self) {
+        self.ln_p();
+        self.ln_1mp();
+    }
+
+    fn invalidate(&mut self) {
+        self.ln_p = OnceLock::new();
+        self.ln_1mp = OnceLock::new();
+    }
+}
+
 impl std::error::Error for GeometricError {}
 
 impl fmt::Display for GeometricError {
@@ -441,4 +475,17 @@ mod tests {
         0.57,
         0.12
     );
+
+    #[test]
+    fn ln_f_stat() {
+        let geom = Geometric::new(0.3).unwrap();
+        let xs: Vec<u32> = vec![0, 4, 1, 7, 2];
+        let mut stat = GeometricSuffStat::new();
+        stat.observe_many(&xs);
+
+        let ln_f_sum: f64 = xs.iter().map(|x| geom.ln_f(x)).sum();
+        let ln_f_stat =
+            <Geometric as HasSuffStat<u32>>::ln_f_stat(&geom, &stat);
+        assert::close(ln_f_stat, ln_f_sum, TOL);
+    }
 }
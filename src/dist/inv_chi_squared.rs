@@ -235,6 +235,16 @@ impl Kurtosis for InvChiSquared {
 impl_traits!(f64);
 impl_traits!(f32);
 
+impl CacheControl for InvChiSquared {
+    fn precompute(&self) {
+        self.ln_f_const();
+    }
+
+    fn invalidate(&mut self) {
+        self.ln_f_const = OnceLock::new();
+    }
+}
+
 impl std::error::Error for InvChiSquaredError {}
 
 impl fmt::Display for InvChiSquaredError {
@@ -354,6 +354,16 @@ impl Kurtosis for InvGaussian {
 impl_traits!(f32);
 impl_traits!(f64);
 
+impl CacheControl for InvGaussian {
+    fn precompute(&self) {
+        self.ln_lambda();
+    }
+
+    fn invalidate(&mut self) {
+        self.ln_lambda = OnceLock::new();
+    }
+}
+
 impl std::error::Error for InvGaussianError {}
 
 impl fmt::Display for InvGaussianError {
@@ -0,0 +1,198 @@
+//! Joint distribution of independent random variables of different types
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::traits::*;
+use rand::Rng;
+
+/// The joint distribution of independent random variables.
+///
+/// `Joint` wraps a tuple of two to four distributions and is a distribution
+/// over tuples of their values. The density is the product of the component
+/// densities, the mean and variance are those of the components, and the
+/// entropy is the sum of the component entropies.
+///
+/// Unlike [`ProductDistribution`](crate::dist::ProductDistribution), the
+/// types of the components are checked at compile time, so `Joint` composes
+/// with any distribution, including other `Joint`s and `Mixture`s.
+///
+/// # Example
+///
+/// A mixture of two joint distributions over a label and a measurement
+///
+/// ```
+/// use rv::dist::{Bernoulli, Gaussian, Joint, Mixture};
+/// use rv::traits::*;
+///
+/// let joint_a = Joint::new((
+///     Bernoulli::new(0.9).unwrap(),
+///     Gaussian::new(-2.0, 1.0).unwrap(),
+/// ));
+/// let joint_b = Joint::new((
+///     Bernoulli::new(0.1).unwrap(),
+///     Gaussian::new(2.0, 1.0).unwrap(),
+/// ));
+///
+/// // The density is the product of the component densities
+/// let ln_f = joint_a.ln_f(&(true, -1.5_f64));
+/// let ln_f_x: f64 = Gaussian::new(-2.0, 1.0).unwrap().ln_f(&-1.5);
+/// assert::close(ln_f, 0.9_f64.ln() + ln_f_x, 1E-12);
+///
+/// let mm = Mixture::uniform(vec![joint_a, joint_b]).unwrap();
+/// assert!(mm.f(&(true, -2.0_f64)) > mm.f(&(false, -2.0_f64)));
+///
+/// let mut rng = rand::thread_rng();
+/// let (label, x): (bool, f64) = mm.draw(&mut rng);
+/// # let _ = (label, x);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct Joint<T> {
+    dists: T,
+}
+
+impl<T> Joint<T> {
+    /// Create the joint distribution of the tuple of distributions, `dists`
+    #[inline]
+    pub fn new(dists: T) -> Self {
+        Joint { dists }
+    }
+
+    /// The component distributions
+    #[inline]
+    pub fn dists(&self) -> &T {
+        &self.dists
+    }
+
+    /// Take the component distributions
+    #[inline]
+    pub fn into_dists(self) -> T {
+        self.dists
+    }
+}
+
+macro_rules! impl_joint {
+    ($($fx: ident, $x: ident, $ix: tt);+) => {
+        impl<$($fx, $x),+> Rv<($($x,)+)> for Joint<($($fx,)+)>
+        where
+            $($fx: Rv<$x>),+
+        {
+            fn ln_f(&self, x: &($($x,)+)) -> f64 {
+                0.0 $(+ self.dists.$ix.ln_f(&x.$ix))+
+            }
+
+            fn draw<R: Rng>(&self, rng: &mut R) -> ($($x,)+) {
+                ($(self.dists.$ix.draw(rng),)+)
+            }
+        }
+
+        impl<$($fx, $x),+> Support<($($x,)+)> for Joint<($($fx,)+)>
+        where
+            $($fx: Support<$x>),+
+        {
+            fn supports(&self, x: &($($x,)+)) -> bool {
+                true $(&& self.dists.$ix.supports(&x.$ix))+
+            }
+        }
+
+        impl<$($fx, $x),+> ContinuousDistr<($($x,)+)> for Joint<($($fx,)+)>
+        where
+            $($fx: ContinuousDistr<$x>),+
+        {
+        }
+
+        impl<$($fx, $x),+> DiscreteDistr<($($x,)+)> for Joint<($($fx,)+)>
+        where
+            $($fx: DiscreteDistr<$x>),+
+        {
+        }
+
+        impl<$($fx, $x),+> Mean<($($x,)+)> for Joint<($($fx,)+)>
+        where
+            $($fx: Mean<$x>),+
+        {
+            fn mean(&self) -> Option<($($x,)+)> {
+                Some(($(self.dists.$ix.mean()?,)+))
+            }
+        }
+
+        impl<$($fx, $x),+> Variance<($($x,)+)> for Joint<($($fx,)+)>
+        where
+            $($fx: Variance<$x>),+
+        {
+            fn variance(&self) -> Option<($($x,)+)> {
+                Some(($(self.dists.$ix.variance()?,)+))
+            }
+        }
+
+        impl<$($fx),+> Entropy for Joint<($($fx,)+)>
+        where
+            $($fx: Entropy),+
+        {
+            fn entropy(&self) -> f64 {
+                0.0 $(+ self.dists.$ix.entropy())+
+            }
+        }
+    };
+}
+
+impl_joint!(F0, X0, 0; F1, X1, 1);
+impl_joint!(F0, X0, 0; F1, X1, 1; F2, X2, 2);
+impl_joint!(F0, X0, 0; F1, X1, 1; F2, X2, 2; F3, X3, 3);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Bernoulli, Exponential, Gaussian, Poisson};
+
+    const TOL: f64 = 1E-12;
+
+    #[test]
+    fn ln_f_is_sum_of_components() {
+        let g = Gaussian::new(1.0, 2.0).unwrap();
+        let p = Poisson::new(3.0).unwrap();
+        let e = Exponential::new(1.5).unwrap();
+        let joint = Joint::new((g.clone(), p.clone(), e.clone()));
+
+        let x = (0.5_f64, 2_u32, 0.7_f64);
+        let expected = g.ln_f(&x.0) + p.ln_f(&x.1) + e.ln_f(&x.2);
+        assert::close(joint.ln_f(&x), expected, TOL);
+    }
+
+    #[test]
+    fn supports_requires_all_components() {
+        let joint =
+            Joint::new((Gaussian::standard(), Exponential::new(1.0).unwrap()));
+        assert!(joint.supports(&(-1.0_f64, 1.0_f64)));
+        assert!(!joint.supports(&(-1.0_f64, -1.0_f64)));
+        assert_eq!(joint.ln_pdf(&(-1.0_f64, -1.0_f64)), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn moments_and_entropy() {
+        let g = Gaussian::new(1.0, 2.0).unwrap();
+        let e = Exponential::new(4.0).unwrap();
+        let b = Bernoulli::new(0.3).unwrap();
+        let joint = Joint::new((g.clone(), e.clone(), b.clone(), g.clone()));
+
+        let mean: (f64, f64, f64, f64) = joint.mean().unwrap();
+        assert_eq!(mean, (1.0, 0.25, 0.3, 1.0));
+        let var: (f64, f64, f64, f64) = joint.variance().unwrap();
+        assert::close(var.1, 1.0 / 16.0, TOL);
+
+        let h = g.entropy() + e.entropy() + b.entropy() + g.entropy();
+        assert::close(joint.entropy(), h, TOL);
+    }
+
+    #[test]
+    fn draws_are_supported() {
+        let joint = Joint::new((
+            Poisson::new(2.0).unwrap(),
+            Exponential::new(1.0).unwrap(),
+        ));
+        let mut rng = rand::thread_rng();
+        let xs: Vec<(u32, f64)> = joint.sample(100, &mut rng);
+        assert!(xs.iter().all(|x| joint.supports(x)));
+    }
+}
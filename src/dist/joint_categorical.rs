@@ -0,0 +1,410 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::dist::condition::free_indices;
+use crate::dist::marginalize::dropped_indices;
+use crate::dist::{Categorical, ConditionError, MarginalizeError};
+use crate::impl_display;
+use crate::misc::{argmax, ln_pflip, logsumexp};
+use crate::traits::*;
+use rand::Rng;
+use std::fmt;
+
+/// Joint distribution over several categorical variables, given by a
+/// probability table.
+///
+/// Variable `i` takes values in `0..shape[i]`. The table is stored in
+/// row-major order, so the last variable varies fastest.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{Categorical, JointCategorical};
+/// use rv::traits::*;
+///
+/// // Rain (rows) and a wet lawn (columns)
+/// let joint = JointCategorical::new(
+///     vec![2, 2],
+///     &[0.56, 0.14, 0.03, 0.27],
+/// ).unwrap();
+/// assert::close(joint.f(&vec![1, 1]), 0.27, 1E-12);
+///
+/// // Rain given that the lawn is wet
+/// let rain: Categorical =
+///     joint.condition(&[(1, 1)]).unwrap().into_categorical().unwrap();
+/// assert::close(rain.pmf(&1_usize), 0.27 / 0.41, 1E-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct JointCategorical {
+    shape: Vec<usize>,
+    // Normalized log probabilities in row-major order
+    ln_weights: Vec<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum JointCategoricalError {
+    /// There are no variables or one of them has no categories
+    EmptyShape,
+    /// The number of weights differs from the size of the table
+    WeightsShapeMismatch { n_weights: usize, n_cells: usize },
+    /// A weight is negative, infinite, or NaN
+    InvalidWeight { ix: usize, weight: f64 },
+    /// The weights sum to zero
+    WeightsSumToZero,
+}
+
+impl JointCategorical {
+    /// Create a new joint categorical from the row-major table of weights.
+    /// The weights need not sum to one.
+    pub fn new(
+        shape: Vec<usize>,
+        weights: &[f64],
+    ) -> Result<Self, JointCategoricalError> {
+        if shape.is_empty() || shape.contains(&0) {
+            return Err(JointCategoricalError::EmptyShape);
+        }
+        let n_cells: usize = shape.iter().product();
+        if weights.len() != n_cells {
+            return Err(JointCategoricalError::WeightsShapeMismatch {
+                n_weights: weights.len(),
+                n_cells,
+            });
+        }
+        weights.iter().enumerate().try_for_each(|(ix, &weight)| {
+            if weight >= 0.0 && weight.is_finite() {
+                Ok(())
+            } else {
+                Err(JointCategoricalError::InvalidWeight { ix, weight })
+            }
+        })?;
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return Err(JointCategoricalError::WeightsSumToZero);
+        }
+        let ln_total = total.ln();
+        let ln_weights = weights.iter().map(|w| w.ln() - ln_total).collect();
+        Ok(JointCategorical { shape, ln_weights })
+    }
+
+    /// Creates a new JointCategorical without checking whether the
+    /// normalized, row-major ln weights are valid.
+    #[inline]
+    pub fn new_unchecked(shape: Vec<usize>, ln_weights: Vec<f64>) -> Self {
+        JointCategorical { shape, ln_weights }
+    }
+
+    /// The number of categories of each variable
+    #[inline]
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// The number of variables
+    #[inline]
+    pub fn n_vars(&self) -> usize {
+        self.shape.len()
+    }
+
+    /// The normalized ln probability table in row-major order
+    #[inline]
+    pub fn ln_weights(&self) -> &[f64] {
+        &self.ln_weights
+    }
+
+    /// The probability table in row-major order
+    pub fn weights(&self) -> Vec<f64> {
+        self.ln_weights.iter().map(|w| w.exp()).collect()
+    }
+
+    /// Convert a distribution over a single variable to a `Categorical`.
+    /// Returns `None` if there is more than one variable.
+    pub fn into_categorical(self) -> Option<Categorical> {
+        if self.shape.len() == 1 {
+            Some(Categorical::new_unchecked(self.ln_weights))
+        } else {
+            None
+        }
+    }
+
+    /// The position of `x` in the table
+    fn flat_index(&self, x: &[usize]) -> usize {
+        x.iter()
+            .zip(self.shape.iter())
+            .fold(0, |acc, (&xi, &k)| acc * k + xi)
+    }
+
+    /// The values of the variables at a position in the table
+    fn unflatten(&self, ix: usize) -> Vec<usize> {
+        unflatten(&self.shape, ix)
+    }
+}
+
+/// The values of the variables at a position in a row-major table
+fn unflatten(shape: &[usize], mut ix: usize) -> Vec<usize> {
+    let mut x = vec![0; shape.len()];
+    for (xi, &k) in x.iter_mut().zip(shape.iter()).rev() {
+        *xi = ix % k;
+        ix /= k;
+    }
+    x
+}
+
+impl From<&JointCategorical> for String {
+    fn from(joint: &JointCategorical) -> String {
+        format!("JointCategorical(shape: {:?})", joint.shape)
+    }
+}
+
+impl_display!(JointCategorical);
+
+impl Rv<Vec<usize>> for JointCategorical {
+    fn ln_f(&self, x: &Vec<usize>) -> f64 {
+        self.ln_weights[self.flat_index(x)]
+    }
+
+    fn draw<R: Rng>(&self, mut rng: &mut R) -> Vec<usize> {
+        let ix = ln_pflip(&self.ln_weights, 1, true, &mut rng)[0];
+        self.unflatten(ix)
+    }
+
+    fn sample<R: Rng>(&self, n: usize, mut rng: &mut R) -> Vec<Vec<usize>> {
+        ln_pflip(&self.ln_weights, n, true, &mut rng)
+            .into_iter()
+            .map(|ix| self.unflatten(ix))
+            .collect()
+    }
+}
+
+impl Support<Vec<usize>> for JointCategorical {
+    fn supports(&self, x: &Vec<usize>) -> bool {
+        x.len() == self.shape.len()
+            && x.iter().zip(self.shape.iter()).all(|(xi, k)| xi < k)
+    }
+}
+
+impl DiscreteDistr<Vec<usize>> for JointCategorical {}
+
+impl Mode<Vec<usize>> for JointCategorical {
+    fn mode(&self) -> Option<Vec<usize>> {
+        // Return None if more than one max value
+        let max_ixs = argmax(&self.ln_weights);
+        if max_ixs.len() > 1 {
+            None
+        } else {
+            Some(self.unflatten(max_ixs[0]))
+        }
+    }
+}
+
+impl Entropy for JointCategorical {
+    fn entropy(&self) -> f64 {
+        self.ln_weights
+            .iter()
+            .filter(|w| w.is_finite())
+            .fold(0.0, |acc, ln_weight| acc - ln_weight.exp() * ln_weight)
+    }
+}
+
+/// Condition on the values of some variables, given as `(index, value)`
+/// pairs, to get the joint distribution of the remaining variables
+impl Condition<[(usize, usize)]> for JointCategorical {
+    type Conditional = JointCategorical;
+
+    fn condition(
+        &self,
+        evidence: &[(usize, usize)],
+    ) -> Result<JointCategorical, ConditionError> {
+        let free =
+            free_indices(evidence.iter().map(|(ix, _)| ix), self.n_vars())?;
+        if evidence.iter().any(|&(ix, x)| x >= self.shape[ix]) {
+            return Err(ConditionError::ImpossibleEvidence);
+        }
+
+        // Walk the cells of the free variables in row-major order with the
+        // evidence fixed
+        let shape: Vec<usize> = free.iter().map(|&ix| self.shape[ix]).collect();
+        let n_cells: usize = shape.iter().product();
+        let mut x = vec![0; self.n_vars()];
+        evidence.iter().for_each(|&(ix, value)| x[ix] = value);
+        let ln_weights: Vec<f64> = (0..n_cells)
+            .map(|cell| {
+                let free_values = unflatten(&shape, cell);
+                free.iter()
+                    .zip(free_values)
+                    .for_each(|(&ix, value)| x[ix] = value);
+                self.ln_weights[self.flat_index(&x)]
+            })
+            .collect();
+
+        let ln_z = logsumexp(&ln_weights);
+        if !ln_z.is_finite() {
+            return Err(ConditionError::ImpossibleEvidence);
+        }
+        Ok(JointCategorical::new_unchecked(
+            shape,
+            ln_weights.iter().map(|w| w - ln_z).collect(),
+        ))
+    }
+}
+
+/// Sum out every variable not in `ixs` to get the joint distribution of the
+/// selected variables, in the order given
+impl Marginalize for JointCategorical {
+    type Marginal = JointCategorical;
+
+    fn marginalize(
+        &self,
+        ixs: &[usize],
+    ) -> Result<JointCategorical, MarginalizeError> {
+        dropped_indices(ixs, self.n_vars())?;
+        let shape: Vec<usize> = ixs.iter().map(|&ix| self.shape[ix]).collect();
+        let n_cells: usize = shape.iter().product();
+        let mut weights = vec![0.0; n_cells];
+        let mut kept = vec![0; ixs.len()];
+        self.ln_weights
+            .iter()
+            .enumerate()
+            .for_each(|(cell, ln_weight)| {
+                let x = self.unflatten(cell);
+                kept.iter_mut().zip(ixs).for_each(|(k, &ix)| *k = x[ix]);
+                let marginal_cell = kept
+                    .iter()
+                    .zip(shape.iter())
+                    .fold(0, |acc, (&xi, &k)| acc * k + xi);
+                weights[marginal_cell] += ln_weight.exp();
+            });
+        Ok(JointCategorical::new_unchecked(
+            shape,
+            weights.iter().map(|w| w.ln()).collect(),
+        ))
+    }
+}
+
+impl std::error::Error for JointCategoricalError {}
+
+impl fmt::Display for JointCategoricalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyShape => {
+                write!(f, "every variable must have at least one category")
+            }
+            Self::WeightsShapeMismatch { n_weights, n_cells } => write!(
+                f,
+                "{} weights given for a table of {} cells",
+                n_weights, n_cells
+            ),
+            Self::InvalidWeight { ix, weight } => write!(
+                f,
+                "weight at index {} ({}) must be finite and non-negative",
+                ix, weight
+            ),
+            Self::WeightsSumToZero => write!(f, "the weights sum to zero"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_basic_impls;
+
+    const TOL: f64 = 1E-12;
+
+    fn table() -> JointCategorical {
+        let weights: Vec<f64> = (1..=24).map(f64::from).collect();
+        JointCategorical::new(vec![2, 3, 4], &weights).unwrap()
+    }
+
+    test_basic_impls!(table(), vec![1_usize, 2, 3]);
+
+    #[test]
+    fn new_validates_table() {
+        assert_eq!(
+            JointCategorical::new(vec![2, 0], &[]),
+            Err(JointCategoricalError::EmptyShape)
+        );
+        assert!(JointCategorical::new(vec![2, 2], &[1.0; 3]).is_err());
+        assert!(JointCategorical::new(vec![2], &[1.0, f64::NAN]).is_err());
+        assert_eq!(
+            JointCategorical::new(vec![2], &[0.0, 0.0]),
+            Err(JointCategoricalError::WeightsSumToZero)
+        );
+    }
+
+    #[test]
+    fn index_round_trips() {
+        let joint = table();
+        for ix in 0..24 {
+            let x = joint.unflatten(ix);
+            assert!(joint.supports(&x));
+            assert_eq!(joint.flat_index(&x), ix);
+        }
+        // Row-major: the last variable varies fastest
+        assert_eq!(joint.unflatten(1), vec![0, 0, 1]);
+        assert::close(joint.f(&vec![0, 0, 1]), 2.0 / 300.0, TOL);
+    }
+
+    #[test]
+    fn condition_is_renormalized_slice() {
+        let joint = table();
+        let cond = joint.condition(&[(1, 2)]).unwrap();
+        assert_eq!(cond.shape(), &[2, 4]);
+
+        // Cell (a, 2, d) has weight 12a + 9 + d
+        let z: f64 = (9.0 + 10.0 + 11.0 + 12.0) + (21.0 + 22.0 + 23.0 + 24.0);
+        assert::close(cond.f(&vec![1, 3]), 24.0 / z, TOL);
+        assert::close(cond.weights().iter().sum::<f64>(), 1.0, TOL);
+
+        // Conditioning in steps is the same as all at once
+        let twice = cond.condition(&[(0, 1)]).unwrap();
+        let once = joint.condition(&[(1, 2), (0, 1)]).unwrap();
+        assert_eq!(twice.shape(), &[4]);
+        assert::close(
+            twice.ln_weights().to_vec(),
+            once.ln_weights().to_vec(),
+            TOL,
+        );
+    }
+
+    #[test]
+    fn marginal_times_conditional_is_joint() {
+        let joint = table();
+        let marginal = joint.marginalize(&[2, 0]).unwrap();
+        assert_eq!(marginal.shape(), &[4, 2]);
+        assert::close(marginal.weights().iter().sum::<f64>(), 1.0, TOL);
+
+        for x in [vec![0, 1, 3], vec![1, 2, 0], vec![1, 0, 2]] {
+            let cond = joint.condition(&[(0, x[0]), (2, x[2])]).unwrap();
+            let ln_f =
+                marginal.ln_f(&vec![x[2], x[0]]) + cond.ln_f(&vec![x[1]]);
+            assert::close(joint.ln_f(&x), ln_f, TOL);
+        }
+        assert::close(
+            joint.marginalize(&[0, 1, 2]).unwrap().ln_weights().to_vec(),
+            joint.ln_weights().to_vec(),
+            TOL,
+        );
+    }
+
+    #[test]
+    fn condition_on_impossible_evidence() {
+        let joint =
+            JointCategorical::new(vec![2, 2], &[1.0, 1.0, 0.0, 0.0]).unwrap();
+        assert_eq!(
+            joint.condition(&[(0, 1)]),
+            Err(ConditionError::ImpossibleEvidence)
+        );
+        assert_eq!(
+            joint.condition(&[(1, 2)]),
+            Err(ConditionError::ImpossibleEvidence)
+        );
+        assert_eq!(
+            joint.condition(&[(0, 0), (1, 0)]),
+            Err(ConditionError::NoFreeVariables)
+        );
+    }
+}
@@ -0,0 +1,794 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::consts::HALF_LN_2PI;
+use crate::dist::Gaussian;
+use crate::misc::logsumexp;
+use crate::traits::*;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use std::fmt;
+
+/// Number of grid points used by the binned cross-validation scores
+const N_BINS: usize = 1024;
+
+/// Kernels further than this many bandwidths apart are ignored by the binned
+/// cross-validation scores
+const KERNEL_RADIUS: f64 = 6.0;
+
+/// Gaussian [kernel density
+/// estimate](https://en.wikipedia.org/wiki/Kernel_density_estimation) from
+/// possibly weighted observations.
+///
+/// ```math
+/// f(x) = \sum_i w_i \frac{1}{h} \phi\left(\frac{x - x_i}{h}\right)
+/// ```
+///
+/// # Example
+///
+/// Choose the bandwidth by likelihood cross-validation
+///
+/// ```
+/// use rv::dist::{Bandwidth, Gaussian, Kde};
+/// use rv::traits::*;
+///
+/// let mut rng = rand::thread_rng();
+/// let xs: Vec<f64> = Gaussian::standard().sample(500, &mut rng);
+///
+/// let kde = Kde::from_sample(xs, Bandwidth::LikelihoodCv).unwrap();
+/// assert!(kde.bandwidth() > 0.05 && kde.bandwidth() < 1.0);
+/// assert!((kde.f(&0.0) - 0.3989).abs() < 0.1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct Kde {
+    xs: Vec<f64>,
+    /// Normalized to sum to one
+    weights: Vec<f64>,
+    bandwidth: f64,
+}
+
+/// Bandwidth selection methods for [`Kde`]
+///
+/// The cross-validation selectors score the leave-one-out estimate. Samples
+/// with more than 1024 observations are linearly binned onto a grid of 1024
+/// points first, so each score costs `O(n)` for binning and at most
+/// `O(1024²)` afterwards, which makes selection feasible for millions of
+/// observations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum Bandwidth {
+    /// Silverman's rule of thumb, `0.9 min(σ, IQR / 1.34) n^(-1/5)`
+    Silverman,
+    /// Least-squares (unbiased) cross-validation, which minimizes an
+    /// estimate of the integrated squared error
+    LeastSquaresCv,
+    /// Likelihood cross-validation, which maximizes the leave-one-out log
+    /// likelihood and so minimizes an estimate of the KL divergence from the
+    /// data distribution
+    LikelihoodCv,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum KdeError {
+    /// There are no observations
+    EmptySample,
+    /// An observation is infinite or NaN
+    SampleNotFinite { x: f64 },
+    /// The number of weights differs from the number of observations
+    WeightsLengthMismatch { n_xs: usize, n_weights: usize },
+    /// A weight is negative, infinite, or NaN
+    InvalidWeight { weight: f64 },
+    /// The weights sum to zero
+    WeightsSumToZero,
+    /// The bandwidth is less than or equal to zero
+    BandwidthTooLow { bandwidth: f64 },
+    /// The bandwidth is infinite or NaN
+    BandwidthNotFinite { bandwidth: f64 },
+    /// The sample has no spread, so no bandwidth can be selected
+    ZeroSpread,
+}
+
+impl Kde {
+    /// Create a new `Kde` from equally weighted observations
+    pub fn new(xs: Vec<f64>, bandwidth: f64) -> Result<Self, KdeError> {
+        let weights = vec![1.0; xs.len()];
+        Self::new_weighted(xs, weights, bandwidth)
+    }
+
+    /// Create a new `Kde` from weighted observations. The weights need not
+    /// sum to one.
+    pub fn new_weighted(
+        xs: Vec<f64>,
+        weights: Vec<f64>,
+        bandwidth: f64,
+    ) -> Result<Self, KdeError> {
+        let weights = validate(&xs, weights)?;
+        validate_bandwidth(bandwidth)?;
+        Ok(Kde {
+            xs,
+            weights,
+            bandwidth,
+        })
+    }
+
+    /// Create a new `Kde` from equally weighted observations, selecting the
+    /// bandwidth with `method`
+    pub fn from_sample(
+        xs: Vec<f64>,
+        method: Bandwidth,
+    ) -> Result<Self, KdeError> {
+        let weights = vec![1.0; xs.len()];
+        Self::from_weighted_sample(xs, weights, method)
+    }
+
+    /// Create a new `Kde` from weighted observations, selecting the
+    /// bandwidth with `method`
+    ///
+    /// # Example
+    ///
+    /// Observations with zero weight are ignored
+    ///
+    /// ```
+    /// use rv::dist::{Bandwidth, Kde};
+    ///
+    /// let xs = vec![0.1, 0.5, 1.3, 2.0, 2.2, 3.7, 100.0];
+    /// let weights = vec![1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0];
+    ///
+    /// let weighted =
+    ///     Kde::from_weighted_sample(xs, weights, Bandwidth::LeastSquaresCv)
+    ///         .unwrap();
+    /// let kde = Kde::from_sample(
+    ///     vec![0.1, 0.5, 1.3, 2.0, 2.2, 3.7],
+    ///     Bandwidth::LeastSquaresCv,
+    /// )
+    /// .unwrap();
+    /// assert!((weighted.bandwidth() - kde.bandwidth()).abs() < 1E-8);
+    /// ```
+    pub fn from_weighted_sample(
+        xs: Vec<f64>,
+        weights: Vec<f64>,
+        method: Bandwidth,
+    ) -> Result<Self, KdeError> {
+        let weights = validate(&xs, weights)?;
+        let bandwidth = select_bandwidth(&xs, &weights, method)?;
+        Ok(Kde {
+            xs,
+            weights,
+            bandwidth,
+        })
+    }
+
+    /// Creates a new Kde without checking whether the parameters are valid.
+    /// The weights must sum to one.
+    #[inline]
+    pub fn new_unchecked(
+        xs: Vec<f64>,
+        weights: Vec<f64>,
+        bandwidth: f64,
+    ) -> Self {
+        Kde {
+            xs,
+            weights,
+            bandwidth,
+        }
+    }
+
+    /// The observations
+    #[inline]
+    pub fn xs(&self) -> &[f64] {
+        &self.xs
+    }
+
+    /// The normalized weights of the observations
+    #[inline]
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Get the bandwidth
+    #[inline]
+    pub fn bandwidth(&self) -> f64 {
+        self.bandwidth
+    }
+
+    /// Set the bandwidth
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rv::dist::Kde;
+    /// let mut kde = Kde::new(vec![0.0, 1.0], 0.5).unwrap();
+    ///
+    /// assert!(kde.set_bandwidth(0.2).is_ok());
+    /// assert_eq!(kde.bandwidth(), 0.2);
+    /// assert!(kde.set_bandwidth(0.0).is_err());
+    /// assert!(kde.set_bandwidth(f64::NAN).is_err());
+    /// ```
+    #[inline]
+    pub fn set_bandwidth(&mut self, bandwidth: f64) -> Result<(), KdeError> {
+        validate_bandwidth(bandwidth)?;
+        self.set_bandwidth_unchecked(bandwidth);
+        Ok(())
+    }
+
+    /// Set the bandwidth without input validation
+    #[inline]
+    pub fn set_bandwidth_unchecked(&mut self, bandwidth: f64) {
+        self.bandwidth = bandwidth
+    }
+
+    /// Select a bandwidth for weighted observations with `method`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::{Bandwidth, Kde};
+    ///
+    /// let xs: Vec<f64> = (0..100).map(|i| f64::from(i) / 10.0).collect();
+    /// let weights = vec![1.0; 100];
+    ///
+    /// let h = Kde::select_bandwidth(&xs, &weights, Bandwidth::LeastSquaresCv)
+    ///     .unwrap();
+    /// assert!(h > 0.0);
+    /// ```
+    pub fn select_bandwidth(
+        xs: &[f64],
+        weights: &[f64],
+        method: Bandwidth,
+    ) -> Result<f64, KdeError> {
+        let weights = validate(xs, weights.to_vec())?;
+        select_bandwidth(xs, &weights, method)
+    }
+}
+
+fn validate(xs: &[f64], mut weights: Vec<f64>) -> Result<Vec<f64>, KdeError> {
+    if xs.is_empty() {
+        return Err(KdeError::EmptySample);
+    } else if xs.len() != weights.len() {
+        return Err(KdeError::WeightsLengthMismatch {
+            n_xs: xs.len(),
+            n_weights: weights.len(),
+        });
+    }
+    if let Some(&x) = xs.iter().find(|x| !x.is_finite()) {
+        return Err(KdeError::SampleNotFinite { x });
+    }
+    if let Some(&weight) =
+        weights.iter().find(|w| !(w.is_finite() && **w >= 0.0))
+    {
+        return Err(KdeError::InvalidWeight { weight });
+    }
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return Err(KdeError::WeightsSumToZero);
+    }
+    weights.iter_mut().for_each(|w| *w /= total);
+    Ok(weights)
+}
+
+fn validate_bandwidth(bandwidth: f64) -> Result<(), KdeError> {
+    if !bandwidth.is_finite() {
+        Err(KdeError::BandwidthNotFinite { bandwidth })
+    } else if bandwidth <= 0.0 {
+        Err(KdeError::BandwidthTooLow { bandwidth })
+    } else {
+        Ok(())
+    }
+}
+
+/// Density of N(0, h²) at `d`
+fn gauss_kernel(d: f64, h: f64) -> f64 {
+    let z = d / h;
+    (-0.5_f64).mul_add(z * z, -HALF_LN_2PI).exp() / h
+}
+
+/// Weighted quantile of sorted `(x, w)` pairs with weights summing to one
+fn weighted_quantile(pairs: &[(f64, f64)], p: f64) -> f64 {
+    let mut cum = 0.0;
+    for &(x, w) in pairs {
+        cum += w;
+        if cum >= p {
+            return x;
+        }
+    }
+    pairs[pairs.len() - 1].0
+}
+
+/// Silverman's rule of thumb with Kish's effective sample size, `1 / Σ wᵢ²`
+fn silverman(xs: &[f64], weights: &[f64]) -> Result<f64, KdeError> {
+    let mean: f64 = xs.iter().zip(weights).map(|(x, w)| w * x).sum();
+    let var: f64 = xs
+        .iter()
+        .zip(weights)
+        .map(|(x, w)| w * (x - mean) * (x - mean))
+        .sum();
+
+    let mut pairs: Vec<(f64, f64)> =
+        xs.iter().copied().zip(weights.iter().copied()).collect();
+    pairs.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    let iqr = weighted_quantile(&pairs, 0.75) - weighted_quantile(&pairs, 0.25);
+
+    let spread = if iqr > 0.0 {
+        var.sqrt().min(iqr / 1.34)
+    } else {
+        var.sqrt()
+    };
+    if spread <= 0.0 {
+        return Err(KdeError::ZeroSpread);
+    }
+    let n_eff = weights.iter().map(|w| w * w).sum::<f64>().recip();
+    Ok(0.9 * spread * n_eff.powf(-0.2))
+}
+
+/// The data, or its linear binning when the sample is large, used to score
+/// bandwidths by cross-validation
+enum CvData<'a> {
+    Exact {
+        xs: &'a [f64],
+        weights: &'a [f64],
+    },
+    Binned {
+        /// Grid spacing
+        delta: f64,
+        /// Weight assigned to each grid point
+        counts: Vec<f64>,
+    },
+}
+
+impl<'a> CvData<'a> {
+    fn new(xs: &'a [f64], weights: &'a [f64]) -> Self {
+        if xs.len() <= N_BINS {
+            return CvData::Exact { xs, weights };
+        }
+        let (min, max) = xs
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &x| {
+                (lo.min(x), hi.max(x))
+            });
+        let delta = (max - min) / (N_BINS - 1) as f64;
+        let mut counts = vec![0.0; N_BINS];
+        for (x, w) in xs.iter().zip(weights) {
+            let pos = (x - min) / delta;
+            let ix = (pos.floor() as usize).min(N_BINS - 2);
+            let frac = pos - ix as f64;
+            counts[ix] += w * (1.0 - frac);
+            counts[ix + 1] += w * frac;
+        }
+        CvData::Binned { delta, counts }
+    }
+
+    /// Sum over pairs of `w_i w_j k(x_i - x_j)`, including `i = j`
+    fn pair_sum<K: Fn(f64) -> f64>(&self, kernel: K, radius: f64) -> f64 {
+        match self {
+            CvData::Exact { xs, weights } => xs
+                .iter()
+                .zip(weights.iter())
+                .map(|(xi, wi)| {
+                    wi * xs
+                        .iter()
+                        .zip(weights.iter())
+                        .map(|(xj, wj)| wj * kernel(xi - xj))
+                        .sum::<f64>()
+                })
+                .sum(),
+            CvData::Binned { delta, counts } => {
+                let smoothed = binned_smooth(counts, *delta, kernel, radius);
+                counts.iter().zip(smoothed).map(|(c, s)| c * s).sum()
+            }
+        }
+    }
+
+    /// Sum of `w_i ln f_{-i}(x_i)` for the leave-one-out estimates, where
+    /// `sum_w_sq` is Σ wᵢ²
+    fn leave_one_out_ln_lik(&self, h: f64, sum_w_sq: f64) -> f64 {
+        let kernel = |d: f64| gauss_kernel(d, h);
+        let self_term = kernel(0.0);
+        match self {
+            CvData::Exact { xs, weights } => (0..xs.len())
+                .filter(|&i| weights[i] > 0.0)
+                .map(|i| {
+                    let ln_terms: Vec<f64> = (0..xs.len())
+                        .filter(|&j| j != i)
+                        .map(|j| {
+                            let z = (xs[i] - xs[j]) / h;
+                            (-0.5 * z).mul_add(z, weights[j].ln())
+                        })
+                        .collect();
+                    let ln_f = if ln_terms.is_empty() {
+                        f64::NEG_INFINITY
+                    } else {
+                        logsumexp(&ln_terms)
+                            - HALF_LN_2PI
+                            - h.ln()
+                            - (-weights[i]).ln_1p()
+                    };
+                    weights[i] * ln_f
+                })
+                .sum(),
+            CvData::Binned { delta, counts } => {
+                let smoothed =
+                    binned_smooth(counts, *delta, kernel, KERNEL_RADIUS * h);
+                // Remove the average contribution of an observation to its
+                // own estimate. Floor at the smallest positive density to
+                // keep isolated observations finite.
+                counts
+                    .iter()
+                    .zip(smoothed)
+                    .filter(|(c, _)| **c > 0.0)
+                    .map(|(c, s)| {
+                        let f = (sum_w_sq.mul_add(-self_term, s)
+                            / (1.0 - sum_w_sq))
+                            .max(f64::MIN_POSITIVE);
+                        c * f.ln()
+                    })
+                    .sum()
+            }
+        }
+    }
+}
+
+/// `Σ_l c_l k(δ (m - l))` at every grid point, `m`, truncating the kernel at
+/// `radius`
+fn binned_smooth<K: Fn(f64) -> f64>(
+    counts: &[f64],
+    delta: f64,
+    kernel: K,
+    radius: f64,
+) -> Vec<f64> {
+    let n = counts.len();
+    let n_lags = ((radius / delta).ceil() as usize).min(n - 1);
+    let lags: Vec<f64> =
+        (0..=n_lags).map(|d| kernel(d as f64 * delta)).collect();
+    (0..n)
+        .map(|m| {
+            let lo = m.saturating_sub(n_lags);
+            let hi = (m + n_lags).min(n - 1);
+            (lo..=hi).map(|l| counts[l] * lags[m.abs_diff(l)]).sum()
+        })
+        .collect()
+}
+
+/// Least-squares cross-validation score, `∫f² - 2 Σ wᵢ f_{-i}(xᵢ)`
+///
+/// The leave-one-out estimates are renormalized by `1 - Σ wᵢ²` rather than
+/// each `1 - wᵢ`, which is exact for equal weights.
+fn lscv_score(data: &CvData, h: f64, sum_w_sq: f64) -> f64 {
+    let h2 = std::f64::consts::SQRT_2 * h;
+    let int_f_sq = data.pair_sum(|d| gauss_kernel(d, h2), KERNEL_RADIUS * h2);
+    let pairs = data.pair_sum(|d| gauss_kernel(d, h), KERNEL_RADIUS * h);
+    let loo = sum_w_sq.mul_add(-gauss_kernel(0.0, h), pairs) / (1.0 - sum_w_sq);
+    2.0_f64.mul_add(-loo, int_f_sq)
+}
+
+fn select_bandwidth(
+    xs: &[f64],
+    weights: &[f64],
+    method: Bandwidth,
+) -> Result<f64, KdeError> {
+    let h_ref = silverman(xs, weights)?;
+    let sum_w_sq: f64 = weights.iter().map(|w| w * w).sum();
+    if method == Bandwidth::Silverman || sum_w_sq >= 1.0 {
+        return Ok(h_ref);
+    }
+
+    let data = CvData::new(xs, weights);
+    let loss = |ln_h: f64| {
+        let h = ln_h.exp();
+        match method {
+            Bandwidth::LeastSquaresCv => lscv_score(&data, h, sum_w_sq),
+            _ => -data.leave_one_out_ln_lik(h, sum_w_sq),
+        }
+    };
+
+    // Cross-validation scores can have several local optima, so search a
+    // coarse log grid around the rule of thumb before refining
+    let n_grid = 40;
+    let (ln_lo, ln_hi) = ((h_ref / 30.0).ln(), (h_ref * 3.0).ln());
+    let step = (ln_hi - ln_lo) / (n_grid - 1) as f64;
+    let best = (0..n_grid)
+        .map(|i| {
+            let ln_h = step.mul_add(f64::from(i), ln_lo);
+            (ln_h, loss(ln_h))
+        })
+        .fold((ln_lo, f64::INFINITY), |best, cur| {
+            if cur.1 < best.1 {
+                cur
+            } else {
+                best
+            }
+        });
+
+    Ok(golden_section(loss, best.0 - step, best.0 + step, 1E-6).exp())
+}
+
+/// Minimize `f` on `[a, b]` by golden-section search
+fn golden_section<F: Fn(f64) -> f64>(f: F, a: f64, b: f64, tol: f64) -> f64 {
+    let inv_phi = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let (mut a, mut b) = (a, b);
+    let mut c = inv_phi.mul_add(a - b, b);
+    let mut d = inv_phi.mul_add(b - a, a);
+    let (mut fc, mut fd) = (f(c), f(d));
+    while (b - a).abs() > tol {
+        if fc < fd {
+            b = d;
+            d = c;
+            fd = fc;
+            c = inv_phi.mul_add(a - b, b);
+            fc = f(c);
+        } else {
+            a = c;
+            c = d;
+            fc = fd;
+            d = inv_phi.mul_add(b - a, a);
+            fd = f(d);
+        }
+    }
+    (a + b) / 2.0
+}
+
+impl Rv<f64> for Kde {
+    fn ln_f(&self, x: &f64) -> f64 {
+        let ln_terms: Vec<f64> = self
+            .xs
+            .iter()
+            .zip(self.weights.iter())
+            .filter(|(_, w)| **w > 0.0)
+            .map(|(xi, w)| {
+                let z = (x - xi) / self.bandwidth;
+                (-0.5 * z).mul_add(z, w.ln())
+            })
+            .collect();
+        logsumexp(&ln_terms) - self.bandwidth.ln() - HALF_LN_2PI
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> f64 {
+        let ix = WeightedIndex::new(&self.weights).unwrap().sample(rng);
+        let z: f64 = Gaussian::standard().draw(rng);
+        self.bandwidth.mul_add(z, self.xs[ix])
+    }
+
+    fn sample<R: Rng>(&self, n: usize, rng: &mut R) -> Vec<f64> {
+        let ixs = WeightedIndex::new(&self.weights).unwrap();
+        let g = Gaussian::standard();
+        (0..n)
+            .map(|_| {
+                let z: f64 = g.draw(rng);
+                self.bandwidth.mul_add(z, self.xs[ixs.sample(rng)])
+            })
+            .collect()
+    }
+}
+
+impl Support<f64> for Kde {
+    fn supports(&self, x: &f64) -> bool {
+        x.is_finite()
+    }
+}
+
+impl ContinuousDistr<f64> for Kde {}
+
+impl Cdf<f64> for Kde {
+    fn cdf(&self, x: &f64) -> f64 {
+        let g = Gaussian::standard();
+        self.xs
+            .iter()
+            .zip(self.weights.iter())
+            .map(|(xi, w)| w * g.cdf(&((x - xi) / self.bandwidth)))
+            .sum()
+    }
+}
+
+impl Mean<f64> for Kde {
+    fn mean(&self) -> Option<f64> {
+        Some(
+            self.xs
+                .iter()
+                .zip(self.weights.iter())
+                .map(|(x, w)| w * x)
+                .sum(),
+        )
+    }
+}
+
+impl Variance<f64> for Kde {
+    fn variance(&self) -> Option<f64> {
+        let mean: f64 = self.mean()?;
+        let var: f64 = self
+            .xs
+            .iter()
+            .zip(self.weights.iter())
+            .map(|(x, w)| w * (x - mean) * (x - mean))
+            .sum();
+        Some(self.bandwidth.mul_add(self.bandwidth, var))
+    }
+}
+
+impl std::error::Error for KdeError {}
+
+impl fmt::Display for KdeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptySample => write!(f, "the sample is empty"),
+            Self::SampleNotFinite { x } => {
+                write!(f, "non-finite observation: {}", x)
+            }
+            Self::WeightsLengthMismatch { n_xs, n_weights } => write!(
+                f,
+                "{} weights given for {} observations",
+                n_weights, n_xs
+            ),
+            Self::InvalidWeight { weight } => {
+                write!(f, "weight ({}) must be finite and non-negative", weight)
+            }
+            Self::WeightsSumToZero => write!(f, "the weights sum to zero"),
+            Self::BandwidthTooLow { bandwidth } => {
+                write!(f, "bandwidth ({}) must be greater than zero", bandwidth)
+            }
+            Self::BandwidthNotFinite { bandwidth } => {
+                write!(f, "non-finite bandwidth: {}", bandwidth)
+            }
+            Self::ZeroSpread => write!(f, "the observations have no spread"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::misc::ks_test;
+    use crate::test_basic_impls;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    test_basic_impls!(
+        [continuous] Kde::new(vec![0.1, 0.4, 1.2], 0.3).unwrap()
+    );
+
+    fn bimodal(n: usize, seed: u64) -> Vec<f64> {
+        let mut rng = Xoshiro256Plus::seed_from_u64(seed);
+        let g = Gaussian::standard();
+        (0..n)
+            .map(|i| {
+                let z: f64 = g.draw(&mut rng);
+                if i % 2 == 0 {
+                    0.5_f64.mul_add(z, -3.0)
+                } else {
+                    0.5_f64.mul_add(z, 3.0)
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn new_validates_inputs() {
+        assert_eq!(Kde::new(vec![], 1.0), Err(KdeError::EmptySample));
+        assert!(Kde::new(vec![0.0, f64::NAN], 1.0).is_err());
+        assert!(Kde::new(vec![0.0], 0.0).is_err());
+        assert!(Kde::new_weighted(vec![0.0, 1.0], vec![1.0], 1.0).is_err());
+        assert!(
+            Kde::new_weighted(vec![0.0, 1.0], vec![1.0, -1.0], 1.0).is_err()
+        );
+        assert_eq!(
+            Kde::new_weighted(vec![0.0, 1.0], vec![0.0, 0.0], 1.0),
+            Err(KdeError::WeightsSumToZero)
+        );
+    }
+
+    #[test]
+    fn single_point_is_gaussian() {
+        let kde = Kde::new(vec![1.5], 0.7).unwrap();
+        let g = Gaussian::new(1.5, 0.7).unwrap();
+        for x in [-1.0_f64, 0.3, 1.5, 4.0] {
+            assert::close(kde.ln_f(&x), g.ln_f(&x), TOL);
+            assert::close(kde.cdf(&x), g.cdf(&x), TOL);
+        }
+    }
+
+    #[test]
+    fn weights_match_repeated_points() {
+        let weighted =
+            Kde::new_weighted(vec![0.0, 2.0], vec![3.0, 1.0], 0.5).unwrap();
+        let repeated = Kde::new(vec![0.0, 0.0, 0.0, 2.0], 0.5).unwrap();
+        for x in [-1.0_f64, 0.5, 1.8] {
+            assert::close(weighted.ln_f(&x), repeated.ln_f(&x), TOL);
+        }
+        assert::close(
+            weighted.variance().unwrap(),
+            repeated.variance().unwrap(),
+            TOL,
+        );
+    }
+
+    #[test]
+    fn lscv_score_matches_brute_force() {
+        let xs = [0.1, 0.5, 1.3, 2.0, 2.2, 3.7];
+        let weights = vec![1.0 / 6.0; 6];
+        let h = 0.4;
+        let data = CvData::new(&xs, &weights);
+        let score = lscv_score(&data, h, 1.0 / 6.0);
+
+        // ∫f² - 2/n Σ f_{-i}(x_i)
+        let kde = Kde::new(xs.to_vec(), h).unwrap();
+        let int_f_sq: f64 = (0..32)
+            .map(|i| {
+                let a = 0.25_f64.mul_add(f64::from(i), -2.0);
+                crate::misc::gauss_legendre_quadrature(
+                    |x| kde.f(&x).powi(2),
+                    16,
+                    (a, a + 0.25),
+                )
+            })
+            .sum();
+        let loo: f64 = (0..6)
+            .map(|i| {
+                let others: Vec<f64> =
+                    (0..6).filter(|&j| j != i).map(|j| xs[j]).collect();
+                Kde::new(others, h).unwrap().f(&xs[i])
+            })
+            .sum::<f64>()
+            / 6.0;
+        assert::close(score, 2.0_f64.mul_add(-loo, int_f_sq), 1E-6);
+    }
+
+    #[test]
+    fn binned_scores_approximate_exact() {
+        let xs = bimodal(2000, 1);
+        let weights = vec![1.0 / 2000.0; 2000];
+        let binned = CvData::new(&xs, &weights);
+        let exact = CvData::Exact {
+            xs: &xs,
+            weights: &weights,
+        };
+        let h = 0.3;
+        let sum_w_sq = 1.0 / 2000.0;
+        assert::close(
+            lscv_score(&binned, h, sum_w_sq),
+            lscv_score(&exact, h, sum_w_sq),
+            1E-3,
+        );
+        assert::close(
+            binned.leave_one_out_ln_lik(h, sum_w_sq),
+            exact.leave_one_out_ln_lik(h, sum_w_sq),
+            1E-2,
+        );
+    }
+
+    #[test]
+    fn cv_beats_rule_of_thumb_on_bimodal() {
+        // Silverman's rule oversmooths well separated modes
+        let xs = bimodal(400, 2);
+        let h_rot =
+            Kde::select_bandwidth(&xs, &[1.0; 400], Bandwidth::Silverman)
+                .unwrap();
+        for method in [Bandwidth::LeastSquaresCv, Bandwidth::LikelihoodCv] {
+            let h = Kde::select_bandwidth(&xs, &[1.0; 400], method).unwrap();
+            assert!(h < 0.6 * h_rot, "{:?}: {} vs {}", method, h, h_rot);
+            assert!(h > 0.05, "{:?}: {}", method, h);
+        }
+    }
+
+    #[test]
+    fn large_sample_selection() {
+        let xs = bimodal(100_000, 3);
+        let kde = Kde::from_sample(xs, Bandwidth::LikelihoodCv).unwrap();
+        // The AMISE optimal bandwidth for the equal mixture of the well
+        // separated N(±3, 0.5²) is about 0.06
+        assert!((kde.bandwidth() - 0.06).abs() < 0.03, "{}", kde.bandwidth());
+    }
+
+    #[test]
+    fn draws_follow_cdf() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(4);
+        let kde = Kde::new(vec![-1.0, 0.0, 2.5], 0.4).unwrap();
+        let xs: Vec<f64> = kde.sample(1000, &mut rng);
+        let (_, p) = ks_test(&xs, |x| kde.cdf(&x));
+        assert!(p > 0.01);
+    }
+}
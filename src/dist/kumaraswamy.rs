@@ -3,9 +3,11 @@
 use serde::{Deserialize, Serialize};
 
 use crate::consts::EULER_MASCERONI;
+use crate::dist::Beta;
 use crate::impl_display;
 use crate::traits::*;
 use rand::Rng;
+use special::Beta as _;
 use special::Gamma as _;
 use std::f64;
 use std::fmt;
@@ -322,6 +324,115 @@ impl Kumaraswamy {
     fn ab_ln(&self) -> f64 {
         *self.ab_ln.get_or_init(|| self.a.ln() + self.b.ln())
     }
+
+    /// E[x^n] = b B(1 + n/a, b)
+    fn raw_moment(&self, n: f64) -> f64 {
+        self.b * (1.0 + n / self.a).ln_beta(self.b).exp()
+    }
+
+    /// The second through fourth central moments
+    fn central_moments(&self) -> (f64, f64, f64) {
+        let m = self.raw_moment(1.0);
+        let m2 = self.raw_moment(2.0);
+        let m3 = self.raw_moment(3.0);
+        let m4 = self.raw_moment(4.0);
+        let mm = m * m;
+        let mu2 = m.mul_add(-m, m2);
+        let mu3 = (3.0 * m).mul_add(-m2, 2.0_f64.mul_add(mm * m, m3));
+        let mu4 = (3.0 * mm)
+            .mul_add(-mm, (4.0 * m).mul_add(-m3, (6.0 * mm).mul_add(m2, m4)));
+        (mu2, mu3, mu4)
+    }
+
+    /// The KL divergence, KL(K | B), from `beta` to this distribution
+    ///
+    /// Uses the closed form of Nalisnick & Smyth (2017), which has one
+    /// infinite series.
+    ///
+    /// # Example
+    ///
+    /// The Kumaraswamy is often used in place of a Beta, e.g., as the
+    /// variational posterior of stick-breaking weights, because its
+    /// quantile function is closed form. The KL divergence measures the cost
+    /// of that substitution.
+    ///
+    /// ```
+    /// use rv::dist::{Beta, Kumaraswamy};
+    ///
+    /// let beta = Beta::new(1.0, 3.0).unwrap();
+    ///
+    /// // Kumaraswamy(1, b) is Beta(1, b)
+    /// let kuma = Kumaraswamy::new(1.0, 3.0).unwrap();
+    /// assert!(kuma.kl_to_beta(&beta).abs() < 1E-8);
+    ///
+    /// let kuma = Kumaraswamy::new(1.2, 3.0).unwrap();
+    /// assert!(kuma.kl_to_beta(&beta) > 0.0);
+    /// ```
+    pub fn kl_to_beta(&self, beta: &Beta) -> f64 {
+        let (a, b) = (self.a, self.b);
+        let (alpha, beta_b) = (beta.alpha(), beta.beta());
+        // E[ln x] and E[ln(1 - x)] under this distribution
+        let e_ln_x = -(EULER_MASCERONI + b.digamma() + b.recip()) / a;
+        let e_ln_1mx = -power_law_sum(
+            |m| self.raw_moment(m) / m,
+            (b + 1.0).gamma() * a.powf(b),
+            b,
+        );
+        let cross = (beta_b - 1.0).mul_add(
+            e_ln_1mx,
+            (alpha - 1.0).mul_add(e_ln_x, -alpha.ln_beta(beta_b)),
+        );
+        -self.entropy() - cross
+    }
+
+    /// The KL divergence, KL(B | K), from this distribution to `beta`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::{Beta, Kumaraswamy};
+    ///
+    /// let kuma = Kumaraswamy::new(2.0, 3.0).unwrap();
+    /// let beta = Beta::new(1.5, 4.0).unwrap();
+    ///
+    /// // KL divergences are not symmetric
+    /// let kl_kb = kuma.kl_to_beta(&beta);
+    /// let kl_bk = kuma.kl_from_beta(&beta);
+    /// assert!((kl_kb - 0.4799).abs() < 1E-4);
+    /// assert!((kl_bk - 0.4632).abs() < 1E-4);
+    /// ```
+    pub fn kl_from_beta(&self, beta: &Beta) -> f64 {
+        let (a, b) = (self.a, self.b);
+        let (alpha, beta_b) = (beta.alpha(), beta.beta());
+        let ln_beta_ab = alpha.ln_beta(beta_b);
+        // E[ln x] and E[ln(1 - x^a)] under the Beta
+        let e_ln_x = alpha.digamma() - (alpha + beta_b).digamma();
+        let e_ln_1mxa = -power_law_sum(
+            |m| (a.mul_add(m, alpha).ln_beta(beta_b) - ln_beta_ab).exp() / m,
+            (beta_b.mul_add(-a.ln(), beta_b.ln_gamma().0) - ln_beta_ab).exp(),
+            beta_b,
+        );
+        let cross = (b - 1.0)
+            .mul_add(e_ln_1mxa, (a - 1.0).mul_add(e_ln_x, self.ab_ln()));
+        -beta.entropy() - cross
+    }
+}
+
+/// Σ_{m ≥ 1} term(m) for terms that decay like c m^-(s + 1)
+///
+/// If the series has not converged after a fixed number of terms, the rest
+/// is estimated by the integral of c x^-(s + 1).
+fn power_law_sum<F: Fn(f64) -> f64>(term: F, c: f64, s: f64) -> f64 {
+    const MAX_TERMS: usize = 100_000;
+    let mut sum = 0.0;
+    for m in 1..=MAX_TERMS {
+        let t = term(m as f64);
+        sum += t;
+        if t <= f64::EPSILON * sum {
+            return sum;
+        }
+    }
+    sum + c * (MAX_TERMS as f64 + 0.5).powf(-s) / s
 }
 
 #[inline]
@@ -410,11 +521,42 @@ impl Entropy for Kumaraswamy {
     fn entropy(&self) -> f64 {
         // Harmonic function for reals see:
         // https://en.wikipedia.org/wiki/Harmonic_number#Harmonic_numbers_for_real_and_complex_values
-        let hb = self.b.digamma() + EULER_MASCERONI;
+        let hb = (self.b + 1.0).digamma() + EULER_MASCERONI;
         (1.0 - self.a.recip()).mul_add(hb, 1.0 - self.b.recip()) - self.ab_ln()
     }
 }
 
+impl Variance<f64> for Kumaraswamy {
+    fn variance(&self) -> Option<f64> {
+        let (mu2, _, _) = self.central_moments();
+        Some(mu2)
+    }
+}
+
+impl Skewness for Kumaraswamy {
+    fn skewness(&self) -> Option<f64> {
+        let (mu2, mu3, _) = self.central_moments();
+        Some(mu3 / (mu2 * mu2.sqrt()))
+    }
+}
+
+impl Kurtosis for Kumaraswamy {
+    fn kurtosis(&self) -> Option<f64> {
+        let (mu2, _, mu4) = self.central_moments();
+        Some(mu4 / (mu2 * mu2) - 3.0)
+    }
+}
+
+impl CacheControl for Kumaraswamy {
+    fn precompute(&self) {
+        self.ab_ln();
+    }
+
+    fn invalidate(&mut self) {
+        self.ab_ln = OnceLock::new();
+    }
+}
+
 impl std::error::Error for KumaraswamyError {}
 
 impl fmt::Display for KumaraswamyError {
@@ -581,6 +723,89 @@ mod tests {
             .for_each(|p: &f64| equiv(p + 1_f64))
     }
 
+    #[test]
+    fn moments() {
+        // Reference values from mpmath
+        let kuma = Kumaraswamy::new(2.0, 3.0).unwrap();
+        assert::close(
+            kuma.variance().unwrap(),
+            0.041_020_408_163_265_31,
+            1E-12,
+        );
+        assert::close(
+            kuma.skewness().unwrap(),
+            0.071_119_425_280_601_62,
+            1E-10,
+        );
+        assert::close(
+            kuma.kurtosis().unwrap(),
+            -0.734_247_502_124_534_9,
+            1E-10,
+        );
+
+        // K(1, b) = B(1, b)
+        let kuma = Kumaraswamy::new(1.0, 2.5).unwrap();
+        let beta = Beta::new(1.0, 2.5).unwrap();
+        assert::close(
+            kuma.variance().unwrap(),
+            beta.variance().unwrap(),
+            1E-12,
+        );
+        assert::close(
+            kuma.skewness().unwrap(),
+            beta.skewness().unwrap(),
+            1E-10,
+        );
+        assert::close(
+            kuma.kurtosis().unwrap(),
+            beta.kurtosis().unwrap(),
+            1E-10,
+        );
+    }
+
+    #[test]
+    fn entropy() {
+        // Reference value from quadrature in mpmath
+        let kuma = Kumaraswamy::new(2.0, 3.0).unwrap();
+        assert::close(kuma.entropy(), -0.208_426_135_894_721_67, 1E-12);
+
+        // K(1, b) = B(1, b)
+        let kuma = Kumaraswamy::new(1.0, 2.5).unwrap();
+        let beta = Beta::new(1.0, 2.5).unwrap();
+        assert::close(kuma.entropy(), beta.entropy(), 1E-12);
+    }
+
+    #[test]
+    fn kl_divergences_to_and_from_beta() {
+        // Reference values from quadrature in mpmath
+        let cases = [
+            (
+                (2.0, 3.0),
+                (1.5, 4.0),
+                0.479_922_733_202_148_5,
+                0.463_168_982_806_617_3,
+            ),
+            (
+                (0.5, 0.3),
+                (0.7, 0.4),
+                0.040_456_983_146_000,
+                0.032_736_753_597_254_9,
+            ),
+            (
+                (1.3, 2.2),
+                (1.3, 2.2),
+                0.002_617_446_583_675_04,
+                0.002_625_803_601_069_84,
+            ),
+        ];
+        for ((a, b), (alpha, beta), kl_kb, kl_bk) in cases {
+            let kuma = Kumaraswamy::new(a, b).unwrap();
+            let beta = Beta::new(alpha, beta).unwrap();
+            assert::close(kuma.kl_to_beta(&beta), kl_kb, 1E-6);
+            assert::close(kuma.kl_from_beta(&beta), kl_bk, 1E-6);
+        }
+    }
+
     #[test]
     fn no_mode_for_a_or_b_less_than_1() {
         fn mode(a: f64, b: f64) -> Option<f64> {
@@ -0,0 +1,282 @@
+//! Categorical distribution over string labels
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::{LabelMap, LabelMapError};
+use crate::dist::{Categorical, CategoricalError};
+use crate::impl_display;
+use crate::traits::*;
+use rand::Rng;
+use std::fmt;
+
+/// [Categorical distribution](https://en.wikipedia.org/wiki/Categorical_distribution)
+/// over a set of string labels.
+///
+/// Owns a [`LabelMap`] between the labels and the indices of an inner
+/// [`Categorical`], so that labels and weights need not be kept in parallel.
+/// Labels not in the map have probability zero.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::LabeledCategorical;
+/// use rv::traits::*;
+///
+/// let pos = LabeledCategorical::from_pairs([
+///     ("noun", 5.0),
+///     ("verb", 3.0),
+///     ("adjective", 2.0),
+/// ])
+/// .unwrap();
+///
+/// assert::close(pos.f_label("verb"), 0.3, 1E-12);
+/// assert_eq!(pos.f_label("adverb"), 0.0);
+///
+/// let mut rng = rand::thread_rng();
+/// let label: &str = pos.draw_label(&mut rng);
+/// assert!(pos.labels().contains(label));
+///
+/// // Also a distribution over owned labels
+/// let label: String = pos.draw(&mut rng);
+/// assert!(pos.supports(&label));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct LabeledCategorical {
+    labels: LabelMap,
+    categorical: Categorical,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum LabeledCategoricalError {
+    /// The labels are invalid
+    LabelMap(LabelMapError),
+    /// The weights are invalid
+    Categorical(CategoricalError),
+    /// The number of labels and weights differ
+    LengthMismatch { n_labels: usize, n_weights: usize },
+}
+
+impl LabeledCategorical {
+    /// Create a new LabeledCategorical from labels and the weight of each
+    /// label. The weights do not need to sum to one.
+    pub fn new(
+        labels: LabelMap,
+        weights: &[f64],
+    ) -> Result<Self, LabeledCategoricalError> {
+        let categorical = Categorical::new(weights)
+            .map_err(LabeledCategoricalError::Categorical)?;
+        Self::from_parts(labels, categorical)
+    }
+
+    /// Create a new LabeledCategorical from (label, weight) pairs
+    pub fn from_pairs<S: Into<String>>(
+        pairs: impl IntoIterator<Item = (S, f64)>,
+    ) -> Result<Self, LabeledCategoricalError> {
+        let (labels, weights): (Vec<S>, Vec<f64>) = pairs.into_iter().unzip();
+        let labels =
+            LabelMap::new(labels).map_err(LabeledCategoricalError::LabelMap)?;
+        Self::new(labels, &weights)
+    }
+
+    /// Label the categories of `categorical`
+    pub fn from_parts(
+        labels: LabelMap,
+        categorical: Categorical,
+    ) -> Result<Self, LabeledCategoricalError> {
+        if labels.len() == categorical.k() {
+            Ok(LabeledCategorical {
+                labels,
+                categorical,
+            })
+        } else {
+            Err(LabeledCategoricalError::LengthMismatch {
+                n_labels: labels.len(),
+                n_weights: categorical.k(),
+            })
+        }
+    }
+
+    /// Creates a new LabeledCategorical without checking whether there is
+    /// one label per category.
+    #[inline]
+    pub fn from_parts_unchecked(
+        labels: LabelMap,
+        categorical: Categorical,
+    ) -> Self {
+        LabeledCategorical {
+            labels,
+            categorical,
+        }
+    }
+
+    /// The map between labels and category indices
+    #[inline]
+    pub fn labels(&self) -> &LabelMap {
+        &self.labels
+    }
+
+    /// The distribution over category indices
+    #[inline]
+    pub fn categorical(&self) -> &Categorical {
+        &self.categorical
+    }
+
+    /// Get the number of labels
+    #[inline]
+    pub fn k(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// The probability of `label`
+    #[inline]
+    pub fn f_label(&self, label: &str) -> f64 {
+        self.ln_f_label(label).exp()
+    }
+
+    /// The log probability of `label`
+    pub fn ln_f_label(&self, label: &str) -> f64 {
+        self.labels
+            .index(label)
+            .map_or(f64::NEG_INFINITY, |ix| self.categorical.ln_f(&ix))
+    }
+
+    /// Draw a label without allocating
+    pub fn draw_label<R: Rng>(&self, rng: &mut R) -> &str {
+        let ix: usize = self.categorical.draw(rng);
+        &self.labels.labels()[ix]
+    }
+
+    /// The labels with their weights, in index order
+    pub fn weights(&self) -> Vec<(&str, f64)> {
+        self.labels
+            .labels()
+            .iter()
+            .map(String::as_str)
+            .zip(self.categorical.weights())
+            .collect()
+    }
+}
+
+impl From<&LabeledCategorical> for String {
+    fn from(cat: &LabeledCategorical) -> String {
+        format!("LabeledCategorical({})", cat.k())
+    }
+}
+
+impl_display!(LabeledCategorical);
+
+impl Rv<String> for LabeledCategorical {
+    fn ln_f(&self, x: &String) -> f64 {
+        self.ln_f_label(x)
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> String {
+        String::from(self.draw_label(rng))
+    }
+}
+
+impl Support<String> for LabeledCategorical {
+    fn supports(&self, x: &String) -> bool {
+        self.labels.contains(x)
+    }
+}
+
+impl DiscreteDistr<String> for LabeledCategorical {}
+
+impl Mode<String> for LabeledCategorical {
+    fn mode(&self) -> Option<String> {
+        let ix: usize = self.categorical.mode()?;
+        self.labels.label(ix).map(String::from)
+    }
+}
+
+impl Entropy for LabeledCategorical {
+    fn entropy(&self) -> f64 {
+        self.categorical.entropy()
+    }
+}
+
+impl std::error::Error for LabeledCategoricalError {}
+
+impl fmt::Display for LabeledCategoricalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LabelMap(err) => write!(f, "invalid labels: {}", err),
+            Self::Categorical(err) => write!(f, "invalid weights: {}", err),
+            Self::LengthMismatch {
+                n_labels,
+                n_weights,
+            } => write!(
+                f,
+                "there are {} labels but {} weights",
+                n_labels, n_weights
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOL: f64 = 1E-12;
+
+    fn colors() -> LabeledCategorical {
+        LabeledCategorical::from_pairs([("red", 1.0), ("green", 3.0)]).unwrap()
+    }
+
+    #[test]
+    fn ln_f_forwards_to_categorical() {
+        let cat = colors();
+        assert::close(cat.f(&String::from("red")), 0.25, TOL);
+        assert::close(cat.f_label("green"), 0.75, TOL);
+        assert_eq!(cat.ln_f_label("blue"), f64::NEG_INFINITY);
+        assert_eq!(cat.mode(), Some(String::from("green")));
+        let weights = cat.weights();
+        assert_eq!(weights[1].0, "green");
+        assert::close(weights[1].1, 0.75, TOL);
+    }
+
+    #[test]
+    fn construction_errors() {
+        assert!(matches!(
+            LabeledCategorical::from_pairs([("a", 1.0), ("a", 2.0)]),
+            Err(LabeledCategoricalError::LabelMap(_))
+        ));
+        assert!(matches!(
+            LabeledCategorical::from_pairs([("a", 1.0), ("b", -2.0)]),
+            Err(LabeledCategoricalError::Categorical(_))
+        ));
+        let labels = LabelMap::new(["a", "b"]).unwrap();
+        assert_eq!(
+            LabeledCategorical::from_parts(labels, Categorical::uniform(3)),
+            Err(LabeledCategoricalError::LengthMismatch {
+                n_labels: 2,
+                n_weights: 3
+            })
+        );
+    }
+
+    #[test]
+    fn draws_follow_weights() {
+        let cat = colors();
+        let mut rng = rand::thread_rng();
+        let n_green = (0..2_000)
+            .filter(|_| cat.draw_label(&mut rng) == "green")
+            .count();
+        assert!((1_300..1_700).contains(&n_green));
+    }
+
+    #[cfg(feature = "serde1")]
+    #[test]
+    fn serde_round_trip() {
+        let cat = colors();
+        let json = serde_json::to_string(&cat).unwrap();
+        let cat_de: LabeledCategorical = serde_json::from_str(&json).unwrap();
+        assert_eq!(cat_de, cat);
+    }
+}
@@ -0,0 +1,364 @@
+//! Log-uniform (reciprocal) distribution over x in [a, b]
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::impl_display;
+use crate::traits::*;
+use rand::Rng;
+use std::f64;
+use std::fmt;
+
+/// [Log-uniform (reciprocal) distribution](https://en.wikipedia.org/wiki/Reciprocal_distribution),
+/// LogU(a, b) over x in [a, b], 0 < a < b.
+///
+/// ln(x) is uniform on [ln(a), ln(b)], so every order of magnitude in the
+/// interval has the same mass. This makes it the usual prior for scale-like
+/// hyperparameters, such as learning rates and regularization strengths,
+/// whose order of magnitude is unknown.
+///
+/// ```math
+///                    1
+/// f(x|a, b) = ---------------
+///              x ln(b / a)
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::LogUniform;
+/// use rv::traits::*;
+///
+/// let lu = LogUniform::new(1E-4, 1E-1).unwrap();
+///
+/// // Each decade has a third of the mass
+/// let p = lu.cdf(&1E-3_f64) - lu.cdf(&1E-4_f64);
+/// assert!((p - 1.0 / 3.0).abs() < 1E-12);
+///
+/// let median: f64 = lu.median().unwrap();
+/// assert!((median - 1E-4_f64.sqrt() * 1E-1_f64.sqrt()).abs() < 1E-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct LogUniform {
+    a: f64,
+    b: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum LogUniformError {
+    /// A is less than or equal to zero
+    ATooLow { a: f64 },
+    /// A >= B
+    InvalidInterval { a: f64, b: f64 },
+    /// A was infinite or NaN
+    ANotFinite { a: f64 },
+    /// B was infinite or NaN
+    BNotFinite { b: f64 },
+}
+
+impl LogUniform {
+    /// Create a new log-uniform distribution on [a, b]
+    #[inline]
+    pub fn new(a: f64, b: f64) -> Result<Self, LogUniformError> {
+        if !a.is_finite() {
+            Err(LogUniformError::ANotFinite { a })
+        } else if !b.is_finite() {
+            Err(LogUniformError::BNotFinite { b })
+        } else if a <= 0.0 {
+            Err(LogUniformError::ATooLow { a })
+        } else if a >= b {
+            Err(LogUniformError::InvalidInterval { a, b })
+        } else {
+            Ok(LogUniform::new_unchecked(a, b))
+        }
+    }
+
+    /// Creates a new LogUniform without checking whether the parameters are
+    /// valid.
+    #[inline]
+    pub fn new_unchecked(a: f64, b: f64) -> Self {
+        LogUniform { a, b }
+    }
+
+    /// Get the lower bound, a
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rv::dist::LogUniform;
+    /// let lu = LogUniform::new(0.1, 10.0).unwrap();
+    /// assert_eq!(lu.a(), 0.1);
+    /// ```
+    #[inline]
+    pub fn a(&self) -> f64 {
+        self.a
+    }
+
+    /// Get the upper bound, b
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rv::dist::LogUniform;
+    /// let lu = LogUniform::new(0.1, 10.0).unwrap();
+    /// assert_eq!(lu.b(), 10.0);
+    /// ```
+    #[inline]
+    pub fn b(&self) -> f64 {
+        self.b
+    }
+
+    /// ln(b / a), the width of the support on the log scale
+    #[inline]
+    fn ln_ratio(&self) -> f64 {
+        self.b.ln() - self.a.ln()
+    }
+
+    /// E[x^k]
+    fn raw_moment(&self, k: i32) -> f64 {
+        (self.b.powi(k) - self.a.powi(k)) / (f64::from(k) * self.ln_ratio())
+    }
+
+    /// The mean and the second through fourth central moments
+    fn central_moments(&self) -> (f64, f64, f64, f64) {
+        let m = self.raw_moment(1);
+        let m2 = self.raw_moment(2);
+        let m3 = self.raw_moment(3);
+        let m4 = self.raw_moment(4);
+        let mm = m * m;
+        let mu2 = m.mul_add(-m, m2);
+        let mu3 = (3.0 * m).mul_add(-m2, 2.0_f64.mul_add(mm * m, m3));
+        let mu4 = (3.0 * mm)
+            .mul_add(-mm, (4.0 * m).mul_add(-m3, (6.0 * mm).mul_add(m2, m4)));
+        (m, mu2, mu3, mu4)
+    }
+}
+
+impl From<&LogUniform> for String {
+    fn from(lu: &LogUniform) -> String {
+        format!("LogU({}, {})", lu.a, lu.b)
+    }
+}
+
+impl_display!(LogUniform);
+
+macro_rules! impl_traits {
+    ($kind:ty) => {
+        impl Rv<$kind> for LogUniform {
+            fn ln_f(&self, x: &$kind) -> f64 {
+                let xf = f64::from(*x);
+                if self.a <= xf && xf <= self.b {
+                    -xf.ln() - self.ln_ratio().ln()
+                } else {
+                    f64::NEG_INFINITY
+                }
+            }
+
+            fn draw<R: Rng>(&self, rng: &mut R) -> $kind {
+                let u: f64 = rng.gen();
+                self.invcdf(u)
+            }
+        }
+
+        #[allow(clippy::cmp_owned)]
+        impl Support<$kind> for LogUniform {
+            fn supports(&self, x: &$kind) -> bool {
+                x.is_finite()
+                    && self.a <= f64::from(*x)
+                    && f64::from(*x) <= self.b
+            }
+        }
+
+        impl ContinuousDistr<$kind> for LogUniform {}
+
+        impl Cdf<$kind> for LogUniform {
+            fn cdf(&self, x: &$kind) -> f64 {
+                let xf = f64::from(*x);
+                if xf <= self.a {
+                    0.0
+                } else if xf >= self.b {
+                    1.0
+                } else {
+                    (xf.ln() - self.a.ln()) / self.ln_ratio()
+                }
+            }
+
+            fn sf(&self, x: &$kind) -> f64 {
+                let xf = f64::from(*x);
+                if xf <= self.a {
+                    1.0
+                } else if xf >= self.b {
+                    0.0
+                } else {
+                    (self.b.ln() - xf.ln()) / self.ln_ratio()
+                }
+            }
+        }
+
+        impl InverseCdf<$kind> for LogUniform {
+            fn invcdf(&self, p: f64) -> $kind {
+                let x = p.mul_add(self.ln_ratio(), self.a.ln()).exp();
+                x.clamp(self.a, self.b) as $kind
+            }
+        }
+
+        impl Mean<$kind> for LogUniform {
+            fn mean(&self) -> Option<$kind> {
+                Some(self.raw_moment(1) as $kind)
+            }
+        }
+
+        impl Median<$kind> for LogUniform {
+            fn median(&self) -> Option<$kind> {
+                Some((self.a * self.b).sqrt() as $kind)
+            }
+        }
+
+        impl Mode<$kind> for LogUniform {
+            fn mode(&self) -> Option<$kind> {
+                Some(self.a as $kind)
+            }
+        }
+    };
+}
+
+impl Variance<f64> for LogUniform {
+    fn variance(&self) -> Option<f64> {
+        let (_, mu2, _, _) = self.central_moments();
+        Some(mu2)
+    }
+}
+
+impl Entropy for LogUniform {
+    fn entropy(&self) -> f64 {
+        // ln ln(b/a) + E[ln x], and ln x is uniform on [ln a, ln b]
+        self.ln_ratio().ln() + (self.a.ln() + self.b.ln()) / 2.0
+    }
+}
+
+impl Skewness for LogUniform {
+    fn skewness(&self) -> Option<f64> {
+        let (_, mu2, mu3, _) = self.central_moments();
+        Some(mu3 / (mu2 * mu2.sqrt()))
+    }
+}
+
+impl Kurtosis for LogUniform {
+    fn kurtosis(&self) -> Option<f64> {
+        let (_, mu2, _, mu4) = self.central_moments();
+        Some(mu4 / (mu2 * mu2) - 3.0)
+    }
+}
+
+impl_traits!(f32);
+impl_traits!(f64);
+
+impl std::error::Error for LogUniformError {}
+
+impl fmt::Display for LogUniformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ATooLow { a } => {
+                write!(f, "a ({}) must be greater than zero", a)
+            }
+            Self::InvalidInterval { a, b } => {
+                write!(f, "invalid interval: (a, b) = ({}, {})", a, b)
+            }
+            Self::ANotFinite { a } => write!(f, "non-finite a: {}", a),
+            Self::BNotFinite { b } => write!(f, "non-finite b: {}", b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::Uniform;
+    use crate::misc::ks_test;
+    use crate::test_basic_impls;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    test_basic_impls!([continuous] LogUniform::new(0.1, 2.0).unwrap());
+
+    #[test]
+    fn new() {
+        assert!(LogUniform::new(0.1, 2.0).is_ok());
+        assert!(LogUniform::new(0.0, 2.0).is_err());
+        assert!(LogUniform::new(-1.0, 2.0).is_err());
+        assert!(LogUniform::new(2.0, 2.0).is_err());
+        assert!(LogUniform::new(3.0, 2.0).is_err());
+        assert!(LogUniform::new(f64::NAN, 2.0).is_err());
+        assert!(LogUniform::new(1.0, f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn log_is_uniform() {
+        let lu = LogUniform::new(0.5, 20.0).unwrap();
+        let u = Uniform::new(0.5_f64.ln(), 20.0_f64.ln()).unwrap();
+        for x in [0.5_f64, 0.7, 3.0, 19.9, 20.0] {
+            // Change of variables: f(x) = g(ln x) / x
+            assert::close(lu.ln_f(&x), u.ln_f(&x.ln()) - x.ln(), TOL);
+            assert::close(lu.cdf(&x), u.cdf(&x.ln()), TOL);
+            assert::close(lu.cdf(&x) + lu.sf(&x), 1.0, TOL);
+        }
+        assert_eq!(lu.ln_f(&0.4_f64), f64::NEG_INFINITY);
+        assert_eq!(lu.cdf(&0.4_f64), 0.0);
+        assert_eq!(lu.cdf(&21.0_f64), 1.0);
+    }
+
+    #[test]
+    fn invcdf_inverts_cdf() {
+        let lu = LogUniform::new(1E-3, 10.0).unwrap();
+        for p in [0.0, 1E-10, 0.1, 0.5, 0.99, 1.0] {
+            let x: f64 = lu.invcdf(p);
+            assert::close(lu.cdf(&x), p, TOL);
+        }
+        let median: f64 = lu.median().unwrap();
+        assert::close(lu.cdf(&median), 0.5, TOL);
+    }
+
+    #[test]
+    fn moments_match_quadrature() {
+        let lu = LogUniform::new(0.5, 4.0).unwrap();
+        // Midpoint rule in u = F(x)
+        let n = 100_000;
+        let xs: Vec<f64> = (0..n)
+            .map(|i| lu.invcdf((i as f64 + 0.5) / n as f64))
+            .collect();
+        let e = |f: &dyn Fn(f64) -> f64| {
+            xs.iter().map(|&x| f(x)).sum::<f64>() / n as f64
+        };
+
+        let mean: f64 = lu.mean().unwrap();
+        let var = lu.variance().unwrap();
+        assert::close(mean, e(&|x| x), 1E-6);
+        assert::close(var, e(&|x| (x - mean).powi(2)), 1E-6);
+        assert::close(
+            lu.skewness().unwrap(),
+            e(&|x| (x - mean).powi(3)) / var.powf(1.5),
+            1E-5,
+        );
+        assert::close(
+            lu.kurtosis().unwrap(),
+            e(&|x| (x - mean).powi(4)) / (var * var) - 3.0,
+            1E-5,
+        );
+        assert::close(lu.entropy(), -e(&|x| lu.ln_f(&x)), 1E-6);
+    }
+
+    #[test]
+    fn draw_test() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x1061);
+        let lu = LogUniform::new(1E-4, 1E-1).unwrap();
+        let xs: Vec<f64> = lu.sample(1000, &mut rng);
+        assert!(xs.iter().all(|x| lu.supports(x)));
+        let (_, p) = ks_test(&xs, |x| lu.cdf(&x));
+        assert!(p > 0.01);
+    }
+}
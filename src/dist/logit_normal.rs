@@ -0,0 +1,443 @@
+//! Logit-normal distribution over x in (0, 1)
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::consts::*;
+use crate::dist::{Beta, BetaError};
+use crate::impl_display;
+use crate::misc::gauss_hermite_table;
+use crate::traits::*;
+use rand::Rng;
+use rand_distr::Normal;
+use special::Error as _;
+use std::f64::consts::{PI, SQRT_2};
+use std::fmt;
+use std::sync::OnceLock;
+
+/// The number of Gauss-Hermite points used for the moments
+const N_QUAD: usize = 100;
+
+/// [Logit-normal distribution](https://en.wikipedia.org/wiki/Logit-normal_distribution)
+/// over proportions. If y ~ Normal(μ, σ), then 1 / (1 + e^-y) ~
+/// LogitNormal(μ, σ).
+///
+/// The mean, variance, and entropy have no closed form and are computed by
+/// Gauss-Hermite quadrature on the logit scale. They are accurate to about
+/// 1E-10 for σ up to 1.5, degrading to about 1E-6 by σ = 4.
+///
+/// # Example
+///
+/// Convert to the Beta with the same mean and variance
+///
+/// ```
+/// use rv::dist::LogitNormal;
+/// use rv::traits::{Mean, Variance};
+///
+/// let ln = LogitNormal::new(-1.0, 0.5).unwrap();
+/// let beta = ln.to_beta_moment_matched().unwrap();
+///
+/// let m1: f64 = ln.mean().unwrap();
+/// let m2: f64 = beta.mean().unwrap();
+/// assert!((m1 - m2).abs() < 1E-12);
+/// assert!((ln.variance().unwrap() - beta.variance().unwrap()).abs() < 1E-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct LogitNormal {
+    /// logit scale mean
+    mu: f64,
+    /// logit scale standard deviation
+    sigma: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum LogitNormalError {
+    /// The mu parameter is infinite or NaN
+    MuNotFinite { mu: f64 },
+    /// The sigma parameter is less than or equal to zero
+    SigmaTooLow { sigma: f64 },
+    /// The sigma parameter is infinite or NaN
+    SigmaNotFinite { sigma: f64 },
+}
+
+impl LogitNormal {
+    /// Create a new LogitNormal distribution
+    ///
+    /// # Arguments
+    /// - mu: logit scale mean
+    /// - sigma: logit scale standard deviation
+    #[inline]
+    pub fn new(mu: f64, sigma: f64) -> Result<Self, LogitNormalError> {
+        if !mu.is_finite() {
+            Err(LogitNormalError::MuNotFinite { mu })
+        } else if sigma <= 0.0 {
+            Err(LogitNormalError::SigmaTooLow { sigma })
+        } else if !sigma.is_finite() {
+            Err(LogitNormalError::SigmaNotFinite { sigma })
+        } else {
+            Ok(LogitNormal { mu, sigma })
+        }
+    }
+
+    /// Creates a new LogitNormal without checking whether the parameters are
+    /// valid.
+    #[inline]
+    pub fn new_unchecked(mu: f64, sigma: f64) -> Self {
+        LogitNormal { mu, sigma }
+    }
+
+    /// LogitNormal(0, 1)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rv::dist::LogitNormal;
+    /// let ln = LogitNormal::standard();
+    /// assert_eq!(ln, LogitNormal::new(0.0, 1.0).unwrap());
+    /// ```
+    #[inline]
+    pub fn standard() -> Self {
+        LogitNormal {
+            mu: 0.0,
+            sigma: 1.0,
+        }
+    }
+
+    /// Get the mu parameter
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rv::dist::LogitNormal;
+    /// let ln = LogitNormal::new(-1.0, 2.0).unwrap();
+    /// assert_eq!(ln.mu(), -1.0);
+    /// ```
+    #[inline]
+    pub fn mu(&self) -> f64 {
+        self.mu
+    }
+
+    /// Set the value of mu
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rv::dist::LogitNormal;
+    /// let mut ln = LogitNormal::new(2.0, 1.5).unwrap();
+    /// ln.set_mu(1.3).unwrap();
+    /// assert_eq!(ln.mu(), 1.3);
+    ///
+    /// assert!(ln.set_mu(f64::INFINITY).is_err());
+    /// assert!(ln.set_mu(f64::NAN).is_err());
+    /// ```
+    #[inline]
+    pub fn set_mu(&mut self, mu: f64) -> Result<(), LogitNormalError> {
+        if !mu.is_finite() {
+            Err(LogitNormalError::MuNotFinite { mu })
+        } else {
+            self.set_mu_unchecked(mu);
+            Ok(())
+        }
+    }
+
+    /// Set the value of mu without input validation
+    #[inline]
+    pub fn set_mu_unchecked(&mut self, mu: f64) {
+        self.mu = mu;
+    }
+
+    /// Get the sigma parameter
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rv::dist::LogitNormal;
+    /// let ln = LogitNormal::new(-1.0, 2.0).unwrap();
+    /// assert_eq!(ln.sigma(), 2.0);
+    /// ```
+    #[inline]
+    pub fn sigma(&self) -> f64 {
+        self.sigma
+    }
+
+    /// Set the value of sigma
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rv::dist::LogitNormal;
+    /// let mut ln = LogitNormal::standard();
+    /// ln.set_sigma(2.3).unwrap();
+    /// assert_eq!(ln.sigma(), 2.3);
+    ///
+    /// assert!(ln.set_sigma(0.0).is_err());
+    /// assert!(ln.set_sigma(f64::INFINITY).is_err());
+    /// assert!(ln.set_sigma(f64::NAN).is_err());
+    /// ```
+    #[inline]
+    pub fn set_sigma(&mut self, sigma: f64) -> Result<(), LogitNormalError> {
+        if sigma <= 0.0 {
+            Err(LogitNormalError::SigmaTooLow { sigma })
+        } else if !sigma.is_finite() {
+            Err(LogitNormalError::SigmaNotFinite { sigma })
+        } else {
+            self.set_sigma_unchecked(sigma);
+            Ok(())
+        }
+    }
+
+    /// Set the value of sigma without input validation
+    #[inline]
+    pub fn set_sigma_unchecked(&mut self, sigma: f64) {
+        self.sigma = sigma;
+    }
+
+    /// The Beta distribution with the same mean and variance
+    ///
+    /// Returns an error only if the moments are so extreme that the matched
+    /// Beta parameters underflow to zero.
+    pub fn to_beta_moment_matched(&self) -> Result<Beta, BetaError> {
+        let (mean, var) = self.mean_and_variance();
+        let k = mean * (1.0 - mean) / var - 1.0;
+        Beta::new(mean * k, (1.0 - mean) * k)
+    }
+
+    /// E[f(y)] where y ~ N(μ, σ) is the logit of x
+    fn logit_expectation<F>(&self, f: F) -> f64
+    where
+        F: Fn(f64) -> f64,
+    {
+        static RULE: OnceLock<(Vec<f64>, Vec<f64>)> = OnceLock::new();
+        let (weights, roots) = RULE.get_or_init(|| gauss_hermite_table(N_QUAD));
+        let scale = SQRT_2 * self.sigma;
+        weights
+            .iter()
+            .zip(roots.iter())
+            .map(|(w, &r)| w * f(scale.mul_add(r, self.mu)))
+            .sum::<f64>()
+            / PI.sqrt()
+    }
+
+    fn mean_and_variance(&self) -> (f64, f64) {
+        let mean = self.logit_expectation(logistic);
+        let var = self.logit_expectation(|y| (logistic(y) - mean).powi(2));
+        (mean, var)
+    }
+}
+
+/// The inverse of the logit, 1 / (1 + e^-y)
+#[inline]
+fn logistic(y: f64) -> f64 {
+    if y >= 0.0 {
+        (1.0 + (-y).exp()).recip()
+    } else {
+        let ey = y.exp();
+        ey / (1.0 + ey)
+    }
+}
+
+impl Default for LogitNormal {
+    fn default() -> Self {
+        LogitNormal::standard()
+    }
+}
+
+impl From<&LogitNormal> for String {
+    fn from(ln: &LogitNormal) -> String {
+        format!("LogitNormal(μ: {}, σ: {})", ln.mu, ln.sigma)
+    }
+}
+
+impl_display!(LogitNormal);
+
+macro_rules! impl_traits {
+    ($kind: ty) => {
+        impl Rv<$kind> for LogitNormal {
+            fn ln_f(&self, x: &$kind) -> f64 {
+                let xk = f64::from(*x);
+                let ln_x = xk.ln();
+                let ln_1mx = (-xk).ln_1p();
+                let d = (ln_x - ln_1mx - self.mu) / self.sigma;
+                (0.5 * d)
+                    .mul_add(-d, -ln_x - ln_1mx - self.sigma.ln() - HALF_LN_2PI)
+            }
+
+            fn draw<R: Rng>(&self, rng: &mut R) -> $kind {
+                let g = Normal::new(self.mu, self.sigma).unwrap();
+                logistic(rng.sample(g)) as $kind
+            }
+
+            fn sample<R: Rng>(&self, n: usize, rng: &mut R) -> Vec<$kind> {
+                let g = Normal::new(self.mu, self.sigma).unwrap();
+                (0..n).map(|_| logistic(rng.sample(g)) as $kind).collect()
+            }
+        }
+
+        impl ContinuousDistr<$kind> for LogitNormal {}
+
+        impl Support<$kind> for LogitNormal {
+            fn supports(&self, x: &$kind) -> bool {
+                *x > 0.0 && *x < 1.0
+            }
+        }
+
+        impl Cdf<$kind> for LogitNormal {
+            fn cdf(&self, x: &$kind) -> f64 {
+                let xk = f64::from(*x);
+                let y = xk.ln() - (-xk).ln_1p();
+                0.5_f64.mul_add(
+                    ((y - self.mu) / (SQRT_2 * self.sigma)).error(),
+                    0.5,
+                )
+            }
+        }
+
+        impl InverseCdf<$kind> for LogitNormal {
+            fn invcdf(&self, p: f64) -> $kind {
+                let y = SQRT_2.mul_add(
+                    self.sigma * 2.0_f64.mul_add(p, -1.0).inv_error(),
+                    self.mu,
+                );
+                logistic(y) as $kind
+            }
+        }
+
+        impl Mean<$kind> for LogitNormal {
+            fn mean(&self) -> Option<$kind> {
+                Some(self.logit_expectation(logistic) as $kind)
+            }
+        }
+
+        impl Median<$kind> for LogitNormal {
+            fn median(&self) -> Option<$kind> {
+                Some(logistic(self.mu) as $kind)
+            }
+        }
+    };
+}
+
+impl Variance<f64> for LogitNormal {
+    fn variance(&self) -> Option<f64> {
+        Some(self.mean_and_variance().1)
+    }
+}
+
+impl Entropy for LogitNormal {
+    fn entropy(&self) -> f64 {
+        // H(x) = H(y) + E[ln x + ln(1 - x)], and
+        // -ln x - ln(1 - x) = |y| + 2 ln(1 + e^-|y|)
+        let e_ln_jac = self.logit_expectation(|y| {
+            2.0_f64.mul_add((-y.abs()).exp().ln_1p(), y.abs())
+        });
+        HALF_LN_2PI_E + self.sigma.ln() - e_ln_jac
+    }
+}
+
+impl_traits!(f32);
+impl_traits!(f64);
+
+impl std::error::Error for LogitNormalError {}
+
+impl fmt::Display for LogitNormalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MuNotFinite { mu } => write!(f, "non-finite mu: {}", mu),
+            Self::SigmaTooLow { sigma } => {
+                write!(f, "sigma ({}) must be greater than zero", sigma)
+            }
+            Self::SigmaNotFinite { sigma } => {
+                write!(f, "non-finite sigma: {}", sigma)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::misc::ks_test;
+    use crate::test_basic_impls;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    test_basic_impls!([continuous] LogitNormal::default());
+
+    #[test]
+    fn new() {
+        let ln = LogitNormal::new(1.2, 3.0).unwrap();
+        assert::close(ln.mu, 1.2, TOL);
+        assert::close(ln.sigma, 3.0, TOL);
+        assert!(LogitNormal::new(f64::NAN, 1.0).is_err());
+        assert!(LogitNormal::new(0.0, 0.0).is_err());
+        assert!(LogitNormal::new(0.0, f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn ln_pdf() {
+        // mpmath reference values
+        let ln = LogitNormal::new(0.5, 1.5).unwrap();
+        assert::close(ln.ln_pdf(&0.3_f64), -0.167_136_231_849_043_6, TOL);
+        assert::close(ln.ln_pdf(&0.95_f64), 0.394_781_499_324_644_5, TOL);
+    }
+
+    #[test]
+    fn cdf_and_invcdf_agree() {
+        let ln = LogitNormal::new(-0.4, 0.8).unwrap();
+        let median: f64 = ln.median().unwrap();
+        assert::close(ln.cdf(&median), 0.5, TOL);
+        for p in [0.01, 0.3, 0.5, 0.77, 0.999] {
+            let x: f64 = ln.invcdf(p);
+            assert::close(ln.cdf(&x), p, 1E-10);
+        }
+    }
+
+    #[test]
+    fn moments_against_reference() {
+        // mpmath quadrature reference values
+        let ln = LogitNormal::new(0.5, 1.5).unwrap();
+        let mean: f64 = ln.mean().unwrap();
+        assert::close(mean, 0.587_596_205_190_970_4, 1E-10);
+        assert::close(ln.variance().unwrap(), 0.070_179_155_780_532_92, 1E-10);
+        assert::close(ln.entropy(), -0.069_689_334_578_777_23, 1E-10);
+    }
+
+    #[test]
+    fn moments_with_wide_sigma() {
+        let ln = LogitNormal::new(-1.0, 4.0).unwrap();
+        let mean: f64 = ln.mean().unwrap();
+        assert::close(mean, 0.409_608_442_273_292_5, 1E-6);
+        assert::close(ln.variance().unwrap(), 0.153_011_515_221_727_4, 1E-6);
+    }
+
+    #[test]
+    fn symmetric_about_one_half() {
+        let ln = LogitNormal::new(0.0, 2.0).unwrap();
+        let mean: f64 = ln.mean().unwrap();
+        assert::close(mean, 0.5, TOL);
+        assert::close(ln.ln_pdf(&0.2_f64), ln.ln_pdf(&0.8_f64), TOL);
+    }
+
+    #[test]
+    fn beta_moment_match() {
+        let ln = LogitNormal::new(1.3, 0.7).unwrap();
+        let beta = ln.to_beta_moment_matched().unwrap();
+        let m1: f64 = ln.mean().unwrap();
+        let m2: f64 = beta.mean().unwrap();
+        assert::close(m1, m2, TOL);
+        assert::close(ln.variance().unwrap(), beta.variance().unwrap(), TOL);
+    }
+
+    #[test]
+    fn draws_match_cdf() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x10);
+        let ln = LogitNormal::new(0.3, 1.2).unwrap();
+        let xs: Vec<f64> = ln.sample(1000, &mut rng);
+        assert!(xs.iter().all(|x| ln.supports(x)));
+        let (_, p) = ks_test(&xs, |x| ln.cdf(&x));
+        assert!(p > 0.01);
+    }
+}
@@ -311,6 +311,12 @@ impl Kurtosis for LogNormal {
 impl_traits!(f32);
 impl_traits!(f64);
 
+impl QuadBounds for LogNormal {
+    fn quad_bounds(&self) -> (f64, f64) {
+        self.interval(0.999_999_999_999)
+    }
+}
+
 impl std::error::Error for LogNormalError {}
 
 impl fmt::Display for LogNormalError {
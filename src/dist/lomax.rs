@@ -0,0 +1,423 @@
+//! Lomax (Pareto type II) distribution over x in [0, ∞)
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::impl_display;
+use crate::traits::*;
+use rand::Rng;
+use std::f64;
+use std::fmt;
+
+/// [Lomax distribution](https://en.wikipedia.org/wiki/Lomax_distribution),
+/// Lomax(α, λ), over x in [0, ∞).
+///
+/// The Lomax is a Pareto shifted to start at zero: if x ~ Pareto(α, λ) then
+/// x - λ ~ Lomax(α, λ). It is also the marginal of an Exponential whose rate
+/// has a Gamma(α, λ) prior.
+///
+/// ```math
+///              α  /     x \ -(α + 1)
+/// f(x|α, λ) = --- | 1 + - |
+///              λ  \     λ /
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{Lomax, Pareto};
+/// use rv::traits::*;
+///
+/// let lomax = Lomax::new(2.5, 1.5).unwrap();
+/// let pareto = Pareto::new(2.5, 1.5).unwrap();
+///
+/// let x: f64 = 0.7;
+/// assert::close(lomax.ln_f(&x), pareto.ln_f(&(x + 1.5)), 1E-12);
+/// assert::close(lomax.cdf(&x), pareto.cdf(&(x + 1.5)), 1E-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct Lomax {
+    shape: f64,
+    scale: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum LomaxError {
+    /// Shape parameter is less than or equal to zero
+    ShapeTooLow { shape: f64 },
+    /// Shape parameter is infinite or NaN
+    ShapeNotFinite { shape: f64 },
+    /// Scale parameter is less than or equal to zero
+    ScaleTooLow { scale: f64 },
+    /// Scale parameter is infinite or NaN
+    ScaleNotFinite { scale: f64 },
+}
+
+impl Lomax {
+    /// Create a new `Lomax` distribution with shape (α) and scale (λ).
+    pub fn new(shape: f64, scale: f64) -> Result<Self, LomaxError> {
+        if shape <= 0.0 {
+            Err(LomaxError::ShapeTooLow { shape })
+        } else if !shape.is_finite() {
+            Err(LomaxError::ShapeNotFinite { shape })
+        } else if scale <= 0.0 {
+            Err(LomaxError::ScaleTooLow { scale })
+        } else if !scale.is_finite() {
+            Err(LomaxError::ScaleNotFinite { scale })
+        } else {
+            Ok(Lomax { shape, scale })
+        }
+    }
+
+    /// Creates a new Lomax without checking whether the parameters are valid.
+    #[inline]
+    pub fn new_unchecked(shape: f64, scale: f64) -> Self {
+        Lomax { shape, scale }
+    }
+
+    /// Get shape parameter
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rv::dist::Lomax;
+    /// let lomax = Lomax::new(1.0, 2.0).unwrap();
+    /// assert_eq!(lomax.shape(), 1.0);
+    /// ```
+    #[inline]
+    pub fn shape(&self) -> f64 {
+        self.shape
+    }
+
+    /// Set the shape parameter
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rv::dist::Lomax;
+    /// let mut lomax = Lomax::new(2.0, 1.0).unwrap();
+    /// assert_eq!(lomax.shape(), 2.0);
+    ///
+    /// lomax.set_shape(1.1).unwrap();
+    /// assert_eq!(lomax.shape(), 1.1);
+    /// ```
+    ///
+    /// Will error for invalid values
+    ///
+    /// ```rust
+    /// # use rv::dist::Lomax;
+    /// # let mut lomax = Lomax::new(2.0, 1.0).unwrap();
+    /// assert!(lomax.set_shape(1.1).is_ok());
+    /// assert!(lomax.set_shape(0.0).is_err());
+    /// assert!(lomax.set_shape(-1.0).is_err());
+    /// assert!(lomax.set_shape(f64::INFINITY).is_err());
+    /// assert!(lomax.set_shape(f64::NAN).is_err());
+    /// ```
+    #[inline]
+    pub fn set_shape(&mut self, shape: f64) -> Result<(), LomaxError> {
+        if shape <= 0.0 {
+            Err(LomaxError::ShapeTooLow { shape })
+        } else if !shape.is_finite() {
+            Err(LomaxError::ShapeNotFinite { shape })
+        } else {
+            self.set_shape_unchecked(shape);
+            Ok(())
+        }
+    }
+
+    /// Set the shape parameter without input validation
+    #[inline]
+    pub fn set_shape_unchecked(&mut self, shape: f64) {
+        self.shape = shape;
+    }
+
+    /// Get scale parameter
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rv::dist::Lomax;
+    /// let lomax = Lomax::new(1.0, 2.0).unwrap();
+    /// assert_eq!(lomax.scale(), 2.0);
+    /// ```
+    #[inline]
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Set the scale parameter
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rv::dist::Lomax;
+    /// let mut lomax = Lomax::new(2.0, 1.0).unwrap();
+    /// assert_eq!(lomax.scale(), 1.0);
+    ///
+    /// lomax.set_scale(1.1).unwrap();
+    /// assert_eq!(lomax.scale(), 1.1);
+    /// ```
+    ///
+    /// Will error for invalid values
+    ///
+    /// ```rust
+    /// # use rv::dist::Lomax;
+    /// # let mut lomax = Lomax::new(2.0, 1.0).unwrap();
+    /// assert!(lomax.set_scale(1.1).is_ok());
+    /// assert!(lomax.set_scale(0.0).is_err());
+    /// assert!(lomax.set_scale(-1.0).is_err());
+    /// assert!(lomax.set_scale(f64::INFINITY).is_err());
+    /// assert!(lomax.set_scale(f64::NAN).is_err());
+    /// ```
+    #[inline]
+    pub fn set_scale(&mut self, scale: f64) -> Result<(), LomaxError> {
+        if scale <= 0.0 {
+            Err(LomaxError::ScaleTooLow { scale })
+        } else if !scale.is_finite() {
+            Err(LomaxError::ScaleNotFinite { scale })
+        } else {
+            self.set_scale_unchecked(scale);
+            Ok(())
+        }
+    }
+
+    /// Set the scale parameter without input validation
+    #[inline]
+    pub fn set_scale_unchecked(&mut self, scale: f64) {
+        self.scale = scale;
+    }
+}
+
+impl From<&Lomax> for String {
+    fn from(lomax: &Lomax) -> String {
+        format!("Lomax(α: {}, λ: {})", lomax.shape, lomax.scale)
+    }
+}
+
+impl_display!(Lomax);
+
+macro_rules! impl_traits {
+    ($kind:ty) => {
+        impl Rv<$kind> for Lomax {
+            fn ln_f(&self, x: &$kind) -> f64 {
+                let z = f64::from(*x) / self.scale;
+                (self.shape + 1.0)
+                    .mul_add(-z.ln_1p(), self.shape.ln() - self.scale.ln())
+            }
+
+            fn draw<R: Rng>(&self, rng: &mut R) -> $kind {
+                let u: f64 = rng.gen();
+                self.invcdf(u)
+            }
+        }
+
+        impl ContinuousDistr<$kind> for Lomax {}
+
+        impl Support<$kind> for Lomax {
+            fn supports(&self, x: &$kind) -> bool {
+                x.is_finite() && *x >= 0.0
+            }
+        }
+
+        impl Cdf<$kind> for Lomax {
+            fn cdf(&self, x: &$kind) -> f64 {
+                let xk = f64::from(*x);
+                if xk <= 0.0 {
+                    0.0
+                } else {
+                    -(-self.shape * (xk / self.scale).ln_1p()).exp_m1()
+                }
+            }
+
+            fn sf(&self, x: &$kind) -> f64 {
+                let xk = f64::from(*x).max(0.0);
+                (-self.shape * (xk / self.scale).ln_1p()).exp()
+            }
+        }
+
+        impl InverseCdf<$kind> for Lomax {
+            fn invcdf(&self, p: f64) -> $kind {
+                let z = (-(-p).ln_1p() / self.shape).exp_m1();
+                (self.scale * z) as $kind
+            }
+        }
+
+        impl Mean<$kind> for Lomax {
+            fn mean(&self) -> Option<$kind> {
+                if self.shape <= 1.0 {
+                    Some(f64::INFINITY as $kind)
+                } else {
+                    Some((self.scale / (self.shape - 1.0)) as $kind)
+                }
+            }
+        }
+
+        impl Median<$kind> for Lomax {
+            fn median(&self) -> Option<$kind> {
+                let z = (f64::consts::LN_2 / self.shape).exp_m1();
+                Some((self.scale * z) as $kind)
+            }
+        }
+
+        impl Mode<$kind> for Lomax {
+            fn mode(&self) -> Option<$kind> {
+                Some(0.0)
+            }
+        }
+    };
+}
+
+impl Variance<f64> for Lomax {
+    fn variance(&self) -> Option<f64> {
+        if self.shape <= 2.0 {
+            Some(f64::INFINITY)
+        } else {
+            let am1 = self.shape - 1.0;
+            Some(
+                (self.scale * self.scale * self.shape)
+                    / (am1 * am1 * (self.shape - 2.0)),
+            )
+        }
+    }
+}
+
+impl Entropy for Lomax {
+    fn entropy(&self) -> f64 {
+        (self.scale / self.shape).ln() + self.shape.recip() + 1.0
+    }
+}
+
+impl Skewness for Lomax {
+    fn skewness(&self) -> Option<f64> {
+        if self.shape <= 3.0 {
+            None
+        } else {
+            Some(
+                ((2.0 * (1.0 + self.shape)) / (self.shape - 3.0))
+                    * ((self.shape - 2.0) / self.shape).sqrt(),
+            )
+        }
+    }
+}
+
+impl Kurtosis for Lomax {
+    fn kurtosis(&self) -> Option<f64> {
+        let s = self.shape;
+        if s <= 4.0 {
+            None
+        } else {
+            let s2 = s * s;
+            Some(
+                6.0 * (6.0_f64.mul_add(-s, s2.mul_add(s, s2)) - 2.0)
+                    / (s * (s - 3.0) * (s - 4.0)),
+            )
+        }
+    }
+}
+
+impl_traits!(f32);
+impl_traits!(f64);
+
+impl std::error::Error for LomaxError {}
+
+impl fmt::Display for LomaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ShapeTooLow { shape } => {
+                write!(f, "shape ({}) must be greater than zero", shape)
+            }
+            Self::ShapeNotFinite { shape } => {
+                write!(f, "non-finite shape: {}", shape)
+            }
+            Self::ScaleTooLow { scale } => {
+                write!(f, "scale ({}) must be greater than zero", scale)
+            }
+            Self::ScaleNotFinite { scale } => {
+                write!(f, "non-finite scale: {}", scale)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::Pareto;
+    use crate::misc::ks_test;
+    use crate::test_basic_impls;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    test_basic_impls!([continuous] Lomax::new(1.5, 0.5).unwrap());
+
+    #[test]
+    fn new() {
+        assert!(Lomax::new(1.0, 2.0).is_ok());
+        assert!(Lomax::new(0.0, 2.0).is_err());
+        assert!(Lomax::new(1.0, -2.0).is_err());
+        assert!(Lomax::new(f64::NAN, 2.0).is_err());
+        assert!(Lomax::new(1.0, f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn shifted_pareto() {
+        let lomax = Lomax::new(3.2, 0.8).unwrap();
+        let pareto = Pareto::new(3.2, 0.8).unwrap();
+        for x in [0.0, 0.1, 1.0, 12.5] {
+            assert::close(lomax.ln_f(&x), pareto.ln_f(&(x + 0.8)), TOL);
+            assert::close(lomax.cdf(&x), pareto.cdf(&(x + 0.8)), TOL);
+        }
+        let mean: f64 = lomax.mean().unwrap();
+        let pareto_mean: f64 = pareto.mean().unwrap();
+        assert::close(mean, pareto_mean - 0.8, TOL);
+        assert::close(
+            lomax.variance().unwrap(),
+            pareto.variance().unwrap(),
+            TOL,
+        );
+        assert_eq!(lomax.skewness(), pareto.skewness());
+        assert_eq!(lomax.kurtosis(), pareto.kurtosis());
+    }
+
+    #[test]
+    fn invcdf_inverts_cdf() {
+        let lomax = Lomax::new(1.2, 3.4).unwrap();
+        for p in [0.0, 1E-10, 0.1, 0.5, 0.99] {
+            let x: f64 = lomax.invcdf(p);
+            assert::close(lomax.cdf(&x), p, TOL);
+        }
+        let median: f64 = lomax.median().unwrap();
+        assert::close(lomax.cdf(&median), 0.5, TOL);
+        assert_eq!(lomax.cdf(&-1.0_f64), 0.0);
+    }
+
+    #[test]
+    fn entropy() {
+        // ∫ f ln f over [0, ∞) via the substitution u = F(x)
+        let lomax = Lomax::new(2.5, 1.5).unwrap();
+        let n = 100_000;
+        let h = -(0..n)
+            .map(|i| {
+                let u = (i as f64 + 0.5) / n as f64;
+                let x: f64 = lomax.invcdf(u);
+                lomax.ln_f(&x)
+            })
+            .sum::<f64>()
+            / n as f64;
+        assert::close(lomax.entropy(), h, 1E-4);
+    }
+
+    #[test]
+    fn draw_test() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xABCD);
+        let lomax = Lomax::new(1.2, 3.4).unwrap();
+        let xs: Vec<f64> = lomax.sample(1000, &mut rng);
+        let (_, p) = ks_test(&xs, |x| lomax.cdf(&x));
+        assert!(p > 0.01);
+    }
+}
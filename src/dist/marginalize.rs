@@ -0,0 +1,60 @@
+use std::fmt;
+
+/// Errors from marginalizing a joint distribution with
+/// [`Marginalize`](crate::traits::Marginalize)
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarginalizeError {
+    /// A selected variable index is out of bounds
+    IndexOutOfBounds {
+        /// The offending index
+        ix: usize,
+        /// The number of variables in the joint distribution
+        n_vars: usize,
+    },
+    /// A variable is selected more than once
+    DuplicateIndex {
+        /// The repeated index
+        ix: usize,
+    },
+    /// No variables are selected
+    NoVariables,
+}
+
+/// Validates the indices of the variables kept from `n_vars` variables and
+/// returns the indices of the variables that are integrated out
+pub(crate) fn dropped_indices(
+    ixs: &[usize],
+    n_vars: usize,
+) -> Result<Vec<usize>, MarginalizeError> {
+    if ixs.is_empty() {
+        return Err(MarginalizeError::NoVariables);
+    }
+    let mut kept = vec![false; n_vars];
+    for &ix in ixs {
+        if ix >= n_vars {
+            return Err(MarginalizeError::IndexOutOfBounds { ix, n_vars });
+        } else if kept[ix] {
+            return Err(MarginalizeError::DuplicateIndex { ix });
+        }
+        kept[ix] = true;
+    }
+    Ok((0..n_vars).filter(|&ix| !kept[ix]).collect())
+}
+
+impl std::error::Error for MarginalizeError {}
+
+impl fmt::Display for MarginalizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IndexOutOfBounds { ix, n_vars } => write!(
+                f,
+                "index {} is out of bounds for {} variables",
+                ix, n_vars
+            ),
+            Self::DuplicateIndex { ix } => {
+                write!(f, "variable {} is selected more than once", ix)
+            }
+            Self::NoVariables => write!(f, "no variables are selected"),
+        }
+    }
+}
@@ -0,0 +1,492 @@
+//! Discrete-state Markov chain over paths of categorical states
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::{CategoricalDatum, MarkovChainSuffStat};
+use crate::dist::{Categorical, CategoricalError, DirichletError};
+use crate::impl_display;
+use crate::traits::*;
+use rand::Rng;
+use std::fmt;
+
+mod prior;
+
+pub use prior::MarkovChainPrior;
+
+/// Smallest pivot accepted when solving for the stationary distribution
+const PIVOT_TOL: f64 = 1E-12;
+
+/// [Markov chain](https://en.wikipedia.org/wiki/Markov_chain) over paths of
+/// states in [0, k).
+///
+/// The first state is drawn from the initial distribution and each later
+/// state from the row of the transition matrix of the state before it.
+/// Draws are paths of [`draw_len`](MarkovChain::draw_len) states, one by
+/// default; [`simulate`](MarkovChain::simulate) draws paths of any length.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::MarkovChain;
+/// use rv::traits::*;
+///
+/// // Dry (0) and wet (1) days
+/// let weather = MarkovChain::new(
+///     &[0.5, 0.5],
+///     &[vec![0.9, 0.1], vec![0.5, 0.5]],
+/// ).unwrap();
+///
+/// let p = weather.f(&vec![0_usize, 0, 1]);
+/// assert::close(p, 0.5 * 0.9 * 0.1, 1E-12);
+///
+/// let stationary = weather.stationary().unwrap();
+/// assert::close(stationary.weights()[1], 1.0 / 6.0, 1E-12);
+///
+/// let mut rng = rand::thread_rng();
+/// let path: Vec<u8> = weather.simulate(10, &mut rng);
+/// assert_eq!(path.len(), 10);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct MarkovChain {
+    /// Distribution of the first state
+    initial: Categorical,
+    /// Distribution of the next state, by current state
+    transitions: Vec<Categorical>,
+    /// Length of the paths produced by `Rv::draw`
+    draw_len: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum MarkovChainError {
+    /// There are no states
+    NoStates,
+    /// The initial distribution has a different number of states than the
+    /// transition matrix
+    InitialLengthMismatch { n_initial: usize, n_states: usize },
+    /// A row of the transition matrix has the wrong number of states
+    RowLengthMismatch {
+        row: usize,
+        n_cols: usize,
+        n_states: usize,
+    },
+    /// The initial weights are invalid
+    InitialWeights(CategoricalError),
+    /// The weights of a row of the transition matrix are invalid
+    RowWeights { row: usize, err: CategoricalError },
+    /// A Dirichlet prior is invalid
+    Dirichlet(DirichletError),
+}
+
+impl MarkovChain {
+    /// Create a Markov chain from `initial` state weights and the rows of
+    /// the `transition` matrix. Each set of weights is normalized.
+    pub fn new(
+        initial: &[f64],
+        transition: &[Vec<f64>],
+    ) -> Result<Self, MarkovChainError> {
+        let k = transition.len();
+        check_shape(initial.len(), transition.iter().map(Vec::len), k)?;
+        let initial = Categorical::new(initial)
+            .map_err(MarkovChainError::InitialWeights)?;
+        let transitions = transition
+            .iter()
+            .enumerate()
+            .map(|(row, weights)| {
+                Categorical::new(weights)
+                    .map_err(|err| MarkovChainError::RowWeights { row, err })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new_unchecked(initial, transitions))
+    }
+
+    /// Create a Markov chain from the initial distribution and the
+    /// distribution of the next state from each state
+    pub fn from_categoricals(
+        initial: Categorical,
+        transitions: Vec<Categorical>,
+    ) -> Result<Self, MarkovChainError> {
+        check_shape(
+            initial.k(),
+            transitions.iter().map(Categorical::k),
+            transitions.len(),
+        )?;
+        Ok(Self::new_unchecked(initial, transitions))
+    }
+
+    /// Creates a new MarkovChain without checking whether the shapes agree
+    #[inline]
+    pub fn new_unchecked(
+        initial: Categorical,
+        transitions: Vec<Categorical>,
+    ) -> Self {
+        MarkovChain {
+            initial,
+            transitions,
+            draw_len: 1,
+        }
+    }
+
+    /// Draw paths of `draw_len` states from `Rv::draw`
+    #[must_use]
+    pub fn with_draw_len(mut self, draw_len: usize) -> Self {
+        self.draw_len = draw_len;
+        self
+    }
+
+    /// The number of states
+    #[inline]
+    pub fn n_states(&self) -> usize {
+        self.transitions.len()
+    }
+
+    /// The length of the paths produced by `Rv::draw`
+    #[inline]
+    pub fn draw_len(&self) -> usize {
+        self.draw_len
+    }
+
+    /// The distribution of the first state
+    #[inline]
+    pub fn initial(&self) -> &Categorical {
+        &self.initial
+    }
+
+    /// The distribution of the next state from each state
+    #[inline]
+    pub fn transitions(&self) -> &[Categorical] {
+        &self.transitions
+    }
+
+    /// The transition matrix; entry `[i][j]` is the probability of moving
+    /// from `i` to `j`
+    pub fn transition_matrix(&self) -> Vec<Vec<f64>> {
+        self.transitions.iter().map(Categorical::weights).collect()
+    }
+
+    /// The stationary distribution, `π = π P`, or `None` if it is not
+    /// unique because the chain has more than one closed class
+    pub fn stationary(&self) -> Option<Categorical> {
+        let k = self.n_states();
+        // Solve (Pᵀ - I) π = 0 with the last equation replaced by Σπ = 1
+        let p = self.transition_matrix();
+        let mut a: Vec<Vec<f64>> = (0..k)
+            .map(|i| {
+                let mut row: Vec<f64> = if i + 1 == k {
+                    vec![1.0; k + 1]
+                } else {
+                    (0..k).map(|j| p[j][i]).chain([0.0]).collect()
+                };
+                if i + 1 < k {
+                    row[i] -= 1.0;
+                }
+                row
+            })
+            .collect();
+
+        // Gaussian elimination with partial pivoting on the augmented matrix
+        for col in 0..k {
+            let pivot = (col..k)
+                .max_by(|&r, &s| a[r][col].abs().total_cmp(&a[s][col].abs()))?;
+            if a[pivot][col].abs() < PIVOT_TOL {
+                return None;
+            }
+            a.swap(col, pivot);
+            let (upper, lower) = a.split_at_mut(col + 1);
+            let pivot_row = &upper[col];
+            for row in lower.iter_mut() {
+                let factor = row[col] / pivot_row[col];
+                row.iter_mut()
+                    .zip(pivot_row.iter())
+                    .skip(col)
+                    .for_each(|(x, &p)| *x = (-factor).mul_add(p, *x));
+            }
+        }
+        let mut pi = vec![0.0; k];
+        for row in (0..k).rev() {
+            let tail: f64 =
+                ((row + 1)..k).map(|j| a[row][j] * pi[j]).sum::<f64>();
+            pi[row] = (a[row][k] - tail) / a[row][row];
+        }
+
+        let weights: Vec<f64> = pi.iter().map(|p| p.max(0.0)).collect();
+        Categorical::new(&weights).ok()
+    }
+
+    /// Simulate a path of `n` states
+    pub fn simulate<X: CategoricalDatum, R: Rng>(
+        &self,
+        n: usize,
+        rng: &mut R,
+    ) -> Vec<X> {
+        if n == 0 {
+            return Vec::new();
+        }
+        let x0: usize = self.initial.draw(rng);
+        self.simulate_from(X::from_usize(x0), n - 1, rng)
+    }
+
+    /// Simulate `n` states following `x0`. The path includes `x0`.
+    pub fn simulate_from<X: CategoricalDatum, R: Rng>(
+        &self,
+        x0: X,
+        n: usize,
+        rng: &mut R,
+    ) -> Vec<X> {
+        let mut path = Vec::with_capacity(n + 1);
+        path.push(x0);
+        let mut x = x0.into_usize();
+        for _ in 0..n {
+            x = self.transitions[x].draw(rng);
+            path.push(X::from_usize(x));
+        }
+        path
+    }
+}
+
+fn check_shape(
+    n_initial: usize,
+    row_lens: impl Iterator<Item = usize>,
+    k: usize,
+) -> Result<(), MarkovChainError> {
+    if k == 0 {
+        return Err(MarkovChainError::NoStates);
+    }
+    if n_initial != k {
+        return Err(MarkovChainError::InitialLengthMismatch {
+            n_initial,
+            n_states: k,
+        });
+    }
+    row_lens.enumerate().try_for_each(|(row, n_cols)| {
+        if n_cols == k {
+            Ok(())
+        } else {
+            Err(MarkovChainError::RowLengthMismatch {
+                row,
+                n_cols,
+                n_states: k,
+            })
+        }
+    })
+}
+
+impl From<&MarkovChain> for String {
+    fn from(chain: &MarkovChain) -> String {
+        format!("MarkovChain(states: {})", chain.n_states())
+    }
+}
+
+impl_display!(MarkovChain);
+
+impl<X: CategoricalDatum> Rv<Vec<X>> for MarkovChain {
+    fn ln_f(&self, x: &Vec<X>) -> f64 {
+        let ln_f0 = x.first().map_or(0.0, |x0| self.initial.ln_f(x0));
+        x.windows(2).fold(ln_f0, |acc, w| {
+            acc + self.transitions[w[0].into_usize()].ln_f(&w[1])
+        })
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> Vec<X> {
+        self.simulate(self.draw_len, rng)
+    }
+}
+
+impl<X: CategoricalDatum> Support<Vec<X>> for MarkovChain {
+    fn supports(&self, x: &Vec<X>) -> bool {
+        !x.is_empty() && x.iter().all(|xi| xi.into_usize() < self.n_states())
+    }
+}
+
+impl<X: CategoricalDatum> DiscreteDistr<Vec<X>> for MarkovChain {}
+
+impl<X: CategoricalDatum> HasSuffStat<Vec<X>> for MarkovChain {
+    type Stat = MarkovChainSuffStat;
+
+    fn empty_suffstat(&self) -> Self::Stat {
+        MarkovChainSuffStat::new(self.n_states())
+    }
+
+    fn ln_f_stat(&self, stat: &Self::Stat) -> f64 {
+        // Skip zero counts so impossible moves that never happen add nothing
+        let dot = |counts: &[f64], ln_weights: &[f64]| {
+            counts
+                .iter()
+                .zip(ln_weights.iter())
+                .filter(|(&ct, _)| ct > 0.0)
+                .map(|(&ct, &lnw)| ct * lnw)
+                .sum::<f64>()
+        };
+        stat.transition_counts()
+            .iter()
+            .zip(self.transitions.iter())
+            .fold(
+                dot(stat.initial_counts(), self.initial.ln_weights()),
+                |acc, (counts, row)| acc + dot(counts, row.ln_weights()),
+            )
+    }
+}
+
+impl std::error::Error for MarkovChainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InitialWeights(err) | Self::RowWeights { err, .. } => {
+                Some(err)
+            }
+            Self::Dirichlet(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MarkovChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoStates => {
+                write!(f, "the chain must have at least one state")
+            }
+            Self::InitialLengthMismatch {
+                n_initial,
+                n_states,
+            } => write!(
+                f,
+                "{} initial weights given for {} states",
+                n_initial, n_states
+            ),
+            Self::RowLengthMismatch {
+                row,
+                n_cols,
+                n_states,
+            } => write!(
+                f,
+                "row {} of the transition matrix has {} entries but there \
+                are {} states",
+                row, n_cols, n_states
+            ),
+            Self::InitialWeights(err) => {
+                write!(f, "invalid initial weights: {}", err)
+            }
+            Self::RowWeights { row, err } => write!(
+                f,
+                "invalid weights in row {} of the transition matrix: {}",
+                row, err
+            ),
+            Self::Dirichlet(err) => write!(f, "invalid Dirichlet: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_basic_impls;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    fn chain() -> MarkovChain {
+        MarkovChain::new(
+            &[0.2, 0.3, 0.5],
+            &[
+                vec![0.0, 1.0, 0.0],
+                vec![0.5, 0.0, 0.5],
+                vec![0.1, 0.1, 0.8],
+            ],
+        )
+        .unwrap()
+    }
+
+    test_basic_impls!(chain(), vec![0_usize, 1, 2, 2]);
+
+    #[test]
+    fn ln_f_path() {
+        let chain = chain();
+        let expected =
+            0.3_f64.ln() + 0.5_f64.ln() + 0.8_f64.ln() + 0.1_f64.ln();
+        assert::close(chain.ln_f(&vec![1_u8, 2, 2, 1]), expected, TOL);
+        assert_eq!(chain.ln_f(&vec![0_u8, 0]), f64::NEG_INFINITY);
+        assert!(chain.supports(&vec![2_u8, 0]));
+        assert!(!chain.supports(&vec![3_u8]));
+        assert!(!Support::<Vec<u8>>::supports(&chain, &vec![]));
+    }
+
+    #[test]
+    fn ln_f_stat_matches_ln_f() {
+        let chain = chain();
+        let paths: Vec<Vec<u8>> =
+            vec![vec![1, 2, 2, 1], vec![0, 1, 0], vec![2]];
+        let mut stat = MarkovChainSuffStat::new(3);
+        stat.observe_many(&paths);
+        let expected: f64 = paths.iter().map(|p| chain.ln_f(p)).sum();
+        let ln_f_stat =
+            <MarkovChain as HasSuffStat<Vec<u8>>>::ln_f_stat(&chain, &stat);
+        assert::close(ln_f_stat, expected, TOL);
+    }
+
+    #[test]
+    fn stationary_solves_balance() {
+        let chain = chain();
+        let pi = chain.stationary().unwrap().weights();
+        let p = chain.transition_matrix();
+        for j in 0..3 {
+            let flow: f64 = (0..3).map(|i| pi[i] * p[i][j]).sum();
+            assert::close(flow, pi[j], TOL);
+        }
+
+        // Periodic chains still have a stationary distribution
+        let flip =
+            MarkovChain::new(&[1.0, 0.0], &[vec![0.0, 1.0], vec![1.0, 0.0]])
+                .unwrap();
+        assert::close(flip.stationary().unwrap().weights(), &[0.5, 0.5], TOL);
+
+        // Two absorbing states do not
+        let absorbing =
+            MarkovChain::new(&[1.0, 1.0], &[vec![1.0, 0.0], vec![0.0, 1.0]])
+                .unwrap();
+        assert!(absorbing.stationary().is_none());
+    }
+
+    #[test]
+    fn simulated_visits_match_stationary() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x86);
+        let chain = chain().with_draw_len(50_000);
+        let path: Vec<usize> = chain.draw(&mut rng);
+        assert_eq!(path.len(), 50_000);
+        let pi = chain.stationary().unwrap().weights();
+        for (state, p) in pi.iter().enumerate() {
+            let freq = path.iter().filter(|&&x| x == state).count() as f64
+                / path.len() as f64;
+            assert::close(freq, *p, 0.02);
+        }
+        // Impossible moves never happen
+        assert!(path.windows(2).all(|w| !(w[0] == 0 && w[1] != 1)));
+    }
+
+    #[test]
+    fn new_validates_shape() {
+        assert_eq!(MarkovChain::new(&[], &[]), Err(MarkovChainError::NoStates));
+        assert_eq!(
+            MarkovChain::new(&[1.0], &[vec![1.0, 0.0], vec![0.0, 1.0]]),
+            Err(MarkovChainError::InitialLengthMismatch {
+                n_initial: 1,
+                n_states: 2
+            })
+        );
+        assert_eq!(
+            MarkovChain::new(&[1.0, 1.0], &[vec![1.0, 0.0], vec![1.0]]),
+            Err(MarkovChainError::RowLengthMismatch {
+                row: 1,
+                n_cols: 1,
+                n_states: 2
+            })
+        );
+        assert!(matches!(
+            MarkovChain::new(&[1.0, 1.0], &[vec![1.0, 0.0], vec![-1.0, 2.0]]),
+            Err(MarkovChainError::RowWeights { row: 1, .. })
+        ));
+    }
+}
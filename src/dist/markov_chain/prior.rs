@@ -0,0 +1,310 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use rand::Rng;
+
+use crate::data::{
+    extract_stat_then, CategoricalDatum, CategoricalSuffStat, DataOrSuffStat,
+    MarkovChainSuffStat,
+};
+use crate::dist::{Categorical, Dirichlet, MarkovChain, MarkovChainError};
+use crate::traits::*;
+
+/// Conjugate prior on a [`MarkovChain`]: independent Dirichlets on the
+/// initial distribution and on each row of the transition matrix.
+///
+/// Observing paths adds the initial-state counts to the initial Dirichlet
+/// and the transition counts out of each state to that state's row.
+///
+/// # Example
+///
+/// ```
+/// use rv::data::DataOrSuffStat;
+/// use rv::dist::{MarkovChain, MarkovChainPrior};
+/// use rv::traits::*;
+///
+/// let prior = MarkovChainPrior::symmetric(1.0, 2).unwrap();
+/// let paths: Vec<Vec<u8>> = vec![vec![0, 0, 0, 1], vec![1, 1, 0]];
+/// let data: DataOrSuffStat<Vec<u8>, MarkovChain> =
+///     DataOrSuffStat::Data(&paths);
+///
+/// let posterior = prior.posterior(&data);
+/// // One prior pseudo-count plus two observed moves 0 → 0
+/// assert_eq!(*posterior.rows()[0].alphas(), vec![3.0, 2.0]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct MarkovChainPrior {
+    initial: Dirichlet,
+    rows: Vec<Dirichlet>,
+}
+
+impl MarkovChainPrior {
+    /// Create a prior from the Dirichlet on the initial distribution and
+    /// the Dirichlets on each row of the transition matrix
+    pub fn new(
+        initial: Dirichlet,
+        rows: Vec<Dirichlet>,
+    ) -> Result<Self, MarkovChainError> {
+        super::check_shape(
+            initial.k(),
+            rows.iter().map(Dirichlet::k),
+            rows.len(),
+        )?;
+        Ok(MarkovChainPrior { initial, rows })
+    }
+
+    /// Creates a new MarkovChainPrior without checking whether the shapes
+    /// agree
+    #[inline]
+    pub fn new_unchecked(initial: Dirichlet, rows: Vec<Dirichlet>) -> Self {
+        MarkovChainPrior { initial, rows }
+    }
+
+    /// Every Dirichlet is symmetric with concentration `alpha` over `k`
+    /// states
+    pub fn symmetric(alpha: f64, k: usize) -> Result<Self, MarkovChainError> {
+        let dir = Dirichlet::symmetric(alpha, k)
+            .map_err(MarkovChainError::Dirichlet)?;
+        Ok(MarkovChainPrior {
+            initial: dir.clone(),
+            rows: vec![dir; k],
+        })
+    }
+
+    /// The number of states
+    #[inline]
+    pub fn n_states(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The Dirichlet on the initial distribution
+    #[inline]
+    pub fn initial(&self) -> &Dirichlet {
+        &self.initial
+    }
+
+    /// The Dirichlets on the rows of the transition matrix
+    #[inline]
+    pub fn rows(&self) -> &[Dirichlet] {
+        &self.rows
+    }
+
+    /// The posterior mean transition matrix
+    pub fn mean_transition_matrix(&self) -> Vec<Vec<f64>> {
+        self.rows.iter().map(dirichlet_mean).collect()
+    }
+
+    /// Each Dirichlet paired with the counts it is updated by
+    fn with_counts<'a>(
+        &'a self,
+        stat: &'a MarkovChainSuffStat,
+    ) -> impl Iterator<Item = (&'a Dirichlet, &'a [f64])> {
+        std::iter::once((&self.initial, stat.initial_counts())).chain(
+            self.rows
+                .iter()
+                .zip(stat.transition_counts().iter().map(Vec::as_slice)),
+        )
+    }
+}
+
+fn dirichlet_mean(dir: &Dirichlet) -> Vec<f64> {
+    let total: f64 = dir.alphas().iter().sum();
+    dir.alphas().iter().map(|a| a / total).collect()
+}
+
+fn add_counts(dir: &Dirichlet, counts: &[f64]) -> Dirichlet {
+    Dirichlet::new_unchecked(
+        dir.alphas()
+            .iter()
+            .zip(counts.iter())
+            .map(|(a, ct)| a + ct)
+            .collect(),
+    )
+}
+
+/// The counts as a `Categorical` statistic, so the Dirichlet-Categorical
+/// marginal likelihood can be reused
+fn categorical_stat(counts: &[f64]) -> CategoricalSuffStat {
+    let n = counts.iter().sum::<f64>() as usize;
+    CategoricalSuffStat::from_parts_unchecked(n, counts.to_vec())
+}
+
+impl Rv<MarkovChain> for MarkovChainPrior {
+    fn ln_f(&self, x: &MarkovChain) -> f64 {
+        x.transitions().iter().zip(self.rows.iter()).fold(
+            self.initial.ln_f(&x.initial().weights()),
+            |acc, (row, dir)| acc + dir.ln_f(&row.weights()),
+        )
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> MarkovChain {
+        let initial: Categorical = self.initial.draw(rng);
+        let transitions: Vec<Categorical> =
+            self.rows.iter().map(|dir| dir.draw(rng)).collect();
+        MarkovChain::new_unchecked(initial, transitions)
+    }
+}
+
+impl<X: CategoricalDatum> ConjugatePrior<Vec<X>, MarkovChain>
+    for MarkovChainPrior
+{
+    type Posterior = Self;
+    /// `ln_m_cache` of each Dirichlet, initial first
+    type LnMCache = Vec<(f64, f64)>;
+    /// The posterior and its marginal likelihood cache
+    type LnPpCache = (Self, Vec<(f64, f64)>);
+
+    fn posterior(&self, x: &DataOrSuffStat<Vec<X>, MarkovChain>) -> Self {
+        extract_stat_then(
+            x,
+            || MarkovChainSuffStat::new(self.n_states()),
+            |stat: MarkovChainSuffStat| {
+                let mut dirs = self
+                    .with_counts(&stat)
+                    .map(|(dir, counts)| add_counts(dir, counts));
+                let initial = dirs.next().unwrap();
+                MarkovChainPrior {
+                    initial,
+                    rows: dirs.collect(),
+                }
+            },
+        )
+    }
+
+    fn ln_m_cache(&self) -> Self::LnMCache {
+        std::iter::once(&self.initial)
+            .chain(self.rows.iter())
+            .map(<Dirichlet as ConjugatePrior<X, Categorical>>::ln_m_cache)
+            .collect()
+    }
+
+    fn ln_m_with_cache(
+        &self,
+        cache: &Self::LnMCache,
+        x: &DataOrSuffStat<Vec<X>, MarkovChain>,
+    ) -> f64 {
+        extract_stat_then(
+            x,
+            || MarkovChainSuffStat::new(self.n_states()),
+            |stat: MarkovChainSuffStat| {
+                self.with_counts(&stat).zip(cache.iter()).fold(
+                    0.0,
+                    |acc, ((dir, counts), dir_cache)| {
+                        let stat = categorical_stat(counts);
+                        let data: DataOrSuffStat<X, Categorical> =
+                            DataOrSuffStat::SuffStat(&stat);
+                        acc + dir.ln_m_with_cache(dir_cache, &data)
+                    },
+                )
+            },
+        )
+    }
+
+    fn ln_pp_cache(
+        &self,
+        x: &DataOrSuffStat<Vec<X>, MarkovChain>,
+    ) -> Self::LnPpCache {
+        let post = self.posterior(x);
+        let cache =
+            <Self as ConjugatePrior<Vec<X>, MarkovChain>>::ln_m_cache(&post);
+        (post, cache)
+    }
+
+    /// The posterior predictive of a whole path, which accounts for the
+    /// path informing its own later transitions
+    fn ln_pp_with_cache(&self, cache: &Self::LnPpCache, y: &Vec<X>) -> f64 {
+        let (post, post_cache) = cache;
+        post.ln_m_with_cache(
+            post_cache,
+            &DataOrSuffStat::Data(std::slice::from_ref(y)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::MarkovChainSuffStat;
+
+    const TOL: f64 = 1E-12;
+
+    fn prior() -> MarkovChainPrior {
+        MarkovChainPrior::new(
+            Dirichlet::new(vec![1.0, 2.0]).unwrap(),
+            vec![
+                Dirichlet::new(vec![0.5, 1.5]).unwrap(),
+                Dirichlet::new(vec![2.0, 2.0]).unwrap(),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn ln_m_is_sum_of_dirichlet_categorical_marginals() {
+        let prior = prior();
+        let paths: Vec<Vec<usize>> = vec![vec![0, 0, 1, 1, 0], vec![1, 0]];
+        let data: DataOrSuffStat<Vec<usize>, MarkovChain> =
+            DataOrSuffStat::Data(&paths);
+
+        let m_initial =
+            prior.initial().ln_m(&DataOrSuffStat::Data(&[0_usize, 1]));
+        let m_row0 = prior.rows()[0].ln_m(&DataOrSuffStat::Data(&[0_usize, 1]));
+        let m_row1 =
+            prior.rows()[1].ln_m(&DataOrSuffStat::Data(&[1_usize, 0, 0]));
+        assert::close(prior.ln_m(&data), m_initial + m_row0 + m_row1, TOL);
+    }
+
+    #[test]
+    fn ln_pp_is_ratio_of_marginals() {
+        let prior = prior();
+        let paths: Vec<Vec<u8>> = vec![vec![0, 1, 1], vec![1, 1]];
+        let new_path: Vec<u8> = vec![1, 0, 0, 0];
+        let mut all = paths.clone();
+        all.push(new_path.clone());
+
+        let ln_pp = prior.ln_pp(&new_path, &DataOrSuffStat::Data(&paths));
+        let expected = prior.ln_m(&DataOrSuffStat::Data(&all))
+            - prior.ln_m(&DataOrSuffStat::Data(&paths));
+        assert::close(ln_pp, expected, TOL);
+    }
+
+    #[test]
+    fn posterior_mean_converges_to_transitions() {
+        let mut rng = rand::thread_rng();
+        let truth =
+            MarkovChain::new(&[0.5, 0.5], &[vec![0.9, 0.1], vec![0.3, 0.7]])
+                .unwrap();
+        let path: Vec<usize> = truth.simulate(20_000, &mut rng);
+        let mut stat = MarkovChainSuffStat::new(2);
+        stat.observe(&path);
+
+        let post = MarkovChainPrior::symmetric(1.0, 2)
+            .unwrap()
+            .posterior(&DataOrSuffStat::<Vec<usize>, _>::SuffStat(&stat));
+        let mean = post.mean_transition_matrix();
+        assert::close(mean[0][0], 0.9, 0.02);
+        assert::close(mean[1][1], 0.7, 0.02);
+
+        let draw: MarkovChain = post.draw(&mut rng);
+        assert::close(draw.transition_matrix()[0][0], 0.9, 0.03);
+        assert!(post.ln_f(&draw).is_finite());
+    }
+
+    #[test]
+    fn new_validates_shape() {
+        let dir = Dirichlet::symmetric(1.0, 2).unwrap();
+        assert_eq!(
+            MarkovChainPrior::new(dir.clone(), vec![dir.clone()]),
+            Err(MarkovChainError::InitialLengthMismatch {
+                n_initial: 2,
+                n_states: 1
+            })
+        );
+        assert!(matches!(
+            MarkovChainPrior::symmetric(0.0, 2),
+            Err(MarkovChainError::Dirichlet(_))
+        ));
+    }
+}
@@ -626,6 +626,20 @@ where
     }
 }
 
+impl<Fx: CacheControl> CacheControl for Mixture<Fx> {
+    fn precompute(&self) {
+        self.ln_weights();
+        self.components.iter().for_each(|cpnt| cpnt.precompute());
+    }
+
+    fn invalidate(&mut self) {
+        self.ln_weights = OnceLock::new();
+        self.components
+            .iter_mut()
+            .for_each(|cpnt| cpnt.invalidate());
+    }
+}
+
 impl std::error::Error for MixtureError {}
 
 impl fmt::Display for MixtureError {
@@ -1388,6 +1402,25 @@ mod tests {
             assert!(b > 20.0);
         }
 
+        #[test]
+        fn spread_out_gauss_mixture_cdf_quad() {
+            use crate::traits::QuadCdf;
+
+            let g1 = Gaussian::new(0.0, 0.1).unwrap();
+            let g2 = Gaussian::new(10.0, 0.5).unwrap();
+            let g3 = Gaussian::new(20.0, 0.2).unwrap();
+
+            let mm = Mixture::uniform(vec![g1, g2, g3]).unwrap();
+
+            for x in [-0.1, 0.05, 5.0, 10.3, 19.9, 25.0] {
+                assert::close(mm.cdf_quad(&x), mm.cdf(&x), 1E-9);
+            }
+            for p in [0.1, 0.5, 0.9] {
+                let x = mm.invcdf_quad(p);
+                assert::close(mm.cdf(&x), p, 1E-9);
+            }
+        }
+
         #[test]
         fn gauss_2_component_mixture_entropy() {
             let components = vec![
@@ -1450,4 +1483,21 @@ mod tests {
             assert!(0.0 < jsd);
         }
     }
+
+    #[test]
+    fn precompute_fills_component_caches() {
+        let inner = Mixture::uniform(vec![
+            Gaussian::new(-1.0, 1.0).unwrap(),
+            Gaussian::new(1.0, 2.0).unwrap(),
+        ])
+        .unwrap();
+        let mut mm = Mixture::uniform(vec![inner.clone(), inner]).unwrap();
+        mm.precompute();
+        assert!(mm.ln_weights.get().is_some());
+        assert!(mm.components.iter().all(|c| c.ln_weights.get().is_some()));
+
+        mm.invalidate();
+        assert!(mm.ln_weights.get().is_none());
+        assert!(mm.components.iter().all(|c| c.ln_weights.get().is_none()));
+    }
 }
@@ -7,34 +7,57 @@
 //!    on a continuum.
 //! 3. **Prior** distributions assign probability to other probability
 //!    distributions.
+mod affine;
 mod bernoulli;
 mod beta;
 mod beta_binom;
 mod binomial;
+mod bivariate_gaussian;
 mod categorical;
 mod cauchy;
 mod chi_squared;
+mod compound_poisson;
+mod condition;
+mod convolution;
 mod crp;
 mod dirichlet;
+mod discrete_survival;
 mod discrete_uniform;
+mod distorted;
 #[cfg(feature = "datum")]
 mod distribution;
+mod dpd;
+mod draw_policy;
 mod empirical;
 mod exponential;
+#[cfg(feature = "datum")]
+mod fit;
 mod gamma;
+mod gamma_rate_prior;
 mod gaussian;
 mod geometric;
 mod gev;
 mod inv_chi_squared;
 mod invgamma;
 mod invgaussian;
+mod joint;
+mod joint_categorical;
+mod kde;
 mod ks;
 mod kumaraswamy;
+mod labeled_categorical;
 mod laplace;
+mod log_uniform;
+mod logit_normal;
 mod lognormal;
+mod lomax;
+mod marginalize;
+mod markov_chain;
 mod mixture;
 #[cfg(feature = "arraydist")]
 mod mvg;
+#[cfg(feature = "arraydist")]
+mod mvg_diag;
 mod neg_binom;
 #[cfg(feature = "arraydist")]
 mod niw;
@@ -42,45 +65,85 @@ mod normal_gamma;
 mod normal_inv_chi_squared;
 mod normal_inv_gamma;
 mod pareto;
+mod pareto_shape_prior;
 mod poisson;
+mod pool;
 #[cfg(feature = "datum")]
 mod product;
+mod projection;
+mod quantile_match;
+mod record;
+mod risk;
+mod scaled_beta;
 mod scaled_inv_chi_squared;
+mod shared;
 mod skellam;
+mod stick_breaking;
 mod students_t;
 mod uniform;
 mod vonmises;
+mod weibull;
 #[cfg(feature = "arraydist")]
 mod wishart;
+mod zero_inflated;
 
+pub use affine::AffineError;
 pub use bernoulli::{Bernoulli, BernoulliError};
 pub use beta::{Beta, BetaError};
 pub use beta_binom::{BetaBinomial, BetaBinomialError};
 pub use binomial::{Binomial, BinomialError};
+pub use bivariate_gaussian::{BivariateGaussian, BivariateGaussianError};
 pub use categorical::{Categorical, CategoricalError};
 pub use cauchy::{Cauchy, CauchyError};
 pub use chi_squared::{ChiSquared, ChiSquaredError};
+pub use compound_poisson::{
+    Aggregation, CompoundPoisson, CompoundPoissonError,
+};
+pub use condition::ConditionError;
+pub use convolution::{convolve_grid, ConvolutionError};
+pub(crate) use crp::draw_alpha_posterior;
 pub use crp::{Crp, CrpError};
 pub use dirichlet::{Dirichlet, DirichletError, SymmetricDirichlet};
+pub use discrete_survival::{
+    CompetingRisks, DiscreteSurvival, DiscreteSurvivalError,
+};
 pub use discrete_uniform::{DiscreteUniform, DiscreteUniformError};
+pub(crate) use distorted::distorted_mean;
+pub use distorted::{Distorted, Distortion, DistortionError};
 #[cfg(feature = "datum")]
 pub use distribution::Distribution;
+pub use dpd::{Dpd, DpdError};
+pub use draw_policy::{DrawError, DrawPolicy};
 pub use empirical::Empirical;
 pub use exponential::{Exponential, ExponentialError};
+#[cfg(feature = "datum")]
+pub use fit::{fit_best, Family, FamilyFit, FitCriterion, FitError};
 pub use gamma::{Gamma, GammaError};
+pub use gamma_rate_prior::{GammaRatePrior, GammaRatePriorError};
 pub use gaussian::{Gaussian, GaussianError};
 pub use geometric::{Geometric, GeometricError};
 pub use gev::{Gev, GevError};
 pub use inv_chi_squared::{InvChiSquared, InvChiSquaredError};
 pub use invgamma::{InvGamma, InvGammaError};
 pub use invgaussian::{InvGaussian, InvGaussianError};
+pub use joint::Joint;
+pub use joint_categorical::{JointCategorical, JointCategoricalError};
+pub use kde::{Bandwidth, Kde, KdeError};
 pub use ks::KsTwoAsymptotic;
 pub use kumaraswamy::{Kumaraswamy, KumaraswamyError};
+pub use labeled_categorical::{LabeledCategorical, LabeledCategoricalError};
 pub use laplace::{Laplace, LaplaceError};
+pub use log_uniform::{LogUniform, LogUniformError};
+pub use logit_normal::{LogitNormal, LogitNormalError};
 pub use lognormal::{LogNormal, LogNormalError};
+pub use lomax::{Lomax, LomaxError};
+pub use marginalize::MarginalizeError;
+pub use markov_chain::{MarkovChain, MarkovChainError, MarkovChainPrior};
 pub use mixture::{Mixture, MixtureError};
 #[cfg(feature = "arraydist")]
 pub use mvg::{MvGaussian, MvGaussianError};
+#[cfg(feature = "arraydist")]
+pub use mvg_diag::{MvGaussianDiag, MvGaussianDiagError};
 pub use neg_binom::{NegBinomial, NegBinomialError};
 #[cfg(feature = "arraydist")]
 pub use niw::{NormalInvWishart, NormalInvWishartError};
@@ -90,15 +153,27 @@ pub use normal_inv_chi_squared::{
 };
 pub use normal_inv_gamma::{NormalInvGamma, NormalInvGammaError};
 pub use pareto::{Pareto, ParetoError};
+pub use pareto_shape_prior::{ParetoShapePrior, ParetoShapePriorError};
 pub use poisson::{Poisson, PoissonError};
+pub use pool::{linear_pool, LogLinearPool, PoolError};
 #[cfg(feature = "datum")]
 pub use product::ProductDistribution;
+pub(crate) use projection::project;
+pub use projection::{ProjectionError, ProjectionMethod};
+pub use quantile_match::QuantileMatchError;
+pub use record::{RecordError, RecordTime, RecordValue, RecordWaitingTime};
+pub(crate) use risk::quantile_tail_mean;
+pub use scaled_beta::{ScaledBeta, ScaledBetaError};
 pub use scaled_inv_chi_squared::{
     ScaledInvChiSquared, ScaledInvChiSquaredError,
 };
+pub use shared::SharedRv;
 pub use skellam::{Skellam, SkellamError};
+pub use stick_breaking::{StickBreaking, StickBreakingError};
 pub use students_t::{StudentsT, StudentsTError};
 pub use uniform::{Uniform, UniformError};
 pub use vonmises::{VonMises, VonMisesError};
+pub use weibull::{Weibull, WeibullError};
 #[cfg(feature = "arraydist")]
 pub use wishart::{InvWishart, InvWishartError};
+pub use zero_inflated::{ZeroInflated, ZeroInflatedError};
@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 use crate::consts::HALF_LN_2PI_E;
 use crate::consts::LN_2PI;
 use crate::data::MvGaussianSuffStat;
+use crate::dist::condition::free_indices;
+use crate::dist::marginalize::dropped_indices;
+use crate::dist::{ConditionError, MarginalizeError};
 use crate::impl_display;
 use crate::traits::*;
 use nalgebra::linalg::Cholesky;
@@ -17,25 +20,30 @@ use std::sync::OnceLock;
 struct MvgCache {
     /// Covariant Matrix Cholesky Decomposition
     pub cov_chol: Cholesky<f64, Dyn>,
-    /// Inverse of Covariance Matrix
-    pub cov_inv: DMatrix<f64>,
+    /// Log determinant of the covariance matrix
+    pub ln_det: f64,
 }
 
 impl MvgCache {
     pub fn from_cov(cov: &DMatrix<f64>) -> Result<Self, MvGaussianError> {
         match cov.clone().cholesky() {
             None => Err(MvGaussianError::CovNotPositiveSemiDefinite),
-            Some(cov_chol) => {
-                let cov_inv = cov_chol.inverse();
-                Ok(MvgCache { cov_chol, cov_inv })
-            }
+            Some(cov_chol) => Ok(MvgCache::from_chol(cov_chol)),
         }
     }
 
     #[inline]
     pub fn from_chol(cov_chol: Cholesky<f64, Dyn>) -> Self {
-        let cov_inv = cov_chol.inverse();
-        MvgCache { cov_chol, cov_inv }
+        let ln_det = cov_chol.ln_determinant();
+        MvgCache { cov_chol, ln_det }
+    }
+
+    /// Solve L z = x, where L is the lower Cholesky factor
+    #[inline]
+    pub fn whiten(&self, x: DVector<f64>) -> DVector<f64> {
+        let mut z = x;
+        self.cov_chol.l_dirty().solve_lower_triangular_mut(&mut z);
+        z
     }
 
     #[inline]
@@ -48,6 +56,11 @@ impl MvgCache {
 /// [Multivariate Gaussian/Normal Distribution](https://en.wikipedia.org/wiki/Multivariate_normal_distribution),
 /// 𝒩(μ, Σ).
 ///
+/// The Cholesky factor of Σ is cached, so after the first call `ln_f` and
+/// `draw` cost O(k²) rather than O(k³). It is computed when the distribution
+/// is created with [`MvGaussian::new`], lazily otherwise, and is invalidated
+/// when the covariance changes.
+///
 /// # Example
 ///
 /// Generate a Wishart random 3x3 matrix **Σ** ~ W<sub>ν</sub>(S)
@@ -209,10 +222,15 @@ impl MvGaussian {
 
     /// Creates a new MvGaussian from mean and covariance without checking
     /// whether the parameters are valid.
+    ///
+    /// The Cholesky factor of the covariance is computed on first use.
     #[inline]
     pub fn new_unchecked(mu: DVector<f64>, cov: DMatrix<f64>) -> Self {
-        let cache = OnceLock::from(MvgCache::from_cov(&cov).unwrap());
-        MvGaussian { mu, cov, cache }
+        MvGaussian {
+            mu,
+            cov,
+            cache: OnceLock::new(),
+        }
     }
 
     /// Creates a new MvGaussian from mean and covariance's Cholesky factorization
@@ -373,11 +391,12 @@ impl MvGaussian {
     }
 
     /// Set the covariance matrix without input validation
+    ///
+    /// The Cholesky factor of the covariance is recomputed on next use.
     #[inline]
     pub fn set_cov_unchecked(&mut self, cov: DMatrix<f64>) {
-        let cache = MvgCache::from_cov(&cov).unwrap();
         self.cov = cov;
-        self.cache = OnceLock::from(cache);
+        self.cache = OnceLock::new();
     }
 
     #[inline]
@@ -397,19 +416,10 @@ impl_display!(MvGaussian);
 
 impl Rv<DVector<f64>> for MvGaussian {
     fn ln_f(&self, x: &DVector<f64>) -> f64 {
-        let diff = x - &self.mu;
-        let det_sqrt: f64 = self
-            .cache()
-            .cov_chol
-            .l_dirty()
-            .diagonal()
-            .row_iter()
-            .fold(1.0, |acc, y| acc * y[0]);
-
-        let det = det_sqrt * det_sqrt;
-        let inv = &(self.cache().cov_inv);
-        let term: f64 = (diff.transpose() * inv * &diff)[0];
-        -0.5 * (det.ln() + (diff.nrows() as f64).mul_add(LN_2PI, term))
+        let cache = self.cache();
+        let z = cache.whiten(x - &self.mu);
+        let term = z.norm_squared();
+        -0.5 * (cache.ln_det + (z.nrows() as f64).mul_add(LN_2PI, term))
     }
 
     fn draw<R: Rng>(&self, rng: &mut R) -> DVector<f64> {
@@ -458,19 +468,85 @@ impl Variance<DMatrix<f64>> for MvGaussian {
 
 impl Entropy for MvGaussian {
     fn entropy(&self) -> f64 {
-        let det_sqrt: f64 = self
-            .cache()
-            .cov_chol
-            .l_dirty()
-            .diagonal()
-            .row_iter()
-            .fold(1.0, |acc, x| acc * x[0]);
-        let det = det_sqrt * det_sqrt;
-        det.ln()
+        self.cache()
+            .ln_det
             .mul_add(0.5, HALF_LN_2PI_E * (self.cov.nrows() as f64))
     }
 }
 
+/// Condition on the values of some dimensions, given as `(index, value)`
+/// pairs, to get the Gaussian of the remaining dimensions
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::{DMatrix, DVector};
+/// use rv::dist::MvGaussian;
+/// use rv::traits::Condition;
+///
+/// let mu = DVector::from_vec(vec![0.0, 1.0, 2.0]);
+/// let cov = DMatrix::from_row_slice(3, 3, &[
+///     1.0, 0.5, 0.0,
+///     0.5, 2.0, 0.3,
+///     0.0, 0.3, 1.0,
+/// ]);
+/// let mvg = MvGaussian::new(mu, cov).unwrap();
+///
+/// // Dimensions 0 and 2 given dimension 1 is 2.0
+/// let cond = mvg.condition(&[(1, 2.0)]).unwrap();
+/// assert_eq!(cond.ndims(), 2);
+/// assert!((cond.mu()[0] - 0.25).abs() < 1E-12);
+/// assert!((cond.cov()[(0, 0)] - 0.875).abs() < 1E-12);
+/// ```
+impl Condition<[(usize, f64)]> for MvGaussian {
+    type Conditional = MvGaussian;
+
+    fn condition(
+        &self,
+        evidence: &[(usize, f64)],
+    ) -> Result<MvGaussian, ConditionError> {
+        let free =
+            free_indices(evidence.iter().map(|(ix, _)| ix), self.ndims())?;
+        if evidence.is_empty() {
+            return Ok(self.clone());
+        } else if evidence.iter().any(|(_, x)| !x.is_finite()) {
+            return Err(ConditionError::ImpossibleEvidence);
+        }
+
+        let obs: Vec<usize> = evidence.iter().map(|&(ix, _)| ix).collect();
+        let x_obs =
+            DVector::from_iterator(obs.len(), evidence.iter().map(|&(_, x)| x));
+        let cov_free = self.cov.select_rows(&free);
+        let cov_ff = cov_free.select_columns(&free);
+        let cov_fo = cov_free.select_columns(&obs);
+        let cov_oo = self.cov.select_rows(&obs).select_columns(&obs);
+        let chol = cov_oo.cholesky().ok_or(ConditionError::Degenerate)?;
+
+        let mu = self.mu.select_rows(&free)
+            + &cov_fo * chol.solve(&(x_obs - self.mu.select_rows(&obs)));
+        let cov = cov_ff - &cov_fo * chol.solve(&cov_fo.transpose());
+        // Remove the asymmetry from round off
+        let cov = (&cov + cov.transpose()) * 0.5;
+        MvGaussian::new(mu, cov).map_err(|_| ConditionError::Degenerate)
+    }
+}
+
+impl Marginalize for MvGaussian {
+    type Marginal = MvGaussian;
+
+    fn marginalize(
+        &self,
+        ixs: &[usize],
+    ) -> Result<MvGaussian, MarginalizeError> {
+        dropped_indices(ixs, self.ndims())?;
+        let mu = self.mu.select_rows(ixs);
+        let cov = self.cov.select_rows(ixs).select_columns(ixs);
+        // A principal submatrix of a positive definite matrix is positive
+        // definite
+        Ok(MvGaussian::new_unchecked(mu, cov))
+    }
+}
+
 impl HasSuffStat<DVector<f64>> for MvGaussian {
     type Stat = MvGaussianSuffStat;
     fn empty_suffstat(&self) -> Self::Stat {
@@ -479,25 +555,31 @@ impl HasSuffStat<DVector<f64>> for MvGaussian {
 
     fn ln_f_stat(&self, stat: &Self::Stat) -> f64 {
         let n = stat.n() as f64;
-        let k = stat.sum_x().len() as f64;
-        let x_bar = stat.sum_x() / n;
-        let sigma_hat =
-            stat.sum_x_sq() - (stat.sum_x() * stat.sum_x().transpose()) / n;
-        let sigma_inv = &self.cache().cov_inv;
-        let ln_cov_det = self.cache().cov_chol.ln_determinant();
+        let k = stat.mean().len() as f64;
+        let cache = self.cache();
+        let z = cache.whiten(stat.mean() - &self.mu);
+        // tr(Σ⁻¹ S)
+        let scatter_term = cache.cov_chol.solve(stat.scatter()).trace();
 
         let neg_half_n = -0.5 * n;
 
         neg_half_n.mul_add(
-            LN_2PI.mul_add(k, ln_cov_det)
-                + ((&x_bar - &self.mu).transpose()
-                    * sigma_inv
-                    * (&x_bar - &self.mu))[0],
-            -(sigma_inv * sigma_hat).trace() / 2.0,
+            LN_2PI.mul_add(k, cache.ln_det) + z.norm_squared(),
+            -scatter_term / 2.0,
         )
     }
 }
 
+impl CacheControl for MvGaussian {
+    fn precompute(&self) {
+        self.cache();
+    }
+
+    fn invalidate(&mut self) {
+        self.cache = OnceLock::new();
+    }
+}
+
 impl std::error::Error for MvGaussianError {}
 
 impl fmt::Display for MvGaussianError {
@@ -801,4 +883,107 @@ mod tests {
             assert::close(f.ln_f_stat(&stat), ln_f_sum, 1E-13);
         }
     }
+
+    #[test]
+    fn condition_matches_bivariate_gaussian() {
+        use crate::dist::BivariateGaussian;
+
+        let mvg = MvGaussian::new(
+            dvector![1.0, -2.0],
+            dmatrix![2.25, 0.63; 0.63, 0.49],
+        )
+        .unwrap();
+        let bvg = BivariateGaussian::new((1.0, -2.0), (1.5, 0.7), 0.6).unwrap();
+
+        let cond = mvg.condition(&[(0, 0.4)]).unwrap();
+        let expected = bvg.condition(&(0, 0.4)).unwrap();
+        assert::close(cond.mu()[0], expected.mu(), TOL);
+        assert::close(cond.cov()[(0, 0)].sqrt(), expected.sigma(), TOL);
+
+        assert_eq!(
+            mvg.condition(&[(0, 0.4), (1, 0.0)]),
+            Err(ConditionError::NoFreeVariables)
+        );
+        assert_eq!(mvg.condition(&[]).unwrap(), mvg);
+    }
+
+    #[test]
+    fn marginalize_matches_bivariate_gaussian() {
+        use crate::dist::BivariateGaussian;
+
+        let mvg = MvGaussian::new(
+            dvector![1.0, 5.0, -2.0],
+            dmatrix![2.25, 0.1, 0.63; 0.1, 1.0, 0.2; 0.63, 0.2, 0.49],
+        )
+        .unwrap();
+        let bvg = BivariateGaussian::new((1.0, -2.0), (1.5, 0.7), 0.6).unwrap();
+
+        let marginal = mvg.marginalize(&[0, 2]).unwrap();
+        for (x, y) in [(0.0, 0.0), (1.3, -2.5), (-1.0, 1.0)] {
+            assert::close(
+                marginal.ln_f(&dvector![x, y]),
+                bvg.ln_f(&(x, y)),
+                TOL,
+            );
+        }
+
+        // Reordering the selection permutes the marginal
+        let flipped = mvg.marginalize(&[2, 0]).unwrap();
+        assert::close(
+            flipped.ln_f(&dvector![-2.5, 1.3]),
+            marginal.ln_f(&dvector![1.3, -2.5]),
+            TOL,
+        );
+
+        let x_marginal = mvg.marginalize(&[0]).unwrap();
+        assert::close(
+            x_marginal.ln_f(&dvector![0.4]),
+            bvg.marginal_x().ln_f(&0.4),
+            TOL,
+        );
+    }
+
+    #[test]
+    fn marginalize_commutes_with_condition() {
+        let mvg = MvGaussian::new(
+            dvector![1.0, 5.0, -2.0],
+            dmatrix![2.25, 0.1, 0.63; 0.1, 1.0, 0.2; 0.63, 0.2, 0.49],
+        )
+        .unwrap();
+
+        // x₀ | x₂ from the marginal of (x₀, x₂) and from x₀, x₁ | x₂
+        let a = mvg
+            .marginalize(&[0, 2])
+            .unwrap()
+            .condition(&[(1, 0.3)])
+            .unwrap();
+        let b = mvg
+            .condition(&[(2, 0.3)])
+            .unwrap()
+            .marginalize(&[0])
+            .unwrap();
+        assert::close(a.mu()[0], b.mu()[0], TOL);
+        assert::close(a.cov()[(0, 0)], b.cov()[(0, 0)], TOL);
+    }
+
+    #[test]
+    fn precompute_from_many_threads() {
+        let mvg =
+            MvGaussian::new(dvector![0.0, 1.0], dmatrix![2.0, 0.5; 0.5, 1.0])
+                .unwrap();
+        let x = dvector![0.2, -0.3];
+        let ln_f = mvg.clone().ln_f(&x);
+
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| mvg.precompute());
+            }
+        });
+        assert!(mvg.cache.get().is_some());
+        assert::close(mvg.ln_f(&x), ln_f, TOL);
+
+        let mut mvg = mvg;
+        mvg.invalidate();
+        assert!(mvg.cache.get().is_none());
+    }
 }
@@ -0,0 +1,597 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::consts::{HALF_LN_2PI, HALF_LN_2PI_E};
+use crate::data::MvGaussianSuffStat;
+use crate::dist::condition::free_indices;
+use crate::dist::marginalize::dropped_indices;
+use crate::dist::{ConditionError, MarginalizeError, MvGaussian};
+use crate::impl_display;
+use crate::traits::*;
+use nalgebra::{DMatrix, DVector};
+use rand::Rng;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Multivariate Gaussian with diagonal covariance, 𝒩(μ, diag(σ²))
+///
+/// The dimensions are independent, so the density, draws, and sufficient
+/// statistic likelihood cost O(k) rather than the O(k²) and O(k³) of the
+/// dense [`MvGaussian`]. Converts to the dense representation with `From`,
+/// and from it with `TryFrom` when its covariance is diagonal.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::DVector;
+/// use rv::dist::{MvGaussian, MvGaussianDiag};
+/// use rv::traits::Rv;
+///
+/// let mu = DVector::from_vec(vec![0.0, 1.0, -1.0]);
+/// let sigma = DVector::from_vec(vec![1.0, 0.5, 2.0]);
+/// let diag = MvGaussianDiag::new(mu, sigma).unwrap();
+///
+/// let x = DVector::from_vec(vec![0.3, 0.9, 0.0]);
+/// let dense = MvGaussian::from(&diag);
+/// assert!((diag.ln_f(&x) - dense.ln_f(&x)).abs() < 1E-12);
+///
+/// let back = MvGaussianDiag::try_from(&dense).unwrap();
+/// assert_eq!(back, diag);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct MvGaussianDiag {
+    // Mean vector
+    mu: DVector<f64>,
+    // Standard deviation of each dimension
+    sigma: DVector<f64>,
+    // Cached Σ ln σ
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    sum_ln_sigma: OnceLock<f64>,
+}
+
+impl PartialEq for MvGaussianDiag {
+    fn eq(&self, other: &MvGaussianDiag) -> bool {
+        self.mu == other.mu && self.sigma == other.sigma
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum MvGaussianDiagError {
+    /// The mu and sigma parameters have different lengths
+    MuSigmaDimensionMismatch {
+        /// Length of the mu vector
+        n_mu: usize,
+        /// Length of the sigma vector
+        n_sigma: usize,
+    },
+    /// An entry of mu is infinite or NaN
+    MuNotFinite { ix: usize, mu: f64 },
+    /// An entry of sigma is less than or equal to zero
+    SigmaTooLow { ix: usize, sigma: f64 },
+    /// An entry of sigma is infinite or NaN
+    SigmaNotFinite { ix: usize, sigma: f64 },
+    /// The covariance of a dense MvGaussian has non-zero off-diagonal
+    /// entries
+    CovNotDiagonal,
+    /// Requested dimension is too low
+    ZeroDimension,
+}
+
+fn validate_mu(mu: &DVector<f64>) -> Result<(), MvGaussianDiagError> {
+    mu.iter().enumerate().try_for_each(|(ix, &mu)| {
+        if mu.is_finite() {
+            Ok(())
+        } else {
+            Err(MvGaussianDiagError::MuNotFinite { ix, mu })
+        }
+    })
+}
+
+fn validate_sigma(sigma: &DVector<f64>) -> Result<(), MvGaussianDiagError> {
+    sigma.iter().enumerate().try_for_each(|(ix, &sigma)| {
+        if sigma <= 0.0 {
+            Err(MvGaussianDiagError::SigmaTooLow { ix, sigma })
+        } else if !sigma.is_finite() {
+            Err(MvGaussianDiagError::SigmaNotFinite { ix, sigma })
+        } else {
+            Ok(())
+        }
+    })
+}
+
+impl MvGaussianDiag {
+    /// Create a new diagonal multivariate Gaussian distribution
+    ///
+    /// # Arguments
+    /// - mu: k-length mean vector
+    /// - sigma: k-length vector of positive standard deviations
+    pub fn new(
+        mu: DVector<f64>,
+        sigma: DVector<f64>,
+    ) -> Result<Self, MvGaussianDiagError> {
+        if mu.is_empty() {
+            Err(MvGaussianDiagError::ZeroDimension)
+        } else if mu.len() != sigma.len() {
+            Err(MvGaussianDiagError::MuSigmaDimensionMismatch {
+                n_mu: mu.len(),
+                n_sigma: sigma.len(),
+            })
+        } else {
+            validate_mu(&mu)?;
+            validate_sigma(&sigma)?;
+            Ok(MvGaussianDiag::new_unchecked(mu, sigma))
+        }
+    }
+
+    /// Creates a new MvGaussianDiag without checking whether the parameters
+    /// are valid.
+    #[inline]
+    pub fn new_unchecked(mu: DVector<f64>, sigma: DVector<f64>) -> Self {
+        MvGaussianDiag {
+            mu,
+            sigma,
+            sum_ln_sigma: OnceLock::new(),
+        }
+    }
+
+    /// Create a standard Gaussian distribution with zero mean and identity
+    /// covariance matrix.
+    #[inline]
+    pub fn standard(dims: usize) -> Result<Self, MvGaussianDiagError> {
+        if dims == 0 {
+            Err(MvGaussianDiagError::ZeroDimension)
+        } else {
+            Ok(MvGaussianDiag {
+                mu: DVector::zeros(dims),
+                sigma: DVector::repeat(dims, 1.0),
+                sum_ln_sigma: OnceLock::from(0.0),
+            })
+        }
+    }
+
+    /// Get the number of dimensions
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rv::dist::MvGaussianDiag;
+    /// let mvg = MvGaussianDiag::standard(4).unwrap();
+    /// assert_eq!(mvg.ndims(), 4);
+    /// ```
+    #[inline]
+    pub fn ndims(&self) -> usize {
+        self.mu.len()
+    }
+
+    /// Get a reference to the mean
+    #[inline]
+    pub fn mu(&self) -> &DVector<f64> {
+        &self.mu
+    }
+
+    /// Get a reference to the standard deviations
+    #[inline]
+    pub fn sigma(&self) -> &DVector<f64> {
+        &self.sigma
+    }
+
+    /// Set the mean
+    #[inline]
+    pub fn set_mu(
+        &mut self,
+        mu: DVector<f64>,
+    ) -> Result<(), MvGaussianDiagError> {
+        if mu.len() != self.sigma.len() {
+            Err(MvGaussianDiagError::MuSigmaDimensionMismatch {
+                n_mu: mu.len(),
+                n_sigma: self.sigma.len(),
+            })
+        } else {
+            validate_mu(&mu)?;
+            self.set_mu_unchecked(mu);
+            Ok(())
+        }
+    }
+
+    /// Set the mean without input validation
+    #[inline]
+    pub fn set_mu_unchecked(&mut self, mu: DVector<f64>) {
+        self.mu = mu;
+    }
+
+    /// Set the standard deviations
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rv::dist::MvGaussianDiag;
+    /// # use nalgebra::DVector;
+    /// let mut mvg = MvGaussianDiag::standard(2).unwrap();
+    /// mvg.set_sigma(DVector::from_vec(vec![0.5, 2.0])).unwrap();
+    /// assert_eq!(mvg.sigma()[1], 2.0);
+    ///
+    /// assert!(mvg.set_sigma(DVector::from_vec(vec![1.0, 0.0])).is_err());
+    /// assert!(mvg.set_sigma(DVector::from_vec(vec![1.0])).is_err());
+    /// ```
+    #[inline]
+    pub fn set_sigma(
+        &mut self,
+        sigma: DVector<f64>,
+    ) -> Result<(), MvGaussianDiagError> {
+        if sigma.len() != self.mu.len() {
+            Err(MvGaussianDiagError::MuSigmaDimensionMismatch {
+                n_mu: self.mu.len(),
+                n_sigma: sigma.len(),
+            })
+        } else {
+            validate_sigma(&sigma)?;
+            self.set_sigma_unchecked(sigma);
+            Ok(())
+        }
+    }
+
+    /// Set the standard deviations without input validation
+    #[inline]
+    pub fn set_sigma_unchecked(&mut self, sigma: DVector<f64>) {
+        self.sigma = sigma;
+        self.sum_ln_sigma = OnceLock::new();
+    }
+
+    /// The dense covariance matrix, diag(σ²)
+    pub fn cov(&self) -> DMatrix<f64> {
+        DMatrix::from_diagonal(&self.sigma.map(|s| s * s))
+    }
+
+    /// Σ ln σ, half the log determinant of the covariance
+    #[inline]
+    fn sum_ln_sigma(&self) -> f64 {
+        *self
+            .sum_ln_sigma
+            .get_or_init(|| self.sigma.iter().map(|s| s.ln()).sum())
+    }
+
+    /// Σ ((x - μ) / σ)²
+    #[inline]
+    fn mahalanobis_sq(&self, x: &DVector<f64>) -> f64 {
+        x.iter()
+            .zip(self.mu.iter().zip(self.sigma.iter()))
+            .map(|(&x, (&mu, &sigma))| ((x - mu) / sigma).powi(2))
+            .sum()
+    }
+}
+
+impl From<MvGaussianDiag> for MvGaussian {
+    fn from(mvg: MvGaussianDiag) -> Self {
+        MvGaussian::from(&mvg)
+    }
+}
+
+impl From<&MvGaussianDiag> for MvGaussian {
+    fn from(mvg: &MvGaussianDiag) -> Self {
+        MvGaussian::new_unchecked(mvg.mu.clone(), mvg.cov())
+    }
+}
+
+impl TryFrom<&MvGaussian> for MvGaussianDiag {
+    type Error = MvGaussianDiagError;
+
+    fn try_from(mvg: &MvGaussian) -> Result<Self, MvGaussianDiagError> {
+        let cov = mvg.cov();
+        let k = mvg.ndims();
+        let off_diagonal =
+            (0..k).any(|i| (0..k).any(|j| i != j && cov[(i, j)] != 0.0));
+        if off_diagonal {
+            Err(MvGaussianDiagError::CovNotDiagonal)
+        } else {
+            MvGaussianDiag::new(mvg.mu().clone(), cov.diagonal().map(f64::sqrt))
+        }
+    }
+}
+
+impl TryFrom<MvGaussian> for MvGaussianDiag {
+    type Error = MvGaussianDiagError;
+
+    fn try_from(mvg: MvGaussian) -> Result<Self, MvGaussianDiagError> {
+        MvGaussianDiag::try_from(&mvg)
+    }
+}
+
+impl From<&MvGaussianDiag> for String {
+    fn from(mvg: &MvGaussianDiag) -> String {
+        format!(
+            "Nₖ({}, diag)\n  μ: {}\n  σ: {})",
+            mvg.ndims(),
+            mvg.mu,
+            mvg.sigma
+        )
+    }
+}
+
+impl_display!(MvGaussianDiag);
+
+impl Rv<DVector<f64>> for MvGaussianDiag {
+    fn ln_f(&self, x: &DVector<f64>) -> f64 {
+        let k = self.ndims() as f64;
+        (-0.5_f64).mul_add(
+            self.mahalanobis_sq(x),
+            -k.mul_add(HALF_LN_2PI, self.sum_ln_sigma()),
+        )
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> DVector<f64> {
+        let norm = rand_distr::StandardNormal;
+        DVector::from_iterator(
+            self.ndims(),
+            self.mu.iter().zip(self.sigma.iter()).map(|(&mu, &sigma)| {
+                let z: f64 = rng.sample(norm);
+                sigma.mul_add(z, mu)
+            }),
+        )
+    }
+}
+
+impl Support<DVector<f64>> for MvGaussianDiag {
+    fn supports(&self, x: &DVector<f64>) -> bool {
+        x.len() == self.mu.len()
+    }
+}
+
+impl ContinuousDistr<DVector<f64>> for MvGaussianDiag {}
+
+impl Mean<DVector<f64>> for MvGaussianDiag {
+    fn mean(&self) -> Option<DVector<f64>> {
+        Some(self.mu.clone())
+    }
+}
+
+impl Mode<DVector<f64>> for MvGaussianDiag {
+    fn mode(&self) -> Option<DVector<f64>> {
+        Some(self.mu.clone())
+    }
+}
+
+/// The variance of each dimension, the diagonal of the covariance. Use
+/// [`MvGaussianDiag::cov`] for the dense matrix.
+impl Variance<DVector<f64>> for MvGaussianDiag {
+    fn variance(&self) -> Option<DVector<f64>> {
+        Some(self.sigma.map(|s| s * s))
+    }
+}
+
+impl Entropy for MvGaussianDiag {
+    fn entropy(&self) -> f64 {
+        HALF_LN_2PI_E.mul_add(self.ndims() as f64, self.sum_ln_sigma())
+    }
+}
+
+/// Condition on the values of some dimensions, given as `(index, value)`
+/// pairs. The dimensions are independent, so the conditional is the marginal
+/// of the remaining dimensions.
+impl Condition<[(usize, f64)]> for MvGaussianDiag {
+    type Conditional = MvGaussianDiag;
+
+    fn condition(
+        &self,
+        evidence: &[(usize, f64)],
+    ) -> Result<MvGaussianDiag, ConditionError> {
+        let free =
+            free_indices(evidence.iter().map(|(ix, _)| ix), self.ndims())?;
+        if evidence.iter().any(|(_, x)| !x.is_finite()) {
+            return Err(ConditionError::ImpossibleEvidence);
+        }
+        Ok(MvGaussianDiag::new_unchecked(
+            self.mu.select_rows(&free),
+            self.sigma.select_rows(&free),
+        ))
+    }
+}
+
+impl Marginalize for MvGaussianDiag {
+    type Marginal = MvGaussianDiag;
+
+    fn marginalize(
+        &self,
+        ixs: &[usize],
+    ) -> Result<MvGaussianDiag, MarginalizeError> {
+        dropped_indices(ixs, self.ndims())?;
+        Ok(MvGaussianDiag::new_unchecked(
+            self.mu.select_rows(ixs),
+            self.sigma.select_rows(ixs),
+        ))
+    }
+}
+
+impl HasSuffStat<DVector<f64>> for MvGaussianDiag {
+    type Stat = MvGaussianSuffStat;
+
+    fn empty_suffstat(&self) -> Self::Stat {
+        MvGaussianSuffStat::new(self.mu.len())
+    }
+
+    fn ln_f_stat(&self, stat: &Self::Stat) -> f64 {
+        let n = stat.n() as f64;
+        let k = self.ndims() as f64;
+        // Only the diagonal of the scatter matrix enters the likelihood
+        let quad: f64 = (0..self.ndims())
+            .map(|i| {
+                let d = stat.mean()[i] - self.mu[i];
+                n.mul_add(d * d, stat.scatter()[(i, i)])
+                    / (self.sigma[i] * self.sigma[i])
+            })
+            .sum();
+        (-n).mul_add(k.mul_add(HALF_LN_2PI, self.sum_ln_sigma()), -0.5 * quad)
+    }
+}
+
+impl CacheControl for MvGaussianDiag {
+    fn precompute(&self) {
+        self.sum_ln_sigma();
+    }
+
+    fn invalidate(&mut self) {
+        self.sum_ln_sigma = OnceLock::new();
+    }
+}
+
+impl std::error::Error for MvGaussianDiagError {}
+
+impl fmt::Display for MvGaussianDiagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MuSigmaDimensionMismatch { n_mu, n_sigma } => write!(
+                f,
+                "mu and sigma do not align. mu is {} dimensions but sigma \
+                    is {} dimensions",
+                n_mu, n_sigma
+            ),
+            Self::MuNotFinite { ix, mu } => {
+                write!(f, "non-finite mu at index {}: {}", ix, mu)
+            }
+            Self::SigmaTooLow { ix, sigma } => write!(
+                f,
+                "sigma at index {} ({}) must be greater than zero",
+                ix, sigma
+            ),
+            Self::SigmaNotFinite { ix, sigma } => {
+                write!(f, "non-finite sigma at index {}: {}", ix, sigma)
+            }
+            Self::CovNotDiagonal => {
+                write!(f, "covariance has non-zero off-diagonal entries")
+            }
+            Self::ZeroDimension => write!(f, "requested dimension is too low"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::misc::ks_test;
+    use crate::test_basic_impls;
+    use nalgebra::{dmatrix, dvector};
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    test_basic_impls!(MvGaussianDiag::standard(3).unwrap(), DVector::zeros(3));
+
+    fn mvg() -> MvGaussianDiag {
+        MvGaussianDiag::new(dvector![0.5, -1.0, 2.0], dvector![1.5, 0.3, 2.0])
+            .unwrap()
+    }
+
+    #[test]
+    fn new_validates_parameters() {
+        assert_eq!(
+            MvGaussianDiag::new(dvector![0.0, 1.0], dvector![1.0]),
+            Err(MvGaussianDiagError::MuSigmaDimensionMismatch {
+                n_mu: 2,
+                n_sigma: 1
+            })
+        );
+        assert_eq!(
+            MvGaussianDiag::new(dvector![0.0, 1.0], dvector![1.0, -1.0]),
+            Err(MvGaussianDiagError::SigmaTooLow { ix: 1, sigma: -1.0 })
+        );
+        assert!(MvGaussianDiag::new(dvector![f64::NAN], dvector![1.0]).is_err());
+        assert_eq!(
+            MvGaussianDiag::standard(0),
+            Err(MvGaussianDiagError::ZeroDimension)
+        );
+    }
+
+    #[test]
+    fn ln_f_and_entropy_match_dense() {
+        let diag = mvg();
+        let dense = MvGaussian::from(&diag);
+        for x in [dvector![0.0, 0.0, 0.0], dvector![1.2, -0.8, 5.0]] {
+            assert::close(diag.ln_f(&x), dense.ln_f(&x), TOL);
+        }
+        assert::close(diag.entropy(), dense.entropy(), TOL);
+    }
+
+    #[test]
+    fn ln_f_stat_matches_dense() {
+        let diag = mvg();
+        let dense = MvGaussian::from(&diag);
+        let mut stat = diag.empty_suffstat();
+        stat.observe_many(&[
+            dvector![1.0, 2.0, 0.0],
+            dvector![3.0, -4.0, 1.0],
+            dvector![0.5, 0.6, -2.0],
+        ]);
+        assert::close(diag.ln_f_stat(&stat), dense.ln_f_stat(&stat), 1E-10);
+    }
+
+    #[test]
+    fn condition_and_marginalize_match_dense() {
+        let diag = mvg();
+        let dense = MvGaussian::from(&diag);
+
+        let cond = diag.condition(&[(1, 0.4)]).unwrap();
+        assert_eq!(
+            MvGaussian::from(&cond),
+            dense.condition(&[(1, 0.4)]).unwrap()
+        );
+
+        let marg = diag.marginalize(&[2, 0]).unwrap();
+        assert_eq!(
+            MvGaussian::from(&marg),
+            dense.marginalize(&[2, 0]).unwrap()
+        );
+
+        assert_eq!(
+            diag.condition(&[(0, 1.0), (1, 1.0), (2, 1.0)]),
+            Err(ConditionError::NoFreeVariables)
+        );
+    }
+
+    #[test]
+    fn try_from_dense_requires_diagonal_cov() {
+        let dense =
+            MvGaussian::new(dvector![0.0, 1.0], dmatrix![2.0, 0.5; 0.5, 1.0])
+                .unwrap();
+        assert_eq!(
+            MvGaussianDiag::try_from(&dense),
+            Err(MvGaussianDiagError::CovNotDiagonal)
+        );
+
+        let dense =
+            MvGaussian::new(dvector![0.0, 1.0], dmatrix![4.0, 0.0; 0.0, 1.0])
+                .unwrap();
+        let diag = MvGaussianDiag::try_from(dense).unwrap();
+        assert_eq!(diag.sigma(), &dvector![2.0, 1.0]);
+    }
+
+    #[test]
+    fn draw_marginals_match_gaussians() {
+        use crate::dist::Gaussian;
+
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x1234);
+        let diag = mvg();
+        let xs = diag.sample(1000, &mut rng);
+        for i in 0..diag.ndims() {
+            let g = Gaussian::new(diag.mu()[i], diag.sigma()[i]).unwrap();
+            let xi: Vec<f64> = xs.iter().map(|x| x[i]).collect();
+            let (_, p) = ks_test(&xi, |x| g.cdf(&x));
+            assert!(p > 0.01);
+        }
+    }
+
+    #[test]
+    fn set_sigma_invalidates_cache() {
+        let mut diag = mvg();
+        diag.precompute();
+        diag.set_sigma(dvector![1.0, 1.0, 1.0]).unwrap();
+        let x = dvector![0.0, 0.0, 0.0];
+        let fresh =
+            MvGaussianDiag::new(diag.mu().clone(), dvector![1.0, 1.0, 1.0])
+                .unwrap();
+        assert::close(diag.ln_f(&x), fresh.ln_f(&x), TOL);
+    }
+}
@@ -292,6 +292,18 @@ impl_traits!(u8);
 impl_traits!(u16);
 impl_traits!(u32);
 
+impl CacheControl for NegBinomial {
+    fn precompute(&self) {
+        self.ln_1mp();
+        self.r_ln_p();
+    }
+
+    fn invalidate(&mut self) {
+        self.ln_1mp = OnceLock::new();
+        self.r_ln_p = OnceLock::new();
+    }
+}
+
 impl std::error::Error for NegBinomialError {}
 
 impl fmt::Display for NegBinomialError {
@@ -1,7 +1,8 @@
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
 
-use crate::dist::{InvWishart, MvGaussian};
+use crate::dist::marginalize::dropped_indices;
+use crate::dist::{InvWishart, MarginalizeError, MvGaussian};
 use crate::impl_display;
 use crate::traits::*;
 use nalgebra::{DMatrix, DVector};
@@ -273,6 +274,27 @@ impl Support<MvGaussian> for NormalInvWishart {
 
 impl ContinuousDistr<MvGaussian> for NormalInvWishart {}
 
+/// The prior on the mean and covariance of the Gaussian marginal of the
+/// dimensions `ixs`
+impl Marginalize for NormalInvWishart {
+    type Marginal = NormalInvWishart;
+
+    fn marginalize(
+        &self,
+        ixs: &[usize],
+    ) -> Result<NormalInvWishart, MarginalizeError> {
+        let dropped = dropped_indices(ixs, self.ndims())?;
+        let mu = self.mu.select_rows(ixs);
+        let scale = self.scale.select_rows(ixs).select_columns(ixs);
+        Ok(NormalInvWishart::new_unchecked(
+            mu,
+            self.k,
+            self.df - dropped.len(),
+            scale,
+        ))
+    }
+}
+
 impl std::error::Error for NormalInvWishartError {}
 
 impl fmt::Display for NormalInvWishartError {
@@ -307,6 +329,26 @@ impl fmt::Display for NormalInvWishartError {
 mod tests {
     use super::*;
 
+    #[test]
+    fn marginalize_selects_params() {
+        let mu = DVector::from_column_slice(&[1.0, 2.0, 3.0]);
+        let scale = DMatrix::from_row_slice(
+            3,
+            3,
+            &[2.0, 0.5, 0.1, 0.5, 1.0, 0.3, 0.1, 0.3, 1.5],
+        );
+        let niw = NormalInvWishart::new(mu, 2.0, 5, scale).unwrap();
+        let marginal = niw.marginalize(&[2, 0]).unwrap();
+        assert_eq!(marginal.mu().as_slice(), &[3.0, 1.0]);
+        assert_eq!(marginal.k(), 2.0);
+        assert_eq!(marginal.df(), 4);
+        assert_eq!(marginal.scale().as_slice(), &[1.5, 0.1, 0.1, 2.0]);
+        assert_eq!(
+            niw.marginalize(&[0, 0]),
+            Err(MarginalizeError::DuplicateIndex { ix: 0 })
+        );
+    }
+
     #[test]
     fn disallow_zero_k() {
         let mu = DVector::zeros(2);
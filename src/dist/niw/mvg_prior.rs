@@ -34,21 +34,14 @@ impl ConjugatePrior<DVector<f64>, MvGaussian> for NormalInvWishart {
             x,
             || MvGaussianSuffStat::new(self.ndims()),
             |stat: MvGaussianSuffStat| {
-                let xbar = stat.sum_x() / stat.n() as f64;
-                let diff = &xbar - self.mu();
+                let xbar = stat.mean();
+                let diff = xbar - self.mu();
                 // s = \sum_{i=1}^N (x_i - \bar{x}) (x_i - \bar{x})^T
-                // = \sum_{i=1}^N (x_i x_i^T - x_i \bar{x}^T - \bar{x} x_i^T + \bar{x}\bar{x}^T)
-                // = N \bar{x} \bar{x}^T + \sum_{i=1}^N (x_i x_i^T - x_i \bar{x}^T - \bar{x} x_i^T)
-                // = N \bar{x} \bar{x}^T + \sum_{i=1}^N x_i x_i^T
-                //   - (\sum_{i=1}^N x_i) \bar{x}^T - \bar{x} (\sum_{i=1}^N x_i^T)
-                let s: DMatrix<f64> = stat.sum_x_sq()
-                    + nf * (&xbar * &xbar.transpose())
-                    - stat.sum_x() * &xbar.transpose()
-                    - &xbar * stat.sum_x().transpose();
+                let s: &DMatrix<f64> = stat.scatter();
 
                 let kn = self.k() + stat.n() as f64;
                 let vn = self.df() + stat.n();
-                let mn = (self.k() * self.mu() + stat.sum_x()) / kn;
+                let mn = (self.k() * self.mu() + nf * xbar) / kn;
                 let sn = self.scale()
                     + s
                     + (self.k() * stat.n() as f64) / kn
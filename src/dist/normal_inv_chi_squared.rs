@@ -9,7 +9,7 @@ mod gaussian_prior;
 
 use crate::dist::{Gaussian, ScaledInvChiSquared};
 use crate::impl_display;
-use crate::traits::Rv;
+use crate::traits::{CacheControl, Rv};
 use rand::Rng;
 use std::sync::OnceLock;
 
@@ -378,6 +378,16 @@ impl Rv<Gaussian> for NormalInvChiSquared {
     }
 }
 
+impl CacheControl for NormalInvChiSquared {
+    fn precompute(&self) {
+        self.scaled_inv_x2().precompute();
+    }
+
+    fn invalidate(&mut self) {
+        self.scaled_inv_x2 = OnceLock::new();
+    }
+}
+
 impl std::error::Error for NormalInvChiSquaredError {}
 
 impl std::fmt::Display for NormalInvChiSquaredError {
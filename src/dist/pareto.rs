@@ -2,12 +2,15 @@
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
 
+use crate::data::ParetoSuffStat;
 use crate::impl_display;
 use crate::traits::*;
 use rand::Rng;
 use std::f64;
 use std::fmt;
 
+mod uniform_prior;
+
 /// [Pareto distribution](https://en.wikipedia.org/wiki/Pareto_distribution) Pareto(x_m, α)
 /// over x in (x_m, ∞).
 ///
@@ -215,7 +218,38 @@ macro_rules! impl_traits {
         impl Cdf<$kind> for Pareto {
             fn cdf(&self, x: &$kind) -> f64 {
                 let xk = f64::from(*x);
-                1.0 - (self.scale / xk).powf(self.shape)
+                if xk <= self.scale {
+                    0.0
+                } else {
+                    1.0 - (self.scale / xk).powf(self.shape)
+                }
+            }
+        }
+
+        impl InverseCdf<$kind> for Pareto {
+            fn invcdf(&self, p: f64) -> $kind {
+                (self.scale * (1.0 - p).powf(-self.shape.recip())) as $kind
+            }
+        }
+
+        impl Median<$kind> for Pareto {
+            fn median(&self) -> Option<$kind> {
+                Some((self.scale * self.shape.recip().exp2()) as $kind)
+            }
+        }
+
+        impl HasSuffStat<$kind> for Pareto {
+            type Stat = ParetoSuffStat;
+
+            fn empty_suffstat(&self) -> Self::Stat {
+                ParetoSuffStat::new()
+            }
+
+            fn ln_f_stat(&self, stat: &Self::Stat) -> f64 {
+                let n = stat.n() as f64;
+                let ln_norm =
+                    self.shape.mul_add(self.scale.ln(), self.shape.ln());
+                (self.shape + 1.0).mul_add(-stat.sum_ln_x(), n * ln_norm)
             }
         }
 
@@ -605,4 +639,30 @@ mod tests {
 
         assert!(passes > 0);
     }
+
+    #[test]
+    fn invcdf_inverts_cdf() {
+        let par = Pareto::new(1.2, 3.4).unwrap();
+        for p in [0.0, 0.1, 0.5, 0.99] {
+            let x: f64 = par.invcdf(p);
+            assert::close(par.cdf(&x), p, TOL);
+        }
+        let median: f64 = par.median().unwrap();
+        assert::close(par.cdf(&median), 0.5, TOL);
+        assert_eq!(par.cdf(&1.0_f64), 0.0);
+    }
+
+    #[test]
+    fn ln_f_stat() {
+        use crate::traits::SuffStat;
+
+        let par = Pareto::new(1.2, 3.4).unwrap();
+        let xs: Vec<f64> = vec![3.5, 4.0, 10.2, 7.7];
+        let mut stat = ParetoSuffStat::new();
+        stat.observe_many(&xs);
+
+        let ln_f_sum: f64 = xs.iter().map(|x| par.ln_f(x)).sum();
+        let ln_f_stat = <Pareto as HasSuffStat<f64>>::ln_f_stat(&par, &stat);
+        assert::close(ln_f_stat, ln_f_sum, TOL);
+    }
 }
@@ -0,0 +1,187 @@
+use rand::Rng;
+
+use crate::data::{extract_stat, DataOrSuffStat, UniformSuffStat};
+use crate::dist::{Pareto, Uniform};
+use crate::traits::*;
+
+// The Pareto is a prior on θ for the Uniform(0, θ) likelihood. Uniforms with a
+// non-zero lower bound are outside of its support.
+
+impl Rv<Uniform> for Pareto {
+    fn ln_f(&self, x: &Uniform) -> f64 {
+        if self.supports(x) {
+            self.ln_f(&x.b())
+        } else {
+            f64::NEG_INFINITY
+        }
+    }
+
+    fn draw<R: Rng>(&self, mut rng: &mut R) -> Uniform {
+        let theta: f64 = self.draw(&mut rng);
+        Uniform::new(0.0, theta).expect("Failed to draw valid θ")
+    }
+}
+
+impl Support<Uniform> for Pareto {
+    fn supports(&self, x: &Uniform) -> bool {
+        x.a() == 0.0 && x.b() >= self.scale() && x.b().is_finite()
+    }
+}
+
+impl ContinuousDistr<Uniform> for Pareto {}
+
+macro_rules! impl_traits {
+    ($kind: ty) => {
+        impl ConjugatePrior<$kind, Uniform> for Pareto {
+            type Posterior = Self;
+            type LnMCache = f64;
+            // (shape, ln(scale), ln(shape / (shape + 1))) of the posterior
+            type LnPpCache = (f64, f64, f64);
+
+            fn posterior(&self, x: &DataOrSuffStat<$kind, Uniform>) -> Self {
+                let stat = extract_stat(x, UniformSuffStat::new);
+                let shape = self.shape() + stat.n() as f64;
+                let scale = self.scale().max(stat.max());
+                Pareto::new(shape, scale).expect("Invalid posterior parameters")
+            }
+
+            #[inline]
+            fn ln_m_cache(&self) -> Self::LnMCache {
+                self.shape().mul_add(self.scale().ln(), self.shape().ln())
+            }
+
+            fn ln_m_with_cache(
+                &self,
+                cache: &Self::LnMCache,
+                x: &DataOrSuffStat<$kind, Uniform>,
+            ) -> f64 {
+                // m(x) = α x_m^α / ((α + n) max(x_m, x_max)^(α + n))
+                let stat = extract_stat(x, UniformSuffStat::new);
+                if stat.min() < 0.0 {
+                    return f64::NEG_INFINITY;
+                }
+                let post = self.posterior(x);
+                post.shape().mul_add(-post.scale().ln(), *cache)
+                    - post.shape().ln()
+            }
+
+            #[inline]
+            fn ln_pp_cache(
+                &self,
+                x: &DataOrSuffStat<$kind, Uniform>,
+            ) -> Self::LnPpCache {
+                let post = self.posterior(x);
+                let shape = post.shape();
+                (shape, post.scale().ln(), (shape / (shape + 1.0)).ln())
+            }
+
+            fn ln_pp_with_cache(
+                &self,
+                cache: &Self::LnPpCache,
+                y: &$kind,
+            ) -> f64 {
+                // f(y) = α x_m^α / ((α + 1) max(x_m, y)^(α + 1))
+                let (shape, ln_scale, ln_z) = *cache;
+                let y = f64::from(*y);
+                if y < 0.0 {
+                    f64::NEG_INFINITY
+                } else {
+                    let ln_max = ln_scale.max(y.ln());
+                    (shape + 1.0)
+                        .mul_add(-ln_max, shape.mul_add(ln_scale, ln_z))
+                }
+            }
+        }
+    };
+}
+
+impl_traits!(f32);
+impl_traits!(f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOL: f64 = 1E-12;
+
+    #[test]
+    fn posterior_from_data() {
+        let data: Vec<f64> = vec![0.5, 2.5, 1.0];
+        let xs = DataOrSuffStat::Data::<f64, Uniform>(&data);
+        let posterior = Pareto::new(2.0, 1.0).unwrap().posterior(&xs);
+
+        assert::close(posterior.shape(), 5.0, TOL);
+        assert::close(posterior.scale(), 2.5, TOL);
+    }
+
+    #[test]
+    fn posterior_keeps_prior_scale_above_data() {
+        let data: Vec<f32> = vec![0.5, 0.2];
+        let xs = DataOrSuffStat::Data::<f32, Uniform>(&data);
+        let posterior = Pareto::new(2.0, 1.0).unwrap().posterior(&xs);
+
+        assert::close(posterior.shape(), 4.0, TOL);
+        assert::close(posterior.scale(), 1.0, TOL);
+    }
+
+    #[test]
+    fn ln_m_no_data() {
+        let prior = Pareto::new(1.5, 0.7).unwrap();
+        let data: DataOrSuffStat<f64, Uniform> = DataOrSuffStat::None;
+        assert::close(prior.ln_m(&data), 0.0, TOL);
+    }
+
+    #[test]
+    fn ln_m_negative_data() {
+        let prior = Pareto::new(1.5, 0.7).unwrap();
+        let data: Vec<f64> = vec![0.2, -0.1];
+        let ln_m = prior.ln_m(&DataOrSuffStat::<f64, Uniform>::Data(&data));
+        assert_eq!(ln_m, f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn ln_pp_matches_quadrature() {
+        // E[1(y ≤ θ) / θ] under the prior, integrated over ln θ
+        let prior = Pareto::new(2.0, 1.0).unwrap();
+        let n = 200_000;
+        let upper = 40.0_f64;
+        for y in [0.5_f64, 1.0, 3.0] {
+            let lower = y.ln().max(prior.scale().ln());
+            let h = (upper - lower) / n as f64;
+            let expected: f64 = (0..n)
+                .map(|i| {
+                    let theta = (i as f64 + 0.5).mul_add(h, lower).exp();
+                    prior.f(&theta)
+                })
+                .sum::<f64>()
+                * h;
+            let ln_pp = prior.ln_pp(&y, &DataOrSuffStat::<f64, Uniform>::None);
+            assert::close(ln_pp.exp(), expected, 1E-6);
+        }
+    }
+
+    #[test]
+    fn ln_m_is_sum_of_sequential_ln_pp() {
+        let prior = Pareto::new(1.2, 0.7).unwrap();
+        let data: Vec<f64> = vec![0.3, 2.2, 0.1, 1.4, 5.0];
+        let mut stat = UniformSuffStat::new();
+        let mut total = 0.0;
+        for x in data.iter() {
+            total += prior
+                .ln_pp(x, &DataOrSuffStat::<f64, Uniform>::SuffStat(&stat));
+            stat.observe(x);
+        }
+        let ln_m = prior.ln_m(&DataOrSuffStat::<f64, Uniform>::Data(&data));
+        assert::close(ln_m, total, 1E-10);
+    }
+
+    #[test]
+    fn draw_is_supported() {
+        let mut rng = rand::thread_rng();
+        let prior = Pareto::new(3.0, 2.0).unwrap();
+        for _ in 0..100 {
+            let u: Uniform = prior.draw(&mut rng);
+            assert!(prior.supports(&u));
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! Gamma prior on the shape of a Pareto with known scale
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::{extract_stat, DataOrSuffStat, ParetoSuffStat};
+use crate::dist::{Gamma, Lomax, Pareto};
+use crate::impl_display;
+use crate::traits::*;
+use rand::Rng;
+use special::Gamma as _;
+use std::fmt;
+
+/// Conjugate prior on the shape, α, of a [`Pareto`] with known scale, x_m,
+/// where α ~ Gamma(a, b).
+///
+/// The likelihood depends on the data only through n and
+/// T = Σ ln(x / x_m), so the posterior is Gamma(a + n, b + T). All data must
+/// be at least `scale`.
+///
+/// # Example
+///
+/// ```
+/// use rv::data::DataOrSuffStat;
+/// use rv::dist::{Gamma, Pareto, ParetoShapePrior};
+/// use rv::traits::*;
+///
+/// let prior = ParetoShapePrior::new(Gamma::new(2.0, 1.0).unwrap(), 1.0).unwrap();
+///
+/// let xs: Vec<f64> = vec![1.5, 2.0, 1.1, 8.0];
+/// let data: DataOrSuffStat<f64, Pareto> = DataOrSuffStat::Data(&xs);
+/// let post = prior.posterior(&data);
+///
+/// let t: f64 = xs.iter().map(|x| x.ln()).sum();
+/// assert::close(post.gamma().shape(), 6.0, 1E-12);
+/// assert::close(post.gamma().rate(), 1.0 + t, 1E-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct ParetoShapePrior {
+    gamma: Gamma,
+    scale: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum ParetoShapePriorError {
+    /// Scale parameter is less than or equal to zero
+    ScaleTooLow { scale: f64 },
+    /// Scale parameter is infinite or NaN
+    ScaleNotFinite { scale: f64 },
+}
+
+impl ParetoShapePrior {
+    /// Create a new prior from the Gamma on the shape and the known scale
+    pub fn new(
+        gamma: Gamma,
+        scale: f64,
+    ) -> Result<Self, ParetoShapePriorError> {
+        if scale <= 0.0 {
+            Err(ParetoShapePriorError::ScaleTooLow { scale })
+        } else if !scale.is_finite() {
+            Err(ParetoShapePriorError::ScaleNotFinite { scale })
+        } else {
+            Ok(ParetoShapePrior { gamma, scale })
+        }
+    }
+
+    /// Creates a new ParetoShapePrior without checking whether the scale is
+    /// valid.
+    #[inline]
+    pub fn new_unchecked(gamma: Gamma, scale: f64) -> Self {
+        ParetoShapePrior { gamma, scale }
+    }
+
+    /// The Gamma distribution on the shape
+    #[inline]
+    pub fn gamma(&self) -> &Gamma {
+        &self.gamma
+    }
+
+    /// The known scale, x_m
+    #[inline]
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// n and T = Σ ln(x / x_m) from the data
+    fn n_and_t<X>(&self, x: &DataOrSuffStat<X, Pareto>) -> (f64, f64)
+    where
+        Pareto: HasSuffStat<X, Stat = ParetoSuffStat>,
+    {
+        let stat = extract_stat(x, ParetoSuffStat::new);
+        let n = stat.n() as f64;
+        (n, n.mul_add(-self.scale.ln(), stat.sum_ln_x()))
+    }
+}
+
+impl From<&ParetoShapePrior> for String {
+    fn from(prior: &ParetoShapePrior) -> String {
+        format!(
+            "ParetoShapePrior(a: {}, b: {}, xₘ: {})",
+            prior.gamma.shape(),
+            prior.gamma.rate(),
+            prior.scale
+        )
+    }
+}
+
+impl_display!(ParetoShapePrior);
+
+impl Rv<Pareto> for ParetoShapePrior {
+    fn ln_f(&self, x: &Pareto) -> f64 {
+        if x.scale() == self.scale {
+            self.gamma.ln_f(&x.shape())
+        } else {
+            f64::NEG_INFINITY
+        }
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> Pareto {
+        let shape: f64 = self.gamma.draw(rng);
+        Pareto::new_unchecked(shape.max(f64::MIN_POSITIVE), self.scale)
+    }
+}
+
+impl Support<Pareto> for ParetoShapePrior {
+    fn supports(&self, x: &Pareto) -> bool {
+        x.scale() == self.scale && x.shape() > 0.0 && x.shape().is_finite()
+    }
+}
+
+impl ContinuousDistr<Pareto> for ParetoShapePrior {}
+
+macro_rules! impl_traits {
+    ($kind: ty) => {
+        impl ConjugatePrior<$kind, Pareto> for ParetoShapePrior {
+            type Posterior = Self;
+            type LnMCache = f64;
+            // The posterior predictive of ln(y / x_m) is a Lomax
+            type LnPpCache = Lomax;
+
+            fn posterior(&self, x: &DataOrSuffStat<$kind, Pareto>) -> Self {
+                let (n, t) = self.n_and_t(x);
+                let a = self.gamma.shape() + n;
+                let b = self.gamma.rate() + t;
+                let gamma = Gamma::new(a, b).expect("Invalid posterior params");
+                ParetoShapePrior::new_unchecked(gamma, self.scale)
+            }
+
+            #[inline]
+            fn ln_m_cache(&self) -> Self::LnMCache {
+                let a = self.gamma.shape();
+                a.mul_add(self.gamma.rate().ln(), -a.ln_gamma().0)
+            }
+
+            fn ln_m_with_cache(
+                &self,
+                cache: &Self::LnMCache,
+                x: &DataOrSuffStat<$kind, Pareto>,
+            ) -> f64 {
+                let stat = extract_stat(x, ParetoSuffStat::new);
+                let n = stat.n() as f64;
+                let t = n.mul_add(-self.scale.ln(), stat.sum_ln_x());
+                let a = self.gamma.shape() + n;
+                let b = self.gamma.rate() + t;
+                a.mul_add(-b.ln(), a.ln_gamma().0) + cache - stat.sum_ln_x()
+            }
+
+            #[inline]
+            fn ln_pp_cache(
+                &self,
+                x: &DataOrSuffStat<$kind, Pareto>,
+            ) -> Self::LnPpCache {
+                let post = self.posterior(x);
+                Lomax::new_unchecked(post.gamma.shape(), post.gamma.rate())
+            }
+
+            fn ln_pp_with_cache(
+                &self,
+                cache: &Self::LnPpCache,
+                y: &$kind,
+            ) -> f64 {
+                let y = f64::from(*y);
+                if y < self.scale {
+                    f64::NEG_INFINITY
+                } else {
+                    cache.ln_f(&(y / self.scale).ln()) - y.ln()
+                }
+            }
+        }
+    };
+}
+
+impl_traits!(f32);
+impl_traits!(f64);
+
+impl std::error::Error for ParetoShapePriorError {}
+
+impl fmt::Display for ParetoShapePriorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ScaleTooLow { scale } => {
+                write!(f, "scale ({}) must be greater than zero", scale)
+            }
+            Self::ScaleNotFinite { scale } => {
+                write!(f, "non-finite scale: {}", scale)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::misc::adaptive_gauss_legendre_quadrature;
+
+    const TOL: f64 = 1E-10;
+
+    fn prior() -> ParetoShapePrior {
+        ParetoShapePrior::new(Gamma::new(2.5, 1.5).unwrap(), 0.5).unwrap()
+    }
+
+    #[test]
+    fn new_validates_scale() {
+        let gamma = Gamma::default();
+        assert!(ParetoShapePrior::new(gamma.clone(), 1.0).is_ok());
+        assert!(ParetoShapePrior::new(gamma.clone(), 0.0).is_err());
+        assert!(ParetoShapePrior::new(gamma, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn posterior_params() {
+        let xs: Vec<f64> = vec![0.6, 1.2, 4.0];
+        let data: DataOrSuffStat<f64, Pareto> = DataOrSuffStat::Data(&xs);
+        let post = prior().posterior(&data);
+        let t: f64 = xs.iter().map(|x| (x / 0.5).ln()).sum();
+        assert::close(post.gamma().shape(), 5.5, TOL);
+        assert::close(post.gamma().rate(), 1.5 + t, TOL);
+        assert_eq!(post.scale(), 0.5);
+    }
+
+    #[test]
+    fn ln_m_matches_quadrature() {
+        let pr = prior();
+        let xs: Vec<f64> = vec![0.6, 1.2, 4.0];
+        let data: DataOrSuffStat<f64, Pareto> = DataOrSuffStat::Data(&xs);
+        let m = adaptive_gauss_legendre_quadrature(
+            |shape| {
+                let par = Pareto::new_unchecked(shape, 0.5);
+                let ln_lik: f64 = xs.iter().map(|x| par.ln_f(x)).sum();
+                (ln_lik + pr.gamma().ln_f(&shape)).exp()
+            },
+            (1E-12, 60.0),
+            1E-14,
+            30,
+        );
+        assert::close(pr.ln_m(&data), m.ln(), 1E-8);
+    }
+
+    #[test]
+    fn ln_m_no_data_is_zero() {
+        let data: DataOrSuffStat<f64, Pareto> = DataOrSuffStat::None;
+        assert::close(prior().ln_m(&data), 0.0, TOL);
+    }
+
+    #[test]
+    fn ln_pp_is_ratio_of_marginals() {
+        let pr = prior();
+        let xs: Vec<f64> = vec![0.6, 1.2, 4.0];
+        let mut xys = xs.clone();
+        xys.push(2.2);
+        let ln_pp = pr.ln_pp(&2.2, &DataOrSuffStat::Data(&xs));
+        let ln_m_x = pr.ln_m(&DataOrSuffStat::Data(&xs));
+        let ln_m_xy = pr.ln_m(&DataOrSuffStat::Data(&xys));
+        assert::close(ln_pp, ln_m_xy - ln_m_x, TOL);
+        assert_eq!(
+            pr.ln_pp(&0.4, &DataOrSuffStat::Data(&xs)),
+            f64::NEG_INFINITY
+        );
+    }
+}
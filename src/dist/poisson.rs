@@ -287,6 +287,13 @@ impl KlDivergence for Poisson {
     }
 }
 
+impl HellingerDistance for Poisson {
+    fn hellinger(&self, other: &Poisson) -> f64 {
+        let d = self.rate().sqrt() - other.rate().sqrt();
+        (1.0 - (-0.5 * d * d).exp()).max(0.0).sqrt()
+    }
+}
+
 impl Entropy for Poisson {
     fn entropy(&self) -> f64 {
         // TODO: optimize this. Should be some better approximations out there
@@ -313,6 +320,16 @@ impl_traits!(u16);
 impl_traits!(u32);
 impl_traits!(usize);
 
+impl CacheControl for Poisson {
+    fn precompute(&self) {
+        self.ln_rate();
+    }
+
+    fn invalidate(&mut self) {
+        self.ln_rate = OnceLock::new();
+    }
+}
+
 impl std::error::Error for PoissonError {}
 
 impl fmt::Display for PoissonError {
@@ -489,6 +506,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn hellinger_vs_brute() {
+        let pois_x = Poisson::new(2.5).unwrap();
+        let pois_y = Poisson::new(7.0).unwrap();
+        let bc: f64 = (0..1_000_u32)
+            .map(|x| (0.5 * (pois_x.ln_f(&x) + pois_y.ln_f(&x))).exp())
+            .sum();
+        assert::close(pois_x.hellinger(&pois_y), (1.0 - bc).sqrt(), TOL);
+    }
+
     #[test]
     fn entropy_value_checks() {
         let rates = vec![0.1, 0.5, 1.0, 2.2, 3.4, 10.2, 131.4];
@@ -550,4 +577,22 @@ mod tests {
 
         assert::close(ln_f_base, ln_f_stat, TOL);
     }
+
+    #[test]
+    fn hdi_grows_from_mode() {
+        let pois = Poisson::new(3.5).unwrap();
+        let (lower, upper) = pois.hdi(0.9);
+        assert_eq!((lower, upper), (1, 6));
+        let mass: f64 = (lower..=upper).map(|x| pois.pmf(&x)).sum();
+        assert!(mass >= 0.9);
+
+        // The mode is at zero
+        let pois = Poisson::new(0.3).unwrap();
+        assert_eq!(pois.hdi(0.95), (0, 1));
+
+        // Far from zero, the interval is centered near the mean
+        let (lower, upper) = Poisson::new(1E4).unwrap().hdi(0.5);
+        assert!(lower < 10_000 && upper > 10_000);
+        assert!(upper - lower < 200);
+    }
 }
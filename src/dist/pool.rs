@@ -0,0 +1,596 @@
+//! Combining predictive distributions into ensembles
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::dist::{DrawError, DrawPolicy, Mixture, MixtureError};
+use crate::misc::optimize::nelder_mead;
+use crate::misc::{adaptive_gauss_legendre_quadrature, logsumexp};
+use crate::traits::*;
+use rand::Rng;
+use std::fmt;
+
+/// Maximum number of EM iterations used to fit linear pool weights
+const EM_MAX_ITER: usize = 1_000;
+/// Stop EM when no weight changes by more than this
+const EM_TOL: f64 = 1E-10;
+/// Number of rejection sampling proposals `LogLinearPool::draw` makes before
+/// falling back to inverting the CDF
+const DRAW_MAX_PROPOSALS: usize = 100;
+/// Number of bisections used to invert the CDF of the log-linear pool, which
+/// locates the draw to within 2^-52 of the width of the quad bounds
+const INV_CDF_BISECTIONS: usize = 52;
+
+/// Errors from pooling predictive distributions
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum PoolError {
+    /// The weights or components are invalid
+    Mixture(MixtureError),
+    /// There is no validation data to fit the weights to
+    NoValidationData,
+    /// The pooled density does not integrate to a positive, finite value,
+    /// e.g., because the components do not overlap
+    NotNormalizable { ln_z: f64 },
+}
+
+/// Linear pool of the `components`, with the weights that maximize the mean
+/// log score, `ln f(x)`, of the `validation` data.
+///
+/// The linear pool is the mixture of the components. Its weights are found
+/// with the EM algorithm, which converges to the global optimum because the
+/// mean log score is concave in the weights.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{linear_pool, Gaussian};
+/// use rv::traits::*;
+///
+/// let forecasts = vec![
+///     Gaussian::new(0.0, 1.0).unwrap(),
+///     Gaussian::new(5.0, 1.0).unwrap(),
+/// ];
+///
+/// // Most of the observations are near the second forecast
+/// let xs = [4.5, 5.2, 5.8, 4.9, 0.3, 5.1, 4.4, 5.5];
+///
+/// let pool = linear_pool(forecasts, &xs).unwrap();
+/// assert!((pool.weights()[1] - 0.875).abs() < 1E-3);
+/// ```
+pub fn linear_pool<X, Fx>(
+    components: Vec<Fx>,
+    validation: &[X],
+) -> Result<Mixture<Fx>, PoolError>
+where
+    Fx: Rv<X>,
+{
+    if components.is_empty() {
+        return Err(PoolError::Mixture(MixtureError::ComponentsEmpty));
+    } else if validation.is_empty() {
+        return Err(PoolError::NoValidationData);
+    }
+
+    let k = components.len();
+    let ln_fs: Vec<Vec<f64>> = validation
+        .iter()
+        .map(|x| components.iter().map(|cpnt| cpnt.ln_f(x)).collect())
+        .collect();
+
+    let mut weights = vec![(k as f64).recip(); k];
+    for _ in 0..EM_MAX_ITER {
+        let mut next = vec![0.0; k];
+        for ln_f in ln_fs.iter() {
+            let ln_joint: Vec<f64> = ln_f
+                .iter()
+                .zip(weights.iter())
+                .map(|(lf, w)| lf + w.ln())
+                .collect();
+            let ln_marginal = logsumexp(&ln_joint);
+            if ln_marginal.is_finite() {
+                next.iter_mut()
+                    .zip(ln_joint.iter())
+                    .for_each(|(w, lj)| *w += (lj - ln_marginal).exp());
+            }
+        }
+        let total: f64 = next.iter().sum();
+        if total <= 0.0 {
+            // No component gives the data positive density
+            break;
+        }
+        next.iter_mut().for_each(|w| *w /= total);
+
+        let change = next
+            .iter()
+            .zip(weights.iter())
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        weights = next;
+        if change < EM_TOL {
+            break;
+        }
+    }
+
+    Mixture::new(weights, components).map_err(PoolError::Mixture)
+}
+
+/// Log-linear (geometric) pool of continuous predictive distributions.
+///
+/// The density is the renormalized weighted geometric mean of the component
+/// densities,
+///
+/// ```math
+/// f(x) = (1/Z) ∏ f_i(x)^(w_i),
+/// ```
+///
+/// where the weights sum to one. Unlike the linear pool, which is a
+/// [`Mixture`], the log-linear pool is unimodal when the components are
+/// log-concave and is sharper than its components when they agree.
+///
+/// The normalizing constant, Z, is computed by quadrature over the
+/// [`QuadBounds`] of the components. By the weighted AM–GM inequality,
+/// the unnormalized density is bounded by the linear pool, which is used as
+/// a rejection sampling envelope. The acceptance rate is Z, so if none of
+/// the first 100 proposals is accepted, e.g., because the components barely
+/// overlap, `draw` falls back to inverting the CDF by quadrature and
+/// bisection. [`TryDraw`] gives control over the number of proposals.
+///
+/// # Example
+///
+/// The log-linear pool of Gaussians is the Gaussian with the weighted mean
+/// of their precisions
+///
+/// ```
+/// use rv::dist::{Gaussian, LogLinearPool};
+/// use rv::traits::*;
+///
+/// let pool = LogLinearPool::new(
+///     vec![0.5, 0.5],
+///     vec![Gaussian::new(-1.0, 1.0).unwrap(), Gaussian::new(1.0, 1.0).unwrap()],
+/// )
+/// .unwrap();
+///
+/// let expected = Gaussian::standard();
+/// assert!((pool.ln_f(&0.5) - expected.ln_f(&0.5_f64)).abs() < 1E-8);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct LogLinearPool<Fx> {
+    /// The linear pool with the same weights, which bounds the unnormalized
+    /// density
+    linear: Mixture<Fx>,
+    /// The log normalizing constant, ln Z
+    ln_z: f64,
+}
+
+/// ∑ w_i ln f_i(x), skipping zero weights so that components without
+/// support at x do not contribute NaN
+fn weighted_ln_f(weights: &[f64], ln_fs: impl Iterator<Item = f64>) -> f64 {
+    weights
+        .iter()
+        .zip(ln_fs)
+        .filter(|(&w, _)| w > 0.0)
+        .map(|(w, lf)| w * lf)
+        .sum()
+}
+
+fn pooled_quad_bounds<Fx: QuadBounds>(components: &[Fx]) -> (f64, f64) {
+    components
+        .iter()
+        .map(QuadBounds::quad_bounds)
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(a, b), (lo, hi)| {
+            (a.min(lo), b.max(hi))
+        })
+}
+
+fn pooled_ln_z<Fx>(weights: &[f64], components: &[Fx]) -> f64
+where
+    Fx: ContinuousDistr<f64> + QuadBounds,
+{
+    let bounds = pooled_quad_bounds(components);
+    let ln_z_shifted = |shift: f64| {
+        let f = |x: f64| {
+            let ln_fs = components.iter().map(|cpnt| cpnt.ln_f(&x));
+            (weighted_ln_f(weights, ln_fs) - shift).exp()
+        };
+        adaptive_gauss_legendre_quadrature(f, bounds, 1E-12, 30).ln() + shift
+    };
+    // The quadrature tolerance is absolute, so when Z is far from one,
+    // integrate again relative to the first estimate
+    let ln_z = ln_z_shifted(0.0);
+    if ln_z.is_finite() && ln_z.abs() > 1.0 {
+        ln_z_shifted(ln_z)
+    } else {
+        ln_z
+    }
+}
+
+impl<Fx> LogLinearPool<Fx>
+where
+    Fx: ContinuousDistr<f64> + QuadBounds,
+{
+    /// Create the log-linear pool of the `components` with the given
+    /// `weights`, which must be non-negative and sum to one.
+    pub fn new(
+        weights: Vec<f64>,
+        components: Vec<Fx>,
+    ) -> Result<Self, PoolError> {
+        let linear =
+            Mixture::new(weights, components).map_err(PoolError::Mixture)?;
+        let ln_z = pooled_ln_z(linear.weights(), linear.components());
+        if ln_z.is_finite() {
+            Ok(LogLinearPool { linear, ln_z })
+        } else {
+            Err(PoolError::NotNormalizable { ln_z })
+        }
+    }
+
+    /// Log-linear pool of the `components`, with the weights that maximize
+    /// the mean log score of the `validation` data.
+    ///
+    /// The mean log score is concave in the weights, which are found with
+    /// the Nelder-Mead algorithm. Each evaluation integrates the pooled
+    /// density, so fitting many components is slow.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::{Gaussian, LogLinearPool};
+    ///
+    /// let forecasts = vec![
+    ///     Gaussian::new(0.0, 1.0).unwrap(),
+    ///     Gaussian::new(2.0, 1.0).unwrap(),
+    /// ];
+    /// let xs = [1.4, 1.9, 1.2, 1.7, 1.3, 1.6];
+    ///
+    /// let pool = LogLinearPool::fit(forecasts, &xs).unwrap();
+    /// assert!(pool.weights()[1] > pool.weights()[0]);
+    /// ```
+    pub fn fit(
+        components: Vec<Fx>,
+        validation: &[f64],
+    ) -> Result<Self, PoolError> {
+        if components.is_empty() {
+            return Err(PoolError::Mixture(MixtureError::ComponentsEmpty));
+        } else if validation.is_empty() {
+            return Err(PoolError::NoValidationData);
+        }
+
+        let k = components.len();
+        let n = validation.len() as f64;
+        let ln_fs: Vec<Vec<f64>> = validation
+            .iter()
+            .map(|x| components.iter().map(|cpnt| cpnt.ln_f(x)).collect())
+            .collect();
+
+        // Softmax weights, with the last logit fixed at zero
+        let to_weights = |logits: &[f64]| {
+            let mut ln_ws = logits.to_vec();
+            ln_ws.push(0.0);
+            let ln_norm = logsumexp(&ln_ws);
+            ln_ws
+                .iter()
+                .map(|lw| (lw - ln_norm).exp())
+                .collect::<Vec<f64>>()
+        };
+        let neg_score = |logits: &[f64]| {
+            let weights = to_weights(logits);
+            let ln_z = pooled_ln_z(&weights, &components);
+            let sum_ln_q: f64 = ln_fs
+                .iter()
+                .map(|ln_f| weighted_ln_f(&weights, ln_f.iter().copied()))
+                .sum();
+            let score = sum_ln_q / n - ln_z;
+            if score.is_finite() {
+                -score
+            } else {
+                f64::INFINITY
+            }
+        };
+
+        let logits = if k == 1 {
+            Vec::new()
+        } else {
+            nelder_mead(neg_score, &vec![0.0; k - 1], 1.0, 1E-10, 500).0
+        };
+        Self::new(to_weights(&logits), components)
+    }
+}
+
+impl<Fx> LogLinearPool<Fx>
+where
+    Fx: ContinuousDistr<f64> + QuadBounds,
+{
+    /// Rejection sampling proposal from the linear pool, which is accepted
+    /// with probability Z
+    fn propose<R: Rng>(&self, rng: &mut R) -> Option<f64> {
+        let x: f64 = self.linear.draw(rng);
+        let ln_fs: Vec<f64> =
+            self.components().iter().map(|cpnt| cpnt.ln_f(&x)).collect();
+        let ln_envelope = logsumexp(
+            &ln_fs
+                .iter()
+                .zip(self.linear.ln_weights())
+                .map(|(lf, lw)| lf + lw)
+                .collect::<Vec<f64>>(),
+        );
+        let ln_q = weighted_ln_f(self.weights(), ln_fs.iter().copied());
+        let u: f64 = rng.gen();
+        Some(x).filter(|_| u.ln() < ln_q - ln_envelope)
+    }
+
+    /// Draw by inverting the CDF with bisection. The CDF is found by
+    /// quadrature, so this is slow, but its cost does not depend on Z.
+    fn inv_cdf_draw<R: Rng>(&self, rng: &mut R) -> f64 {
+        let u: f64 = rng.gen();
+        let f = |x: f64| self.ln_f(&x).exp();
+        let (mut lower, mut upper) = self.quad_bounds();
+        // The CDF at `lower`
+        let mut cdf_lower = 0.0;
+        for _ in 0..INV_CDF_BISECTIONS {
+            let mid = (lower + upper) / 2.0;
+            let cdf_mid = cdf_lower
+                + adaptive_gauss_legendre_quadrature(
+                    f,
+                    (lower, mid),
+                    1E-12,
+                    30,
+                );
+            if cdf_mid < u {
+                lower = mid;
+                cdf_lower = cdf_mid;
+            } else {
+                upper = mid;
+            }
+        }
+        (lower + upper) / 2.0
+    }
+}
+
+impl<Fx> LogLinearPool<Fx> {
+    /// The weight of each component
+    #[inline]
+    pub fn weights(&self) -> &[f64] {
+        self.linear.weights()
+    }
+
+    /// The pooled distributions
+    #[inline]
+    pub fn components(&self) -> &[Fx] {
+        self.linear.components()
+    }
+
+    /// The log of the normalizing constant, ln Z
+    #[inline]
+    pub fn ln_z(&self) -> f64 {
+        self.ln_z
+    }
+
+    /// The linear pool with the same weights
+    #[inline]
+    pub fn linear_pool(&self) -> &Mixture<Fx> {
+        &self.linear
+    }
+}
+
+impl<Fx> Rv<f64> for LogLinearPool<Fx>
+where
+    Fx: ContinuousDistr<f64> + QuadBounds,
+{
+    fn ln_f(&self, x: &f64) -> f64 {
+        let ln_fs = self.components().iter().map(|cpnt| cpnt.ln_f(x));
+        weighted_ln_f(self.weights(), ln_fs) - self.ln_z
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> f64 {
+        (0..DRAW_MAX_PROPOSALS)
+            .find_map(|_| self.propose(rng))
+            .unwrap_or_else(|| self.inv_cdf_draw(rng))
+    }
+}
+
+impl<Fx> TryDraw<f64> for LogLinearPool<Fx>
+where
+    Fx: ContinuousDistr<f64> + QuadBounds,
+{
+    fn try_draw<R: Rng>(
+        &self,
+        max_proposals: usize,
+        policy: DrawPolicy,
+        rng: &mut R,
+    ) -> Result<f64, DrawError> {
+        if let Some(x) = (0..max_proposals).find_map(|_| self.propose(rng)) {
+            return Ok(x);
+        }
+        match policy {
+            DrawPolicy::Retry => loop {
+                if let Some(x) = self.propose(rng) {
+                    return Ok(x);
+                }
+            },
+            DrawPolicy::Fallback => Ok(self.inv_cdf_draw(rng)),
+            DrawPolicy::Error => Err(DrawError::ProposalLimitReached {
+                n_proposals: max_proposals,
+            }),
+        }
+    }
+}
+
+impl<Fx> Support<f64> for LogLinearPool<Fx>
+where
+    Fx: ContinuousDistr<f64> + QuadBounds,
+{
+    fn supports(&self, x: &f64) -> bool {
+        self.components()
+            .iter()
+            .zip(self.weights())
+            .all(|(cpnt, &w)| w == 0.0 || cpnt.supports(x))
+    }
+}
+
+impl<Fx> ContinuousDistr<f64> for LogLinearPool<Fx> where
+    Fx: ContinuousDistr<f64> + QuadBounds
+{
+}
+
+impl<Fx: QuadBounds> QuadBounds for LogLinearPool<Fx> {
+    fn quad_bounds(&self) -> (f64, f64) {
+        pooled_quad_bounds(self.components())
+    }
+}
+
+impl std::error::Error for PoolError {}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mixture(err) => write!(f, "invalid pool: {}", err),
+            Self::NoValidationData => {
+                write!(f, "no validation data to fit the weights to")
+            }
+            Self::NotNormalizable { ln_z } => write!(
+                f,
+                "the pooled density cannot be normalized (ln Z = {})",
+                ln_z
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::Gaussian;
+    use crate::misc::ks_test;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-8;
+
+    fn gaussians() -> Vec<Gaussian> {
+        vec![
+            Gaussian::new(-1.0, 1.0).unwrap(),
+            Gaussian::new(2.0, 0.5).unwrap(),
+        ]
+    }
+
+    // Pooled Gaussians have the weighted mean precision and the
+    // precision-weighted mean
+    fn pooled_gaussian(weights: &[f64], gs: &[Gaussian]) -> Gaussian {
+        let prec: f64 = weights
+            .iter()
+            .zip(gs)
+            .map(|(w, g)| w / (g.sigma() * g.sigma()))
+            .sum();
+        let mu: f64 = weights
+            .iter()
+            .zip(gs)
+            .map(|(w, g)| w * g.mu() / (g.sigma() * g.sigma()))
+            .sum::<f64>()
+            / prec;
+        Gaussian::new(mu, prec.sqrt().recip()).unwrap()
+    }
+
+    #[test]
+    fn log_linear_pool_of_gaussians_is_gaussian() {
+        let weights = vec![0.3, 0.7];
+        let pool = LogLinearPool::new(weights.clone(), gaussians()).unwrap();
+        let expected = pooled_gaussian(&weights, &gaussians());
+        for x in [-2.0_f64, 0.0, 1.3, 2.5] {
+            assert::close(pool.ln_f(&x), expected.ln_f(&x), TOL);
+        }
+    }
+
+    #[test]
+    fn log_linear_pool_draws_follow_density() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x9001);
+        let weights = vec![0.3, 0.7];
+        let pool = LogLinearPool::new(weights.clone(), gaussians()).unwrap();
+        let expected = pooled_gaussian(&weights, &gaussians());
+
+        let xs: Vec<f64> = pool.sample(1_000, &mut rng);
+        let (_, p) = ks_test(&xs, |x| expected.cdf(&x));
+        assert!(p > 0.01);
+    }
+
+    #[test]
+    fn log_linear_pool_of_separated_components() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x9004);
+        let gs = vec![
+            Gaussian::new(-10.0, 1.0).unwrap(),
+            Gaussian::new(10.0, 1.0).unwrap(),
+        ];
+        let pool = LogLinearPool::new(vec![0.5, 0.5], gs).unwrap();
+        assert::close(pool.ln_z(), -50.0, 1E-6);
+        assert::close(pool.ln_f(&0.5), Gaussian::standard().ln_f(&0.5), TOL);
+
+        // Almost no proposals are accepted
+        assert_eq!(
+            pool.try_draw(10, DrawPolicy::Error, &mut rng),
+            Err(DrawError::ProposalLimitReached { n_proposals: 10 })
+        );
+
+        let xs: Vec<f64> = pool.sample(200, &mut rng);
+        let (_, p) = ks_test(&xs, |x| Gaussian::standard().cdf(&x));
+        assert!(p > 0.01);
+    }
+
+    #[test]
+    fn log_linear_pool_inv_cdf_draws_follow_density() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x9005);
+        let weights = vec![0.3, 0.7];
+        let pool = LogLinearPool::new(weights.clone(), gaussians()).unwrap();
+        let expected = pooled_gaussian(&weights, &gaussians());
+
+        let xs: Vec<f64> = (0..200)
+            .map(|_| pool.try_draw(0, DrawPolicy::Fallback, &mut rng).unwrap())
+            .collect();
+        let (_, p) = ks_test(&xs, |x| expected.cdf(&x));
+        assert!(p > 0.01);
+    }
+
+    #[test]
+    fn linear_pool_recovers_mixture_weights() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x9002);
+        let truth = Mixture::new(vec![0.2, 0.8], gaussians()).unwrap();
+        let xs: Vec<f64> = truth.sample(2_000, &mut rng);
+
+        let pool = linear_pool(gaussians(), &xs).unwrap();
+        assert!((pool.weights()[0] - 0.2).abs() < 0.03);
+    }
+
+    #[test]
+    fn log_linear_pool_fit_improves_log_score() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x9003);
+        let truth = pooled_gaussian(&[0.4, 0.6], &gaussians());
+        let xs: Vec<f64> = truth.sample(500, &mut rng);
+
+        let pool = LogLinearPool::fit(gaussians(), &xs).unwrap();
+        assert!((pool.weights()[0] - 0.4).abs() < 0.1);
+
+        let score = |fx: &LogLinearPool<Gaussian>| {
+            xs.iter().map(|x| fx.ln_f(x)).sum::<f64>()
+        };
+        let even = LogLinearPool::new(vec![0.5, 0.5], gaussians()).unwrap();
+        assert!(score(&pool) >= score(&even));
+    }
+
+    #[test]
+    fn pool_errors() {
+        assert_eq!(
+            linear_pool::<f64, Gaussian>(vec![], &[1.0]),
+            Err(PoolError::Mixture(MixtureError::ComponentsEmpty))
+        );
+        assert_eq!(
+            linear_pool::<f64, Gaussian>(gaussians(), &[]),
+            Err(PoolError::NoValidationData)
+        );
+        assert!(matches!(
+            LogLinearPool::new(vec![0.5, 0.6], gaussians()),
+            Err(PoolError::Mixture(
+                MixtureError::WeightsDoNotSumToOne { .. }
+            ))
+        ));
+    }
+}
@@ -0,0 +1,303 @@
+//! Projection of distributions onto tractable families
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::dist::{Beta, Gamma, Gaussian, LogNormal};
+use crate::misc::adaptive_gauss_legendre_quadrature;
+use crate::misc::optimize::nelder_mead;
+use crate::traits::*;
+use std::fmt;
+
+/// Tolerance of the quadrature for moments and cross entropies
+const QUAD_TOL: f64 = 1E-12;
+/// Stop minimizing the KL divergence when it changes by less than this
+const KL_TOL: f64 = 1E-12;
+
+/// How [`Project::project_to`] chooses the closest member of a family
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum ProjectionMethod {
+    /// Match the mean and variance
+    MomentMatch,
+    /// Minimize KL(p || q) from the distribution, p, to the member of the
+    /// family, q, as in expectation propagation. The search starts from the
+    /// moment-matched member. For Gaussians the two methods agree.
+    MinKl,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum ProjectionError {
+    /// The distribution has no mass within its quad bounds, or its moments
+    /// are not finite
+    MomentsNotFinite,
+    /// No member of the family has the mean and variance of the
+    /// distribution
+    MomentsNotMatched { mean: f64, var: f64 },
+    /// Every member of the family is infinitely far from the distribution,
+    /// e.g., because the distribution has mass outside the support of the
+    /// family
+    InfiniteDivergence,
+}
+
+/// The mean and variance of `fx` by quadrature over its quad bounds
+fn moments<Fx>(fx: &Fx) -> Result<(f64, f64), ProjectionError>
+where
+    Fx: ContinuousDistr<f64> + QuadBounds + ?Sized,
+{
+    let bounds = fx.quad_bounds();
+    let quad = |f: &dyn Fn(f64) -> f64| {
+        adaptive_gauss_legendre_quadrature(f, bounds, QUAD_TOL, 20)
+    };
+    let mass = quad(&|x| fx.pdf(&x));
+    let mean = quad(&|x| x * fx.pdf(&x)) / mass;
+    let var = quad(&|x| (x - mean).powi(2) * fx.pdf(&x)) / mass;
+    if mass > 0.0 && mean.is_finite() && var.is_finite() {
+        Ok((mean, var))
+    } else {
+        Err(ProjectionError::MomentsNotFinite)
+    }
+}
+
+/// The cross entropy, -∫ p(x) ln q(x) dx, which is KL(p || q) up to a
+/// constant in q
+fn cross_entropy<P, Q>(p: &P, q: &Q) -> f64
+where
+    P: ContinuousDistr<f64> + QuadBounds + ?Sized,
+    Q: ContinuousDistr<f64>,
+{
+    let integrand = |x: f64| {
+        let px = p.pdf(&x);
+        if px > 0.0 {
+            -px * q.ln_pdf(&x)
+        } else {
+            0.0
+        }
+    };
+    adaptive_gauss_legendre_quadrature(integrand, p.quad_bounds(), QUAD_TOL, 20)
+}
+
+pub(crate) fn project<Fx, D>(
+    fx: &Fx,
+    method: ProjectionMethod,
+) -> Result<D, ProjectionError>
+where
+    Fx: ContinuousDistr<f64> + QuadBounds + ?Sized,
+    D: ProjectionFamily,
+{
+    let (mean, var) = moments(fx)?;
+    let matched = D::from_mean_var(mean, var)
+        .ok_or(ProjectionError::MomentsNotMatched { mean, var })?;
+    if method == ProjectionMethod::MomentMatch {
+        return Ok(matched);
+    }
+
+    let loss = |params: &[f64]| {
+        D::from_unconstrained_params(params)
+            .map_or(f64::INFINITY, |q| cross_entropy(fx, &q))
+    };
+    let init = matched.unconstrained_params();
+    let mut best = (init.clone(), loss(&init));
+    if !best.1.is_finite() {
+        return Err(ProjectionError::InfiniteDivergence);
+    }
+
+    // Restart from the best point found so that a collapsed simplex does not
+    // end the search early.
+    for step in [0.5, 0.1, 0.02] {
+        let next = nelder_mead(loss, &best.0, step, KL_TOL, 2_000);
+        if next.1 < best.1 {
+            best = next;
+        }
+    }
+    Ok(D::from_unconstrained_params(&best.0)
+        .expect("the best parameters have a finite loss, so are valid"))
+}
+
+impl ProjectionFamily for Gaussian {
+    fn from_mean_var(mean: f64, var: f64) -> Option<Self> {
+        Gaussian::new(mean, var.sqrt()).ok()
+    }
+
+    fn unconstrained_params(&self) -> Vec<f64> {
+        vec![self.mu(), self.sigma().ln()]
+    }
+
+    fn from_unconstrained_params(params: &[f64]) -> Option<Self> {
+        Gaussian::new(params[0], params[1].exp()).ok()
+    }
+}
+
+impl ProjectionFamily for Gamma {
+    fn from_mean_var(mean: f64, var: f64) -> Option<Self> {
+        if mean > 0.0 {
+            Gamma::new(mean * mean / var, mean / var).ok()
+        } else {
+            None
+        }
+    }
+
+    fn unconstrained_params(&self) -> Vec<f64> {
+        vec![self.shape().ln(), self.rate().ln()]
+    }
+
+    fn from_unconstrained_params(params: &[f64]) -> Option<Self> {
+        Gamma::new(params[0].exp(), params[1].exp()).ok()
+    }
+}
+
+impl ProjectionFamily for LogNormal {
+    fn from_mean_var(mean: f64, var: f64) -> Option<Self> {
+        if mean > 0.0 {
+            let sigma2 = (var / (mean * mean)).ln_1p();
+            LogNormal::new(0.5_f64.mul_add(-sigma2, mean.ln()), sigma2.sqrt())
+                .ok()
+        } else {
+            None
+        }
+    }
+
+    fn unconstrained_params(&self) -> Vec<f64> {
+        vec![self.mu(), self.sigma().ln()]
+    }
+
+    fn from_unconstrained_params(params: &[f64]) -> Option<Self> {
+        LogNormal::new(params[0], params[1].exp()).ok()
+    }
+}
+
+impl ProjectionFamily for Beta {
+    fn from_mean_var(mean: f64, var: f64) -> Option<Self> {
+        if 0.0 < mean && mean < 1.0 && var < mean * (1.0 - mean) {
+            let concentration = mean * (1.0 - mean) / var - 1.0;
+            Beta::new(mean * concentration, (1.0 - mean) * concentration).ok()
+        } else {
+            None
+        }
+    }
+
+    fn unconstrained_params(&self) -> Vec<f64> {
+        vec![self.alpha().ln(), self.beta().ln()]
+    }
+
+    fn from_unconstrained_params(params: &[f64]) -> Option<Self> {
+        Beta::new(params[0].exp(), params[1].exp()).ok()
+    }
+}
+
+impl std::error::Error for ProjectionError {}
+
+impl fmt::Display for ProjectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MomentsNotFinite => write!(
+                f,
+                "the distribution has no finite mean and variance within its \
+                 quad bounds"
+            ),
+            Self::MomentsNotMatched { mean, var } => write!(
+                f,
+                "no member of the family has mean {} and variance {}",
+                mean, var
+            ),
+            Self::InfiniteDivergence => write!(
+                f,
+                "the KL divergence to every member of the family is infinite"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::Mixture;
+
+    fn bimodal() -> Mixture<Gaussian> {
+        Mixture::new(
+            vec![0.3, 0.7],
+            vec![
+                Gaussian::new(1.0, 0.5).unwrap(),
+                Gaussian::new(4.0, 1.0).unwrap(),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn gaussian_projections_match_moments() {
+        let mm = bimodal();
+        let mean: f64 = mm.mean().unwrap();
+        let var: f64 = mm.variance().unwrap();
+
+        let g: Gaussian = mm.project_to(ProjectionMethod::MomentMatch).unwrap();
+        assert::close(g.mu(), mean, 1E-10);
+        assert::close(g.sigma(), var.sqrt(), 1E-10);
+
+        // The KL projection onto the Gaussians also matches the moments
+        let g: Gaussian = mm.project_to(ProjectionMethod::MinKl).unwrap();
+        assert::close(g.mu(), mean, 1E-5);
+        assert::close(g.sigma(), var.sqrt(), 1E-5);
+    }
+
+    #[test]
+    fn moment_matching_other_families() {
+        let gamma = Gamma::new(3.0, 2.0).unwrap();
+        let lognormal: LogNormal =
+            gamma.project_to(ProjectionMethod::MomentMatch).unwrap();
+        let (mean, var): (f64, f64) =
+            (lognormal.mean().unwrap(), lognormal.variance().unwrap());
+        assert::close(mean, 1.5, 1E-8);
+        assert::close(var, 0.75, 1E-8);
+
+        let lognormal = LogNormal::new(-1.0, 0.3).unwrap();
+        let beta: Beta =
+            lognormal.project_to(ProjectionMethod::MomentMatch).unwrap();
+        let (mean, var): (f64, f64) =
+            (lognormal.mean().unwrap(), lognormal.variance().unwrap());
+        let beta_mean: f64 = beta.mean().unwrap();
+        assert::close(beta_mean, mean, 1E-8);
+        assert::close(beta.variance().unwrap(), var, 1E-8);
+    }
+
+    #[test]
+    fn min_kl_matches_sufficient_statistics() {
+        // The KL projection onto the Gammas matches E[x] and E[ln x]
+        let lognormal = LogNormal::new(0.5, 0.6).unwrap();
+        let gamma: Gamma =
+            lognormal.project_to(ProjectionMethod::MinKl).unwrap();
+        let mean: f64 = gamma.mean().unwrap();
+        assert::close(mean, 0.6_f64.mul_add(0.3, 0.5).exp(), 1E-4);
+        let ln_mean =
+            special::Gamma::digamma(gamma.shape()) - gamma.rate().ln();
+        assert::close(ln_mean, 0.5, 1E-4);
+
+        // A member of the family projects onto itself
+        let beta = Beta::new(2.0, 5.0).unwrap();
+        let projected: Beta = beta.project_to(ProjectionMethod::MinKl).unwrap();
+        assert::close(projected.alpha(), 2.0, 1E-4);
+        assert::close(projected.beta(), 5.0, 1E-4);
+    }
+
+    #[test]
+    fn projection_errors() {
+        let g = Gaussian::new(-1.0, 1.0).unwrap();
+        match g.project_to::<Gamma>(ProjectionMethod::MomentMatch) {
+            Err(ProjectionError::MomentsNotMatched { mean, var }) => {
+                assert::close(mean, -1.0, 1E-8);
+                assert::close(var, 1.0, 1E-8);
+            }
+            res => panic!("expected MomentsNotMatched, got {:?}", res),
+        }
+
+        // Mass below zero is infinitely surprising to a Gamma
+        let g = Gaussian::new(5.0, 1.0).unwrap();
+        assert!(g.project_to::<Gamma>(ProjectionMethod::MomentMatch).is_ok());
+        assert_eq!(
+            g.project_to::<Gamma>(ProjectionMethod::MinKl),
+            Err(ProjectionError::InfiniteDivergence)
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Shared machinery for constructing distributions from quantiles
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use special::Error as _;
+use std::f64::consts::SQRT_2;
+use std::fmt;
+
+use crate::misc::optimize::nelder_mead;
+
+/// Smallest CDF value used when mapping to the probit scale
+const CDF_FLOOR: f64 = 1E-15;
+
+/// Error from constructing a distribution from quantiles
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum QuantileMatchError {
+    /// Fewer quantiles were given than there are parameters to solve for
+    TooFewQuantiles { given: usize, required: usize },
+    /// A probability was not in (0, 1)
+    ProbabilityOutOfRange { p: f64 },
+    /// The quantile values do not strictly increase with probability
+    NotIncreasing,
+    /// A quantile value is outside the support of the distribution
+    ValueOutOfSupport { x: f64 },
+    /// No valid parameters could be found to match the quantiles
+    NoSolution,
+}
+
+impl std::error::Error for QuantileMatchError {}
+
+impl fmt::Display for QuantileMatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooFewQuantiles { given, required } => write!(
+                f,
+                "{} quantiles given but at least {} are required",
+                given, required
+            ),
+            Self::ProbabilityOutOfRange { p } => {
+                write!(f, "probability ({}) must be in (0, 1)", p)
+            }
+            Self::NotIncreasing => write!(
+                f,
+                "quantile values must strictly increase with probability"
+            ),
+            Self::ValueOutOfSupport { x } => {
+                write!(f, "quantile value ({}) is outside the support", x)
+            }
+            Self::NoSolution => {
+                write!(f, "no valid parameters match the quantiles")
+            }
+        }
+    }
+}
+
+/// Standard normal quantile function
+pub(crate) fn probit(p: f64) -> f64 {
+    SQRT_2 * 2.0_f64.mul_add(p, -1.0).inv_error()
+}
+
+/// Check the `(p, x)` pairs and return them sorted by `p`.
+pub(crate) fn validate<S>(
+    quantiles: &[(f64, f64)],
+    required: usize,
+    supports: S,
+) -> Result<Vec<(f64, f64)>, QuantileMatchError>
+where
+    S: Fn(f64) -> bool,
+{
+    if quantiles.len() < required {
+        return Err(QuantileMatchError::TooFewQuantiles {
+            given: quantiles.len(),
+            required,
+        });
+    }
+
+    for &(p, x) in quantiles {
+        if !(0.0 < p && p < 1.0) {
+            return Err(QuantileMatchError::ProbabilityOutOfRange { p });
+        }
+        if !x.is_finite() || !supports(x) {
+            return Err(QuantileMatchError::ValueOutOfSupport { x });
+        }
+    }
+
+    let mut sorted = quantiles.to_vec();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    if sorted
+        .windows(2)
+        .any(|w| w[0].0 >= w[1].0 || w[0].1 >= w[1].1)
+    {
+        Err(QuantileMatchError::NotIncreasing)
+    } else {
+        Ok(sorted)
+    }
+}
+
+/// Find the parameters, `θ`, that minimize the squared distance between
+/// `Φ⁻¹(F(x; θ))` and `Φ⁻¹(p)` over the quantiles.
+///
+/// `cdf` takes the unconstrained parameters; it may return NaN to mark
+/// invalid parameters.
+pub(crate) fn fit<F>(quantiles: &[(f64, f64)], init: &[f64], cdf: F) -> Vec<f64>
+where
+    F: Fn(&[f64], f64) -> f64,
+{
+    let targets: Vec<f64> = quantiles.iter().map(|&(p, _)| probit(p)).collect();
+    let loss = |params: &[f64]| {
+        quantiles
+            .iter()
+            .zip(targets.iter())
+            .map(|(&(_, x), &z)| {
+                let q = cdf(params, x).clamp(CDF_FLOOR, 1.0 - CDF_FLOOR);
+                (probit(q) - z).powi(2)
+            })
+            .sum::<f64>()
+    };
+
+    // Restart from the best point found so that a collapsed simplex does not
+    // end the search early.
+    let mut best = nelder_mead(loss, init, 0.5, 1E-14, 2_000);
+    for _ in 0..3 {
+        let next = nelder_mead(loss, &best.0, 0.1, 1E-14, 2_000);
+        if next.1 >= best.1 {
+            break;
+        }
+        best = next;
+    }
+    best.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probit_values() {
+        assert::close(probit(0.5), 0.0, 1E-12);
+        assert::close(probit(0.975), 1.959_963_984_540_054, 1E-8);
+        assert::close(probit(0.025), -1.959_963_984_540_054, 1E-8);
+    }
+
+    #[test]
+    fn validate_sorts_by_probability() {
+        let qs = validate(&[(0.9, 3.0), (0.1, 1.0)], 2, |_| true).unwrap();
+        assert_eq!(qs, vec![(0.1, 1.0), (0.9, 3.0)]);
+    }
+
+    #[test]
+    fn validate_errors() {
+        assert_eq!(
+            validate(&[(0.5, 1.0)], 2, |_| true),
+            Err(QuantileMatchError::TooFewQuantiles {
+                given: 1,
+                required: 2
+            })
+        );
+        assert_eq!(
+            validate(&[(0.0, 1.0), (0.5, 2.0)], 2, |_| true),
+            Err(QuantileMatchError::ProbabilityOutOfRange { p: 0.0 })
+        );
+        assert_eq!(
+            validate(&[(0.1, 2.0), (0.5, 1.0)], 2, |_| true),
+            Err(QuantileMatchError::NotIncreasing)
+        );
+        assert_eq!(
+            validate(&[(0.1, -1.0), (0.5, 1.0)], 2, |x| x > 0.0),
+            Err(QuantileMatchError::ValueOutOfSupport { x: -1.0 })
+        );
+    }
+}
@@ -0,0 +1,624 @@
+//! Record values, record times, and waiting times between records of iid
+//! sequences
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::dist::Gamma;
+use crate::impl_display;
+use crate::misc::adaptive_gauss_legendre_quadrature;
+use crate::traits::*;
+use num::{FromPrimitive, Integer, ToPrimitive, Unsigned};
+use rand::Rng;
+use rand_distr::Open01;
+use std::fmt;
+
+/// Width of the integration segments over the cumulative hazard
+const SEGMENT_WIDTH: f64 = 2.0;
+/// Maximum bisections of each integration segment
+const MAX_DEPTH: usize = 20;
+
+/// Distribution of the `n`-th upper `k`-record value of an iid sequence
+/// drawn from `base`.
+///
+/// A `k`-record is a new `k`-th largest value: the first is the smallest
+/// of the first `k` draws, and each later one is the `k`-th largest value
+/// once a draw exceeds the previous `k`-record. With `k = 1` these are the
+/// ordinary records, the running maxima, and the first record is the first
+/// draw.
+///
+/// The cumulative hazard of the `n`-th `k`-record, `-ln(1 - F(x))`, is
+/// Gamma(n, k) distributed, which gives its density, CDF, and draws.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{Exponential, RecordValue};
+/// use rv::traits::*;
+///
+/// // Exponential records are sums of the spacings, so the third record of
+/// // Exp(1) is Gamma(3, 1)
+/// let third = RecordValue::new(Exponential::new(1.0).unwrap(), 3).unwrap();
+/// assert::close(third.cdf(&2.0), 1.0 - 5.0 * (-2.0_f64).exp(), 1E-12);
+///
+/// let mut rng = rand::thread_rng();
+/// let x: f64 = third.draw(&mut rng);
+/// assert!(x > 0.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct RecordValue<Fx> {
+    base: Fx,
+    n: usize,
+    k: usize,
+}
+
+/// Distribution of the index, starting from 1, of the `n`-th `k`-record of
+/// an iid sequence from any continuous distribution.
+///
+/// The `m`-th draw is a `k`-record with probability `k / m` independently
+/// of the others, so the record times do not depend on the distribution.
+/// For `k = 1` and `n ≥ 2` they are heavy tailed with no mean.
+///
+/// Evaluating the pmf or CDF at `j` takes O(j n) time.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::RecordTime;
+/// use rv::traits::*;
+///
+/// // The second record is at draw j with probability 1 / (j (j - 1))
+/// let second = RecordTime::new(2).unwrap();
+/// assert::close(second.f(&5_u32), 1.0 / 20.0, 1E-12);
+/// assert::close(second.cdf(&5_u32), 0.8, 1E-12);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct RecordTime {
+    n: usize,
+    k: usize,
+}
+
+/// Distribution of the number of draws from the `n`-th `k`-record to the
+/// next, for an iid sequence from any continuous distribution.
+///
+/// Given the cumulative hazard `h` of the `n`-th record, the wait is
+/// geometric with success probability `exp(-h)`; the pmf and CDF average
+/// that over the Gamma(n, k) distribution of `h` by quadrature.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::RecordWaitingTime;
+/// use rv::traits::*;
+///
+/// // The first wait is j draws with probability 1 / (j (j + 1))
+/// let wait = RecordWaitingTime::new(1).unwrap();
+/// assert::close(wait.f(&3_u32), 1.0 / 12.0, 1E-10);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct RecordWaitingTime {
+    n: usize,
+    k: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum RecordError {
+    /// The record number is zero; records are numbered from 1
+    NIsZero,
+    /// The record order k is zero
+    KIsZero,
+}
+
+fn check_nk(n: usize, k: usize) -> Result<(), RecordError> {
+    if n == 0 {
+        Err(RecordError::NIsZero)
+    } else if k == 0 {
+        Err(RecordError::KIsZero)
+    } else {
+        Ok(())
+    }
+}
+
+/// The Gamma(n, k) distribution of the cumulative hazard of the `n`-th
+/// `k`-record
+fn hazard_dist(n: usize, k: usize) -> Gamma {
+    Gamma::new_unchecked(n as f64, k as f64)
+}
+
+/// Draw the index and cumulative hazard of the `n`-th `k`-record by
+/// stepping through the records before it
+fn draw_record_index<R: Rng>(n: usize, k: usize, rng: &mut R) -> (usize, f64) {
+    let k_f = k as f64;
+    let exp_k = |rng: &mut R| -rng.sample::<f64, _>(Open01).ln() / k_f;
+    let mut ix = k;
+    let mut h = exp_k(rng);
+    for _ in 1..n {
+        ix = ix.saturating_add(draw_wait(h, rng));
+        h += exp_k(rng);
+    }
+    (ix, h)
+}
+
+/// Geometric number of draws, from 1, to exceed a record with cumulative
+/// hazard `h`
+fn draw_wait<R: Rng>(h: f64, rng: &mut R) -> usize {
+    let ln_q = (-(-h).exp()).ln_1p();
+    let u: f64 = rng.sample(Open01);
+    // Saturating cast for records too high to be beaten in practice
+    (u.ln() / ln_q).ceil().max(1.0) as usize
+}
+
+/// `P(N_m = r)` for `r` in `0..=r_max`, where `N_m` is the number of
+/// `k`-records among the first `m` draws
+fn record_count_probs(m: usize, r_max: usize, k: usize) -> Vec<f64> {
+    let mut probs = vec![0.0; r_max + 1];
+    probs[0] = 1.0;
+    for i in k..=m {
+        let p = k as f64 / i as f64;
+        for r in (0..=r_max).rev() {
+            let from_below = if r == 0 { 0.0 } else { probs[r - 1] * p };
+            probs[r] = probs[r].mul_add(1.0 - p, from_below);
+        }
+    }
+    probs
+}
+
+/// E[g(h)] with h ~ Gamma(n, k) by adaptive quadrature, with segments
+/// fine enough not to miss the peak of `g` near `h_peak`
+fn hazard_expectation<F: Fn(f64) -> f64>(
+    n: usize,
+    k: usize,
+    h_peak: f64,
+    g: F,
+) -> f64 {
+    let dist = hazard_dist(n, k);
+    let integrand = |h: f64| g(h) * dist.f(&h);
+    let sd = (n as f64).sqrt() / k as f64;
+    let upper = 40.0_f64.mul_add(sd, n as f64 / k as f64).max(h_peak + 40.0);
+    let n_segments = (upper / SEGMENT_WIDTH).ceil() as usize;
+    let integrate = |tol: f64| {
+        (0..n_segments)
+            .map(|i| {
+                let a = i as f64 * SEGMENT_WIDTH;
+                adaptive_gauss_legendre_quadrature(
+                    integrand,
+                    (a, a + SEGMENT_WIDTH),
+                    tol,
+                    MAX_DEPTH,
+                )
+            })
+            .sum::<f64>()
+    };
+    // Refine to a tolerance relative to the size of the result
+    let rough = integrate(1E-10);
+    integrate((rough * 1E-10).max(f64::MIN_POSITIVE))
+}
+
+impl<Fx> RecordValue<Fx> {
+    /// The distribution of the `n`-th record of draws from `base`
+    pub fn new(base: Fx, n: usize) -> Result<Self, RecordError> {
+        Self::k_record(base, n, 1)
+    }
+
+    /// The distribution of the `n`-th `k`-record of draws from `base`
+    pub fn k_record(base: Fx, n: usize, k: usize) -> Result<Self, RecordError> {
+        check_nk(n, k)?;
+        Ok(RecordValue { base, n, k })
+    }
+
+    /// Creates a new RecordValue without checking whether `n` and `k` are
+    /// at least one
+    #[inline]
+    pub fn new_unchecked(base: Fx, n: usize, k: usize) -> Self {
+        RecordValue { base, n, k }
+    }
+
+    /// The distribution of the draws
+    #[inline]
+    pub fn base(&self) -> &Fx {
+        &self.base
+    }
+
+    /// The record number, from 1
+    #[inline]
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// The record order
+    #[inline]
+    pub fn k(&self) -> usize {
+        self.k
+    }
+}
+
+impl RecordTime {
+    /// The distribution of the index of the `n`-th record
+    pub fn new(n: usize) -> Result<Self, RecordError> {
+        Self::k_record(n, 1)
+    }
+
+    /// The distribution of the index of the `n`-th `k`-record
+    pub fn k_record(n: usize, k: usize) -> Result<Self, RecordError> {
+        check_nk(n, k)?;
+        Ok(RecordTime { n, k })
+    }
+
+    /// The record number, from 1
+    #[inline]
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// The record order
+    #[inline]
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// The earliest index the record can have
+    #[inline]
+    fn first_index(&self) -> usize {
+        self.k + self.n - 1
+    }
+}
+
+impl RecordWaitingTime {
+    /// The distribution of the wait after the `n`-th record
+    pub fn new(n: usize) -> Result<Self, RecordError> {
+        Self::k_record(n, 1)
+    }
+
+    /// The distribution of the wait after the `n`-th `k`-record
+    pub fn k_record(n: usize, k: usize) -> Result<Self, RecordError> {
+        check_nk(n, k)?;
+        Ok(RecordWaitingTime { n, k })
+    }
+
+    /// The record number, from 1
+    #[inline]
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// The record order
+    #[inline]
+    pub fn k(&self) -> usize {
+        self.k
+    }
+}
+
+impl<Fx: fmt::Display> From<&RecordValue<Fx>> for String {
+    fn from(record: &RecordValue<Fx>) -> String {
+        format!(
+            "RecordValue(n: {}, k: {}, base: {})",
+            record.n, record.k, record.base
+        )
+    }
+}
+
+impl<Fx: fmt::Display> fmt::Display for RecordValue<Fx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from(self))
+    }
+}
+
+impl From<&RecordTime> for String {
+    fn from(record: &RecordTime) -> String {
+        format!("RecordTime(n: {}, k: {})", record.n, record.k)
+    }
+}
+
+impl_display!(RecordTime);
+
+impl From<&RecordWaitingTime> for String {
+    fn from(record: &RecordWaitingTime) -> String {
+        format!("RecordWaitingTime(n: {}, k: {})", record.n, record.k)
+    }
+}
+
+impl_display!(RecordWaitingTime);
+
+impl<Fx> Rv<f64> for RecordValue<Fx>
+where
+    Fx: Rv<f64> + Cdf<f64> + InverseCdf<f64>,
+{
+    fn ln_f(&self, x: &f64) -> f64 {
+        // Change of variables from the cumulative hazard, dH/dx = f / S
+        let ln_sf = self.base.sf(x).ln();
+        hazard_dist(self.n, self.k).ln_f(&-ln_sf) + self.base.ln_f(x) - ln_sf
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> f64 {
+        let h: f64 = hazard_dist(self.n, self.k).draw(rng);
+        self.base.invcdf(-(-h).exp_m1())
+    }
+}
+
+impl<Fx> Support<f64> for RecordValue<Fx>
+where
+    Fx: Rv<f64> + Cdf<f64> + InverseCdf<f64> + Support<f64>,
+{
+    fn supports(&self, x: &f64) -> bool {
+        self.base.supports(x)
+    }
+}
+
+impl<Fx> ContinuousDistr<f64> for RecordValue<Fx> where
+    Fx: Rv<f64> + Cdf<f64> + InverseCdf<f64> + Support<f64>
+{
+}
+
+impl<Fx> Cdf<f64> for RecordValue<Fx>
+where
+    Fx: Rv<f64> + Cdf<f64> + InverseCdf<f64>,
+{
+    fn cdf(&self, x: &f64) -> f64 {
+        hazard_dist(self.n, self.k).cdf(&-self.base.sf(x).ln())
+    }
+}
+
+impl<X> Rv<X> for RecordTime
+where
+    X: Unsigned + Integer + FromPrimitive + ToPrimitive,
+{
+    fn ln_f(&self, x: &X) -> f64 {
+        let j = x.to_usize().unwrap();
+        if j < self.first_index() {
+            return f64::NEG_INFINITY;
+        }
+        // The (n - 1)-th record by draw j - 1, then a record at draw j
+        let probs = record_count_probs(j - 1, self.n - 1, self.k);
+        probs[self.n - 1].ln() + (self.k as f64 / j as f64).ln()
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> X {
+        let (ix, _) = draw_record_index(self.n, self.k, rng);
+        X::from_usize(ix).unwrap()
+    }
+}
+
+impl<X> Support<X> for RecordTime
+where
+    X: Unsigned + Integer + FromPrimitive + ToPrimitive,
+{
+    fn supports(&self, x: &X) -> bool {
+        x.to_usize().is_some_and(|j| j >= self.first_index())
+    }
+}
+
+impl<X> DiscreteDistr<X> for RecordTime where
+    X: Unsigned + Integer + FromPrimitive + ToPrimitive
+{
+}
+
+impl<X> Cdf<X> for RecordTime
+where
+    X: Unsigned + Integer + FromPrimitive + ToPrimitive,
+{
+    fn cdf(&self, x: &X) -> f64 {
+        1.0 - self.sf(x)
+    }
+
+    /// Fewer than `n` records among the first `j` draws
+    fn sf(&self, x: &X) -> f64 {
+        let j = x.to_usize().unwrap();
+        record_count_probs(j, self.n - 1, self.k).iter().sum()
+    }
+}
+
+impl<X> Rv<X> for RecordWaitingTime
+where
+    X: Unsigned + Integer + FromPrimitive + ToPrimitive,
+{
+    fn ln_f(&self, x: &X) -> f64 {
+        let j = x.to_usize().unwrap();
+        if j == 0 {
+            return f64::NEG_INFINITY;
+        }
+        let j_f = j as f64;
+        // Geometric pmf, exp(-h) (1 - exp(-h))^(j - 1), peaks at h ≈ ln j
+        hazard_expectation(self.n, self.k, j_f.ln(), |h| {
+            let ln_q = (-(-h).exp()).ln_1p();
+            (j_f - 1.0).mul_add(ln_q, -h).exp()
+        })
+        .ln()
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> X {
+        let h: f64 = hazard_dist(self.n, self.k).draw(rng);
+        X::from_usize(draw_wait(h, rng)).unwrap()
+    }
+}
+
+impl<X> Support<X> for RecordWaitingTime
+where
+    X: Unsigned + Integer + FromPrimitive + ToPrimitive,
+{
+    fn supports(&self, x: &X) -> bool {
+        !x.is_zero()
+    }
+}
+
+impl<X> DiscreteDistr<X> for RecordWaitingTime where
+    X: Unsigned + Integer + FromPrimitive + ToPrimitive
+{
+}
+
+impl<X> Cdf<X> for RecordWaitingTime
+where
+    X: Unsigned + Integer + FromPrimitive + ToPrimitive,
+{
+    fn cdf(&self, x: &X) -> f64 {
+        1.0 - self.sf(x)
+    }
+
+    /// No record in the next `j` draws, E[(1 - exp(-h))^j]
+    fn sf(&self, x: &X) -> f64 {
+        let j_f = x.to_f64().unwrap();
+        hazard_expectation(self.n, self.k, j_f.max(1.0).ln(), |h| {
+            (j_f * (-(-h).exp()).ln_1p()).exp()
+        })
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NIsZero => {
+                write!(f, "records are numbered from 1, but n was 0")
+            }
+            Self::KIsZero => write!(f, "the record order k must be at least 1"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Exponential, Gaussian};
+    use crate::test_basic_impls;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    test_basic_impls!(
+        RecordValue::new(Gaussian::standard(), 2).unwrap(),
+        0.5_f64,
+        value_impls
+    );
+    test_basic_impls!(RecordTime::k_record(3, 2).unwrap(), 6_u32, time_impls);
+    test_basic_impls!(RecordWaitingTime::new(2).unwrap(), 3_u32, wait_impls);
+
+    /// Records of draws from `base`, simulated directly
+    fn simulate_records<R: Rng>(
+        base: &Gaussian,
+        n: usize,
+        rng: &mut R,
+    ) -> (f64, usize) {
+        let mut best = f64::NEG_INFINITY;
+        let mut count = 0;
+        for ix in 1.. {
+            let x: f64 = base.draw(rng);
+            if x > best {
+                best = x;
+                count += 1;
+                if count == n {
+                    return (best, ix);
+                }
+            }
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn exponential_k_records_are_gamma() {
+        // Spacings of Exp(1) k-records are Exp(k)
+        let base = Exponential::new(1.0).unwrap();
+        let record = RecordValue::k_record(base, 3, 2).unwrap();
+        let gamma = Gamma::new(3.0, 2.0).unwrap();
+        for x in [0.3, 1.0, 2.5] {
+            assert::close(record.ln_f(&x), gamma.ln_f(&x), TOL);
+            assert::close(record.cdf(&x), gamma.cdf(&x), TOL);
+        }
+    }
+
+    #[test]
+    fn gaussian_record_values_match_simulation() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x88);
+        let base = Gaussian::standard();
+        let record = RecordValue::new(base.clone(), 3).unwrap();
+        let n = 20_000;
+        let simulated: Vec<f64> = (0..n)
+            .map(|_| simulate_records(&base, 3, &mut rng).0)
+            .collect();
+        let drawn: Vec<f64> = record.sample(n, &mut rng);
+        for x in [0.5, 1.0, 1.5, 2.5] {
+            let sim_cdf =
+                simulated.iter().filter(|&&s| s <= x).count() as f64 / n as f64;
+            let draw_cdf =
+                drawn.iter().filter(|&&s| s <= x).count() as f64 / n as f64;
+            assert::close(record.cdf(&x), sim_cdf, 0.015);
+            assert::close(record.cdf(&x), draw_cdf, 0.015);
+        }
+    }
+
+    #[test]
+    fn record_time_pmf() {
+        // Third record at draw 4: records at draws {1, 2, 4} or {1, 3, 4}
+        let third = RecordTime::new(3).unwrap();
+        let p = 1.0 / 2.0 * 2.0 / 3.0 * 1.0 / 4.0 + 1.0 / 2.0 * 1.0 / 3.0 / 4.0;
+        assert::close(third.f(&4_u32), p, TOL);
+        assert_eq!(third.f(&2_u32), 0.0);
+
+        let first = RecordTime::k_record(1, 3).unwrap();
+        assert_eq!(first.f(&3_u32), 1.0);
+        assert_eq!(first.cdf(&2_u32), 0.0);
+
+        let pmf_sum: f64 = (0..=40_u32).map(|j| third.f(&j)).sum();
+        assert::close(third.cdf(&40_u32), pmf_sum, TOL);
+    }
+
+    #[test]
+    fn record_time_draws_match_simulation() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x89);
+        let base = Gaussian::standard();
+        let time = RecordTime::new(3).unwrap();
+        let n = 20_000;
+        let simulated: Vec<usize> = (0..n)
+            .map(|_| simulate_records(&base, 3, &mut rng).1)
+            .collect();
+        let drawn: Vec<usize> = time.sample(n, &mut rng);
+        for j in [3_usize, 5, 10, 50] {
+            let cdf = time.cdf(&j);
+            let sim = simulated.iter().filter(|&&s| s <= j).count() as f64;
+            let draw = drawn.iter().filter(|&&s| s <= j).count() as f64;
+            assert::close(cdf, sim / n as f64, 0.015);
+            assert::close(cdf, draw / n as f64, 0.015);
+        }
+    }
+
+    #[test]
+    fn waiting_time_consistent_with_record_times() {
+        // The first wait is L(2) - 1
+        let wait = RecordWaitingTime::new(1).unwrap();
+        let second = RecordTime::new(2).unwrap();
+        for j in [1_u32, 2, 10, 100] {
+            assert::close(wait.f(&j), second.f(&(j + 1)), 1E-10);
+            assert::close(wait.cdf(&j), second.cdf(&(j + 1)), 1E-10);
+        }
+
+        // Later waits are longer
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x8A);
+        let later = RecordWaitingTime::k_record(3, 2).unwrap();
+        let pmf_sum: f64 = (1..=20_u32).map(|j| later.f(&j)).sum();
+        assert::close(later.cdf(&20_u32), pmf_sum, 1E-9);
+        let n = 20_000;
+        let drawn: Vec<u32> = later.sample(n, &mut rng);
+        let freq = drawn.iter().filter(|&&j| j <= 5).count() as f64 / n as f64;
+        assert::close(later.cdf(&5_u32), freq, 0.015);
+    }
+
+    #[test]
+    fn zero_n_or_k_is_rejected() {
+        assert_eq!(RecordTime::new(0), Err(RecordError::NIsZero));
+        assert_eq!(
+            RecordWaitingTime::k_record(1, 0),
+            Err(RecordError::KIsZero)
+        );
+        assert_eq!(
+            RecordValue::new(Gaussian::standard(), 0),
+            Err(RecordError::NIsZero)
+        );
+    }
+}
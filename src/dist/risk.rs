@@ -0,0 +1,285 @@
+//! Value at risk and expected shortfall of loss distributions
+use crate::consts::HALF_LN_2PI;
+use crate::dist::{
+    Beta, Cauchy, CompoundPoisson, Empirical, Exponential, Gamma, Gaussian,
+    KsTwoAsymptotic, Kumaraswamy, LogNormal, LogUniform, LogitNormal, Lomax,
+    Pareto, ScaledBeta, Uniform, Weibull,
+};
+use crate::misc::adaptive_gauss_legendre_quadrature;
+use crate::traits::*;
+use special::Gamma as _;
+
+/// Smallest tail probability the quantile function is evaluated at
+const MIN_TAIL: f64 = 1E-15;
+/// Tolerance of the quadrature over each unit of the transformed tail
+const QUAD_TOL: f64 = 1E-12;
+
+/// The average of `f` over tail probabilities in (0, `tail`).
+///
+/// Substituting τ = tail exp(-t) turns the average into ∫ f(τ(t)) exp(-t) dt
+/// over t > 0, which is integrated over unit intervals until τ reaches
+/// `MIN_TAIL`. Returns the first segment that is not finite, e.g. for a
+/// tail without a mean.
+pub(crate) fn tail_average<F>(f: F, tail: f64) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    let t_max = (tail / MIN_TAIL).ln().max(1.0);
+    let integrand = |t: f64| f(tail * (-t).exp()) * (-t).exp();
+
+    let n_segments = t_max.ceil() as usize;
+    let mut total = 0.0;
+    for i in 0..n_segments {
+        let a = i as f64;
+        let b = (a + 1.0).min(t_max);
+        let segment =
+            adaptive_gauss_legendre_quadrature(integrand, (a, b), QUAD_TOL, 12);
+        if !segment.is_finite() {
+            return segment;
+        }
+        total += segment;
+    }
+    total
+}
+
+/// The mean of the quantile function over (p, 1)
+pub(crate) fn quantile_tail_mean<Fx>(fx: &Fx, p: f64) -> f64
+where
+    Fx: InverseCdf<f64> + ?Sized,
+{
+    tail_average(|tau| fx.invcdf(1.0 - tau), 1.0 - p)
+}
+
+fn check_p(p: f64) {
+    assert!(0.0 < p && p < 1.0, "p must be in (0, 1)");
+}
+
+/// The standard normal density and quantile at `p`
+fn std_normal_pdf_quantile(p: f64) -> (f64, f64) {
+    let z: f64 = Gaussian::standard().invcdf(p);
+    ((-0.5 * z).mul_add(z, -HALF_LN_2PI).exp(), z)
+}
+
+impl RiskMeasure for Gaussian {
+    fn expected_shortfall(&self, p: f64) -> f64 {
+        check_p(p);
+        let (pdf, _) = std_normal_pdf_quantile(p);
+        self.sigma().mul_add(pdf / (1.0 - p), self.mu())
+    }
+}
+
+impl RiskMeasure for LogNormal {
+    fn expected_shortfall(&self, p: f64) -> f64 {
+        check_p(p);
+        let (_, z) = std_normal_pdf_quantile(p);
+        let sigma = self.sigma();
+        let upper: f64 = Gaussian::standard().sf(&(z - sigma));
+        (0.5 * sigma).mul_add(sigma, self.mu()).exp() * upper / (1.0 - p)
+    }
+}
+
+impl RiskMeasure for Exponential {
+    // Memoryless, so the mean excess is the mean
+    fn expected_shortfall(&self, p: f64) -> f64 {
+        self.value_at_risk(p) + self.rate().recip()
+    }
+}
+
+impl RiskMeasure for Uniform {
+    fn expected_shortfall(&self, p: f64) -> f64 {
+        (self.value_at_risk(p) + self.b()) / 2.0
+    }
+}
+
+impl RiskMeasure for LogUniform {
+    fn expected_shortfall(&self, p: f64) -> f64 {
+        let var = self.value_at_risk(p);
+        (self.b() - var) / ((1.0 - p) * (self.b() / self.a()).ln())
+    }
+}
+
+impl RiskMeasure for Gamma {
+    // The tail of x f(x) is proportional to the tail of Gamma(shape + 1)
+    fn expected_shortfall(&self, p: f64) -> f64 {
+        let var = self.value_at_risk(p);
+        let upper = 1.0 - (self.rate() * var).inc_gamma(self.shape() + 1.0);
+        self.shape() / self.rate() * upper / (1.0 - p)
+    }
+}
+
+impl RiskMeasure for Weibull {
+    // The upper incomplete gamma function Γ(1 + 1/k, -ln(1 - p))
+    fn expected_shortfall(&self, p: f64) -> f64 {
+        check_p(p);
+        let s = 1.0 + self.shape().recip();
+        let x = -(-p).ln_1p();
+        let upper = (1.0 - x.inc_gamma(s)) * special::Gamma::gamma(s);
+        self.scale() * upper / (1.0 - p)
+    }
+}
+
+impl RiskMeasure for Pareto {
+    fn expected_shortfall(&self, p: f64) -> f64 {
+        let var = self.value_at_risk(p);
+        let shape = self.shape();
+        if shape <= 1.0 {
+            f64::INFINITY
+        } else {
+            var * shape / (shape - 1.0)
+        }
+    }
+}
+
+impl RiskMeasure for Lomax {
+    // The mean excess over u is (scale + u) / (shape - 1)
+    fn expected_shortfall(&self, p: f64) -> f64 {
+        let var = self.value_at_risk(p);
+        let shape = self.shape();
+        if shape <= 1.0 {
+            f64::INFINITY
+        } else {
+            var + (self.scale() + var) / (shape - 1.0)
+        }
+    }
+}
+
+impl RiskMeasure for Cauchy {
+    fn expected_shortfall(&self, p: f64) -> f64 {
+        check_p(p);
+        f64::INFINITY
+    }
+}
+
+impl RiskMeasure for Beta {}
+impl RiskMeasure for Kumaraswamy {}
+impl RiskMeasure for ScaledBeta {}
+impl RiskMeasure for LogitNormal {}
+impl RiskMeasure for KsTwoAsymptotic {}
+impl RiskMeasure for Empirical {}
+
+impl<Sev: Rv<f64>> RiskMeasure for CompoundPoisson<Sev> {
+    /// Found from the upper tail, so accurate for `p` near 1
+    fn value_at_risk(&self, p: f64) -> f64 {
+        check_p(p);
+        self.upper_quantile(1.0 - p)
+    }
+
+    /// The average of the worst `1 - p` of outcomes, counting only part of
+    /// the atom at the VaR if needed, and summed from the end of the grid.
+    /// Mass past the end of the grid is left out, so check
+    /// [`truncated_mass`](CompoundPoisson::truncated_mass). Infinite if the
+    /// VaR is past the end of the grid.
+    fn expected_shortfall(&self, p: f64) -> f64 {
+        let var = self.value_at_risk(p);
+        if var.is_infinite() {
+            return f64::INFINITY;
+        }
+        let var_ix = (var / self.span()).round() as usize;
+        let (tail_mean, tail_mass) = self.masses()[var_ix + 1..]
+            .iter()
+            .enumerate()
+            .rev()
+            .fold((0.0, 0.0), |(total, mass), (i, m)| {
+                let x = (var_ix + 1 + i) as f64 * self.span();
+                (m.mul_add(x, total), mass + m)
+            });
+        let atom = (1.0 - p) - tail_mass;
+        atom.mul_add(var, tail_mean) / (1.0 - p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOL: f64 = 1E-9;
+
+    /// The expected shortfall from the default quadrature
+    fn numeric<Fx: InverseCdf<f64>>(fx: &Fx, p: f64) -> f64 {
+        quantile_tail_mean(fx, p)
+    }
+
+    #[test]
+    fn closed_forms_match_quadrature() {
+        for p in [0.5, 0.95, 0.99] {
+            let gauss = Gaussian::new(1.0, 2.0).unwrap();
+            assert::close(gauss.expected_shortfall(p), numeric(&gauss, p), TOL);
+            let lognormal = LogNormal::new(0.5, 0.4).unwrap();
+            assert::close(
+                lognormal.expected_shortfall(p),
+                numeric(&lognormal, p),
+                TOL,
+            );
+            let expon = Exponential::new(2.0).unwrap();
+            assert::close(expon.expected_shortfall(p), numeric(&expon, p), TOL);
+            let uniform = Uniform::new(-1.0, 3.0).unwrap();
+            assert::close(
+                uniform.expected_shortfall(p),
+                numeric(&uniform, p),
+                TOL,
+            );
+            let log_uniform = LogUniform::new(0.1, 10.0).unwrap();
+            assert::close(
+                log_uniform.expected_shortfall(p),
+                numeric(&log_uniform, p),
+                TOL,
+            );
+            let gamma = Gamma::new(2.5, 1.5).unwrap();
+            assert::close(
+                gamma.expected_shortfall(p),
+                numeric(&gamma, p),
+                1E-7,
+            );
+            let weibull = Weibull::new(1.5, 2.0).unwrap();
+            assert::close(
+                weibull.expected_shortfall(p),
+                numeric(&weibull, p),
+                TOL,
+            );
+            // Heavy tails converge slowly, so the quadrature is looser
+            let pareto = Pareto::new(4.0, 1.0).unwrap();
+            assert::close(
+                pareto.expected_shortfall(p),
+                numeric(&pareto, p),
+                1E-6,
+            );
+            let lomax = Lomax::new(4.0, 2.0).unwrap();
+            assert::close(
+                lomax.expected_shortfall(p),
+                numeric(&lomax, p),
+                1E-6,
+            );
+        }
+    }
+
+    #[test]
+    fn expected_shortfall_at_least_value_at_risk() {
+        let beta = Beta::new(2.0, 5.0).unwrap();
+        let p = 0.9;
+        let es = beta.expected_shortfall(p);
+        assert!(es > beta.value_at_risk(p));
+        assert!(es < 1.0);
+
+        // No mean, no expected shortfall
+        let pareto = Pareto::new(1.0, 1.0).unwrap();
+        assert_eq!(pareto.expected_shortfall(0.9), f64::INFINITY);
+        assert_eq!(Cauchy::default().expected_shortfall(0.9), f64::INFINITY);
+    }
+
+    #[test]
+    fn compound_poisson_tail_measures() {
+        // Every severity rounds to 1, so the sum is Poisson(2)
+        let severity = Uniform::new(0.6, 1.4).unwrap();
+        let cp = CompoundPoisson::new(2.0, severity, 1.0, 40).unwrap();
+        let poisson = crate::dist::Poisson::new(2.0).unwrap();
+        let p = 0.9;
+        // P(N ≤ 3) = 0.857 and P(N ≤ 4) = 0.947
+        assert_eq!(cp.value_at_risk(p), 4.0);
+
+        let tail_mean: f64 =
+            (5..40_u32).map(|k| f64::from(k) * poisson.f(&k)).sum();
+        let atom = poisson.cdf(&4_u32) - p;
+        let expected = atom.mul_add(4.0, tail_mean) / (1.0 - p);
+        assert::close(cp.expected_shortfall(p), expected, 1E-12);
+    }
+}
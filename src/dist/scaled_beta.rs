@@ -0,0 +1,391 @@
+//! Four-parameter Beta distribution over x in (a, b)
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::dist::{Beta, BetaError};
+use crate::impl_display;
+use crate::traits::*;
+use rand::Rng;
+use std::f64;
+use std::fmt;
+
+/// [Four-parameter Beta distribution](https://en.wikipedia.org/wiki/Beta_distribution#Four_parameters),
+/// Beta(α, β) scaled and shifted to x in (a, b).
+///
+/// If y ~ Beta(α, β) then x = a + (b - a) y ~ ScaledBeta(α, β, a, b). Bounded
+/// quantities, such as durations between a known minimum and maximum, rarely
+/// live on (0, 1) exactly.
+///
+/// ```math
+///                     1     / x - a \ α-1 / b - x \ β-1
+/// f(x|α, β, a, b) = ------- | ----- |     | ----- |
+///                   (b-a)B  \ b - a /     \ b - a /
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{Beta, ScaledBeta};
+/// use rv::traits::*;
+///
+/// let beta = Beta::new(2.0, 5.0).unwrap();
+/// let sbeta = ScaledBeta::new(2.0, 5.0, 10.0, 30.0).unwrap();
+///
+/// // The density picks up the Jacobian of the transform, 1 / (b - a)
+/// let f = sbeta.pdf(&15.0_f64);
+/// assert!((f - beta.pdf(&0.25_f64) / 20.0).abs() < 1E-12);
+///
+/// let median: f64 = sbeta.invcdf(0.5);
+/// assert!((sbeta.cdf(&median) - 0.5).abs() < 1E-10);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct ScaledBeta {
+    beta: Beta,
+    a: f64,
+    b: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum ScaledBetaError {
+    /// The shape parameters, α or β, are invalid
+    Shape(BetaError),
+    /// A >= B
+    InvalidInterval { a: f64, b: f64 },
+    /// A was infinite or NaN
+    ANotFinite { a: f64 },
+    /// B was infinite or NaN
+    BNotFinite { b: f64 },
+}
+
+impl ScaledBeta {
+    /// Create a Beta(α, β) over (a, b)
+    pub fn new(
+        alpha: f64,
+        beta: f64,
+        a: f64,
+        b: f64,
+    ) -> Result<Self, ScaledBetaError> {
+        let beta = Beta::new(alpha, beta).map_err(ScaledBetaError::Shape)?;
+        Self::from_beta(beta, a, b)
+    }
+
+    /// Scale and shift `beta` from (0, 1) to (a, b)
+    pub fn from_beta(
+        beta: Beta,
+        a: f64,
+        b: f64,
+    ) -> Result<Self, ScaledBetaError> {
+        if !a.is_finite() {
+            Err(ScaledBetaError::ANotFinite { a })
+        } else if !b.is_finite() {
+            Err(ScaledBetaError::BNotFinite { b })
+        } else if a >= b {
+            Err(ScaledBetaError::InvalidInterval { a, b })
+        } else {
+            Ok(ScaledBeta { beta, a, b })
+        }
+    }
+
+    /// Creates a new ScaledBeta without checking whether the parameters are
+    /// valid.
+    #[inline]
+    pub fn new_unchecked(alpha: f64, beta: f64, a: f64, b: f64) -> Self {
+        ScaledBeta {
+            beta: Beta::new_unchecked(alpha, beta),
+            a,
+            b,
+        }
+    }
+
+    /// The Beta distribution on (0, 1) before scaling
+    #[inline]
+    pub fn beta_dist(&self) -> &Beta {
+        &self.beta
+    }
+
+    /// Get the first shape parameter, α
+    #[inline]
+    pub fn alpha(&self) -> f64 {
+        self.beta.alpha()
+    }
+
+    /// Get the second shape parameter, β
+    #[inline]
+    pub fn beta(&self) -> f64 {
+        self.beta.beta()
+    }
+
+    /// Get the lower bound, a
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rv::dist::ScaledBeta;
+    /// let sbeta = ScaledBeta::new(2.0, 3.0, -1.0, 4.0).unwrap();
+    /// assert_eq!(sbeta.a(), -1.0);
+    /// ```
+    #[inline]
+    pub fn a(&self) -> f64 {
+        self.a
+    }
+
+    /// Get the upper bound, b
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rv::dist::ScaledBeta;
+    /// let sbeta = ScaledBeta::new(2.0, 3.0, -1.0, 4.0).unwrap();
+    /// assert_eq!(sbeta.b(), 4.0);
+    /// ```
+    #[inline]
+    pub fn b(&self) -> f64 {
+        self.b
+    }
+
+    #[inline]
+    fn width(&self) -> f64 {
+        self.b - self.a
+    }
+
+    /// Map x in (a, b) to (0, 1)
+    #[inline]
+    fn to_unit(&self, x: f64) -> f64 {
+        (x - self.a) / self.width()
+    }
+
+    /// Map y in (0, 1) to (a, b)
+    #[inline]
+    fn to_scaled(&self, y: f64) -> f64 {
+        y.mul_add(self.width(), self.a)
+    }
+}
+
+impl From<&ScaledBeta> for String {
+    fn from(sbeta: &ScaledBeta) -> String {
+        format!(
+            "ScaledBeta(α: {}, β: {}, a: {}, b: {})",
+            sbeta.alpha(),
+            sbeta.beta(),
+            sbeta.a,
+            sbeta.b
+        )
+    }
+}
+
+impl_display!(ScaledBeta);
+
+macro_rules! impl_traits {
+    ($kind:ty) => {
+        impl Rv<$kind> for ScaledBeta {
+            fn ln_f(&self, x: &$kind) -> f64 {
+                let xf = f64::from(*x);
+                if self.a < xf && xf < self.b {
+                    let y = self.to_unit(xf);
+                    self.beta.ln_f(&y) - self.width().ln()
+                } else {
+                    f64::NEG_INFINITY
+                }
+            }
+
+            fn draw<R: Rng>(&self, rng: &mut R) -> $kind {
+                let y: f64 = self.beta.draw(rng);
+                self.to_scaled(y) as $kind
+            }
+
+            fn sample<R: Rng>(&self, n: usize, rng: &mut R) -> Vec<$kind> {
+                let ys: Vec<f64> = self.beta.sample(n, rng);
+                ys.iter().map(|&y| self.to_scaled(y) as $kind).collect()
+            }
+        }
+
+        impl Support<$kind> for ScaledBeta {
+            fn supports(&self, x: &$kind) -> bool {
+                let xf = f64::from(*x);
+                self.a < xf && xf < self.b
+            }
+        }
+
+        impl ContinuousDistr<$kind> for ScaledBeta {}
+
+        impl Cdf<$kind> for ScaledBeta {
+            fn cdf(&self, x: &$kind) -> f64 {
+                let xf = f64::from(*x);
+                if xf <= self.a {
+                    0.0
+                } else if xf >= self.b {
+                    1.0
+                } else {
+                    self.beta.cdf(&self.to_unit(xf))
+                }
+            }
+        }
+
+        impl InverseCdf<$kind> for ScaledBeta {
+            fn invcdf(&self, p: f64) -> $kind {
+                let y: f64 = self.beta.invcdf(p);
+                self.to_scaled(y) as $kind
+            }
+        }
+
+        impl Mean<$kind> for ScaledBeta {
+            fn mean(&self) -> Option<$kind> {
+                let y: f64 = self.beta.mean()?;
+                Some(self.to_scaled(y) as $kind)
+            }
+        }
+
+        impl Mode<$kind> for ScaledBeta {
+            fn mode(&self) -> Option<$kind> {
+                let y: f64 = self.beta.mode()?;
+                Some(self.to_scaled(y) as $kind)
+            }
+        }
+    };
+}
+
+impl Variance<f64> for ScaledBeta {
+    fn variance(&self) -> Option<f64> {
+        let w = self.width();
+        self.beta.variance().map(|v| v * w * w)
+    }
+}
+
+impl Entropy for ScaledBeta {
+    fn entropy(&self) -> f64 {
+        self.beta.entropy() + self.width().ln()
+    }
+}
+
+impl Skewness for ScaledBeta {
+    fn skewness(&self) -> Option<f64> {
+        self.beta.skewness()
+    }
+}
+
+impl Kurtosis for ScaledBeta {
+    fn kurtosis(&self) -> Option<f64> {
+        self.beta.kurtosis()
+    }
+}
+
+impl_traits!(f32);
+impl_traits!(f64);
+
+impl CacheControl for ScaledBeta {
+    fn precompute(&self) {
+        self.beta.precompute();
+    }
+
+    fn invalidate(&mut self) {
+        self.beta.invalidate();
+    }
+}
+
+impl std::error::Error for ScaledBetaError {}
+
+impl fmt::Display for ScaledBetaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Shape(err) => write!(f, "invalid shape: {}", err),
+            Self::InvalidInterval { a, b } => {
+                write!(f, "invalid interval: (a, b) = ({}, {})", a, b)
+            }
+            Self::ANotFinite { a } => write!(f, "non-finite a: {}", a),
+            Self::BNotFinite { b } => write!(f, "non-finite b: {}", b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::misc::ks_test;
+    use crate::test_basic_impls;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    test_basic_impls!([continuous] ScaledBeta::new(2.0, 3.0, 0.0, 2.0).unwrap());
+
+    #[test]
+    fn new() {
+        assert!(ScaledBeta::new(2.0, 3.0, -1.0, 1.0).is_ok());
+        assert!(matches!(
+            ScaledBeta::new(0.0, 3.0, -1.0, 1.0),
+            Err(ScaledBetaError::Shape(_))
+        ));
+        assert!(ScaledBeta::new(2.0, 3.0, 1.0, 1.0).is_err());
+        assert!(ScaledBeta::new(2.0, 3.0, 1.0, -1.0).is_err());
+        assert!(ScaledBeta::new(2.0, 3.0, f64::NAN, 1.0).is_err());
+        assert!(ScaledBeta::new(2.0, 3.0, 0.0, f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn unit_interval_is_beta() {
+        let beta = Beta::new(1.5, 4.0).unwrap();
+        let sbeta = ScaledBeta::from_beta(beta.clone(), 0.0, 1.0).unwrap();
+        for x in [0.01, 0.3, 0.5, 0.99_f64] {
+            assert::close(sbeta.ln_f(&x), beta.ln_f(&x), TOL);
+            assert::close(sbeta.cdf(&x), beta.cdf(&x), TOL);
+        }
+        assert::close(sbeta.entropy(), beta.entropy(), TOL);
+    }
+
+    #[test]
+    fn pdf_integrates_to_one() {
+        let sbeta = ScaledBeta::new(2.5, 1.5, -3.0, 5.0).unwrap();
+        let n = 100_000;
+        let h = 8.0 / n as f64;
+        let total: f64 = (0..n)
+            .map(|i| sbeta.pdf(&(i as f64 + 0.5).mul_add(h, -3.0)) * h)
+            .sum();
+        assert::close(total, 1.0, 1E-6);
+        assert_eq!(sbeta.ln_f(&-3.5_f64), f64::NEG_INFINITY);
+        assert_eq!(sbeta.cdf(&-3.5_f64), 0.0);
+        assert_eq!(sbeta.cdf(&5.5_f64), 1.0);
+    }
+
+    #[test]
+    fn invcdf_inverts_cdf() {
+        let sbeta = ScaledBeta::new(0.8, 2.2, 100.0, 250.0).unwrap();
+        for p in [1E-6, 0.1, 0.5, 0.9, 0.999] {
+            let x: f64 = sbeta.invcdf(p);
+            assert!(sbeta.supports(&x));
+            assert::close(sbeta.cdf(&x), p, 1E-10);
+        }
+    }
+
+    #[test]
+    fn moments_scale() {
+        let beta = Beta::new(2.0, 5.0).unwrap();
+        let sbeta = ScaledBeta::from_beta(beta.clone(), 10.0, 30.0).unwrap();
+        let m: f64 = sbeta.mean().unwrap();
+        let bm: f64 = beta.mean().unwrap();
+        assert::close(m, 20.0_f64.mul_add(bm, 10.0), TOL);
+        let mode: f64 = sbeta.mode().unwrap();
+        assert::close(mode, 14.0, TOL);
+        assert::close(
+            sbeta.variance().unwrap(),
+            400.0 * beta.variance().unwrap(),
+            TOL,
+        );
+        assert_eq!(sbeta.skewness(), beta.skewness());
+        assert_eq!(sbeta.kurtosis(), beta.kurtosis());
+    }
+
+    #[test]
+    fn draw_test() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x5BE7A);
+        let sbeta = ScaledBeta::new(2.0, 3.0, -2.0, 6.0).unwrap();
+        let xs: Vec<f64> = sbeta.sample(1000, &mut rng);
+        assert!(xs.iter().all(|x| sbeta.supports(x)));
+        let (_, p) = ks_test(&xs, |x| sbeta.cdf(&x));
+        assert!(p > 0.01);
+    }
+}
@@ -320,6 +320,18 @@ impl Kurtosis for ScaledInvChiSquared {
 impl_traits!(f64);
 impl_traits!(f32);
 
+impl CacheControl for ScaledInvChiSquared {
+    fn precompute(&self) {
+        self.ln_gamma_v_2();
+        self.ln_f_const();
+    }
+
+    fn invalidate(&mut self) {
+        self.ln_gamma_v_2 = OnceLock::new();
+        self.ln_f_const = OnceLock::new();
+    }
+}
+
 impl std::error::Error for ScaledInvChiSquaredError {}
 
 impl fmt::Display for ScaledInvChiSquaredError {
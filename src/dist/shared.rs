@@ -0,0 +1,370 @@
+//! Cheaply cloneable handles for evaluating one distribution from many threads
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::traits::*;
+use rand::Rng;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A shared, read-only handle to a distribution.
+///
+/// Every distribution in rv is `Send + Sync`, so a fitted model can be
+/// evaluated from many threads at once. Cloning a `SharedRv` copies a
+/// pointer rather than the distribution, so each worker of a thread pool can
+/// own a handle without copying large parameters such as covariance matrices.
+///
+/// Lazily computed quantities are stored in `OnceLock`s. Once a cache is
+/// filled, reading it does not lock, but threads that need a cache while
+/// another thread is filling it wait for the result.
+/// [`SharedRv::precomputed`] fills the caches up front so that readers never
+/// wait.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{Gaussian, SharedRv};
+/// use rv::traits::*;
+///
+/// let gauss = SharedRv::precomputed(Gaussian::new(1.0, 2.0).unwrap());
+///
+/// let ln_fs: Vec<f64> = std::thread::scope(|s| {
+///     let handles: Vec<_> = (0..4)
+///         .map(|i| {
+///             let gauss = gauss.clone();
+///             s.spawn(move || gauss.ln_f(&(i as f64)))
+///         })
+///         .collect();
+///     handles.into_iter().map(|h| h.join().unwrap()).collect()
+/// });
+///
+/// assert_eq!(ln_fs[2], gauss.ln_f(&2.0_f64));
+/// ```
+pub struct SharedRv<Fx>(Arc<Fx>);
+
+impl<Fx> SharedRv<Fx> {
+    /// Share `fx`
+    pub fn new(fx: Fx) -> Self {
+        SharedRv(Arc::new(fx))
+    }
+
+    /// The shared distribution
+    #[inline]
+    pub fn inner(&self) -> &Fx {
+        &self.0
+    }
+
+    /// The number of handles to the distribution
+    #[inline]
+    pub fn handle_count(&self) -> usize {
+        Arc::strong_count(&self.0)
+    }
+
+    /// Take the distribution back if this is the only handle to it, otherwise
+    /// return the handle.
+    pub fn try_unwrap(self) -> Result<Fx, Self> {
+        Arc::try_unwrap(self.0).map_err(SharedRv)
+    }
+}
+
+impl<Fx: CacheControl> SharedRv<Fx> {
+    /// Share `fx` after filling its caches, so that no reader waits on
+    /// another to compute them
+    pub fn precomputed(fx: Fx) -> Self {
+        fx.precompute();
+        Self::new(fx)
+    }
+}
+
+impl<Fx> From<Arc<Fx>> for SharedRv<Fx> {
+    fn from(fx: Arc<Fx>) -> Self {
+        SharedRv(fx)
+    }
+}
+
+impl<Fx> Clone for SharedRv<Fx> {
+    fn clone(&self) -> Self {
+        SharedRv(Arc::clone(&self.0))
+    }
+}
+
+impl<Fx> Deref for SharedRv<Fx> {
+    type Target = Fx;
+
+    fn deref(&self) -> &Fx {
+        &self.0
+    }
+}
+
+impl<Fx> AsRef<Fx> for SharedRv<Fx> {
+    fn as_ref(&self) -> &Fx {
+        &self.0
+    }
+}
+
+impl<Fx: fmt::Debug> fmt::Debug for SharedRv<Fx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SharedRv").field(&self.0).finish()
+    }
+}
+
+impl<Fx: fmt::Display> fmt::Display for SharedRv<Fx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<Fx: PartialEq> PartialEq for SharedRv<Fx> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.0 == other.0
+    }
+}
+
+// Serialized as the distribution itself. Deserializing creates a new,
+// unshared, distribution.
+#[cfg(feature = "serde1")]
+impl<Fx: Serialize> Serialize for SharedRv<Fx> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde1")]
+impl<'de, Fx: Deserialize<'de>> Deserialize<'de> for SharedRv<Fx> {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        Fx::deserialize(deserializer).map(SharedRv::new)
+    }
+}
+
+impl<X, Fx: Rv<X>> Rv<X> for SharedRv<Fx> {
+    fn f(&self, x: &X) -> f64 {
+        self.0.f(x)
+    }
+
+    fn ln_f(&self, x: &X) -> f64 {
+        self.0.ln_f(x)
+    }
+
+    fn ln_f_many(&self, xs: &[X], out: &mut [f64]) {
+        self.0.ln_f_many(xs, out)
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> X {
+        self.0.draw(rng)
+    }
+
+    fn sample<R: Rng>(&self, n: usize, rng: &mut R) -> Vec<X> {
+        self.0.sample(n, rng)
+    }
+}
+
+impl<X, Fx: Support<X>> Support<X> for SharedRv<Fx> {
+    fn supports(&self, x: &X) -> bool {
+        self.0.supports(x)
+    }
+}
+
+impl<X, Fx: ContinuousDistr<X>> ContinuousDistr<X> for SharedRv<Fx> {
+    fn pdf(&self, x: &X) -> f64 {
+        self.0.pdf(x)
+    }
+
+    fn ln_pdf(&self, x: &X) -> f64 {
+        self.0.ln_pdf(x)
+    }
+}
+
+impl<X, Fx: DiscreteDistr<X>> DiscreteDistr<X> for SharedRv<Fx> {
+    fn pmf(&self, x: &X) -> f64 {
+        self.0.pmf(x)
+    }
+
+    fn ln_pmf(&self, x: &X) -> f64 {
+        self.0.ln_pmf(x)
+    }
+}
+
+impl<X, Fx: Cdf<X>> Cdf<X> for SharedRv<Fx> {
+    fn cdf(&self, x: &X) -> f64 {
+        self.0.cdf(x)
+    }
+
+    fn sf(&self, x: &X) -> f64 {
+        self.0.sf(x)
+    }
+}
+
+impl<X, Fx: InverseCdf<X>> InverseCdf<X> for SharedRv<Fx> {
+    fn invcdf(&self, p: f64) -> X {
+        self.0.invcdf(p)
+    }
+}
+
+impl<X, Fx: Mean<X>> Mean<X> for SharedRv<Fx> {
+    fn mean(&self) -> Option<X> {
+        self.0.mean()
+    }
+}
+
+impl<X, Fx: Median<X>> Median<X> for SharedRv<Fx> {
+    fn median(&self) -> Option<X> {
+        self.0.median()
+    }
+}
+
+impl<X, Fx: Mode<X>> Mode<X> for SharedRv<Fx> {
+    fn mode(&self) -> Option<X> {
+        self.0.mode()
+    }
+}
+
+impl<X, Fx: Variance<X>> Variance<X> for SharedRv<Fx> {
+    fn variance(&self) -> Option<X> {
+        self.0.variance()
+    }
+}
+
+impl<Fx: Entropy> Entropy for SharedRv<Fx> {
+    fn entropy(&self) -> f64 {
+        self.0.entropy()
+    }
+}
+
+impl<Fx: Skewness> Skewness for SharedRv<Fx> {
+    fn skewness(&self) -> Option<f64> {
+        self.0.skewness()
+    }
+}
+
+impl<Fx: Kurtosis> Kurtosis for SharedRv<Fx> {
+    fn kurtosis(&self) -> Option<f64> {
+        self.0.kurtosis()
+    }
+}
+
+impl<Fx: KlDivergence> KlDivergence for SharedRv<Fx> {
+    fn kl(&self, other: &Self) -> f64 {
+        self.0.kl(&other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn distributions_are_send_and_sync() {
+        assert_send_sync::<Bernoulli>();
+        assert_send_sync::<Beta>();
+        assert_send_sync::<BetaBinomial>();
+        assert_send_sync::<Binomial>();
+        assert_send_sync::<BivariateGaussian>();
+        assert_send_sync::<Categorical>();
+        assert_send_sync::<Cauchy>();
+        assert_send_sync::<ChiSquared>();
+        assert_send_sync::<Crp>();
+        assert_send_sync::<Dirichlet>();
+        assert_send_sync::<SymmetricDirichlet>();
+        assert_send_sync::<DiscreteUniform<u32>>();
+        assert_send_sync::<Dpd>();
+        assert_send_sync::<Empirical>();
+        assert_send_sync::<Exponential>();
+        assert_send_sync::<Gamma>();
+        assert_send_sync::<GammaRatePrior>();
+        assert_send_sync::<Gaussian>();
+        assert_send_sync::<Geometric>();
+        assert_send_sync::<Gev>();
+        assert_send_sync::<InvChiSquared>();
+        assert_send_sync::<InvGamma>();
+        assert_send_sync::<InvGaussian>();
+        assert_send_sync::<JointCategorical>();
+        assert_send_sync::<Kde>();
+        assert_send_sync::<KsTwoAsymptotic>();
+        assert_send_sync::<Kumaraswamy>();
+        assert_send_sync::<Laplace>();
+        assert_send_sync::<LogNormal>();
+        assert_send_sync::<LogUniform>();
+        assert_send_sync::<LogitNormal>();
+        assert_send_sync::<Lomax>();
+        assert_send_sync::<Mixture<Gaussian>>();
+        assert_send_sync::<NegBinomial>();
+        assert_send_sync::<NormalGamma>();
+        assert_send_sync::<NormalInvChiSquared>();
+        assert_send_sync::<NormalInvGamma>();
+        assert_send_sync::<Pareto>();
+        assert_send_sync::<ParetoShapePrior>();
+        assert_send_sync::<Poisson>();
+        assert_send_sync::<ScaledBeta>();
+        assert_send_sync::<ScaledInvChiSquared>();
+        assert_send_sync::<Skellam>();
+        assert_send_sync::<StickBreaking>();
+        assert_send_sync::<StudentsT>();
+        assert_send_sync::<Uniform>();
+        assert_send_sync::<VonMises>();
+        assert_send_sync::<Weibull>();
+        assert_send_sync::<ZeroInflated<Poisson>>();
+        assert_send_sync::<SharedRv<Gaussian>>();
+    }
+
+    #[cfg(feature = "arraydist")]
+    #[test]
+    fn array_distributions_are_send_and_sync() {
+        assert_send_sync::<MvGaussian>();
+        assert_send_sync::<MvGaussianDiag>();
+        assert_send_sync::<NormalInvWishart>();
+        assert_send_sync::<InvWishart>();
+    }
+
+    #[cfg(feature = "datum")]
+    #[test]
+    fn datum_distributions_are_send_and_sync() {
+        assert_send_sync::<Distribution>();
+        assert_send_sync::<ProductDistribution>();
+    }
+
+    #[test]
+    fn clones_share_the_distribution() {
+        let gauss = SharedRv::new(Gaussian::new(0.5, 1.5).unwrap());
+        let other = gauss.clone();
+        assert_eq!(gauss.handle_count(), 2);
+        assert!(std::ptr::eq(gauss.inner(), other.inner()));
+
+        assert!(gauss.try_unwrap().is_err());
+        assert_eq!(other.try_unwrap(), Ok(Gaussian::new(0.5, 1.5).unwrap()));
+    }
+
+    #[test]
+    fn concurrent_evaluation_matches_serial() {
+        // Evaluated by a distinct Skellam so the shared cache starts empty
+        let serial = Skellam::new(3.0, 2.0).unwrap();
+        let skellam = SharedRv::new(serial.clone());
+        let xs: Vec<i32> = (-10..=10).collect();
+        let expected: Vec<f64> = xs.iter().map(|x| serial.ln_f(x)).collect();
+
+        let results: Vec<Vec<f64>> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    let skellam = skellam.clone();
+                    let xs = &xs;
+                    s.spawn(move || {
+                        xs.iter().map(|x| skellam.ln_f(x)).collect()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        results
+            .iter()
+            .for_each(|ln_fs| assert_eq!(ln_fs, &expected));
+    }
+}
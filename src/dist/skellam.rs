@@ -8,7 +8,8 @@ use crate::misc::bessel::bessel_iv;
 use crate::traits::*;
 use lru::LruCache;
 use rand::Rng;
-use std::{cell::RefCell, num::NonZeroUsize};
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, MutexGuard};
 
 /// [Skellam distribution](https://en.wikipedia.org/wiki/Skellam_distribution)
 /// over x in {.., -2, -1, 0, 1, ... }.
@@ -34,15 +35,15 @@ pub struct Skellam {
     mu_1: f64,
     /// Mean of second poisson
     mu_2: f64,
-    /// Cached values of bessel_iv. Note that the cache is not invalidated when
-    /// the values of mu_1 or mu_2 change.
+    /// Cached values of ln(bessel_iv). Cleared when mu_1 or mu_2 change.
+    /// Behind a `Mutex` so that a `Skellam` may be shared between threads.
     #[cfg_attr(feature = "serde1", serde(skip, default = "cache_default"))]
-    bessel_iv_cache: RefCell<LruCache<i32, f64>>,
+    bessel_iv_cache: Mutex<LruCache<i32, f64>>,
 }
 
-fn cache_default() -> RefCell<LruCache<i32, f64>> {
+fn cache_default() -> Mutex<LruCache<i32, f64>> {
     // SAFETY: 100 is a valid usize.
-    RefCell::new(LruCache::new(unsafe { NonZeroUsize::new_unchecked(100) }))
+    Mutex::new(LruCache::new(unsafe { NonZeroUsize::new_unchecked(100) }))
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -140,6 +141,7 @@ impl Skellam {
     #[inline]
     pub fn set_mu_1_unchecked(&mut self, mu_1: f64) {
         self.mu_1 = mu_1;
+        self.cache_mut().clear();
     }
 
     /// Get the mu_2 parameter
@@ -197,6 +199,7 @@ impl Skellam {
     #[inline]
     pub fn set_mu_2_unchecked(&mut self, mu_2: f64) {
         self.mu_2 = mu_2;
+        self.cache_mut().clear();
     }
 
     /// Set the cache size on the internal LRU for Bessel Iv calls.
@@ -206,9 +209,21 @@ impl Skellam {
     /// Panics if `cap` is 0.
     #[inline]
     pub fn set_cache_cap(&self, cap: usize) {
+        self.cache().resize(NonZeroUsize::new(cap).unwrap());
+    }
+
+    // The cache only holds values computed from the parameters, so it is
+    // still valid if another thread panicked while holding the lock.
+    fn cache(&self) -> MutexGuard<'_, LruCache<i32, f64>> {
+        self.bessel_iv_cache
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn cache_mut(&mut self) -> &mut LruCache<i32, f64> {
         self.bessel_iv_cache
-            .borrow_mut()
-            .resize(NonZeroUsize::new(cap).unwrap());
+            .get_mut()
+            .unwrap_or_else(|err| err.into_inner())
     }
 }
 
@@ -225,16 +240,15 @@ macro_rules! impl_traits {
         impl Rv<$kind> for Skellam {
             fn ln_f(&self, x: &$kind) -> f64 {
                 let kf = f64::from(*x);
-                let mut cache = self.bessel_iv_cache.borrow_mut();
-                let bf: f64 =
-                    cache.get(&(*x as i32)).map(|b| *b).unwrap_or_else(|| {
-                        let b =
-                            bessel_iv(kf, 2.0 * (self.mu_1 * self.mu_2).sqrt())
-                                .unwrap()
-                                .ln();
-                        cache.put((*x as i32), b);
-                        b
-                    });
+                // Do not hold the lock while computing the Bessel function
+                let cached = self.cache().get(&(*x as i32)).copied();
+                let bf: f64 = cached.unwrap_or_else(|| {
+                    let b = bessel_iv(kf, 2.0 * (self.mu_1 * self.mu_2).sqrt())
+                        .unwrap()
+                        .ln();
+                    self.cache().put((*x as i32), b);
+                    b
+                });
 
                 -(self.mu_1 + self.mu_2)
                     + (kf / 2.0).mul_add((self.mu_1 / self.mu_2).ln(), bf)
@@ -245,7 +259,7 @@ macro_rules! impl_traits {
                 let pois_2 = Poisson::new_unchecked(self.mu_2);
                 let x_1: u32 = pois_1.draw(rng);
                 let x_2: u32 = pois_2.draw(rng);
-                (x_1 - x_2) as $kind
+                (x_1 as $kind) - (x_2 as $kind)
             }
 
             fn sample<R: Rng>(&self, n: usize, rng: &mut R) -> Vec<$kind> {
@@ -309,7 +323,7 @@ impl PartialEq for Skellam {
 
 impl Clone for Skellam {
     fn clone(&self) -> Self {
-        let old_cache = self.bessel_iv_cache.borrow();
+        let old_cache = self.cache();
         let mut cache = LruCache::new(old_cache.cap());
         for (key, value) in old_cache.iter() {
             cache.put(*key, *value);
@@ -317,7 +331,7 @@ impl Clone for Skellam {
         Skellam {
             mu_1: self.mu_1,
             mu_2: self.mu_2,
-            bessel_iv_cache: RefCell::new(cache),
+            bessel_iv_cache: Mutex::new(cache),
         }
     }
 }
@@ -377,6 +391,41 @@ mod tests {
         assert::close(skel.ln_pmf(&-11_i32), -6.094_413_746_413_306, TOL);
     }
 
+    #[test]
+    fn pmf_is_difference_of_poissons() {
+        let skel = Skellam::new(2.5, 1.2).unwrap();
+        let pois_1 = Poisson::new(2.5).unwrap();
+        let pois_2 = Poisson::new(1.2).unwrap();
+        for k in -6_i32..=8 {
+            let f: f64 = (0_u32..60)
+                .filter_map(|x_2| {
+                    let x_1 = i64::from(x_2) + i64::from(k);
+                    (x_1 >= 0)
+                        .then(|| pois_1.pmf(&(x_1 as u32)) * pois_2.pmf(&x_2))
+                })
+                .sum();
+            assert::close(skel.pmf(&k), f, TOL);
+        }
+    }
+
+    #[test]
+    fn setters_clear_cache() {
+        let mut skel = Skellam::new(5.3, 6.5).unwrap();
+        let _ = skel.ln_pmf(&1_i32);
+        skel.set_mu_1(2.5).unwrap();
+        skel.set_mu_2(1.2).unwrap();
+        let fresh = Skellam::new(2.5, 1.2).unwrap();
+        assert::close(skel.ln_pmf(&1_i32), fresh.ln_pmf(&1_i32), TOL);
+    }
+
+    #[test]
+    fn draws_can_be_negative() {
+        let mut rng = rand::thread_rng();
+        let skel = Skellam::new(0.5, 8.0).unwrap();
+        let xs: Vec<i32> = (0..100).map(|_| skel.draw(&mut rng)).collect();
+        assert!(xs.iter().any(|&x| x < 0));
+    }
+
     #[test]
     fn mean() {
         let m1 = Skellam::new(1.5, 2.3).unwrap().mean().unwrap();
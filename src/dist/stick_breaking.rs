@@ -0,0 +1,433 @@
+//! Stick-breaking construction of the Dirichlet process
+//!
+//! The weights of a Dirichlet process DP(α) are generated by repeatedly
+//! breaking off a Beta(1, α) fraction of the remaining stick. After observing
+//! data, the fraction broken off at stick `i` has a Beta(1 + nᵢ, α + n₍>ᵢ₎)
+//! posterior, where nᵢ counts the observations of atom `i` and n₍>ᵢ₎ counts
+//! observations of later atoms.
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::{
+    extract_stat_then, CategoricalDatum, CategoricalSuffStat, DataOrSuffStat,
+};
+use crate::dist::{Beta, Dpd};
+use crate::impl_display;
+use crate::misc::vec_to_string;
+use crate::traits::*;
+use rand::Rng;
+use special::Gamma as _;
+use std::fmt;
+
+type DpdData<'a, X> = DataOrSuffStat<'a, X, Dpd>;
+
+/// Stick-breaking process, a distribution over [`Dpd`]s
+///
+/// The first breaks may have their own Beta parameters, which is how
+/// posteriors are represented. All later breaks are Beta(1, α). Draws break
+/// the stick `truncation` times, or once per explicit break if there are more,
+/// and put the remaining mass on the `Dpd`'s new atom.
+///
+/// # Example
+///
+/// ```
+/// use rv::data::DataOrSuffStat;
+/// use rv::dist::{Dpd, StickBreaking};
+/// use rv::traits::*;
+///
+/// let mut rng = rand::thread_rng();
+///
+/// let sb = StickBreaking::new(1.5, 20).unwrap();
+/// let dpd: Dpd = sb.draw(&mut rng);
+/// assert_eq!(dpd.k(), 20);
+///
+/// // Condition on data. Atom 0 becomes the most likely next observation.
+/// let xs: Vec<usize> = vec![0, 0, 0, 1, 0, 2];
+/// let data: DataOrSuffStat<usize, Dpd> = DataOrSuffStat::Data(&xs);
+/// let post = sb.posterior(&data);
+/// assert_eq!(post.breaks().len(), 3);
+/// assert!(sb.ln_pp(&0, &data) > sb.ln_pp(&1, &data));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct StickBreaking {
+    /// Concentration parameter
+    alpha: f64,
+    /// Distributions of the first breaks
+    breaks: Vec<Beta>,
+    /// Minimum number of breaks made when drawing
+    truncation: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum StickBreakingError {
+    /// alpha parameter is less than or equal to zero
+    AlphaTooLow { alpha: f64 },
+    /// alpha parameter is infinite or NaN
+    AlphaNotFinite { alpha: f64 },
+    /// truncation parameter is zero
+    TruncationIsZero,
+}
+
+/// ln B(a, b)
+#[inline]
+fn ln_beta_fn(a: f64, b: f64) -> f64 {
+    a.ln_gamma().0 + b.ln_gamma().0 - (a + b).ln_gamma().0
+}
+
+impl StickBreaking {
+    /// Create a new stick-breaking process
+    ///
+    /// # Arguments
+    /// - alpha: Concentration parameter in (0, ∞)
+    /// - truncation: Number of sticks broken when drawing
+    pub fn new(
+        alpha: f64,
+        truncation: usize,
+    ) -> Result<Self, StickBreakingError> {
+        if alpha <= 0.0 {
+            Err(StickBreakingError::AlphaTooLow { alpha })
+        } else if !alpha.is_finite() {
+            Err(StickBreakingError::AlphaNotFinite { alpha })
+        } else if truncation == 0 {
+            Err(StickBreakingError::TruncationIsZero)
+        } else {
+            Ok(Self::new_unchecked(alpha, truncation))
+        }
+    }
+
+    /// Create a new stick-breaking process without checking whether the
+    /// parameters are valid
+    #[inline]
+    pub fn new_unchecked(alpha: f64, truncation: usize) -> Self {
+        StickBreaking {
+            alpha,
+            breaks: Vec::new(),
+            truncation,
+        }
+    }
+
+    /// Get the concentration parameter
+    #[inline]
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// Get the minimum number of sticks broken when drawing
+    #[inline]
+    pub fn truncation(&self) -> usize {
+        self.truncation
+    }
+
+    /// Set the minimum number of sticks broken when drawing
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rv::dist::{Dpd, StickBreaking};
+    /// # use rv::traits::*;
+    /// let mut sb = StickBreaking::new(1.0, 10).unwrap();
+    /// sb.set_truncation(4).unwrap();
+    ///
+    /// let dpd: Dpd = sb.draw(&mut rand::thread_rng());
+    /// assert_eq!(dpd.k(), 4);
+    ///
+    /// assert!(sb.set_truncation(0).is_err());
+    /// ```
+    #[inline]
+    pub fn set_truncation(
+        &mut self,
+        truncation: usize,
+    ) -> Result<(), StickBreakingError> {
+        if truncation == 0 {
+            Err(StickBreakingError::TruncationIsZero)
+        } else {
+            self.truncation = truncation;
+            Ok(())
+        }
+    }
+
+    /// Distributions of the breaks that differ from the Beta(1, α) prior
+    #[inline]
+    pub fn breaks(&self) -> &[Beta] {
+        &self.breaks
+    }
+
+    /// Beta parameters of break `i`
+    #[inline]
+    fn break_params(&self, i: usize) -> (f64, f64) {
+        self.breaks
+            .get(i)
+            .map_or((1.0, self.alpha), |b| (b.alpha(), b.beta()))
+    }
+
+    /// Number of breaks made when drawing
+    #[inline]
+    fn n_breaks(&self) -> usize {
+        self.truncation.max(self.breaks.len())
+    }
+}
+
+impl From<&StickBreaking> for String {
+    fn from(sb: &StickBreaking) -> String {
+        let breaks: Vec<(f64, f64)> =
+            (0..sb.breaks.len()).map(|i| sb.break_params(i)).collect();
+        format!(
+            "StickBreaking(α: {}, truncation: {}, breaks: {})",
+            sb.alpha,
+            sb.truncation,
+            vec_to_string(&breaks, 5)
+        )
+    }
+}
+
+impl_display!(StickBreaking);
+
+impl Rv<Dpd> for StickBreaking {
+    /// Density of the break proportions that produce the `Dpd`'s weights
+    fn ln_f(&self, x: &Dpd) -> f64 {
+        let mut remaining = 1.0;
+        x.weights()
+            .iter()
+            .enumerate()
+            .map(|(i, &w)| {
+                let (a, b) = self.break_params(i);
+                let v = w / remaining;
+                let ln_f = (a - 1.0).mul_add(v.ln(), (b - 1.0) * (-v).ln_1p())
+                    - ln_beta_fn(a, b)
+                    - remaining.ln();
+                remaining -= w;
+                ln_f
+            })
+            .sum()
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> Dpd {
+        let mut remaining = 1.0;
+        let weights: Vec<f64> = (0..self.n_breaks())
+            .map(|i| {
+                let (a, b) = self.break_params(i);
+                let v: f64 = Beta::new_unchecked(a, b).draw(rng);
+                let w = v * remaining;
+                remaining -= w;
+                w
+            })
+            .collect();
+        Dpd::new_unchecked(weights, remaining)
+    }
+}
+
+impl Support<Dpd> for StickBreaking {
+    fn supports(&self, x: &Dpd) -> bool {
+        x.rest() > 0.0 && x.weights().iter().all(|&w| w > 0.0)
+    }
+}
+
+impl ContinuousDistr<Dpd> for StickBreaking {}
+
+impl<X: CategoricalDatum> ConjugatePrior<X, Dpd> for StickBreaking {
+    type Posterior = Self;
+    type LnMCache = ();
+    /// Log posterior predictive of each atom with an explicit break, and the
+    /// log of the stick remaining after them
+    type LnPpCache = (Vec<f64>, f64);
+
+    fn posterior(&self, x: &DpdData<X>) -> Self {
+        extract_stat_then(
+            x,
+            || CategoricalSuffStat::with_capacity_growing(0),
+            |stat: CategoricalSuffStat| {
+                let n_breaks = stat.n_cats().max(self.breaks.len());
+                let mut n_after = stat.n() as f64;
+                let breaks = (0..n_breaks)
+                    .map(|i| {
+                        let (a, b) = self.break_params(i);
+                        let ct = stat.counts().get(i).copied().unwrap_or(0.0);
+                        n_after -= ct;
+                        Beta::new_unchecked(a + ct, b + n_after)
+                    })
+                    .collect();
+                StickBreaking {
+                    alpha: self.alpha,
+                    breaks,
+                    truncation: self.truncation,
+                }
+            },
+        )
+    }
+
+    #[inline]
+    fn ln_m_cache(&self) -> Self::LnMCache {}
+
+    fn ln_m_with_cache(&self, _cache: &Self::LnMCache, x: &DpdData<X>) -> f64 {
+        extract_stat_then(
+            x,
+            || CategoricalSuffStat::with_capacity_growing(0),
+            |stat: CategoricalSuffStat| {
+                let mut n_after = stat.n() as f64;
+                stat.counts()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &ct)| {
+                        let (a, b) = self.break_params(i);
+                        n_after -= ct;
+                        ln_beta_fn(a + ct, b + n_after) - ln_beta_fn(a, b)
+                    })
+                    .sum()
+            },
+        )
+    }
+
+    fn ln_pp_cache(&self, x: &DpdData<X>) -> Self::LnPpCache {
+        let post = <Self as ConjugatePrior<X, Dpd>>::posterior(self, x);
+        let mut ln_remaining = 0.0;
+        let ln_pps = (0..post.breaks.len())
+            .map(|i| {
+                let (a, b) = post.break_params(i);
+                let ln_ab = (a + b).ln();
+                let ln_pp = ln_remaining + a.ln() - ln_ab;
+                ln_remaining += b.ln() - ln_ab;
+                ln_pp
+            })
+            .collect();
+        (ln_pps, ln_remaining)
+    }
+
+    fn ln_pp_with_cache(&self, cache: &Self::LnPpCache, y: &X) -> f64 {
+        let (ln_pps, ln_remaining) = cache;
+        let ix = y.into_usize();
+        ln_pps.get(ix).copied().unwrap_or_else(|| {
+            // Atoms past the explicit breaks use the Beta(1, α) prior
+            let ln_norm = self.alpha.ln_1p();
+            let n_skip = (ix - ln_pps.len()) as f64;
+            n_skip.mul_add(self.alpha.ln() - ln_norm, ln_remaining - ln_norm)
+        })
+    }
+}
+
+impl std::error::Error for StickBreakingError {}
+
+impl fmt::Display for StickBreakingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlphaTooLow { alpha } => {
+                write!(f, "alpha ({}) must be greater than zero", alpha)
+            }
+            Self::AlphaNotFinite { alpha } => {
+                write!(f, "alpha ({}) was non-finite", alpha)
+            }
+            Self::TruncationIsZero => {
+                write!(f, "truncation must be greater than zero")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::misc::logsumexp;
+    use crate::test_basic_impls;
+
+    const TOL: f64 = 1E-12;
+
+    test_basic_impls!(
+        StickBreaking::new(1.2, 3).unwrap(),
+        Dpd::new(vec![0.5, 0.25, 0.125], 0.125).unwrap()
+    );
+
+    #[test]
+    fn new_validates_parameters() {
+        assert!(StickBreaking::new(1.0, 1).is_ok());
+        assert_eq!(
+            StickBreaking::new(0.0, 1),
+            Err(StickBreakingError::AlphaTooLow { alpha: 0.0 })
+        );
+        assert!(matches!(
+            StickBreaking::new(f64::INFINITY, 1),
+            Err(StickBreakingError::AlphaNotFinite { .. })
+        ));
+        assert_eq!(
+            StickBreaking::new(1.0, 0),
+            Err(StickBreakingError::TruncationIsZero)
+        );
+    }
+
+    #[test]
+    fn ln_f_of_single_break_is_beta_density() {
+        // With one break, the density is that of the first weight
+        let sb = StickBreaking::new(2.5, 1).unwrap();
+        let dpd = Dpd::new(vec![0.3], 0.7).unwrap();
+        let beta = Beta::new(1.0, 2.5).unwrap();
+        assert::close(sb.ln_f(&dpd), beta.ln_f(&0.3_f64), TOL);
+    }
+
+    #[test]
+    fn draws_sum_to_one() {
+        let mut rng = rand::thread_rng();
+        let sb = StickBreaking::new(3.0, 15).unwrap();
+        for _ in 0..20 {
+            let dpd: Dpd = sb.draw(&mut rng);
+            assert_eq!(dpd.k(), 15);
+            assert::close(
+                dpd.weights().iter().sum::<f64>() + dpd.rest(),
+                1.0,
+                TOL,
+            );
+        }
+    }
+
+    #[test]
+    fn ln_m_matches_sequential_ln_pp() {
+        let sb = StickBreaking::new(0.7, 5).unwrap();
+        let xs: Vec<usize> = vec![0, 2, 0, 1, 4, 0, 2];
+
+        let mut seq = 0.0;
+        for i in 0..xs.len() {
+            let data: DpdData<usize> = DataOrSuffStat::Data(&xs[..i]);
+            seq += sb.ln_pp(&xs[i], &data);
+        }
+        let data: DpdData<usize> = DataOrSuffStat::Data(&xs);
+        assert::close(sb.ln_m(&data), seq, 1E-10);
+    }
+
+    #[test]
+    fn ln_pp_sums_to_one() {
+        let sb = StickBreaking::new(1.3, 5).unwrap();
+        let xs: Vec<usize> = vec![0, 2, 0, 1];
+        let mut stat = CategoricalSuffStat::with_capacity_growing(0);
+        stat.observe_many(&xs);
+        let data: DpdData<usize> = DataOrSuffStat::SuffStat(&stat);
+
+        let ln_pps: Vec<f64> =
+            (0..200_usize).map(|y| sb.ln_pp(&y, &data)).collect();
+        assert::close(logsumexp(&ln_pps), 0.0, 1E-8);
+    }
+
+    #[test]
+    fn posterior_breaks() {
+        let sb = StickBreaking::new(2.0, 5).unwrap();
+        let xs: Vec<usize> = vec![0, 0, 1, 3];
+        let data: DpdData<usize> = DataOrSuffStat::Data(&xs);
+        let post = sb.posterior(&data);
+
+        let params: Vec<(f64, f64)> = post
+            .breaks()
+            .iter()
+            .map(|b| (b.alpha(), b.beta()))
+            .collect();
+        assert_eq!(
+            params,
+            vec![(3.0, 4.0), (2.0, 3.0), (1.0, 3.0), (2.0, 2.0)]
+        );
+
+        // Conditioning the posterior on more data composes
+        let ys: Vec<usize> = vec![1, 5];
+        let both: Vec<usize> = xs.iter().chain(ys.iter()).copied().collect();
+        let post_both = sb.posterior(&DpdData::Data(&both));
+        assert_eq!(post.posterior(&DpdData::Data(&ys)), post_both);
+    }
+}
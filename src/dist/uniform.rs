@@ -2,6 +2,7 @@
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
 
+use crate::data::UniformSuffStat;
 use crate::impl_display;
 use crate::traits::*;
 use rand::Rng;
@@ -165,6 +166,24 @@ macro_rules! impl_traits {
 
         impl ContinuousDistr<$kind> for Uniform {}
 
+        impl HasSuffStat<$kind> for Uniform {
+            type Stat = UniformSuffStat;
+
+            fn empty_suffstat(&self) -> Self::Stat {
+                UniformSuffStat::new()
+            }
+
+            fn ln_f_stat(&self, stat: &Self::Stat) -> f64 {
+                if stat.n() == 0 {
+                    0.0
+                } else if self.a <= stat.min() && stat.max() <= self.b {
+                    stat.n() as f64 * self.lnf()
+                } else {
+                    f64::NEG_INFINITY
+                }
+            }
+        }
+
         impl Mean<$kind> for Uniform {
             fn mean(&self) -> Option<$kind> {
                 let m = (self.b + self.a) / 2.0;
@@ -230,6 +249,16 @@ impl Entropy for Uniform {
 impl_traits!(f64);
 impl_traits!(f32);
 
+impl CacheControl for Uniform {
+    fn precompute(&self) {
+        self.lnf();
+    }
+
+    fn invalidate(&mut self) {
+        self.lnf = OnceLock::new();
+    }
+}
+
 impl std::error::Error for UniformError {}
 
 impl fmt::Display for UniformError {
@@ -354,4 +383,20 @@ mod tests {
         });
         assert!(passes > 0);
     }
+
+    #[test]
+    fn ln_f_stat() {
+        let u = Uniform::new(1.0, 3.0).unwrap();
+        let xs: Vec<f64> = vec![1.5, 2.0, 2.9];
+        let mut stat = UniformSuffStat::new();
+        stat.observe_many(&xs);
+
+        let ln_f_sum: f64 = xs.iter().map(|x| u.ln_f(x)).sum();
+        let ln_f_stat = <Uniform as HasSuffStat<f64>>::ln_f_stat(&u, &stat);
+        assert::close(ln_f_stat, ln_f_sum, TOL);
+
+        stat.observe(&3.5_f64);
+        let ln_f_stat = <Uniform as HasSuffStat<f64>>::ln_f_stat(&u, &stat);
+        assert_eq!(ln_f_stat, f64::NEG_INFINITY);
+    }
 }
@@ -2,6 +2,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::consts::LN_2PI;
+use crate::dist::{DrawError, DrawPolicy};
 use crate::impl_display;
 use crate::misc::bessel;
 use crate::traits::*;
@@ -204,6 +205,45 @@ impl VonMises {
         self.k = k;
         self.i0_k = bessel::i0(k);
     }
+
+    /// The envelope parameter, r, of the Best–Fisher rejection sampler
+    #[inline]
+    fn best_fisher_r(&self) -> f64 {
+        let tau = 1.0 + 4.0_f64.mul_add(self.k * self.k, 1.0).sqrt();
+        let rho = (tau * (2.0 * tau).sqrt()) / (2.0 * self.k);
+        rho.mul_add(rho, 1.0) / (2.0 * rho)
+    }
+
+    /// One proposal of the Best–Fisher sampler, or `None` if it is rejected
+    fn best_fisher_proposal<R: Rng>(&self, r: f64, rng: &mut R) -> Option<f64> {
+        // Best, D. J., & Fisher, N. I. (1979). Efficient simulation of the
+        //     von Mises distribution. Applied Statistics, 152-157.
+        // https://www.researchgate.net/publication/246035131_Efficient_Simulation_of_the_von_Mises_Distribution
+        let u = rand::distributions::Open01;
+        let u1: f64 = rng.sample(u);
+        let u2: f64 = rng.sample(u);
+
+        let z: f64 = (PI * u1).cos();
+        let f = r.mul_add(z, 1.0) / (r + z);
+        let c = self.k * (r - f);
+
+        if (c.mul_add(2.0 - c, -u2) >= 0.0) || ((c / u2).ln() + 1.0 - c >= 0.0)
+        {
+            let u3: f64 = rng.sample(u);
+            let y = (u3 - 0.5).signum().mul_add(f.acos(), self.mu);
+            Some(y.rem_euclid(2.0 * PI))
+        } else {
+            None
+        }
+    }
+
+    /// A draw from the wrapped normal with variance 1/k, which approaches the
+    /// VonMises as k grows and the circular uniform as k shrinks
+    fn wrapped_normal_draw<R: Rng>(&self, rng: &mut R) -> f64 {
+        let z: f64 = rng.sample(rand_distr::StandardNormal);
+        z.mul_add(self.k.sqrt().recip(), self.mu)
+            .rem_euclid(2.0 * PI)
+    }
 }
 
 impl Default for VonMises {
@@ -229,29 +269,11 @@ macro_rules! impl_traits {
                 self.k.mul_add((xf - self.mu).cos(), -LN_2PI) - self.i0_k.ln()
             }
 
-            // Best, D. J., & Fisher, N. I. (1979). Efficient simulation of the
-            //     von Mises distribution. Applied Statistics, 152-157.
-            // https://www.researchgate.net/publication/246035131_Efficient_Simulation_of_the_von_Mises_Distribution
             fn draw<R: Rng>(&self, rng: &mut R) -> $kind {
-                let u = rand::distributions::Open01;
-                let tau = 1.0 + 4.0_f64.mul_add(self.k * self.k, 1.0).sqrt();
-                let rho = (tau * (2.0 * tau).sqrt()) / (2.0 * self.k);
-                let r = rho.mul_add(rho, 1.0) / (2.0 * rho);
-
+                let r = self.best_fisher_r();
                 loop {
-                    let u1: f64 = rng.sample(u);
-                    let u2: f64 = rng.sample(u);
-
-                    let z: f64 = (PI * u1).cos();
-                    let f = r.mul_add(z, 1.0) / (r + z);
-                    let c = self.k * (r - f);
-
-                    if (c.mul_add(2.0 - c, -u2) >= 0.0)
-                        || ((c / u2).ln() + 1.0 - c >= 0.0)
-                    {
-                        let u3: f64 = rng.sample(u);
-                        let y = (u3 - 0.5).signum().mul_add(f.acos(), self.mu);
-                        let x = y.rem_euclid(2.0 * PI) as $kind;
+                    if let Some(y) = self.best_fisher_proposal(r, rng) {
+                        let x = y as $kind;
                         if self.supports(&x) {
                             return x;
                         } else {
@@ -262,6 +284,45 @@ macro_rules! impl_traits {
             }
         }
 
+        impl TryDraw<$kind> for VonMises {
+            fn try_draw<R: Rng>(
+                &self,
+                max_proposals: usize,
+                policy: DrawPolicy,
+                rng: &mut R,
+            ) -> Result<$kind, DrawError> {
+                // Draws that round to just outside the support, e.g., to
+                // 2π as f32, are rejected like any other proposal
+                let supported =
+                    |y: f64| Some(y as $kind).filter(|x| self.supports(x));
+                let r = self.best_fisher_r();
+                let mut propose =
+                    || self.best_fisher_proposal(r, rng).and_then(supported);
+                for _ in 0..max_proposals {
+                    if let Some(x) = propose() {
+                        return Ok(x);
+                    }
+                }
+                match policy {
+                    DrawPolicy::Retry => loop {
+                        if let Some(x) = propose() {
+                            return Ok(x);
+                        }
+                    },
+                    DrawPolicy::Fallback => loop {
+                        if let Some(x) =
+                            supported(self.wrapped_normal_draw(rng))
+                        {
+                            return Ok(x);
+                        }
+                    },
+                    DrawPolicy::Error => Err(DrawError::ProposalLimitReached {
+                        n_proposals: max_proposals,
+                    }),
+                }
+            }
+        }
+
         // TODO: XXX:This is going to be SLOW, because it uses quadrature.
         impl Cdf<$kind> for VonMises {
             fn cdf(&self, x: &$kind) -> f64 {
@@ -493,4 +554,59 @@ mod tests {
 
         assert!(passes > 0);
     }
+
+    #[test]
+    fn try_draw_policies_without_proposals() {
+        let mut rng = rand::thread_rng();
+        let vm = VonMises::new(1.0, 2.0).unwrap();
+
+        let res: Result<f64, DrawError> =
+            vm.try_draw(0, DrawPolicy::Error, &mut rng);
+        assert_eq!(
+            res,
+            Err(DrawError::ProposalLimitReached { n_proposals: 0 })
+        );
+
+        let x: f64 = vm.try_draw(0, DrawPolicy::Retry, &mut rng).unwrap();
+        assert!(vm.supports(&x));
+
+        let x: f64 = vm.try_draw(0, DrawPolicy::Fallback, &mut rng).unwrap();
+        assert!(vm.supports(&x));
+    }
+
+    #[test]
+    fn f32_fallback_draws_are_supported() {
+        // With k this large, many fallback draws are just below 2π, and
+        // round up to 2π as f32
+        let mut rng = rand::thread_rng();
+        let vm = VonMises::new(0.0, 1E14).unwrap();
+        assert!((0..1_000).all(|_| {
+            let x: f32 =
+                vm.try_draw(0, DrawPolicy::Fallback, &mut rng).unwrap();
+            vm.supports(&x)
+        }));
+    }
+
+    #[test]
+    fn try_draw_matches_circular_variance() {
+        // E[cos(x - mu)] = I1(k) / I0(k)
+        let mut rng = rand::thread_rng();
+        let n = 10_000;
+        for (policy, max_proposals, k) in [
+            (DrawPolicy::Error, 100, 2.0),
+            (DrawPolicy::Fallback, 0, 50.0),
+        ] {
+            let vm = VonMises::new(PI, k).unwrap();
+            let mean_cos = (0..n)
+                .map(|_| {
+                    let x: f64 =
+                        vm.try_draw(max_proposals, policy, &mut rng).unwrap();
+                    (x - PI).cos()
+                })
+                .sum::<f64>()
+                / n as f64;
+            let v: f64 = vm.variance().unwrap();
+            assert::close(mean_cos, 1.0 - v, 0.02);
+        }
+    }
 }
@@ -0,0 +1,574 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::consts::EULER_MASCERONI;
+use crate::impl_display;
+use crate::traits::*;
+use rand::Rng;
+use special::Gamma as _;
+use std::f64::consts::LN_2;
+use std::fmt;
+
+/// [Weibull distribution](https://en.wikipedia.org/wiki/Weibull_distribution),
+/// Weibull(k, λ), over x in [0, ∞).
+///
+/// ```math
+/// f(x|k, λ) = \frac{k}{λ} \left(\frac{x}{λ}\right)^{k-1} e^{-(x/λ)^k}
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use rv::prelude::*;
+///
+/// let weibull = Weibull::new(1.5, 2.0).unwrap();
+///
+/// // The CDF has a closed-form inverse
+/// let x: f64 = weibull.invcdf(0.3);
+/// assert!((weibull.cdf(&x) - 0.3).abs() < 1E-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct Weibull {
+    shape: f64,
+    scale: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum WeibullError {
+    /// The shape parameter is less than or equal to zero
+    ShapeTooLow { shape: f64 },
+    /// The shape parameter is infinite or NaN
+    ShapeNotFinite { shape: f64 },
+    /// The scale parameter is less than or equal to zero
+    ScaleTooLow { scale: f64 },
+    /// The scale parameter is infinite or NaN
+    ScaleNotFinite { scale: f64 },
+    /// No data were given to fit
+    EmptyData,
+    /// A datum to fit is not finite and positive
+    DataOutOfSupport { x: f64 },
+    /// All the data to fit are the same, so the shape is unbounded
+    DataNotDistinct,
+}
+
+impl Weibull {
+    /// Create a new `Weibull` distribution with shape, k, and scale, λ.
+    pub fn new(shape: f64, scale: f64) -> Result<Self, WeibullError> {
+        if shape <= 0.0 {
+            Err(WeibullError::ShapeTooLow { shape })
+        } else if !shape.is_finite() {
+            Err(WeibullError::ShapeNotFinite { shape })
+        } else if scale <= 0.0 {
+            Err(WeibullError::ScaleTooLow { scale })
+        } else if !scale.is_finite() {
+            Err(WeibullError::ScaleNotFinite { scale })
+        } else {
+            Ok(Weibull { shape, scale })
+        }
+    }
+
+    /// Creates a new Weibull without checking whether the parameters are
+    /// valid.
+    #[inline]
+    pub fn new_unchecked(shape: f64, scale: f64) -> Self {
+        Weibull { shape, scale }
+    }
+
+    /// Maximum likelihood estimate of the Weibull from positive data.
+    ///
+    /// The shape is the root of the profile likelihood equation
+    ///
+    /// ```math
+    /// \frac{\sum x_i^k \ln x_i}{\sum x_i^k} - \frac{1}{k}
+    ///     - \frac{1}{n} \sum \ln x_i = 0
+    /// ```
+    ///
+    /// found by safeguarded Newton iterations, and the scale is
+    /// `(Σ xᵢᵏ / n)^(1/k)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::prelude::*;
+    ///
+    /// let mut rng = rand::thread_rng();
+    /// let weibull = Weibull::new(2.5, 3.0).unwrap();
+    /// let xs: Vec<f64> = weibull.sample(5_000, &mut rng);
+    ///
+    /// let fit = Weibull::fit_mle(&xs).unwrap();
+    /// assert!((fit.shape() - 2.5).abs() < 0.2);
+    /// assert!((fit.scale() - 3.0).abs() < 0.1);
+    /// ```
+    ///
+    /// Data must be positive and not all the same
+    ///
+    /// ```
+    /// # use rv::dist::{Weibull, WeibullError};
+    /// assert_eq!(Weibull::fit_mle(&[]), Err(WeibullError::EmptyData));
+    /// assert!(Weibull::fit_mle(&[1.0, 0.0, 2.0]).is_err());
+    /// assert_eq!(
+    ///     Weibull::fit_mle(&[2.0, 2.0]),
+    ///     Err(WeibullError::DataNotDistinct)
+    /// );
+    /// ```
+    pub fn fit_mle(xs: &[f64]) -> Result<Self, WeibullError> {
+        if xs.is_empty() {
+            return Err(WeibullError::EmptyData);
+        }
+        if let Some(&x) = xs.iter().find(|&&x| !(x > 0.0 && x.is_finite())) {
+            return Err(WeibullError::DataOutOfSupport { x });
+        }
+
+        // Work with x / max(x) so the powers cannot overflow
+        let x_max = xs.iter().copied().fold(f64::MIN_POSITIVE, f64::max);
+        let ln_xs: Vec<f64> = xs.iter().map(|x| (x / x_max).ln()).collect();
+        let n = xs.len() as f64;
+        let mean_ln_x = ln_xs.iter().sum::<f64>() / n;
+        let var_ln_x = ln_xs
+            .iter()
+            .map(|y| (y - mean_ln_x) * (y - mean_ln_x))
+            .sum::<f64>()
+            / n;
+        if var_ln_x <= 0.0 {
+            return Err(WeibullError::DataNotDistinct);
+        }
+
+        // Returns Σ xᵏ and the profile equation and its derivative
+        let profile = |k: f64| {
+            let (s0, s1, s2) =
+                ln_xs.iter().fold((0.0, 0.0, 0.0), |(s0, s1, s2), &y| {
+                    let w = (k * y).exp();
+                    (s0 + w, w.mul_add(y, s1), (w * y).mul_add(y, s2))
+                });
+            let m1 = s1 / s0;
+            let g = m1 - k.recip() - mean_ln_x;
+            let dg = m1.mul_add(-m1, s2 / s0) + (k * k).recip();
+            (s0, g, dg)
+        };
+
+        // The profile equation increases with k, so bracket the root and
+        // fall back to bisection when a Newton step leaves the bracket.
+        // The moment estimate, π / (σ_ln √6), is the first guess.
+        let mut k = 1.282_549_830_161_864 / var_ln_x.sqrt();
+        let mut lower = 0.0;
+        let mut upper = f64::INFINITY;
+        for _ in 0..200 {
+            let (_, g, dg) = profile(k);
+            if g < 0.0 {
+                lower = k;
+            } else {
+                upper = k;
+            }
+            let newton = k - g / dg;
+            let next = if newton > lower && newton < upper {
+                newton
+            } else if upper.is_finite() {
+                (lower + upper) / 2.0
+            } else {
+                2.0 * k
+            };
+            let done = (next - k).abs() <= 1E-12 * k;
+            k = next;
+            if done {
+                break;
+            }
+        }
+
+        let (s0, _, _) = profile(k);
+        let scale = x_max * (s0 / n).powf(k.recip());
+        Self::new(k, scale)
+    }
+
+    /// Get the shape parameter, k
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rv::dist::Weibull;
+    /// let weibull = Weibull::new(1.5, 2.0).unwrap();
+    ///
+    /// assert_eq!(weibull.shape(), 1.5);
+    /// ```
+    #[inline]
+    pub fn shape(&self) -> f64 {
+        self.shape
+    }
+
+    /// Set the shape parameter
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rv::dist::Weibull;
+    /// let mut weibull = Weibull::new(1.5, 2.0).unwrap();
+    /// assert_eq!(weibull.shape(), 1.5);
+    ///
+    /// weibull.set_shape(2.8).unwrap();
+    /// assert_eq!(weibull.shape(), 2.8);
+    /// ```
+    ///
+    /// Will error for invalid values
+    ///
+    /// ```rust
+    /// # use rv::dist::Weibull;
+    /// # let mut weibull = Weibull::new(1.5, 2.0).unwrap();
+    /// assert!(weibull.set_shape(2.8).is_ok());
+    /// assert!(weibull.set_shape(0.0).is_err());
+    /// assert!(weibull.set_shape(-1.0).is_err());
+    /// assert!(weibull.set_shape(f64::INFINITY).is_err());
+    /// assert!(weibull.set_shape(f64::NAN).is_err());
+    /// ```
+    #[inline]
+    pub fn set_shape(&mut self, shape: f64) -> Result<(), WeibullError> {
+        if !shape.is_finite() {
+            Err(WeibullError::ShapeNotFinite { shape })
+        } else if shape <= 0.0 {
+            Err(WeibullError::ShapeTooLow { shape })
+        } else {
+            self.set_shape_unchecked(shape);
+            Ok(())
+        }
+    }
+
+    /// Set the shape parameter without input validation
+    #[inline]
+    pub fn set_shape_unchecked(&mut self, shape: f64) {
+        self.shape = shape
+    }
+
+    /// Get the scale parameter, λ
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rv::dist::Weibull;
+    /// let weibull = Weibull::new(1.5, 2.0).unwrap();
+    ///
+    /// assert_eq!(weibull.scale(), 2.0);
+    /// ```
+    #[inline]
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Set the scale parameter
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rv::dist::Weibull;
+    /// let mut weibull = Weibull::new(1.5, 2.0).unwrap();
+    /// assert_eq!(weibull.scale(), 2.0);
+    ///
+    /// weibull.set_scale(2.8).unwrap();
+    /// assert_eq!(weibull.scale(), 2.8);
+    /// ```
+    ///
+    /// Will error for invalid values
+    ///
+    /// ```rust
+    /// # use rv::dist::Weibull;
+    /// # let mut weibull = Weibull::new(1.5, 2.0).unwrap();
+    /// assert!(weibull.set_scale(2.8).is_ok());
+    /// assert!(weibull.set_scale(0.0).is_err());
+    /// assert!(weibull.set_scale(-1.0).is_err());
+    /// assert!(weibull.set_scale(f64::INFINITY).is_err());
+    /// assert!(weibull.set_scale(f64::NAN).is_err());
+    /// ```
+    #[inline]
+    pub fn set_scale(&mut self, scale: f64) -> Result<(), WeibullError> {
+        if !scale.is_finite() {
+            Err(WeibullError::ScaleNotFinite { scale })
+        } else if scale <= 0.0 {
+            Err(WeibullError::ScaleTooLow { scale })
+        } else {
+            self.set_scale_unchecked(scale);
+            Ok(())
+        }
+    }
+
+    /// Set the scale parameter without input validation
+    #[inline]
+    pub fn set_scale_unchecked(&mut self, scale: f64) {
+        self.scale = scale
+    }
+
+    /// Γ(1 + i/k), the i-th raw moment of Weibull(k, 1)
+    fn raw_moment(&self, i: f64) -> f64 {
+        (1.0 + i / self.shape).gamma()
+    }
+}
+
+impl From<&Weibull> for String {
+    fn from(weibull: &Weibull) -> String {
+        format!("Weibull(k: {}, λ: {})", weibull.shape, weibull.scale)
+    }
+}
+
+impl_display!(Weibull);
+
+macro_rules! impl_traits {
+    ($kind: ty) => {
+        impl Rv<$kind> for Weibull {
+            fn ln_f(&self, x: &$kind) -> f64 {
+                let x = f64::from(*x);
+                if x < 0.0 {
+                    return f64::NEG_INFINITY;
+                }
+                let z = x / self.scale;
+                // Avoid 0 * -∞ at x = 0 when k = 1
+                let ln_kernel = if self.shape == 1.0 {
+                    0.0
+                } else {
+                    (self.shape - 1.0) * z.ln()
+                };
+                self.shape.ln() - self.scale.ln() + ln_kernel
+                    - z.powf(self.shape)
+            }
+
+            fn draw<R: Rng>(&self, rng: &mut R) -> $kind {
+                let u: f64 = rng.sample(rand_distr::Open01);
+                (self.scale * (-u.ln()).powf(self.shape.recip())) as $kind
+            }
+        }
+
+        impl Support<$kind> for Weibull {
+            fn supports(&self, x: &$kind) -> bool {
+                *x >= 0.0 && x.is_finite()
+            }
+        }
+
+        impl ContinuousDistr<$kind> for Weibull {}
+
+        impl Cdf<$kind> for Weibull {
+            fn cdf(&self, x: &$kind) -> f64 {
+                let x = f64::from(*x);
+                if x <= 0.0 {
+                    0.0
+                } else {
+                    -(-(x / self.scale).powf(self.shape)).exp_m1()
+                }
+            }
+        }
+
+        impl InverseCdf<$kind> for Weibull {
+            fn invcdf(&self, p: f64) -> $kind {
+                (self.scale * (-(-p).ln_1p()).powf(self.shape.recip())) as $kind
+            }
+        }
+
+        impl Mean<$kind> for Weibull {
+            fn mean(&self) -> Option<$kind> {
+                Some((self.scale * self.raw_moment(1.0)) as $kind)
+            }
+        }
+
+        impl Median<$kind> for Weibull {
+            fn median(&self) -> Option<$kind> {
+                Some((self.scale * LN_2.powf(self.shape.recip())) as $kind)
+            }
+        }
+
+        impl Mode<$kind> for Weibull {
+            fn mode(&self) -> Option<$kind> {
+                if self.shape > 1.0 {
+                    let k = self.shape;
+                    Some(
+                        (self.scale * ((k - 1.0) / k).powf(k.recip())) as $kind,
+                    )
+                } else {
+                    Some(0.0)
+                }
+            }
+        }
+
+        impl Variance<$kind> for Weibull {
+            fn variance(&self) -> Option<$kind> {
+                let g1 = self.raw_moment(1.0);
+                let g2 = self.raw_moment(2.0);
+                Some((self.scale * self.scale * g1.mul_add(-g1, g2)) as $kind)
+            }
+        }
+    };
+}
+
+impl Skewness for Weibull {
+    fn skewness(&self) -> Option<f64> {
+        let g1 = self.raw_moment(1.0);
+        let g2 = self.raw_moment(2.0);
+        let g3 = self.raw_moment(3.0);
+        let var = g1.mul_add(-g1, g2);
+        let m3 = (2.0 * g1 * g1).mul_add(g1, (-3.0 * g1).mul_add(g2, g3));
+        Some(m3 / var.powf(1.5))
+    }
+}
+
+impl Kurtosis for Weibull {
+    fn kurtosis(&self) -> Option<f64> {
+        let g1 = self.raw_moment(1.0);
+        let g2 = self.raw_moment(2.0);
+        let g3 = self.raw_moment(3.0);
+        let g4 = self.raw_moment(4.0);
+        let var = g1.mul_add(-g1, g2);
+        let g1_sq = g1 * g1;
+        let m4 = (-3.0 * g1_sq).mul_add(
+            g1_sq,
+            (6.0 * g1_sq).mul_add(g2, (-4.0 * g1).mul_add(g3, g4)),
+        );
+        Some(m4 / (var * var) - 3.0)
+    }
+}
+
+impl Entropy for Weibull {
+    fn entropy(&self) -> f64 {
+        EULER_MASCERONI.mul_add(1.0 - self.shape.recip(), self.scale.ln())
+            - self.shape.ln()
+            + 1.0
+    }
+}
+
+impl_traits!(f32);
+impl_traits!(f64);
+
+impl std::error::Error for WeibullError {}
+
+impl fmt::Display for WeibullError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ShapeTooLow { shape } => {
+                write!(f, "shape ({}) must be greater than zero", shape)
+            }
+            Self::ShapeNotFinite { shape } => {
+                write!(f, "non-finite shape: {}", shape)
+            }
+            Self::ScaleTooLow { scale } => {
+                write!(f, "scale ({}) must be greater than zero", scale)
+            }
+            Self::ScaleNotFinite { scale } => {
+                write!(f, "non-finite scale: {}", scale)
+            }
+            Self::EmptyData => write!(f, "no data to fit"),
+            Self::DataOutOfSupport { x } => {
+                write!(f, "datum ({}) must be finite and positive", x)
+            }
+            Self::DataNotDistinct => write!(f, "all the data are the same"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::Exponential;
+    use crate::misc::ks_test;
+    use crate::test_basic_impls;
+
+    const TOL: f64 = 1E-12;
+    const KS_PVAL: f64 = 0.2;
+    const N_TRIES: usize = 5;
+
+    test_basic_impls!([continuous] Weibull::new(1.5, 2.0).unwrap());
+
+    #[test]
+    fn new_should_reject_bad_params() {
+        assert!(Weibull::new(1.0, 1.0).is_ok());
+        assert!(Weibull::new(0.0, 1.0).is_err());
+        assert!(Weibull::new(f64::INFINITY, 1.0).is_err());
+        assert!(Weibull::new(1.0, -1.0).is_err());
+        assert!(Weibull::new(1.0, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn shape_one_is_exponential() {
+        let weibull = Weibull::new(1.0, 2.0).unwrap();
+        let expon = Exponential::new(0.5).unwrap();
+        for x in [0.0_f64, 0.3, 1.0, 4.5] {
+            assert::close(weibull.ln_f(&x), expon.ln_f(&x), TOL);
+            assert::close(weibull.cdf(&x), expon.cdf(&x), TOL);
+        }
+        assert::close(weibull.entropy(), expon.entropy(), TOL);
+        assert::close(weibull.skewness().unwrap(), 2.0, 1E-10);
+        assert::close(weibull.kurtosis().unwrap(), 6.0, 1E-10);
+    }
+
+    #[test]
+    fn ln_f() {
+        let weibull = Weibull::new(1.5, 2.0).unwrap();
+        // ln(0.75) + 0.5 ln(0.5) - 0.5^1.5
+        assert::close(weibull.ln_f(&1.0_f64), -0.987_809_053_325_027_2, TOL);
+        assert_eq!(weibull.ln_f(&-1.0_f64), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn invcdf_inverts_cdf() {
+        let weibull = Weibull::new(0.7, 3.0).unwrap();
+        for p in [0.01, 0.3, 0.5, 0.99] {
+            let x: f64 = weibull.invcdf(p);
+            assert::close(weibull.cdf(&x), p, TOL);
+        }
+        let median: f64 = weibull.median().unwrap();
+        assert::close(weibull.cdf(&median), 0.5, TOL);
+    }
+
+    #[test]
+    fn moments_of_rayleigh() {
+        // Weibull(2, λ) is Rayleigh(λ / √2)
+        let weibull = Weibull::new(2.0, 2.0).unwrap();
+        let pi = std::f64::consts::PI;
+        let mean: f64 = weibull.mean().unwrap();
+        let var: f64 = weibull.variance().unwrap();
+        let mode: f64 = weibull.mode().unwrap();
+        assert::close(mean, pi.sqrt(), TOL);
+        assert::close(var, 4.0 - pi, TOL);
+        assert::close(mode, 2.0_f64.sqrt(), TOL);
+        let skew = 2.0 * pi.sqrt() * (pi - 3.0) / (4.0 - pi).powf(1.5);
+        assert::close(weibull.skewness().unwrap(), skew, 1E-10);
+    }
+
+    #[test]
+    fn fit_mle_solves_likelihood_equations() {
+        let xs = [0.4, 1.3, 2.2, 0.9, 3.1, 1.7, 0.2, 2.6];
+        let fit = Weibull::fit_mle(&xs).unwrap();
+        let (k, lambda) = (fit.shape(), fit.scale());
+
+        // Both partial derivatives of the log likelihood vanish
+        let n = xs.len() as f64;
+        let sum_pow: f64 = xs.iter().map(|x: &f64| x.powf(k)).sum();
+        let sum_pow_ln: f64 = xs.iter().map(|x: &f64| x.powf(k) * x.ln()).sum();
+        let sum_ln: f64 = xs.iter().map(|x: &f64| x.ln()).sum();
+        assert::close(lambda.powf(k), sum_pow / n, 1E-10);
+        assert::close(sum_pow_ln / sum_pow - k.recip(), sum_ln / n, 1E-10);
+    }
+
+    #[test]
+    fn fit_mle_is_scale_equivariant() {
+        let xs = [0.4, 1.3, 2.2, 0.9, 3.1, 1.7, 0.2, 2.6];
+        let ys: Vec<f64> = xs.iter().map(|x| x * 1E6).collect();
+        let fit_x = Weibull::fit_mle(&xs).unwrap();
+        let fit_y = Weibull::fit_mle(&ys).unwrap();
+        assert::close(fit_x.shape(), fit_y.shape(), 1E-8);
+        assert::close(fit_x.scale() * 1E6, fit_y.scale(), 1E-2);
+    }
+
+    #[test]
+    fn draw_test() {
+        let mut rng = rand::thread_rng();
+        let weibull = Weibull::new(0.8, 1.5).unwrap();
+        let cdf = |x: f64| weibull.cdf(&x);
+
+        let passes = (0..N_TRIES).fold(0, |acc, _| {
+            let xs: Vec<f64> = weibull.sample(1000, &mut rng);
+            let (_, p) = ks_test(&xs, cdf);
+            if p > KS_PVAL {
+                acc + 1
+            } else {
+                acc
+            }
+        });
+        assert!(passes > 0);
+    }
+}
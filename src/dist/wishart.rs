@@ -1,7 +1,8 @@
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
 
-use crate::dist::MvGaussian;
+use crate::dist::marginalize::dropped_indices;
+use crate::dist::{MarginalizeError, MvGaussian};
 use crate::misc::lnmv_gamma;
 use crate::traits::*;
 use nalgebra::{DMatrix, DVector};
@@ -219,6 +220,24 @@ impl Mode<DMatrix<f64>> for InvWishart {
     }
 }
 
+/// The marginal of the principal submatrix with rows and columns `ixs`,
+/// W<sup>-1</sup>(**Ψ**<sub>ixs</sub>, ν - (p - |ixs|))
+impl Marginalize for InvWishart {
+    type Marginal = InvWishart;
+
+    fn marginalize(
+        &self,
+        ixs: &[usize],
+    ) -> Result<InvWishart, MarginalizeError> {
+        let dropped = dropped_indices(ixs, self.ndims())?;
+        let inv_scale = self.inv_scale.select_rows(ixs).select_columns(ixs);
+        Ok(InvWishart::new_unchecked(
+            inv_scale,
+            self.df - dropped.len(),
+        ))
+    }
+}
+
 impl std::error::Error for InvWishartError {}
 
 impl fmt::Display for InvWishartError {
@@ -248,6 +267,37 @@ mod tests {
 
     test_basic_impls!(InvWishart::identity(3), DMatrix::identity(3, 3));
 
+    #[test]
+    fn diagonal_marginal_is_inv_gamma() {
+        use crate::dist::InvGamma;
+        use crate::misc::ks_test;
+        use rand::SeedableRng;
+        use rand_xoshiro::Xoshiro256Plus;
+
+        let inv_scale = DMatrix::from_row_slice(
+            3,
+            3,
+            &[2.0, 0.5, 0.1, 0.5, 1.0, 0.3, 0.1, 0.3, 1.5],
+        );
+        let iw = InvWishart::new(inv_scale, 6).unwrap();
+        let marginal = iw.marginalize(&[1]).unwrap();
+        assert_eq!(marginal.df(), 4);
+        assert_eq!(marginal.inv_scale()[(0, 0)], 1.0);
+
+        // A 1x1 inverse Wishart is InvGamma(ν / 2, ψ / 2)
+        let ig = InvGamma::new(2.0, 0.5).unwrap();
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x1234);
+        let draws: Vec<DMatrix<f64>> = iw.sample(500, &mut rng);
+        let xs: Vec<f64> = draws.iter().map(|x| x[(1, 1)]).collect();
+        let (_, p) = ks_test(&xs, |x| ig.cdf(&x));
+        assert!(p > 0.01);
+        assert::close(
+            marginal.ln_f(&DMatrix::from_element(1, 1, 0.7)),
+            ig.ln_f(&0.7),
+            TOL,
+        );
+    }
+
     #[test]
     fn new_should_reject_df_too_low() {
         let inv_scale = DMatrix::identity(4, 4);
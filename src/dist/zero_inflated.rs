@@ -0,0 +1,356 @@
+//! Zero-inflated wrapper for count distributions
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::ZeroInflatedSuffStat;
+use crate::traits::*;
+use num::Zero;
+use rand::Rng;
+use std::fmt;
+
+/// [Zero-inflated](https://en.wikipedia.org/wiki/Zero-inflated_model)
+/// distribution, which mixes a point mass at zero with a base count
+/// distribution.
+///
+/// With probability π a draw is a structural zero, otherwise it is drawn
+/// from the base distribution, so
+///
+/// f(0) = π + (1 - π) g(0), and f(x) = (1 - π) g(x) for x > 0
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{Poisson, ZeroInflated};
+/// use rv::traits::*;
+///
+/// let pois = Poisson::new(3.0).unwrap();
+/// let zip = ZeroInflated::new(0.4, pois.clone()).unwrap();
+///
+/// // Zero is more likely than under the base distribution
+/// assert!(zip.f(&0_u32) > pois.f(&0_u32));
+///
+/// // Everything else is less likely
+/// assert::close(zip.f(&2_u32), 0.6 * pois.f(&2_u32), 1E-12);
+///
+/// // The mean shrinks toward zero
+/// let mean: f64 = zip.mean().unwrap();
+/// assert::close(mean, 0.6 * 3.0, 1E-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct ZeroInflated<Fx> {
+    /// The probability of a structural zero
+    pi: f64,
+    /// The base distribution
+    base: Fx,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum ZeroInflatedError {
+    /// The zero probability is infinite or NaN
+    PiNotFinite { pi: f64 },
+    /// The zero probability is less than zero
+    PiTooLow { pi: f64 },
+    /// The zero probability is greater than one
+    PiTooHigh { pi: f64 },
+}
+
+impl<Fx> ZeroInflated<Fx> {
+    /// Create a new zero-inflated distribution with structural zero
+    /// probability `pi` and base distribution `base`
+    pub fn new(pi: f64, base: Fx) -> Result<Self, ZeroInflatedError> {
+        if !pi.is_finite() {
+            Err(ZeroInflatedError::PiNotFinite { pi })
+        } else if pi < 0.0 {
+            Err(ZeroInflatedError::PiTooLow { pi })
+        } else if pi > 1.0 {
+            Err(ZeroInflatedError::PiTooHigh { pi })
+        } else {
+            Ok(ZeroInflated { pi, base })
+        }
+    }
+
+    /// Creates a new ZeroInflated without checking whether the parameters
+    /// are valid.
+    #[inline]
+    pub fn new_unchecked(pi: f64, base: Fx) -> Self {
+        ZeroInflated { pi, base }
+    }
+
+    /// Get the probability of a structural zero
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rv::dist::{Geometric, ZeroInflated};
+    /// let zig = ZeroInflated::new(0.2, Geometric::new(0.5).unwrap()).unwrap();
+    /// assert_eq!(zig.pi(), 0.2);
+    /// ```
+    #[inline]
+    pub fn pi(&self) -> f64 {
+        self.pi
+    }
+
+    /// Set the probability of a structural zero
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rv::dist::{Geometric, ZeroInflated};
+    /// let mut zig = ZeroInflated::new(0.2, Geometric::new(0.5).unwrap()).unwrap();
+    /// zig.set_pi(0.7).unwrap();
+    /// assert_eq!(zig.pi(), 0.7);
+    ///
+    /// assert!(zig.set_pi(1.1).is_err());
+    /// assert!(zig.set_pi(-0.1).is_err());
+    /// assert!(zig.set_pi(f64::NAN).is_err());
+    /// ```
+    #[inline]
+    pub fn set_pi(&mut self, pi: f64) -> Result<(), ZeroInflatedError> {
+        if !pi.is_finite() {
+            Err(ZeroInflatedError::PiNotFinite { pi })
+        } else if pi < 0.0 {
+            Err(ZeroInflatedError::PiTooLow { pi })
+        } else if pi > 1.0 {
+            Err(ZeroInflatedError::PiTooHigh { pi })
+        } else {
+            self.set_pi_unchecked(pi);
+            Ok(())
+        }
+    }
+
+    /// Set the probability of a structural zero without input validation
+    #[inline]
+    pub fn set_pi_unchecked(&mut self, pi: f64) {
+        self.pi = pi;
+    }
+
+    /// Get a reference to the base distribution
+    #[inline]
+    pub fn base(&self) -> &Fx {
+        &self.base
+    }
+
+    /// Get a mutable reference to the base distribution
+    #[inline]
+    pub fn base_mut(&mut self) -> &mut Fx {
+        &mut self.base
+    }
+}
+
+impl<Fx: fmt::Display> fmt::Display for ZeroInflated<Fx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ZI(π: {}, {})", self.pi, self.base)
+    }
+}
+
+impl<X, Fx> Rv<X> for ZeroInflated<Fx>
+where
+    X: Zero,
+    Fx: Rv<X>,
+{
+    fn ln_f(&self, x: &X) -> f64 {
+        if x.is_zero() {
+            (1.0 - self.pi).mul_add(self.base.f(x), self.pi).ln()
+        } else {
+            (1.0 - self.pi).ln() + self.base.ln_f(x)
+        }
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> X {
+        if rng.gen::<f64>() < self.pi {
+            X::zero()
+        } else {
+            self.base.draw(rng)
+        }
+    }
+}
+
+impl<X, Fx> Support<X> for ZeroInflated<Fx>
+where
+    X: Zero,
+    Fx: Rv<X> + Support<X>,
+{
+    fn supports(&self, x: &X) -> bool {
+        x.is_zero() || self.base.supports(x)
+    }
+}
+
+impl<X, Fx> DiscreteDistr<X> for ZeroInflated<Fx>
+where
+    X: Zero,
+    Fx: DiscreteDistr<X>,
+{
+}
+
+impl<X, Fx> Cdf<X> for ZeroInflated<Fx>
+where
+    X: Zero,
+    Fx: Rv<X> + Cdf<X>,
+{
+    /// Assumes the base distribution has no mass below zero
+    fn cdf(&self, x: &X) -> f64 {
+        (1.0 - self.pi).mul_add(self.base.cdf(x), self.pi)
+    }
+}
+
+impl<Fx: Mean<f64>> Mean<f64> for ZeroInflated<Fx> {
+    fn mean(&self) -> Option<f64> {
+        self.base.mean().map(|mu| (1.0 - self.pi) * mu)
+    }
+}
+
+impl<Fx> Variance<f64> for ZeroInflated<Fx>
+where
+    Fx: Mean<f64> + Variance<f64>,
+{
+    fn variance(&self) -> Option<f64> {
+        let mu = self.base.mean()?;
+        let var = self.base.variance()?;
+        Some((1.0 - self.pi) * (self.pi * mu).mul_add(mu, var))
+    }
+}
+
+impl<X, Fx> HasSuffStat<X> for ZeroInflated<Fx>
+where
+    X: Zero,
+    Fx: HasSuffStat<X>,
+{
+    type Stat = ZeroInflatedSuffStat<Fx::Stat>;
+
+    fn empty_suffstat(&self) -> Self::Stat {
+        ZeroInflatedSuffStat::new(self.base.empty_suffstat())
+    }
+
+    fn ln_f_stat(&self, stat: &Self::Stat) -> f64 {
+        let n_nonzero = stat.nonzero().n() as f64;
+        let ln_f_nonzero = if n_nonzero > 0.0 {
+            n_nonzero.mul_add(
+                (1.0 - self.pi).ln(),
+                self.base.ln_f_stat(stat.nonzero()),
+            )
+        } else {
+            0.0
+        };
+        if stat.n_zeros() > 0 {
+            let ln_f_zero = self.ln_f(&X::zero());
+            (stat.n_zeros() as f64).mul_add(ln_f_zero, ln_f_nonzero)
+        } else {
+            ln_f_nonzero
+        }
+    }
+}
+
+impl std::error::Error for ZeroInflatedError {}
+
+impl fmt::Display for ZeroInflatedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PiNotFinite { pi } => write!(f, "non-finite pi: {}", pi),
+            Self::PiTooLow { pi } => {
+                write!(f, "pi ({}) must be greater than or equal to zero", pi)
+            }
+            Self::PiTooHigh { pi } => {
+                write!(f, "pi ({}) must be less than or equal to one", pi)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::PoissonSuffStat;
+    use crate::dist::{Geometric, NegBinomial, Poisson};
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    #[test]
+    fn new_validates_pi() {
+        let pois = Poisson::new(1.0).unwrap();
+        assert!(ZeroInflated::new(0.0, pois.clone()).is_ok());
+        assert!(ZeroInflated::new(1.0, pois.clone()).is_ok());
+        assert_eq!(
+            ZeroInflated::new(-0.1, pois.clone()),
+            Err(ZeroInflatedError::PiTooLow { pi: -0.1 })
+        );
+        assert_eq!(
+            ZeroInflated::new(1.1, pois.clone()),
+            Err(ZeroInflatedError::PiTooHigh { pi: 1.1 })
+        );
+        assert!(ZeroInflated::new(f64::NAN, pois).is_err());
+    }
+
+    #[test]
+    fn pmf_sums_to_one() {
+        let zinb = ZeroInflated::new(0.3, NegBinomial::new(2.0, 0.4).unwrap())
+            .unwrap();
+        let total: f64 = (0_u32..200).map(|x| zinb.f(&x)).sum();
+        assert::close(total, 1.0, 1E-10);
+        assert::close(zinb.cdf(&199_u32), 1.0, 1E-10);
+    }
+
+    #[test]
+    fn cdf_matches_cumulative_pmf() {
+        let zig =
+            ZeroInflated::new(0.25, Geometric::new(0.3).unwrap()).unwrap();
+        let mut cum = 0.0;
+        for x in 0_u32..20 {
+            cum += zig.f(&x);
+            assert::close(zig.cdf(&x), cum, TOL);
+        }
+    }
+
+    #[test]
+    fn moments_match_sample() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(3);
+        let zip = ZeroInflated::new(0.3, Poisson::new(4.0).unwrap()).unwrap();
+        let xs: Vec<u32> = zip.sample(100_000, &mut rng);
+        let n = xs.len() as f64;
+        let mean = xs.iter().map(|&x| f64::from(x)).sum::<f64>() / n;
+        let var = xs
+            .iter()
+            .map(|&x| (f64::from(x) - mean).powi(2))
+            .sum::<f64>()
+            / n;
+
+        let zip_mean: f64 = zip.mean().unwrap();
+        let zip_var: f64 = zip.variance().unwrap();
+        assert::close(zip_mean, 2.8, TOL);
+        assert::close(zip_var, 0.7 * 0.3_f64.mul_add(16.0, 4.0), TOL);
+        assert!((mean - zip_mean).abs() < 0.05);
+        assert!((var - zip_var).abs() < 0.1);
+
+        let n_zeros = xs.iter().filter(|&&x| x == 0).count() as f64;
+        assert!((n_zeros / n - zip.f(&0_u32)).abs() < 0.01);
+    }
+
+    #[test]
+    fn ln_f_stat_matches_sum_of_ln_f() {
+        let zip = ZeroInflated::new(0.2, Poisson::new(1.5).unwrap()).unwrap();
+        let xs: Vec<u32> = vec![0, 3, 0, 0, 1, 5, 2];
+
+        let mut stat: ZeroInflatedSuffStat<PoissonSuffStat> =
+            HasSuffStat::<u32>::empty_suffstat(&zip);
+        stat.observe_many(&xs);
+        assert_eq!(SuffStat::<u32>::n(&stat), 7);
+        assert_eq!(stat.n_zeros(), 3);
+
+        let expected: f64 = xs.iter().map(|x| zip.ln_f(x)).sum();
+        assert::close(
+            HasSuffStat::<u32>::ln_f_stat(&zip, &stat),
+            expected,
+            TOL,
+        );
+
+        stat.forget(&0_u32);
+        stat.forget(&5_u32);
+        assert_eq!(stat.n_zeros(), 2);
+        assert_eq!(stat.nonzero().n(), 3);
+    }
+}
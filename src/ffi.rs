@@ -0,0 +1,427 @@
+//! C foreign function interface
+//!
+//! Exposes construction, density, distribution, and quantile functions, and
+//! sampling of the main univariate distributions through opaque handles, so
+//! that code in other languages can use the same implementations. The C
+//! declarations are in `include/rv.h`.
+//!
+//! Build a shared library with
+//!
+//! ```text
+//! cargo rustc --release --features ffi --crate-type cdylib
+//! ```
+//!
+//! # Conventions
+//!
+//! - Constructors return a null pointer if the parameters are invalid.
+//! - Every handle returned by a constructor must be freed exactly once with
+//!   the matching `_free` function. Freeing a null pointer does nothing.
+//! - Functions that return a `double` return NaN if a handle is null or an
+//!   argument is out of range. Discrete distributions take and return their
+//!   values as `double`s, and have zero mass at non-integers.
+//! - No function unwinds across the boundary. Functions that return a
+//!   `double` return NaN if rv panics.
+use std::os::raw::c_double;
+
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use crate::dist::{
+    Beta, Cauchy, Exponential, Gamma, Gaussian, LogNormal, Poisson, Uniform,
+    Weibull,
+};
+use crate::traits::{
+    Cdf, ContinuousDistr, DiscreteDistr, InverseCdf, Rv, Support,
+};
+
+/// Opaque handle to a univariate distribution
+pub enum RvDist {
+    Gaussian(Gaussian),
+    LogNormal(LogNormal),
+    Gamma(Gamma),
+    Beta(Beta),
+    Exponential(Exponential),
+    Uniform(Uniform),
+    Cauchy(Cauchy),
+    Weibull(Weibull),
+    Poisson(Poisson),
+}
+
+/// Opaque handle to a seeded random number generator
+pub struct RvRng(SmallRng);
+
+// Apply `$body` to the continuous distribution bound to `$fx`, or
+// `$discrete` to the Poisson bound to `$pois`.
+macro_rules! dispatch {
+    ($dist: expr, $fx: ident => $body: expr, $pois: ident => $discrete: expr) => {
+        match $dist {
+            RvDist::Gaussian($fx) => $body,
+            RvDist::LogNormal($fx) => $body,
+            RvDist::Gamma($fx) => $body,
+            RvDist::Beta($fx) => $body,
+            RvDist::Exponential($fx) => $body,
+            RvDist::Uniform($fx) => $body,
+            RvDist::Cauchy($fx) => $body,
+            RvDist::Weibull($fx) => $body,
+            RvDist::Poisson($pois) => $discrete,
+        }
+    };
+}
+
+fn into_handle<Fx, E>(res: Result<Fx, E>, f: fn(Fx) -> RvDist) -> *mut RvDist {
+    res.map_or(std::ptr::null_mut(), |fx| Box::into_raw(Box::new(f(fx))))
+}
+
+// Evaluate `f`, returning NaN rather than unwinding across the boundary
+fn nan_on_panic<F: FnOnce() -> f64>(f: F) -> f64 {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f))
+        .unwrap_or(f64::NAN)
+}
+
+/// The count represented by `x`, if `x` is a non-negative integer that fits
+/// in a `u32`
+fn as_count(x: f64) -> Option<u32> {
+    if x.fract() == 0.0 && (0.0..=f64::from(u32::MAX)).contains(&x) {
+        Some(x as u32)
+    } else {
+        None
+    }
+}
+
+/// The smallest count whose CDF is at least `p`
+fn poisson_quantile(pois: &Poisson, p: f64) -> f64 {
+    if p >= 1.0 {
+        return f64::INFINITY;
+    }
+    let cdf = |k: u32| Cdf::<u32>::cdf(pois, &k);
+    let mut k = pois.rate().round().min(f64::from(u32::MAX - 1)) as u32;
+    while k > 0 && cdf(k - 1) >= p {
+        k -= 1;
+    }
+    while k < u32::MAX && cdf(k) < p {
+        k += 1;
+    }
+    f64::from(k)
+}
+
+/// Create a Gaussian with mean `mu` and standard deviation `sigma`
+#[no_mangle]
+pub extern "C" fn rv_gaussian_new(
+    mu: c_double,
+    sigma: c_double,
+) -> *mut RvDist {
+    into_handle(Gaussian::new(mu, sigma), RvDist::Gaussian)
+}
+
+/// Create a log-normal whose log has mean `mu` and standard deviation `sigma`
+#[no_mangle]
+pub extern "C" fn rv_lognormal_new(
+    mu: c_double,
+    sigma: c_double,
+) -> *mut RvDist {
+    into_handle(LogNormal::new(mu, sigma), RvDist::LogNormal)
+}
+
+/// Create a Gamma with `shape` and `rate`
+#[no_mangle]
+pub extern "C" fn rv_gamma_new(shape: c_double, rate: c_double) -> *mut RvDist {
+    into_handle(Gamma::new(shape, rate), RvDist::Gamma)
+}
+
+/// Create a Beta with `alpha` and `beta`
+#[no_mangle]
+pub extern "C" fn rv_beta_new(alpha: c_double, beta: c_double) -> *mut RvDist {
+    into_handle(Beta::new(alpha, beta), RvDist::Beta)
+}
+
+/// Create an Exponential with `rate`
+#[no_mangle]
+pub extern "C" fn rv_exponential_new(rate: c_double) -> *mut RvDist {
+    into_handle(Exponential::new(rate), RvDist::Exponential)
+}
+
+/// Create a Uniform on [`a`, `b`]
+#[no_mangle]
+pub extern "C" fn rv_uniform_new(a: c_double, b: c_double) -> *mut RvDist {
+    into_handle(Uniform::new(a, b), RvDist::Uniform)
+}
+
+/// Create a Cauchy with location `loc` and `scale`
+#[no_mangle]
+pub extern "C" fn rv_cauchy_new(loc: c_double, scale: c_double) -> *mut RvDist {
+    into_handle(Cauchy::new(loc, scale), RvDist::Cauchy)
+}
+
+/// Create a Weibull with `shape` and `scale`
+#[no_mangle]
+pub extern "C" fn rv_weibull_new(
+    shape: c_double,
+    scale: c_double,
+) -> *mut RvDist {
+    into_handle(Weibull::new(shape, scale), RvDist::Weibull)
+}
+
+/// Create a Poisson with `rate`
+#[no_mangle]
+pub extern "C" fn rv_poisson_new(rate: c_double) -> *mut RvDist {
+    into_handle(Poisson::new(rate), RvDist::Poisson)
+}
+
+/// Free a distribution
+///
+/// # Safety
+/// `dist` must be null or a handle returned by a constructor that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn rv_dist_free(dist: *mut RvDist) {
+    if !dist.is_null() {
+        drop(Box::from_raw(dist));
+    }
+}
+
+/// Probability density, or mass for discrete distributions, at `x`
+///
+/// # Safety
+/// `dist` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rv_pdf(dist: *const RvDist, x: c_double) -> c_double {
+    rv_ln_pdf(dist, x).exp()
+}
+
+/// Log probability density, or mass for discrete distributions, at `x`
+///
+/// # Safety
+/// `dist` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rv_ln_pdf(
+    dist: *const RvDist,
+    x: c_double,
+) -> c_double {
+    let Some(dist) = dist.as_ref() else {
+        return f64::NAN;
+    };
+    if x.is_nan() {
+        return f64::NAN;
+    }
+    nan_on_panic(|| {
+        dispatch!(dist,
+            fx => if fx.supports(&x) {
+                ContinuousDistr::<f64>::ln_pdf(fx, &x)
+            } else {
+                f64::NEG_INFINITY
+            },
+            pois => as_count(x).map_or(f64::NEG_INFINITY, |k| pois.ln_pmf(&k))
+        )
+    })
+}
+
+/// Cumulative distribution function at `x`
+///
+/// # Safety
+/// `dist` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rv_cdf(dist: *const RvDist, x: c_double) -> c_double {
+    let Some(dist) = dist.as_ref() else {
+        return f64::NAN;
+    };
+    if x.is_nan() {
+        return f64::NAN;
+    }
+    nan_on_panic(|| {
+        dispatch!(dist,
+            // The supports are intervals, so the median tells which side of
+            // the support an unsupported `x` is on
+            fx => if fx.supports(&x) {
+                Cdf::<f64>::cdf(fx, &x)
+            } else if x < InverseCdf::<f64>::invcdf(fx, 0.5) {
+                0.0
+            } else {
+                1.0
+            },
+            pois => if x < 0.0 {
+                0.0
+            } else if x >= f64::from(u32::MAX) {
+                1.0
+            } else {
+                Cdf::<u32>::cdf(pois, &(x.floor() as u32))
+            }
+        )
+    })
+}
+
+/// Quantile function, or inverse CDF, at probability `p`
+///
+/// For discrete distributions, the smallest value whose CDF is at least `p`.
+///
+/// # Safety
+/// `dist` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rv_quantile(
+    dist: *const RvDist,
+    p: c_double,
+) -> c_double {
+    let Some(dist) = dist.as_ref() else {
+        return f64::NAN;
+    };
+    if !(0.0..=1.0).contains(&p) {
+        return f64::NAN;
+    }
+    nan_on_panic(|| {
+        dispatch!(dist,
+            fx => InverseCdf::<f64>::invcdf(fx, p),
+            pois => poisson_quantile(pois, p)
+        )
+    })
+}
+
+/// Create a random number generator from `seed`
+#[no_mangle]
+pub extern "C" fn rv_rng_new(seed: u64) -> *mut RvRng {
+    Box::into_raw(Box::new(RvRng(SmallRng::seed_from_u64(seed))))
+}
+
+/// Free a random number generator
+///
+/// # Safety
+/// `rng` must be null or a handle returned by [`rv_rng_new`] that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn rv_rng_free(rng: *mut RvRng) {
+    if !rng.is_null() {
+        drop(Box::from_raw(rng));
+    }
+}
+
+/// Draw one value
+///
+/// # Safety
+/// `dist` and `rng` must be null or live handles, and `rng` must not be used
+/// from another thread at the same time.
+#[no_mangle]
+pub unsafe extern "C" fn rv_draw(
+    dist: *const RvDist,
+    rng: *mut RvRng,
+) -> c_double {
+    match (dist.as_ref(), rng.as_mut()) {
+        (Some(dist), Some(RvRng(rng))) => nan_on_panic(|| {
+            dispatch!(dist,
+                fx => Rv::<f64>::draw(fx, rng),
+                pois => f64::from(Rv::<u32>::draw(pois, rng))
+            )
+        }),
+        _ => f64::NAN,
+    }
+}
+
+/// Draw `n` values into `out`
+///
+/// Returns zero on success, or -1 if a handle is null.
+///
+/// # Safety
+/// `dist` and `rng` must be null or live handles, `rng` must not be used
+/// from another thread at the same time, and `out` must be valid for writes
+/// of `n` doubles.
+#[no_mangle]
+pub unsafe extern "C" fn rv_sample(
+    dist: *const RvDist,
+    rng: *mut RvRng,
+    n: usize,
+    out: *mut c_double,
+) -> i32 {
+    if dist.is_null() || rng.is_null() || (out.is_null() && n > 0) {
+        return -1;
+    }
+    if n > 0 {
+        std::slice::from_raw_parts_mut(out, n)
+            .iter_mut()
+            .for_each(|x| *x = rv_draw(dist, rng));
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOL: f64 = 1E-12;
+
+    #[test]
+    fn continuous_functions_match_rust_api() {
+        let dist = rv_gamma_new(2.0, 3.0);
+        assert!(!dist.is_null());
+        let gamma = Gamma::new(2.0, 3.0).unwrap();
+        unsafe {
+            assert::close(rv_pdf(dist, 0.4), gamma.pdf(&0.4_f64), TOL);
+            assert::close(rv_ln_pdf(dist, 0.4), gamma.ln_pdf(&0.4_f64), TOL);
+            assert::close(rv_cdf(dist, 0.4), gamma.cdf(&0.4_f64), TOL);
+            let x = rv_quantile(dist, 0.3);
+            assert::close(rv_cdf(dist, x), 0.3, 1E-10);
+
+            // Outside the support
+            assert_eq!(rv_pdf(dist, -1.0), 0.0);
+            assert_eq!(rv_cdf(dist, -1.0), 0.0);
+            assert_eq!(rv_quantile(dist, 0.0), 0.0);
+            assert!(rv_quantile(dist, 1.5).is_nan());
+            rv_dist_free(dist);
+        }
+    }
+
+    #[test]
+    fn poisson_functions() {
+        let dist = rv_poisson_new(3.5);
+        let pois = Poisson::new(3.5).unwrap();
+        unsafe {
+            assert::close(rv_pdf(dist, 2.0), pois.pmf(&2_u32), TOL);
+            assert_eq!(rv_pdf(dist, 2.5), 0.0);
+            assert_eq!(rv_pdf(dist, -1.0), 0.0);
+            assert::close(rv_cdf(dist, 2.5), pois.cdf(&2_u32), TOL);
+            assert_eq!(rv_quantile(dist, 0.0), 0.0);
+            assert_eq!(rv_quantile(dist, 1.0), f64::INFINITY);
+            for p in [0.01, 0.3, 0.5, 0.9, 0.999] {
+                let k = rv_quantile(dist, p);
+                assert!(rv_cdf(dist, k) >= p);
+                assert!(k == 0.0 || rv_cdf(dist, k - 1.0) < p);
+            }
+            rv_dist_free(dist);
+        }
+    }
+
+    #[test]
+    fn invalid_parameters_and_null_handles() {
+        assert!(rv_gaussian_new(0.0, -1.0).is_null());
+        assert!(rv_beta_new(f64::NAN, 1.0).is_null());
+        assert!(rv_poisson_new(0.0).is_null());
+        unsafe {
+            let null = std::ptr::null();
+            assert!(rv_pdf(null, 0.0).is_nan());
+            assert!(rv_cdf(null, 0.0).is_nan());
+            assert!(rv_quantile(null, 0.5).is_nan());
+            assert!(rv_draw(null, std::ptr::null_mut()).is_nan());
+            rv_dist_free(std::ptr::null_mut());
+            rv_rng_free(std::ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn seeded_sampling_is_reproducible() {
+        let dist = rv_uniform_new(1.0, 2.0);
+        unsafe {
+            let mut xs = vec![0.0; 100];
+            let mut ys = vec![0.0; 100];
+
+            let rng = rv_rng_new(1337);
+            assert_eq!(rv_sample(dist, rng, 100, xs.as_mut_ptr()), 0);
+            rv_rng_free(rng);
+
+            let rng = rv_rng_new(1337);
+            assert_eq!(rv_sample(dist, rng, 100, ys.as_mut_ptr()), 0);
+            assert!(rv_draw(dist, rng) >= 1.0);
+            assert_eq!(rv_sample(dist, rng, 1, std::ptr::null_mut()), -1);
+            rv_rng_free(rng);
+
+            assert_eq!(xs, ys);
+            assert!(xs.iter().all(|x| (1.0..=2.0).contains(x)));
+            rv_dist_free(dist);
+        }
+    }
+}
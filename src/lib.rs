@@ -10,11 +10,17 @@
 //!
 //! # Features
 //!
-//! - `serde1`: enables serialization and de-serialization of structs via
+//! - `serde1`: enables serialization and de-serialization of all
+//!   distributions, sufficient statistics, models, and kernels via
 //!   [serde](https://crates.io/crates/serde)
 //! - `process`: Gives you access to Gaussian processes.
 //! - `arraydist`: Enables distributions and statistical tests that require the
 //!   [nalgebra](https://crates.io/crates/nalgebra) crate.
+//! - `persist`: Gives you access to a compact, versioned binary format for
+//!   saving and loading distributions, sufficient statistics, and fitted
+//!   models. Implies `serde1`.
+//! - `ffi`: Gives you access to a C interface to the main univariate
+//!   distributions. The header is in `include/rv.h`.
 //!
 //! # Design
 //!
@@ -93,11 +99,19 @@ doctest!("../README.md");
 pub mod consts;
 pub mod data;
 pub mod dist;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod misc;
-mod model;
+pub mod model;
+#[cfg(feature = "persist")]
+pub mod persist;
 pub mod prelude;
 #[cfg(feature = "process")]
 pub mod process;
+#[cfg(feature = "arraydist")]
+pub mod regression;
+pub mod rng;
+pub mod sampler;
 pub mod test;
 pub mod traits;
 
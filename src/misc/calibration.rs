@@ -0,0 +1,237 @@
+//! Reliability diagrams and calibration error of probability forecasts
+use crate::dist::{Bernoulli, Categorical};
+
+/// One bin of a reliability diagram
+///
+/// See [`reliability_diagram`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReliabilityBin {
+    /// The lower edge of the bin
+    pub lower: f64,
+    /// The upper edge of the bin
+    pub upper: f64,
+    /// The mean forecast probability of the events in the bin
+    pub mean_forecast: f64,
+    /// The proportion of the events in the bin that occurred
+    pub frequency: f64,
+    /// The number of forecasts in the bin
+    pub count: usize,
+}
+
+/// Reliability diagram data for forecast probabilities of binary events.
+///
+/// Forecasts are grouped into `n_bins` bins of equal width on [0, 1]. For a
+/// calibrated forecaster, the frequency of events in each bin is close to the
+/// mean forecast probability in that bin. Empty bins are omitted.
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::{expected_calibration_error, reliability_diagram};
+///
+/// let probs = [0.1, 0.15, 0.8, 0.9, 0.85, 0.2];
+/// let outcomes = [false, false, true, true, false, true];
+///
+/// let bins = reliability_diagram(&probs, &outcomes, 2);
+/// assert_eq!(bins.len(), 2);
+/// assert_eq!(bins[0].count, 3);
+/// assert!((bins[0].mean_forecast - 0.15).abs() < 1E-12);
+/// assert!((bins[0].frequency - 1.0 / 3.0).abs() < 1E-12);
+///
+/// let ece = expected_calibration_error(&bins);
+/// assert!((ece - 0.1833333333333333).abs() < 1E-12);
+/// ```
+///
+/// # Panics
+/// If `n_bins` is zero, if `probs` and `outcomes` have different lengths, or
+/// if any probability is not in [0, 1]
+pub fn reliability_diagram(
+    probs: &[f64],
+    outcomes: &[bool],
+    n_bins: usize,
+) -> Vec<ReliabilityBin> {
+    assert!(n_bins > 0, "n_bins must be greater than zero");
+    assert_eq!(
+        probs.len(),
+        outcomes.len(),
+        "probs and outcomes must be the same length"
+    );
+
+    // (sum of forecasts, number of events, number of forecasts)
+    let mut sums = vec![(0.0, 0_usize, 0_usize); n_bins];
+    probs.iter().zip(outcomes).for_each(|(&p, &occurred)| {
+        assert!((0.0..=1.0).contains(&p), "probabilities must be in [0, 1]");
+        let ix = ((p * n_bins as f64) as usize).min(n_bins - 1);
+        let bin = &mut sums[ix];
+        bin.0 += p;
+        bin.1 += usize::from(occurred);
+        bin.2 += 1;
+    });
+
+    let width = (n_bins as f64).recip();
+    sums.iter()
+        .enumerate()
+        .filter(|(_, (_, _, count))| *count > 0)
+        .map(|(ix, &(sum_p, n_events, count))| ReliabilityBin {
+            lower: ix as f64 * width,
+            upper: (ix + 1) as f64 * width,
+            mean_forecast: sum_p / count as f64,
+            frequency: n_events as f64 / count as f64,
+            count,
+        })
+        .collect()
+}
+
+/// Reliability diagram data for Bernoulli forecasts of the `outcomes`.
+///
+/// See [`reliability_diagram`].
+///
+/// # Panics
+/// If `n_bins` is zero or if `forecasts` and `outcomes` have different
+/// lengths
+pub fn reliability_diagram_bernoulli(
+    forecasts: &[Bernoulli],
+    outcomes: &[bool],
+    n_bins: usize,
+) -> Vec<ReliabilityBin> {
+    let probs: Vec<f64> = forecasts.iter().map(Bernoulli::p).collect();
+    reliability_diagram(&probs, outcomes, n_bins)
+}
+
+/// Top-label reliability diagram data for Categorical forecasts of the
+/// `outcomes`.
+///
+/// Each forecast is summarized by the probability of its most likely
+/// category, and the event is that the outcome is that category. Ties go to
+/// the lowest index.
+///
+/// See [`reliability_diagram`].
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::Categorical;
+/// use rv::misc::reliability_diagram_categorical;
+///
+/// let forecasts = vec![
+///     Categorical::new(&[0.8, 0.1, 0.1]).unwrap(),
+///     Categorical::new(&[0.1, 0.35, 0.55]).unwrap(),
+/// ];
+///
+/// let bins = reliability_diagram_categorical(&forecasts, &[0, 1], 10);
+/// assert_eq!(bins.len(), 2);
+/// assert_eq!(bins[0].frequency, 0.0);
+/// assert_eq!(bins[1].frequency, 1.0);
+/// ```
+///
+/// # Panics
+/// If `n_bins` is zero or if `forecasts` and `outcomes` have different
+/// lengths
+pub fn reliability_diagram_categorical(
+    forecasts: &[Categorical],
+    outcomes: &[usize],
+    n_bins: usize,
+) -> Vec<ReliabilityBin> {
+    assert_eq!(
+        forecasts.len(),
+        outcomes.len(),
+        "forecasts and outcomes must be the same length"
+    );
+    let (probs, hits): (Vec<f64>, Vec<bool>) = forecasts
+        .iter()
+        .zip(outcomes)
+        .map(|(fx, &outcome)| {
+            let (ix_max, p_max) = fx.weights().into_iter().enumerate().fold(
+                (0, f64::NEG_INFINITY),
+                |(ix_max, p_max), (ix, p)| {
+                    if p > p_max {
+                        (ix, p)
+                    } else {
+                        (ix_max, p_max)
+                    }
+                },
+            );
+            (p_max.clamp(0.0, 1.0), ix_max == outcome)
+        })
+        .unzip();
+    reliability_diagram(&probs, &hits, n_bins)
+}
+
+/// Expected calibration error of a reliability diagram: the mean absolute
+/// difference between the forecast probability and the event frequency of
+/// each bin, weighted by the number of forecasts in the bin.
+///
+/// Returns zero if there are no forecasts.
+pub fn expected_calibration_error(bins: &[ReliabilityBin]) -> f64 {
+    let n: usize = bins.iter().map(|bin| bin.count).sum();
+    if n == 0 {
+        return 0.0;
+    }
+    bins.iter()
+        .map(|bin| bin.count as f64 * (bin.frequency - bin.mean_forecast).abs())
+        .sum::<f64>()
+        / n as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::Rv;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    #[test]
+    fn bins_cover_unit_interval() {
+        let probs = [0.0, 0.25, 0.5, 1.0];
+        let bins = reliability_diagram(&probs, &[false; 4], 4);
+        let edges: Vec<(f64, f64)> =
+            bins.iter().map(|bin| (bin.lower, bin.upper)).collect();
+        // One is in the last bin
+        assert_eq!(
+            edges,
+            vec![(0.0, 0.25), (0.25, 0.5), (0.5, 0.75), (0.75, 1.0)]
+        );
+        assert::close(expected_calibration_error(&bins), 0.4375, TOL);
+    }
+
+    #[test]
+    fn calibrated_bernoulli_forecasts_have_small_error() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xCA1);
+        let forecasts: Vec<Bernoulli> = (0..5_000)
+            .map(|i| Bernoulli::new(f64::from(i % 100) / 100.0).unwrap())
+            .collect();
+        let outcomes: Vec<bool> =
+            forecasts.iter().map(|fx| fx.draw(&mut rng)).collect();
+
+        let bins = reliability_diagram_bernoulli(&forecasts, &outcomes, 10);
+        assert_eq!(bins.len(), 10);
+        assert_eq!(bins.iter().map(|bin| bin.count).sum::<usize>(), 5_000);
+        assert!(expected_calibration_error(&bins) < 0.03);
+
+        // Overconfident forecasts are miscalibrated
+        let sharpened: Vec<Bernoulli> = forecasts
+            .iter()
+            .map(|fx| {
+                Bernoulli::new(if fx.p() < 0.5 { 0.0 } else { 1.0 }).unwrap()
+            })
+            .collect();
+        let bins = reliability_diagram_bernoulli(&sharpened, &outcomes, 10);
+        assert!(expected_calibration_error(&bins) > 0.2);
+    }
+
+    #[test]
+    fn categorical_uses_top_label() {
+        let forecasts = vec![
+            Categorical::new(&[0.5, 0.3, 0.2]).unwrap(),
+            Categorical::new(&[0.2, 0.5, 0.3]).unwrap(),
+        ];
+        let bins = reliability_diagram_categorical(&forecasts, &[0, 2], 5);
+        assert_eq!(bins.len(), 1);
+        assert_eq!(bins[0].count, 2);
+        assert::close(bins[0].mean_forecast, 0.5, TOL);
+        assert::close(bins[0].frequency, 0.5, TOL);
+        assert::close(expected_calibration_error(&bins), 0.0, TOL);
+    }
+}
@@ -0,0 +1,510 @@
+//! Split conformal prediction intervals from predictive distributions
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::traits::{Cdf, Mean, Variance};
+use std::fmt;
+
+/// Bisection steps when inverting a predictive CDF
+const MAX_BISECTIONS: usize = 200;
+
+/// How unusual an observation is under its predictive distribution, for
+/// conformal prediction
+///
+/// The set of observations that score at most a threshold must be an
+/// interval, which [`interval`](NonconformityScore::interval) returns.
+pub trait NonconformityScore<Fx> {
+    /// The nonconformity of `y` under `forecast`. Higher is less
+    /// conforming.
+    fn score(&self, forecast: &Fx, y: f64) -> f64;
+
+    /// The interval of `y` whose score under `forecast` is at most
+    /// `threshold`
+    fn interval(&self, forecast: &Fx, threshold: f64) -> (f64, f64);
+}
+
+/// The absolute distance from the predictive mean, `|y - μ|`
+///
+/// Gives intervals of the same width for every forecast.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct AbsoluteResidual;
+
+/// The absolute distance from the predictive mean in predictive standard
+/// deviations, `|y - μ| / σ`
+///
+/// Gives intervals that are wider where the forecast is less certain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct StandardizedResidual;
+
+/// The distance of the probability integral transform from one half,
+/// `|F(y) - 1/2|`
+///
+/// Gives central intervals of the predictive distribution, so skewed and
+/// multimodal forecasts keep their shape. Only the CDF is needed; it is
+/// inverted numerically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+pub struct PitDistance;
+
+impl<Fx: Mean<f64>> NonconformityScore<Fx> for AbsoluteResidual {
+    fn score(&self, forecast: &Fx, y: f64) -> f64 {
+        (y - mean(forecast)).abs()
+    }
+
+    fn interval(&self, forecast: &Fx, threshold: f64) -> (f64, f64) {
+        let mu = mean(forecast);
+        (mu - threshold, mu + threshold)
+    }
+}
+
+impl<Fx> NonconformityScore<Fx> for StandardizedResidual
+where
+    Fx: Mean<f64> + Variance<f64>,
+{
+    fn score(&self, forecast: &Fx, y: f64) -> f64 {
+        (y - mean(forecast)).abs() / std(forecast)
+    }
+
+    fn interval(&self, forecast: &Fx, threshold: f64) -> (f64, f64) {
+        let mu = mean(forecast);
+        let half_width = threshold * std(forecast);
+        (mu - half_width, mu + half_width)
+    }
+}
+
+impl<Fx: Cdf<f64>> NonconformityScore<Fx> for PitDistance {
+    fn score(&self, forecast: &Fx, y: f64) -> f64 {
+        (forecast.cdf(&y) - 0.5).abs()
+    }
+
+    fn interval(&self, forecast: &Fx, threshold: f64) -> (f64, f64) {
+        if threshold >= 0.5 {
+            (f64::NEG_INFINITY, f64::INFINITY)
+        } else {
+            (
+                cdf_quantile(forecast, 0.5 - threshold),
+                cdf_quantile(forecast, 0.5 + threshold),
+            )
+        }
+    }
+}
+
+fn mean<Fx: Mean<f64>>(forecast: &Fx) -> f64 {
+    forecast
+        .mean()
+        .expect("the forecast must have a defined mean")
+}
+
+fn std<Fx: Variance<f64>>(forecast: &Fx) -> f64 {
+    forecast
+        .variance()
+        .expect("the forecast must have a defined variance")
+        .sqrt()
+}
+
+/// The `p` quantile of `fx` by bracketing and bisection of its CDF
+fn cdf_quantile<Fx: Cdf<f64>>(fx: &Fx, p: f64) -> f64 {
+    let mut lower = -1.0;
+    while fx.cdf(&lower) > p {
+        lower *= 2.0;
+    }
+    let mut upper = 1.0;
+    while fx.cdf(&upper) < p {
+        upper *= 2.0;
+    }
+    for _ in 0..MAX_BISECTIONS {
+        let mid = 0.5 * (lower + upper);
+        if mid <= lower || mid >= upper {
+            break;
+        }
+        if fx.cdf(&mid) < p {
+            lower = mid;
+        } else {
+            upper = mid;
+        }
+    }
+    0.5 * (lower + upper)
+}
+
+/// Split conformal prediction intervals
+///
+/// Calibrated on forecasts and outcomes held out from fitting, the
+/// intervals contain a new outcome with probability at least `1 - alpha`
+/// whenever the calibration and new data are exchangeable, no matter how
+/// poor the forecasts are (Vovk et al., 2005; Lei et al., 2018). Better
+/// forecasts and scores give narrower intervals.
+///
+/// # Example
+///
+/// A forecaster that is overconfident by a factor of two
+///
+/// ```
+/// use rv::dist::Gaussian;
+/// use rv::misc::{SplitConformal, StandardizedResidual};
+/// use rv::traits::*;
+///
+/// let mut rng = rand::thread_rng();
+/// let truth = Gaussian::new(0.0, 2.0).unwrap();
+/// let forecast = Gaussian::standard();
+///
+/// let ys: Vec<f64> = truth.sample(1_000, &mut rng);
+/// let forecasts = vec![forecast.clone(); ys.len()];
+/// let conformal =
+///     SplitConformal::calibrate(StandardizedResidual, &forecasts, &ys, 0.1)
+///         .unwrap();
+///
+/// // The 90% interval is stretched to about ±1.645 × 2
+/// let (lower, upper) = conformal.interval(&forecast);
+/// assert!((upper - 3.29).abs() < 0.4);
+/// assert!((lower + 3.29).abs() < 0.4);
+///
+/// let new_ys: Vec<f64> = truth.sample(1_000, &mut rng);
+/// let coverage = conformal.coverage(&vec![forecast; 1_000], &new_ys);
+/// assert!((coverage - 0.9).abs() < 0.04);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct SplitConformal<S> {
+    score: S,
+    /// Miscoverage rate
+    alpha: f64,
+    /// Calibration scores in ascending order
+    scores: Vec<f64>,
+    /// Largest score inside the intervals
+    threshold: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum ConformalError {
+    /// There are no calibration data
+    EmptyCalibration,
+    /// There are a different number of forecasts and outcomes
+    LengthMismatch {
+        /// The number of forecasts
+        n_forecasts: usize,
+        /// The number of outcomes
+        n_ys: usize,
+    },
+    /// The miscoverage rate is not in (0, 1)
+    AlphaOutOfRange { alpha: f64 },
+    /// A calibration score is NaN
+    ScoreNan {
+        /// Index of the calibration datum
+        ix: usize,
+    },
+}
+
+impl<S> SplitConformal<S> {
+    /// Calibrate intervals with miscoverage rate `alpha` from held-out
+    /// `forecasts` and their outcomes, `ys`
+    ///
+    /// With `n` calibration points, the threshold is the
+    /// `⌈(n + 1)(1 - alpha)⌉`-th smallest score. If that exceeds `n`, the
+    /// intervals are the whole real line.
+    pub fn calibrate<Fx>(
+        score: S,
+        forecasts: &[Fx],
+        ys: &[f64],
+        alpha: f64,
+    ) -> Result<Self, ConformalError>
+    where
+        S: NonconformityScore<Fx>,
+    {
+        if forecasts.len() != ys.len() {
+            return Err(ConformalError::LengthMismatch {
+                n_forecasts: forecasts.len(),
+                n_ys: ys.len(),
+            });
+        }
+        if ys.is_empty() {
+            return Err(ConformalError::EmptyCalibration);
+        }
+        if !(alpha > 0.0 && alpha < 1.0) {
+            return Err(ConformalError::AlphaOutOfRange { alpha });
+        }
+
+        let mut scores = forecasts
+            .iter()
+            .zip(ys.iter())
+            .enumerate()
+            .map(|(ix, (fx, &y))| {
+                let s = score.score(fx, y);
+                if s.is_nan() {
+                    Err(ConformalError::ScoreNan { ix })
+                } else {
+                    Ok(s)
+                }
+            })
+            .collect::<Result<Vec<f64>, _>>()?;
+        scores.sort_unstable_by(f64::total_cmp);
+
+        let n = scores.len();
+        let rank = ((n + 1) as f64 * (1.0 - alpha)).ceil() as usize;
+        let threshold = if rank > n {
+            f64::INFINITY
+        } else {
+            scores[rank.max(1) - 1]
+        };
+
+        Ok(SplitConformal {
+            score,
+            alpha,
+            scores,
+            threshold,
+        })
+    }
+
+    /// The nonconformity score
+    #[inline]
+    pub fn score(&self) -> &S {
+        &self.score
+    }
+
+    /// The miscoverage rate
+    #[inline]
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// The largest score inside the intervals
+    #[inline]
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// The number of calibration data
+    #[inline]
+    pub fn n_calibration(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// The prediction interval for the outcome of `forecast`
+    pub fn interval<Fx>(&self, forecast: &Fx) -> (f64, f64)
+    where
+        S: NonconformityScore<Fx>,
+    {
+        self.score.interval(forecast, self.threshold)
+    }
+
+    /// The prediction interval for the outcome of each of `forecasts`
+    pub fn intervals<Fx>(&self, forecasts: &[Fx]) -> Vec<(f64, f64)>
+    where
+        S: NonconformityScore<Fx>,
+    {
+        forecasts.iter().map(|fx| self.interval(fx)).collect()
+    }
+
+    /// Whether `y` is in the prediction interval of `forecast`
+    pub fn contains<Fx>(&self, forecast: &Fx, y: f64) -> bool
+    where
+        S: NonconformityScore<Fx>,
+    {
+        self.score.score(forecast, y) <= self.threshold
+    }
+
+    /// The fraction of `ys` in the prediction intervals of their
+    /// `forecasts`
+    ///
+    /// # Panics
+    /// If `forecasts` and `ys` have different lengths
+    pub fn coverage<Fx>(&self, forecasts: &[Fx], ys: &[f64]) -> f64
+    where
+        S: NonconformityScore<Fx>,
+    {
+        assert_eq!(
+            forecasts.len(),
+            ys.len(),
+            "forecasts and ys must be the same length"
+        );
+        let n_covered = forecasts
+            .iter()
+            .zip(ys.iter())
+            .filter(|(fx, &y)| self.contains(*fx, y))
+            .count();
+        n_covered as f64 / ys.len() as f64
+    }
+
+    /// The conformal p-value of `y` under `forecast`, the fraction of the
+    /// calibration data and `y` that score at least as high as `y`
+    ///
+    /// Small p-values flag outcomes that do not conform to the calibration
+    /// data. Under exchangeability, `P(p ≤ a) ≤ a`.
+    pub fn p_value<Fx>(&self, forecast: &Fx, y: f64) -> f64
+    where
+        S: NonconformityScore<Fx>,
+    {
+        let s = self.score.score(forecast, y);
+        let n_below = self.scores.partition_point(|&c| c < s);
+        let n_at_least = self.scores.len() - n_below;
+        (n_at_least + 1) as f64 / (self.scores.len() + 1) as f64
+    }
+}
+
+impl std::error::Error for ConformalError {}
+
+impl fmt::Display for ConformalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyCalibration => write!(f, "no calibration data"),
+            Self::LengthMismatch { n_forecasts, n_ys } => write!(
+                f,
+                "{} forecasts but {} outcomes were given",
+                n_forecasts, n_ys
+            ),
+            Self::AlphaOutOfRange { alpha } => {
+                write!(f, "alpha ({}) must be in (0, 1)", alpha)
+            }
+            Self::ScoreNan { ix } => {
+                write!(f, "the score of calibration datum {} is NaN", ix)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Gaussian, Mixture};
+    use crate::traits::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    #[test]
+    fn threshold_is_conformal_order_statistic() {
+        let forecasts = vec![Gaussian::standard(); 9];
+        let ys: Vec<f64> = (1..=9).map(|i| i as f64).collect();
+        // ⌈10 × 0.8⌉ = 8th smallest
+        let conformal =
+            SplitConformal::calibrate(AbsoluteResidual, &forecasts, &ys, 0.2)
+                .unwrap();
+        assert_eq!(conformal.threshold(), 8.0);
+        assert_eq!(conformal.interval(&forecasts[0]), (-8.0, 8.0));
+
+        // ⌈10 × 0.95⌉ = 10 > 9, so the interval is unbounded
+        let conformal =
+            SplitConformal::calibrate(AbsoluteResidual, &forecasts, &ys, 0.05)
+                .unwrap();
+        assert_eq!(conformal.threshold(), f64::INFINITY);
+
+        assert::close(conformal.p_value(&forecasts[0], 8.0), 0.3, 1E-12);
+        assert::close(conformal.p_value(&forecasts[0], 20.0), 0.1, 1E-12);
+        assert::close(conformal.p_value(&forecasts[0], 0.0), 1.0, 1E-12);
+    }
+
+    #[test]
+    fn coverage_holds_for_misspecified_forecasts() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xC0);
+        // Heteroscedastic truth, homoscedastic and biased forecasts
+        let draw = |n: usize, rng: &mut Xoshiro256Plus| {
+            let sigmas: Vec<f64> =
+                (0..n).map(|i| 0.5 + (i % 5) as f64).collect();
+            let ys: Vec<f64> = sigmas
+                .iter()
+                .map(|&s| Gaussian::new(1.0, s).unwrap().draw(rng))
+                .collect();
+            (vec![Gaussian::standard(); n], ys)
+        };
+        let (forecasts, ys) = draw(2_000, &mut rng);
+        let (new_forecasts, new_ys) = draw(20_000, &mut rng);
+
+        for alpha in [0.05, 0.2] {
+            let abs = SplitConformal::calibrate(
+                AbsoluteResidual,
+                &forecasts,
+                &ys,
+                alpha,
+            )
+            .unwrap();
+            let std = SplitConformal::calibrate(
+                StandardizedResidual,
+                &forecasts,
+                &ys,
+                alpha,
+            )
+            .unwrap();
+            let pit =
+                SplitConformal::calibrate(PitDistance, &forecasts, &ys, alpha)
+                    .unwrap();
+            for coverage in [
+                abs.coverage(&new_forecasts, &new_ys),
+                std.coverage(&new_forecasts, &new_ys),
+                pit.coverage(&new_forecasts, &new_ys),
+            ] {
+                assert!((coverage - (1.0 - alpha)).abs() < 0.025);
+            }
+        }
+    }
+
+    #[test]
+    fn pit_intervals_follow_mixture_shape() {
+        let mixture = Mixture::new(
+            vec![0.7, 0.3],
+            vec![
+                Gaussian::new(0.0, 1.0).unwrap(),
+                Gaussian::new(6.0, 1.0).unwrap(),
+            ],
+        )
+        .unwrap();
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xC1);
+        let ys: Vec<f64> = mixture.sample(5_000, &mut rng);
+        let forecasts = vec![mixture.clone(); ys.len()];
+        let conformal =
+            SplitConformal::calibrate(PitDistance, &forecasts, &ys, 0.1)
+                .unwrap();
+
+        // A calibrated forecast gives back its own central 90% interval
+        let (lower, upper) = conformal.interval(&mixture);
+        assert::close(mixture.cdf(&lower), 0.05, 0.01);
+        assert::close(mixture.cdf(&upper), 0.95, 0.01);
+        assert::close(cdf_quantile(&mixture, mixture.cdf(&2.5)), 2.5, 1E-10);
+    }
+
+    #[test]
+    fn invalid_calibration_is_rejected() {
+        let forecasts = vec![Gaussian::standard(); 3];
+        assert_eq!(
+            SplitConformal::calibrate(
+                AbsoluteResidual,
+                &forecasts,
+                &[1.0],
+                0.1
+            ),
+            Err(ConformalError::LengthMismatch {
+                n_forecasts: 3,
+                n_ys: 1
+            })
+        );
+        assert_eq!(
+            SplitConformal::calibrate(
+                AbsoluteResidual,
+                &forecasts[..0],
+                &[],
+                0.1
+            ),
+            Err(ConformalError::EmptyCalibration)
+        );
+        assert_eq!(
+            SplitConformal::calibrate(
+                AbsoluteResidual,
+                &forecasts,
+                &[1.0, 2.0, 3.0],
+                1.0
+            ),
+            Err(ConformalError::AlphaOutOfRange { alpha: 1.0 })
+        );
+        assert_eq!(
+            SplitConformal::calibrate(
+                AbsoluteResidual,
+                &forecasts,
+                &[1.0, f64::NAN, 3.0],
+                0.1
+            ),
+            Err(ConformalError::ScoreNan { ix: 1 })
+        );
+    }
+}
@@ -1,6 +1,4 @@
 use crate::consts::{LN_2PI, LN_PI};
-use rand::distributions::Open01;
-use rand::Rng;
 use special::Gamma;
 use std::cmp::Ordering;
 use std::cmp::PartialOrd;
@@ -96,136 +94,6 @@ where
         .collect()
 }
 
-#[inline]
-fn binary_search(cws: &[f64], r: f64) -> usize {
-    let mut left: usize = 0;
-    let mut right: usize = cws.len();
-    while left < right {
-        let mid = (left + right) / 2;
-        if cws[mid] < r {
-            left = mid + 1;
-        } else {
-            right = mid;
-        }
-    }
-    left
-}
-
-#[inline]
-fn catflip_bisection(cws: &[f64], r: f64) -> Option<usize> {
-    let ix = binary_search(cws, r);
-    if ix < cws.len() {
-        Some(ix)
-    } else {
-        None
-    }
-}
-
-#[inline]
-fn catflip_standard(cws: &[f64], r: f64) -> Option<usize> {
-    cws.iter().position(|&w| w > r)
-}
-
-fn catflip(cws: &[f64], r: f64) -> Option<usize> {
-    if cws.len() > 9 {
-        catflip_bisection(cws, r)
-    } else {
-        catflip_standard(cws, r)
-    }
-}
-
-/// Draw `n` indices in proportion to their `weights`
-pub fn pflip(weights: &[f64], n: usize, rng: &mut impl Rng) -> Vec<usize> {
-    assert!(!weights.is_empty(), "Empty container");
-
-    let cws: Vec<f64> = cumsum(weights);
-    let scale: f64 = *cws.last().unwrap();
-    let u = rand::distributions::Uniform::new(0.0, 1.0);
-
-    (0..n)
-        .map(|_| {
-            let r = rng.sample(u) * scale;
-            match catflip(&cws, r) {
-                Some(ix) => ix,
-                None => {
-                    let wsvec = weights.to_vec();
-                    panic!("Could not draw from {:?}", wsvec)
-                }
-            }
-        })
-        .collect()
-}
-
-/// Draw an index according to log-domain weights
-///
-/// Draw a `usize` from the categorical distribution defined by `ln_weights`.
-/// If `normed` is `true` then exp(`ln_weights`) is assumed to sum to 1.
-///
-/// # Examples
-///
-/// ```rust
-/// use rv::misc::ln_pflip;
-///
-/// let weights: Vec<f64> = vec![0.4, 0.2, 0.3, 0.1];
-/// let ln_weights: Vec<f64> = weights.iter().map(|&w| w.ln()).collect();
-///
-/// let xs = ln_pflip(&ln_weights, 100, true, &mut rand::thread_rng());
-///
-/// assert_eq!(xs.len(), 100);
-/// assert!(xs.iter().all(|&x| x <= 3));
-/// assert!(!xs.iter().any(|&x| x > 3));
-/// ```
-///
-/// Can handle -Inf ln weights
-///
-/// ```rust
-/// # use rv::misc::ln_pflip;
-/// use std::f64::NEG_INFINITY;
-/// use std::f64::consts::LN_2;
-///
-/// let ln_weights: Vec<f64> = vec![-LN_2, NEG_INFINITY, -LN_2];
-///
-/// let xs = ln_pflip(&ln_weights, 100, true, &mut rand::thread_rng());
-///
-/// let zero_count = xs.iter().filter(|&&x| x == 0).count();
-/// let one_count = xs.iter().filter(|&&x| x == 1).count();
-/// let two_count = xs.iter().filter(|&&x| x == 2).count();
-///
-/// assert!(zero_count > 30);
-/// assert_eq!(one_count, 0);
-/// assert!(two_count > 30);
-/// ```
-pub fn ln_pflip<R: Rng>(
-    ln_weights: &[f64],
-    n: usize,
-    normed: bool,
-    rng: &mut R,
-) -> Vec<usize> {
-    let z = if normed { 0.0 } else { logsumexp(ln_weights) };
-
-    // doing this instead of calling pflip shaves about 30% off the runtime.
-    let cws: Vec<f64> = ln_weights
-        .iter()
-        .scan(0.0, |state, w| {
-            *state += (w - z).exp();
-            Some(*state)
-        })
-        .collect();
-
-    (0..n)
-        .map(|_| {
-            let r = rng.sample(Open01);
-            match catflip(&cws, r) {
-                Some(ix) => ix,
-                None => {
-                    let wsvec = ln_weights.to_vec();
-                    panic!("Could not draw from {:?}", wsvec)
-                }
-            }
-        })
-        .collect()
-}
-
 /// Indices of the largest element(s) in xs.
 ///
 /// If there is more than one largest element, `argmax` returns the indices of
@@ -649,22 +517,6 @@ mod tests {
         assert::close(lnmv_gamma(3, 8.23), 25.709_195_968_438_628, TOL);
     }
 
-    #[test]
-    fn bisection_and_stanard_catflip_equivalence() {
-        let mut rng = rand::thread_rng();
-        for _ in 0..1000 {
-            let n: usize = rng.gen_range(10..100);
-            let cws: Vec<f64> = (1..=n).map(|i| i as f64).collect();
-            let u2 = rand::distributions::Uniform::new(0.0, n as f64);
-            let r = rng.sample(u2);
-
-            let ix1 = catflip_standard(&cws, r).unwrap();
-            let ix2 = catflip_bisection(&cws, r).unwrap();
-
-            assert_eq!(ix1, ix2);
-        }
-    }
-
     #[test]
     fn ln_fact_agrees_with_naive() {
         fn ln_fact_naive(x: usize) -> f64 {
@@ -681,22 +533,4 @@ mod tests {
             assert::close(f1, f2, 1e-9);
         }
     }
-
-    #[test]
-    fn ln_pflip_works_with_zero_weights() {
-        use std::f64::consts::LN_2;
-        use std::f64::NEG_INFINITY;
-
-        let ln_weights: Vec<f64> = vec![-LN_2, NEG_INFINITY, -LN_2];
-
-        let xs = ln_pflip(&ln_weights, 100, true, &mut rand::thread_rng());
-
-        let zero_count = xs.iter().filter(|&&x| x == 0).count();
-        let one_count = xs.iter().filter(|&&x| x == 1).count();
-        let two_count = xs.iter().filter(|&&x| x == 2).count();
-
-        assert!(zero_count > 30);
-        assert_eq!(one_count, 0);
-        assert!(two_count > 30);
-    }
 }
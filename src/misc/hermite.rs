@@ -0,0 +1,161 @@
+//! Gauss Hermite Quadrature for integrals against a Gaussian weight
+
+use std::f64::consts::{PI, SQRT_2};
+
+const MAX_NEWTON_ITERS: usize = 100;
+
+/// Gauss Hermite Quadrature
+///
+/// Approximates ∫ exp(-x²) f(x) dx over the real line with an `n`-point
+/// rule, which is exact when `f` is a polynomial of degree less than `2n`.
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::gauss_hermite_quadrature;
+///
+/// // ∫ exp(-x²) x² dx = √π / 2
+/// let q = gauss_hermite_quadrature(|x| x * x, 8);
+/// assert!((q - std::f64::consts::PI.sqrt() / 2.0).abs() < 1E-12);
+/// ```
+pub fn gauss_hermite_quadrature<F>(f: F, n: usize) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    let (weights, roots) = gauss_hermite_table(n);
+    weights
+        .iter()
+        .zip(roots.iter())
+        .map(|(weight, &root)| weight * f(root))
+        .sum::<f64>()
+}
+
+/// The expectation of `f(y)` where y ~ N(μ, σ), by `n`-point Gauss Hermite
+/// Quadrature
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::gauss_hermite_expectation;
+///
+/// // E[y²] = μ² + σ²
+/// let ey2 = gauss_hermite_expectation(|y| y * y, 1.5, 0.5, 8);
+/// assert!((ey2 - 2.5).abs() < 1E-12);
+/// ```
+pub fn gauss_hermite_expectation<F>(f: F, mu: f64, sigma: f64, n: usize) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    let scale = SQRT_2 * sigma;
+    gauss_hermite_quadrature(|x| f(scale.mul_add(x, mu)), n) / PI.sqrt()
+}
+
+/// The weights and roots of the `n`-point Gauss Hermite rule
+///
+/// The roots are found by Newton's method on the normalized Hermite
+/// polynomials, starting from asymptotic approximations (Numerical Recipes,
+/// 3rd ed., §4.6). Roots are in descending order.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+pub fn gauss_hermite_table(n: usize) -> (Vec<f64>, Vec<f64>) {
+    assert!(n > 0, "Hermite quadrature requires at least one point");
+
+    // π^(-1/4)
+    let pim4 = PI.powf(-0.25);
+    let nf = n as f64;
+
+    let mut roots = vec![0_f64; n];
+    let mut weights = vec![0_f64; n];
+    let mut z = 0_f64;
+
+    for i in 0..(n + 1) / 2 {
+        z = match i {
+            0 => {
+                let m = 2.0_f64.mul_add(nf, 1.0);
+                1.855_75_f64.mul_add(-m.powf(-1.0 / 6.0), m.sqrt())
+            }
+            1 => z - 1.14 * nf.powf(0.426) / z,
+            2 => 1.86_f64.mul_add(z, -0.86 * roots[0]),
+            3 => 1.91_f64.mul_add(z, -0.91 * roots[1]),
+            _ => 2.0_f64.mul_add(z, -roots[i - 2]),
+        };
+
+        let mut dp = 0_f64;
+        for _ in 0..MAX_NEWTON_ITERS {
+            // Recurrence for the normalized Hermite polynomials
+            let mut p1 = pim4;
+            let mut p2 = 0_f64;
+            for j in 0..n {
+                let p3 = p2;
+                p2 = p1;
+                let jf = j as f64;
+                p1 = (z * (2.0 / (jf + 1.0)).sqrt())
+                    .mul_add(p2, -(jf / (jf + 1.0)).sqrt() * p3);
+            }
+            dp = (2.0 * nf).sqrt() * p2;
+            let step = p1 / dp;
+            z -= step;
+            if step.abs() <= 1E-15 * z.abs().max(1.0) {
+                break;
+            }
+        }
+
+        roots[i] = z;
+        roots[n - 1 - i] = -z;
+        weights[i] = 2.0 / (dp * dp);
+        weights[n - 1 - i] = weights[i];
+    }
+
+    (weights, roots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOL: f64 = 1E-12;
+
+    #[test]
+    fn weights_sum_to_sqrt_pi() {
+        for n in [1, 2, 5, 20, 64, 150] {
+            let (weights, _) = gauss_hermite_table(n);
+            assert::close(weights.iter().sum::<f64>(), PI.sqrt(), TOL);
+        }
+    }
+
+    #[test]
+    fn small_rules_match_closed_form() {
+        let (weights, roots) = gauss_hermite_table(2);
+        assert::close(roots, vec![0.5_f64.sqrt(), -(0.5_f64.sqrt())], TOL);
+        assert::close(weights, vec![PI.sqrt() / 2.0; 2], TOL);
+
+        let (weights, roots) = gauss_hermite_table(3);
+        let r = 1.5_f64.sqrt();
+        assert::close(roots, vec![r, 0.0, -r], TOL);
+        assert::close(
+            weights,
+            vec![PI.sqrt() / 6.0, 2.0 * PI.sqrt() / 3.0, PI.sqrt() / 6.0],
+            TOL,
+        );
+    }
+
+    #[test]
+    fn exact_for_polynomials() {
+        // E[y⁴] = 3 for y ~ N(0, 1); exact for n >= 3
+        let m4 = gauss_hermite_expectation(|y| y.powi(4), 0.0, 1.0, 3);
+        assert::close(m4, 3.0, TOL);
+        // E[y⁶] = 15
+        let m6 = gauss_hermite_expectation(|y| y.powi(6), 0.0, 1.0, 10);
+        assert::close(m6, 15.0, 1E-10);
+    }
+
+    #[test]
+    fn gaussian_mgf() {
+        // E[exp(y)] = exp(μ + σ²/2)
+        let (mu, sigma) = (0.3, 0.8);
+        let q = gauss_hermite_expectation(f64::exp, mu, sigma, 40);
+        assert::close(q, sigma.mul_add(sigma / 2.0, mu).exp(), 1E-10);
+    }
+}
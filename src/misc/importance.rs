@@ -0,0 +1,350 @@
+//! Importance sampling
+use crate::misc::logsumexp;
+use crate::traits::Rv;
+use rand::Rng;
+
+/// Self-normalized importance sampling estimate of an expectation
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportanceEstimate<X> {
+    /// The estimate of `E[f(X)]` under the target
+    pub estimate: f64,
+    /// The draws from the proposal
+    pub samples: Vec<X>,
+    /// The log importance weights, normalized so their exponents sum to 1
+    pub ln_weights: Vec<f64>,
+    /// The effective sample size of the weights
+    pub ess: f64,
+}
+
+/// Pareto-smoothed importance weights
+///
+/// See [`psis`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Psis {
+    /// The smoothed log weights, normalized so their exponents sum to 1
+    pub ln_weights: Vec<f64>,
+    /// The estimated shape of the generalized Pareto distribution fit to the
+    /// largest weights.
+    ///
+    /// Estimates are reliable if `k_hat < 0.5` and usable if `k_hat < 0.7`.
+    /// Above 0.7, the variance of the weights is likely infinite. `k_hat` is
+    /// infinite if there were too few weights to fit.
+    pub k_hat: f64,
+    /// The effective sample size of the smoothed weights
+    pub ess: f64,
+}
+
+impl Psis {
+    /// Estimate `E[f(X)]` from the `values` of `f` at the samples
+    ///
+    /// # Panics
+    /// If `values` and the weights have different lengths
+    pub fn expectation(&self, values: &[f64]) -> f64 {
+        weighted_mean(&self.ln_weights, values)
+    }
+}
+
+/// Self-normalized importance sampling of `E[f(X)]`
+///
+/// Draws `n` samples from `proposal` and weights them by the ratio of the
+/// target density to the proposal density. `ln_f` only needs to be known up
+/// to a constant, e.g., an unnormalized posterior.
+///
+/// # Example
+///
+/// Estimate the mean of `N(1, 1)` with draws from `N(0, 2)`
+///
+/// ```
+/// use rv::dist::Gaussian;
+/// use rv::misc::importance::importance_sample;
+/// use rv::traits::Rv;
+///
+/// let mut rng = rand::thread_rng();
+/// let target = Gaussian::new(1.0, 1.0).unwrap();
+/// let proposal = Gaussian::new(0.0, 2.0).unwrap();
+///
+/// let est = importance_sample(
+///     |x: &f64| target.ln_f(x),
+///     &proposal,
+///     |x: &f64| *x,
+///     5_000,
+///     &mut rng,
+/// );
+///
+/// assert!((est.estimate - 1.0).abs() < 0.1);
+/// assert!(est.ess > 1_000.0);
+/// ```
+pub fn importance_sample<X, Q, L, F, R>(
+    ln_f: L,
+    proposal: &Q,
+    f: F,
+    n: usize,
+    rng: &mut R,
+) -> ImportanceEstimate<X>
+where
+    Q: Rv<X>,
+    L: Fn(&X) -> f64,
+    F: Fn(&X) -> f64,
+    R: Rng,
+{
+    let samples: Vec<X> = proposal.sample(n, rng);
+    let ln_weights: Vec<f64> =
+        samples.iter().map(|x| ln_f(x) - proposal.ln_f(x)).collect();
+    let ln_weights = normalize(ln_weights);
+    let values: Vec<f64> = samples.iter().map(f).collect();
+
+    ImportanceEstimate {
+        estimate: weighted_mean(&ln_weights, &values),
+        ess: effective_sample_size(&ln_weights),
+        samples,
+        ln_weights,
+    }
+}
+
+/// Effective sample size, `(Σw)² / Σw²`, of a set of log weights
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::importance::effective_sample_size;
+///
+/// assert::close(effective_sample_size(&[0.0; 10]), 10.0, 1E-12);
+///
+/// // One weight dominates
+/// let ess = effective_sample_size(&[0.0, -20.0, -20.0]);
+/// assert::close(ess, 1.0, 1E-6);
+/// ```
+pub fn effective_sample_size(ln_weights: &[f64]) -> f64 {
+    let z = logsumexp(ln_weights);
+    let ln_sum_sq = logsumexp(
+        &ln_weights
+            .iter()
+            .map(|w| 2.0 * (w - z))
+            .collect::<Vec<f64>>(),
+    );
+    (-ln_sum_sq).exp()
+}
+
+/// Pareto smoothed importance sampling (PSIS)
+///
+/// Replaces the largest weights with quantiles of a generalized Pareto
+/// distribution fit to them, which stabilizes estimates from heavy-tailed
+/// weights, and reports the fitted shape, `k_hat`, as a diagnostic of how
+/// reliable the weights are. Follows Vehtari et al. (2015), "Pareto
+/// smoothed importance sampling".
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::Gaussian;
+/// use rv::misc::importance::psis;
+/// use rv::traits::Rv;
+///
+/// let mut rng = rand::thread_rng();
+/// let target = Gaussian::standard();
+///
+/// // A proposal wider than the target gives bounded weights
+/// let wide = Gaussian::new(0.0, 1.5).unwrap();
+/// let xs: Vec<f64> = wide.sample(2_000, &mut rng);
+/// let ln_weights: Vec<f64> =
+///     xs.iter().map(|x| target.ln_f(x) - wide.ln_f(x)).collect();
+///
+/// let smoothed = psis(&ln_weights);
+/// assert!(smoothed.k_hat < 0.5);
+///
+/// let values: Vec<f64> = xs.iter().map(|x| x * x).collect();
+/// assert!((smoothed.expectation(&values) - 1.0).abs() < 0.2);
+/// ```
+pub fn psis(ln_weights: &[f64]) -> Psis {
+    let n = ln_weights.len();
+    let max = ln_weights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let mut lw: Vec<f64> = ln_weights.iter().map(|w| w - max).collect();
+
+    // Split the weights into the body and the right tail
+    let n_tail = ((0.2 * n as f64).min(3.0 * (n as f64).sqrt()).ceil()
+        as usize)
+        .min(n.saturating_sub(1));
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_unstable_by(|&a, &b| lw[a].partial_cmp(&lw[b]).unwrap());
+
+    let k_hat = if n_tail <= 4 {
+        f64::INFINITY
+    } else {
+        let tail_ixs = &order[n - n_tail..];
+        let cutoff = lw[order[n - n_tail - 1]].max(f64::MIN_POSITIVE.ln());
+        let exp_cutoff = cutoff.exp();
+        let exceedances: Vec<f64> = tail_ixs
+            .iter()
+            .map(|&ix| lw[ix].exp() - exp_cutoff)
+            .collect();
+        let (k, sigma) = gpd_fit(&exceedances);
+
+        if k.is_finite() {
+            // Replace the tail with the expected order statistics of the fit,
+            // truncated at the largest raw weight
+            for (i, &ix) in tail_ixs.iter().enumerate() {
+                let p = (i as f64 + 0.5) / n_tail as f64;
+                let q = gpd_invcdf(p, k, sigma) + exp_cutoff;
+                lw[ix] = q.ln().min(0.0);
+            }
+        }
+        k
+    };
+
+    let ln_weights = normalize(lw);
+    Psis {
+        ess: effective_sample_size(&ln_weights),
+        ln_weights,
+        k_hat,
+    }
+}
+
+/// Shift log weights so their exponents sum to 1
+fn normalize(mut ln_weights: Vec<f64>) -> Vec<f64> {
+    let z = logsumexp(&ln_weights);
+    ln_weights.iter_mut().for_each(|w| *w -= z);
+    ln_weights
+}
+
+/// Mean of `values` weighted by normalized log weights
+fn weighted_mean(ln_weights: &[f64], values: &[f64]) -> f64 {
+    assert_eq!(
+        ln_weights.len(),
+        values.len(),
+        "weights and values must be the same length"
+    );
+    ln_weights
+        .iter()
+        .zip(values.iter())
+        .map(|(w, v)| w.exp() * v)
+        .sum()
+}
+
+/// Quantile function of the generalized Pareto distribution with location
+/// zero
+fn gpd_invcdf(p: f64, k: f64, sigma: f64) -> f64 {
+    if k == 0.0 {
+        -sigma * (-p).ln_1p()
+    } else {
+        sigma * (-k * (-p).ln_1p()).exp_m1() / k
+    }
+}
+
+/// Fit a generalized Pareto distribution with location zero to sorted,
+/// positive `xs` with the method of Zhang and Stephens (2009). Returns the
+/// shape and scale.
+fn gpd_fit(xs: &[f64]) -> (f64, f64) {
+    const PRIOR: f64 = 3.0;
+    let n = xs.len();
+    let nf = n as f64;
+    let m = 30 + (nf.sqrt() as usize);
+
+    let x_quartile = xs[((nf / 4.0 + 0.5) as usize).max(1) - 1];
+    let x_max = xs[n - 1];
+    let mean_ln1p =
+        |b: f64| xs.iter().map(|&x| (-b * x).ln_1p()).sum::<f64>() / nf;
+
+    let bs: Vec<f64> = (1..=m)
+        .map(|j| {
+            let c = 1.0 - (m as f64 / (j as f64 - 0.5)).sqrt();
+            c / (PRIOR * x_quartile) + 1.0 / x_max
+        })
+        .collect();
+    let ln_liks: Vec<f64> = bs
+        .iter()
+        .map(|&b| {
+            let k = mean_ln1p(b);
+            nf * ((-b / k).ln() - k - 1.0)
+        })
+        .collect();
+
+    // Posterior weights of each b
+    let ws: Vec<f64> = ln_liks
+        .iter()
+        .map(|l| 1.0 / ln_liks.iter().map(|lj| (lj - l).exp()).sum::<f64>())
+        .collect();
+    let w_sum: f64 = ws.iter().sum();
+    let b = bs
+        .iter()
+        .zip(ws.iter())
+        .map(|(b, w)| b * w / w_sum)
+        .sum::<f64>();
+
+    let k = mean_ln1p(b);
+    let sigma = -k / b;
+
+    // Weakly informative prior shrinking k toward 0.5
+    let a = 10.0;
+    let k = k.mul_add(nf / (nf + a), a * 0.5 / (nf + a));
+    (k, sigma)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::Gaussian;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    fn gaussian_weights(proposal_sigma: f64, n: usize) -> (Vec<f64>, Vec<f64>) {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x1234);
+        let target = Gaussian::standard();
+        let proposal = Gaussian::new(0.0, proposal_sigma).unwrap();
+        let xs: Vec<f64> = proposal.sample(n, &mut rng);
+        let lw = xs
+            .iter()
+            .map(|x| target.ln_f(x) - proposal.ln_f(x))
+            .collect();
+        (xs, lw)
+    }
+
+    #[test]
+    fn importance_sample_weights_are_normalized() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xABCD);
+        let target = Gaussian::new(1.0, 1.0).unwrap();
+        let est = importance_sample(
+            |x: &f64| target.ln_f(x) + 3.0,
+            &Gaussian::new(0.0, 2.0).unwrap(),
+            |x: &f64| x * x,
+            5_000,
+            &mut rng,
+        );
+        assert::close(logsumexp(&est.ln_weights), 0.0, 1E-10);
+        // E[X²] = 1 + 1²
+        assert::close(est.estimate, 2.0, 0.15);
+        assert_eq!(est.samples.len(), 5_000);
+    }
+
+    #[test]
+    fn narrow_proposal_has_large_k_hat() {
+        let (_, lw) = gaussian_weights(0.5, 2_000);
+        let smoothed = psis(&lw);
+        assert!(smoothed.k_hat > 0.7);
+        assert!(smoothed.ess <= effective_sample_size(&lw) * 1.5);
+    }
+
+    #[test]
+    fn wide_proposal_has_small_k_hat() {
+        let (xs, lw) = gaussian_weights(1.5, 2_000);
+        let smoothed = psis(&lw);
+        assert!(smoothed.k_hat < 0.5);
+
+        let values: Vec<f64> = xs.iter().map(|x| x * x).collect();
+        assert::close(smoothed.expectation(&values), 1.0, 0.1);
+    }
+
+    #[test]
+    fn too_few_weights_gives_infinite_k_hat() {
+        let smoothed = psis(&[0.0, -1.0, -2.0, 0.5]);
+        assert!(smoothed.k_hat.is_infinite());
+        assert::close(logsumexp(&smoothed.ln_weights), 0.0, 1E-12);
+    }
+
+    #[test]
+    fn gpd_invcdf_inverts_cdf() {
+        let (k, sigma) = (0.3, 2.0);
+        let x = gpd_invcdf(0.7, k, sigma);
+        let cdf = 1.0 - (1.0 + k * x / sigma).powf(-1.0 / k);
+        assert::close(cdf, 0.7, 1E-12);
+    }
+}
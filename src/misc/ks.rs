@@ -55,6 +55,78 @@ where
     (d, p)
 }
 
+/// One-sample Kolmogorov-Smirnov test on a frequency table.
+///
+/// Like [`ks_test`], but each value in `xs` is observed `weights[i]` times.
+/// This avoids expanding aggregated data into raw observations. The sum of
+/// the weights, rounded to the nearest integer, is used as the number of
+/// observations when computing the p-value, so non-integer weights should be
+/// scaled to sum to the effective sample size. Repeated values in `xs` are
+/// allowed and their weights are pooled.
+///
+/// # Example
+///
+/// ```rust
+/// use rv::prelude::*;
+/// use rv::misc::{ks_test, ks_test_weighted};
+///
+/// let gauss = Gaussian::standard();
+/// let cdf = |x: f64| gauss.cdf(&x);
+///
+/// // 0.5 was seen 3 times, 1.5 once, and -0.2 twice
+/// let (d_w, p_w) =
+///     ks_test_weighted(&[0.5, 1.5, -0.2], &[3.0, 1.0, 2.0], cdf);
+/// let (d, p) = ks_test(&[0.5, 0.5, 0.5, 1.5, -0.2, -0.2], cdf);
+///
+/// assert::close(d_w, d, 1E-12);
+/// assert::close(p_w, p, 1E-12);
+/// ```
+///
+/// # Panics
+/// If `xs` and `weights` have different lengths or if any weight is
+/// negative
+pub fn ks_test_weighted<X, F>(xs: &[X], weights: &[f64], cdf: F) -> (f64, f64)
+where
+    X: Copy + PartialOrd,
+    F: Fn(X) -> f64,
+{
+    assert_eq!(
+        xs.len(),
+        weights.len(),
+        "xs and weights must be the same length"
+    );
+    assert!(
+        weights.iter().all(|&w| w >= 0.0),
+        "weights must be non-negative"
+    );
+
+    let mut xws: Vec<(X, f64)> =
+        xs.iter().copied().zip(weights.iter().copied()).collect();
+    xws.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    let total: f64 = weights.iter().sum();
+    let mut below = 0.0;
+    let mut d: f64 = 0.0;
+    let mut ix = 0;
+    while ix < xws.len() {
+        let x = xws[ix].0;
+        let mut w = 0.0;
+        while ix < xws.len() && xws[ix].0 == x {
+            w += xws[ix].1;
+            ix += 1;
+        }
+        // The empirical CDF jumps at x, so compare F(x) to both sides
+        let fx = cdf(x);
+        let lower = below / total;
+        below += w;
+        let upper = below / total;
+        d = d.max((lower - fx).abs()).max((upper - fx).abs());
+    }
+
+    let p = 1.0 - ks_cdf(total.round() as usize, d);
+    (d, p)
+}
+
 const KS_AUTO_CUTOVER: usize = 10_000;
 
 /// Mode in which to run the KS Test
@@ -499,6 +571,29 @@ mod tests {
         assert::close(p, 0.002_180_450_252_694_976_5, TOL);
     }
 
+    #[test]
+    fn ks_test_weighted_matches_expanded() {
+        let xs: Vec<f64> =
+            vec![0.42, 0.24, 0.86, 0.85, 0.82, 0.25, 0.78, 0.13, 0.27];
+        let ws: Vec<f64> = vec![1.0, 1.0, 1.0, 1.0, 2.0, 1.0, 1.0, 1.0, 1.0];
+
+        let g = Gaussian::standard();
+        let cdf = |x: f64| g.cdf(&x);
+        let (ks, p) = ks_test_weighted(&xs, &ws, cdf);
+
+        assert::close(ks, 0.551_716_786_654_561_1, TOL);
+        assert::close(p, 0.002_180_450_252_694_976_5, TOL);
+    }
+
+    #[test]
+    fn ks_test_weighted_checks_both_sides_of_jump() {
+        // All the mass at one point: the ECDF jumps from 0 to 1 at 0, where
+        // the CDF is 1/2
+        let g = Gaussian::standard();
+        let (ks, _) = ks_test_weighted(&[0.0], &[50.0], |x: f64| g.cdf(&x));
+        assert::close(ks, 0.5, TOL);
+    }
+
     #[test]
     fn ks_two_sample_exact() {
         let xs = [
@@ -51,6 +51,69 @@ where
         )
 }
 
+/// Adaptive Gauss Legendre Quadrature
+///
+/// Recursively bisects `(a, b)` until 16-point quadrature on each half
+/// agrees with quadrature on the whole to within `tol`, or after `max_depth`
+/// bisections. Features much narrower than `(a, b)` may be missed entirely,
+/// so `(a, b)` should be split around them.
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::adaptive_gauss_legendre_quadrature;
+///
+/// // A narrow bump that a single fixed-order rule integrates poorly
+/// let f = |x: f64| (-0.5 * ((x - 0.3) / 0.05).powi(2)).exp();
+/// let q = adaptive_gauss_legendre_quadrature(f, (-2.0, 2.0), 1E-12, 30);
+/// let exact = 0.05 * (2.0 * std::f64::consts::PI).sqrt();
+/// assert!((q - exact).abs() < 1E-10);
+/// ```
+pub fn adaptive_gauss_legendre_quadrature<F>(
+    f: F,
+    (a, b): (f64, f64),
+    tol: f64,
+    max_depth: usize,
+) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    let (weights, roots) = gauss_legendre_table(16);
+    let whole = gauss_legendre_quadrature_cached(&f, (a, b), &weights, &roots);
+    adaptive_step(&f, (a, b), whole, tol, max_depth, &weights, &roots)
+}
+
+fn adaptive_step<F>(
+    f: &F,
+    (a, b): (f64, f64),
+    whole: f64,
+    tol: f64,
+    depth: usize,
+    weights: &[f64],
+    roots: &[f64],
+) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    let mid = (a + b) / 2.0;
+    let left = gauss_legendre_quadrature_cached(f, (a, mid), weights, roots);
+    let right = gauss_legendre_quadrature_cached(f, (mid, b), weights, roots);
+    if depth == 0 || (left + right - whole).abs() <= tol {
+        left + right
+    } else {
+        adaptive_step(f, (a, mid), left, tol / 2.0, depth - 1, weights, roots)
+            + adaptive_step(
+                f,
+                (mid, b),
+                right,
+                tol / 2.0,
+                depth - 1,
+                weights,
+                roots,
+            )
+    }
+}
+
 // =============================================================================
 // Gauss Legendre Backends
 // =============================================================================
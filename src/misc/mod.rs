@@ -1,18 +1,54 @@
 //! Random utilities
 pub mod bessel;
+mod calibration;
+mod conformal;
 pub(crate) mod entropy;
 mod func;
+mod hermite;
+pub mod importance;
 mod ks;
 mod legendre;
 #[cfg(feature = "arraydist")]
 mod mardia;
+mod normality;
+pub(crate) mod optimize;
+mod pit;
+pub mod sampling;
 mod seq;
+mod slice;
+pub mod stats;
+mod tolerance;
+mod two_sample;
 mod x2;
 
+pub use calibration::{
+    expected_calibration_error, reliability_diagram,
+    reliability_diagram_bernoulli, reliability_diagram_categorical,
+    ReliabilityBin,
+};
+pub use conformal::{
+    AbsoluteResidual, ConformalError, NonconformityScore, PitDistance,
+    SplitConformal, StandardizedResidual,
+};
 pub use func::*;
+pub use hermite::*;
 pub use ks::*;
 pub use legendre::*;
 #[cfg(feature = "arraydist")]
 pub use mardia::mardia;
+pub use normality::{jarque_bera, shapiro_wilk};
+pub use pit::{
+    pit_uniformity, pit_values, randomized_pit_values, standardized_residuals,
+};
+pub use sampling::{
+    gumbel_max, ln_pflip, pflip, AliasTable, AliasTableError, WeightedReservoir,
+};
 pub use seq::*;
-pub use x2::x2_test;
+pub use slice::{slice_sample, slice_sample_posterior};
+pub use tolerance::*;
+#[cfg(feature = "arraydist")]
+pub use two_sample::energy_test;
+#[cfg(feature = "process")]
+pub use two_sample::mmd_test;
+pub use two_sample::{mann_whitney_u, permutation_test, TwoSampleError};
+pub use x2::{g_test, x2_independence_test, x2_test, x2_test_weighted};
@@ -0,0 +1,235 @@
+use crate::dist::Gaussian;
+use crate::traits::{Cdf, InverseCdf};
+use std::f64::consts::PI;
+
+/// Evaluate a polynomial with coefficients in increasing order of degree
+fn poly(coeffs: &[f64], x: f64) -> f64 {
+    coeffs.iter().rev().fold(0.0, |acc, &c| acc.mul_add(x, c))
+}
+
+/// [Shapiro-Wilk](https://en.wikipedia.org/wiki/Shapiro%E2%80%93Wilk_test)
+/// test for univariate normality.
+///
+/// Returns the `W` statistic and its p-value using Royston's (1992)
+/// approximations to the coefficients and the null distribution of `W`.
+/// Small p-values are evidence against normality.
+///
+/// # Example
+///
+/// ```rust
+/// use rv::dist::{Exponential, Gaussian};
+/// use rv::misc::shapiro_wilk;
+/// use rv::traits::Rv;
+///
+/// let mut rng = rand::thread_rng();
+///
+/// let xs: Vec<f64> = Gaussian::standard().sample(200, &mut rng);
+/// let (w, _) = shapiro_wilk(&xs);
+/// assert!(w > 0.9);
+///
+/// let ys: Vec<f64> = Exponential::new(1.0).unwrap().sample(200, &mut rng);
+/// let (_, p) = shapiro_wilk(&ys);
+/// assert!(p < 0.01);
+/// ```
+///
+/// # Panics
+/// If `xs` has fewer than 3 or more than 5000 observations, or if all the
+/// observations are the same
+pub fn shapiro_wilk(xs: &[f64]) -> (f64, f64) {
+    let n = xs.len();
+    assert!(
+        (3..=5000).contains(&n),
+        "Shapiro-Wilk requires between 3 and 5000 observations"
+    );
+
+    let mut xs = xs.to_vec();
+    xs.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let nf = n as f64;
+    let g = Gaussian::standard();
+    let a = if n == 3 {
+        vec![-0.5_f64.sqrt(), 0.0, 0.5_f64.sqrt()]
+    } else {
+        let m: Vec<f64> = (1..=n)
+            .map(|i| g.invcdf((i as f64 - 0.375) / (nf + 0.25)))
+            .collect();
+        let mm: f64 = m.iter().map(|mi| mi * mi).sum();
+        let u = 1.0 / nf.sqrt();
+
+        let a_n = m[n - 1] / mm.sqrt()
+            + poly(
+                &[
+                    0.0, 0.221_157, -0.147_981, -2.071_190, 4.434_685,
+                    -2.706_056,
+                ],
+                u,
+            );
+        let mut a: Vec<f64> = vec![0.0; n];
+        a[n - 1] = a_n;
+        a[0] = -a_n;
+
+        // The outer one or two coefficients are corrected, the rest scaled
+        let n_outer = if n > 5 {
+            let a_n1 = m[n - 2] / mm.sqrt()
+                + poly(
+                    &[
+                        0.0, 0.042_981, -0.293_762, -1.752_461, 5.682_633,
+                        -3.582_633,
+                    ],
+                    u,
+                );
+            a[n - 2] = a_n1;
+            a[1] = -a_n1;
+            2
+        } else {
+            1
+        };
+        let outer_m: f64 = m[n - n_outer..].iter().map(|mi| mi * mi).sum();
+        let outer_a: f64 = a[n - n_outer..].iter().map(|ai| ai * ai).sum();
+        let phi =
+            2.0_f64.mul_add(-outer_m, mm) / 2.0_f64.mul_add(-outer_a, 1.0);
+        for i in n_outer..n - n_outer {
+            a[i] = m[i] / phi.sqrt();
+        }
+        a
+    };
+
+    let mean = xs.iter().sum::<f64>() / nf;
+    let ss: f64 = xs.iter().map(|x| (x - mean) * (x - mean)).sum();
+    assert!(ss > 0.0, "all observations are the same");
+    let b: f64 = a.iter().zip(xs.iter()).map(|(ai, xi)| ai * xi).sum();
+    let w = (b * b / ss).min(1.0);
+
+    let p = if n == 3 {
+        let p = 6.0 / PI * (w.sqrt().asin() - (0.75_f64).sqrt().asin());
+        p.max(0.0)
+    } else {
+        let ln_1mw = (-w).ln_1p();
+        let gamma = 0.459_f64.mul_add(nf, -2.273);
+        if n <= 11 && ln_1mw >= gamma {
+            // Beyond the range of the approximation
+            return (w, 0.0);
+        }
+        let (w1, mu, sigma) = if n <= 11 {
+            let w1 = -(gamma - ln_1mw).ln();
+            let mu = poly(&[0.5440, -0.399_78, 0.025_054, -0.000_671_4], nf);
+            let sigma =
+                poly(&[1.3822, -0.778_57, 0.062_767, -0.002_032_2], nf).exp();
+            (w1, mu, sigma)
+        } else {
+            let ln_n = nf.ln();
+            let mu = poly(&[-1.5861, -0.310_82, -0.083_751, 0.003_891_5], ln_n);
+            let sigma = poly(&[-0.4803, -0.082_676, 0.003_030_2], ln_n).exp();
+            (ln_1mw, mu, sigma)
+        };
+        1.0 - g.cdf(&((w1 - mu) / sigma))
+    };
+
+    (w, p)
+}
+
+/// [Jarque-Bera](https://en.wikipedia.org/wiki/Jarque%E2%80%93Bera_test)
+/// test for univariate normality.
+///
+/// Returns the statistic, `n/6 (S² + (K - 3)² / 4)`, where `S` and `K` are
+/// the sample skewness and kurtosis, and its asymptotic p-value from the
+/// chi-squared distribution with two degrees of freedom. The asymptotic
+/// p-value is only accurate for large samples.
+///
+/// # Example
+///
+/// ```rust
+/// use rv::dist::{Exponential, Gaussian};
+/// use rv::misc::jarque_bera;
+/// use rv::traits::Rv;
+///
+/// let mut rng = rand::thread_rng();
+///
+/// let ys: Vec<f64> = Exponential::new(1.0).unwrap().sample(500, &mut rng);
+/// let (_, p) = jarque_bera(&ys);
+/// assert!(p < 0.01);
+/// ```
+///
+/// # Panics
+/// If `xs` is empty or all the observations are the same
+pub fn jarque_bera(xs: &[f64]) -> (f64, f64) {
+    assert!(!xs.is_empty(), "xs must not be empty");
+    let nf = xs.len() as f64;
+    let mean = xs.iter().sum::<f64>() / nf;
+    let (m2, m3, m4) = xs.iter().fold((0.0, 0.0, 0.0), |(m2, m3, m4), x| {
+        let d = x - mean;
+        let d2 = d * d;
+        (m2 + d2, d2.mul_add(d, m3), d2.mul_add(d2, m4))
+    });
+    let (m2, m3, m4) = (m2 / nf, m3 / nf, m4 / nf);
+    assert!(m2 > 0.0, "all observations are the same");
+
+    let skew = m3 / m2.powf(1.5);
+    let excess_kurt = m4 / (m2 * m2) - 3.0;
+    let jb = nf / 6.0 * skew.mul_add(skew, excess_kurt * excess_kurt / 4.0);
+
+    // The survival function of the chi-squared with 2 dof is exp(-x/2)
+    (jb, (-jb / 2.0).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOL: f64 = 1E-10;
+
+    fn gaussian_quantiles(n: usize) -> Vec<f64> {
+        let g = Gaussian::standard();
+        (1..=n)
+            .map(|i| g.invcdf((i as f64 - 0.5) / n as f64))
+            .collect()
+    }
+
+    #[test]
+    fn shapiro_wilk_three_is_exact() {
+        // For n = 3, W = 4.5 / (42 / 9)
+        let (w, p) = shapiro_wilk(&[1.0, 2.0, 4.0]);
+        assert::close(w, 27.0 / 28.0, TOL);
+        let expected = 6.0 / PI * ((27.0_f64 / 28.0).sqrt().asin() - PI / 3.0);
+        assert::close(p, expected, TOL);
+    }
+
+    #[test]
+    fn shapiro_wilk_is_location_scale_invariant() {
+        let xs = vec![0.3, 1.9, -0.2, 2.4, 0.8, 1.1, 5.0, 0.0, 0.4];
+        let ys: Vec<f64> =
+            xs.iter().map(|x| 3.0_f64.mul_add(*x, 7.0)).collect();
+        let (w_x, p_x) = shapiro_wilk(&xs);
+        let (w_y, p_y) = shapiro_wilk(&ys);
+        assert::close(w_x, w_y, TOL);
+        assert::close(p_x, p_y, TOL);
+    }
+
+    #[test]
+    fn shapiro_wilk_accepts_gaussian_quantiles() {
+        for n in [5, 10, 50, 500] {
+            let (w, p) = shapiro_wilk(&gaussian_quantiles(n));
+            assert!(w > 0.95, "n = {}, w = {}", n, w);
+            assert!(p > 0.5, "n = {}, p = {}", n, p);
+        }
+    }
+
+    #[test]
+    fn shapiro_wilk_rejects_skewed() {
+        // Quantiles of an exponential
+        let xs: Vec<f64> = (1..=100)
+            .map(|i| -(1.0 - (f64::from(i) - 0.5) / 100.0).ln())
+            .collect();
+        let (_, p) = shapiro_wilk(&xs);
+        assert!(p < 1E-4);
+    }
+
+    #[test]
+    fn jarque_bera_of_symmetric_data() {
+        // Symmetric, so only the kurtosis contributes. For a two point
+        // distribution, the kurtosis is 1.
+        let (jb, p) = jarque_bera(&[-1.0, 1.0, -1.0, 1.0, -1.0, 1.0]);
+        assert::close(jb, 1.0, TOL);
+        assert::close(p, (-0.5_f64).exp(), TOL);
+    }
+}
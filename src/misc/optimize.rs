@@ -0,0 +1,141 @@
+//! Derivative-free optimization used for fitting distribution parameters
+
+/// Minimize `f` with the Nelder-Mead simplex algorithm.
+///
+/// The initial simplex is `x0` plus `x0` perturbed by `step` along each
+/// axis. The search stops when the spread of the function values over the
+/// simplex falls below `tol`, or after `max_iter` iterations.
+///
+/// Returns the best point found and the value of `f` there.
+pub(crate) fn nelder_mead<F>(
+    f: F,
+    x0: &[f64],
+    step: f64,
+    tol: f64,
+    max_iter: usize,
+) -> (Vec<f64>, f64)
+where
+    F: Fn(&[f64]) -> f64,
+{
+    const ALPHA: f64 = 1.0;
+    const GAMMA: f64 = 2.0;
+    const RHO: f64 = 0.5;
+    const SIGMA: f64 = 0.5;
+
+    let n = x0.len();
+    // NaN is treated as +inf so that invalid regions are avoided
+    let eval = |x: &[f64]| {
+        let fx = f(x);
+        if fx.is_nan() {
+            f64::INFINITY
+        } else {
+            fx
+        }
+    };
+
+    let mut simplex: Vec<(Vec<f64>, f64)> = (0..=n)
+        .map(|i| {
+            let mut x = x0.to_vec();
+            if i > 0 {
+                x[i - 1] += step;
+            }
+            let fx = eval(&x);
+            (x, fx)
+        })
+        .collect();
+
+    let along = |from: &[f64], to: &[f64], t: f64| -> Vec<f64> {
+        from.iter()
+            .zip(to.iter())
+            .map(|(&a, &b)| t.mul_add(b - a, a))
+            .collect()
+    };
+
+    for _ in 0..max_iter {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let f_best = simplex[0].1;
+        let f_worst = simplex[n].1;
+        if (f_worst - f_best).abs() <= tol {
+            break;
+        }
+
+        let centroid: Vec<f64> = (0..n)
+            .map(|j| simplex[..n].iter().map(|(x, _)| x[j]).sum::<f64>())
+            .map(|s| s / n as f64)
+            .collect();
+
+        let worst = simplex[n].0.clone();
+        let reflected = along(&centroid, &worst, -ALPHA);
+        let f_reflected = eval(&reflected);
+
+        if f_reflected < f_best {
+            let expanded = along(&centroid, &worst, -GAMMA);
+            let f_expanded = eval(&expanded);
+            simplex[n] = if f_expanded < f_reflected {
+                (expanded, f_expanded)
+            } else {
+                (reflected, f_reflected)
+            };
+        } else if f_reflected < simplex[n - 1].1 {
+            simplex[n] = (reflected, f_reflected);
+        } else {
+            let contracted = if f_reflected < f_worst {
+                along(&centroid, &reflected, RHO)
+            } else {
+                along(&centroid, &worst, RHO)
+            };
+            let f_contracted = eval(&contracted);
+            if f_contracted < f_worst.min(f_reflected) {
+                simplex[n] = (contracted, f_contracted);
+            } else {
+                let best = simplex[0].0.clone();
+                simplex.iter_mut().skip(1).for_each(|vertex| {
+                    let x = along(&best, &vertex.0, SIGMA);
+                    let fx = eval(&x);
+                    *vertex = (x, fx);
+                });
+            }
+        }
+    }
+
+    simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+    simplex.swap_remove(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nelder_mead_finds_quadratic_minimum() {
+        let f = |x: &[f64]| (x[0] - 1.5).powi(2) + 3.0 * (x[1] + 2.0).powi(2);
+        let (x, fx) = nelder_mead(f, &[0.0, 0.0], 1.0, 1E-16, 1_000);
+        assert::close(x[0], 1.5, 1E-6);
+        assert::close(x[1], -2.0, 1E-6);
+        assert::close(fx, 0.0, 1E-12);
+    }
+
+    #[test]
+    fn nelder_mead_rosenbrock() {
+        let f = |x: &[f64]| {
+            (1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0] * x[0]).powi(2)
+        };
+        let (x, _) = nelder_mead(f, &[-1.2, 1.0], 0.5, 1E-20, 5_000);
+        assert::close(x[0], 1.0, 1E-4);
+        assert::close(x[1], 1.0, 1E-4);
+    }
+
+    #[test]
+    fn nelder_mead_avoids_nan_region() {
+        let f = |x: &[f64]| {
+            if x[0] < 0.0 {
+                f64::NAN
+            } else {
+                (x[0] - 0.1).powi(2)
+            }
+        };
+        let (x, _) = nelder_mead(f, &[2.0], 1.0, 1E-16, 1_000);
+        assert::close(x[0], 0.1, 1E-6);
+    }
+}
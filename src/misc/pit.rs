@@ -0,0 +1,173 @@
+//! Calibration checks for probabilistic forecasts
+use crate::misc::ks_test;
+use crate::traits::{Cdf, DiscreteDistr, Mean, Variance};
+use rand::Rng;
+
+/// Probability integral transforms of the observations `xs` under their
+/// forecast distributions.
+///
+/// The PIT of `xs[i]` is `forecasts[i].cdf(&xs[i])`. For continuous
+/// forecasts that are calibrated, the PITs are uniform on [0, 1].
+///
+/// # Panics
+/// If `forecasts` and `xs` have different lengths
+pub fn pit_values<X, Fx>(forecasts: &[Fx], xs: &[X]) -> Vec<f64>
+where
+    Fx: Cdf<X>,
+{
+    assert_eq!(
+        forecasts.len(),
+        xs.len(),
+        "forecasts and xs must be the same length"
+    );
+    forecasts.iter().zip(xs).map(|(fx, x)| fx.pit(x)).collect()
+}
+
+/// Randomized probability integral transforms of discrete observations
+/// under their forecast distributions.
+///
+/// See [`Cdf::randomized_pit`].
+///
+/// # Panics
+/// If `forecasts` and `xs` have different lengths
+pub fn randomized_pit_values<X, Fx, R>(
+    forecasts: &[Fx],
+    xs: &[X],
+    rng: &mut R,
+) -> Vec<f64>
+where
+    Fx: Cdf<X> + DiscreteDistr<X>,
+    R: Rng,
+{
+    assert_eq!(
+        forecasts.len(),
+        xs.len(),
+        "forecasts and xs must be the same length"
+    );
+    forecasts
+        .iter()
+        .zip(xs)
+        .map(|(fx, x)| fx.randomized_pit(x, rng))
+        .collect()
+}
+
+/// Test whether probability integral transforms are uniform on [0, 1].
+///
+/// Uses the one-sample Kolmogorov-Smirnov test against U(0, 1). A small
+/// p-value indicates that the forecasts are miscalibrated.
+///
+/// # Returns
+/// `(stat, p)`: the KS statistic and the p-value
+///
+/// # Example
+///
+/// Forecasts that are too narrow are flagged
+///
+/// ```rust
+/// use rv::dist::Gaussian;
+/// use rv::misc::{pit_uniformity, pit_values};
+/// use rv::traits::*;
+///
+/// let mut rng = rand::thread_rng();
+/// let truth = Gaussian::standard();
+/// let xs: Vec<f64> = truth.sample(500, &mut rng);
+///
+/// let calibrated = vec![Gaussian::standard(); 500];
+/// let narrow = vec![Gaussian::new(0.0, 0.5).unwrap(); 500];
+///
+/// let (_, p) = pit_uniformity(&pit_values(&calibrated, &xs));
+/// assert!(p > 1E-4);
+/// let (_, p) = pit_uniformity(&pit_values(&narrow, &xs));
+/// assert!(p < 1E-4);
+/// ```
+pub fn pit_uniformity(pits: &[f64]) -> (f64, f64) {
+    ks_test(pits, |u: f64| u.clamp(0.0, 1.0))
+}
+
+/// Standardized residuals, `(x - mean) / std`, of the observations `xs`
+/// under their forecast distributions.
+///
+/// The residual is NaN if the mean or variance of a forecast is undefined.
+///
+/// # Panics
+/// If `forecasts` and `xs` have different lengths
+pub fn standardized_residuals<Fx>(forecasts: &[Fx], xs: &[f64]) -> Vec<f64>
+where
+    Fx: Mean<f64> + Variance<f64>,
+{
+    assert_eq!(
+        forecasts.len(),
+        xs.len(),
+        "forecasts and xs must be the same length"
+    );
+    forecasts
+        .iter()
+        .zip(xs)
+        .map(|(fx, x)| match (fx.mean(), fx.variance()) {
+            (Some(mean), Some(var)) => (x - mean) / var.sqrt(),
+            _ => f64::NAN,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Gaussian, Poisson, StudentsT};
+    use crate::traits::Rv;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    #[test]
+    fn calibrated_forecasts_have_uniform_pits() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x5EED);
+        let forecasts: Vec<Gaussian> = (0..400)
+            .map(|i| Gaussian::new(f64::from(i) / 100.0, 1.5).unwrap())
+            .collect();
+        let xs: Vec<f64> =
+            forecasts.iter().map(|fx| fx.draw(&mut rng)).collect();
+
+        let (_, p) = pit_uniformity(&pit_values(&forecasts, &xs));
+        assert!(p > 0.01);
+
+        let biased: Vec<Gaussian> = forecasts
+            .iter()
+            .map(|fx| Gaussian::new(fx.mu() + 1.0, 1.5).unwrap())
+            .collect();
+        let (_, p) = pit_uniformity(&pit_values(&biased, &xs));
+        assert!(p < 0.01);
+    }
+
+    #[test]
+    fn randomized_pit_is_uniform_for_discrete_forecasts() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x5EED);
+        let forecasts = vec![Poisson::new(1.5).unwrap(); 500];
+        let xs: Vec<u32> =
+            forecasts.iter().map(|fx| fx.draw(&mut rng)).collect();
+
+        let pits = randomized_pit_values(&forecasts, &xs, &mut rng);
+        assert!(pits.iter().all(|u| (0.0..=1.0).contains(u)));
+        let (_, p) = pit_uniformity(&pits);
+        assert!(p > 0.01);
+
+        // The plain PIT of a count is not uniform
+        let (_, p) = pit_uniformity(&pit_values(&forecasts, &xs));
+        assert!(p < 0.01);
+    }
+
+    #[test]
+    fn standardized_residual_values() {
+        let forecasts = [
+            Gaussian::new(1.0, 2.0).unwrap(),
+            Gaussian::new(-1.0, 0.5).unwrap(),
+        ];
+        let zs = standardized_residuals(&forecasts, &[2.0, -2.0]);
+        assert::close(zs, vec![0.5, -2.0], TOL);
+
+        let zs =
+            standardized_residuals(&[StudentsT::new(1.5).unwrap()], &[0.0]);
+        assert!(zs[0].is_nan());
+    }
+}
@@ -0,0 +1,228 @@
+//! Circular statistics
+//!
+//! Summaries and tests for angles, e.g., data modeled with
+//! [`VonMises`](crate::dist::VonMises). Angles are in radians.
+
+/// Mean of the cosines and sines of the angles
+fn mean_cos_sin(angles: &[f64]) -> (f64, f64) {
+    let n = angles.len() as f64;
+    let (c, s) = angles
+        .iter()
+        .fold((0.0, 0.0), |(c, s), a| (c + a.cos(), s + a.sin()));
+    (c / n, s / n)
+}
+
+/// Pearson correlation of two equal-length samples
+fn correlation(xs: &[f64], ys: &[f64]) -> f64 {
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = ys.iter().sum::<f64>() / n;
+    let (sxy, sxx, syy) = xs.iter().zip(ys.iter()).fold(
+        (0.0, 0.0, 0.0),
+        |(sxy, sxx, syy), (x, y)| {
+            let dx = x - mean_x;
+            let dy = y - mean_y;
+            (
+                dx.mul_add(dy, sxy),
+                dx.mul_add(dx, sxx),
+                dy.mul_add(dy, syy),
+            )
+        },
+    );
+    sxy / (sxx * syy).sqrt()
+}
+
+/// Mean resultant length, `R̄`, of a set of angles
+///
+/// `R̄` is in [0, 1]. It is 1 when all the angles are the same and near 0
+/// when the angles are spread evenly around the circle.
+///
+/// # Panics
+/// If `angles` is empty
+pub fn mean_resultant_length(angles: &[f64]) -> f64 {
+    assert!(!angles.is_empty(), "angles must not be empty");
+    let (c, s) = mean_cos_sin(angles);
+    c.hypot(s)
+}
+
+/// Circular mean of a set of angles in (-π, π]
+///
+/// # Example
+///
+/// The arithmetic mean of angles near ±π is near 0, which is the opposite
+/// direction.
+///
+/// ```
+/// use rv::misc::stats::circular_mean;
+/// use std::f64::consts::PI;
+///
+/// let mean = circular_mean(&[PI - 0.1, -PI + 0.1]);
+/// assert!((mean.abs() - PI).abs() < 1E-12);
+/// ```
+///
+/// # Panics
+/// If `angles` is empty
+pub fn circular_mean(angles: &[f64]) -> f64 {
+    assert!(!angles.is_empty(), "angles must not be empty");
+    let (c, s) = mean_cos_sin(angles);
+    s.atan2(c)
+}
+
+/// Circular variance, `1 - R̄`, of a set of angles
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::stats::circular_variance;
+/// use std::f64::consts::PI;
+///
+/// assert!(circular_variance(&[1.0, 1.0, 1.0]) < 1E-12);
+///
+/// let spread = [0.0, PI / 2.0, PI, 3.0 * PI / 2.0];
+/// assert!((circular_variance(&spread) - 1.0).abs() < 1E-12);
+/// ```
+///
+/// # Panics
+/// If `angles` is empty
+pub fn circular_variance(angles: &[f64]) -> f64 {
+    1.0 - mean_resultant_length(angles)
+}
+
+/// Circular standard deviation, `sqrt(-2 ln R̄)`, of a set of angles
+///
+/// # Panics
+/// If `angles` is empty
+pub fn circular_std(angles: &[f64]) -> f64 {
+    (-2.0 * mean_resultant_length(angles).ln()).sqrt()
+}
+
+/// [Rayleigh test](https://en.wikipedia.org/wiki/Rayleigh_test) for
+/// uniformity on the circle.
+///
+/// Tests against a unimodal alternative. Returns the statistic, `Z = n R̄²`,
+/// and its p-value using Zar's (1999) approximation.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::VonMises;
+/// use rv::misc::stats::rayleigh_test;
+/// use rv::traits::Rv;
+///
+/// let mut rng = rand::thread_rng();
+/// let vm = VonMises::new(1.0, 2.0).unwrap();
+/// let angles: Vec<f64> = vm.sample(100, &mut rng);
+///
+/// let (_, p) = rayleigh_test(&angles);
+/// assert!(p < 0.01);
+/// ```
+///
+/// # Panics
+/// If `angles` is empty
+pub fn rayleigh_test(angles: &[f64]) -> (f64, f64) {
+    let n = angles.len() as f64;
+    let r_bar = mean_resultant_length(angles);
+    let z = n * r_bar * r_bar;
+    let r_n = n * r_bar;
+    let p = 4.0_f64
+        .mul_add(n.mul_add(n, -r_n * r_n), 4.0_f64.mul_add(n, 1.0))
+        .sqrt()
+        - 2.0_f64.mul_add(n, 1.0);
+    (z, p.exp().min(1.0))
+}
+
+/// Circular-linear correlation between angles and a linear variable
+///
+/// Returns Mardia's correlation coefficient, `r` in [0, 1], and its
+/// asymptotic p-value from `n r² ~ χ²(2)` under independence.
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::stats::circular_linear_correlation;
+///
+/// // Angle increases with x
+/// let xs: Vec<f64> = (0..50).map(f64::from).collect();
+/// let angles: Vec<f64> = xs.iter().map(|x| x / 20.0).collect();
+///
+/// let (r, p) = circular_linear_correlation(&angles, &xs);
+/// assert!(r > 0.9);
+/// assert!(p < 0.001);
+/// ```
+///
+/// # Panics
+/// If `angles` and `xs` have different lengths or fewer than 3 entries
+pub fn circular_linear_correlation(angles: &[f64], xs: &[f64]) -> (f64, f64) {
+    assert_eq!(
+        angles.len(),
+        xs.len(),
+        "angles and xs must be the same length"
+    );
+    assert!(angles.len() > 2, "need at least 3 observations");
+
+    let cos: Vec<f64> = angles.iter().map(|a| a.cos()).collect();
+    let sin: Vec<f64> = angles.iter().map(|a| a.sin()).collect();
+    let r_xc = correlation(xs, &cos);
+    let r_xs = correlation(xs, &sin);
+    let r_cs = correlation(&cos, &sin);
+
+    let r2 = (2.0 * r_xc * r_xs)
+        .mul_add(-r_cs, r_xc.mul_add(r_xc, r_xs * r_xs))
+        / r_cs.mul_add(-r_cs, 1.0);
+    let r2 = r2.clamp(0.0, 1.0);
+    let n = xs.len() as f64;
+    (r2.sqrt(), (-n * r2 / 2.0).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    const TOL: f64 = 1E-12;
+
+    #[test]
+    fn circular_mean_wraps() {
+        let mean = circular_mean(&[0.1, 2.0_f64.mul_add(PI, -0.1), 0.2, -0.2]);
+        assert::close(mean, 0.0, TOL);
+    }
+
+    #[test]
+    fn circular_std_of_identical_angles_is_zero() {
+        assert::close(
+            circular_std(&[0.4, 2.0_f64.mul_add(PI, 0.4)]),
+            0.0,
+            1E-7,
+        );
+    }
+
+    #[test]
+    fn rayleigh_uniform_angles() {
+        let angles: Vec<f64> =
+            (0..24).map(|i| f64::from(i) * PI / 12.0).collect();
+        let (z, p) = rayleigh_test(&angles);
+        assert::close(z, 0.0, 1E-10);
+        assert!(p > 0.99);
+    }
+
+    #[test]
+    fn rayleigh_concentrated_angles() {
+        // Nine angles at 0 and one at π/2, so R̄ = √82 / 10
+        let mut angles = vec![0.0; 9];
+        angles.push(PI / 2.0);
+        let (z, p) = rayleigh_test(&angles);
+        assert::close(z, 8.2, 1E-10);
+        assert!(p < 0.001);
+    }
+
+    #[test]
+    fn circular_linear_correlation_of_independent_is_small() {
+        let xs: Vec<f64> = (0..40).map(f64::from).collect();
+        // Golden angle steps, unrelated to the trend in x
+        let angles: Vec<f64> =
+            (0..40).map(|i| f64::from(i) * 2.399_963).collect();
+        let (r, p) = circular_linear_correlation(&angles, &xs);
+        assert!(r < 0.1);
+        assert!(p > 0.5);
+    }
+}
@@ -0,0 +1,334 @@
+use crate::dist::{Beta, ChiSquared, Gaussian};
+use crate::misc::gauss_legendre_quadrature;
+use crate::traits::{Cdf, ContinuousDistr, InverseCdf};
+
+/// Confidence that the interval between order statistics `X_(r)` and
+/// `X_(n + 1 - s)` contains at least `content` of the population.
+///
+/// Holds for any continuous distribution. `r` counts up from the smallest
+/// observation and `s` down from the largest, both starting at 1, so `r = s
+/// = 1` is the interval between the sample minimum and maximum. The
+/// coverage of the interval follows `Beta(n + 1 - r - s, r + s)`. A value
+/// of 0 for `r` or `s` means the interval is unbounded on that side.
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::tolerance_confidence;
+///
+/// // With 59 observations, the maximum bounds 95% of the population with
+/// // 95% confidence
+/// let conf = tolerance_confidence(59, 0, 1, 0.95);
+/// assert!(conf > 0.95);
+/// assert!(tolerance_confidence(58, 0, 1, 0.95) < 0.95);
+/// ```
+///
+/// # Panics
+/// If `r + s` is zero or greater than `n`, or `content` is not in (0, 1)
+pub fn tolerance_confidence(n: usize, r: usize, s: usize, content: f64) -> f64 {
+    assert!(r + s > 0 && r + s <= n, "need 0 < r + s <= n");
+    assert!(0.0 < content && content < 1.0, "content must be in (0, 1)");
+    let coverage = Beta::new((n + 1 - r - s) as f64, (r + s) as f64).unwrap();
+    1.0 - coverage.cdf(&content)
+}
+
+/// Two-sided nonparametric tolerance interval from order statistics
+///
+/// Returns the narrowest interval `[X_(r), X_(n + 1 - r)]` that contains at
+/// least `content` of the population with probability `confidence`, or
+/// `None` if there are too few observations for even the sample range to
+/// suffice.
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::nonparametric_tolerance_interval;
+///
+/// let xs: Vec<f64> = (0..200).map(|i| f64::from(i) / 2.0).collect();
+/// let (lower, upper) =
+///     nonparametric_tolerance_interval(&xs, 0.9, 0.95).unwrap();
+/// assert!(lower > 0.0 && upper < 99.5);
+///
+/// // Ten observations are not enough
+/// assert!(nonparametric_tolerance_interval(&xs[..10], 0.9, 0.95).is_none());
+/// ```
+///
+/// # Panics
+/// If `content` or `confidence` is not in (0, 1)
+pub fn nonparametric_tolerance_interval<X>(
+    xs: &[X],
+    content: f64,
+    confidence: f64,
+) -> Option<(X, X)>
+where
+    X: Copy + PartialOrd,
+{
+    assert!(
+        0.0 < confidence && confidence < 1.0,
+        "confidence must be in (0, 1)"
+    );
+    let n = xs.len();
+    // Confidence decreases as the interval shrinks
+    let r = (1..=n / 2)
+        .take_while(|&r| tolerance_confidence(n, r, r, content) >= confidence)
+        .last()?;
+
+    let mut xs = xs.to_vec();
+    xs.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+    Some((xs[r - 1], xs[n - r]))
+}
+
+/// Two-sided tolerance factor, `k`, for normally distributed data
+///
+/// `x̄ ± k s` contains at least `content` of the population with
+/// probability `confidence`, where `x̄` and `s` are the mean and standard
+/// deviation of `n` observations. Uses Howe's approximation,
+///
+/// ```math
+/// k = z_{(1 + p) / 2} \sqrt{\frac{(n - 1)(1 + 1/n)}{\chi^2_{1 - \gamma, n - 1}}}
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::gaussian_tolerance_factor;
+///
+/// let k = gaussian_tolerance_factor(10, 0.9, 0.95);
+/// assert::close(k, 2.839, 1E-3);
+/// ```
+///
+/// # Panics
+/// If `n < 2` or `content` or `confidence` is not in (0, 1)
+pub fn gaussian_tolerance_factor(
+    n: usize,
+    content: f64,
+    confidence: f64,
+) -> f64 {
+    assert!(n > 1, "need at least two observations");
+    assert!(0.0 < content && content < 1.0, "content must be in (0, 1)");
+    assert!(
+        0.0 < confidence && confidence < 1.0,
+        "confidence must be in (0, 1)"
+    );
+    let nf = n as f64;
+    let z: f64 = Gaussian::standard().invcdf((1.0 + content) / 2.0);
+    let x2 = chi_squared_quantile(nf - 1.0, 1.0 - confidence);
+    z * ((nf - 1.0) * (1.0 + 1.0 / nf) / x2).sqrt()
+}
+
+/// Two-sided tolerance interval, `x̄ ± k s`, for normally distributed data
+///
+/// See [`gaussian_tolerance_factor`].
+///
+/// # Panics
+/// If `xs` has fewer than two observations or `content` or `confidence` is
+/// not in (0, 1)
+pub fn gaussian_tolerance_interval(
+    xs: &[f64],
+    content: f64,
+    confidence: f64,
+) -> (f64, f64) {
+    let k = gaussian_tolerance_factor(xs.len(), content, confidence);
+    let nf = xs.len() as f64;
+    let mean = xs.iter().sum::<f64>() / nf;
+    let sd = (xs.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>()
+        / (nf - 1.0))
+        .sqrt();
+    (k.mul_add(-sd, mean), k.mul_add(sd, mean))
+}
+
+/// CDF of the range of `n` observations from `Uniform(0, 1)`
+///
+/// The range of `n` observations from `Uniform(a, b)` is `(b - a)` times
+/// this range.
+pub fn uniform_range_cdf(n: usize, w: f64) -> f64 {
+    assert!(n > 1, "need at least two observations");
+    if w <= 0.0 {
+        0.0
+    } else if w >= 1.0 {
+        1.0
+    } else {
+        let nf = n as f64;
+        let wn1 = w.powi(n as i32 - 1);
+        wn1 * (nf - 1.0).mul_add(-w, nf)
+    }
+}
+
+/// PDF of the range of `n` observations from `Uniform(0, 1)`
+pub fn uniform_range_pdf(n: usize, w: f64) -> f64 {
+    assert!(n > 1, "need at least two observations");
+    if w <= 0.0 || w >= 1.0 {
+        0.0
+    } else {
+        let nf = n as f64;
+        nf * (nf - 1.0) * w.powi(n as i32 - 2) * (1.0 - w)
+    }
+}
+
+/// Integrate over the real line, where the integrand is negligible outside
+/// of (-10, 10)
+fn integrate_real_line<F: Fn(f64) -> f64>(f: F) -> f64 {
+    (0..20)
+        .map(|i| {
+            let a = f64::from(i) - 10.0;
+            gauss_legendre_quadrature(&f, 16, (a, a + 1.0))
+        })
+        .sum()
+}
+
+/// CDF of the range of `n` observations from the standard normal
+///
+/// The range of `n` observations from `N(μ, σ²)` is `σ` times this range.
+/// Computed by quadrature of
+///
+/// ```math
+/// P(R \le w) = n \int \phi(x) [\Phi(x + w) - \Phi(x)]^{n - 1} dx
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::gaussian_range_cdf;
+///
+/// // The range of two observations is |X - Y| ~ Half-normal(√2)
+/// assert::close(gaussian_range_cdf(2, 1.0), 0.520_499_877_8, 1E-8);
+/// ```
+pub fn gaussian_range_cdf(n: usize, w: f64) -> f64 {
+    assert!(n > 1, "need at least two observations");
+    if w <= 0.0 {
+        return 0.0;
+    }
+    let g = Gaussian::standard();
+    let nf = n as f64;
+    let p = integrate_real_line(|x| {
+        let d: f64 = g.cdf(&(x + w)) - g.cdf(&x);
+        nf * g.pdf(&x) * d.powi(n as i32 - 1)
+    });
+    p.clamp(0.0, 1.0)
+}
+
+/// PDF of the range of `n` observations from the standard normal
+///
+/// See [`gaussian_range_cdf`].
+pub fn gaussian_range_pdf(n: usize, w: f64) -> f64 {
+    assert!(n > 1, "need at least two observations");
+    if w <= 0.0 {
+        return 0.0;
+    }
+    let g = Gaussian::standard();
+    let nf = n as f64;
+    integrate_real_line(|x| {
+        let d: f64 = g.cdf(&(x + w)) - g.cdf(&x);
+        nf * (nf - 1.0) * g.pdf(&x) * g.pdf(&(x + w)) * d.powi(n as i32 - 2)
+    })
+}
+
+/// Expected range of `n` observations from the standard normal
+///
+/// This is the `d2` constant used to estimate `σ` from ranges in control
+/// charts.
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::gaussian_range_mean;
+///
+/// assert::close(gaussian_range_mean(2), 1.128, 1E-3);
+/// assert::close(gaussian_range_mean(5), 2.326, 1E-3);
+/// ```
+pub fn gaussian_range_mean(n: usize) -> f64 {
+    assert!(n > 1, "need at least two observations");
+    let g = Gaussian::standard();
+    integrate_real_line(|x| {
+        let p: f64 = g.cdf(&x);
+        1.0 - p.powi(n as i32) - (1.0 - p).powi(n as i32)
+    })
+}
+
+/// The `p` quantile of the chi-squared distribution with `k` degrees of
+/// freedom
+fn chi_squared_quantile(k: f64, p: f64) -> f64 {
+    let x2 = ChiSquared::new_unchecked(k);
+    let mut lower = 0.0;
+    let mut upper = k.max(1.0);
+    while x2.cdf(&upper) < p {
+        lower = upper;
+        upper *= 2.0;
+    }
+    for _ in 0..100 {
+        let mid = (lower + upper) / 2.0;
+        if x2.cdf(&mid) < p {
+            lower = mid;
+        } else {
+            upper = mid;
+        }
+    }
+    (lower + upper) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOL: f64 = 1E-10;
+
+    #[test]
+    fn confidence_of_sample_range() {
+        // Coverage of (min, max) is Beta(n - 1, 2)
+        let n = 20;
+        let p = 0.9_f64;
+        let expected = p.powi(19).mul_add(-19.0_f64.mul_add(-p, 20.0), 1.0);
+        assert::close(tolerance_confidence(n, 1, 1, p), expected, TOL);
+    }
+
+    #[test]
+    fn nonparametric_interval_meets_confidence() {
+        let xs: Vec<f64> = (0..100).map(f64::from).collect();
+        let (lower, upper) =
+            nonparametric_tolerance_interval(&xs, 0.8, 0.9).unwrap();
+        let r = lower as usize + 1;
+        assert_eq!(upper as usize, 100 - r);
+        assert!(tolerance_confidence(100, r, r, 0.8) >= 0.9);
+        assert!(tolerance_confidence(100, r + 1, r + 1, 0.8) < 0.9);
+    }
+
+    #[test]
+    fn chi_squared_quantile_inverts_cdf() {
+        let x = chi_squared_quantile(9.0, 0.05);
+        assert::close(x, 3.325_112_843_066_815, 1E-8);
+    }
+
+    #[test]
+    fn uniform_range_pdf_integrates_to_cdf() {
+        let cdf = gauss_legendre_quadrature(
+            |w| uniform_range_pdf(6, w),
+            16,
+            (0.0, 0.7),
+        );
+        assert::close(cdf, uniform_range_cdf(6, 0.7), TOL);
+    }
+
+    #[test]
+    fn gaussian_range_pdf_integrates_to_cdf() {
+        let cdf = (0..8)
+            .map(|i| {
+                let a = f64::from(i) * 0.25;
+                gauss_legendre_quadrature(
+                    |w| gaussian_range_pdf(4, w),
+                    16,
+                    (a, a + 0.25),
+                )
+            })
+            .sum::<f64>();
+        assert::close(cdf, gaussian_range_cdf(4, 2.0), 1E-8);
+    }
+
+    #[test]
+    fn gaussian_interval_is_centered() {
+        let xs = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        let (lower, upper) = gaussian_tolerance_interval(&xs, 0.9, 0.95);
+        assert::close((lower + upper) / 2.0, 3.0, TOL);
+        let k = gaussian_tolerance_factor(5, 0.9, 0.95);
+        assert::close(upper - 3.0, k * 2.5_f64.sqrt(), TOL);
+    }
+}
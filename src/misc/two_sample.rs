@@ -0,0 +1,508 @@
+//! Tests of whether two samples were drawn from the same distribution
+#[cfg(feature = "arraydist")]
+use nalgebra::{DMatrix, DVector};
+#[cfg(feature = "arraydist")]
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+#[cfg(feature = "arraydist")]
+use rand::SeedableRng;
+use special::Error as _;
+use std::f64::consts::SQRT_2;
+use std::fmt;
+
+#[cfg(feature = "process")]
+use crate::process::gaussian::kernel::Kernel;
+#[cfg(all(feature = "arraydist", feature = "rayon"))]
+use rayon::prelude::*;
+
+/// Errors from two-sample tests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TwoSampleError {
+    /// One of the samples is empty
+    EmptySample,
+    /// The observations in the samples have different dimensions
+    DimensionMismatch {
+        /// Dimension of the first offending observation in `xs`
+        n_dims_x: usize,
+        /// Dimension of the first offending observation in `ys`
+        n_dims_y: usize,
+    },
+}
+
+/// Multivariate two-sample [energy distance
+/// test](https://en.wikipedia.org/wiki/Energy_distance).
+///
+/// Tests whether `xs` and `ys` were drawn from the same distribution. Returns
+/// the energy distance
+///
+/// ```math
+/// 2 E||X - Y|| - E||X - X'|| - E||Y - Y'||
+/// ```
+///
+/// and its p-value from `n_perms` random permutations of the pooled sample.
+/// With the `rayon` feature, the permutations run in parallel.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::DVector;
+/// use rv::misc::energy_test;
+///
+/// let mut rng = rand::thread_rng();
+///
+/// let xs: Vec<DVector<f64>> = (0..30)
+///     .map(|i| DVector::from_vec(vec![(i % 5) as f64, (i % 3) as f64]))
+///     .collect();
+/// let ys: Vec<DVector<f64>> =
+///     xs.iter().map(|x| x.add_scalar(3.0)).collect();
+///
+/// let (_, p) = energy_test(&xs, &ys, 200, &mut rng).unwrap();
+/// assert!(p < 0.05);
+/// ```
+#[cfg(feature = "arraydist")]
+pub fn energy_test<R: Rng>(
+    xs: &[DVector<f64>],
+    ys: &[DVector<f64>],
+    n_perms: usize,
+    rng: &mut R,
+) -> Result<(f64, f64), TwoSampleError> {
+    validate(xs, ys)?;
+
+    let pooled: Vec<&DVector<f64>> = xs.iter().chain(ys.iter()).collect();
+    let n = pooled.len();
+    let dists = DMatrix::from_fn(n, n, |i, j| (pooled[i] - pooled[j]).norm());
+
+    // The energy distance is the MMD with the negative distance as kernel
+    let neg_dists = -dists;
+    Ok(mmd_permutation_test(&neg_dists, xs.len(), n_perms, rng))
+}
+
+/// Multivariate two-sample [maximum mean
+/// discrepancy](https://en.wikipedia.org/wiki/Kernel_embedding_of_distributions#Kernel_two-sample_test)
+/// test.
+///
+/// Tests whether `xs` and `ys` were drawn from the same distribution.
+/// Observations are the rows of `xs` and `ys`, and `kernel` is any Gaussian
+/// process kernel. Returns the biased estimate of the squared MMD,
+///
+/// ```math
+/// E[k(X, X')] + E[k(Y, Y')] - 2 E[k(X, Y)]
+/// ```
+///
+/// and its p-value from `n_perms` random permutations of the pooled sample.
+/// With the `rayon` feature, the permutations run in parallel.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::DMatrix;
+/// use rv::misc::mmd_test;
+/// use rv::process::gaussian::kernel::RBFKernel;
+///
+/// let mut rng = rand::thread_rng();
+///
+/// let xs = DMatrix::from_fn(30, 2, |i, j| ((i + j) % 5) as f64 / 5.0);
+/// let ys = xs.add_scalar(2.0);
+///
+/// let kernel = RBFKernel::new(1.0).unwrap();
+/// let (_, p) = mmd_test(&xs, &ys, &kernel, 200, &mut rng).unwrap();
+/// assert!(p < 0.05);
+/// ```
+#[cfg(feature = "process")]
+pub fn mmd_test<K, R>(
+    xs: &DMatrix<f64>,
+    ys: &DMatrix<f64>,
+    kernel: &K,
+    n_perms: usize,
+    rng: &mut R,
+) -> Result<(f64, f64), TwoSampleError>
+where
+    K: Kernel,
+    R: Rng,
+{
+    if xs.nrows() == 0 || ys.nrows() == 0 {
+        return Err(TwoSampleError::EmptySample);
+    } else if xs.ncols() != ys.ncols() {
+        return Err(TwoSampleError::DimensionMismatch {
+            n_dims_x: xs.ncols(),
+            n_dims_y: ys.ncols(),
+        });
+    }
+
+    let mut pooled = xs.clone().resize_vertically(xs.nrows() + ys.nrows(), 0.0);
+    pooled.rows_mut(xs.nrows(), ys.nrows()).copy_from(ys);
+    let gram = kernel.covariance(&pooled, &pooled);
+
+    Ok(mmd_permutation_test(&gram, xs.nrows(), n_perms, rng))
+}
+
+/// Two-sample [permutation test](https://en.wikipedia.org/wiki/Permutation_test)
+/// with a user-defined statistic.
+///
+/// Tests whether `xs` and `ys` were drawn from the same distribution by
+/// comparing `statistic(xs, ys)` with its value on `n_perms` random
+/// relabelings of the pooled sample. Larger values of the statistic must
+/// indicate a greater difference between the samples.
+///
+/// # Returns
+/// `(stat, p)`: the observed statistic and its p-value. The observed
+/// statistic is counted as one of the permutations, so the p-value is never
+/// zero.
+///
+/// # Example
+///
+/// Test for a difference in medians
+///
+/// ```
+/// use rv::misc::permutation_test;
+///
+/// let median = |xs: &[f64]| {
+///     let mut xs = xs.to_vec();
+///     xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+///     xs[xs.len() / 2]
+/// };
+/// let median_diff = |xs: &[f64], ys: &[f64]| (median(xs) - median(ys)).abs();
+///
+/// let xs: Vec<f64> = (0..30).map(|i| f64::from(i) / 10.0).collect();
+/// let ys: Vec<f64> = xs.iter().map(|x| x + 2.0).collect();
+///
+/// let mut rng = rand::thread_rng();
+/// let (stat, p) = permutation_test(&xs, &ys, median_diff, 500, &mut rng)
+///     .unwrap();
+///
+/// assert!((stat - 2.0).abs() < 1E-12);
+/// assert!(p < 0.01);
+/// ```
+pub fn permutation_test<X, F, R>(
+    xs: &[X],
+    ys: &[X],
+    statistic: F,
+    n_perms: usize,
+    rng: &mut R,
+) -> Result<(f64, f64), TwoSampleError>
+where
+    X: Clone,
+    F: Fn(&[X], &[X]) -> f64,
+    R: Rng,
+{
+    if xs.is_empty() || ys.is_empty() {
+        return Err(TwoSampleError::EmptySample);
+    }
+
+    let stat = statistic(xs, ys);
+    let mut pooled: Vec<X> = xs.iter().chain(ys.iter()).cloned().collect();
+    let n_exceed = (0..n_perms)
+        .filter(|_| {
+            pooled.shuffle(rng);
+            let (perm_xs, perm_ys) = pooled.split_at(xs.len());
+            statistic(perm_xs, perm_ys) >= stat
+        })
+        .count();
+
+    let p = (n_exceed + 1) as f64 / (n_perms + 1) as f64;
+    Ok((stat, p))
+}
+
+/// Two-sided [Mann-Whitney U](https://en.wikipedia.org/wiki/Mann%E2%80%93Whitney_U_test)
+/// test.
+///
+/// Tests whether values in `xs` tend to be larger or smaller than values in
+/// `ys`. Ties get their average rank. For a test that is sensitive to any
+/// difference between the distributions, see
+/// [`ks_two_sample`](crate::misc::ks_two_sample).
+///
+/// # Returns
+/// `(u, p)`: `u` is the U statistic of `xs`, the number of pairs in which
+/// the value from `xs` is larger, counting ties as one half. `p` is the
+/// two-sided p-value from the normal approximation with tie and continuity
+/// corrections, which is accurate when both samples have more than about 10
+/// observations.
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::mann_whitney_u;
+///
+/// let xs = [1.2, 3.4, 0.5, 2.2, 1.9, 0.7, 2.8, 1.1, 0.9, 2.0];
+/// let ys = [3.1, 4.5, 2.9, 5.2, 3.8, 4.1, 2.5, 3.3, 4.9, 3.6];
+///
+/// let (u, p) = mann_whitney_u(&xs, &ys).unwrap();
+/// assert_eq!(u, 5.0);
+/// assert!(p < 0.01);
+/// ```
+pub fn mann_whitney_u<X>(
+    xs: &[X],
+    ys: &[X],
+) -> Result<(f64, f64), TwoSampleError>
+where
+    X: Copy + PartialOrd,
+{
+    if xs.is_empty() || ys.is_empty() {
+        return Err(TwoSampleError::EmptySample);
+    }
+
+    let mut pooled: Vec<(X, bool)> = xs
+        .iter()
+        .map(|&x| (x, true))
+        .chain(ys.iter().map(|&y| (y, false)))
+        .collect();
+    pooled.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    // Sum the ranks of xs, averaging over runs of ties
+    let n = pooled.len();
+    let mut rank_sum_x = 0.0;
+    let mut tie_sum = 0.0;
+    let mut start = 0;
+    while start < n {
+        let end = start
+            + pooled[start..]
+                .iter()
+                .take_while(|(z, _)| *z == pooled[start].0)
+                .count();
+        let rank = (start + end + 1) as f64 / 2.0;
+        let n_x_tied = pooled[start..end].iter().filter(|(_, is_x)| *is_x);
+        rank_sum_x += rank * n_x_tied.count() as f64;
+        let t = (end - start) as f64;
+        tie_sum += t.mul_add(t * t, -t);
+        start = end;
+    }
+
+    let n_x = xs.len() as f64;
+    let n_y = ys.len() as f64;
+    let n_f = n as f64;
+    let u = rank_sum_x - n_x * (n_x + 1.0) / 2.0;
+
+    let mean = n_x * n_y / 2.0;
+    let var = n_x * n_y / 12.0 * (n_f + 1.0 - tie_sum / (n_f * (n_f - 1.0)));
+    if var <= 0.0 {
+        // Every value is tied
+        return Ok((u, 1.0));
+    }
+
+    let z = ((u - mean).abs() - 0.5).max(0.0) / var.sqrt();
+    let p = (z / SQRT_2).compl_error().min(1.0);
+    Ok((u, p))
+}
+
+#[cfg(feature = "arraydist")]
+fn validate(
+    xs: &[DVector<f64>],
+    ys: &[DVector<f64>],
+) -> Result<(), TwoSampleError> {
+    if xs.is_empty() || ys.is_empty() {
+        return Err(TwoSampleError::EmptySample);
+    }
+    let n_dims = xs[0].len();
+    let bad_x = xs.iter().find(|x| x.len() != n_dims);
+    let bad_y = ys.iter().find(|y| y.len() != n_dims);
+    match (bad_x, bad_y) {
+        (Some(x), _) => Err(TwoSampleError::DimensionMismatch {
+            n_dims_x: x.len(),
+            n_dims_y: ys[0].len(),
+        }),
+        (None, Some(y)) => Err(TwoSampleError::DimensionMismatch {
+            n_dims_x: n_dims,
+            n_dims_y: y.len(),
+        }),
+        (None, None) => Ok(()),
+    }
+}
+
+/// Squared MMD between the first `n_x` and remaining pooled observations,
+/// where `ixs` maps positions to rows of the Gram matrix
+#[cfg(feature = "arraydist")]
+fn mmd_stat(gram: &DMatrix<f64>, ixs: &[usize], n_x: usize) -> f64 {
+    let (xs, ys) = ixs.split_at(n_x);
+    let mean = |a: &[usize], b: &[usize]| {
+        let sum: f64 = a
+            .iter()
+            .map(|&i| b.iter().map(|&j| gram[(i, j)]).sum::<f64>())
+            .sum();
+        sum / (a.len() * b.len()) as f64
+    };
+    2.0_f64.mul_add(-mean(xs, ys), mean(xs, xs) + mean(ys, ys))
+}
+
+/// Statistic and permutation p-value of the MMD for a pooled Gram matrix
+#[cfg(feature = "arraydist")]
+fn mmd_permutation_test<R: Rng>(
+    gram: &DMatrix<f64>,
+    n_x: usize,
+    n_perms: usize,
+    rng: &mut R,
+) -> (f64, f64) {
+    let n = gram.nrows();
+    let ixs: Vec<usize> = (0..n).collect();
+    let stat = mmd_stat(gram, &ixs, n_x);
+
+    // Seed one generator per permutation so the result does not depend on
+    // how the permutations are scheduled
+    let seeds: Vec<u64> = (0..n_perms).map(|_| rng.gen()).collect();
+    let exceeds = |&seed: &u64| {
+        let mut perm_rng = SmallRng::seed_from_u64(seed);
+        let mut perm = ixs.clone();
+        perm.shuffle(&mut perm_rng);
+        mmd_stat(gram, &perm, n_x) >= stat
+    };
+
+    #[cfg(feature = "rayon")]
+    let n_exceed = seeds.par_iter().filter(|seed| exceeds(seed)).count();
+    #[cfg(not(feature = "rayon"))]
+    let n_exceed = seeds.iter().filter(|seed| exceeds(seed)).count();
+
+    // Count the observed statistic as one of the permutations
+    let p = (n_exceed + 1) as f64 / (n_perms + 1) as f64;
+    (stat, p)
+}
+
+impl std::error::Error for TwoSampleError {}
+
+impl fmt::Display for TwoSampleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptySample => write!(f, "one of the samples is empty"),
+            Self::DimensionMismatch { n_dims_x, n_dims_y } => write!(
+                f,
+                "observations have different dimensions: {} and {}",
+                n_dims_x, n_dims_y
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    #[cfg(feature = "arraydist")]
+    fn grid(n: usize, shift: f64) -> Vec<DVector<f64>> {
+        (0..n)
+            .map(|i| {
+                DVector::from_vec(vec![
+                    (i % 4) as f64 + shift,
+                    (i % 7) as f64 / 2.0,
+                ])
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "arraydist")]
+    #[test]
+    fn energy_distance_of_points() {
+        // One point each at distance 2: 2 * 2 - 0 - 0
+        let xs = vec![DVector::from_vec(vec![0.0, 0.0])];
+        let ys = vec![DVector::from_vec(vec![0.0, 2.0])];
+        let mut rng = Xoshiro256Plus::seed_from_u64(1);
+        let (stat, _) = energy_test(&xs, &ys, 10, &mut rng).unwrap();
+        assert::close(stat, 4.0, 1E-12);
+    }
+
+    #[cfg(feature = "arraydist")]
+    #[test]
+    fn energy_same_distribution_is_not_rejected() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(2);
+        let xs = grid(28, 0.0);
+        let mut ys = xs.clone();
+        ys.reverse();
+        let (stat, p) = energy_test(&xs, &ys, 100, &mut rng).unwrap();
+        assert::close(stat, 0.0, 1E-10);
+        assert!(p > 0.9);
+    }
+
+    #[cfg(feature = "arraydist")]
+    #[test]
+    fn energy_shift_is_rejected() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(3);
+        let (_, p) =
+            energy_test(&grid(28, 0.0), &grid(28, 1.5), 100, &mut rng).unwrap();
+        assert!(p < 0.05);
+    }
+
+    #[cfg(feature = "arraydist")]
+    #[test]
+    fn validates_inputs() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(4);
+        assert_eq!(
+            energy_test(&[], &grid(3, 0.0), 10, &mut rng),
+            Err(TwoSampleError::EmptySample)
+        );
+        let ys = vec![DVector::from_vec(vec![1.0, 2.0, 3.0])];
+        assert_eq!(
+            energy_test(&grid(3, 0.0), &ys, 10, &mut rng),
+            Err(TwoSampleError::DimensionMismatch {
+                n_dims_x: 2,
+                n_dims_y: 3
+            })
+        );
+    }
+
+    #[cfg(feature = "process")]
+    #[test]
+    fn mmd_detects_scale_change() {
+        use crate::process::gaussian::kernel::RBFKernel;
+
+        let mut rng = Xoshiro256Plus::seed_from_u64(5);
+        let xs = DMatrix::from_fn(30, 1, |i, _| (i as f64 - 14.5) / 10.0);
+        let ys = &xs * 3.0;
+        let kernel = RBFKernel::new(1.0).unwrap();
+
+        let (stat, p) = mmd_test(&xs, &ys, &kernel, 100, &mut rng).unwrap();
+        assert!(stat > 0.0);
+        assert!(p < 0.05);
+
+        let (_, p) = mmd_test(&xs, &xs, &kernel, 100, &mut rng).unwrap();
+        assert!(p > 0.5);
+    }
+
+    #[test]
+    fn mann_whitney_reference_values() {
+        // p-values from the normal approximation computed with mpmath
+        let (u, p) =
+            mann_whitney_u(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]).unwrap();
+        assert_eq!(u, 0.0);
+        assert::close(p, 0.080_855_598_370_052_28, 1E-10);
+
+        // With ties
+        let xs = [1_u32, 2, 2, 3, 5, 5, 7];
+        let ys = [2_u32, 3, 3, 4, 6, 8];
+        let (u, p) = mann_whitney_u(&xs, &ys).unwrap();
+        assert_eq!(u, 16.0);
+        assert::close(p, 0.515_075_514_747_953_7, 1E-10);
+
+        let (u_rev, p_rev) = mann_whitney_u(&ys, &xs).unwrap();
+        assert_eq!(u + u_rev, 42.0);
+        assert::close(p_rev, p, 1E-12);
+    }
+
+    #[test]
+    fn mann_whitney_all_tied() {
+        assert_eq!(mann_whitney_u(&[1.0, 1.0], &[1.0]), Ok((1.0, 1.0)));
+        assert_eq!(
+            mann_whitney_u::<f64>(&[], &[1.0]),
+            Err(TwoSampleError::EmptySample)
+        );
+    }
+
+    #[test]
+    fn permutation_test_of_mean_difference() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(6);
+        let mean = |xs: &[f64]| xs.iter().sum::<f64>() / xs.len() as f64;
+        let mean_diff = |xs: &[f64], ys: &[f64]| (mean(xs) - mean(ys)).abs();
+
+        let xs: Vec<f64> = (0..20).map(|i| f64::from(i % 5)).collect();
+        let shifted: Vec<f64> = xs.iter().map(|x| x + 3.0).collect();
+        let (stat, p) =
+            permutation_test(&xs, &shifted, mean_diff, 200, &mut rng).unwrap();
+        assert::close(stat, 3.0, 1E-12);
+        assert::close(p, 1.0 / 201.0, 1E-12);
+
+        let mut same = xs.clone();
+        same.reverse();
+        let (_, p) =
+            permutation_test(&xs, &same, mean_diff, 200, &mut rng).unwrap();
+        assert::close(p, 1.0, 1E-12);
+    }
+}
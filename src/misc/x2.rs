@@ -1,3 +1,5 @@
+use crate::dist::ChiSquared;
+use crate::traits::Cdf;
 use special::Gamma;
 
 /// Χ<sup>2</sup> (Chi-squared) test.
@@ -19,11 +21,47 @@ use special::Gamma;
 /// assert!(p > 0.05);
 /// ```
 pub fn x2_test(f_obs: &[u32], ps: &[f64]) -> (f64, f64) {
+    let f_obs: Vec<f64> = f_obs.iter().map(|&o| f64::from(o)).collect();
+    x2_test_weighted(&f_obs, ps)
+}
+
+/// Χ<sup>2</sup> (Chi-squared) test on weighted or aggregated counts.
+///
+/// Like [`x2_test`], but the observed frequencies may be non-integer, e.g.,
+/// the summed weights of the observations in each bin. The total weight is
+/// used as the number of observations.
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::{x2_test, x2_test_weighted};
+///
+/// let ps: Vec<f64> = vec![0.25; 4];
+///
+/// // Integer frequencies give the same result as `x2_test`
+/// let f_obs: Vec<f64> = vec![28.0, 31.0, 40.0, 35.0];
+/// let (stat, p) = x2_test_weighted(&f_obs, &ps);
+/// assert_eq!((stat, p), x2_test(&[28, 31, 40, 35], &ps));
+///
+/// // Observations with weights
+/// let f_obs: Vec<f64> = vec![27.5, 31.25, 40.0, 35.5];
+/// let (_, p) = x2_test_weighted(&f_obs, &ps);
+/// assert!(p > 0.05);
+/// ```
+///
+/// # Panics
+/// If `f_obs` and `ps` have different lengths
+pub fn x2_test_weighted(f_obs: &[f64], ps: &[f64]) -> (f64, f64) {
+    assert_eq!(
+        f_obs.len(),
+        ps.len(),
+        "f_obs and ps must be the same length"
+    );
     let k = f_obs.len();
-    let nf = f64::from(f_obs.iter().sum::<u32>());
+    let nf: f64 = f_obs.iter().sum();
     let x2 = nf
         * f_obs.iter().zip(ps.iter()).fold(0.0, |acc, (&o, &p)| {
-            let err_term = f64::from(o) / nf - p;
+            let err_term = o / nf - p;
             acc + err_term * err_term / p
         });
 
@@ -32,6 +70,128 @@ pub fn x2_test(f_obs: &[u32], ps: &[f64]) -> (f64, f64) {
     (x2, p)
 }
 
+/// Χ<sup>2</sup> (Chi-squared) test of independence on a contingency table.
+///
+/// Tests whether the row and column variables of the two-way table of counts
+/// `table` are independent. Each entry of `table` is a row.
+///
+/// # Returns
+/// `(stat, dof, p)`: the Χ<sup>2</sup> statistic, its degrees of freedom,
+/// `(rows - 1) * (columns - 1)`, and the p-value.
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::x2_independence_test;
+///
+/// // Rows are treatments and columns are outcomes
+/// let table = [[10, 20, 30], [6, 9, 17]];
+///
+/// let (stat, dof, p) = x2_independence_test(&table);
+/// assert_eq!(dof, 2);
+/// assert!(p > 0.05);
+/// ```
+///
+/// # Panics
+/// If `table` has fewer than two rows or columns, if the rows have different
+/// lengths, or if any row or column sums to zero
+pub fn x2_independence_test<R: AsRef<[u32]>>(table: &[R]) -> (f64, usize, f64) {
+    let (expected, dof) = expected_counts(table);
+    let stat = table
+        .iter()
+        .zip(expected.iter())
+        .flat_map(|(row, e_row)| row.as_ref().iter().zip(e_row.iter()))
+        .map(|(&o, &e)| {
+            let diff = f64::from(o) - e;
+            diff * diff / e
+        })
+        .sum();
+    (stat, dof, x2_sf(stat, dof))
+}
+
+/// [G-test](https://en.wikipedia.org/wiki/G-test) of independence on a
+/// contingency table.
+///
+/// The likelihood ratio alternative to [`x2_independence_test`], with
+/// statistic `2 Σ O ln(O / E)`, which is asymptotically Χ<sup>2</sup>
+/// distributed.
+///
+/// # Returns
+/// `(stat, dof, p)`: the G statistic, its degrees of freedom, and the p-value
+///
+/// # Example
+///
+/// ```
+/// use rv::misc::{g_test, x2_independence_test};
+///
+/// let table = [[12, 0], [3, 15]];
+///
+/// let (g, dof, p) = g_test(&table);
+/// assert_eq!(dof, 1);
+/// assert!(p < 0.01);
+///
+/// // The statistics agree for large samples but differ for small ones
+/// let (x2, _, _) = x2_independence_test(&table);
+/// assert!(g > x2);
+/// ```
+///
+/// # Panics
+/// If `table` has fewer than two rows or columns, if the rows have different
+/// lengths, or if any row or column sums to zero
+pub fn g_test<R: AsRef<[u32]>>(table: &[R]) -> (f64, usize, f64) {
+    let (expected, dof) = expected_counts(table);
+    let stat = 2.0
+        * table
+            .iter()
+            .zip(expected.iter())
+            .flat_map(|(row, e_row)| row.as_ref().iter().zip(e_row.iter()))
+            .filter(|(&o, _)| o > 0)
+            .map(|(&o, &e)| {
+                let o = f64::from(o);
+                o * (o / e).ln()
+            })
+            .sum::<f64>();
+    (stat, dof, x2_sf(stat, dof))
+}
+
+/// The counts expected under independence and the degrees of freedom
+fn expected_counts<R: AsRef<[u32]>>(table: &[R]) -> (Vec<Vec<f64>>, usize) {
+    let n_rows = table.len();
+    let n_cols = table.first().map_or(0, |row| row.as_ref().len());
+    assert!(
+        n_rows > 1 && n_cols > 1,
+        "table must have at least two rows and two columns"
+    );
+    assert!(
+        table.iter().all(|row| row.as_ref().len() == n_cols),
+        "table rows must have the same length"
+    );
+
+    let row_sums: Vec<f64> = table
+        .iter()
+        .map(|row| row.as_ref().iter().map(|&o| f64::from(o)).sum())
+        .collect();
+    let col_sums: Vec<f64> = (0..n_cols)
+        .map(|j| table.iter().map(|row| f64::from(row.as_ref()[j])).sum())
+        .collect();
+    assert!(
+        row_sums.iter().chain(col_sums.iter()).all(|&sum| sum > 0.0),
+        "every row and column must have a nonzero total"
+    );
+
+    let total: f64 = row_sums.iter().sum();
+    let expected = row_sums
+        .iter()
+        .map(|r| col_sums.iter().map(|c| r * c / total).collect())
+        .collect();
+    (expected, (n_rows - 1) * (n_cols - 1))
+}
+
+/// Upper tail probability of the Χ<sup>2</sup> distribution
+fn x2_sf(stat: f64, dof: usize) -> f64 {
+    ChiSquared::new_unchecked(dof as f64).sf(&stat)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -46,4 +206,48 @@ mod tests {
         assert::close(x2, 2.417_910_447_761_194, TOL);
         assert::close(p, 0.490_309_306_965_388_3, TOL);
     }
+
+    #[test]
+    fn weighted_scales_with_total_weight() {
+        // Doubling every count doubles the statistic
+        let ps: Vec<f64> = vec![0.25; 4];
+        let (x2, _) = x2_test(&[28, 31, 40, 35], &ps);
+        let (x2_w, p_w) = x2_test_weighted(&[56.0, 62.0, 80.0, 70.0], &ps);
+
+        assert::close(x2_w, 2.0 * x2, TOL);
+        assert!(p_w < 0.490_309_306_965_388_3);
+    }
+
+    #[test]
+    fn independence_reference_values() {
+        // Computed with mpmath
+        let table = [[10, 20, 30], [6, 9, 17]];
+        let (x2, dof, p) = x2_independence_test(&table);
+        assert_eq!(dof, 2);
+        assert::close(x2, 0.271_574_651_504_035, TOL);
+        assert::close(p, 0.873_028_283_380_073, 1E-10);
+
+        let (g, dof, p) = g_test(&table);
+        assert_eq!(dof, 2);
+        assert::close(g, 0.274_026_542_024_661, 1E-10);
+        assert::close(p, 0.871_958_654_281_272, 1E-10);
+    }
+
+    #[test]
+    fn independence_with_empty_cell() {
+        let table = vec![vec![12, 0], vec![3, 15]];
+        let (x2, _, p) = x2_independence_test(&table);
+        assert::close(x2, 20.0, TOL);
+        assert::close(p, 7.744_216_431_044_08E-6, 1E-10);
+
+        let (g, _, p) = g_test(&table);
+        assert::close(g, 25.368_627_314_409_7, 1E-10);
+        assert::close(p, 4.735_573_584_579_92E-7, 1E-10);
+    }
+
+    #[test]
+    #[should_panic]
+    fn independence_rejects_zero_column() {
+        x2_independence_test(&[[1, 0], [2, 0]]);
+    }
 }
@@ -1,6 +1,39 @@
+//! Models built from rv distributions
+mod anomaly;
+mod dag;
+mod dpm;
+mod dpm_slice;
+mod dpm_svi;
+#[cfg(feature = "arraydist")]
+mod factor;
+mod gmm;
+mod gmm_auto;
+mod hierarchical;
+mod kalman;
+mod latent_class;
+mod logp;
+
+pub use anomaly::{
+    anomaly_scores_from_ln_f, AnomalyError, AnomalyScorer, TailThreshold,
+};
+pub use dag::{Dag, DagBuilder, DagError, DagValue, NodeId};
+pub use dpm::{DpMixture, DpMixtureError, DpmInference};
+pub use dpm_slice::DpMixtureSlice;
+pub use dpm_svi::{DpMixtureSvi, DpMixtureSviError, SviComponent};
+#[cfg(feature = "arraydist")]
+pub use factor::{FactorModel, FactorModelError, FactorNoise};
+pub use gmm::{FittedGmm, FittedGmmError};
+pub use gmm_auto::{ComponentSelection, FitAutoOptions};
+pub use hierarchical::{HierarchicalModel, HierarchicalModelError};
+pub use kalman::{ScalarKalman, ScalarKalmanError};
+pub use latent_class::{LatentClassError, LatentClassModel};
+pub use logp::{LogpAccumulator, LogpTerm};
+
 use crate::data::DataOrSuffStat;
 use crate::traits::*;
 use rand::Rng;
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 use std::sync::Arc;
 
@@ -11,7 +44,19 @@ use std::sync::Arc;
 /// `X`: The type of the data/observations to be modeled
 /// `Fx`: The type of the likelihood, *f(x|θ)*
 /// `Pr`: The type of the prior on the parameters of `Fx`, π(θ)
+///
+/// A deserialized model owns a new copy of the prior; it is no longer shared
+/// with the other models that shared it before serialization.
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+#[cfg_attr(
+    feature = "serde1",
+    serde(bound(
+        serialize = "Fx::Stat: Serialize, Pr: Serialize",
+        deserialize = "Fx::Stat: Deserialize<'de>, Pr: Deserialize<'de>"
+    ))
+)]
 pub struct ConjugateModel<X, Fx, Pr>
 where
     Fx: Rv<X> + HasSuffStat<X>,
@@ -21,6 +66,7 @@ where
     prior: Arc<Pr>,
     /// A `SuffStat` for `Fx`
     suffstat: Fx::Stat,
+    #[cfg_attr(feature = "serde1", serde(skip))]
     _phantom: PhantomData<X>,
 }
 
@@ -64,6 +110,51 @@ where
         self.prior.ln_pp(y, &self.obs())
     }
 
+    /// Log Bayes factor of this model over `other`,
+    /// *ln f(obs|self) - ln f(obs|other)*
+    ///
+    /// Positive values favor this model. Both models must have observed the
+    /// same data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use rv::prelude::*;
+    /// use rv::ConjugateModel;
+    ///
+    /// let flips: Vec<bool> = vec![true, true, true, false, true, true];
+    /// let fx = Bernoulli::uniform();
+    ///
+    /// // A prior favoring fair coins against one favoring biased coins
+    /// let fair = Arc::new(Beta::new(20.0, 20.0).unwrap());
+    /// let biased = Arc::new(Beta::new(5.0, 1.0).unwrap());
+    /// let mut m_fair = ConjugateModel::<bool, Bernoulli, Beta>::new(&fx, fair);
+    /// let mut m_biased = ConjugateModel::<bool, Bernoulli, Beta>::new(&fx, biased);
+    /// m_fair.observe_many(&flips);
+    /// m_biased.observe_many(&flips);
+    ///
+    /// assert!(m_biased.ln_bayes_factor(&m_fair) > 0.0);
+    /// ```
+    ///
+    /// # Panics
+    /// If the models have observed different numbers of data
+    pub fn ln_bayes_factor<Fy, Py>(
+        &self,
+        other: &ConjugateModel<X, Fy, Py>,
+    ) -> f64
+    where
+        Fy: Rv<X> + HasSuffStat<X>,
+        Py: ConjugatePrior<X, Fy>,
+    {
+        assert_eq!(
+            self.n(),
+            other.n(),
+            "the models must have observed the same data"
+        );
+        self.ln_m() - other.ln_m()
+    }
+
     /// Return the posterior distribution
     /// # Example
     ///
@@ -88,6 +179,44 @@ where
         self.prior.posterior(&self.obs())
     }
 
+    /// Observe each of `xs` in turn, adding the log posterior predictive of
+    /// each given the observations before it to `acc`
+    ///
+    /// The contributions sum to the change in `ln_m`, and a tracing
+    /// accumulator shows which observations the model found surprising.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use rv::prelude::*;
+    /// use rv::model::LogpAccumulator;
+    /// use rv::ConjugateModel;
+    ///
+    /// let flips: Vec<bool> = vec![true, true, true, true, false];
+    ///
+    /// let pr = Arc::new(Beta::new(1.0, 1.0).unwrap());
+    /// let fx = Bernoulli::uniform();
+    /// let mut model = ConjugateModel::<bool, Bernoulli, Beta>::new(&fx, pr);
+    ///
+    /// let mut acc = LogpAccumulator::tracing();
+    /// model.observe_many_with_logp("flips", &flips, &mut acc);
+    ///
+    /// assert::close(acc.total(), model.ln_m(), 1E-12);
+    /// assert_eq!(acc.worst(1)[0].ix, 4);
+    /// ```
+    pub fn observe_many_with_logp(
+        &mut self,
+        source: &str,
+        xs: &[X],
+        acc: &mut LogpAccumulator,
+    ) {
+        xs.iter().enumerate().for_each(|(ix, x)| {
+            acc.push(source, ix, self.ln_pp(x));
+            self.observe(x);
+        });
+    }
+
     /// Return the observations
     fn obs(&self) -> DataOrSuffStat<X, Fx> {
         DataOrSuffStat::SuffStat(&self.suffstat)
@@ -137,3 +266,238 @@ where
             .collect()
     }
 }
+
+/// A model of data of type `X` with a marginal likelihood, so that models
+/// with different likelihoods and priors can be compared by
+/// [`model_probs`]
+pub trait ModelEvidence<X> {
+    /// Log marginal likelihood of the observed data
+    fn ln_evidence(&self) -> f64;
+
+    /// The number of observed data
+    fn n_observed(&self) -> usize;
+}
+
+impl<X, Fx, Pr> ModelEvidence<X> for ConjugateModel<X, Fx, Pr>
+where
+    Fx: Rv<X> + HasSuffStat<X>,
+    Pr: ConjugatePrior<X, Fx>,
+{
+    fn ln_evidence(&self) -> f64 {
+        self.ln_m()
+    }
+
+    fn n_observed(&self) -> usize {
+        self.n()
+    }
+}
+
+/// Posterior probabilities of `models` that have observed the same data,
+/// given their prior probabilities.
+///
+/// The probability of model *k* is proportional to *w_k f(obs|k)*. The
+/// `prior_weights` are normalized, so they need not sum to one.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use rv::prelude::*;
+/// use rv::model::{model_probs, ModelEvidence};
+/// use rv::ConjugateModel;
+///
+/// let xs: Vec<u32> = vec![4, 6, 5, 7, 3, 5];
+///
+/// // A Poisson and a Geometric model of the same counts
+/// let mut poisson = ConjugateModel::<u32, Poisson, Gamma>::new(
+///     &Poisson::new(1.0).unwrap(),
+///     Arc::new(Gamma::new(2.0, 0.5).unwrap()),
+/// );
+/// let mut geometric = ConjugateModel::<u32, Geometric, Beta>::new(
+///     &Geometric::new(0.5).unwrap(),
+///     Arc::new(Beta::new(1.0, 1.0).unwrap()),
+/// );
+/// poisson.observe_many(&xs);
+/// geometric.observe_many(&xs);
+///
+/// let models: [&dyn ModelEvidence<u32>; 2] = [&poisson, &geometric];
+/// let probs = model_probs(&models, &[1.0, 1.0]).unwrap();
+/// assert!((probs[0] + probs[1] - 1.0).abs() < 1E-12);
+/// assert!(probs[0] > 0.9);
+/// ```
+pub fn model_probs<X>(
+    models: &[&dyn ModelEvidence<X>],
+    prior_weights: &[f64],
+) -> Result<Vec<f64>, ModelProbsError> {
+    if models.is_empty() {
+        return Err(ModelProbsError::NoModels);
+    } else if models.len() != prior_weights.len() {
+        return Err(ModelProbsError::WeightsLengthMismatch {
+            n_models: models.len(),
+            n_weights: prior_weights.len(),
+        });
+    } else if let Some((ix, &weight)) = prior_weights
+        .iter()
+        .enumerate()
+        .find(|(_, w)| !(w.is_finite() && **w >= 0.0))
+    {
+        return Err(ModelProbsError::InvalidWeight { ix, weight });
+    } else if prior_weights.iter().all(|&w| w == 0.0) {
+        return Err(ModelProbsError::WeightsAllZero);
+    }
+
+    let n = models[0].n_observed();
+    if let Some(ix) = models.iter().position(|m| m.n_observed() != n) {
+        return Err(ModelProbsError::DifferentData { ix });
+    }
+
+    let ln_posts: Vec<f64> = models
+        .iter()
+        .zip(prior_weights.iter())
+        .map(|(m, &w)| {
+            if w == 0.0 {
+                f64::NEG_INFINITY
+            } else {
+                w.ln() + m.ln_evidence()
+            }
+        })
+        .collect();
+    let ln_z = crate::misc::logsumexp(&ln_posts);
+    if !ln_z.is_finite() {
+        return Err(ModelProbsError::EvidenceNotFinite);
+    }
+    Ok(ln_posts.iter().map(|lp| (lp - ln_z).exp()).collect())
+}
+
+/// Error from [`model_probs`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum ModelProbsError {
+    /// There are no models to compare
+    NoModels,
+    /// There is not one prior weight per model
+    WeightsLengthMismatch { n_models: usize, n_weights: usize },
+    /// A prior weight is negative or not finite
+    InvalidWeight { ix: usize, weight: f64 },
+    /// Every prior weight is zero
+    WeightsAllZero,
+    /// The model at `ix` has observed a different number of data than the
+    /// first model
+    DifferentData { ix: usize },
+    /// No model with a nonzero weight has a finite, nonzero evidence
+    EvidenceNotFinite,
+}
+
+impl std::error::Error for ModelProbsError {}
+
+impl std::fmt::Display for ModelProbsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoModels => write!(f, "there are no models to compare"),
+            Self::WeightsLengthMismatch {
+                n_models,
+                n_weights,
+            } => write!(
+                f,
+                "there are {} models but {} prior weights",
+                n_models, n_weights
+            ),
+            Self::InvalidWeight { ix, weight } => write!(
+                f,
+                "prior weight {} at index {} must be finite and nonnegative",
+                weight, ix
+            ),
+            Self::WeightsAllZero => write!(f, "every prior weight is zero"),
+            Self::DifferentData { ix } => write!(
+                f,
+                "model {} has observed different data than model 0",
+                ix
+            ),
+            Self::EvidenceNotFinite => {
+                write!(f, "no weighted model has a finite evidence")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Bernoulli, Beta};
+
+    fn beta_bernoulli(
+        a: f64,
+        b: f64,
+        flips: &[bool],
+    ) -> ConjugateModel<bool, Bernoulli, Beta> {
+        let mut model = ConjugateModel::new(
+            &Bernoulli::uniform(),
+            Arc::new(Beta::new(a, b).unwrap()),
+        );
+        model.observe_many(flips);
+        model
+    }
+
+    #[test]
+    fn probs_match_bayes_factor() {
+        let flips = [true, true, false, true, true, true, false, true];
+        let m1 = beta_bernoulli(1.0, 1.0, &flips);
+        let m2 = beta_bernoulli(8.0, 2.0, &flips);
+
+        // Beta(1, 1) gives 1 / ((n + 1) C(n, k))
+        assert::close(m1.ln_m(), -(9.0_f64 * 28.0).ln(), 1E-12);
+
+        let models: [&dyn ModelEvidence<bool>; 2] = [&m1, &m2];
+        let probs = model_probs(&models, &[1.0, 1.0]).unwrap();
+        assert::close(
+            probs[0] / probs[1],
+            m1.ln_bayes_factor(&m2).exp(),
+            1E-12,
+        );
+
+        // Prior odds multiply the posterior odds
+        let probs = model_probs(&models, &[3.0, 1.0]).unwrap();
+        assert::close(
+            probs[0] / probs[1],
+            3.0 * m1.ln_bayes_factor(&m2).exp(),
+            1E-12,
+        );
+        let probs = model_probs(&models, &[0.0, 1.0]).unwrap();
+        assert_eq!(probs, vec![0.0, 1.0]);
+    }
+
+    #[test]
+    fn model_probs_errors() {
+        let m1 = beta_bernoulli(1.0, 1.0, &[true, false]);
+        let m2 = beta_bernoulli(1.0, 1.0, &[true]);
+        let models: [&dyn ModelEvidence<bool>; 2] = [&m1, &m2];
+
+        assert_eq!(
+            model_probs::<bool>(&[], &[]),
+            Err(ModelProbsError::NoModels)
+        );
+        assert_eq!(
+            model_probs(&models, &[1.0]),
+            Err(ModelProbsError::WeightsLengthMismatch {
+                n_models: 2,
+                n_weights: 1
+            })
+        );
+        assert_eq!(
+            model_probs(&models, &[1.0, -1.0]),
+            Err(ModelProbsError::InvalidWeight {
+                ix: 1,
+                weight: -1.0
+            })
+        );
+        assert_eq!(
+            model_probs(&models, &[0.0, 0.0]),
+            Err(ModelProbsError::WeightsAllZero)
+        );
+        assert_eq!(
+            model_probs(&models, &[1.0, 1.0]),
+            Err(ModelProbsError::DifferentData { ix: 1 })
+        );
+    }
+}
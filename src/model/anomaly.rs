@@ -0,0 +1,552 @@
+//! Anomaly scores from fitted densities
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::traits::Rv;
+use std::fmt;
+
+/// The fewest exceedances a generalized Pareto tail is fit to
+const MIN_EXCEEDANCES: usize = 10;
+
+/// Shapes closer to zero than this use the exponential limit of the
+/// generalized Pareto
+const SHAPE_EPS: f64 = 1E-8;
+
+/// Scores data by how unexpected they are under a fitted density
+///
+/// The anomaly score of `x` is its negative log predictive density, less
+/// the given quantile of the negative log densities of reference data,
+///
+/// ```math
+/// s(x) = -\ln f(x) - q,
+/// ```
+///
+/// so it is the log of how many times less probable `x` is than the
+/// reference point. Any [`Rv`] works as the density, e.g., a
+/// [`FittedGmm`](crate::model::FittedGmm) or [`Kde`](crate::dist::Kde).
+/// Use [`TailThreshold`] to turn scores into a decision with a chosen false
+/// alarm rate.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{Bandwidth, Gaussian, Kde};
+/// use rv::model::AnomalyScorer;
+/// use rv::traits::*;
+///
+/// let mut rng = rand::thread_rng();
+/// let xs: Vec<f64> = Gaussian::standard().sample(1_000, &mut rng);
+///
+/// let kde = Kde::from_sample(xs.clone(), Bandwidth::Silverman).unwrap();
+/// let scorer = AnomalyScorer::new(kde, &xs, 0.5).unwrap();
+///
+/// // Typical points score near zero, outliers much higher
+/// assert!(scorer.anomaly_score(&0.5) < 1.0);
+/// assert!(scorer.anomaly_score(&5.0) > 5.0);
+///
+/// // Flag one in a thousand typical points
+/// let threshold = scorer.calibrate(&xs, 0.05).unwrap();
+/// let cutoff = threshold.threshold(1E-3).unwrap();
+/// assert!(scorer.anomaly_score(&5.0) > cutoff);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct AnomalyScorer<Fx> {
+    density: Fx,
+    /// The reference quantile of the negative log density
+    reference: f64,
+    quantile: f64,
+}
+
+/// A peaks-over-threshold model of the upper tail of anomaly scores
+///
+/// Scores above the `1 - tail_fraction` quantile of a reference sample are
+/// fit with a generalized Pareto distribution by probability-weighted
+/// moments (Hosking & Wallis, 1987), so thresholds can be set for false
+/// alarm rates smaller than the reference sample resolves. Below the tail,
+/// the empirical distribution is used.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct TailThreshold {
+    /// The reference scores in ascending order
+    scores: Vec<f64>,
+    /// Where the tail begins
+    location: f64,
+    /// Generalized Pareto scale
+    scale: f64,
+    /// Generalized Pareto shape
+    shape: f64,
+    /// Fraction of the reference scores in the tail
+    tail_fraction: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum AnomalyError {
+    /// There are no reference data
+    EmptyReference,
+    /// The reference quantile is not in [0, 1]
+    QuantileOutOfRange { quantile: f64 },
+    /// A log density is NaN
+    DensityNan {
+        /// Index of the datum
+        ix: usize,
+    },
+    /// The reference negative log density is infinite
+    ReferenceNotFinite { reference: f64 },
+    /// A score is infinite or NaN
+    ScoreNotFinite {
+        /// Index of the score
+        ix: usize,
+        /// The score
+        score: f64,
+    },
+    /// The tail fraction is not in (0, 1)
+    TailFractionOutOfRange { tail_fraction: f64 },
+    /// Too few scores fall in the tail to fit it
+    TooFewExceedances {
+        /// The number of scores in the tail
+        n_exceedances: usize,
+        /// The number required
+        min: usize,
+    },
+    /// The false alarm rate is not in (0, 1)
+    FalseAlarmRateOutOfRange { rate: f64 },
+}
+
+/// The `p` quantile of ascending `xs` by linear interpolation
+fn sorted_quantile(xs: &[f64], p: f64) -> f64 {
+    let h = p * (xs.len() - 1) as f64;
+    let lower = h.floor() as usize;
+    let upper = (lower + 1).min(xs.len() - 1);
+    let frac = h - lower as f64;
+    if frac == 0.0 {
+        xs[lower]
+    } else {
+        frac.mul_add(xs[upper] - xs[lower], xs[lower])
+    }
+}
+
+/// Anomaly scores from log predictive densities, normalized by the
+/// `quantile` of their own negative values
+///
+/// This scores data whose predictive densities come from elsewhere, e.g.
+/// the leave-one-out predictives of a Gaussian process.
+///
+/// # Example
+///
+/// ```
+/// use rv::model::anomaly_scores_from_ln_f;
+///
+/// let ln_f = [-1.0, -1.5, -0.9, -12.0, -1.2];
+/// let scores = anomaly_scores_from_ln_f(&ln_f, 0.5).unwrap();
+/// assert_eq!(scores[4], 0.0);
+/// assert!((scores[3] - 10.8).abs() < 1E-12);
+/// ```
+pub fn anomaly_scores_from_ln_f(
+    ln_f: &[f64],
+    quantile: f64,
+) -> Result<Vec<f64>, AnomalyError> {
+    let reference = reference_nlpd(ln_f.iter().copied(), quantile)?;
+    Ok(ln_f.iter().map(|lf| -lf - reference).collect())
+}
+
+/// The `quantile` of the negative log densities
+fn reference_nlpd<I>(ln_f: I, quantile: f64) -> Result<f64, AnomalyError>
+where
+    I: Iterator<Item = f64>,
+{
+    if !(0.0..=1.0).contains(&quantile) {
+        return Err(AnomalyError::QuantileOutOfRange { quantile });
+    }
+    let mut nlpd = ln_f
+        .enumerate()
+        .map(|(ix, lf)| {
+            if lf.is_nan() {
+                Err(AnomalyError::DensityNan { ix })
+            } else {
+                Ok(-lf)
+            }
+        })
+        .collect::<Result<Vec<f64>, _>>()?;
+    if nlpd.is_empty() {
+        return Err(AnomalyError::EmptyReference);
+    }
+    nlpd.sort_unstable_by(f64::total_cmp);
+
+    let reference = sorted_quantile(&nlpd, quantile);
+    if reference.is_finite() {
+        Ok(reference)
+    } else {
+        Err(AnomalyError::ReferenceNotFinite { reference })
+    }
+}
+
+impl<Fx> AnomalyScorer<Fx> {
+    /// Create a scorer that compares against the `quantile` of the negative
+    /// log density of `reference` data under `density`
+    ///
+    /// The reference data are usually those the density was fit to. With a
+    /// `quantile` of 0.5, a score of zero is a typical datum.
+    pub fn new<X>(
+        density: Fx,
+        reference: &[X],
+        quantile: f64,
+    ) -> Result<Self, AnomalyError>
+    where
+        Fx: Rv<X>,
+    {
+        let reference = reference_nlpd(
+            reference.iter().map(|x| density.ln_f(x)),
+            quantile,
+        )?;
+        Ok(AnomalyScorer {
+            density,
+            reference,
+            quantile,
+        })
+    }
+
+    /// The density data are scored under
+    #[inline]
+    pub fn density(&self) -> &Fx {
+        &self.density
+    }
+
+    /// The negative log density of the reference point, which scores zero
+    #[inline]
+    pub fn reference(&self) -> f64 {
+        self.reference
+    }
+
+    /// The quantile of the reference data that scores zero
+    #[inline]
+    pub fn quantile(&self) -> f64 {
+        self.quantile
+    }
+
+    /// The anomaly score of `x`. Higher is more anomalous.
+    pub fn anomaly_score<X>(&self, x: &X) -> f64
+    where
+        Fx: Rv<X>,
+    {
+        -self.density.ln_f(x) - self.reference
+    }
+
+    /// The anomaly score of each of `xs`
+    pub fn anomaly_scores<X>(&self, xs: &[X]) -> Vec<f64>
+    where
+        Fx: Rv<X>,
+    {
+        xs.iter().map(|x| self.anomaly_score(x)).collect()
+    }
+
+    /// Fit a [`TailThreshold`] to the scores of `xs`, which should be
+    /// free of anomalies, modeling the top `tail_fraction` of them
+    pub fn calibrate<X>(
+        &self,
+        xs: &[X],
+        tail_fraction: f64,
+    ) -> Result<TailThreshold, AnomalyError>
+    where
+        Fx: Rv<X>,
+    {
+        TailThreshold::fit(self.anomaly_scores(xs), tail_fraction)
+    }
+}
+
+impl TailThreshold {
+    /// Fit the top `tail_fraction` of the reference `scores` with a
+    /// generalized Pareto distribution
+    ///
+    /// At least ten scores must fall in the tail. A tail fraction of 0.05
+    /// to 0.1 is typical.
+    pub fn fit(
+        mut scores: Vec<f64>,
+        tail_fraction: f64,
+    ) -> Result<Self, AnomalyError> {
+        if !(tail_fraction > 0.0 && tail_fraction < 1.0) {
+            return Err(AnomalyError::TailFractionOutOfRange { tail_fraction });
+        }
+        if let Some((ix, &score)) =
+            scores.iter().enumerate().find(|(_, s)| !s.is_finite())
+        {
+            return Err(AnomalyError::ScoreNotFinite { ix, score });
+        }
+        let n = scores.len();
+        let n_exceedances = (tail_fraction * n as f64).floor() as usize;
+        if n_exceedances < MIN_EXCEEDANCES || n_exceedances == n {
+            return Err(AnomalyError::TooFewExceedances {
+                n_exceedances,
+                min: MIN_EXCEEDANCES,
+            });
+        }
+        scores.sort_unstable_by(f64::total_cmp);
+
+        let location = scores[n - n_exceedances - 1];
+        let exceedances: Vec<f64> = scores[n - n_exceedances..]
+            .iter()
+            .map(|s| s - location)
+            .collect();
+
+        // Probability-weighted moments of the ascending exceedances with
+        // plotting positions (j - 0.35) / k
+        let k = n_exceedances as f64;
+        let a0 = exceedances.iter().sum::<f64>() / k;
+        let a1 = exceedances
+            .iter()
+            .enumerate()
+            .map(|(j, y)| (1.0 - (j as f64 + 0.65) / k) * y)
+            .sum::<f64>()
+            / k;
+        let (shape, scale) = if a0 > 0.0 {
+            // Hosking & Wallis use the opposite sign for the shape
+            let denom = 2.0_f64.mul_add(-a1, a0);
+            (2.0 - a0 / denom, 2.0 * a0 * a1 / denom)
+        } else {
+            // Every tail score is tied with the location
+            (0.0, 0.0)
+        };
+
+        Ok(TailThreshold {
+            scores,
+            location,
+            scale,
+            shape,
+            tail_fraction: k / n as f64,
+        })
+    }
+
+    /// The score above which the generalized Pareto tail is used
+    #[inline]
+    pub fn location(&self) -> f64 {
+        self.location
+    }
+
+    /// The scale of the generalized Pareto tail
+    #[inline]
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// The shape of the generalized Pareto tail. Positive shapes are heavy
+    /// tailed, and negative shapes have a largest possible score.
+    #[inline]
+    pub fn shape(&self) -> f64 {
+        self.shape
+    }
+
+    /// The fraction of reference scores in the tail
+    #[inline]
+    pub fn tail_fraction(&self) -> f64 {
+        self.tail_fraction
+    }
+
+    /// The probability that a reference datum scores above `score`
+    ///
+    /// This is the p-value of `score` for the null hypothesis that it is
+    /// not anomalous.
+    pub fn exceedance_probability(&self, score: f64) -> f64 {
+        if score <= self.location {
+            let n_above = self.scores.len()
+                - self.scores.partition_point(|&s| s <= score);
+            return n_above as f64 / self.scores.len() as f64;
+        }
+        let z = (score - self.location) / self.scale;
+        let survival = if self.shape.abs() < SHAPE_EPS {
+            (-z).exp()
+        } else {
+            let base = self.shape.mul_add(z, 1.0);
+            if base <= 0.0 {
+                0.0
+            } else {
+                base.powf(-self.shape.recip())
+            }
+        };
+        self.tail_fraction * survival
+    }
+
+    /// The score that reference data exceed with probability `rate`
+    ///
+    /// Flagging scores above this threshold raises false alarms on a
+    /// `rate` fraction of non-anomalous data.
+    pub fn threshold(&self, rate: f64) -> Result<f64, AnomalyError> {
+        if !(rate > 0.0 && rate < 1.0) {
+            return Err(AnomalyError::FalseAlarmRateOutOfRange { rate });
+        }
+        if rate >= self.tail_fraction {
+            return Ok(sorted_quantile(&self.scores, 1.0 - rate));
+        }
+        let ratio = rate / self.tail_fraction;
+        let excess = if self.shape.abs() < SHAPE_EPS {
+            -ratio.ln()
+        } else {
+            (ratio.powf(-self.shape) - 1.0) / self.shape
+        };
+        Ok(self.scale.mul_add(excess, self.location))
+    }
+}
+
+impl std::error::Error for AnomalyError {}
+
+impl fmt::Display for AnomalyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyReference => write!(f, "no reference data"),
+            Self::QuantileOutOfRange { quantile } => {
+                write!(f, "quantile ({}) must be in [0, 1]", quantile)
+            }
+            Self::DensityNan { ix } => {
+                write!(f, "the log density of datum {} is NaN", ix)
+            }
+            Self::ReferenceNotFinite { reference } => write!(
+                f,
+                "the reference negative log density ({}) must be finite",
+                reference
+            ),
+            Self::ScoreNotFinite { ix, score } => {
+                write!(f, "score {} is not finite: {}", ix, score)
+            }
+            Self::TailFractionOutOfRange { tail_fraction } => {
+                write!(f, "tail fraction ({}) must be in (0, 1)", tail_fraction)
+            }
+            Self::TooFewExceedances { n_exceedances, min } => write!(
+                f,
+                "{} scores fall in the tail but at least {} are required",
+                n_exceedances, min
+            ),
+            Self::FalseAlarmRateOutOfRange { rate } => {
+                write!(f, "false alarm rate ({}) must be in (0, 1)", rate)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Gaussian, Uniform};
+    use crate::model::FittedGmm;
+    use crate::traits::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    #[test]
+    fn score_is_nlpd_less_reference_quantile() {
+        let g = Gaussian::standard();
+        let reference = [0.0, 1.0, -2.0];
+        let scorer = AnomalyScorer::new(g.clone(), &reference, 0.5).unwrap();
+        // The median negative log density is that of x = 1
+        assert::close(scorer.reference(), -g.ln_f(&1.0), 1E-12);
+        assert::close(scorer.anomaly_score(&1.0), 0.0, 1E-12);
+        assert::close(scorer.anomaly_score(&3.0), 0.5 * (9.0 - 1.0), 1E-12);
+
+        let scorer = AnomalyScorer::new(g, &reference, 0.25).unwrap();
+        // Halfway between the densities of x = 0 and x = 1
+        assert::close(scorer.anomaly_score(&0.5), 0.125 - 0.25, 1E-12);
+    }
+
+    #[test]
+    fn gmm_scores_flag_points_between_clusters() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xA0);
+        let left: Vec<f64> =
+            Gaussian::new(-5.0, 1.0).unwrap().sample(500, &mut rng);
+        let right: Vec<f64> =
+            Gaussian::new(5.0, 1.0).unwrap().sample(500, &mut rng);
+        let xs = [left, right].concat();
+        let gmm = FittedGmm::fit(&xs, 2, &mut rng).unwrap();
+        let scorer = AnomalyScorer::new(gmm, &xs, 0.5).unwrap();
+
+        let scores = scorer.anomaly_scores(&[-5.0, 0.0, 5.0]);
+        assert!(scores[0] < 0.0 && scores[2] < 0.0);
+        assert!(scores[1] > 8.0);
+    }
+
+    #[test]
+    fn pwm_fit_recovers_generalized_pareto() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xA1);
+        let (shape, scale) = (0.2, 1.5);
+        // Every score is in the tail except those at or below zero
+        let scores: Vec<f64> = std::iter::repeat(0.0)
+            .take(20_000)
+            .chain(
+                Uniform::new(0.0, 1.0)
+                    .unwrap()
+                    .sample(20_000, &mut rng)
+                    .into_iter()
+                    .map(|u: f64| {
+                        scale * ((1.0 - u).powf(-shape) - 1.0) / shape
+                    }),
+            )
+            .collect();
+        let tail = TailThreshold::fit(scores, 0.5).unwrap();
+        assert_eq!(tail.location(), 0.0);
+        assert::close(tail.shape(), shape, 0.05);
+        assert::close(tail.scale(), scale, 0.1);
+    }
+
+    #[test]
+    fn threshold_controls_false_alarm_rate() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xA2);
+        let g = Gaussian::standard();
+        let reference: Vec<f64> = g.sample(20_000, &mut rng);
+        let scorer = AnomalyScorer::new(g.clone(), &reference, 0.5).unwrap();
+        let tail = scorer.calibrate(&reference, 0.05).unwrap();
+
+        // Beyond the resolution of the reference sample
+        let cutoff = tail.threshold(1E-4).unwrap();
+        // Standard Gaussian scores exceed the cutoff beyond ±x
+        let x = 2.0_f64
+            .mul_add(cutoff + scorer.reference(), -crate::consts::LN_2PI)
+            .sqrt();
+        assert::close(2.0 * g.cdf(&-x), 1E-4, 5E-5);
+        assert::close(tail.exceedance_probability(cutoff), 1E-4, 1E-12);
+
+        // Inside the bulk the empirical distribution is used
+        let cutoff = tail.threshold(0.5).unwrap();
+        assert::close(cutoff, 0.0, 1E-12);
+        assert::close(tail.exceedance_probability(cutoff), 0.5, 1E-4);
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        let g = Gaussian::standard();
+        assert_eq!(
+            AnomalyScorer::new(g.clone(), &[] as &[f64], 0.5),
+            Err(AnomalyError::EmptyReference)
+        );
+        assert_eq!(
+            AnomalyScorer::new(g.clone(), &[0.0], 1.5),
+            Err(AnomalyError::QuantileOutOfRange { quantile: 1.5 })
+        );
+        assert_eq!(
+            anomaly_scores_from_ln_f(&[-1.0, f64::NEG_INFINITY], 1.0),
+            Err(AnomalyError::ReferenceNotFinite {
+                reference: f64::INFINITY
+            })
+        );
+        assert_eq!(
+            TailThreshold::fit(vec![0.0; 100], 0.05),
+            Err(AnomalyError::TooFewExceedances {
+                n_exceedances: 5,
+                min: MIN_EXCEEDANCES
+            })
+        );
+        assert!(matches!(
+            TailThreshold::fit(vec![0.0, f64::NAN], 0.5),
+            Err(AnomalyError::ScoreNotFinite { ix: 1, .. })
+        ));
+
+        // Tied scores give a degenerate tail at their value
+        let tail = TailThreshold::fit(vec![1.0; 200], 0.1).unwrap();
+        assert_eq!(tail.threshold(1E-3), Ok(1.0));
+        assert_eq!(tail.exceedance_probability(1.5), 0.0);
+        assert_eq!(
+            tail.threshold(0.0),
+            Err(AnomalyError::FalseAlarmRateOutOfRange { rate: 0.0 })
+        );
+    }
+}
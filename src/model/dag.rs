@@ -0,0 +1,529 @@
+//! Directed acyclic graphs of distributions
+use crate::model::LogpAccumulator;
+use crate::traits::*;
+use rand::{Rng, RngCore};
+use std::fmt;
+use std::sync::Arc;
+
+/// A value that a node of a [`Dag`] can take
+///
+/// Every node value is stored as an `f64` so that children can compute their
+/// parameters from any parent. Discrete values round trip exactly.
+pub trait DagValue: Sized {
+    /// Convert the value to an `f64`
+    fn into_f64(self) -> f64;
+    /// Convert an `f64` back to a value. Returns `None` if `x` is not a
+    /// valid value of this type.
+    fn from_f64(x: f64) -> Option<Self>;
+}
+
+impl DagValue for f64 {
+    fn into_f64(self) -> f64 {
+        self
+    }
+
+    fn from_f64(x: f64) -> Option<Self> {
+        Some(x)
+    }
+}
+
+impl DagValue for f32 {
+    fn into_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    fn from_f64(x: f64) -> Option<Self> {
+        Some(x as f32)
+    }
+}
+
+impl DagValue for bool {
+    fn into_f64(self) -> f64 {
+        if self {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn from_f64(x: f64) -> Option<Self> {
+        if x == 1.0 {
+            Some(true)
+        } else if x == 0.0 {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+macro_rules! impl_dag_value_uint {
+    ($kind:ty) => {
+        impl DagValue for $kind {
+            fn into_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn from_f64(x: f64) -> Option<Self> {
+                if x >= 0.0 && x <= <$kind>::MAX as f64 && x.fract() == 0.0 {
+                    Some(x as $kind)
+                } else {
+                    None
+                }
+            }
+        }
+    };
+}
+
+impl_dag_value_uint!(u8);
+impl_dag_value_uint!(u16);
+impl_dag_value_uint!(u32);
+impl_dag_value_uint!(usize);
+
+/// Identifies a node in a [`DagBuilder`] and the [`Dag`] it builds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    /// The position of the node's value in a draw from the [`Dag`]
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DagError {
+    /// Another node already has this name
+    DuplicateName { name: String },
+    /// A parent is not a node in this builder
+    UnknownParent { ix: usize, n_nodes: usize },
+}
+
+/// Object-safe wrapper around a node's conditional distribution
+trait NodeFn: Send + Sync {
+    fn ln_f(&self, parents: &[f64], x: f64) -> f64;
+    fn draw(&self, parents: &[f64], rng: &mut dyn RngCore) -> f64;
+}
+
+struct Node<X, Fx, F>
+where
+    F: Fn(&[f64]) -> Fx,
+{
+    f: F,
+    _phantom: std::marker::PhantomData<fn() -> (X, Fx)>,
+}
+
+impl<X, Fx, F> NodeFn for Node<X, Fx, F>
+where
+    X: DagValue,
+    Fx: Rv<X>,
+    F: Fn(&[f64]) -> Fx + Send + Sync,
+{
+    fn ln_f(&self, parents: &[f64], x: f64) -> f64 {
+        match X::from_f64(x) {
+            Some(x) => (self.f)(parents).ln_f(&x),
+            None => f64::NEG_INFINITY,
+        }
+    }
+
+    fn draw(&self, parents: &[f64], mut rng: &mut dyn RngCore) -> f64 {
+        (self.f)(parents).draw(&mut rng).into_f64()
+    }
+}
+
+#[derive(Clone)]
+struct DagNode {
+    name: String,
+    parents: Vec<usize>,
+    dist: Arc<dyn NodeFn>,
+}
+
+impl fmt::Debug for DagNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DagNode")
+            .field("name", &self.name)
+            .field("parents", &self.parents)
+            .finish()
+    }
+}
+
+/// Builds a [`Dag`] one node at a time
+///
+/// A node can only have nodes that already exist as parents, so the graph
+/// is acyclic by construction and the nodes are in ancestral order.
+#[derive(Debug, Clone, Default)]
+pub struct DagBuilder {
+    nodes: Vec<DagNode>,
+}
+
+impl DagBuilder {
+    /// Create a builder with no nodes
+    pub fn new() -> Self {
+        DagBuilder { nodes: Vec::new() }
+    }
+
+    /// Add a node over `f64` whose distribution is `f` of the values of its
+    /// `parents`, passed in the order given
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Gaussian;
+    /// use rv::model::DagBuilder;
+    ///
+    /// let mut builder = DagBuilder::new();
+    /// let mu = builder.node("mu", &[], |_| Gaussian::standard()).unwrap();
+    /// let _x = builder
+    ///     .node("x", &[mu], |pa| Gaussian::new_unchecked(pa[0], 1.0))
+    ///     .unwrap();
+    ///
+    /// // Names must be unique
+    /// assert!(builder.node("mu", &[], |_| Gaussian::standard()).is_err());
+    /// ```
+    pub fn node<Fx, F>(
+        &mut self,
+        name: &str,
+        parents: &[NodeId],
+        f: F,
+    ) -> Result<NodeId, DagError>
+    where
+        Fx: Rv<f64> + 'static,
+        F: Fn(&[f64]) -> Fx + Send + Sync + 'static,
+    {
+        self.typed_node::<f64, Fx>(name, parents, f)
+    }
+
+    /// Add a node over values of type `X`, e.g., `bool` or `u32` for
+    /// discrete distributions
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::{Bernoulli, Beta};
+    /// use rv::model::DagBuilder;
+    ///
+    /// let mut builder = DagBuilder::new();
+    /// let p = builder.node("p", &[], |_| Beta::jeffreys()).unwrap();
+    /// let _coin = builder
+    ///     .typed_node::<bool, _>("coin", &[p], |pa| {
+    ///         Bernoulli::new_unchecked(pa[0])
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn typed_node<X, Fx>(
+        &mut self,
+        name: &str,
+        parents: &[NodeId],
+        f: impl Fn(&[f64]) -> Fx + Send + Sync + 'static,
+    ) -> Result<NodeId, DagError>
+    where
+        X: DagValue + 'static,
+        Fx: Rv<X> + 'static,
+    {
+        if self.nodes.iter().any(|node| node.name == name) {
+            return Err(DagError::DuplicateName {
+                name: name.to_owned(),
+            });
+        }
+        let n_nodes = self.nodes.len();
+        if let Some(parent) = parents.iter().find(|id| id.0 >= n_nodes) {
+            return Err(DagError::UnknownParent {
+                ix: parent.0,
+                n_nodes,
+            });
+        }
+        self.nodes.push(DagNode {
+            name: name.to_owned(),
+            parents: parents.iter().map(|id| id.0).collect(),
+            dist: Arc::new(Node {
+                f,
+                _phantom: std::marker::PhantomData::<fn() -> (X, Fx)>,
+            }),
+        });
+        Ok(NodeId(n_nodes))
+    }
+
+    /// Finish building
+    pub fn build(self) -> Dag {
+        Dag { nodes: self.nodes }
+    }
+}
+
+/// A generative model given by a directed acyclic graph of distributions
+///
+/// Each node is an rv distribution whose parameters are a deterministic
+/// function of the values of its parents. The joint distribution is over a
+/// `Vec<f64>` with one value per node, in the order the nodes were added.
+/// Draws are by ancestral sampling.
+///
+/// # Example
+///
+/// A hierarchical model for the mean of some Gaussian data
+///
+/// ```
+/// use rv::dist::{Gaussian, InvGamma};
+/// use rv::model::DagBuilder;
+/// use rv::traits::*;
+///
+/// let mut builder = DagBuilder::new();
+/// let mu = builder.node("mu", &[], |_| Gaussian::standard()).unwrap();
+/// let var = builder
+///     .node("var", &[], |_| InvGamma::new_unchecked(2.0, 1.0))
+///     .unwrap();
+/// let x = builder
+///     .node("x", &[mu, var], |pa| Gaussian::new_unchecked(pa[0], pa[1].sqrt()))
+///     .unwrap();
+/// let dag = builder.build();
+///
+/// let mut rng = rand::thread_rng();
+/// let draw: Vec<f64> = dag.draw(&mut rng);
+/// assert_eq!(draw.len(), 3);
+///
+/// // The joint density is the product of the node densities
+/// let values = vec![0.5, 2.0, 1.0];
+/// let ln_f = Gaussian::standard().ln_f(&0.5)
+///     + InvGamma::new(2.0, 1.0).unwrap().ln_f(&2.0)
+///     + Gaussian::new(0.5, 2.0_f64.sqrt()).unwrap().ln_f(&1.0);
+/// assert::close(dag.ln_f(&values), ln_f, 1E-12);
+/// assert_eq!(dag.id("x"), Some(x));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Dag {
+    nodes: Vec<DagNode>,
+}
+
+impl Dag {
+    /// Create a builder
+    pub fn builder() -> DagBuilder {
+        DagBuilder::new()
+    }
+
+    /// The number of nodes
+    #[inline]
+    pub fn n_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The id of the node named `name`
+    pub fn id(&self, name: &str) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .position(|node| node.name == name)
+            .map(NodeId)
+    }
+
+    /// The name of a node
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.nodes[id.0].name
+    }
+
+    /// The parents of a node
+    pub fn parents(&self, id: NodeId) -> Vec<NodeId> {
+        self.nodes[id.0]
+            .parents
+            .iter()
+            .map(|&ix| NodeId(ix))
+            .collect()
+    }
+
+    /// The log density of a node's value given the values of its parents in
+    /// `values`
+    pub fn ln_f_node(&self, id: NodeId, values: &[f64]) -> f64 {
+        let node = &self.nodes[id.0];
+        let parents: Vec<f64> =
+            node.parents.iter().map(|&ix| values[ix]).collect();
+        node.dist.ln_f(&parents, values[id.0])
+    }
+
+    /// The log density of every node given the values of its parents
+    pub fn ln_f_nodes(&self, values: &[f64]) -> Vec<f64> {
+        (0..self.nodes.len())
+            .map(|ix| self.ln_f_node(NodeId(ix), values))
+            .collect()
+    }
+
+    /// Add the log density of every node to `acc`, with the node names as
+    /// sources and `ix` as the index of `values` in a data set
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Gaussian;
+    /// use rv::model::{DagBuilder, LogpAccumulator};
+    ///
+    /// let mut builder = DagBuilder::new();
+    /// let mu = builder.node("mu", &[], |_| Gaussian::standard()).unwrap();
+    /// builder
+    ///     .node("x", &[mu], |pa| Gaussian::new_unchecked(pa[0], 0.1))
+    ///     .unwrap();
+    /// let dag = builder.build();
+    ///
+    /// let data = vec![vec![0.1, 0.15], vec![-0.2, -0.3], vec![0.3, 2.0]];
+    /// let mut acc = LogpAccumulator::tracing();
+    /// data.iter()
+    ///     .enumerate()
+    ///     .for_each(|(ix, values)| dag.accumulate_ln_f(ix, values, &mut acc));
+    ///
+    /// // x in the last row is far from its mean
+    /// let worst = acc.worst(1);
+    /// assert_eq!((worst[0].source.as_str(), worst[0].ix), ("x", 2));
+    /// ```
+    pub fn accumulate_ln_f(
+        &self,
+        ix: usize,
+        values: &[f64],
+        acc: &mut LogpAccumulator,
+    ) {
+        self.nodes.iter().enumerate().for_each(|(node_ix, node)| {
+            acc.push(&node.name, ix, self.ln_f_node(NodeId(node_ix), values));
+        });
+    }
+}
+
+impl Rv<Vec<f64>> for Dag {
+    fn ln_f(&self, x: &Vec<f64>) -> f64 {
+        (0..self.nodes.len())
+            .map(|ix| self.ln_f_node(NodeId(ix), x))
+            .sum()
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> Vec<f64> {
+        let mut values = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let parents: Vec<f64> =
+                node.parents.iter().map(|&ix| values[ix]).collect();
+            values.push(node.dist.draw(&parents, rng));
+        }
+        values
+    }
+}
+
+impl Support<Vec<f64>> for Dag {
+    fn supports(&self, x: &Vec<f64>) -> bool {
+        x.len() == self.nodes.len() && self.ln_f(x) > f64::NEG_INFINITY
+    }
+}
+
+impl std::error::Error for DagError {}
+
+impl fmt::Display for DagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateName { name } => {
+                write!(f, "a node named '{}' already exists", name)
+            }
+            Self::UnknownParent { ix, n_nodes } => write!(
+                f,
+                "parent {} is not one of the {} nodes in the builder",
+                ix, n_nodes
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Bernoulli, Beta, Gaussian, Poisson};
+    use crate::misc::ks_test;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    fn coin_flips() -> Dag {
+        let mut builder = DagBuilder::new();
+        let p = builder.node("p", &[], |_| Beta::new_unchecked(2.0, 3.0));
+        let p = p.unwrap();
+        for i in 0..3 {
+            builder
+                .typed_node::<bool, _>(&format!("flip_{}", i), &[p], |pa| {
+                    Bernoulli::new_unchecked(pa[0])
+                })
+                .unwrap();
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn ln_f_is_sum_of_nodes() {
+        let dag = coin_flips();
+        let values = vec![0.3, 1.0, 0.0, 1.0];
+        let beta = Beta::new(2.0, 3.0).unwrap();
+        let bern = Bernoulli::new(0.3).unwrap();
+        let ln_f = beta.ln_f(&0.3)
+            + bern.ln_f(&true)
+            + bern.ln_f(&false)
+            + bern.ln_f(&true);
+        assert::close(dag.ln_f(&values), ln_f, TOL);
+        assert::close(dag.ln_f_nodes(&values).iter().sum::<f64>(), ln_f, TOL);
+
+        // A flip that isn't a bool is outside the support
+        assert!(!dag.supports(&vec![0.3, 0.5, 0.0, 1.0]));
+        assert!(dag.supports(&values));
+    }
+
+    #[test]
+    fn discrete_draws_round_trip() {
+        let mut builder = DagBuilder::new();
+        let rate = builder.node("rate", &[], |_| Beta::uniform()).unwrap();
+        builder
+            .typed_node::<u32, _>("count", &[rate], |pa| {
+                Poisson::new_unchecked(10.0 * pa[0])
+            })
+            .map(|id| assert_eq!(id.index(), 1))
+            .unwrap();
+        let dag = builder.build();
+
+        let mut rng = Xoshiro256Plus::seed_from_u64(7);
+        for _ in 0..100 {
+            let x: Vec<f64> = dag.draw(&mut rng);
+            assert_eq!(x[1].fract(), 0.0);
+            assert!(dag.ln_f(&x).is_finite());
+        }
+    }
+
+    #[test]
+    fn ancestral_draws_have_right_marginal() {
+        // x ~ N(0, 1), y | x ~ N(x, 1) => y ~ N(0, √2)
+        let mut builder = DagBuilder::new();
+        let x = builder.node("x", &[], |_| Gaussian::standard()).unwrap();
+        builder
+            .node("y", &[x], |pa| Gaussian::new_unchecked(pa[0], 1.0))
+            .unwrap();
+        let dag = builder.build();
+
+        let mut rng = Xoshiro256Plus::seed_from_u64(1337);
+        let ys: Vec<f64> = dag
+            .sample(1000, &mut rng)
+            .iter()
+            .map(|xy: &Vec<f64>| xy[1])
+            .collect();
+        let marginal = Gaussian::new(0.0, 2.0_f64.sqrt()).unwrap();
+        let (_, p) = ks_test(&ys, |y| marginal.cdf(&y));
+        assert!(p > 0.01);
+    }
+
+    #[test]
+    fn builder_validates_nodes() {
+        let mut builder = DagBuilder::new();
+        let x = builder.node("x", &[], |_| Gaussian::standard()).unwrap();
+        assert_eq!(
+            builder.node("x", &[], |_| Gaussian::standard()),
+            Err(DagError::DuplicateName {
+                name: String::from("x")
+            })
+        );
+        assert_eq!(
+            builder.node("y", &[x, NodeId(4)], |_| Gaussian::standard()),
+            Err(DagError::UnknownParent { ix: 4, n_nodes: 1 })
+        );
+
+        let dag = builder.build();
+        assert_eq!(dag.n_nodes(), 1);
+        assert_eq!(dag.id("x"), Some(x));
+        assert_eq!(dag.name(x), "x");
+        assert!(dag.parents(x).is_empty());
+    }
+}
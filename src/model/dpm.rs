@@ -0,0 +1,513 @@
+//! Dirichlet process mixtures with a choice of inference backend
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::DataOrSuffStat;
+use crate::dist::Gamma;
+use crate::model::{DpMixtureSlice, DpMixtureSvi, SviComponent};
+use crate::traits::*;
+use rand::Rng;
+use std::fmt;
+
+/// How to fit a [`DpMixture`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum DpmInference {
+    /// Stochastic variational inference on a truncated stick-breaking
+    /// representation. Scales to datasets far too large for sampling.
+    Svi {
+        /// The maximum number of components
+        truncation: usize,
+        /// The number of data in each minibatch
+        batch_size: usize,
+        /// The number of natural gradient steps
+        n_steps: usize,
+    },
+    /// Walker's slice sampler, which has no truncation error
+    Slice {
+        /// The number of sweeps over the data
+        n_iters: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum DpMixtureError {
+    /// The concentration parameter is less than or equal to zero
+    AlphaTooLow { alpha: f64 },
+    /// The concentration parameter is infinite or NaN
+    AlphaNotFinite { alpha: f64 },
+    /// The SVI truncation level is zero
+    TruncationTooLow,
+    /// The SVI minibatch size is zero
+    BatchSizeTooLow,
+}
+
+// The derives cannot see that the slice state needs bounds on `Fx::Stat`, so
+// the trait impls for the fitted state and the mixture are written out, and
+// the serde bounds are given explicitly.
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+#[cfg_attr(
+    feature = "serde1",
+    serde(bound(
+        serialize = "Fx: Serialize, Fx::Stat: Serialize, Pr: Serialize",
+        deserialize = "Fx: Deserialize<'de>, Fx::Stat: Deserialize<'de>, \
+                       Pr: Deserialize<'de>"
+    ))
+)]
+enum DpmFit<X, Fx, Pr>
+where
+    Fx: HasSuffStat<X>,
+{
+    Svi(DpMixtureSvi<X, Fx, Pr>),
+    Slice(DpMixtureSlice<X, Fx, Pr>),
+}
+
+impl<X, Fx, Pr> Clone for DpmFit<X, Fx, Pr>
+where
+    Fx: HasSuffStat<X>,
+    DpMixtureSvi<X, Fx, Pr>: Clone,
+    DpMixtureSlice<X, Fx, Pr>: Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Self::Svi(svi) => Self::Svi(svi.clone()),
+            Self::Slice(slice) => Self::Slice(slice.clone()),
+        }
+    }
+}
+
+impl<X, Fx, Pr> fmt::Debug for DpmFit<X, Fx, Pr>
+where
+    Fx: HasSuffStat<X>,
+    DpMixtureSvi<X, Fx, Pr>: fmt::Debug,
+    DpMixtureSlice<X, Fx, Pr>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Svi(svi) => f.debug_tuple("Svi").field(svi).finish(),
+            Self::Slice(slice) => f.debug_tuple("Slice").field(slice).finish(),
+        }
+    }
+}
+
+impl<X, Fx, Pr> PartialEq for DpmFit<X, Fx, Pr>
+where
+    Fx: HasSuffStat<X>,
+    DpMixtureSvi<X, Fx, Pr>: PartialEq,
+    DpMixtureSlice<X, Fx, Pr>: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Svi(a), Self::Svi(b)) => a == b,
+            (Self::Slice(a), Self::Slice(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// A Dirichlet process mixture of conjugate components
+///
+/// The inference backend is chosen with [`DpmInference`]; both fit the same
+/// model, and the fitted mixture is used the same way regardless of the
+/// backend. Before fitting, the mixture is the prior predictive.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{Gamma, Poisson};
+/// use rv::model::{DpMixture, DpmInference};
+/// use rv::traits::*;
+///
+/// let mut rng = rand::thread_rng();
+///
+/// let xs: Vec<u32> = vec![0, 1, 0, 2, 1, 40, 38, 41, 0, 39, 1, 42];
+///
+/// for inference in [
+///     DpmInference::Svi { truncation: 10, batch_size: 12, n_steps: 200 },
+///     DpmInference::Slice { n_iters: 200 },
+/// ] {
+///     let prior = Gamma::new(1.0, 0.1).unwrap();
+///     let mut dpm: DpMixture<u32, Poisson, Gamma> =
+///         DpMixture::new(1.0, prior, inference).unwrap();
+///     dpm.fit(&xs, &mut rng);
+///
+///     // Counts between the clusters are unlikely
+///     assert!(dpm.f(&20) < dpm.f(&1));
+///     assert!(dpm.f(&20) < dpm.f(&40));
+/// }
+/// ```
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+#[cfg_attr(
+    feature = "serde1",
+    serde(bound(
+        serialize = "Fx: Serialize, Fx::Stat: Serialize, Pr: Serialize",
+        deserialize = "Fx: Deserialize<'de>, Fx::Stat: Deserialize<'de>, \
+                       Pr: Deserialize<'de>"
+    ))
+)]
+pub struct DpMixture<X, Fx, Pr>
+where
+    Fx: HasSuffStat<X>,
+{
+    alpha: f64,
+    alpha_prior: Option<Gamma>,
+    prior: Pr,
+    inference: DpmInference,
+    fit: Option<DpmFit<X, Fx, Pr>>,
+}
+
+impl<X, Fx, Pr> Clone for DpMixture<X, Fx, Pr>
+where
+    Fx: HasSuffStat<X>,
+    Pr: Clone,
+    DpmFit<X, Fx, Pr>: Clone,
+{
+    fn clone(&self) -> Self {
+        DpMixture {
+            alpha: self.alpha,
+            alpha_prior: self.alpha_prior.clone(),
+            prior: self.prior.clone(),
+            inference: self.inference.clone(),
+            fit: self.fit.clone(),
+        }
+    }
+}
+
+impl<X, Fx, Pr> fmt::Debug for DpMixture<X, Fx, Pr>
+where
+    Fx: HasSuffStat<X>,
+    Pr: fmt::Debug,
+    DpmFit<X, Fx, Pr>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DpMixture")
+            .field("alpha", &self.alpha)
+            .field("alpha_prior", &self.alpha_prior)
+            .field("prior", &self.prior)
+            .field("inference", &self.inference)
+            .field("fit", &self.fit)
+            .finish()
+    }
+}
+
+impl<X, Fx, Pr> PartialEq for DpMixture<X, Fx, Pr>
+where
+    Fx: HasSuffStat<X>,
+    Pr: PartialEq,
+    DpmFit<X, Fx, Pr>: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.alpha == other.alpha
+            && self.alpha_prior == other.alpha_prior
+            && self.prior == other.prior
+            && self.inference == other.inference
+            && self.fit == other.fit
+    }
+}
+
+impl<X, Fx, Pr> DpMixture<X, Fx, Pr>
+where
+    X: Clone,
+    Fx: Rv<X> + HasSuffStat<X>,
+    Pr: SviComponent<X, Fx>,
+{
+    /// Create a new mixture with concentration `alpha` and component prior
+    /// `prior` to be fit by `inference`
+    pub fn new(
+        alpha: f64,
+        prior: Pr,
+        inference: DpmInference,
+    ) -> Result<Self, DpMixtureError> {
+        if alpha <= 0.0 {
+            return Err(DpMixtureError::AlphaTooLow { alpha });
+        } else if !alpha.is_finite() {
+            return Err(DpMixtureError::AlphaNotFinite { alpha });
+        }
+        if let DpmInference::Svi {
+            truncation,
+            batch_size,
+            ..
+        } = inference
+        {
+            if truncation == 0 {
+                return Err(DpMixtureError::TruncationTooLow);
+            } else if batch_size == 0 {
+                return Err(DpMixtureError::BatchSizeTooLow);
+            }
+        }
+        Ok(DpMixture {
+            alpha,
+            alpha_prior: None,
+            prior,
+            inference,
+            fit: None,
+        })
+    }
+
+    /// Infer alpha under a Gamma prior instead of holding it fixed. The
+    /// slice sampler resamples alpha every sweep; SVI sets alpha to its
+    /// variational mean every step.
+    pub fn with_alpha_prior(mut self, alpha_prior: Gamma) -> Self {
+        self.alpha_prior = Some(alpha_prior);
+        self
+    }
+
+    /// The DP concentration parameter. If alpha is inferred and the mixture
+    /// has been fit, this is the backend's current alpha.
+    pub fn alpha(&self) -> f64 {
+        match &self.fit {
+            Some(DpmFit::Svi(svi)) => svi.alpha(),
+            Some(DpmFit::Slice(slice)) => slice.alpha(),
+            None => self.alpha,
+        }
+    }
+
+    /// The prior on alpha, if alpha is inferred
+    #[inline]
+    pub fn alpha_prior(&self) -> Option<&Gamma> {
+        self.alpha_prior.as_ref()
+    }
+
+    /// The inference backend
+    #[inline]
+    pub fn inference(&self) -> &DpmInference {
+        &self.inference
+    }
+
+    /// Whether the mixture has been fit
+    #[inline]
+    pub fn is_fit(&self) -> bool {
+        self.fit.is_some()
+    }
+
+    /// The weights of the fitted components, empty if not fit
+    ///
+    /// Under SVI these are the expected weights of all the truncated
+    /// components. Under slice sampling these are the instantiated weights
+    /// of the last sweep, which sum to less than one.
+    pub fn weights(&self) -> Vec<f64> {
+        match &self.fit {
+            Some(DpmFit::Svi(svi)) => svi.weights(),
+            Some(DpmFit::Slice(slice)) => slice.weights().to_vec(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The SVI state, if fit by SVI
+    pub fn svi(&self) -> Option<&DpMixtureSvi<X, Fx, Pr>> {
+        match &self.fit {
+            Some(DpmFit::Svi(svi)) => Some(svi),
+            _ => None,
+        }
+    }
+
+    /// The slice sampler state, if fit by slice sampling
+    pub fn slice(&self) -> Option<&DpMixtureSlice<X, Fx, Pr>> {
+        match &self.fit {
+            Some(DpmFit::Slice(slice)) => Some(slice),
+            _ => None,
+        }
+    }
+
+    /// Fit the mixture to `xs` with the chosen backend, continuing from any
+    /// previous fit
+    pub fn fit<R: Rng>(&mut self, xs: &[X], rng: &mut R) {
+        match self.inference {
+            DpmInference::Svi {
+                truncation,
+                batch_size,
+                n_steps,
+            } => {
+                if !matches!(self.fit, Some(DpmFit::Svi(_))) {
+                    let mut svi = DpMixtureSvi::new(
+                        self.alpha,
+                        truncation,
+                        self.prior.clone(),
+                    )
+                    .expect("Invalid SVI params");
+                    if let Some(alpha_prior) = self.alpha_prior.clone() {
+                        svi = svi.with_alpha_prior(alpha_prior);
+                    }
+                    self.fit = Some(DpmFit::Svi(svi));
+                }
+                if let Some(DpmFit::Svi(svi)) = self.fit.as_mut() {
+                    svi.fit(xs, batch_size, n_steps, rng);
+                }
+            }
+            DpmInference::Slice { n_iters } => {
+                if !matches!(self.fit, Some(DpmFit::Slice(_))) {
+                    let mut slice =
+                        DpMixtureSlice::new(self.alpha, self.prior.clone())
+                            .expect("Invalid slice sampler params");
+                    if let Some(alpha_prior) = self.alpha_prior.clone() {
+                        slice = slice.with_alpha_prior(alpha_prior);
+                    }
+                    self.fit = Some(DpmFit::Slice(slice));
+                }
+                if let Some(DpmFit::Slice(slice)) = self.fit.as_mut() {
+                    slice.run(xs, n_iters, rng);
+                }
+            }
+        }
+    }
+}
+
+impl<X, Fx, Pr> Rv<X> for DpMixture<X, Fx, Pr>
+where
+    Fx: Rv<X> + HasSuffStat<X>,
+    Pr: SviComponent<X, Fx>,
+{
+    fn ln_f(&self, x: &X) -> f64 {
+        match &self.fit {
+            Some(DpmFit::Svi(svi)) => svi.ln_f(x),
+            Some(DpmFit::Slice(slice)) => slice.ln_f(x),
+            None => self.prior.ln_pp(x, &DataOrSuffStat::None),
+        }
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> X {
+        match &self.fit {
+            Some(DpmFit::Svi(svi)) => svi.draw(rng),
+            Some(DpmFit::Slice(slice)) => slice.draw(rng),
+            None => {
+                let fx: Fx = self.prior.draw(rng);
+                fx.draw(rng)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DpMixtureError {}
+
+impl fmt::Display for DpMixtureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlphaTooLow { alpha } => {
+                write!(f, "alpha ({}) must be greater than zero", alpha)
+            }
+            Self::AlphaNotFinite { alpha } => {
+                write!(f, "non-finite alpha: {}", alpha)
+            }
+            Self::TruncationTooLow => {
+                write!(f, "truncation must be at least one")
+            }
+            Self::BatchSizeTooLow => {
+                write!(f, "batch size must be at least one")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Gaussian, NormalGamma};
+
+    fn prior() -> NormalGamma {
+        NormalGamma::new_unchecked(0.0, 1.0, 1.0, 1.0)
+    }
+
+    #[test]
+    fn new_validates_inference() {
+        let new = |inference| {
+            DpMixture::<f64, Gaussian, NormalGamma>::new(
+                1.0,
+                prior(),
+                inference,
+            )
+        };
+        assert!(new(DpmInference::Slice { n_iters: 1 }).is_ok());
+        assert_eq!(
+            new(DpmInference::Svi {
+                truncation: 0,
+                batch_size: 10,
+                n_steps: 1
+            }),
+            Err(DpMixtureError::TruncationTooLow)
+        );
+        assert_eq!(
+            new(DpmInference::Svi {
+                truncation: 5,
+                batch_size: 0,
+                n_steps: 1
+            }),
+            Err(DpMixtureError::BatchSizeTooLow)
+        );
+    }
+
+    #[test]
+    fn unfit_is_prior_predictive() {
+        let dpm: DpMixture<f64, Gaussian, NormalGamma> =
+            DpMixture::new(1.0, prior(), DpmInference::Slice { n_iters: 1 })
+                .unwrap();
+        assert!(!dpm.is_fit());
+        assert!(dpm.weights().is_empty());
+        let ln_pp: f64 = prior().ln_pp(&0.5, &DataOrSuffStat::None);
+        assert_eq!(dpm.ln_f(&0.5), ln_pp);
+    }
+
+    #[test]
+    fn fit_uses_the_chosen_backend() {
+        let mut rng = rand::thread_rng();
+        let xs: Vec<f64> = vec![-1.0, -0.5, 0.0, 0.5, 1.0];
+
+        let mut dpm: DpMixture<f64, Gaussian, NormalGamma> =
+            DpMixture::new(1.0, prior(), DpmInference::Slice { n_iters: 3 })
+                .unwrap();
+        dpm.fit(&xs, &mut rng);
+        assert_eq!(dpm.slice().unwrap().n_iters(), 3);
+        assert!(dpm.svi().is_none());
+
+        let mut dpm: DpMixture<f64, Gaussian, NormalGamma> = DpMixture::new(
+            1.0,
+            prior(),
+            DpmInference::Svi {
+                truncation: 4,
+                batch_size: 2,
+                n_steps: 7,
+            },
+        )
+        .unwrap();
+        dpm.fit(&xs, &mut rng);
+        assert_eq!(dpm.svi().unwrap().n_steps(), 7);
+        assert_eq!(dpm.weights().len(), 4);
+    }
+
+    #[test]
+    fn alpha_prior_is_passed_to_the_backend() {
+        let mut rng = rand::thread_rng();
+        let xs: Vec<f64> = vec![-1.0, -0.5, 0.0, 0.5, 1.0];
+        let alpha_prior = Gamma::new(2.0, 2.0).unwrap();
+
+        let mut dpm: DpMixture<f64, Gaussian, NormalGamma> =
+            DpMixture::new(1.0, prior(), DpmInference::Slice { n_iters: 3 })
+                .unwrap()
+                .with_alpha_prior(alpha_prior.clone());
+        dpm.fit(&xs, &mut rng);
+        let slice = dpm.slice().unwrap();
+        assert_eq!(slice.alpha_prior(), Some(&alpha_prior));
+        assert_eq!(dpm.alpha(), slice.alpha());
+
+        let mut dpm: DpMixture<f64, Gaussian, NormalGamma> = DpMixture::new(
+            1.0,
+            prior(),
+            DpmInference::Svi {
+                truncation: 4,
+                batch_size: 2,
+                n_steps: 7,
+            },
+        )
+        .unwrap()
+        .with_alpha_prior(alpha_prior.clone());
+        dpm.fit(&xs, &mut rng);
+        let svi = dpm.svi().unwrap();
+        assert_eq!(svi.alpha_prior(), Some(&alpha_prior));
+        assert_eq!(dpm.alpha(), svi.alpha());
+        assert!(dpm.alpha() != 1.0);
+    }
+}
@@ -0,0 +1,389 @@
+//! Slice sampling for Dirichlet process mixtures
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::DataOrSuffStat;
+use crate::dist::{draw_alpha_posterior, Beta, Gamma};
+use crate::misc::{ln_pflip, logsumexp};
+use crate::model::DpMixtureError;
+use crate::traits::*;
+use rand::Rng;
+use std::marker::PhantomData;
+
+/// A Dirichlet process mixture fit by Walker's slice sampler
+///
+/// Each observation gets a uniform slice variable under the weight of its
+/// component, and only components whose weight exceeds some slice need to be
+/// instantiated. Sticks are broken lazily as the slices require, so the
+/// sampler targets the full infinite mixture without any truncation error.
+///
+/// # Example
+///
+/// ```
+/// use rand::SeedableRng;
+/// use rand_xoshiro::Xoshiro256Plus;
+/// use rv::dist::{Gaussian, NormalGamma};
+/// use rv::model::DpMixtureSlice;
+/// use rv::traits::*;
+///
+/// let mut rng = Xoshiro256Plus::seed_from_u64(0x5eed);
+///
+/// let g1 = Gaussian::new(-5.0, 1.0).unwrap();
+/// let g2 = Gaussian::new(5.0, 1.0).unwrap();
+/// let mut xs: Vec<f64> = g1.sample(100, &mut rng);
+/// let xs2: Vec<f64> = g2.sample(100, &mut rng);
+/// xs.extend(xs2);
+///
+/// let prior = NormalGamma::new_unchecked(0.0, 0.1, 1.0, 1.0);
+/// let mut dpm: DpMixtureSlice<f64, Gaussian, NormalGamma> =
+///     DpMixtureSlice::new(1.0, prior).unwrap();
+/// dpm.run(&xs, 100, &mut rng);
+///
+/// // Values between the clusters are unlikely
+/// assert!(dpm.f(&0.0) < dpm.f(&-5.0));
+/// assert!(dpm.f(&0.0) < dpm.f(&5.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+#[cfg_attr(
+    feature = "serde1",
+    serde(bound(
+        serialize = "Fx: Serialize, Fx::Stat: Serialize, Pr: Serialize",
+        deserialize = "Fx: Deserialize<'de>, Fx::Stat: Deserialize<'de>, \
+                       Pr: Deserialize<'de>"
+    ))
+)]
+pub struct DpMixtureSlice<X, Fx, Pr>
+where
+    Fx: HasSuffStat<X>,
+{
+    /// DP concentration parameter
+    alpha: f64,
+    /// Prior on alpha. If present, alpha is resampled every sweep.
+    alpha_prior: Option<Gamma>,
+    /// The prior on the component parameters
+    prior: Pr,
+    /// The instantiated stick-breaking weights
+    weights: Vec<f64>,
+    /// The instantiated component parameters
+    components: Vec<Fx>,
+    /// The sufficient statistic of the data assigned to each component
+    stats: Vec<Fx::Stat>,
+    /// The component index of each datum
+    assignment: Vec<usize>,
+    /// Number of sweeps taken
+    n_iters: usize,
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    _phantom: PhantomData<X>,
+}
+
+impl<X, Fx, Pr> DpMixtureSlice<X, Fx, Pr>
+where
+    Fx: Rv<X> + HasSuffStat<X>,
+    Pr: ConjugatePrior<X, Fx>,
+{
+    /// Create a new mixture with concentration `alpha` and component prior
+    /// `prior`
+    pub fn new(alpha: f64, prior: Pr) -> Result<Self, DpMixtureError> {
+        if alpha <= 0.0 {
+            Err(DpMixtureError::AlphaTooLow { alpha })
+        } else if !alpha.is_finite() {
+            Err(DpMixtureError::AlphaNotFinite { alpha })
+        } else {
+            Ok(DpMixtureSlice {
+                alpha,
+                alpha_prior: None,
+                prior,
+                weights: Vec::new(),
+                components: Vec::new(),
+                stats: Vec::new(),
+                assignment: Vec::new(),
+                n_iters: 0,
+                _phantom: PhantomData,
+            })
+        }
+    }
+
+    /// Resample alpha every sweep under a Gamma prior instead of holding it
+    /// fixed
+    pub fn with_alpha_prior(mut self, alpha_prior: Gamma) -> Self {
+        self.alpha_prior = Some(alpha_prior);
+        self
+    }
+
+    /// The DP concentration parameter. With a prior on alpha, this is the
+    /// draw from the last sweep.
+    #[inline]
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// The prior on alpha, if alpha is resampled
+    #[inline]
+    pub fn alpha_prior(&self) -> Option<&Gamma> {
+        self.alpha_prior.as_ref()
+    }
+
+    /// The number of sweeps taken
+    #[inline]
+    pub fn n_iters(&self) -> usize {
+        self.n_iters
+    }
+
+    /// The component index of each datum from the last sweep
+    #[inline]
+    pub fn assignment(&self) -> &[usize] {
+        &self.assignment
+    }
+
+    /// The number of components with data assigned
+    pub fn n_occupied(&self) -> usize {
+        self.stats.iter().filter(|stat| stat.n() > 0).count()
+    }
+
+    /// The instantiated component weights. These sum to less than one; the
+    /// remaining mass belongs to components not yet instantiated.
+    #[inline]
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// The instantiated component parameters
+    #[inline]
+    pub fn components(&self) -> &[Fx] {
+        &self.components
+    }
+
+    /// The sufficient statistic of the data assigned to each component
+    #[inline]
+    pub fn stats(&self) -> &[Fx::Stat] {
+        &self.stats
+    }
+
+    /// Run `n_iters` sweeps of the sampler over `xs`
+    ///
+    /// If `xs` differs in length from the data of previous sweeps, the
+    /// sampler starts over with every datum in one component.
+    pub fn run<R: Rng>(&mut self, xs: &[X], n_iters: usize, rng: &mut R) {
+        if xs.is_empty() {
+            return;
+        }
+        if self.assignment.len() != xs.len() {
+            self.assignment = vec![0; xs.len()];
+            self.n_iters = 0;
+            self.rebuild_stats(xs, 1, rng);
+        }
+        (0..n_iters).for_each(|_| self.step(xs, rng));
+    }
+
+    fn rebuild_stats<R: Rng>(&mut self, xs: &[X], n_cpnts: usize, rng: &mut R) {
+        let template: Fx = self.prior.draw(rng);
+        self.stats = (0..n_cpnts).map(|_| template.empty_suffstat()).collect();
+        xs.iter()
+            .zip(self.assignment.iter())
+            .for_each(|(x, &z)| self.stats[z].observe(x));
+    }
+
+    fn step<R: Rng>(&mut self, xs: &[X], rng: &mut R) {
+        // Break the sticks of the occupied components given the counts
+        let counts: Vec<f64> =
+            self.stats.iter().map(|stat| stat.n() as f64).collect();
+        let mut tail: f64 = counts.iter().sum();
+        let mut rest = 1.0;
+        self.weights = counts
+            .iter()
+            .map(|&n_k| {
+                tail -= n_k;
+                let v: f64 =
+                    Beta::new_unchecked(1.0 + n_k, self.alpha + tail).draw(rng);
+                let w = rest * v;
+                rest *= 1.0 - v;
+                w
+            })
+            .collect();
+
+        // Draw a slice under the weight of each datum's component
+        let slices: Vec<f64> = self
+            .assignment
+            .iter()
+            .map(|&z| rng.gen::<f64>() * self.weights[z])
+            .collect();
+        let u_min = slices.iter().copied().fold(f64::INFINITY, f64::min);
+
+        // Break new sticks until the uninstantiated mass is below every slice
+        let stick_prior = Beta::new_unchecked(1.0, self.alpha);
+        while rest > u_min {
+            let v: f64 = stick_prior.draw(rng);
+            self.weights.push(rest * v);
+            rest *= 1.0 - v;
+        }
+
+        // Draw the component parameters
+        let template: Fx = self.prior.draw(rng);
+        let n_cpnts = self.weights.len();
+        self.components = (0..n_cpnts)
+            .map(|k| match self.stats.get(k) {
+                Some(stat) => self
+                    .prior
+                    .posterior(&DataOrSuffStat::SuffStat(stat))
+                    .draw(rng),
+                None => {
+                    let stat = template.empty_suffstat();
+                    self.prior
+                        .posterior(&DataOrSuffStat::SuffStat(&stat))
+                        .draw(rng)
+                }
+            })
+            .collect();
+
+        // Reassign each datum among the components above its slice
+        let mut ixs: Vec<usize> = Vec::with_capacity(n_cpnts);
+        let mut ln_fs: Vec<f64> = Vec::with_capacity(n_cpnts);
+        xs.iter()
+            .zip(slices.iter())
+            .zip(self.assignment.iter_mut())
+            .for_each(|((x, &u), z)| {
+                ixs.clear();
+                ln_fs.clear();
+                self.weights
+                    .iter()
+                    .zip(self.components.iter())
+                    .enumerate()
+                    .filter(|(_, (&w, _))| w > u)
+                    .for_each(|(k, (_, cpnt))| {
+                        ixs.push(k);
+                        ln_fs.push(cpnt.ln_f(x));
+                    });
+                *z = ixs[ln_pflip(&ln_fs, 1, false, rng)[0]];
+            });
+
+        // Drop the trailing components that no datum uses
+        let n_used = self.assignment.iter().max().map_or(0, |z| z + 1);
+        self.weights.truncate(n_used);
+        self.components.truncate(n_used);
+        self.rebuild_stats(xs, n_used, rng);
+
+        if let Some(alpha_prior) = self.alpha_prior.as_ref() {
+            let k = self.n_occupied();
+            self.alpha =
+                draw_alpha_posterior(self.alpha, xs.len(), k, alpha_prior, rng);
+        }
+        self.n_iters += 1;
+    }
+}
+
+impl<X, Fx, Pr> Rv<X> for DpMixtureSlice<X, Fx, Pr>
+where
+    Fx: Rv<X> + HasSuffStat<X>,
+    Pr: ConjugatePrior<X, Fx>,
+{
+    /// The log posterior predictive density of `x` given the current
+    /// weights and the data assigned to each component, with the remaining
+    /// mass going to a new component
+    fn ln_f(&self, x: &X) -> f64 {
+        let rest = 1.0 - self.weights.iter().sum::<f64>();
+        let mut terms: Vec<f64> = self
+            .weights
+            .iter()
+            .zip(self.stats.iter())
+            .map(|(w, stat)| {
+                w.ln() + self.prior.ln_pp(x, &DataOrSuffStat::SuffStat(stat))
+            })
+            .collect();
+        terms.push(rest.ln() + self.prior.ln_pp(x, &DataOrSuffStat::None));
+        logsumexp(&terms)
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> X {
+        let u: f64 = rng.gen();
+        let mut cum = 0.0;
+        let ix = self.weights.iter().position(|w| {
+            cum += w;
+            cum > u
+        });
+        let fx: Fx = match ix {
+            Some(k) => self
+                .prior
+                .posterior(&DataOrSuffStat::SuffStat(&self.stats[k]))
+                .draw(rng),
+            None => self.prior.draw(rng),
+        };
+        fx.draw(rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Bernoulli, Gamma, Poisson};
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    #[test]
+    fn new_validates_alpha() {
+        let pr = Gamma::new(1.0, 1.0).unwrap();
+        let new = |alpha| {
+            DpMixtureSlice::<u32, Poisson, Gamma>::new(alpha, pr.clone())
+        };
+        assert!(new(0.5).is_ok());
+        assert_eq!(new(0.0), Err(DpMixtureError::AlphaTooLow { alpha: 0.0 }));
+        assert!(new(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn state_is_consistent_after_sweeps() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(11);
+        let xs: Vec<bool> = (0..60).map(|i| i % 3 == 0).collect();
+        let mut dpm: DpMixtureSlice<bool, Bernoulli, crate::dist::Beta> =
+            DpMixtureSlice::new(2.0, crate::dist::Beta::jeffreys()).unwrap();
+        dpm.run(&xs, 20, &mut rng);
+
+        assert_eq!(dpm.n_iters(), 20);
+        assert_eq!(dpm.assignment().len(), xs.len());
+        assert_eq!(dpm.weights().len(), dpm.stats().len());
+        assert_eq!(dpm.components().len(), dpm.stats().len());
+        let n: usize = dpm.stats().iter().map(|stat| stat.n()).sum();
+        assert_eq!(n, xs.len());
+        assert!(dpm.weights().iter().sum::<f64>() < 1.0);
+        assert::close(dpm.f(&true) + dpm.f(&false), 1.0, 1E-10);
+    }
+
+    #[test]
+    fn alpha_prior_moves_alpha() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(3);
+        let xs: Vec<u32> = vec![0, 1, 0, 9, 10, 11, 30, 31, 29, 0];
+        let mut dpm: DpMixtureSlice<u32, Poisson, Gamma> =
+            DpMixtureSlice::new(1.0, Gamma::new(1.0, 0.1).unwrap())
+                .unwrap()
+                .with_alpha_prior(Gamma::new(1.0, 1.0).unwrap());
+        let mut alphas = Vec::new();
+        for _ in 0..20 {
+            dpm.run(&xs, 1, &mut rng);
+            alphas.push(dpm.alpha());
+        }
+        assert!(alphas.iter().all(|&a| a > 0.0 && a.is_finite()));
+        assert!(alphas.windows(2).any(|w| w[0] != w[1]));
+    }
+
+    #[test]
+    fn separates_poisson_clusters() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(5);
+        let p1 = Poisson::new(1.0).unwrap();
+        let p2 = Poisson::new(30.0).unwrap();
+        let mut xs: Vec<u32> = p1.sample(150, &mut rng);
+        let xs2: Vec<u32> = p2.sample(50, &mut rng);
+        xs.extend(xs2);
+
+        let mut dpm: DpMixtureSlice<u32, Poisson, Gamma> =
+            DpMixtureSlice::new(1.0, Gamma::new(1.0, 0.1).unwrap()).unwrap();
+        dpm.run(&xs, 200, &mut rng);
+
+        let asgn = dpm.assignment();
+        assert!(asgn[..150].iter().all(|z| !asgn[150..].contains(z)));
+        assert!(dpm.n_occupied() >= 2);
+
+        let f: f64 = (0..200_u32).map(|x| dpm.f(&x)).sum();
+        assert::close(f, 1.0, 1E-6);
+    }
+}
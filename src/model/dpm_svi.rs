@@ -0,0 +1,675 @@
+//! Stochastic variational inference for Dirichlet process mixtures
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::consts::HALF_LN_2PI;
+use crate::data::DataOrSuffStat;
+use crate::dist::{Bernoulli, Beta, Gamma, Gaussian, NormalGamma, Poisson};
+use crate::misc::{ln_pflip, logsumexp};
+use crate::traits::*;
+use rand::Rng;
+use special::Gamma as _;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A conjugate prior whose likelihood is in the exponential family, so that
+/// it can serve as the variational distribution over a mixture component's
+/// parameters.
+///
+/// The natural parameters of the prior, η, are updated by adding the
+/// sufficient statistics, t(x), of each observation, so the posterior under
+/// `xs` has natural parameters `η + Σ t(x)`.
+pub trait SviComponent<X, Fx>: ConjugatePrior<X, Fx> + Clone
+where
+    Fx: Rv<X> + HasSuffStat<X>,
+{
+    /// The natural parameters, η
+    fn natural_params(&self) -> Vec<f64>;
+
+    /// Build the distribution from natural parameters
+    fn from_natural_params(eta: &[f64]) -> Self;
+
+    /// The contribution of a single datum to the natural parameters, t(x)
+    fn natural_suffstat(x: &X) -> Vec<f64>;
+
+    /// The expectation of `ln f(x|θ)` with θ distributed according to `self`
+    fn expected_ln_f(&self, x: &X) -> f64;
+}
+
+impl SviComponent<f64, Gaussian> for NormalGamma {
+    fn natural_params(&self) -> Vec<f64> {
+        let rm = self.r() * self.m();
+        vec![self.r(), rm, rm.mul_add(self.m(), self.s()), self.v()]
+    }
+
+    fn from_natural_params(eta: &[f64]) -> Self {
+        let m = eta[1] / eta[0];
+        NormalGamma::new_unchecked(
+            m,
+            eta[0],
+            (-eta[1]).mul_add(m, eta[2]),
+            eta[3],
+        )
+    }
+
+    fn natural_suffstat(x: &f64) -> Vec<f64> {
+        vec![1.0, *x, x * x, 1.0]
+    }
+
+    fn expected_ln_f(&self, x: &f64) -> f64 {
+        let half_v = 0.5 * self.v();
+        let e_ln_rho = half_v.digamma() - (0.5 * self.s()).ln();
+        let dx = x - self.m();
+        let e_quad = (self.v() / self.s()).mul_add(dx * dx, self.r().recip());
+        0.5_f64.mul_add(e_ln_rho - e_quad, -HALF_LN_2PI)
+    }
+}
+
+macro_rules! impl_poisson_component {
+    ($kind: ty) => {
+        impl SviComponent<$kind, Poisson> for Gamma {
+            fn natural_params(&self) -> Vec<f64> {
+                vec![self.shape(), self.rate()]
+            }
+
+            fn from_natural_params(eta: &[f64]) -> Self {
+                Gamma::new_unchecked(eta[0], eta[1])
+            }
+
+            fn natural_suffstat(x: &$kind) -> Vec<f64> {
+                vec![f64::from(*x), 1.0]
+            }
+
+            fn expected_ln_f(&self, x: &$kind) -> f64 {
+                let xf = f64::from(*x);
+                let e_ln_rate = self.shape().digamma() - self.rate().ln();
+                xf.mul_add(e_ln_rate, -self.shape() / self.rate())
+                    - (xf + 1.0).ln_gamma().0
+            }
+        }
+    };
+}
+
+impl_poisson_component!(u8);
+impl_poisson_component!(u16);
+impl_poisson_component!(u32);
+
+impl SviComponent<bool, Bernoulli> for Beta {
+    fn natural_params(&self) -> Vec<f64> {
+        vec![self.alpha(), self.beta()]
+    }
+
+    fn from_natural_params(eta: &[f64]) -> Self {
+        Beta::new_unchecked(eta[0], eta[1])
+    }
+
+    fn natural_suffstat(x: &bool) -> Vec<f64> {
+        if *x {
+            vec![1.0, 0.0]
+        } else {
+            vec![0.0, 1.0]
+        }
+    }
+
+    fn expected_ln_f(&self, x: &bool) -> f64 {
+        let digamma_ab = (self.alpha() + self.beta()).digamma();
+        if *x {
+            self.alpha().digamma() - digamma_ab
+        } else {
+            self.beta().digamma() - digamma_ab
+        }
+    }
+}
+
+/// A truncated stick-breaking Dirichlet process mixture fit by stochastic
+/// variational inference
+///
+/// Each step draws a minibatch, computes the component responsibilities of
+/// its data under the current variational distribution, and takes a natural
+/// gradient step of size ρₜ = (t + τ)^(-κ) on the stick and component
+/// parameters. The cost of a step depends only on the minibatch size and the
+/// truncation level, so datasets far too large for Gibbs sampling can be
+/// fit.
+///
+/// # Example
+///
+/// Recover two well separated clusters
+///
+/// ```
+/// use rv::dist::{Gaussian, NormalGamma};
+/// use rv::model::DpMixtureSvi;
+/// use rv::traits::*;
+///
+/// let mut rng = rand::thread_rng();
+///
+/// let g1 = Gaussian::new(-5.0, 1.0).unwrap();
+/// let g2 = Gaussian::new(5.0, 1.0).unwrap();
+/// let mut xs: Vec<f64> = g1.sample(1000, &mut rng);
+/// let xs2: Vec<f64> = g2.sample(1000, &mut rng);
+/// xs.extend(xs2);
+///
+/// let prior = NormalGamma::new_unchecked(0.0, 0.1, 1.0, 1.0);
+/// let mut dpm: DpMixtureSvi<f64, Gaussian, NormalGamma> =
+///     DpMixtureSvi::new(1.0, 10, prior).unwrap();
+/// dpm.fit(&xs, 100, 200, &mut rng);
+///
+/// let weights = dpm.weights();
+/// assert::close(weights.iter().sum::<f64>(), 1.0, 1E-10);
+///
+/// // Points from different clusters belong to different components
+/// let argmax = |x: f64| {
+///     let r = dpm.responsibilities(&x);
+///     (0..r.len()).max_by(|&a, &b| r[a].total_cmp(&r[b])).unwrap()
+/// };
+/// assert_ne!(argmax(-5.0), argmax(5.0));
+///
+/// // and the density is low between the clusters
+/// assert!(dpm.ln_f(&0.0) < dpm.ln_f(&-5.0) - 5.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct DpMixtureSvi<X, Fx, Pr> {
+    /// DP concentration parameter
+    alpha: f64,
+    /// Prior on alpha. If present, alpha is set to its variational mean
+    /// every step.
+    alpha_prior: Option<Gamma>,
+    /// The prior on the component parameters
+    prior: Pr,
+    /// Variational Beta parameters for the first `truncation - 1` sticks
+    sticks: Vec<(f64, f64)>,
+    /// Variational distributions over the component parameters
+    components: Vec<Pr>,
+    /// Number of steps taken
+    n_steps: usize,
+    /// Learning rate delay, τ
+    tau: f64,
+    /// Learning rate forgetting rate, κ
+    kappa: f64,
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    _phantom: PhantomData<(X, Fx)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum DpMixtureSviError {
+    /// The concentration parameter is less than or equal to zero
+    AlphaTooLow { alpha: f64 },
+    /// The concentration parameter is infinite or NaN
+    AlphaNotFinite { alpha: f64 },
+    /// The truncation level is zero
+    TruncationTooLow,
+    /// The learning rate delay is negative or not finite
+    InvalidTau { tau: f64 },
+    /// The forgetting rate is not in (0.5, 1]
+    InvalidKappa { kappa: f64 },
+}
+
+impl<X, Fx, Pr> DpMixtureSvi<X, Fx, Pr>
+where
+    Fx: Rv<X> + HasSuffStat<X>,
+    Pr: SviComponent<X, Fx>,
+{
+    /// Create a new mixture with concentration `alpha` and at most
+    /// `truncation` components, each of which starts at `prior`
+    ///
+    /// The learning rate defaults to τ = 1 and κ = 0.7.
+    pub fn new(
+        alpha: f64,
+        truncation: usize,
+        prior: Pr,
+    ) -> Result<Self, DpMixtureSviError> {
+        if alpha <= 0.0 {
+            Err(DpMixtureSviError::AlphaTooLow { alpha })
+        } else if !alpha.is_finite() {
+            Err(DpMixtureSviError::AlphaNotFinite { alpha })
+        } else if truncation == 0 {
+            Err(DpMixtureSviError::TruncationTooLow)
+        } else {
+            Ok(DpMixtureSvi {
+                alpha,
+                alpha_prior: None,
+                sticks: vec![(1.0, alpha); truncation - 1],
+                components: vec![prior.clone(); truncation],
+                prior,
+                n_steps: 0,
+                tau: 1.0,
+                kappa: 0.7,
+                _phantom: PhantomData,
+            })
+        }
+    }
+
+    /// Set the learning rate schedule, ρₜ = (t + τ)^(-κ)
+    ///
+    /// Convergence requires κ in (0.5, 1].
+    pub fn with_learning_rate(
+        mut self,
+        tau: f64,
+        kappa: f64,
+    ) -> Result<Self, DpMixtureSviError> {
+        if !(tau.is_finite() && tau >= 0.0) {
+            Err(DpMixtureSviError::InvalidTau { tau })
+        } else if !(kappa > 0.5 && kappa <= 1.0) {
+            Err(DpMixtureSviError::InvalidKappa { kappa })
+        } else {
+            self.tau = tau;
+            self.kappa = kappa;
+            Ok(self)
+        }
+    }
+
+    /// Infer alpha under a Gamma prior instead of holding it fixed
+    ///
+    /// The variational distribution over alpha is Gamma with shape
+    /// `a + T - 1` and rate `b - Σ E[ln(1 - vₖ)]`, where `T` is the
+    /// truncation level and `vₖ` are the sticks.
+    pub fn with_alpha_prior(mut self, alpha_prior: Gamma) -> Self {
+        self.alpha_prior = Some(alpha_prior);
+        self
+    }
+
+    /// The DP concentration parameter. With a prior on alpha, this is its
+    /// variational mean.
+    #[inline]
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// The prior on alpha, if alpha is inferred
+    #[inline]
+    pub fn alpha_prior(&self) -> Option<&Gamma> {
+        self.alpha_prior.as_ref()
+    }
+
+    /// The maximum number of components
+    #[inline]
+    pub fn truncation(&self) -> usize {
+        self.components.len()
+    }
+
+    /// The number of steps taken
+    #[inline]
+    pub fn n_steps(&self) -> usize {
+        self.n_steps
+    }
+
+    /// The variational distributions over the component parameters
+    #[inline]
+    pub fn components(&self) -> &[Pr] {
+        &self.components
+    }
+
+    /// The step size of the next step
+    pub fn learning_rate(&self) -> f64 {
+        (self.n_steps as f64 + self.tau).powf(-self.kappa)
+    }
+
+    /// The expected mixture weights under the variational distribution
+    pub fn weights(&self) -> Vec<f64> {
+        let mut rest = 1.0;
+        let mut weights: Vec<f64> = self
+            .sticks
+            .iter()
+            .map(|&(a, b)| {
+                let w = rest * a / (a + b);
+                rest *= b / (a + b);
+                w
+            })
+            .collect();
+        weights.push(rest);
+        weights
+    }
+
+    /// E[ln πₖ] for each component under the variational distribution
+    fn expected_ln_weights(&self) -> Vec<f64> {
+        let mut ln_rest = 0.0;
+        let mut ln_weights: Vec<f64> = self
+            .sticks
+            .iter()
+            .map(|&(a, b)| {
+                let digamma_ab = (a + b).digamma();
+                let ln_w = ln_rest + a.digamma() - digamma_ab;
+                ln_rest += b.digamma() - digamma_ab;
+                ln_w
+            })
+            .collect();
+        ln_weights.push(ln_rest);
+        ln_weights
+    }
+
+    fn ln_responsibilities(&self, e_ln_weights: &[f64], x: &X) -> Vec<f64> {
+        let mut ln_phi: Vec<f64> = e_ln_weights
+            .iter()
+            .zip(self.components.iter())
+            .map(|(ln_w, cpnt)| ln_w + cpnt.expected_ln_f(x))
+            .collect();
+        let z = logsumexp(&ln_phi);
+        ln_phi.iter_mut().for_each(|p| *p -= z);
+        ln_phi
+    }
+
+    /// The probability that `x` belongs to each component under the
+    /// variational distribution
+    pub fn responsibilities(&self, x: &X) -> Vec<f64> {
+        let e_ln_weights = self.expected_ln_weights();
+        self.ln_responsibilities(&e_ln_weights, x)
+            .iter()
+            .map(|p| p.exp())
+            .collect()
+    }
+
+    /// Take one step on the minibatch `batch` drawn from `n_data` data
+    ///
+    /// Empty minibatches are ignored.
+    pub fn step(&mut self, batch: &[X], n_data: usize) {
+        if batch.is_empty() {
+            return;
+        }
+
+        let rho = self.learning_rate();
+        let scale = n_data as f64 / batch.len() as f64;
+        let e_ln_weights = self.expected_ln_weights();
+        let eta0 = self.prior.natural_params();
+
+        let k = self.truncation();
+        let mut counts = vec![0.0; k];
+        let mut stats = vec![vec![0.0; eta0.len()]; k];
+        batch.iter().for_each(|x| {
+            let ln_phi = self.ln_responsibilities(&e_ln_weights, x);
+            let tx = Pr::natural_suffstat(x);
+            ln_phi.iter().enumerate().for_each(|(ix, ln_p)| {
+                let phi = ln_p.exp();
+                counts[ix] += phi;
+                stats[ix]
+                    .iter_mut()
+                    .zip(tx.iter())
+                    .for_each(|(s, t)| *s = phi.mul_add(*t, *s));
+            });
+        });
+
+        self.components.iter_mut().zip(stats.iter()).for_each(
+            |(cpnt, stat)| {
+                let eta: Vec<f64> = cpnt
+                    .natural_params()
+                    .iter()
+                    .zip(eta0.iter().zip(stat.iter()))
+                    .map(|(e, (e0, s))| {
+                        let e_hat = scale.mul_add(*s, *e0);
+                        rho.mul_add(e_hat - e, *e)
+                    })
+                    .collect();
+                *cpnt = Pr::from_natural_params(&eta);
+            },
+        );
+
+        let alpha = self.alpha;
+        let mut tail: f64 = counts.iter().sum();
+        self.sticks.iter_mut().zip(counts.iter()).for_each(
+            |((a, b), count)| {
+                tail -= count;
+                let a_hat = scale.mul_add(*count, 1.0);
+                let b_hat = scale.mul_add(tail.max(0.0), alpha);
+                *a = rho.mul_add(a_hat - *a, *a);
+                *b = rho.mul_add(b_hat - *b, *b);
+            },
+        );
+
+        if let Some(alpha_prior) = self.alpha_prior.as_ref() {
+            if !self.sticks.is_empty() {
+                let shape = alpha_prior.shape() + self.sticks.len() as f64;
+                let rate = self
+                    .sticks
+                    .iter()
+                    .fold(alpha_prior.rate(), |acc, &(a, b)| {
+                        acc - (b.digamma() - (a + b).digamma())
+                    });
+                self.alpha = shape / rate;
+            }
+        }
+
+        self.n_steps += 1;
+    }
+
+    /// Take `n_steps` steps on minibatches of `batch_size` data drawn with
+    /// replacement from `xs`
+    ///
+    /// If no steps have been taken yet, the components are first centered
+    /// on distinct randomly chosen data to break their symmetry.
+    pub fn fit<R: Rng>(
+        &mut self,
+        xs: &[X],
+        batch_size: usize,
+        n_steps: usize,
+        rng: &mut R,
+    ) where
+        X: Clone,
+    {
+        if xs.is_empty() || batch_size == 0 {
+            return;
+        }
+
+        if self.n_steps == 0 {
+            self.init_components(xs, rng);
+        }
+
+        let n = xs.len();
+        let mut batch: Vec<X> = Vec::with_capacity(batch_size);
+        (0..n_steps).for_each(|_| {
+            batch.clear();
+            batch.extend(
+                (0..batch_size).map(|_| xs[rng.gen_range(0..n)].clone()),
+            );
+            self.step(&batch, n);
+        });
+    }
+
+    fn init_components<R: Rng>(&mut self, xs: &[X], rng: &mut R) {
+        let eta0 = self.prior.natural_params();
+        let ixs = rand::seq::index::sample(
+            rng,
+            xs.len(),
+            self.truncation().min(xs.len()),
+        );
+        self.components
+            .iter_mut()
+            .zip(ixs.iter())
+            .for_each(|(cpnt, ix)| {
+                let eta: Vec<f64> = eta0
+                    .iter()
+                    .zip(Pr::natural_suffstat(&xs[ix]).iter())
+                    .map(|(e0, t)| e0 + t)
+                    .collect();
+                *cpnt = Pr::from_natural_params(&eta);
+            });
+    }
+}
+
+impl<X, Fx, Pr> Rv<X> for DpMixtureSvi<X, Fx, Pr>
+where
+    Fx: Rv<X> + HasSuffStat<X>,
+    Pr: SviComponent<X, Fx>,
+{
+    /// The log density of `x` under the mixture of component posterior
+    /// predictives with the expected weights
+    fn ln_f(&self, x: &X) -> f64 {
+        let terms: Vec<f64> = self
+            .weights()
+            .iter()
+            .zip(self.components.iter())
+            .map(|(w, cpnt)| w.ln() + cpnt.ln_pp(x, &DataOrSuffStat::None))
+            .collect();
+        logsumexp(&terms)
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> X {
+        let ln_weights: Vec<f64> =
+            self.weights().iter().map(|w| w.ln()).collect();
+        let ix = ln_pflip(&ln_weights, 1, true, rng)[0];
+        let fx: Fx = self.components[ix].draw(rng);
+        fx.draw(rng)
+    }
+}
+
+impl std::error::Error for DpMixtureSviError {}
+
+impl fmt::Display for DpMixtureSviError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlphaTooLow { alpha } => {
+                write!(f, "alpha ({}) must be greater than zero", alpha)
+            }
+            Self::AlphaNotFinite { alpha } => {
+                write!(f, "non-finite alpha: {}", alpha)
+            }
+            Self::TruncationTooLow => {
+                write!(f, "truncation must be at least one")
+            }
+            Self::InvalidTau { tau } => {
+                write!(f, "tau ({}) must be finite and non-negative", tau)
+            }
+            Self::InvalidKappa { kappa } => {
+                write!(f, "kappa ({}) must be in (0.5, 1]", kappa)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-10;
+
+    #[test]
+    fn new_validates_params() {
+        let ng = NormalGamma::new_unchecked(0.0, 1.0, 1.0, 1.0);
+        let new = |alpha, k| {
+            DpMixtureSvi::<f64, Gaussian, NormalGamma>::new(
+                alpha,
+                k,
+                ng.clone(),
+            )
+        };
+        assert!(new(1.0, 5).is_ok());
+        assert!(new(0.0, 5).is_err());
+        assert!(new(f64::INFINITY, 5).is_err());
+        assert_eq!(new(1.0, 0), Err(DpMixtureSviError::TruncationTooLow));
+        assert!(new(1.0, 5).unwrap().with_learning_rate(1.0, 0.5).is_err());
+        assert!(new(1.0, 5).unwrap().with_learning_rate(-1.0, 0.7).is_err());
+    }
+
+    #[test]
+    fn natural_params_round_trip() {
+        let ng = NormalGamma::new(1.2, 2.0, 3.0, 4.0).unwrap();
+        let eta =
+            <NormalGamma as SviComponent<f64, Gaussian>>::natural_params(&ng);
+        let ng2 =
+            <NormalGamma as SviComponent<f64, Gaussian>>::from_natural_params(
+                &eta,
+            );
+        assert::close(ng2.m(), ng.m(), TOL);
+        assert::close(ng2.r(), ng.r(), TOL);
+        assert::close(ng2.s(), ng.s(), TOL);
+        assert::close(ng2.v(), ng.v(), TOL);
+    }
+
+    #[test]
+    fn full_batch_single_component_step_is_posterior() {
+        let xs: Vec<f64> = vec![-1.0, 0.5, 2.0, 3.5];
+        let ng = NormalGamma::new(0.0, 1.0, 2.0, 3.0).unwrap();
+        let mut dpm: DpMixtureSvi<f64, Gaussian, NormalGamma> =
+            DpMixtureSvi::new(1.0, 1, ng.clone()).unwrap();
+        assert_eq!(dpm.learning_rate(), 1.0);
+        dpm.step(&xs, xs.len());
+
+        let post = ng.posterior(&DataOrSuffStat::Data(&xs));
+        let fit = &dpm.components()[0];
+        assert::close(fit.m(), post.m(), TOL);
+        assert::close(fit.r(), post.r(), TOL);
+        assert::close(fit.s(), post.s(), TOL);
+        assert::close(fit.v(), post.v(), TOL);
+        assert_eq!(dpm.weights(), vec![1.0]);
+    }
+
+    #[test]
+    fn expected_ln_f_bounded_by_ln_pp() {
+        // By Jensen's inequality E[ln f] <= ln E[f]
+        let gamma = Gamma::new(3.0, 2.0).unwrap();
+        let beta = Beta::new(2.0, 5.0).unwrap();
+        for x in 0_u32..6 {
+            let e = <Gamma as SviComponent<u32, Poisson>>::expected_ln_f(
+                &gamma, &x,
+            );
+            let ln_pp: f64 = gamma.ln_pp(&x, &DataOrSuffStat::None);
+            assert!(e <= ln_pp);
+        }
+        for x in [true, false] {
+            let e = beta.expected_ln_f(&x);
+            let ln_pp: f64 = beta.ln_pp(&x, &DataOrSuffStat::None);
+            assert!(e <= ln_pp);
+        }
+    }
+
+    #[test]
+    fn alpha_prior_sets_alpha_to_variational_mean() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(2);
+        let xs: Vec<u32> = vec![0, 1, 0, 9, 10, 11, 30, 31, 29, 0];
+        let alpha_prior = Gamma::new(2.0, 1.0).unwrap();
+        let mut dpm: DpMixtureSvi<u32, Poisson, Gamma> =
+            DpMixtureSvi::new(1.0, 5, Gamma::new(1.0, 0.1).unwrap())
+                .unwrap()
+                .with_alpha_prior(alpha_prior.clone());
+        dpm.fit(&xs, 5, 50, &mut rng);
+
+        let rate = dpm
+            .sticks
+            .iter()
+            .fold(1.0, |acc, &(a, b)| acc - (b.digamma() - (a + b).digamma()));
+        assert::close(dpm.alpha(), 6.0 / rate, TOL);
+        assert_eq!(dpm.alpha_prior(), Some(&alpha_prior));
+    }
+
+    #[test]
+    fn separates_poisson_clusters() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(7);
+        let p1 = Poisson::new(1.0).unwrap();
+        let p2 = Poisson::new(30.0).unwrap();
+        let mut xs: Vec<u32> = p1.sample(3000, &mut rng);
+        let xs2: Vec<u32> = p2.sample(1000, &mut rng);
+        xs.extend(xs2);
+
+        let mut dpm: DpMixtureSvi<u32, Poisson, Gamma> =
+            DpMixtureSvi::new(1.0, 8, Gamma::new(1.0, 0.1).unwrap()).unwrap();
+        dpm.fit(&xs, 200, 300, &mut rng);
+
+        let weights = dpm.weights();
+        assert::close(weights.iter().sum::<f64>(), 1.0, TOL);
+
+        // The high-rate cluster gets its own component, though the low-rate
+        // cluster may be split among several
+        let rates: Vec<f64> = dpm
+            .components()
+            .iter()
+            .map(|cpnt| cpnt.shape() / cpnt.rate())
+            .collect();
+        let high: Vec<usize> = (0..rates.len())
+            .filter(|&ix| weights[ix] > 0.01 && rates[ix] > 5.0)
+            .collect();
+        assert_eq!(high.len(), 1);
+        assert!((rates[high[0]] - 30.0).abs() < 1.0);
+        assert!((weights[high[0]] - 0.25).abs() < 0.05);
+        let w_low: f64 = (0..rates.len())
+            .filter(|&ix| rates[ix] < 5.0)
+            .map(|ix| weights[ix])
+            .sum();
+        assert!((w_low - 0.75).abs() < 0.05);
+
+        let f: f64 = (0..200_u32).map(|x| dpm.f(&x)).sum();
+        assert::close(f, 1.0, 1E-6);
+    }
+}
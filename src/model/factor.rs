@@ -0,0 +1,589 @@
+//! Probabilistic PCA and factor analysis
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::consts::LN_2PI;
+use crate::dist::MvGaussian;
+use crate::traits::*;
+use nalgebra::linalg::Cholesky;
+use nalgebra::{DMatrix, DVector, Dyn};
+use rand::Rng;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Maximum number of EM iterations used to fit a factor model
+const EM_MAX_ITER: usize = 1_000;
+/// Stop EM when the log likelihood improves by less than this
+const EM_TOL: f64 = 1E-8;
+/// Lower bound on each noise variance, relative to the sample variance of
+/// its dimension, to keep factor analysis away from degenerate solutions
+const MIN_NOISE_FRAC: f64 = 1E-6;
+
+/// The structure of the noise covariance, Ψ, of a [`FactorModel`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum FactorNoise {
+    /// Ψ = σ²I: probabilistic PCA
+    Isotropic,
+    /// Ψ = diag(ψ): factor analysis
+    Diagonal,
+}
+
+/// A linear Gaussian latent factor model
+///
+/// Each k-dimensional observation is generated from q < k independent
+/// standard normal factors, `z`, as `x = μ + Wz + ε` with ε ~ 𝒩(0, Ψ) and Ψ
+/// diagonal. Marginally, x ~ 𝒩(μ, WWᵀ + Ψ). The loadings, W, are identified
+/// only up to rotation.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::{DMatrix, DVector};
+/// use rv::dist::MvGaussian;
+/// use rv::model::{FactorModel, FactorNoise};
+/// use rv::traits::*;
+///
+/// let mut rng = rand::thread_rng();
+///
+/// // Five measurements driven by one underlying factor
+/// let w = DMatrix::from_column_slice(5, 1, &[1.0, 0.9, 0.8, -0.7, 0.5]);
+/// let truth = FactorModel::new(
+///     DVector::zeros(5),
+///     w,
+///     DVector::from_element(5, 0.1),
+/// )
+/// .unwrap();
+/// let xs: Vec<DVector<f64>> = truth.sample(2000, &mut rng);
+///
+/// let ppca = FactorModel::fit(&xs, 1, FactorNoise::Isotropic).unwrap();
+/// let fa = FactorModel::fit(&xs, 1, FactorNoise::Diagonal).unwrap();
+/// assert!(fa.ln_likelihood(&xs) >= ppca.ln_likelihood(&xs));
+///
+/// // The distribution of the factor behind an observation
+/// let post: MvGaussian = fa.posterior(&xs[0]);
+/// assert_eq!(post.ndims(), 1);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct FactorModel {
+    /// Mean, μ
+    mu: DVector<f64>,
+    /// k-by-q loadings, W
+    loadings: DMatrix<f64>,
+    /// Diagonal of the noise covariance, Ψ
+    noise_var: DVector<f64>,
+    /// The marginal distribution of the observations
+    #[cfg_attr(feature = "serde1", serde(skip))]
+    marginal: OnceLock<MvGaussian>,
+}
+
+impl PartialEq for FactorModel {
+    fn eq(&self, other: &FactorModel) -> bool {
+        self.mu == other.mu
+            && self.loadings == other.loadings
+            && self.noise_var == other.noise_var
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum FactorModelError {
+    /// There are no observations to fit to
+    NoData,
+    /// Zero factors were requested
+    ZeroFactors,
+    /// There must be fewer factors than dimensions
+    TooManyFactors {
+        /// The number of factors
+        n_factors: usize,
+        /// The number of dimensions of the observations
+        dims: usize,
+    },
+    /// An observation, the loadings, or the noise variances have the wrong
+    /// number of dimensions
+    DimensionMismatch {
+        /// The number of dimensions found
+        dims: usize,
+        /// The number of dimensions expected
+        expected: usize,
+    },
+    /// A noise variance is less than or equal to zero
+    NoiseVarTooLow { ix: usize, noise_var: f64 },
+    /// A noise variance, loading, or mean is infinite or NaN
+    NotFinite,
+}
+
+impl FactorModel {
+    /// Create a factor model
+    ///
+    /// # Arguments
+    /// - mu: k-length mean
+    /// - loadings: k-by-q matrix of factor loadings, with q < k
+    /// - noise_var: k-length vector of noise variances, the diagonal of Ψ
+    pub fn new(
+        mu: DVector<f64>,
+        loadings: DMatrix<f64>,
+        noise_var: DVector<f64>,
+    ) -> Result<Self, FactorModelError> {
+        let dims = mu.len();
+        validate_n_factors(loadings.ncols(), dims)?;
+        for n in [loadings.nrows(), noise_var.len()] {
+            if n != dims {
+                return Err(FactorModelError::DimensionMismatch {
+                    dims: n,
+                    expected: dims,
+                });
+            }
+        }
+        if let Some((ix, &noise_var)) =
+            noise_var.iter().enumerate().find(|(_, &v)| v <= 0.0)
+        {
+            return Err(FactorModelError::NoiseVarTooLow { ix, noise_var });
+        }
+        if mu
+            .iter()
+            .chain(loadings.iter())
+            .chain(noise_var.iter())
+            .any(|x| !x.is_finite())
+        {
+            return Err(FactorModelError::NotFinite);
+        }
+        Ok(FactorModel::new_unchecked(mu, loadings, noise_var))
+    }
+
+    /// Creates a new FactorModel without checking whether the parameters
+    /// are valid.
+    #[inline]
+    pub fn new_unchecked(
+        mu: DVector<f64>,
+        loadings: DMatrix<f64>,
+        noise_var: DVector<f64>,
+    ) -> Self {
+        FactorModel {
+            mu,
+            loadings,
+            noise_var,
+            marginal: OnceLock::new(),
+        }
+    }
+
+    /// Fit a model with `n_factors` factors to the observations, `xs`, by
+    /// maximum likelihood
+    ///
+    /// The mean is the sample mean. The loadings and noise variances are
+    /// found by EM (Rubin & Thayer, 1982; Tipping & Bishop, 1999), which
+    /// touches the data only through the sample covariance, starting from
+    /// the leading principal components.
+    pub fn fit(
+        xs: &[DVector<f64>],
+        n_factors: usize,
+        noise: FactorNoise,
+    ) -> Result<Self, FactorModelError> {
+        if xs.is_empty() {
+            return Err(FactorModelError::NoData);
+        }
+        let dims = xs[0].len();
+        validate_n_factors(n_factors, dims)?;
+        if let Some(x) = xs.iter().find(|x| x.len() != dims) {
+            return Err(FactorModelError::DimensionMismatch {
+                dims: x.len(),
+                expected: dims,
+            });
+        }
+        if xs.iter().any(|x| x.iter().any(|xi| !xi.is_finite())) {
+            return Err(FactorModelError::NotFinite);
+        }
+
+        let n = xs.len() as f64;
+        let mu = xs.iter().fold(DVector::zeros(dims), |acc, x| acc + x) / n;
+        let s = xs.iter().fold(DMatrix::zeros(dims, dims), |acc, x| {
+            let d = x - &mu;
+            acc + &d * d.transpose()
+        }) / n;
+        let min_noise = s.diagonal().map(|v| (MIN_NOISE_FRAC * v).max(1E-12));
+
+        // Start from the leading principal components, with half of each
+        // component's variance attributed to the factors
+        let eig = s.clone().symmetric_eigen();
+        let mut order: Vec<usize> = (0..dims).collect();
+        order.sort_by(|&a, &b| {
+            eig.eigenvalues[b].total_cmp(&eig.eigenvalues[a])
+        });
+        let mut w = DMatrix::from_fn(dims, n_factors, |i, j| {
+            let ix = order[j];
+            eig.eigenvectors[(i, ix)]
+                * (0.5 * eig.eigenvalues[ix].max(0.0)).sqrt()
+        });
+        let mut psi = constrain_noise(
+            s.diagonal() - w.map(|x| x * x).column_sum(),
+            &min_noise,
+            noise,
+        );
+
+        let mut ln_lik = f64::NEG_INFINITY;
+        for _ in 0..EM_MAX_ITER {
+            let (w_new, resid) = em_step(&s, &w, &psi);
+            w = w_new;
+            psi = constrain_noise(resid, &min_noise, noise);
+
+            let ln_lik_new = ln_lik_from_cov(&s, &w, &psi, n);
+            let converged = ln_lik_new - ln_lik < EM_TOL;
+            ln_lik = ln_lik_new;
+            if converged {
+                break;
+            }
+        }
+
+        Ok(FactorModel::new_unchecked(mu, w, psi))
+    }
+
+    /// The number of dimensions of an observation
+    #[inline]
+    pub fn ndims(&self) -> usize {
+        self.mu.len()
+    }
+
+    /// The number of factors
+    #[inline]
+    pub fn n_factors(&self) -> usize {
+        self.loadings.ncols()
+    }
+
+    /// The mean, μ
+    #[inline]
+    pub fn mu(&self) -> &DVector<f64> {
+        &self.mu
+    }
+
+    /// The loadings, W
+    #[inline]
+    pub fn loadings(&self) -> &DMatrix<f64> {
+        &self.loadings
+    }
+
+    /// The noise variances, the diagonal of Ψ
+    #[inline]
+    pub fn noise_var(&self) -> &DVector<f64> {
+        &self.noise_var
+    }
+
+    /// The marginal distribution of the observations, 𝒩(μ, WWᵀ + Ψ)
+    pub fn marginal(&self) -> &MvGaussian {
+        self.marginal.get_or_init(|| {
+            let cov = marginal_cov(&self.loadings, &self.noise_var);
+            MvGaussian::new_unchecked(self.mu.clone(), cov)
+        })
+    }
+
+    /// The posterior distribution of the factors behind the observation `x`
+    ///
+    /// With M = I + WᵀΨ⁻¹W, the posterior is 𝒩(M⁻¹WᵀΨ⁻¹(x - μ), M⁻¹). Its
+    /// mean is the usual factor score.
+    pub fn posterior(&self, x: &DVector<f64>) -> MvGaussian {
+        let (m_chol, b) = posterior_parts(&self.loadings, &self.noise_var);
+        let mean = b * (x - &self.mu);
+        MvGaussian::new_unchecked(mean, m_chol.inverse())
+    }
+
+    /// The log likelihood of the observations, `xs`
+    pub fn ln_likelihood(&self, xs: &[DVector<f64>]) -> f64 {
+        let marginal = self.marginal();
+        xs.iter().map(|x| marginal.ln_f(x)).sum()
+    }
+}
+
+fn validate_n_factors(
+    n_factors: usize,
+    dims: usize,
+) -> Result<(), FactorModelError> {
+    if n_factors == 0 {
+        Err(FactorModelError::ZeroFactors)
+    } else if n_factors >= dims {
+        Err(FactorModelError::TooManyFactors { n_factors, dims })
+    } else {
+        Ok(())
+    }
+}
+
+/// WWᵀ + Ψ
+fn marginal_cov(w: &DMatrix<f64>, psi: &DVector<f64>) -> DMatrix<f64> {
+    w * w.transpose() + DMatrix::from_diagonal(psi)
+}
+
+/// The Cholesky factor of M = I + WᵀΨ⁻¹W, and M⁻¹WᵀΨ⁻¹, which maps a
+/// centered observation to the posterior mean of its factors
+fn posterior_parts(
+    w: &DMatrix<f64>,
+    psi: &DVector<f64>,
+) -> (Cholesky<f64, Dyn>, DMatrix<f64>) {
+    let q = w.ncols();
+    // Ψ⁻¹W
+    let mut w_scaled = w.clone();
+    w_scaled
+        .row_iter_mut()
+        .zip(psi.iter())
+        .for_each(|(mut row, p)| row /= *p);
+    let m = DMatrix::identity(q, q) + w.transpose() * &w_scaled;
+    // M is positive definite since WᵀΨ⁻¹W is positive semi-definite
+    let m_chol = m.cholesky().unwrap();
+    let b = m_chol.solve(&w_scaled.transpose());
+    (m_chol, b)
+}
+
+/// One EM update of the loadings from the sample covariance, `s`. Returns
+/// the new loadings and the unconstrained update of the noise variances.
+fn em_step(
+    s: &DMatrix<f64>,
+    w: &DMatrix<f64>,
+    psi: &DVector<f64>,
+) -> (DMatrix<f64>, DVector<f64>) {
+    let (m_chol, b) = posterior_parts(w, psi);
+    // (1/n) Σ E[z] (x - μ)ᵀ
+    let bs = &b * s;
+    // (1/n) Σ E[zzᵀ]
+    let ezz = m_chol.inverse() + &bs * b.transpose();
+    let w_new = ezz.cholesky().unwrap().solve(&bs).transpose();
+    let resid = DVector::from_fn(s.nrows(), |i, _| {
+        s[(i, i)] - w_new.row(i).transpose().dot(&bs.column(i))
+    });
+    (w_new, resid)
+}
+
+/// Apply the noise structure and lower bound to updated noise variances
+fn constrain_noise(
+    resid: DVector<f64>,
+    min_noise: &DVector<f64>,
+    noise: FactorNoise,
+) -> DVector<f64> {
+    let psi = match noise {
+        FactorNoise::Diagonal => resid,
+        FactorNoise::Isotropic => {
+            DVector::from_element(resid.len(), resid.mean())
+        }
+    };
+    psi.zip_map(min_noise, f64::max)
+}
+
+/// Log likelihood of `n` observations with sample covariance `s` about the
+/// mean
+fn ln_lik_from_cov(
+    s: &DMatrix<f64>,
+    w: &DMatrix<f64>,
+    psi: &DVector<f64>,
+    n: f64,
+) -> f64 {
+    let chol = marginal_cov(w, psi).cholesky().unwrap();
+    let k = s.nrows() as f64;
+    let trace = chol.solve(s).trace();
+    -0.5 * n * (k.mul_add(LN_2PI, chol.ln_determinant()) + trace)
+}
+
+impl Rv<DVector<f64>> for FactorModel {
+    fn ln_f(&self, x: &DVector<f64>) -> f64 {
+        self.marginal().ln_f(x)
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> DVector<f64> {
+        let norm = rand_distr::StandardNormal;
+        let z = DVector::from_fn(self.n_factors(), |_, _| rng.sample(norm));
+        let eps = self
+            .noise_var
+            .map(|v| v.sqrt() * rng.sample::<f64, _>(norm));
+        &self.mu + &self.loadings * z + eps
+    }
+}
+
+impl std::error::Error for FactorModelError {}
+
+impl fmt::Display for FactorModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoData => write!(f, "no observations to fit to"),
+            Self::ZeroFactors => write!(f, "at least one factor is required"),
+            Self::TooManyFactors { n_factors, dims } => write!(
+                f,
+                "the number of factors ({}) must be less than the number of \
+                    dimensions ({})",
+                n_factors, dims
+            ),
+            Self::DimensionMismatch { dims, expected } => {
+                write!(f, "expected {} dimensions but found {}", expected, dims)
+            }
+            Self::NoiseVarTooLow { ix, noise_var } => write!(
+                f,
+                "noise variance at index {} ({}) must be greater than zero",
+                ix, noise_var
+            ),
+            Self::NotFinite => write!(f, "parameters must be finite"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::{dmatrix, dvector};
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    fn truth() -> FactorModel {
+        FactorModel::new(
+            dvector![1.0, -1.0, 0.0, 2.0, 0.5],
+            dmatrix![
+                1.0, 0.0;
+                0.8, 0.3;
+                0.0, 1.2;
+                -0.5, 0.7;
+                0.6, -0.4;
+            ],
+            dvector![0.1, 0.3, 0.2, 0.05, 0.4],
+        )
+        .unwrap()
+    }
+
+    fn sample_cov(xs: &[DVector<f64>]) -> DMatrix<f64> {
+        let n = xs.len() as f64;
+        let mean = xs.iter().fold(DVector::zeros(5), |acc, x| acc + x) / n;
+        xs.iter().fold(DMatrix::zeros(5, 5), |acc, x| {
+            let d = x - &mean;
+            acc + &d * d.transpose()
+        }) / n
+    }
+
+    #[test]
+    fn new_validates_parameters() {
+        let w = DMatrix::zeros(3, 1);
+        assert_eq!(
+            FactorModel::new(
+                DVector::zeros(3),
+                w.clone(),
+                dvector![1.0, 0.0, 1.0]
+            ),
+            Err(FactorModelError::NoiseVarTooLow {
+                ix: 1,
+                noise_var: 0.0
+            })
+        );
+        assert_eq!(
+            FactorModel::new(DVector::zeros(3), w, dvector![1.0, 1.0]),
+            Err(FactorModelError::DimensionMismatch {
+                dims: 2,
+                expected: 3
+            })
+        );
+        assert_eq!(
+            FactorModel::new(
+                DVector::zeros(3),
+                DMatrix::zeros(3, 3),
+                DVector::from_element(3, 1.0)
+            ),
+            Err(FactorModelError::TooManyFactors {
+                n_factors: 3,
+                dims: 3
+            })
+        );
+    }
+
+    #[test]
+    fn ppca_matches_closed_form() {
+        // Tipping & Bishop (1999): σ² is the mean of the discarded
+        // eigenvalues of S and WWᵀ = U (Λ - σ²I) Uᵀ over the leading ones
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x31);
+        let xs: Vec<DVector<f64>> = truth().sample(500, &mut rng);
+        let ppca = FactorModel::fit(&xs, 2, FactorNoise::Isotropic).unwrap();
+
+        let s = sample_cov(&xs);
+        let eig = s.clone().symmetric_eigen();
+        let mut order: Vec<usize> = (0..5).collect();
+        order.sort_by(|&a, &b| {
+            eig.eigenvalues[b].total_cmp(&eig.eigenvalues[a])
+        });
+        let sigma2 =
+            order[2..].iter().map(|&i| eig.eigenvalues[i]).sum::<f64>() / 3.0;
+        let wwt = order[..2].iter().fold(DMatrix::zeros(5, 5), |acc, &i| {
+            let u = eig.eigenvectors.column(i);
+            acc + u * u.transpose() * (eig.eigenvalues[i] - sigma2)
+        });
+
+        assert::close(ppca.noise_var()[0], sigma2, 1E-5);
+        let w = ppca.loadings();
+        assert!((w * w.transpose()).relative_eq(&wwt, 1E-4, 1E-4));
+    }
+
+    #[test]
+    fn fa_recovers_covariance() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x32);
+        let truth = truth();
+        let xs: Vec<DVector<f64>> = truth.sample(20_000, &mut rng);
+        let fa = FactorModel::fit(&xs, 2, FactorNoise::Diagonal).unwrap();
+
+        assert!(fa.mu().relative_eq(truth.mu(), 0.05, 0.05));
+        assert!(fa.marginal().cov().relative_eq(
+            truth.marginal().cov(),
+            0.05,
+            0.05
+        ));
+        assert!(fa.noise_var().relative_eq(truth.noise_var(), 0.05, 0.05));
+    }
+
+    #[test]
+    fn fit_maximizes_likelihood() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x33);
+        let truth = truth();
+        let xs: Vec<DVector<f64>> = truth.sample(300, &mut rng);
+        let fa = FactorModel::fit(&xs, 2, FactorNoise::Diagonal).unwrap();
+        let ppca = FactorModel::fit(&xs, 2, FactorNoise::Isotropic).unwrap();
+        assert!(fa.ln_likelihood(&xs) > truth.ln_likelihood(&xs));
+        assert!(fa.ln_likelihood(&xs) > ppca.ln_likelihood(&xs));
+
+        // The sample covariance shortcut agrees with the density
+        let s = sample_cov(&xs);
+        let ln_lik =
+            ln_lik_from_cov(&s, fa.loadings(), fa.noise_var(), xs.len() as f64);
+        assert::close(ln_lik, fa.ln_likelihood(&xs), 1E-8);
+    }
+
+    #[test]
+    fn posterior_matches_conditioned_joint() {
+        // (z, x) is jointly Gaussian with cross covariance Wᵀ
+        let model = truth();
+        let (q, k) = (model.n_factors(), model.ndims());
+        let mut cov = DMatrix::identity(q + k, q + k);
+        cov.view_mut((q, q), (k, k))
+            .copy_from(model.marginal().cov());
+        cov.view_mut((q, 0), (k, q)).copy_from(model.loadings());
+        cov.view_mut((0, q), (q, k))
+            .copy_from(&model.loadings().transpose());
+        let mut mu = DVector::zeros(q + k);
+        mu.rows_mut(q, k).copy_from(model.mu());
+        let joint = MvGaussian::new(mu, cov).unwrap();
+
+        let x = dvector![0.3, -0.2, 1.1, 2.5, 0.0];
+        let evidence: Vec<(usize, f64)> =
+            x.iter().enumerate().map(|(i, &xi)| (q + i, xi)).collect();
+        let expected = joint.condition(&evidence).unwrap();
+        let post = model.posterior(&x);
+        assert!(post.mu().relative_eq(expected.mu(), 1E-10, 1E-10));
+        assert!(post.cov().relative_eq(expected.cov(), 1E-10, 1E-10));
+    }
+
+    #[test]
+    fn fit_validates_data() {
+        let xs = vec![dvector![1.0, 2.0, 3.0], dvector![1.0, 2.0]];
+        assert_eq!(
+            FactorModel::fit(&xs, 1, FactorNoise::Diagonal),
+            Err(FactorModelError::DimensionMismatch {
+                dims: 2,
+                expected: 3
+            })
+        );
+        assert_eq!(
+            FactorModel::fit(&[], 1, FactorNoise::Diagonal),
+            Err(FactorModelError::NoData)
+        );
+    }
+}
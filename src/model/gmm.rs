@@ -0,0 +1,507 @@
+//! Univariate Gaussian mixture models fit by expectation maximization
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::dist::{Gaussian, Mixture, MixtureError};
+use crate::misc::{logsumexp, pflip};
+use crate::traits::*;
+use rand::Rng;
+use std::fmt;
+
+/// Maximum number of EM iterations used to fit a Gaussian mixture
+const EM_MAX_ITER: usize = 1_000;
+/// Stop EM when the log likelihood improves by less than this
+const EM_TOL: f64 = 1E-8;
+/// Component standard deviations are kept above this fraction of the
+/// standard deviation of the data so that a component cannot collapse onto a
+/// single point
+const MIN_SIGMA_FRAC: f64 = 1E-3;
+
+/// A Gaussian mixture fit to data, ready to be used as a density model or
+/// a soft classifier
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::Gaussian;
+/// use rv::model::FittedGmm;
+/// use rv::traits::Rv;
+///
+/// let mut rng = rand::thread_rng();
+/// let left: Vec<f64> = Gaussian::new(-3.0, 1.0).unwrap().sample(200, &mut rng);
+/// let right: Vec<f64> = Gaussian::new(3.0, 1.0).unwrap().sample(200, &mut rng);
+/// let xs = [left, right].concat();
+///
+/// let one = FittedGmm::fit(&xs, 1, &mut rng).unwrap();
+/// let two = FittedGmm::fit(&xs, 2, &mut rng).unwrap();
+/// assert!(two.bic(&xs) < one.bic(&xs));
+///
+/// // Points near each mode belong to different components
+/// let k = two.predict(&-3.0);
+/// assert_ne!(k, two.predict(&3.0));
+/// assert!(two.predict_proba(&-3.0)[k] > 0.99);
+///
+/// // The fitted model is a density
+/// assert!(two.ln_f(&0.0) < two.ln_f(&3.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct FittedGmm {
+    /// Component weights and Gaussian components
+    mixture: Mixture<Gaussian>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum FittedGmmError {
+    /// The component weights or components are invalid
+    Mixture(MixtureError),
+    /// There is no data to fit to
+    NoData,
+    /// Zero components were requested
+    ZeroComponents,
+    /// There are fewer distinct data than requested components
+    TooFewDistinct {
+        /// The number of distinct data
+        n_distinct: usize,
+        /// The number of components requested
+        n_components: usize,
+    },
+    /// A datum is infinite or NaN
+    DatumNotFinite {
+        /// Index of the datum
+        ix: usize,
+        /// The datum
+        x: f64,
+    },
+    /// A prior concentration parameter is less than or equal to zero
+    AlphaTooLow { alpha: f64 },
+    /// A prior concentration parameter is infinite or NaN
+    AlphaNotFinite { alpha: f64 },
+}
+
+impl FittedGmm {
+    /// Create a Gaussian mixture model from component weights and components
+    pub fn new(
+        weights: Vec<f64>,
+        components: Vec<Gaussian>,
+    ) -> Result<Self, FittedGmmError> {
+        Mixture::new(weights, components)
+            .map(|mixture| FittedGmm { mixture })
+            .map_err(FittedGmmError::Mixture)
+    }
+
+    /// Creates a new FittedGmm without checking whether the weights and
+    /// components are valid.
+    #[inline]
+    pub fn new_unchecked(weights: Vec<f64>, components: Vec<Gaussian>) -> Self {
+        FittedGmm {
+            mixture: Mixture::new_unchecked(weights, components),
+        }
+    }
+
+    /// Fit a mixture of `n_components` Gaussians to `xs` by expectation
+    /// maximization
+    ///
+    /// The component means start at data chosen by k-means++ seeding. EM
+    /// finds a local maximum of the likelihood, so fitting from several
+    /// `rng` states and keeping the model with the highest
+    /// [`ln_likelihood`](Self::ln_likelihood) is recommended.
+    pub fn fit<R: Rng>(
+        xs: &[f64],
+        n_components: usize,
+        rng: &mut R,
+    ) -> Result<Self, FittedGmmError> {
+        let distinct = distinct_data(xs)?;
+        if n_components == 0 {
+            Err(FittedGmmError::ZeroComponents)
+        } else if distinct.len() < n_components {
+            Err(FittedGmmError::TooFewDistinct {
+                n_distinct: distinct.len(),
+                n_components,
+            })
+        } else {
+            Ok(Self::fit_em(xs, &distinct, n_components, rng))
+        }
+    }
+
+    /// EM from k-means++ seeds, given the sorted, distinct values of `xs`
+    pub(super) fn fit_em<R: Rng>(
+        xs: &[f64],
+        distinct: &[f64],
+        n_components: usize,
+        rng: &mut R,
+    ) -> Self {
+        let sd = data_sd(xs);
+        let min_sigma = MIN_SIGMA_FRAC * sd;
+
+        let components = kmeanspp_seeds(distinct, n_components, rng)
+            .into_iter()
+            .map(|mu| Gaussian::new_unchecked(mu, sd))
+            .collect();
+        let mut model = FittedGmm {
+            mixture: Mixture::new_unchecked(
+                vec![1.0 / n_components as f64; n_components],
+                components,
+            ),
+        };
+
+        let mut resp = vec![vec![0.0; n_components]; xs.len()];
+        let mut ln_lik = f64::NEG_INFINITY;
+        for _ in 0..EM_MAX_ITER {
+            let ln_lik_new = model.e_step(xs, &mut resp);
+            model = Self::m_step(xs, &resp, min_sigma);
+            let converged = ln_lik_new - ln_lik < EM_TOL;
+            ln_lik = ln_lik_new;
+            if converged {
+                break;
+            }
+        }
+        model
+    }
+
+    /// The maximum likelihood parameters given the responsibilities
+    fn m_step(xs: &[f64], resp: &[Vec<f64>], min_sigma: f64) -> Self {
+        let n_components = resp[0].len();
+        let n = xs.len() as f64;
+        let (weights, components) = (0..n_components)
+            .map(|k| {
+                let nk: f64 = resp.iter().map(|r| r[k]).sum();
+                if nk > 0.0 {
+                    let mu = xs
+                        .iter()
+                        .zip(resp.iter())
+                        .map(|(x, r)| r[k] * x)
+                        .sum::<f64>()
+                        / nk;
+                    let var = xs
+                        .iter()
+                        .zip(resp.iter())
+                        .map(|(x, r)| r[k] * (x - mu).powi(2))
+                        .sum::<f64>()
+                        / nk;
+                    let sigma = var.sqrt().max(min_sigma);
+                    (nk / n, Gaussian::new_unchecked(mu, sigma))
+                } else {
+                    // An empty component keeps no weight
+                    (0.0, Gaussian::new_unchecked(0.0, 1.0))
+                }
+            })
+            .unzip();
+        FittedGmm {
+            mixture: Mixture::new_unchecked(weights, components),
+        }
+    }
+
+    /// Update the responsibilities and return the log likelihood
+    fn e_step(&self, xs: &[f64], resp: &mut [Vec<f64>]) -> f64 {
+        xs.iter()
+            .zip(resp.iter_mut())
+            .map(|(x, r)| {
+                let ln_ps = self.ln_joint(x);
+                let ln_f = logsumexp(&ln_ps);
+                r.iter_mut()
+                    .zip(ln_ps.iter())
+                    .for_each(|(rk, lp)| *rk = (lp - ln_f).exp());
+                ln_f
+            })
+            .sum()
+    }
+
+    /// ln p(component = k, x) for each component
+    fn ln_joint(&self, x: &f64) -> Vec<f64> {
+        self.mixture
+            .ln_weights()
+            .iter()
+            .zip(self.mixture.components().iter())
+            .map(|(ln_w, cpnt)| ln_w + cpnt.ln_f(x))
+            .collect()
+    }
+
+    /// The number of components
+    #[inline]
+    pub fn n_components(&self) -> usize {
+        self.mixture.k()
+    }
+
+    /// The component weights
+    #[inline]
+    pub fn weights(&self) -> &[f64] {
+        self.mixture.weights()
+    }
+
+    /// The Gaussian components
+    #[inline]
+    pub fn components(&self) -> &[Gaussian] {
+        self.mixture.components()
+    }
+
+    /// The underlying mixture distribution
+    #[inline]
+    pub fn mixture(&self) -> &Mixture<Gaussian> {
+        &self.mixture
+    }
+
+    /// The posterior probability that `x` was drawn from each component
+    pub fn predict_proba(&self, x: &f64) -> Vec<f64> {
+        let ln_ps = self.ln_joint(x);
+        let ln_f = logsumexp(&ln_ps);
+        ln_ps.iter().map(|lp| (lp - ln_f).exp()).collect()
+    }
+
+    /// The index of the component most likely to have generated `x`
+    pub fn predict(&self, x: &f64) -> usize {
+        self.ln_joint(x)
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(k, _)| k)
+            .unwrap()
+    }
+
+    /// The log likelihood of the data, `xs`
+    pub fn ln_likelihood(&self, xs: &[f64]) -> f64 {
+        xs.iter().map(|x| self.mixture.ln_f(x)).sum()
+    }
+
+    /// The number of free parameters: the weights less one, plus a mean and
+    /// standard deviation for each component
+    pub fn n_params(&self) -> usize {
+        3 * self.n_components() - 1
+    }
+
+    /// The Bayesian information criterion, `p ln n - 2 ln L`, of the model
+    /// for the data, `xs`. Lower is better.
+    pub fn bic(&self, xs: &[f64]) -> f64 {
+        (self.n_params() as f64)
+            .mul_add((xs.len() as f64).ln(), -2.0 * self.ln_likelihood(xs))
+    }
+
+    /// The Akaike information criterion, `2 p - 2 ln L`, of the model for
+    /// the data, `xs`. Lower is better.
+    pub fn aic(&self, xs: &[f64]) -> f64 {
+        2.0 * (self.n_params() as f64 - self.ln_likelihood(xs))
+    }
+}
+
+/// Check that `xs` is non-empty and finite, and return its sorted, distinct
+/// values
+pub(super) fn distinct_data(xs: &[f64]) -> Result<Vec<f64>, FittedGmmError> {
+    if xs.is_empty() {
+        return Err(FittedGmmError::NoData);
+    }
+    if let Some((ix, &x)) = xs.iter().enumerate().find(|(_, x)| !x.is_finite())
+    {
+        return Err(FittedGmmError::DatumNotFinite { ix, x });
+    }
+    let mut distinct = xs.to_vec();
+    distinct.sort_unstable_by(|a, b| a.total_cmp(b));
+    distinct.dedup();
+    Ok(distinct)
+}
+
+/// Population standard deviation of `xs`, kept above zero
+pub(super) fn data_sd(xs: &[f64]) -> f64 {
+    let n = xs.len() as f64;
+    let mean = xs.iter().sum::<f64>() / n;
+    (xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n)
+        .sqrt()
+        .max(f64::MIN_POSITIVE)
+}
+
+/// Choose `k` of the sorted, distinct `xs` as initial means, each with
+/// probability proportional to its squared distance from the nearest mean
+/// already chosen (Arthur & Vassilvitskii, 2007)
+pub(super) fn kmeanspp_seeds<R: Rng>(
+    xs: &[f64],
+    k: usize,
+    rng: &mut R,
+) -> Vec<f64> {
+    let mut seeds = vec![xs[rng.gen_range(0..xs.len())]];
+    let mut d2: Vec<f64> = xs.iter().map(|x| (x - seeds[0]).powi(2)).collect();
+    while seeds.len() < k {
+        let seed = xs[pflip(&d2, 1, rng)[0]];
+        d2.iter_mut().zip(xs.iter()).for_each(|(d, x)| {
+            *d = d.min((x - seed).powi(2));
+        });
+        seeds.push(seed);
+    }
+    seeds
+}
+
+impl From<FittedGmm> for Mixture<Gaussian> {
+    fn from(gmm: FittedGmm) -> Self {
+        gmm.mixture
+    }
+}
+
+impl Rv<f64> for FittedGmm {
+    fn ln_f(&self, x: &f64) -> f64 {
+        self.mixture.ln_f(x)
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> f64 {
+        self.mixture.draw(rng)
+    }
+
+    fn sample<R: Rng>(&self, n: usize, rng: &mut R) -> Vec<f64> {
+        self.mixture.sample(n, rng)
+    }
+}
+
+impl Support<f64> for FittedGmm {
+    fn supports(&self, x: &f64) -> bool {
+        x.is_finite()
+    }
+}
+
+impl ContinuousDistr<f64> for FittedGmm {}
+
+impl Cdf<f64> for FittedGmm {
+    fn cdf(&self, x: &f64) -> f64 {
+        self.mixture.cdf(x)
+    }
+}
+
+impl std::error::Error for FittedGmmError {}
+
+impl fmt::Display for FittedGmmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mixture(err) => write!(f, "invalid mixture: {}", err),
+            Self::NoData => write!(f, "no data to fit to"),
+            Self::ZeroComponents => {
+                write!(f, "at least one component is required")
+            }
+            Self::TooFewDistinct {
+                n_distinct,
+                n_components,
+            } => write!(
+                f,
+                "{} components requested but the data have only {} \
+                    distinct values",
+                n_components, n_distinct
+            ),
+            Self::DatumNotFinite { ix, x } => {
+                write!(f, "datum {} is not finite: {}", ix, x)
+            }
+            Self::AlphaTooLow { alpha } => {
+                write!(f, "alpha ({}) must be greater than zero", alpha)
+            }
+            Self::AlphaNotFinite { alpha } => {
+                write!(f, "non-finite alpha: {}", alpha)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    fn two_component_data(rng: &mut Xoshiro256Plus) -> Vec<f64> {
+        let left: Vec<f64> = Gaussian::new(-2.0, 0.5).unwrap().sample(300, rng);
+        let right: Vec<f64> = Gaussian::new(4.0, 1.5).unwrap().sample(700, rng);
+        [left, right].concat()
+    }
+
+    #[test]
+    fn fit_validates_input() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(1);
+        assert_eq!(
+            FittedGmm::fit(&[], 2, &mut rng),
+            Err(FittedGmmError::NoData)
+        );
+        assert_eq!(
+            FittedGmm::fit(&[1.0, 2.0], 0, &mut rng),
+            Err(FittedGmmError::ZeroComponents)
+        );
+        assert_eq!(
+            FittedGmm::fit(&[1.0, 1.0, 2.0], 3, &mut rng),
+            Err(FittedGmmError::TooFewDistinct {
+                n_distinct: 2,
+                n_components: 3
+            })
+        );
+        assert!(matches!(
+            FittedGmm::fit(&[1.0, f64::NAN], 1, &mut rng),
+            Err(FittedGmmError::DatumNotFinite { ix: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn single_component_is_mle() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(2);
+        let xs = [1.0, 2.5, -0.5, 3.0, 0.0];
+        let gmm = FittedGmm::fit(&xs, 1, &mut rng).unwrap();
+        let mean = xs.iter().sum::<f64>() / 5.0;
+        let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 5.0;
+        assert::close(gmm.components()[0].mu(), mean, TOL);
+        assert::close(gmm.components()[0].sigma(), var.sqrt(), TOL);
+        assert::close(gmm.weights()[0], 1.0, TOL);
+    }
+
+    #[test]
+    fn fit_recovers_components() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(3);
+        let xs = two_component_data(&mut rng);
+        let gmm = FittedGmm::fit(&xs, 2, &mut rng).unwrap();
+
+        let left = gmm.predict(&-2.0);
+        let right = 1 - left;
+        assert::close(gmm.weights()[left], 0.3, 0.05);
+        assert::close(gmm.components()[left].mu(), -2.0, 0.1);
+        assert::close(gmm.components()[left].sigma(), 0.5, 0.1);
+        assert::close(gmm.components()[right].mu(), 4.0, 0.2);
+        assert::close(gmm.components()[right].sigma(), 1.5, 0.2);
+    }
+
+    #[test]
+    fn predict_proba_is_posterior() {
+        let gmm = FittedGmm::new(
+            vec![0.25, 0.75],
+            vec![
+                Gaussian::new(0.0, 1.0).unwrap(),
+                Gaussian::new(2.0, 0.5).unwrap(),
+            ],
+        )
+        .unwrap();
+        let x = 1.2;
+        let p0 = 0.25 * gmm.components()[0].f(&x);
+        let p1 = 0.75 * gmm.components()[1].f(&x);
+        let proba = gmm.predict_proba(&x);
+        assert::close(proba[0], p0 / (p0 + p1), TOL);
+        assert::close(proba[1], p1 / (p0 + p1), TOL);
+        assert::close(gmm.ln_f(&x), (p0 + p1).ln(), TOL);
+        assert_eq!(gmm.predict(&x), usize::from(p1 > p0));
+    }
+
+    #[test]
+    fn information_criteria_prefer_true_number_of_components() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(4);
+        let xs = two_component_data(&mut rng);
+        let one = FittedGmm::fit(&xs, 1, &mut rng).unwrap();
+        let two = FittedGmm::fit(&xs, 2, &mut rng).unwrap();
+        assert!(two.bic(&xs) < one.bic(&xs));
+        assert!(two.aic(&xs) < one.aic(&xs));
+        assert_eq!(two.n_params(), 5);
+
+        let ln_l = two.ln_likelihood(&xs);
+        assert::close(two.aic(&xs), 2.0_f64.mul_add(-ln_l, 10.0), 1E-9);
+    }
+
+    #[test]
+    fn empty_component_keeps_no_weight() {
+        let xs = [0.0, 0.1, 10.0];
+        let resp = vec![vec![1.0, 0.0]; 3];
+        let gmm = FittedGmm::m_step(&xs, &resp, 1E-3);
+        assert_eq!(gmm.weights(), &[1.0, 0.0]);
+        assert_eq!(gmm.predict(&0.0), 0);
+    }
+}
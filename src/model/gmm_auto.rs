@@ -0,0 +1,622 @@
+//! Choosing the number of components of a Gaussian mixture
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use super::gmm::{data_sd, distinct_data, kmeanspp_seeds};
+use super::{FittedGmm, FittedGmmError, SviComponent};
+use crate::dist::{Gaussian, NormalGamma};
+use crate::misc::logsumexp;
+use special::Gamma as _;
+
+/// Maximum number of variational updates per fit
+const VB_MAX_ITER: usize = 1_000;
+/// Stop the variational updates when the lower bound improves by less than
+/// this fraction of its magnitude
+const VB_TOL: f64 = 1E-8;
+/// Prior pseudo-observations for each component mean. Small, so that the
+/// means are free to move across the data.
+const PRIOR_R: f64 = 1E-2;
+/// Prior degrees of freedom for each component precision
+const PRIOR_V: f64 = 2.0;
+
+/// How [`FittedGmm::fit_auto`] chooses the number of components
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum ComponentSelection {
+    /// Fit each number of components by maximum likelihood and keep the fit
+    /// with the lowest Bayesian information criterion
+    Bic,
+    /// Fit each number of components by maximum likelihood and keep the fit
+    /// with the lowest Akaike information criterion
+    Aic,
+    /// Fit each number of components by variational Bayes, with a
+    /// symmetric Dirichlet(`alpha`) prior on the weights, and keep the fit
+    /// with the highest evidence lower bound
+    Elbo { alpha: f64 },
+    /// Fit one variational Bayes mixture with a Dirichlet process prior of
+    /// concentration `alpha`, truncated at the maximum number of components,
+    /// and keep the components that explain at least one datum
+    DirichletProcess { alpha: f64 },
+}
+
+/// Options for [`FittedGmm::fit_auto`]
+///
+/// By default, up to 10 components are chosen by BIC, with the best of 3
+/// fits from different starting points for each number of components.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct FitAutoOptions {
+    /// How the number of components is chosen
+    selection: ComponentSelection,
+    /// The largest number of components considered
+    max_components: usize,
+    /// Fits from different starting points for each number of components
+    n_restarts: usize,
+}
+
+impl Default for FitAutoOptions {
+    fn default() -> Self {
+        Self::new(ComponentSelection::Bic)
+    }
+}
+
+impl FitAutoOptions {
+    /// Options that choose the number of components by `selection`
+    pub fn new(selection: ComponentSelection) -> Self {
+        FitAutoOptions {
+            selection,
+            max_components: 10,
+            n_restarts: 3,
+        }
+    }
+
+    /// Consider at most `max_components` components
+    pub fn with_max_components(mut self, max_components: usize) -> Self {
+        self.max_components = max_components;
+        self
+    }
+
+    /// Keep the best of `n_restarts` fits for each number of components
+    pub fn with_n_restarts(mut self, n_restarts: usize) -> Self {
+        self.n_restarts = n_restarts;
+        self
+    }
+
+    /// How the number of components is chosen
+    #[inline]
+    pub fn selection(&self) -> &ComponentSelection {
+        &self.selection
+    }
+
+    /// The largest number of components considered
+    #[inline]
+    pub fn max_components(&self) -> usize {
+        self.max_components
+    }
+
+    /// Fits from different starting points for each number of components
+    #[inline]
+    pub fn n_restarts(&self) -> usize {
+        self.n_restarts
+    }
+}
+
+impl FittedGmm {
+    /// Fit a Gaussian mixture to `xs`, choosing the number of components
+    /// as directed by `opts`
+    ///
+    /// The number of components is at most the number of distinct values
+    /// in `xs`. Variational fits are returned with each component at the
+    /// posterior mean of its mean and precision, and the expected weights.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Gaussian;
+    /// use rv::model::{ComponentSelection, FitAutoOptions, FittedGmm};
+    /// use rv::traits::Rv;
+    ///
+    /// let mut rng = rand::thread_rng();
+    /// let xs: Vec<f64> = [-6.0, 0.0, 6.0]
+    ///     .iter()
+    ///     .flat_map(|&mu| {
+    ///         let xs: Vec<f64> =
+    ///             Gaussian::new(mu, 1.0).unwrap().sample(150, &mut rng);
+    ///         xs
+    ///     })
+    ///     .collect();
+    ///
+    /// let gmm = FittedGmm::fit_auto(&xs, &FitAutoOptions::default(), &mut rng)
+    ///     .unwrap();
+    /// assert_eq!(gmm.n_components(), 3);
+    ///
+    /// // Let a Dirichlet process prior decide instead
+    /// let opts = FitAutoOptions::new(ComponentSelection::DirichletProcess {
+    ///     alpha: 1.0,
+    /// });
+    /// let gmm = FittedGmm::fit_auto(&xs, &opts, &mut rng).unwrap();
+    /// assert_eq!(gmm.n_components(), 3);
+    /// ```
+    pub fn fit_auto<R: rand::Rng>(
+        xs: &[f64],
+        opts: &FitAutoOptions,
+        rng: &mut R,
+    ) -> Result<Self, FittedGmmError> {
+        let distinct = distinct_data(xs)?;
+        if opts.max_components == 0 {
+            return Err(FittedGmmError::ZeroComponents);
+        }
+        let k_max = opts.max_components.min(distinct.len());
+        let n_restarts = opts.n_restarts.max(1);
+
+        // Lower scores are better
+        let best_of = |fits: Vec<(f64, FittedGmm)>| {
+            fits.into_iter()
+                .min_by(|(a, _), (b, _)| a.total_cmp(b))
+                .unwrap()
+        };
+
+        let best = match opts.selection {
+            ComponentSelection::Bic | ComponentSelection::Aic => {
+                let score = |gmm: &FittedGmm| {
+                    if opts.selection == ComponentSelection::Bic {
+                        gmm.bic(xs)
+                    } else {
+                        gmm.aic(xs)
+                    }
+                };
+                best_of(
+                    (1..=k_max)
+                        .map(|k| {
+                            // The restarts differ only in likelihood
+                            let (_, gmm) = best_of(
+                                (0..n_restarts)
+                                    .map(|_| {
+                                        let gmm =
+                                            Self::fit_em(xs, &distinct, k, rng);
+                                        (-gmm.ln_likelihood(xs), gmm)
+                                    })
+                                    .collect(),
+                            );
+                            (score(&gmm), gmm)
+                        })
+                        .collect(),
+                )
+            }
+            ComponentSelection::Elbo { alpha } => {
+                validate_alpha(alpha)?;
+                best_of(
+                    (1..=k_max)
+                        .flat_map(|k| {
+                            (0..n_restarts)
+                                .map(|_| {
+                                    let fit = VbFit::fit(
+                                        xs,
+                                        &distinct,
+                                        k,
+                                        WeightPrior::Dirichlet(alpha),
+                                        rng,
+                                    );
+                                    (-fit.elbo, fit.into_gmm(false))
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                        .collect(),
+                )
+            }
+            ComponentSelection::DirichletProcess { alpha } => {
+                validate_alpha(alpha)?;
+                best_of(
+                    (0..n_restarts)
+                        .map(|_| {
+                            let fit = VbFit::fit(
+                                xs,
+                                &distinct,
+                                k_max,
+                                WeightPrior::StickBreaking(alpha),
+                                rng,
+                            );
+                            (-fit.elbo, fit.into_gmm(true))
+                        })
+                        .collect(),
+                )
+            }
+        };
+        Ok(best.1)
+    }
+}
+
+fn validate_alpha(alpha: f64) -> Result<(), FittedGmmError> {
+    if alpha <= 0.0 {
+        Err(FittedGmmError::AlphaTooLow { alpha })
+    } else if !alpha.is_finite() {
+        Err(FittedGmmError::AlphaNotFinite { alpha })
+    } else {
+        Ok(())
+    }
+}
+
+/// The prior on the mixture weights of a variational fit
+#[derive(Clone, Copy, Debug)]
+enum WeightPrior {
+    /// Symmetric Dirichlet with the given concentration
+    Dirichlet(f64),
+    /// Truncated stick breaking with sticks distributed Beta(1, alpha)
+    StickBreaking(f64),
+}
+
+/// A mean-field variational Bayes fit of a Gaussian mixture with
+/// NormalGamma priors on the components
+struct VbFit {
+    /// Variational Dirichlet parameters, or Beta parameters of the sticks
+    weight_params: Vec<(f64, f64)>,
+    weight_prior: WeightPrior,
+    /// Variational distributions over the component parameters
+    components: Vec<NormalGamma>,
+    /// Expected number of data assigned to each component
+    counts: Vec<f64>,
+    /// Evidence lower bound
+    elbo: f64,
+}
+
+impl VbFit {
+    fn fit<R: rand::Rng>(
+        xs: &[f64],
+        distinct: &[f64],
+        k: usize,
+        weight_prior: WeightPrior,
+        rng: &mut R,
+    ) -> Self {
+        let n = xs.len() as f64;
+        let mean = xs.iter().sum::<f64>() / n;
+        let var = data_sd(xs).powi(2);
+        // E[precision] under the prior is that of the data
+        let prior =
+            NormalGamma::new_unchecked(mean, PRIOR_R, PRIOR_V * var, PRIOR_V);
+
+        // Start from hard assignments to the nearest k-means++ seed
+        let seeds = kmeanspp_seeds(distinct, k, rng);
+        let mut resp: Vec<Vec<f64>> = xs
+            .iter()
+            .map(|x| {
+                let nearest = (0..k)
+                    .min_by(|&a, &b| {
+                        (x - seeds[a]).abs().total_cmp(&(x - seeds[b]).abs())
+                    })
+                    .unwrap();
+                (0..k)
+                    .map(|j| if j == nearest { 1.0 } else { 0.0 })
+                    .collect()
+            })
+            .collect();
+
+        let mut fit = VbFit {
+            weight_params: Vec::new(),
+            weight_prior,
+            components: Vec::new(),
+            counts: Vec::new(),
+            elbo: f64::NEG_INFINITY,
+        };
+        for _ in 0..VB_MAX_ITER {
+            fit.update_params(xs, &resp, &prior);
+            let elbo = fit.update_resp(xs, &mut resp, &prior);
+            let converged = elbo - fit.elbo < VB_TOL * elbo.abs();
+            fit.elbo = elbo;
+            if converged {
+                break;
+            }
+        }
+        fit
+    }
+
+    /// Update the weight and component distributions given the
+    /// responsibilities
+    fn update_params(
+        &mut self,
+        xs: &[f64],
+        resp: &[Vec<f64>],
+        prior: &NormalGamma,
+    ) {
+        let k = resp[0].len();
+        let eta0 = prior.natural_params();
+        let mut stats = vec![eta0; k];
+        self.counts = vec![0.0; k];
+        xs.iter().zip(resp.iter()).for_each(|(x, r)| {
+            let tx = NormalGamma::natural_suffstat(x);
+            r.iter().enumerate().for_each(|(j, &rj)| {
+                self.counts[j] += rj;
+                stats[j]
+                    .iter_mut()
+                    .zip(tx.iter())
+                    .for_each(|(s, t)| *s = rj.mul_add(*t, *s));
+            });
+        });
+        self.components = stats
+            .iter()
+            .map(|eta| NormalGamma::from_natural_params(eta))
+            .collect();
+
+        self.weight_params = match self.weight_prior {
+            WeightPrior::Dirichlet(alpha) => {
+                self.counts.iter().map(|c| (alpha + c, 0.0)).collect()
+            }
+            WeightPrior::StickBreaking(alpha) => {
+                let mut tail: f64 = self.counts.iter().sum();
+                self.counts[..k - 1]
+                    .iter()
+                    .map(|c| {
+                        tail -= c;
+                        (1.0 + c, alpha + tail.max(0.0))
+                    })
+                    .collect()
+            }
+        };
+    }
+
+    /// E[ln π] for each component
+    fn expected_ln_weights(&self) -> Vec<f64> {
+        match self.weight_prior {
+            WeightPrior::Dirichlet(_) => {
+                let digamma_sum = self
+                    .weight_params
+                    .iter()
+                    .map(|(a, _)| a)
+                    .sum::<f64>()
+                    .digamma();
+                self.weight_params
+                    .iter()
+                    .map(|(a, _)| a.digamma() - digamma_sum)
+                    .collect()
+            }
+            WeightPrior::StickBreaking(_) => {
+                let mut ln_rest = 0.0;
+                let mut ln_weights: Vec<f64> = self
+                    .weight_params
+                    .iter()
+                    .map(|&(a, b)| {
+                        let digamma_ab = (a + b).digamma();
+                        let ln_w = ln_rest + a.digamma() - digamma_ab;
+                        ln_rest += b.digamma() - digamma_ab;
+                        ln_w
+                    })
+                    .collect();
+                ln_weights.push(ln_rest);
+                ln_weights
+            }
+        }
+    }
+
+    /// KL divergence of the weight distribution from its prior
+    fn weight_kl(&self) -> f64 {
+        match self.weight_prior {
+            WeightPrior::Dirichlet(alpha) => {
+                let a: Vec<f64> =
+                    self.weight_params.iter().map(|(a, _)| *a).collect();
+                kl_dirichlet(&a, &vec![alpha; a.len()])
+            }
+            WeightPrior::StickBreaking(alpha) => self
+                .weight_params
+                .iter()
+                .map(|&(a, b)| kl_dirichlet(&[a, b], &[1.0, alpha]))
+                .sum(),
+        }
+    }
+
+    /// Update the responsibilities and return the evidence lower bound
+    fn update_resp(
+        &self,
+        xs: &[f64],
+        resp: &mut [Vec<f64>],
+        prior: &NormalGamma,
+    ) -> f64 {
+        let e_ln_weights = self.expected_ln_weights();
+        let ln_lik: f64 = xs
+            .iter()
+            .zip(resp.iter_mut())
+            .map(|(x, r)| {
+                let ln_phi: Vec<f64> = e_ln_weights
+                    .iter()
+                    .zip(self.components.iter())
+                    .map(|(ln_w, cpnt)| ln_w + cpnt.expected_ln_f(x))
+                    .collect();
+                let z = logsumexp(&ln_phi);
+                r.iter_mut()
+                    .zip(ln_phi.iter())
+                    .for_each(|(rj, lp)| *rj = (lp - z).exp());
+                z
+            })
+            .sum();
+        let component_kl: f64 = self
+            .components
+            .iter()
+            .map(|q| kl_normal_gamma(q, prior))
+            .sum();
+        ln_lik - self.weight_kl() - component_kl
+    }
+
+    /// The mixture at the posterior means, optionally without the
+    /// components that explain less than one datum
+    fn into_gmm(self, prune: bool) -> FittedGmm {
+        let (weights, components): (Vec<f64>, Vec<Gaussian>) = self
+            .counts
+            .iter()
+            .zip(self.components.iter())
+            .filter(|(&c, _)| !prune || c >= 1.0)
+            .map(|(&c, q)| {
+                let sigma = (q.s() / q.v()).sqrt();
+                (c, Gaussian::new_unchecked(q.m(), sigma))
+            })
+            .unzip();
+        let kept: f64 = weights.iter().sum();
+        let weights = match self.weight_prior {
+            // Expected weights under the Dirichlet
+            WeightPrior::Dirichlet(alpha) => {
+                let norm = alpha.mul_add(weights.len() as f64, kept);
+                weights.iter().map(|c| (c + alpha) / norm).collect()
+            }
+            WeightPrior::StickBreaking(_) => {
+                weights.iter().map(|c| c / kept).collect()
+            }
+        };
+        FittedGmm::new_unchecked(weights, components)
+    }
+}
+
+/// KL(Dir(a) || Dir(a0))
+fn kl_dirichlet(a: &[f64], a0: &[f64]) -> f64 {
+    let sum_a: f64 = a.iter().sum();
+    let sum_a0: f64 = a0.iter().sum();
+    let digamma_sum = sum_a.digamma();
+    a.iter().zip(a0.iter()).fold(
+        sum_a.ln_gamma().0 - sum_a0.ln_gamma().0,
+        |acc, (&ai, &a0i)| {
+            (ai - a0i).mul_add(
+                ai.digamma() - digamma_sum,
+                acc - ai.ln_gamma().0 + a0i.ln_gamma().0,
+            )
+        },
+    )
+}
+
+/// KL(q || p) for NormalGamma distributions, in which the precision, ρ, is
+/// Gamma(v/2, s/2) and the mean is N(m, 1/(r ρ))
+fn kl_normal_gamma(q: &NormalGamma, p: &NormalGamma) -> f64 {
+    let (a_q, b_q) = (0.5 * q.v(), 0.5 * q.s());
+    let (a_p, b_p) = (0.5 * p.v(), 0.5 * p.s());
+    let e_rho = a_q / b_q;
+    let e_ln_rho = a_q.digamma() - b_q.ln();
+
+    let kl_mean = 0.5
+        * p.r().mul_add(
+            e_rho.mul_add((q.m() - p.m()).powi(2), q.r().recip()),
+            (q.r() / p.r()).ln() - 1.0,
+        );
+    let gamma_ln_density = |a: f64, b: f64| {
+        (a - 1.0).mul_add(
+            e_ln_rho,
+            a.mul_add(b.ln(), (-b).mul_add(e_rho, -a.ln_gamma().0)),
+        )
+    };
+    kl_mean + gamma_ln_density(a_q, b_q) - gamma_ln_density(a_p, b_p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{Rv, SuffStat};
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    fn clusters(mus: &[f64], n: usize, rng: &mut Xoshiro256Plus) -> Vec<f64> {
+        mus.iter()
+            .flat_map(|&mu| {
+                let xs: Vec<f64> =
+                    Gaussian::new(mu, 1.0).unwrap().sample(n, rng);
+                xs
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_selection_finds_separated_clusters() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xC1);
+        let xs = clusters(&[-8.0, 0.0, 8.0], 100, &mut rng);
+        for selection in [
+            ComponentSelection::Bic,
+            ComponentSelection::Elbo { alpha: 1.0 },
+            ComponentSelection::DirichletProcess { alpha: 1.0 },
+        ] {
+            let opts =
+                FitAutoOptions::new(selection.clone()).with_max_components(6);
+            let gmm = FittedGmm::fit_auto(&xs, &opts, &mut rng).unwrap();
+            assert_eq!(gmm.n_components(), 3, "{:?}", selection);
+            assert::close(gmm.weights().iter().sum::<f64>(), 1.0, 1E-10);
+        }
+        // AIC penalizes components lightly, so it never chooses fewer
+        let opts =
+            FitAutoOptions::new(ComponentSelection::Aic).with_max_components(6);
+        let gmm = FittedGmm::fit_auto(&xs, &opts, &mut rng).unwrap();
+        assert!(gmm.n_components() >= 3);
+    }
+
+    #[test]
+    fn single_gaussian_selects_one_component() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xC2);
+        let xs = clusters(&[2.0], 300, &mut rng);
+        for selection in [
+            ComponentSelection::Bic,
+            ComponentSelection::Elbo { alpha: 1.0 },
+            ComponentSelection::DirichletProcess { alpha: 1.0 },
+        ] {
+            let opts =
+                FitAutoOptions::new(selection.clone()).with_max_components(4);
+            let gmm = FittedGmm::fit_auto(&xs, &opts, &mut rng).unwrap();
+            assert_eq!(gmm.n_components(), 1, "{:?}", selection);
+        }
+    }
+
+    #[test]
+    fn fit_auto_validates_options() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xC3);
+        let xs = [1.0, 2.0, 3.0];
+        let opts = FitAutoOptions::default().with_max_components(0);
+        assert_eq!(
+            FittedGmm::fit_auto(&xs, &opts, &mut rng),
+            Err(FittedGmmError::ZeroComponents)
+        );
+        let opts =
+            FitAutoOptions::new(ComponentSelection::Elbo { alpha: -1.0 });
+        assert_eq!(
+            FittedGmm::fit_auto(&xs, &opts, &mut rng),
+            Err(FittedGmmError::AlphaTooLow { alpha: -1.0 })
+        );
+        assert_eq!(
+            FittedGmm::fit_auto(&[], &FitAutoOptions::default(), &mut rng),
+            Err(FittedGmmError::NoData)
+        );
+        // Never more components than distinct values
+        let gmm = FittedGmm::fit_auto(
+            &[1.0, 1.0, 4.0],
+            &FitAutoOptions::default(),
+            &mut rng,
+        )
+        .unwrap();
+        assert!(gmm.n_components() <= 2);
+    }
+
+    #[test]
+    fn one_component_elbo_is_log_evidence() {
+        // With one component the mean field is exact, so the lower bound
+        // equals the NormalGamma marginal likelihood
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xC4);
+        let xs = [0.3, -1.2, 2.2, 0.8, 1.1];
+        let fit =
+            VbFit::fit(&xs, &xs, 1, WeightPrior::Dirichlet(1.0), &mut rng);
+
+        let n = xs.len() as f64;
+        let mean = xs.iter().sum::<f64>() / n;
+        let var = data_sd(&xs).powi(2);
+        let prior =
+            NormalGamma::new_unchecked(mean, PRIOR_R, PRIOR_V * var, PRIOR_V);
+        let mut stat = crate::data::GaussianSuffStat::new();
+        xs.iter().for_each(|x| stat.observe(x));
+        let ln_m = <NormalGamma as crate::traits::ConjugatePrior<
+            f64,
+            Gaussian,
+        >>::ln_m(
+            &prior, &crate::data::DataOrSuffStat::SuffStat(&stat)
+        );
+        assert::close(fit.elbo, ln_m, 1E-8);
+    }
+
+    #[test]
+    fn kl_of_identical_distributions_is_zero() {
+        let ng = NormalGamma::new_unchecked(0.5, 2.0, 3.0, 4.0);
+        assert::close(kl_normal_gamma(&ng, &ng), 0.0, 1E-12);
+        assert::close(kl_dirichlet(&[1.5, 2.0], &[1.5, 2.0]), 0.0, 1E-12);
+        let other = NormalGamma::new_unchecked(1.5, 1.0, 2.0, 5.0);
+        assert!(kl_normal_gamma(&other, &ng) > 0.0);
+    }
+}
@@ -0,0 +1,488 @@
+//! Conjugate models of grouped data sharing a prior with a hyperprior
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use super::ConjugateModel;
+use crate::misc::optimize::nelder_mead;
+use crate::misc::slice_sample;
+use crate::traits::*;
+use rand::Rng;
+use std::fmt;
+use std::sync::Arc;
+
+/// Stop empirical Bayes optimization when the summed log marginal
+/// likelihood changes by less than this
+const EB_TOL: f64 = 1E-10;
+/// Maximum number of Nelder-Mead iterations per restart
+const EB_MAX_ITER: usize = 2_000;
+
+/// Partial pooling of groups of data through a shared conjugate prior
+///
+/// Each group is a [`ConjugateModel`] with its own parameters, drawn from a
+/// prior, *π(θ|φ)*, whose hyperparameters, *φ*, have independent
+/// hyperpriors. The group-level parameters are integrated out, so the
+/// hyperparameters are fit to *Σ ln f(group|φ)*, either to its maximum
+/// (empirical Bayes) or by sampling from their posterior.
+///
+/// # Type Parameters
+///
+/// `X`: The type of the data
+/// `Fx`: The type of the likelihood, *f(x|θ)*
+/// `Pr`: The type of the prior on the parameters of `Fx`, π(θ|φ)
+/// `H`: The type of the hyperprior on each hyperparameter
+/// `C`: Builds the prior from the hyperparameters, returning `None` if they
+///   are invalid
+///
+/// # Example
+///
+/// Success rates of several groups, pooled through a Beta prior
+///
+/// ```
+/// use rv::dist::{Bernoulli, Beta, Gamma};
+/// use rv::model::HierarchicalModel;
+///
+/// let mut model = HierarchicalModel::new(
+///     &Bernoulli::uniform(),
+///     3,
+///     vec![Gamma::new(2.0, 0.5).unwrap(); 2],
+///     |phi: &[f64]| Beta::new(phi[0], phi[1]).ok(),
+///     vec![1.0, 1.0],
+/// )
+/// .unwrap();
+///
+/// model.observe_many(0, &[true, true, false, true]);
+/// model.observe_many(1, &[true, false, true, true, true]);
+/// model.observe_many(2, &[false, true]);
+///
+/// let ln_m_before = model.ln_m();
+/// model.optimize_hyper().unwrap();
+/// assert!(model.ln_m() >= ln_m_before);
+///
+/// // Each group's posterior is pulled toward the others
+/// let post = model.group(2).posterior();
+/// assert!(post.alpha() > post.beta());
+/// ```
+pub struct HierarchicalModel<X, Fx, Pr, H, C>
+where
+    Fx: Rv<X> + HasSuffStat<X>,
+    Pr: ConjugatePrior<X, Fx>,
+{
+    hyperpriors: Vec<H>,
+    build: C,
+    /// The current hyperparameters
+    hyper: Vec<f64>,
+    groups: Vec<ConjugateModel<X, Fx, Pr>>,
+    /// The initial width of the slice for each hyperparameter
+    slice_width: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum HierarchicalModelError {
+    /// There must be at least one group
+    NoGroups,
+    /// There is not one hyperprior per hyperparameter
+    HyperpriorsLengthMismatch {
+        n_hyper: usize,
+        n_hyperpriors: usize,
+    },
+    /// The hyperparameters do not build a prior or have zero hyperprior
+    /// density
+    InvalidHyper { hyper: Vec<f64> },
+    /// The optimizer found no hyperparameters with a finite marginal
+    /// likelihood
+    OptimizationFailed,
+}
+
+impl<X, Fx, Pr, H, C> HierarchicalModel<X, Fx, Pr, H, C>
+where
+    Fx: Rv<X> + HasSuffStat<X>,
+    Pr: ConjugatePrior<X, Fx>,
+    H: Rv<f64> + Support<f64>,
+    C: Fn(&[f64]) -> Option<Pr>,
+{
+    /// Create a model of `n_groups` groups with no observations
+    ///
+    /// # Arguments
+    /// - fx: a likelihood, used only to create the empty sufficient
+    ///   statistics
+    /// - n_groups: the number of groups
+    /// - hyperpriors: the hyperprior on each hyperparameter
+    /// - build: builds the prior from the hyperparameters
+    /// - hyper: the initial hyperparameters
+    pub fn new(
+        fx: &Fx,
+        n_groups: usize,
+        hyperpriors: Vec<H>,
+        build: C,
+        hyper: Vec<f64>,
+    ) -> Result<Self, HierarchicalModelError> {
+        if n_groups == 0 {
+            return Err(HierarchicalModelError::NoGroups);
+        } else if hyper.len() != hyperpriors.len() {
+            return Err(HierarchicalModelError::HyperpriorsLengthMismatch {
+                n_hyper: hyper.len(),
+                n_hyperpriors: hyperpriors.len(),
+            });
+        }
+        let prior = Arc::new(build(&hyper).ok_or(
+            HierarchicalModelError::InvalidHyper {
+                hyper: hyper.clone(),
+            },
+        )?);
+        let model = HierarchicalModel {
+            hyperpriors,
+            build,
+            hyper,
+            groups: (0..n_groups)
+                .map(|_| ConjugateModel::new(fx, prior.clone()))
+                .collect(),
+            slice_width: 1.0,
+        };
+        if model.ln_hyperprior(&model.hyper).is_finite() {
+            Ok(model)
+        } else {
+            Err(HierarchicalModelError::InvalidHyper { hyper: model.hyper })
+        }
+    }
+
+    /// Set the initial width of the slice sampler used by
+    /// [`gibbs_step`](HierarchicalModel::gibbs_step), which should be about
+    /// the posterior scale of the hyperparameters. Defaults to 1.
+    ///
+    /// # Panics
+    /// If `slice_width` is not positive and finite
+    #[must_use]
+    pub fn with_slice_width(mut self, slice_width: f64) -> Self {
+        assert!(
+            slice_width > 0.0 && slice_width.is_finite(),
+            "slice_width must be positive and finite"
+        );
+        self.slice_width = slice_width;
+        self
+    }
+
+    /// The number of groups
+    #[inline]
+    pub fn n_groups(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// The model of group `ix`
+    #[inline]
+    pub fn group(&self, ix: usize) -> &ConjugateModel<X, Fx, Pr> {
+        &self.groups[ix]
+    }
+
+    /// The models of every group
+    #[inline]
+    pub fn groups(&self) -> &[ConjugateModel<X, Fx, Pr>] {
+        &self.groups
+    }
+
+    /// The current hyperparameters
+    #[inline]
+    pub fn hyper(&self) -> &[f64] {
+        &self.hyper
+    }
+
+    /// The prior shared by the groups under the current hyperparameters
+    #[inline]
+    pub fn prior(&self) -> &Pr {
+        &self.groups[0].prior
+    }
+
+    /// Observe `x` in group `ix`
+    pub fn observe(&mut self, ix: usize, x: &X) {
+        self.groups[ix].observe(x);
+    }
+
+    /// Observe each of `xs` in group `ix`
+    pub fn observe_many(&mut self, ix: usize, xs: &[X]) {
+        self.groups[ix].observe_many(xs);
+    }
+
+    /// Forget `x` from group `ix`
+    pub fn forget(&mut self, ix: usize, x: &X) {
+        self.groups[ix].forget(x);
+    }
+
+    /// The log marginal likelihood of every group under the current
+    /// hyperparameters, *Σ ln f(group|φ)*
+    pub fn ln_m(&self) -> f64 {
+        self.groups.iter().map(|group| group.ln_m()).sum()
+    }
+
+    /// The unnormalized log posterior of the current hyperparameters
+    pub fn ln_hyper_posterior(&self) -> f64 {
+        self.ln_hyperprior(&self.hyper) + self.ln_m()
+    }
+
+    /// Set the hyperparameters and rebuild the shared prior
+    pub fn set_hyper(
+        &mut self,
+        hyper: Vec<f64>,
+    ) -> Result<(), HierarchicalModelError> {
+        if hyper.len() != self.hyperpriors.len() {
+            return Err(HierarchicalModelError::HyperpriorsLengthMismatch {
+                n_hyper: hyper.len(),
+                n_hyperpriors: self.hyperpriors.len(),
+            });
+        }
+        let prior = (self.build)(&hyper)
+            .filter(|_| self.ln_hyperprior(&hyper).is_finite())
+            .ok_or_else(|| HierarchicalModelError::InvalidHyper {
+                hyper: hyper.clone(),
+            })?;
+        let prior = Arc::new(prior);
+        self.groups
+            .iter_mut()
+            .for_each(|group| group.prior = prior.clone());
+        self.hyper = hyper;
+        Ok(())
+    }
+
+    /// Set the hyperparameters to those maximizing the summed log marginal
+    /// likelihood of the groups (type II maximum likelihood, or empirical
+    /// Bayes), starting from the current ones. The hyperpriors only restrict
+    /// the search to their support. Returns the maximized log marginal
+    /// likelihood.
+    pub fn optimize_hyper(&mut self) -> Result<f64, HierarchicalModelError> {
+        let neg_ln_m = |hyper: &[f64]| {
+            if self.ln_hyperprior(hyper).is_finite() {
+                -self.ln_m_at(hyper)
+            } else {
+                f64::INFINITY
+            }
+        };
+
+        let step = self
+            .hyper
+            .iter()
+            .fold(0.0_f64, |acc, h| acc.max(h.abs()))
+            .max(1.0)
+            * 0.1;
+        let mut best =
+            nelder_mead(neg_ln_m, &self.hyper, step, EB_TOL, EB_MAX_ITER);
+        // Restart from the best point found so that a collapsed simplex does
+        // not end the search early.
+        for _ in 0..3 {
+            let next =
+                nelder_mead(neg_ln_m, &best.0, step / 5.0, EB_TOL, EB_MAX_ITER);
+            if next.1 >= best.1 {
+                break;
+            }
+            best = next;
+        }
+
+        if best.1.is_finite() {
+            self.set_hyper(best.0)?;
+            Ok(-best.1)
+        } else {
+            Err(HierarchicalModelError::OptimizationFailed)
+        }
+    }
+
+    /// Update each hyperparameter in turn by drawing it from its posterior
+    /// given the others, with the group-level parameters integrated out,
+    /// using a slice sampler
+    pub fn gibbs_step<R: Rng>(&mut self, rng: &mut R) {
+        let mut hyper = self.hyper.clone();
+        for ix in 0..hyper.len() {
+            let ln_f = |h: f64| {
+                let mut proposal = hyper.clone();
+                proposal[ix] = h;
+                let ln_prior = self.ln_hyperprior(&proposal);
+                if ln_prior.is_finite() {
+                    ln_prior + self.ln_m_at(&proposal)
+                } else {
+                    f64::NEG_INFINITY
+                }
+            };
+            hyper[ix] =
+                slice_sample(ln_f, hyper[ix], self.slice_width, 1, rng)[0];
+        }
+        self.set_hyper(hyper)
+            .expect("slice samples have positive posterior density");
+    }
+
+    /// Run `n` Gibbs steps and return the hyperparameters after each
+    pub fn sample_hyper<R: Rng>(
+        &mut self,
+        n: usize,
+        rng: &mut R,
+    ) -> Vec<Vec<f64>> {
+        (0..n)
+            .map(|_| {
+                self.gibbs_step(rng);
+                self.hyper.clone()
+            })
+            .collect()
+    }
+
+    /// The log hyperprior density, which is -∞ outside the support
+    fn ln_hyperprior(&self, hyper: &[f64]) -> f64 {
+        self.hyperpriors
+            .iter()
+            .zip(hyper.iter())
+            .map(|(hp, h)| {
+                if hp.supports(h) {
+                    hp.ln_f(h)
+                } else {
+                    f64::NEG_INFINITY
+                }
+            })
+            .sum()
+    }
+
+    /// The summed log marginal likelihood of the groups under `hyper`
+    fn ln_m_at(&self, hyper: &[f64]) -> f64 {
+        (self.build)(hyper).map_or(f64::NEG_INFINITY, |prior| {
+            self.groups
+                .iter()
+                .map(|group| prior.ln_m(&group.obs()))
+                .sum()
+        })
+    }
+}
+
+impl std::error::Error for HierarchicalModelError {}
+
+impl fmt::Display for HierarchicalModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoGroups => write!(f, "there must be at least one group"),
+            Self::HyperpriorsLengthMismatch {
+                n_hyper,
+                n_hyperpriors,
+            } => write!(
+                f,
+                "there are {} hyperparameters but {} hyperpriors",
+                n_hyper, n_hyperpriors
+            ),
+            Self::InvalidHyper { hyper } => write!(
+                f,
+                "the hyperparameters {:?} are not valid or have zero \
+                 hyperprior density",
+                hyper
+            ),
+            Self::OptimizationFailed => write!(
+                f,
+                "no hyperparameters with a finite marginal likelihood were \
+                 found"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Bernoulli, Beta, Gamma};
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    type BetaBernoulli = HierarchicalModel<
+        bool,
+        Bernoulli,
+        Beta,
+        Gamma,
+        fn(&[f64]) -> Option<Beta>,
+    >;
+
+    fn build(phi: &[f64]) -> Option<Beta> {
+        Beta::new(phi[0], phi[1]).ok()
+    }
+
+    fn rates_model(rng: &mut Xoshiro256Plus) -> BetaBernoulli {
+        let mut model = BetaBernoulli::new(
+            &Bernoulli::uniform(),
+            20,
+            vec![Gamma::new(1.0, 0.1).unwrap(); 2],
+            build,
+            vec![1.0, 1.0],
+        )
+        .unwrap();
+
+        // Group rates drawn from a Beta(6, 4)
+        let rates = Beta::new(6.0, 4.0).unwrap();
+        for ix in 0..20 {
+            let p: f64 = rates.draw(rng);
+            let flips: Vec<bool> = Bernoulli::new(p).unwrap().sample(50, rng);
+            model.observe_many(ix, &flips);
+        }
+        model
+    }
+
+    #[test]
+    fn empirical_bayes_finds_local_maximum() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x97);
+        let mut model = rates_model(&mut rng);
+        let ln_m = model.optimize_hyper().unwrap();
+        assert::close(ln_m, model.ln_m(), 1E-10);
+
+        // Every group uses the optimized prior
+        let hyper = model.hyper().to_vec();
+        assert!(model.groups().iter().all(|group| {
+            *group.prior == Beta::new(hyper[0], hyper[1]).unwrap()
+        }));
+
+        // No nearby hyperparameters do better
+        for (da, db) in [(0.05, 0.0), (-0.05, 0.0), (0.0, 0.05), (0.0, -0.05)] {
+            let nudged = vec![hyper[0] * (1.0 + da), hyper[1] * (1.0 + db)];
+            assert!(model.ln_m_at(&nudged) <= ln_m);
+        }
+
+        // The prior mean is near the mean rate
+        assert!((hyper[0] / (hyper[0] + hyper[1]) - 0.6).abs() < 0.1);
+    }
+
+    #[test]
+    fn gibbs_samples_stay_in_support() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x98);
+        let mut model = rates_model(&mut rng);
+        let samples = model.sample_hyper(200, &mut rng);
+        assert_eq!(samples.len(), 200);
+        assert!(samples.iter().all(|phi| phi[0] > 0.0 && phi[1] > 0.0));
+        assert_eq!(model.hyper(), samples[199].as_slice());
+
+        let mean_a =
+            samples[50..].iter().map(|phi| phi[0]).sum::<f64>() / 150.0;
+        let mean_b =
+            samples[50..].iter().map(|phi| phi[1]).sum::<f64>() / 150.0;
+        assert!((mean_a / (mean_a + mean_b) - 0.6).abs() < 0.1);
+    }
+
+    #[test]
+    fn new_validates_hyperparameters() {
+        let hyperpriors = vec![Gamma::new(1.0, 1.0).unwrap(); 2];
+        let fx = Bernoulli::uniform();
+        assert_eq!(
+            BetaBernoulli::new(
+                &fx,
+                0,
+                hyperpriors.clone(),
+                build,
+                vec![1.0, 1.0]
+            )
+            .err(),
+            Some(HierarchicalModelError::NoGroups)
+        );
+        assert_eq!(
+            BetaBernoulli::new(&fx, 2, hyperpriors.clone(), build, vec![1.0])
+                .err(),
+            Some(HierarchicalModelError::HyperpriorsLengthMismatch {
+                n_hyper: 1,
+                n_hyperpriors: 2
+            })
+        );
+        assert_eq!(
+            BetaBernoulli::new(&fx, 2, hyperpriors, build, vec![1.0, -1.0])
+                .err(),
+            Some(HierarchicalModelError::InvalidHyper {
+                hyper: vec![1.0, -1.0]
+            })
+        );
+    }
+}
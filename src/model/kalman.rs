@@ -0,0 +1,313 @@
+//! One-dimensional Kalman filter for fusing noisy measurements
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::dist::Gaussian;
+use crate::traits::Rv;
+use std::fmt;
+
+/// A Gaussian belief about a scalar quantity, updated by measurements with
+/// known noise and widened by process noise as time passes
+///
+/// Each measurement is a conjugate Gaussian update of the mean with known
+/// variance, so the belief stays Gaussian. Between measurements the quantity
+/// is assumed to follow a random walk whose variance grows by `q²` per unit
+/// time.
+///
+/// # Example
+///
+/// Fuse readings from two thermometers of different precision
+///
+/// ```
+/// use rv::dist::Gaussian;
+/// use rv::model::ScalarKalman;
+///
+/// let prior = Gaussian::new(20.0, 10.0).unwrap();
+/// let mut kf = ScalarKalman::new(prior, 0.5, 0.1).unwrap();
+///
+/// kf.observe(21.3);
+/// // A sensor with its own noise level
+/// kf.observe_with_noise(20.9, 2.0).unwrap();
+/// assert!((kf.state().mu() - 21.27).abs() < 0.01);
+///
+/// // The belief spreads out between measurements
+/// let sigma = kf.state().sigma();
+/// kf.predict(5.0);
+/// assert!(kf.state().sigma() > sigma);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct ScalarKalman {
+    /// The current belief about the quantity
+    state: Gaussian,
+    /// Standard deviation of the measurement noise
+    obs_sigma: f64,
+    /// Standard deviation of the process noise per unit time
+    process_sigma: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum ScalarKalmanError {
+    /// The measurement noise is less than or equal to zero
+    ObsSigmaTooLow { obs_sigma: f64 },
+    /// The measurement noise is infinite or NaN
+    ObsSigmaNotFinite { obs_sigma: f64 },
+    /// The process noise is less than zero
+    ProcessSigmaTooLow { process_sigma: f64 },
+    /// The process noise is infinite or NaN
+    ProcessSigmaNotFinite { process_sigma: f64 },
+}
+
+fn validate_obs_sigma(obs_sigma: f64) -> Result<(), ScalarKalmanError> {
+    if obs_sigma <= 0.0 {
+        Err(ScalarKalmanError::ObsSigmaTooLow { obs_sigma })
+    } else if !obs_sigma.is_finite() {
+        Err(ScalarKalmanError::ObsSigmaNotFinite { obs_sigma })
+    } else {
+        Ok(())
+    }
+}
+
+impl ScalarKalman {
+    /// Create a new filter
+    ///
+    /// # Arguments
+    /// - prior: the initial belief about the quantity
+    /// - obs_sigma: standard deviation of the measurement noise
+    /// - process_sigma: standard deviation of the process noise per unit
+    ///   time. Zero for a quantity that does not change.
+    pub fn new(
+        prior: Gaussian,
+        obs_sigma: f64,
+        process_sigma: f64,
+    ) -> Result<Self, ScalarKalmanError> {
+        validate_obs_sigma(obs_sigma)?;
+        if process_sigma < 0.0 {
+            Err(ScalarKalmanError::ProcessSigmaTooLow { process_sigma })
+        } else if !process_sigma.is_finite() {
+            Err(ScalarKalmanError::ProcessSigmaNotFinite { process_sigma })
+        } else {
+            Ok(ScalarKalman {
+                state: prior,
+                obs_sigma,
+                process_sigma,
+            })
+        }
+    }
+
+    /// Creates a new ScalarKalman without checking whether the parameters
+    /// are valid.
+    #[inline]
+    pub fn new_unchecked(
+        prior: Gaussian,
+        obs_sigma: f64,
+        process_sigma: f64,
+    ) -> Self {
+        ScalarKalman {
+            state: prior,
+            obs_sigma,
+            process_sigma,
+        }
+    }
+
+    /// The current belief about the quantity
+    #[inline]
+    pub fn state(&self) -> &Gaussian {
+        &self.state
+    }
+
+    /// Standard deviation of the measurement noise
+    #[inline]
+    pub fn obs_sigma(&self) -> f64 {
+        self.obs_sigma
+    }
+
+    /// Standard deviation of the process noise per unit time
+    #[inline]
+    pub fn process_sigma(&self) -> f64 {
+        self.process_sigma
+    }
+
+    /// The predictive distribution of the next measurement
+    pub fn predictive(&self) -> Gaussian {
+        let sigma = self.state.sigma().hypot(self.obs_sigma);
+        Gaussian::new_unchecked(self.state.mu(), sigma)
+    }
+
+    /// Update the belief with a measurement, `y`, and return its log
+    /// predictive density before the update
+    ///
+    /// The sum of the returned values over a sequence of measurements is the
+    /// log likelihood of the sequence, which can be used to choose the noise
+    /// levels.
+    pub fn observe(&mut self, y: f64) -> f64 {
+        self.update(y, self.obs_sigma)
+    }
+
+    /// Update the belief with a measurement, `y`, whose noise has standard
+    /// deviation `obs_sigma`, and return its log predictive density before
+    /// the update
+    pub fn observe_with_noise(
+        &mut self,
+        y: f64,
+        obs_sigma: f64,
+    ) -> Result<f64, ScalarKalmanError> {
+        validate_obs_sigma(obs_sigma)?;
+        Ok(self.update(y, obs_sigma))
+    }
+
+    /// Advance time by `dt`, adding `process_sigma² dt` to the variance of
+    /// the belief
+    pub fn predict(&mut self, dt: f64) {
+        let var = self.process_sigma.powi(2).mul_add(dt, self.var());
+        self.state.set_sigma_unchecked(var.sqrt());
+    }
+
+    #[inline]
+    fn var(&self) -> f64 {
+        self.state.sigma().powi(2)
+    }
+
+    fn update(&mut self, y: f64, obs_sigma: f64) -> f64 {
+        let var = self.var();
+        let obs_var = obs_sigma * obs_sigma;
+        let pred_var = var + obs_var;
+        let ln_pp =
+            Gaussian::new_unchecked(self.state.mu(), pred_var.sqrt()).ln_f(&y);
+
+        // Kalman gain
+        let gain = var / pred_var;
+        let mu = gain.mul_add(y - self.state.mu(), self.state.mu());
+        let sigma = (var * obs_var / pred_var).sqrt();
+        self.state = Gaussian::new_unchecked(mu, sigma);
+        ln_pp
+    }
+}
+
+impl std::error::Error for ScalarKalmanError {}
+
+impl fmt::Display for ScalarKalmanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ObsSigmaTooLow { obs_sigma } => write!(
+                f,
+                "observation sigma ({}) must be greater than zero",
+                obs_sigma
+            ),
+            Self::ObsSigmaNotFinite { obs_sigma } => {
+                write!(f, "non-finite observation sigma: {}", obs_sigma)
+            }
+            Self::ProcessSigmaTooLow { process_sigma } => write!(
+                f,
+                "process sigma ({}) must be greater than or equal to zero",
+                process_sigma
+            ),
+            Self::ProcessSigmaNotFinite { process_sigma } => {
+                write!(f, "non-finite process sigma: {}", process_sigma)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::BivariateGaussian;
+
+    const TOL: f64 = 1E-12;
+
+    #[test]
+    fn new_validates_noise() {
+        let prior = Gaussian::standard();
+        assert!(ScalarKalman::new(prior.clone(), 1.0, 0.0).is_ok());
+        assert_eq!(
+            ScalarKalman::new(prior.clone(), 0.0, 1.0),
+            Err(ScalarKalmanError::ObsSigmaTooLow { obs_sigma: 0.0 })
+        );
+        assert_eq!(
+            ScalarKalman::new(prior.clone(), 1.0, -1.0),
+            Err(ScalarKalmanError::ProcessSigmaTooLow {
+                process_sigma: -1.0
+            })
+        );
+        assert!(ScalarKalman::new(prior, 1.0, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn static_updates_match_conjugate_posterior() {
+        let (m0, s0, r) = (1.0, 2.0, 0.5);
+        let ys = [1.3, 0.7, 1.9, 1.1];
+        let mut kf =
+            ScalarKalman::new(Gaussian::new(m0, s0).unwrap(), r, 0.0).unwrap();
+        ys.iter().for_each(|&y| {
+            kf.observe(y);
+        });
+
+        // Precision-weighted posterior of a Gaussian mean with known variance
+        let prec = (s0 * s0).recip() + ys.len() as f64 / (r * r);
+        let mean = (m0 / (s0 * s0) + ys.iter().sum::<f64>() / (r * r)) / prec;
+        assert::close(kf.state().mu(), mean, TOL);
+        assert::close(kf.state().sigma(), prec.recip().sqrt(), TOL);
+    }
+
+    #[test]
+    fn ln_pp_sum_is_marginal_likelihood() {
+        // Jointly, two measurements are N(m0 1, s0² 11ᵀ + r² I)
+        let (m0, s0, r) = (0.5, 1.5, 0.8);
+        let ys = [1.0, -0.2];
+        let mut kf =
+            ScalarKalman::new(Gaussian::new(m0, s0).unwrap(), r, 0.0).unwrap();
+        let ln_m: f64 = ys.iter().map(|&y| kf.observe(y)).sum();
+
+        let sd = s0.hypot(r);
+        let joint =
+            BivariateGaussian::new((m0, m0), (sd, sd), (s0 / sd).powi(2))
+                .unwrap();
+        assert::close(ln_m, joint.ln_f(&(ys[0], ys[1])), TOL);
+    }
+
+    #[test]
+    fn predictive_adds_measurement_noise() {
+        let kf = ScalarKalman::new(Gaussian::new(3.0, 0.6).unwrap(), 0.8, 0.1)
+            .unwrap();
+        let pred = kf.predictive();
+        assert::close(pred.mu(), 3.0, TOL);
+        assert::close(pred.sigma(), 1.0, TOL);
+    }
+
+    #[test]
+    fn predict_adds_process_variance() {
+        let mut kf =
+            ScalarKalman::new(Gaussian::new(0.0, 1.0).unwrap(), 1.0, 0.5)
+                .unwrap();
+        kf.predict(4.0);
+        assert::close(kf.state().sigma(), 2.0_f64.sqrt(), TOL);
+        assert::close(kf.state().mu(), 0.0, TOL);
+    }
+
+    #[test]
+    fn variance_converges_to_steady_state() {
+        let (q, r) = (0.3, 1.2);
+        let mut kf =
+            ScalarKalman::new(Gaussian::new(0.0, 5.0).unwrap(), r, q).unwrap();
+        for _ in 0..200 {
+            kf.predict(1.0);
+            kf.observe(0.0);
+        }
+        // Fixed point of P = (P + q²) r² / (P + q² + r²)
+        let (q2, r2) = (q * q, r * r);
+        let p = 0.5 * (-q2 + q2.mul_add(q2, 4.0 * q2 * r2).sqrt());
+        assert::close(kf.state().sigma().powi(2), p, 1E-10);
+    }
+
+    #[test]
+    fn observe_with_noise_rejects_bad_noise() {
+        let mut kf = ScalarKalman::new(Gaussian::standard(), 1.0, 0.0).unwrap();
+        assert!(kf.observe_with_noise(1.0, -2.0).is_err());
+        assert!(kf.observe_with_noise(1.0, f64::INFINITY).is_err());
+        assert_eq!(kf.state(), &Gaussian::standard());
+    }
+}
@@ -0,0 +1,508 @@
+//! Latent class analysis of multivariate categorical data
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::data::CategoricalDatum;
+use crate::dist::{Categorical, Mixture, MixtureError, SymmetricDirichlet};
+use crate::misc::{ln_pflip, logsumexp};
+use crate::traits::*;
+use rand::Rng;
+use std::fmt;
+
+/// Maximum number of EM iterations used to fit a latent class model
+const EM_MAX_ITER: usize = 1_000;
+/// Stop EM when the log likelihood improves by less than this
+const EM_TOL: f64 = 1E-8;
+
+/// A latent class model: a mixture over classes in which the items of a
+/// response vector are independent Categoricals given the class
+///
+/// With two categories per item this is a mixture of multivariate
+/// Bernoullis. Binary responses can be given as `bool`s, and polytomous
+/// responses as `usize`s or `u8`s.
+///
+/// # Example
+///
+/// Find two groups of respondents in a survey of yes/no questions
+///
+/// ```
+/// use rv::model::LatentClassModel;
+/// use rv::traits::Rv;
+///
+/// let mut rng = rand::thread_rng();
+/// let xs: Vec<Vec<bool>> = (0..200)
+///     .map(|i| {
+///         let agree = i % 2 == 0;
+///         vec![agree, agree, agree, !agree]
+///     })
+///     .collect();
+///
+/// let one = LatentClassModel::fit(&xs, 1, &mut rng).unwrap();
+/// let two = LatentClassModel::fit(&xs, 2, &mut rng).unwrap();
+/// assert!(two.bic(&xs) < one.bic(&xs));
+///
+/// // Respondents who agree with the first question are in the same class
+/// let post = two.class_posterior(&vec![true, true, true, false]);
+/// assert!(post.iter().any(|&p| p > 0.99));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct LatentClassModel {
+    /// Class weights, and for each class the distribution of each item
+    mixture: Mixture<Vec<Categorical>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum LatentClassError {
+    /// The class weights or class profiles are invalid
+    Mixture(MixtureError),
+    /// There are no response vectors to fit to
+    NoData,
+    /// The response vectors have no items
+    NoItems,
+    /// Zero classes were requested
+    ZeroClasses,
+    /// A response vector or class profile has the wrong number of items
+    ItemCountMismatch {
+        /// Index of the offending response vector or class
+        ix: usize,
+        /// Its number of items
+        n_items: usize,
+        /// The number of items in the first
+        expected: usize,
+    },
+    /// Two classes give an item different numbers of categories
+    CategoryCountMismatch {
+        /// Index of the item
+        item: usize,
+    },
+}
+
+impl LatentClassModel {
+    /// Create a latent class model from class weights and, for each class,
+    /// the distribution of each item
+    ///
+    /// Every class must have the same number of items, and each item must
+    /// have the same number of categories in every class.
+    pub fn new(
+        weights: Vec<f64>,
+        classes: Vec<Vec<Categorical>>,
+    ) -> Result<Self, LatentClassError> {
+        let n_items = classes.first().map_or(0, |cls| cls.len());
+        if !classes.is_empty() && n_items == 0 {
+            return Err(LatentClassError::NoItems);
+        }
+        classes.iter().enumerate().try_for_each(|(ix, cls)| {
+            if cls.len() != n_items {
+                Err(LatentClassError::ItemCountMismatch {
+                    ix,
+                    n_items: cls.len(),
+                    expected: n_items,
+                })
+            } else {
+                (0..n_items).try_for_each(|item| {
+                    if cls[item].k() == classes[0][item].k() {
+                        Ok(())
+                    } else {
+                        Err(LatentClassError::CategoryCountMismatch { item })
+                    }
+                })
+            }
+        })?;
+        Mixture::new(weights, classes)
+            .map(|mixture| LatentClassModel { mixture })
+            .map_err(LatentClassError::Mixture)
+    }
+
+    /// Fit a model with `n_classes` classes to the response vectors, `xs`,
+    /// by expectation maximization from random class memberships
+    ///
+    /// The number of categories of each item is one more than its largest
+    /// observed value, and at least two. EM finds a local maximum of the
+    /// likelihood, so fitting from several `rng` states and keeping the
+    /// model with the highest [`ln_likelihood`](Self::ln_likelihood) is
+    /// recommended.
+    pub fn fit<X, R>(
+        xs: &[Vec<X>],
+        n_classes: usize,
+        rng: &mut R,
+    ) -> Result<Self, LatentClassError>
+    where
+        X: CategoricalDatum,
+        R: Rng,
+    {
+        if xs.is_empty() {
+            return Err(LatentClassError::NoData);
+        } else if n_classes == 0 {
+            return Err(LatentClassError::ZeroClasses);
+        }
+
+        let n_items = xs[0].len();
+        if n_items == 0 {
+            return Err(LatentClassError::NoItems);
+        }
+        let mut n_cats = vec![2_usize; n_items];
+        xs.iter().enumerate().try_for_each(|(ix, x)| {
+            if x.len() != n_items {
+                return Err(LatentClassError::ItemCountMismatch {
+                    ix,
+                    n_items: x.len(),
+                    expected: n_items,
+                });
+            }
+            x.iter().zip(n_cats.iter_mut()).for_each(|(&xj, k)| {
+                *k = (*k).max(xj.into_usize() + 1);
+            });
+            Ok(())
+        })?;
+
+        let dir = SymmetricDirichlet::new_unchecked(1.0, n_classes);
+        let mut resp: Vec<Vec<f64>> =
+            (0..xs.len()).map(|_| dir.draw(rng)).collect();
+
+        let mut model = Self::m_step(xs, &resp, &n_cats);
+        let mut ln_lik = f64::NEG_INFINITY;
+        for _ in 0..EM_MAX_ITER {
+            let ln_lik_new = model.e_step(xs, &mut resp);
+            model = Self::m_step(xs, &resp, &n_cats);
+            let converged = ln_lik_new - ln_lik < EM_TOL;
+            ln_lik = ln_lik_new;
+            if converged {
+                break;
+            }
+        }
+        Ok(model)
+    }
+
+    /// The maximum likelihood parameters given the class responsibilities
+    fn m_step<X: CategoricalDatum>(
+        xs: &[Vec<X>],
+        resp: &[Vec<f64>],
+        n_cats: &[usize],
+    ) -> Self {
+        let n_classes = resp[0].len();
+        let n = xs.len() as f64;
+        let weights: Vec<f64> = (0..n_classes)
+            .map(|k| resp.iter().map(|r| r[k]).sum::<f64>() / n)
+            .collect();
+
+        let classes = (0..n_classes)
+            .map(|k| {
+                n_cats
+                    .iter()
+                    .enumerate()
+                    .map(|(j, &n_cat)| {
+                        // Responsibility-weighted category counts
+                        let mut counts = vec![0.0; n_cat];
+                        xs.iter().zip(resp.iter()).for_each(|(x, r)| {
+                            counts[x[j].into_usize()] += r[k];
+                        });
+                        // A class with no responsibility has no counts
+                        if counts.iter().sum::<f64>() > 0.0 {
+                            Categorical::new(&counts).unwrap()
+                        } else {
+                            Categorical::uniform(n_cat)
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        LatentClassModel {
+            mixture: Mixture::new_unchecked(weights, classes),
+        }
+    }
+
+    /// Update the class responsibilities and return the log likelihood
+    fn e_step<X: CategoricalDatum>(
+        &self,
+        xs: &[Vec<X>],
+        resp: &mut [Vec<f64>],
+    ) -> f64 {
+        xs.iter()
+            .zip(resp.iter_mut())
+            .map(|(x, r)| {
+                let ln_ps = self.ln_joint(x);
+                let ln_f = logsumexp(&ln_ps);
+                r.iter_mut()
+                    .zip(ln_ps.iter())
+                    .for_each(|(rk, lp)| *rk = (lp - ln_f).exp());
+                ln_f
+            })
+            .sum()
+    }
+
+    /// ln p(class = k, x) for each class
+    fn ln_joint<X: CategoricalDatum>(&self, x: &[X]) -> Vec<f64> {
+        self.mixture
+            .ln_weights()
+            .iter()
+            .zip(self.mixture.components().iter())
+            .map(|(ln_w, cls)| {
+                ln_w + cls
+                    .iter()
+                    .zip(x.iter())
+                    .map(|(cpnt, xj)| cpnt.ln_f(xj))
+                    .sum::<f64>()
+            })
+            .collect()
+    }
+
+    /// The number of classes
+    #[inline]
+    pub fn n_classes(&self) -> usize {
+        self.mixture.k()
+    }
+
+    /// The number of items in a response vector
+    #[inline]
+    pub fn n_items(&self) -> usize {
+        self.mixture.components()[0].len()
+    }
+
+    /// The class weights
+    #[inline]
+    pub fn weights(&self) -> &[f64] {
+        self.mixture.weights()
+    }
+
+    /// For each class, the distribution of each item
+    #[inline]
+    pub fn classes(&self) -> &[Vec<Categorical>] {
+        self.mixture.components()
+    }
+
+    /// The posterior probability of each class given the response vector `x`
+    pub fn class_posterior<X: CategoricalDatum>(&self, x: &[X]) -> Vec<f64> {
+        let ln_ps = self.ln_joint(x);
+        let ln_f = logsumexp(&ln_ps);
+        ln_ps.iter().map(|lp| (lp - ln_f).exp()).collect()
+    }
+
+    /// The log likelihood of the response vectors, `xs`
+    pub fn ln_likelihood<X: CategoricalDatum>(&self, xs: &[Vec<X>]) -> f64 {
+        xs.iter().map(|x| logsumexp(&self.ln_joint(x))).sum()
+    }
+
+    /// The number of free parameters: the class weights less one, plus, in
+    /// each class, the categories of each item less one
+    pub fn n_params(&self) -> usize {
+        let per_class: usize =
+            self.classes()[0].iter().map(|cpnt| cpnt.k() - 1).sum();
+        self.n_classes() * (per_class + 1) - 1
+    }
+
+    /// The Bayesian information criterion, `p ln n - 2 ln L`, of the model
+    /// for the response vectors, `xs`. Lower is better.
+    pub fn bic<X: CategoricalDatum>(&self, xs: &[Vec<X>]) -> f64 {
+        (self.n_params() as f64)
+            .mul_add((xs.len() as f64).ln(), -2.0 * self.ln_likelihood(xs))
+    }
+}
+
+impl<X: CategoricalDatum> Rv<Vec<X>> for LatentClassModel {
+    fn ln_f(&self, x: &Vec<X>) -> f64 {
+        logsumexp(&self.ln_joint(x))
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> Vec<X> {
+        let k = ln_pflip(self.mixture.ln_weights(), 1, true, rng)[0];
+        self.classes()[k]
+            .iter()
+            .map(|cpnt| cpnt.draw(rng))
+            .collect()
+    }
+}
+
+impl std::error::Error for LatentClassError {}
+
+impl fmt::Display for LatentClassError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mixture(err) => write!(f, "invalid classes: {}", err),
+            Self::NoData => write!(f, "no response vectors to fit to"),
+            Self::NoItems => write!(f, "response vectors have no items"),
+            Self::ZeroClasses => write!(f, "at least one class is required"),
+            Self::ItemCountMismatch {
+                ix,
+                n_items,
+                expected,
+            } => write!(
+                f,
+                "entry {} has {} items but the first has {}",
+                ix, n_items, expected
+            ),
+            Self::CategoryCountMismatch { item } => write!(
+                f,
+                "item {} has different numbers of categories in different \
+                    classes",
+                item
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const TOL: f64 = 1E-12;
+
+    fn two_class_model() -> LatentClassModel {
+        let profile = |ps: &[f64]| -> Vec<Categorical> {
+            ps.iter()
+                .map(|&p| Categorical::new(&[1.0 - p, p]).unwrap())
+                .collect()
+        };
+        LatentClassModel::new(
+            vec![0.3, 0.7],
+            vec![
+                profile(&[0.9, 0.8, 0.9, 0.1, 0.2]),
+                profile(&[0.1, 0.3, 0.2, 0.8, 0.9]),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn new_validates_classes() {
+        let cat2 = Categorical::uniform(2);
+        let cat3 = Categorical::uniform(3);
+        assert_eq!(
+            LatentClassModel::new(
+                vec![0.5, 0.5],
+                vec![vec![cat2.clone(); 2], vec![cat2.clone(); 3]]
+            ),
+            Err(LatentClassError::ItemCountMismatch {
+                ix: 1,
+                n_items: 3,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            LatentClassModel::new(
+                vec![0.5, 0.5],
+                vec![
+                    vec![cat2.clone(), cat2.clone()],
+                    vec![cat2.clone(), cat3]
+                ]
+            ),
+            Err(LatentClassError::CategoryCountMismatch { item: 1 })
+        );
+        assert!(matches!(
+            LatentClassModel::new(vec![0.5], vec![vec![cat2.clone()]; 2]),
+            Err(LatentClassError::Mixture(_))
+        ));
+    }
+
+    #[test]
+    fn ln_f_sums_over_classes() {
+        let model = two_class_model();
+        let x = vec![true, true, false, false, true];
+        let expected = model
+            .weights()
+            .iter()
+            .zip(model.classes().iter())
+            .map(|(w, cls)| {
+                w * cls
+                    .iter()
+                    .zip(x.iter())
+                    .map(|(c, xj)| c.f(xj))
+                    .product::<f64>()
+            })
+            .sum::<f64>()
+            .ln();
+        assert::close(model.ln_f(&x), expected, TOL);
+        assert::close(
+            model.ln_likelihood(std::slice::from_ref(&x)),
+            expected,
+            TOL,
+        );
+        assert::close(model.class_posterior(&x).iter().sum::<f64>(), 1.0, TOL);
+    }
+
+    #[test]
+    fn fit_recovers_generating_model() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x7);
+        let truth = two_class_model();
+        let xs: Vec<Vec<bool>> = truth.sample(3000, &mut rng);
+        let fit = LatentClassModel::fit(&xs, 2, &mut rng).unwrap();
+
+        // Match the classes up to relabeling
+        let (small, large) = if fit.weights()[0] < fit.weights()[1] {
+            (0, 1)
+        } else {
+            (1, 0)
+        };
+        assert::close(fit.weights()[small], 0.3, 0.03);
+        for (k_fit, k_true) in [(small, 0), (large, 1)] {
+            for j in 0..truth.n_items() {
+                let p_fit: f64 = fit.classes()[k_fit][j].f(&true);
+                let p_true: f64 = truth.classes()[k_true][j].f(&true);
+                assert::close(p_fit, p_true, 0.05);
+            }
+        }
+        assert!(fit.ln_likelihood(&xs) >= truth.ln_likelihood(&xs));
+    }
+
+    #[test]
+    fn bic_selects_number_of_classes() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x8);
+        let xs: Vec<Vec<bool>> = two_class_model().sample(1000, &mut rng);
+        let bics: Vec<f64> = (1..=3)
+            .map(|k| LatentClassModel::fit(&xs, k, &mut rng).unwrap().bic(&xs))
+            .collect();
+        assert!(bics[1] < bics[0]);
+        assert!(bics[1] < bics[2]);
+    }
+
+    #[test]
+    fn fit_polytomous_items() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x9);
+        let xs: Vec<Vec<usize>> = (0..300)
+            .map(|i| {
+                if i % 3 == 0 {
+                    vec![0, 2, 1]
+                } else {
+                    vec![2, 0, 3]
+                }
+            })
+            .collect();
+        let fit = LatentClassModel::fit(&xs, 2, &mut rng).unwrap();
+        let n_cats: Vec<usize> =
+            fit.classes()[0].iter().map(|c| c.k()).collect();
+        assert_eq!(n_cats, vec![3, 3, 4]);
+        // 1 weight + 2 classes x (2 + 2 + 3) item parameters
+        assert_eq!(fit.n_params(), 15);
+        assert::close(
+            fit.ln_likelihood(&xs),
+            100.0_f64
+                .mul_add((1.0_f64 / 3.0).ln(), 200.0 * (2.0_f64 / 3.0).ln()),
+            1E-6,
+        );
+    }
+
+    #[test]
+    fn fit_rejects_ragged_data() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x9);
+        let xs = vec![vec![true, false], vec![true]];
+        assert_eq!(
+            LatentClassModel::fit(&xs, 2, &mut rng),
+            Err(LatentClassError::ItemCountMismatch {
+                ix: 1,
+                n_items: 1,
+                expected: 2
+            })
+        );
+        let empty: Vec<Vec<bool>> = vec![];
+        assert_eq!(
+            LatentClassModel::fit(&empty, 2, &mut rng),
+            Err(LatentClassError::NoData)
+        );
+    }
+}
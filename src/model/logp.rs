@@ -0,0 +1,164 @@
+//! Accumulation of log-likelihood contributions
+use crate::traits::Rv;
+
+/// One log-likelihood contribution recorded by a tracing
+/// [`LogpAccumulator`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogpTerm {
+    /// What produced the term, e.g., the name of a model node
+    pub source: String,
+    /// The index of the datum within its source
+    pub ix: usize,
+    /// The log-likelihood contribution
+    pub logp: f64,
+}
+
+/// Accumulates per-observation log-likelihood contributions
+///
+/// With tracing on, every contribution is kept along with where it came
+/// from, so the observations that drive a bad fit can be found after the
+/// fact. Without tracing, only the total and count are kept.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::Gaussian;
+/// use rv::model::LogpAccumulator;
+/// use rv::traits::*;
+///
+/// let g = Gaussian::standard();
+/// let xs: Vec<f64> = vec![0.1, -0.4, 6.0, 0.9];
+///
+/// let mut acc = LogpAccumulator::tracing();
+/// acc.add_ln_f("x", &g, &xs);
+///
+/// let total: f64 = xs.iter().map(|x| g.ln_f(x)).sum();
+/// assert::close(acc.total(), total, 1E-12);
+///
+/// // The outlier contributes the least
+/// let worst = acc.worst(1);
+/// assert_eq!(worst[0].ix, 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LogpAccumulator {
+    total: f64,
+    n: usize,
+    trace: Option<Vec<LogpTerm>>,
+}
+
+impl LogpAccumulator {
+    /// Create an accumulator that keeps only the total
+    pub fn new() -> Self {
+        LogpAccumulator {
+            total: 0.0,
+            n: 0,
+            trace: None,
+        }
+    }
+
+    /// Create an accumulator that records every contribution
+    pub fn tracing() -> Self {
+        LogpAccumulator {
+            total: 0.0,
+            n: 0,
+            trace: Some(Vec::new()),
+        }
+    }
+
+    /// Whether contributions are being recorded
+    #[inline]
+    pub fn is_tracing(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    /// Add the contribution of datum `ix` from `source`
+    pub fn push(&mut self, source: &str, ix: usize, logp: f64) {
+        self.total += logp;
+        self.n += 1;
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(LogpTerm {
+                source: source.to_owned(),
+                ix,
+                logp,
+            });
+        }
+    }
+
+    /// Add `fx.ln_f(x)` for every `x` in `xs`, indexed by position
+    pub fn add_ln_f<X, Fx: Rv<X>>(&mut self, source: &str, fx: &Fx, xs: &[X]) {
+        xs.iter()
+            .enumerate()
+            .for_each(|(ix, x)| self.push(source, ix, fx.ln_f(x)));
+    }
+
+    /// The sum of the contributions
+    #[inline]
+    pub fn total(&self) -> f64 {
+        self.total
+    }
+
+    /// The number of contributions
+    #[inline]
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// The recorded contributions in the order they were added, if tracing
+    pub fn trace(&self) -> Option<&[LogpTerm]> {
+        self.trace.as_deref()
+    }
+
+    /// Up to `k` recorded contributions with the lowest log-likelihood,
+    /// lowest first. Empty if not tracing.
+    pub fn worst(&self, k: usize) -> Vec<&LogpTerm> {
+        let mut terms: Vec<&LogpTerm> =
+            self.trace.iter().flat_map(|trace| trace.iter()).collect();
+        terms.sort_by(|a, b| a.logp.total_cmp(&b.logp));
+        terms.truncate(k);
+        terms
+    }
+
+    /// Clear the total, count, and trace, keeping the tracing setting
+    pub fn reset(&mut self) {
+        self.total = 0.0;
+        self.n = 0;
+        if let Some(trace) = self.trace.as_mut() {
+            trace.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::Poisson;
+
+    #[test]
+    fn untraced_keeps_only_total() {
+        let mut acc = LogpAccumulator::new();
+        acc.push("a", 0, -1.0);
+        acc.push("b", 0, -2.5);
+        assert_eq!(acc.total(), -3.5);
+        assert_eq!(acc.n(), 2);
+        assert!(acc.trace().is_none());
+        assert!(acc.worst(3).is_empty());
+    }
+
+    #[test]
+    fn worst_finds_lowest_terms() {
+        let pois = Poisson::new(2.0).unwrap();
+        let xs: Vec<u32> = vec![2, 1, 12, 3, 0, 9];
+        let mut acc = LogpAccumulator::tracing();
+        acc.add_ln_f("count", &pois, &xs);
+
+        let worst: Vec<usize> = acc.worst(2).iter().map(|t| t.ix).collect();
+        assert_eq!(worst, vec![2, 5]);
+        assert_eq!(acc.trace().unwrap().len(), 6);
+        assert!(acc.worst(10).len() == 6);
+
+        acc.reset();
+        assert_eq!(acc.n(), 0);
+        assert!(acc.is_tracing());
+        assert!(acc.trace().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,354 @@
+//! Compact, versioned binary format for saving and loading models
+//!
+//! Anything that implements serde's `Serialize` and `Deserialize` with the
+//! `serde1` feature -- distributions, sufficient statistics, Gaussian
+//! processes (including their Cholesky factors), and chains of samples -- can
+//! be written with [`to_bytes`] or [`save`] and read back with [`from_bytes`]
+//! or [`load`].
+//!
+//! # Format
+//!
+//! Every payload starts with a header:
+//!
+//! | bytes | contents                                            |
+//! |-------|-----------------------------------------------------|
+//! | 0..4  | the magic bytes `RVBN`                              |
+//! | 4..6  | the format version as a little-endian `u16`         |
+//! | 6..   | the version of rv that wrote the payload, as a string |
+//!
+//! followed by the value encoded with [bincode](https://crates.io/crates/bincode)
+//! using variable-length integers. Loading fails with
+//! [`PersistError::UnsupportedVersion`] if the payload was written in a newer
+//! format than this version of rv understands, rather than misreading it.
+//!
+//! Lazily computed caches are not stored. They are recomputed on first use
+//! after loading.
+//!
+//! # Example
+//!
+//! ```
+//! use rv::dist::{Gaussian, Mixture};
+//! use rv::persist;
+//!
+//! let mm = Mixture::new(
+//!     vec![0.3, 0.7],
+//!     vec![Gaussian::standard(), Gaussian::new(2.0, 0.5).unwrap()],
+//! )
+//! .unwrap();
+//!
+//! let bytes = persist::to_bytes(&mm).unwrap();
+//! let header = persist::read_header(&bytes).unwrap();
+//! assert_eq!(header.format_version, persist::FORMAT_VERSION);
+//!
+//! let loaded: Mixture<Gaussian> = persist::from_bytes(&bytes).unwrap();
+//! assert_eq!(loaded, mm);
+//! ```
+use bincode::Options;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{Read, Write};
+
+/// The magic bytes at the start of every payload
+pub const MAGIC: [u8; 4] = *b"RVBN";
+
+/// The format version written by this version of rv
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Errors from saving and loading
+#[derive(Debug)]
+pub enum PersistError {
+    /// Error reading or writing
+    Io(std::io::Error),
+    /// The payload does not start with [`MAGIC`]
+    BadMagic,
+    /// The payload was written in a format newer than [`FORMAT_VERSION`]
+    UnsupportedVersion {
+        /// The format version of the payload
+        found: u16,
+        /// The newest format version this version of rv can read
+        supported: u16,
+    },
+    /// The value could not be encoded or decoded
+    Encoding(bincode::Error),
+}
+
+impl std::error::Error for PersistError {}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "io error: {}", err),
+            Self::BadMagic => write!(f, "not an rv binary payload"),
+            Self::UnsupportedVersion { found, supported } => write!(
+                f,
+                "payload format version {} is newer than the newest \
+                 supported version, {}",
+                found, supported
+            ),
+            Self::Encoding(err) => write!(f, "encoding error: {}", err),
+        }
+    }
+}
+
+impl From<std::io::Error> for PersistError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<bincode::Error> for PersistError {
+    fn from(err: bincode::Error) -> Self {
+        Self::Encoding(err)
+    }
+}
+
+/// The header of a payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// The format version of the payload
+    pub format_version: u16,
+    /// The version of rv that wrote the payload
+    pub crate_version: String,
+}
+
+#[derive(Serialize)]
+struct PayloadRef<'a, T: ?Sized> {
+    crate_version: &'a str,
+    value: &'a T,
+}
+
+#[derive(Deserialize)]
+struct Payload<T> {
+    // Decoded by `read_header`
+    #[allow(dead_code)]
+    crate_version: String,
+    value: T,
+}
+
+// The encoding is part of the format, so it is pinned here rather than
+// relying on the bincode defaults, which differ between functions.
+fn options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_varint_encoding()
+        .with_little_endian()
+        .reject_trailing_bytes()
+}
+
+fn write_preamble<W: Write>(writer: &mut W) -> Result<(), PersistError> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    Ok(())
+}
+
+fn read_preamble<R: Read>(reader: &mut R) -> Result<u16, PersistError> {
+    let mut magic = [0_u8; 4];
+    reader.read_exact(&mut magic).map_err(|err| {
+        if err.kind() == std::io::ErrorKind::UnexpectedEof {
+            PersistError::BadMagic
+        } else {
+            PersistError::Io(err)
+        }
+    })?;
+    if magic != MAGIC {
+        return Err(PersistError::BadMagic);
+    }
+
+    let mut version = [0_u8; 2];
+    reader.read_exact(&mut version)?;
+    let format_version = u16::from_le_bytes(version);
+    if format_version > FORMAT_VERSION {
+        Err(PersistError::UnsupportedVersion {
+            found: format_version,
+            supported: FORMAT_VERSION,
+        })
+    } else {
+        Ok(format_version)
+    }
+}
+
+/// Encode `value`, with a header, to bytes
+pub fn to_bytes<T>(value: &T) -> Result<Vec<u8>, PersistError>
+where
+    T: Serialize + ?Sized,
+{
+    let mut bytes = Vec::new();
+    save(value, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Decode a value from bytes written by [`to_bytes`] or [`save`]
+pub fn from_bytes<T>(mut bytes: &[u8]) -> Result<T, PersistError>
+where
+    T: DeserializeOwned,
+{
+    read_preamble(&mut bytes)?;
+    let payload: Payload<T> = options().deserialize(bytes)?;
+    Ok(payload.value)
+}
+
+/// Write `value`, with a header, to `writer`
+pub fn save<T, W>(value: &T, mut writer: W) -> Result<(), PersistError>
+where
+    T: Serialize + ?Sized,
+    W: Write,
+{
+    write_preamble(&mut writer)?;
+    let payload = PayloadRef {
+        crate_version: env!("CARGO_PKG_VERSION"),
+        value,
+    };
+    options().serialize_into(writer, &payload)?;
+    Ok(())
+}
+
+/// Read a value written by [`to_bytes`] or [`save`] from `reader`
+///
+/// The reader must contain exactly one payload; it is read to the end.
+pub fn load<T, R>(mut reader: R) -> Result<T, PersistError>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    from_bytes(&bytes)
+}
+
+/// Read the header of a payload without decoding the value
+pub fn read_header(mut bytes: &[u8]) -> Result<Header, PersistError> {
+    let format_version = read_preamble(&mut bytes)?;
+    // The crate version is the first field of the payload, so it can be
+    // decoded on its own. Trailing bytes are the value.
+    let crate_version: String = bincode::DefaultOptions::new()
+        .with_varint_encoding()
+        .with_little_endian()
+        .allow_trailing_bytes()
+        .deserialize(bytes)?;
+    Ok(Header {
+        format_version,
+        crate_version,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::GaussianSuffStat;
+    use crate::dist::{Categorical, Gaussian, NormalInvGamma};
+    use crate::traits::{Rv, SuffStat};
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    #[test]
+    fn distributions_round_trip() {
+        let gauss = Gaussian::new(1.5, 0.3).unwrap();
+        // Fill the cache; it must not be stored
+        let ln_f = gauss.ln_f(&0.5_f64);
+        let loaded: Gaussian = from_bytes(&to_bytes(&gauss).unwrap()).unwrap();
+        assert_eq!(loaded, gauss);
+        assert_eq!(loaded.ln_f(&0.5_f64), ln_f);
+
+        let nig = NormalInvGamma::new(0.1, 1.2, 2.3, 3.4).unwrap();
+        let loaded: NormalInvGamma =
+            from_bytes(&to_bytes(&nig).unwrap()).unwrap();
+        assert_eq!(loaded, nig);
+
+        let cat = Categorical::new(&[0.1, 0.2, 0.7]).unwrap();
+        let loaded: Categorical = from_bytes(&to_bytes(&cat).unwrap()).unwrap();
+        assert_eq!(loaded, cat);
+    }
+
+    #[test]
+    fn suffstats_and_chains_round_trip() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xB1);
+        let chain: Vec<f64> = Gaussian::standard().sample(1_000, &mut rng);
+
+        let mut stat = GaussianSuffStat::new();
+        stat.observe_many(&chain);
+        let loaded: GaussianSuffStat =
+            from_bytes(&to_bytes(&stat).unwrap()).unwrap();
+        assert_eq!(loaded, stat);
+
+        let mut bytes = Vec::new();
+        save(&chain, &mut bytes).unwrap();
+        let loaded: Vec<f64> = load(bytes.as_slice()).unwrap();
+        assert_eq!(loaded, chain);
+
+        // The header and length prefix are small next to the values
+        assert!(bytes.len() < 8 * chain.len() + 32);
+    }
+
+    #[test]
+    fn header_records_versions() {
+        let bytes = to_bytes(&Gaussian::standard()).unwrap();
+        assert_eq!(&bytes[..4], &MAGIC);
+        let header = read_header(&bytes).unwrap();
+        assert_eq!(header.format_version, FORMAT_VERSION);
+        assert_eq!(header.crate_version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn rejects_newer_format_versions() {
+        let mut bytes = to_bytes(&Gaussian::standard()).unwrap();
+        bytes[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        let res: Result<Gaussian, _> = from_bytes(&bytes);
+        assert!(matches!(
+            res,
+            Err(PersistError::UnsupportedVersion { found, supported })
+                if found == FORMAT_VERSION + 1 && supported == FORMAT_VERSION
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_payloads() {
+        let res: Result<Gaussian, _> = from_bytes(b"{\"mu\": 0.0}");
+        assert!(matches!(res, Err(PersistError::BadMagic)));
+
+        let res: Result<Gaussian, _> = from_bytes(b"RV");
+        assert!(matches!(res, Err(PersistError::BadMagic)));
+
+        let bytes = to_bytes(&Gaussian::standard()).unwrap();
+        let res: Result<Gaussian, _> = from_bytes(&bytes[..bytes.len() - 1]);
+        assert!(matches!(res, Err(PersistError::Encoding(_))));
+
+        let mut bytes = bytes;
+        bytes.push(0);
+        let res: Result<Gaussian, _> = from_bytes(&bytes);
+        assert!(matches!(res, Err(PersistError::Encoding(_))));
+    }
+
+    #[cfg(feature = "process")]
+    #[test]
+    fn gaussian_process_round_trips() {
+        use crate::process::gaussian::kernel::RBFKernel;
+        use crate::process::gaussian::{GaussianProcess, NoiseModel};
+        use crate::process::RandomProcess;
+        use nalgebra::{DMatrix, DVector};
+
+        let x_train =
+            DMatrix::from_column_slice(5, 1, &[-4.0, -3.0, -2.0, -1.0, 1.0]);
+        let y_train: DVector<f64> =
+            x_train.map(|x: f64| x.sin()).column(0).into();
+        let gp = GaussianProcess::train(
+            RBFKernel::default(),
+            x_train,
+            y_train,
+            NoiseModel::default(),
+        )
+        .unwrap();
+
+        let loaded: GaussianProcess<RBFKernel> =
+            from_bytes(&to_bytes(&gp).unwrap()).unwrap();
+        assert_eq!(loaded.k_chol().l(), gp.k_chol().l());
+        assert_eq!(loaded.k_inv(), gp.k_inv());
+
+        let xs: Vec<DVector<f64>> = [-2.5, 0.0, 2.5]
+            .iter()
+            .map(|&x| DVector::from_element(1, x))
+            .collect();
+        let expected = gp.sample_function(&xs);
+        let actual = loaded.sample_function(&xs);
+        assert_eq!(actual.std(), expected.std());
+    }
+}
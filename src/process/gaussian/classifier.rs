@@ -0,0 +1,558 @@
+//! Binary Gaussian process classification with the Laplace approximation
+use argmin::solver::{linesearch::MoreThuenteLineSearch, quasinewton::LBFGS};
+use nalgebra::linalg::Cholesky;
+use nalgebra::{DMatrix, DVector, Dyn};
+use rand::Rng;
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use super::kernel::{Kernel, KernelError};
+use super::GaussianProcessError;
+use crate::dist::MvGaussian;
+use crate::misc::gauss_hermite_expectation;
+use crate::process::{RandomProcess, RandomProcessMle};
+
+/// Maximum number of Newton iterations used to find the posterior mode
+const NEWTON_MAX_ITER: usize = 100;
+/// Stop Newton's method when the objective improves by less than this
+const NEWTON_TOL: f64 = 1E-10;
+/// Maximum number of times a Newton step is halved to improve the objective
+const MAX_STEP_HALVINGS: usize = 20;
+/// Number of Gauss-Hermite points used to average the class probability
+/// over the latent posterior
+const N_QUAD: usize = 32;
+
+/// 1 / (1 + e^-z)
+#[inline]
+fn logistic(z: f64) -> f64 {
+    if z >= 0.0 {
+        (1.0 + (-z).exp()).recip()
+    } else {
+        let ez = z.exp();
+        ez / (1.0 + ez)
+    }
+}
+
+/// ln(1 / (1 + e^-z))
+#[inline]
+fn ln_logistic(z: f64) -> f64 {
+    if z >= 0.0 {
+        -(-z).exp().ln_1p()
+    } else {
+        z - z.exp().ln_1p()
+    }
+}
+
+/// The Laplace approximation to the posterior of the latent function at
+/// the training inputs
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+struct Laplace {
+    /// Posterior mode of the latent function, f̂
+    f_mode: DVector<f64>,
+    /// ∇ ln p(y | f̂), which equals K⁻¹ f̂
+    grad: DVector<f64>,
+    /// Probability of the positive class at the mode, σ(f̂)
+    pi: DVector<f64>,
+    /// √W, where W = -∇∇ ln p(y | f̂) is diagonal
+    w_sqrt: DVector<f64>,
+    /// Cholesky decomposition of B = I + √W K √W
+    b_chol: Cholesky<f64, Dyn>,
+    /// Approximate log marginal likelihood
+    ln_m: f64,
+}
+
+impl Laplace {
+    /// Find the posterior mode by Newton's method (GPML Algorithm 3.1) for
+    /// prior covariance `k` and labels `y` in {-1, 1}
+    fn fit(
+        k: &DMatrix<f64>,
+        y: &DVector<f64>,
+    ) -> Result<Self, GaussianProcessError> {
+        let n = y.len();
+        let objective = |a: &DVector<f64>, f: &DVector<f64>| {
+            let ln_lik: f64 = y
+                .iter()
+                .zip(f.iter())
+                .map(|(yi, fi)| ln_logistic(yi * fi))
+                .sum();
+            (-0.5_f64).mul_add(a.dot(f), ln_lik)
+        };
+
+        let mut a = DVector::zeros(n);
+        let mut f = DVector::zeros(n);
+        let mut psi = objective(&a, &f);
+        for _ in 0..NEWTON_MAX_ITER {
+            let (pi, grad, w_sqrt, b_chol) = Self::derivatives(k, y, &f)?;
+            let w = pi.map(|p| p * (1.0 - p));
+            let b = w.component_mul(&f) + grad;
+            let kb = k * &b;
+            let mut a_new = &b
+                - w_sqrt
+                    .component_mul(&b_chol.solve(&w_sqrt.component_mul(&kb)));
+            let mut f_new = k * &a_new;
+            let mut psi_new = objective(&a_new, &f_new);
+
+            // The objective is concave, so a short enough step improves it
+            for _ in 0..MAX_STEP_HALVINGS {
+                if psi_new >= psi {
+                    break;
+                }
+                a_new = (&a + a_new) * 0.5;
+                f_new = k * &a_new;
+                psi_new = objective(&a_new, &f_new);
+            }
+
+            let converged = psi_new - psi < NEWTON_TOL;
+            if psi_new >= psi {
+                a = a_new;
+                f = f_new;
+                psi = psi_new;
+            }
+            if converged {
+                break;
+            }
+        }
+
+        let (pi, grad, w_sqrt, b_chol) = Self::derivatives(k, y, &f)?;
+        let half_ln_det_b: f64 = b_chol.l_dirty().diagonal().map(f64::ln).sum();
+        Ok(Laplace {
+            f_mode: f,
+            grad,
+            pi,
+            w_sqrt,
+            b_chol,
+            ln_m: psi - half_ln_det_b,
+        })
+    }
+
+    /// σ(f), ∇ ln p(y | f), √W, and the Cholesky factor of B at `f`
+    #[allow(clippy::type_complexity)]
+    fn derivatives(
+        k: &DMatrix<f64>,
+        y: &DVector<f64>,
+        f: &DVector<f64>,
+    ) -> Result<
+        (DVector<f64>, DVector<f64>, DVector<f64>, Cholesky<f64, Dyn>),
+        GaussianProcessError,
+    > {
+        let pi = f.map(logistic);
+        let grad = y.zip_map(&pi, |yi, p| 0.5_f64.mul_add(yi + 1.0, -p));
+        let w_sqrt = pi.map(|p| (p * (1.0 - p)).sqrt());
+        let mut b = k.clone();
+        for i in 0..b.nrows() {
+            for j in 0..b.ncols() {
+                b[(i, j)] *= w_sqrt[i] * w_sqrt[j];
+            }
+            b[(i, i)] += 1.0;
+        }
+        let b_chol = Cholesky::new(b)
+            .ok_or(GaussianProcessError::NotPositiveSemiDefinite)?;
+        Ok((pi, grad, w_sqrt, b_chol))
+    }
+
+    /// L⁻¹ √W k_*, for the cross covariances, `k_star`, of the training and
+    /// test inputs
+    fn whiten(&self, k_star: &DMatrix<f64>) -> DMatrix<f64> {
+        let mut v = k_star.clone();
+        v.row_iter_mut()
+            .zip(self.w_sqrt.iter())
+            .for_each(|(mut row, w)| row *= *w);
+        self.b_chol.l_dirty().solve_lower_triangular_mut(&mut v);
+        v
+    }
+}
+
+/// Gaussian process classifier for binary labels
+///
+/// The label of each input is `true` with probability σ(f(x)), where σ is
+/// the logistic function and `f ~ GP(0, k)` is a latent function. The
+/// posterior of `f` is approximated by a Gaussian at its mode (the Laplace
+/// approximation, GPML §3.4), which also gives an approximate marginal
+/// likelihood for choosing kernel hyperparameters with
+/// [`RandomProcessMle::optimize`].
+///
+/// If the classes are perfectly separable, the marginal likelihood keeps
+/// increasing with the scale of the kernel, so optimization will inflate
+/// the latent variance.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::DMatrix;
+/// use rv::process::gaussian::kernel::{ConstantKernel, RBFKernel};
+/// use rv::process::gaussian::GaussianProcessClassifier;
+/// use rv::process::RandomProcessMle;
+///
+/// let xs = DMatrix::from_fn(40, 1, |i, _| i as f64 / 4.0);
+/// // Follow the sign of sin(x), with every fifth label flipped
+/// let labels: Vec<bool> = xs
+///     .column(0)
+///     .iter()
+///     .enumerate()
+///     .map(|(i, x)| (x.sin() > 0.0) != (i % 5 == 2))
+///     .collect();
+///
+/// let kernel = ConstantKernel::new(4.0).unwrap() * RBFKernel::new(1.0).unwrap();
+/// let gpc = GaussianProcessClassifier::train(kernel, xs, &labels).unwrap();
+///
+/// let mut rng = rand::thread_rng();
+/// let gpc = gpc.optimize(100, 2, &mut rng).unwrap();
+///
+/// let x_test = DMatrix::from_column_slice(2, 1, &[1.5, 4.5]);
+/// let p = gpc.predict_proba(&x_test);
+/// assert!(p[0] > 0.5);
+/// assert!(p[1] < 0.5);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct GaussianProcessClassifier<K>
+where
+    K: Kernel,
+{
+    /// Covariance Kernel
+    kernel: K,
+    /// x values used in training
+    x_train: DMatrix<f64>,
+    /// Labels used in training, encoded as -1 and 1
+    y_train: DVector<f64>,
+    /// Laplace approximation at the training inputs
+    laplace: Laplace,
+}
+
+impl<K> GaussianProcessClassifier<K>
+where
+    K: Kernel,
+{
+    /// Train a Gaussian process classifier on the given data points
+    ///
+    /// # Arguments
+    /// * `kernel` - Kernel of the latent function
+    /// * `x_train` - Inputs, one per row
+    /// * `labels` - The label of each input
+    pub fn train(
+        kernel: K,
+        x_train: DMatrix<f64>,
+        labels: &[bool],
+    ) -> Result<Self, GaussianProcessError> {
+        if labels.len() != x_train.nrows() {
+            return Err(GaussianProcessError::MisshapenNoiseModel(format!(
+                "labels must have one value per row of x_train \
+                     (expected: {}, got: {})",
+                x_train.nrows(),
+                labels.len()
+            )));
+        }
+        let y_train = DVector::from_iterator(
+            labels.len(),
+            labels.iter().map(|&l| if l { 1.0 } else { -1.0 }),
+        );
+        Self::train_signed(kernel, x_train, y_train)
+    }
+
+    fn train_signed(
+        kernel: K,
+        x_train: DMatrix<f64>,
+        y_train: DVector<f64>,
+    ) -> Result<Self, GaussianProcessError> {
+        let k = kernel.covariance(&x_train, &x_train);
+        let laplace = Laplace::fit(&k, &y_train)?;
+        Ok(GaussianProcessClassifier {
+            kernel,
+            x_train,
+            y_train,
+            laplace,
+        })
+    }
+
+    /// Return the kernel being used in this GP
+    pub fn kernel(&self) -> &K {
+        &self.kernel
+    }
+
+    /// The posterior mode of the latent function at the training inputs
+    pub fn latent_mode(&self) -> &DVector<f64> {
+        &self.laplace.f_mode
+    }
+
+    /// Approximate posterior mean and variance of the latent function at the
+    /// rows of `xs` (GPML Algorithm 3.2)
+    pub fn predict_latent(
+        &self,
+        xs: &DMatrix<f64>,
+    ) -> (DVector<f64>, DVector<f64>) {
+        let k_star = self.kernel.covariance(&self.x_train, xs);
+        let mean = k_star.transpose() * &self.laplace.grad;
+        let k_diag = self.kernel.diag(xs);
+        let v = self.laplace.whiten(&k_star);
+        let var = DVector::from_fn(xs.nrows(), |j, _| {
+            (k_diag[j] - v.column(j).norm_squared()).max(0.0)
+        });
+        (mean, var)
+    }
+
+    /// The probability that the label at each row of `xs` is `true`,
+    /// averaged over the approximate posterior of the latent function
+    pub fn predict_proba(&self, xs: &DMatrix<f64>) -> DVector<f64> {
+        let (mean, var) = self.predict_latent(xs);
+        mean.zip_map(&var, |m, v| {
+            gauss_hermite_expectation(logistic, m, v.sqrt(), N_QUAD)
+        })
+    }
+
+    /// The more probable label at each row of `xs`
+    pub fn predict(&self, xs: &DMatrix<f64>) -> Vec<bool> {
+        // The averaged probability exceeds one half exactly when the latent
+        // mean is positive
+        let (mean, _) = self.predict_latent(xs);
+        mean.iter().map(|&m| m > 0.0).collect()
+    }
+}
+
+impl<K> RandomProcess<f64> for GaussianProcessClassifier<K>
+where
+    K: Kernel,
+{
+    type Index = DVector<f64>;
+    type SampleFunction = MvGaussian;
+    type Error = GaussianProcessError;
+
+    /// The approximate joint posterior of the latent function at `indices`
+    fn sample_function(&self, indices: &[Self::Index]) -> MvGaussian {
+        let m = indices.first().map_or(0, |x| x.len());
+        let xs = DMatrix::from_row_iterator(
+            indices.len(),
+            m,
+            indices.iter().flat_map(|x| x.iter().copied()),
+        );
+        let k_star = self.kernel.covariance(&self.x_train, &xs);
+        let mean = k_star.transpose() * &self.laplace.grad;
+        let v = self.laplace.whiten(&k_star);
+        let cov = self.kernel.covariance(&xs, &xs) - v.transpose() * v;
+        MvGaussian::new_unchecked(mean, cov)
+    }
+
+    fn ln_m(&self) -> f64 {
+        self.laplace.ln_m
+    }
+
+    fn ln_m_with_params(
+        &self,
+        parameters: &DVector<f64>,
+    ) -> Result<(f64, DVector<f64>), GaussianProcessError> {
+        let kernel = self
+            .kernel
+            .reparameterize(&parameters.iter().copied().collect::<Vec<f64>>())
+            .map_err(GaussianProcessError::KernelError)?;
+        let (k, k_grad) = kernel
+            .covariance_with_gradient(&self.x_train)
+            .map_err(|e| GaussianProcessError::KernelError(e.into()))?;
+        let lap = Laplace::fit(&k, &self.y_train)?;
+
+        // GPML Algorithm 5.1
+        let n = k.nrows();
+        let sw = &lap.w_sqrt;
+        // R = √W B⁻¹ √W
+        let mut r = lap.b_chol.solve(&DMatrix::from_diagonal(sw));
+        r.row_iter_mut()
+            .zip(sw.iter())
+            .for_each(|(mut row, w)| row *= *w);
+        // Diagonal of the posterior covariance of f at the training inputs
+        let post_var = DVector::from_fn(n, |i, _| {
+            k[(i, i)] - k.column(i).dot(&(&r * k.column(i)))
+        });
+        // Derivative of the approximate ln_m with respect to f̂ through
+        // -½ ln|B|, which is -½ diag(Σ) ∂W/∂f̂ with ∂W/∂f̂ = π(1 - π)(1 - 2π)
+        let s2 = post_var.zip_map(&lap.pi, |v, p| {
+            -0.5 * v * p * (1.0 - p) * 2.0_f64.mul_add(-p, 1.0)
+        });
+
+        let grad: Vec<f64> = (0..parameters.len())
+            .map(|j| {
+                let c = &k_grad[j];
+                let s1 = 0.5_f64.mul_add(
+                    lap.grad.dot(&(c * &lap.grad)),
+                    -0.5 * r.component_mul(c).sum(),
+                );
+                let b = c * &lap.grad;
+                let s3 = &b - &k * (&r * &b);
+                s1 + s2.dot(&s3)
+            })
+            .collect();
+
+        Ok((lap.ln_m, DVector::from(grad)))
+    }
+
+    fn parameters(&self) -> DVector<f64> {
+        self.kernel.parameters()
+    }
+
+    fn set_parameters(
+        self,
+        parameters: &DVector<f64>,
+    ) -> Result<Self, GaussianProcessError> {
+        let (kernel, leftovers) = self
+            .kernel
+            .consume_parameters(parameters.iter().copied())
+            .map_err(GaussianProcessError::KernelError)?;
+        let leftovers: Vec<f64> = leftovers.collect();
+        if !leftovers.is_empty() {
+            return Err(GaussianProcessError::KernelError(
+                KernelError::ExtraniousParameters(leftovers.len()),
+            ));
+        }
+        Self::train_signed(kernel, self.x_train, self.y_train)
+    }
+}
+
+impl<K> RandomProcessMle<f64> for GaussianProcessClassifier<K>
+where
+    K: Kernel,
+{
+    type Solver = LBFGS<
+        MoreThuenteLineSearch<DVector<f64>, DVector<f64>, f64>,
+        DVector<f64>,
+        DVector<f64>,
+        f64,
+    >;
+
+    fn generate_solver() -> Self::Solver {
+        LBFGS::new(MoreThuenteLineSearch::new(), 10)
+    }
+
+    fn random_params<R: Rng>(&self, rng: &mut R) -> DVector<f64> {
+        let n = self.parameters().len();
+        DVector::from_iterator(n, (0..n).map(|_| rng.gen_range(-5.0..5.0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::gaussian::kernel::{
+        ConstantKernel, ProductKernel, RBFKernel,
+    };
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    fn data() -> (DMatrix<f64>, Vec<bool>) {
+        let xs = DMatrix::from_column_slice(
+            10,
+            1,
+            &[-3.0, -2.4, -1.9, -1.1, -0.3, 0.2, 0.9, 1.4, 2.2, 3.1],
+        );
+        let labels = vec![
+            false, false, true, false, false, true, true, false, true, true,
+        ];
+        (xs, labels)
+    }
+
+    fn kernel() -> ProductKernel<ConstantKernel, RBFKernel> {
+        ConstantKernel::new(2.0).unwrap() * RBFKernel::new(1.5).unwrap()
+    }
+
+    #[test]
+    fn train_rejects_mismatched_labels() {
+        let (xs, labels) = data();
+        assert!(matches!(
+            GaussianProcessClassifier::train(kernel(), xs, &labels[1..]),
+            Err(GaussianProcessError::MisshapenNoiseModel(_))
+        ));
+    }
+
+    #[test]
+    fn mode_is_self_consistent() {
+        // At the mode, f̂ = K ∇ ln p(y | f̂)
+        let (xs, labels) = data();
+        let gpc =
+            GaussianProcessClassifier::train(kernel(), xs.clone(), &labels)
+                .unwrap();
+        let k = gpc.kernel().covariance(&xs, &xs);
+        let f = &k * &gpc.laplace.grad;
+        assert::close(gpc.latent_mode().as_slice(), f.as_slice(), 1E-6);
+    }
+
+    #[test]
+    fn latent_prediction_at_training_inputs_is_mode() {
+        let (xs, labels) = data();
+        let gpc =
+            GaussianProcessClassifier::train(kernel(), xs.clone(), &labels)
+                .unwrap();
+        let (mean, var) = gpc.predict_latent(&xs);
+        assert::close(mean.as_slice(), gpc.latent_mode().as_slice(), 1E-6);
+        assert!(var.iter().all(|&v| v > 0.0 && v < 2.0));
+    }
+
+    #[test]
+    fn flipping_labels_negates_latent_mean() {
+        let (xs, labels) = data();
+        let flipped: Vec<bool> = labels.iter().map(|l| !l).collect();
+        let gpc =
+            GaussianProcessClassifier::train(kernel(), xs.clone(), &labels)
+                .unwrap();
+        let gpc_flip =
+            GaussianProcessClassifier::train(kernel(), xs.clone(), &flipped)
+                .unwrap();
+        let x_test = DMatrix::from_column_slice(3, 1, &[-2.0, 0.5, 4.0]);
+        let p = gpc.predict_proba(&x_test);
+        let p_flip = gpc_flip.predict_proba(&x_test);
+        assert::close(p.as_slice(), p_flip.map(|q| 1.0 - q).as_slice(), 1E-8);
+        assert::close(gpc.ln_m(), gpc_flip.ln_m(), 1E-10);
+    }
+
+    #[test]
+    fn ln_m_with_params_matches_ln_m() {
+        let (xs, labels) = data();
+        let gpc =
+            GaussianProcessClassifier::train(kernel(), xs, &labels).unwrap();
+        let (ln_m, _) = gpc.ln_m_with_params(&gpc.parameters()).unwrap();
+        assert::close(ln_m, gpc.ln_m(), 1E-10);
+    }
+
+    #[test]
+    fn ln_m_gradient_matches_finite_differences() {
+        let (xs, labels) = data();
+        let gpc =
+            GaussianProcessClassifier::train(kernel(), xs, &labels).unwrap();
+        let params = gpc.parameters();
+        let (_, grad) = gpc.ln_m_with_params(&params).unwrap();
+
+        let h = 1E-5;
+        for j in 0..params.len() {
+            let mut hi = params.clone();
+            let mut lo = params.clone();
+            hi[j] += h;
+            lo[j] -= h;
+            let fd = (gpc.ln_m_with_params(&hi).unwrap().0
+                - gpc.ln_m_with_params(&lo).unwrap().0)
+                / (2.0 * h);
+            assert::close(grad[j], fd, 1E-5);
+        }
+    }
+
+    #[test]
+    fn optimize_improves_ln_m_with_noisy_labels() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xABCD);
+        let xs = DMatrix::from_fn(40, 1, |i, _| i as f64 / 4.0 - 5.0);
+        let labels: Vec<bool> = xs
+            .column(0)
+            .iter()
+            .map(|&x| rng.gen::<f64>() < logistic(x))
+            .collect();
+        let gpc =
+            GaussianProcessClassifier::train(kernel(), xs, &labels).unwrap();
+        let ln_m = gpc.ln_m();
+
+        let gpc = gpc.optimize(100, 3, &mut rng).unwrap();
+        assert!(gpc.ln_m() >= ln_m);
+
+        let x_test = DMatrix::from_column_slice(4, 1, &[-4.0, -1.5, 1.5, 4.0]);
+        assert_eq!(gpc.predict(&x_test), vec![false, false, true, true]);
+        let p = gpc.predict_proba(&x_test);
+        assert!(p[0] < p[1] && p[1] < 0.5);
+        assert!(p[2] > 0.5 && p[3] > 0.5);
+    }
+}
@@ -0,0 +1,421 @@
+//! Heteroscedastic Gaussian process regression with a latent log-noise GP
+use argmin::argmin_error;
+use argmin::core::{CostFunction, Executor, Gradient};
+use argmin::solver::{linesearch::MoreThuenteLineSearch, quasinewton::LBFGS};
+use nalgebra::linalg::Cholesky;
+use nalgebra::{DMatrix, DVector};
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use super::kernel::Kernel;
+use super::{GaussianProcess, GaussianProcessError, NoiseModel};
+use crate::consts::HALF_LN_2PI;
+
+/// Jitter, relative to the largest prior variance, added to the diagonal of
+/// the noise GP covariance
+const NOISE_JITTER: f64 = 1E-8;
+
+/// Gaussian process whose observation noise varies with the input
+///
+/// The observations are modeled as
+///
+/// ```math
+/// y_i = f(x_i) + \epsilon_i, \quad \epsilon_i \sim N(0, e^{g(x_i)})
+/// ```
+///
+/// where `f ~ GP(0, k_f)` and the log noise variance `g ~ GP(μ, k_g)`. The
+/// latent `g` at the training inputs and the constant `μ` are set to their
+/// maximum a posteriori values, with `f` marginalized out. Predictions of `f`
+/// then come from an exact GP with per-point noise `e^g`.
+///
+/// The kernels are held fixed during training. A common workflow is to fit
+/// the hyperparameters of `k_f` with a homoscedastic
+/// [`GaussianProcess`](super::GaussianProcess) first.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::{DMatrix, DVector};
+/// use rv::process::gaussian::kernel::{ConstantKernel, RBFKernel};
+/// use rv::process::gaussian::HeteroscedasticGaussianProcess;
+///
+/// // Noise that grows with x
+/// let xs = DMatrix::from_fn(60, 1, |i, _| i as f64 / 60.0);
+/// let ys = xs.column(0).map(|x| {
+///     let wiggle = if (x * 600.0) as usize % 2 == 0 { 1.0 } else { -1.0 };
+///     (4.0 * x).sin() + wiggle * 0.5 * x
+/// });
+///
+/// let gp = HeteroscedasticGaussianProcess::train(
+///     RBFKernel::new(0.3).unwrap(),
+///     ConstantKernel::new(2.0).unwrap() * RBFKernel::new(0.5).unwrap(),
+///     xs,
+///     ys,
+///     200,
+/// )
+/// .unwrap();
+///
+/// let x_test = DMatrix::from_column_slice(2, 1, &[0.1, 0.9]);
+/// let noise = gp.noise_variance(&x_test);
+/// assert!(noise[1] > noise[0]);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct HeteroscedasticGaussianProcess<K, G>
+where
+    K: Kernel,
+    G: Kernel,
+{
+    /// GP on the function, trained with the MAP per-point noise
+    gp: GaussianProcess<K>,
+    /// Kernel of the log-noise GP
+    noise_kernel: G,
+    /// Constant prior mean of the log-noise GP
+    noise_mean: f64,
+    /// MAP log noise variance at the training inputs
+    ln_noise: DVector<f64>,
+    /// K_g⁻¹ (g - μ)
+    noise_weights: DVector<f64>,
+    /// Log joint density of the training outputs and the MAP log noise
+    ln_posterior: f64,
+}
+
+impl<K, G> HeteroscedasticGaussianProcess<K, G>
+where
+    K: Kernel,
+    G: Kernel,
+{
+    /// Train a heteroscedastic Gaussian process on the given data points
+    ///
+    /// The log-noise GP mean starts at a tenth of the variance of `y_train`
+    /// and the latent log noise is optimized with L-BFGS.
+    ///
+    /// # Arguments
+    /// * `kernel` - Kernel of the function GP
+    /// * `noise_kernel` - Kernel of the log noise variance GP
+    /// * `x_train` - Values to use for input into `f`
+    /// * `y_train` - Observed values of `f(x)` plus noise
+    /// * `max_iters` - Maximum number of optimizer iterations
+    pub fn train(
+        kernel: K,
+        noise_kernel: G,
+        x_train: DMatrix<f64>,
+        y_train: DVector<f64>,
+        max_iters: u64,
+    ) -> Result<Self, GaussianProcessError> {
+        let n = x_train.nrows();
+        if y_train.nrows() != n {
+            return Err(GaussianProcessError::MisshapenNoiseModel(format!(
+                "y_train must have one value per row of x_train \
+                     (expected: {}, got: {})",
+                n,
+                y_train.nrows()
+            )));
+        }
+
+        let mut kg = noise_kernel.covariance(&x_train, &x_train);
+        let jitter = NOISE_JITTER * kg.diagonal().max().max(1.0);
+        (0..n).for_each(|i| kg[(i, i)] += jitter);
+        let kg_chol = Cholesky::new(kg)
+            .ok_or(GaussianProcessError::NotPositiveSemiDefinite)?;
+
+        let op = LnNoiseMapOp {
+            kf: kernel.covariance(&x_train, &x_train),
+            lg: kg_chol.l(),
+            y: y_train.clone(),
+        };
+
+        let y_var = y_train.variance();
+        let mut init = DVector::zeros(n + 1);
+        init[n] = if y_var > 0.0 { (0.1 * y_var).ln() } else { 0.0 };
+
+        let solver = LBFGS::new(MoreThuenteLineSearch::new(), 10);
+        let res = Executor::new(op.clone(), solver)
+            .configure(|state| state.param(init).max_iters(max_iters))
+            .run()
+            .map_err(|e| {
+                GaussianProcessError::OptimizationFailed(e.to_string())
+            })?;
+        let z = res.state.best_param.ok_or_else(|| {
+            GaussianProcessError::OptimizationFailed(
+                "optimizer returned no parameters".to_string(),
+            )
+        })?;
+
+        let (ln_posterior, _) = op
+            .ln_posterior_with_grad(&z)
+            .ok_or(GaussianProcessError::NotPositiveSemiDefinite)?;
+        let v = z.rows(0, n).into_owned();
+        let noise_mean = z[n];
+        let ln_noise = op.ln_noise(&z);
+        let noise_weights = kg_chol
+            .l()
+            .transpose()
+            .solve_upper_triangular(&v)
+            .ok_or(GaussianProcessError::NotPositiveSemiDefinite)?;
+
+        let gp = GaussianProcess::train(
+            kernel,
+            x_train,
+            y_train,
+            NoiseModel::PerPoint(ln_noise.map(f64::exp)),
+        )?;
+
+        Ok(Self {
+            gp,
+            noise_kernel,
+            noise_mean,
+            ln_noise,
+            noise_weights,
+            ln_posterior,
+        })
+    }
+
+    /// Return the GP on the function, conditioned on the MAP noise
+    pub fn gp(&self) -> &GaussianProcess<K> {
+        &self.gp
+    }
+
+    /// Return the kernel of the log noise variance GP
+    pub fn noise_kernel(&self) -> &G {
+        &self.noise_kernel
+    }
+
+    /// Return the constant prior mean of the log noise variance GP
+    pub fn noise_mean(&self) -> f64 {
+        self.noise_mean
+    }
+
+    /// Return the MAP log noise variance at the training inputs
+    pub fn ln_noise_train(&self) -> &DVector<f64> {
+        &self.ln_noise
+    }
+
+    /// Log joint density of the training outputs and the MAP log noise, with
+    /// the function marginalized out
+    pub fn ln_posterior(&self) -> f64 {
+        self.ln_posterior
+    }
+
+    /// Posterior mean of the function at the rows of `xs`
+    pub fn mean(&self, xs: &DMatrix<f64>) -> DVector<f64> {
+        self.gp.kernel.covariance(xs, &self.gp.x_train) * &self.gp.alpha
+    }
+
+    /// Posterior variance of the function at the rows of `xs`, excluding the
+    /// observation noise
+    pub fn latent_variance(&self, xs: &DMatrix<f64>) -> DVector<f64> {
+        let kfs = self.gp.kernel.covariance(&self.gp.x_train, xs);
+        let a = self.gp.k_chol.solve(&kfs);
+        let kss = self.gp.kernel.diag(xs);
+        DVector::from_fn(xs.nrows(), |i, _| {
+            (kss[i] - kfs.column(i).dot(&a.column(i))).max(0.0)
+        })
+    }
+
+    /// Predicted log noise variance at the rows of `xs`
+    pub fn ln_noise(&self, xs: &DMatrix<f64>) -> DVector<f64> {
+        let kgs = self.noise_kernel.covariance(xs, &self.gp.x_train);
+        (kgs * &self.noise_weights).add_scalar(self.noise_mean)
+    }
+
+    /// Predicted noise variance at the rows of `xs`
+    pub fn noise_variance(&self, xs: &DMatrix<f64>) -> DVector<f64> {
+        self.ln_noise(xs).map(f64::exp)
+    }
+
+    /// Variance of a new observation at the rows of `xs`, the sum of the
+    /// latent and noise variances
+    pub fn predictive_variance(&self, xs: &DMatrix<f64>) -> DVector<f64> {
+        self.latent_variance(xs) + self.noise_variance(xs)
+    }
+
+    /// Standard deviation of a new observation at the rows of `xs`
+    pub fn predictive_std(&self, xs: &DMatrix<f64>) -> DVector<f64> {
+        self.predictive_variance(xs).map(f64::sqrt)
+    }
+}
+
+/// Optimization target for the MAP log noise
+///
+/// The parameters are `[v, μ]` where the log noise is `g = μ + L_g v` and
+/// `L_g` is the Cholesky factor of the noise GP covariance. Whitening makes
+/// the prior on `v` a standard normal.
+#[derive(Clone, Debug)]
+struct LnNoiseMapOp {
+    /// Function GP covariance at the training inputs
+    kf: DMatrix<f64>,
+    /// Lower Cholesky factor of the noise GP covariance
+    lg: DMatrix<f64>,
+    /// Training outputs
+    y: DVector<f64>,
+}
+
+impl LnNoiseMapOp {
+    fn ln_noise(&self, z: &DVector<f64>) -> DVector<f64> {
+        let n = self.y.nrows();
+        (&self.lg * z.rows(0, n)).add_scalar(z[n])
+    }
+
+    /// Log joint density of `y` and `g`, and its gradient in `[v, μ]`
+    fn ln_posterior_with_grad(
+        &self,
+        z: &DVector<f64>,
+    ) -> Option<(f64, DVector<f64>)> {
+        let n = self.y.nrows();
+        let v = z.rows(0, n);
+        let r = self.ln_noise(z).map(f64::exp);
+
+        let mut k = self.kf.clone();
+        (0..n).for_each(|i| k[(i, i)] += r[i]);
+        let k_chol = Cholesky::new(k)?;
+        let alpha = k_chol.solve(&self.y);
+        let k_inv = k_chol.inverse();
+
+        let half_ln_det_k = k_chol.l_dirty().diagonal().map(|x| x.ln()).sum();
+        let half_ln_det_kg = self.lg.diagonal().map(|x| x.ln()).sum();
+        let ln_lik = (-0.5_f64).mul_add(self.y.dot(&alpha), -half_ln_det_k);
+        let ln_prior = (-0.5_f64).mul_add(v.dot(&v), -half_ln_det_kg);
+        let ln_post = (2.0 * n as f64).mul_add(-HALF_LN_2PI, ln_lik + ln_prior);
+
+        // d/dg_i ln N(y | 0, K_f + diag(e^g)) = e^g_i (α_i² - K⁻¹_ii) / 2
+        let dg = DVector::from_fn(n, |i, _| {
+            0.5 * r[i] * alpha[i].mul_add(alpha[i], -k_inv[(i, i)])
+        });
+        let grad_v = self.lg.tr_mul(&dg) - v;
+        let mut grad = DVector::zeros(n + 1);
+        grad.rows_mut(0, n).copy_from(&grad_v);
+        grad[n] = dg.sum();
+
+        Some((ln_post, grad))
+    }
+}
+
+impl CostFunction for LnNoiseMapOp {
+    type Param = DVector<f64>;
+    type Output = f64;
+
+    fn cost(&self, param: &DVector<f64>) -> Result<f64, argmin::core::Error> {
+        self.ln_posterior_with_grad(param)
+            .map(|(ln_post, _)| -ln_post)
+            .ok_or_else(|| {
+                argmin_error!(
+                    InvalidParameter,
+                    "Noise covariance is not positive definite"
+                )
+            })
+    }
+}
+
+impl Gradient for LnNoiseMapOp {
+    type Param = DVector<f64>;
+    type Gradient = DVector<f64>;
+
+    fn gradient(
+        &self,
+        param: &DVector<f64>,
+    ) -> Result<DVector<f64>, argmin::core::Error> {
+        self.ln_posterior_with_grad(param)
+            .map(|(_, grad)| -grad)
+            .ok_or_else(|| {
+                argmin_error!(
+                    InvalidParameter,
+                    "Noise covariance is not positive definite"
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::gaussian::kernel::{ConstantKernel, RBFKernel};
+    use rand::SeedableRng;
+    use rand_distr::{Distribution, StandardNormal};
+    use rand_xoshiro::Xoshiro256Plus;
+
+    fn data(n: usize) -> (DMatrix<f64>, DVector<f64>) {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x1234);
+        let xs = DMatrix::from_fn(n, 1, |i, _| i as f64 / n as f64);
+        let ys = xs.column(0).map(|x| {
+            let e: f64 = StandardNormal.sample(&mut rng);
+            // noise standard deviation grows from 0.05 to 0.55
+            0.5_f64.mul_add(x, 0.05).mul_add(e, (4.0 * x).sin())
+        });
+        (xs, ys)
+    }
+
+    #[test]
+    fn gradient_matches_finite_differences() {
+        let (xs, ys) = data(12);
+        let kg = (ConstantKernel::new(1.5).unwrap()
+            * RBFKernel::new(0.4).unwrap())
+        .covariance(&xs, &xs);
+        let op = LnNoiseMapOp {
+            kf: RBFKernel::new(0.3).unwrap().covariance(&xs, &xs),
+            lg: Cholesky::new(kg + DMatrix::identity(12, 12) * 1E-6)
+                .unwrap()
+                .l(),
+            y: ys,
+        };
+
+        let z =
+            DVector::from_fn(13, |i, _| (i as f64).sin().mul_add(0.1, -0.2));
+        let (_, grad) = op.ln_posterior_with_grad(&z).unwrap();
+        let h = 1E-6;
+        for i in 0..13 {
+            let mut up = z.clone();
+            up[i] += h;
+            let mut down = z.clone();
+            down[i] -= h;
+            let fd = (op.ln_posterior_with_grad(&up).unwrap().0
+                - op.ln_posterior_with_grad(&down).unwrap().0)
+                / (2.0 * h);
+            assert::close(grad[i], fd, 1E-5);
+        }
+    }
+
+    #[test]
+    fn recovers_increasing_noise() {
+        let (xs, ys) = data(80);
+        let gp = HeteroscedasticGaussianProcess::train(
+            RBFKernel::new(0.3).unwrap(),
+            ConstantKernel::new(2.0).unwrap() * RBFKernel::new(0.5).unwrap(),
+            xs.clone(),
+            ys.clone(),
+            200,
+        )
+        .unwrap();
+
+        let x_test = DMatrix::from_column_slice(2, 1, &[0.1, 0.9]);
+        let noise_std = gp.noise_variance(&x_test).map(f64::sqrt);
+        assert!(noise_std[0] < 0.2);
+        assert!(noise_std[1] > 0.25 && noise_std[1] < 1.0);
+        assert!(noise_std[1] > 2.0 * noise_std[0]);
+
+        let mean = gp.mean(&x_test);
+        assert::close(mean[0], 0.4_f64.sin(), 0.1);
+
+        let ln_noise = gp.ln_noise_train();
+        assert!(ln_noise[75] > ln_noise[5]);
+        assert!(gp.ln_posterior().is_finite());
+    }
+
+    #[test]
+    fn misshapen_outputs() {
+        let (xs, _) = data(10);
+        let res = HeteroscedasticGaussianProcess::train(
+            RBFKernel::default(),
+            RBFKernel::default(),
+            xs,
+            DVector::zeros(3),
+            10,
+        );
+        assert!(matches!(
+            res,
+            Err(GaussianProcessError::MisshapenNoiseModel(_))
+        ));
+    }
+}
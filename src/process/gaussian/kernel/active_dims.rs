@@ -0,0 +1,206 @@
+use super::{CovGrad, CovGradError, Kernel, KernelError, KernelNode};
+use nalgebra::base::constraint::{SameNumberOfColumns, ShapeConstraint};
+use nalgebra::base::storage::Storage;
+use nalgebra::{DMatrix, DVector, Dim, Matrix};
+use std::f64;
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+/// Kernel that only sees a subset of the input columns
+///
+/// This lets different kernels act on different parts of the input, e.g.,
+/// an RBF kernel over continuous columns times a
+/// [`HammingKernel`](super::HammingKernel) over categorical columns.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::dmatrix;
+/// use rv::process::gaussian::kernel::*;
+///
+/// // Column 0 is continuous and column 1 is categorical
+/// let kernel = ActiveDimsKernel::new(RBFKernel::default(), vec![0])
+///     * ActiveDimsKernel::new(HammingKernel::with_dims(1), vec![1]);
+///
+/// let x = dmatrix![0.0, 0.0; 0.0, 1.0];
+/// let cov = kernel.covariance(&x, &x);
+/// assert!((cov[(0, 1)] - (-1.0_f64).exp()).abs() < 1E-12);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct ActiveDimsKernel<K>
+where
+    K: Kernel,
+{
+    base: K,
+    dims: Vec<usize>,
+}
+
+impl<K> ActiveDimsKernel<K>
+where
+    K: Kernel,
+{
+    /// Apply `base` to the input columns in `dims`
+    pub fn new(base: K, dims: Vec<usize>) -> Self {
+        Self { base, dims }
+    }
+
+    /// The base kernel
+    pub fn base(&self) -> &K {
+        &self.base
+    }
+
+    /// The input columns seen by the base kernel
+    pub fn dims(&self) -> &[usize] {
+        &self.dims
+    }
+
+    /// Select the active columns of `x`
+    fn select<R, C, S>(&self, x: &Matrix<f64, R, C, S>) -> DMatrix<f64>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        DMatrix::from_fn(x.nrows(), self.dims.len(), |i, j| {
+            x[(i, self.dims[j])]
+        })
+    }
+
+    // Lives outside of `Kernel::covariance` so that the shape constraint on
+    // that method's generics does not leak into this call.
+    fn base_covariance(
+        &self,
+        x1: &DMatrix<f64>,
+        x2: &DMatrix<f64>,
+    ) -> DMatrix<f64> {
+        self.base.covariance(x1, x2)
+    }
+}
+
+impl<K> Kernel for ActiveDimsKernel<K>
+where
+    K: Kernel,
+{
+    fn n_parameters(&self) -> usize {
+        self.base.n_parameters()
+    }
+
+    fn covariance<R1, R2, C1, C2, S1, S2>(
+        &self,
+        x1: &Matrix<f64, R1, C1, S1>,
+        x2: &Matrix<f64, R2, C2, S2>,
+    ) -> DMatrix<f64>
+    where
+        R1: Dim,
+        R2: Dim,
+        C1: Dim,
+        C2: Dim,
+        S1: Storage<f64, R1, C1>,
+        S2: Storage<f64, R2, C2>,
+        ShapeConstraint: SameNumberOfColumns<C1, C2>,
+    {
+        self.base_covariance(&self.select(x1), &self.select(x2))
+    }
+
+    fn is_stationary(&self) -> bool {
+        self.base.is_stationary()
+    }
+
+    fn diag<R, C, S>(&self, x: &Matrix<f64, R, C, S>) -> DVector<f64>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        self.base.diag(&self.select(x))
+    }
+
+    fn parameters(&self) -> DVector<f64> {
+        self.base.parameters()
+    }
+
+    fn reparameterize(&self, params: &[f64]) -> Result<Self, KernelError> {
+        Ok(Self {
+            base: self.base.reparameterize(params)?,
+            dims: self.dims.clone(),
+        })
+    }
+
+    fn covariance_with_gradient<R, C, S>(
+        &self,
+        x: &Matrix<f64, R, C, S>,
+    ) -> Result<(DMatrix<f64>, CovGrad), CovGradError>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        self.base.covariance_with_gradient(&self.select(x))
+    }
+}
+
+impl<K, B> std::ops::Mul<B> for ActiveDimsKernel<K>
+where
+    K: Kernel,
+    B: Kernel,
+{
+    type Output = super::ProductKernel<Self, B>;
+
+    fn mul(self, rhs: B) -> Self::Output {
+        super::ProductKernel::new(self, rhs)
+    }
+}
+
+impl<K, B> std::ops::Add<B> for ActiveDimsKernel<K>
+where
+    K: Kernel,
+    B: Kernel,
+{
+    type Output = super::AddKernel<Self, B>;
+
+    fn add(self, rhs: B) -> Self::Output {
+        super::AddKernel::new(self, rhs)
+    }
+}
+
+impl<K> From<ActiveDimsKernel<K>> for KernelNode
+where
+    K: Kernel + Into<KernelNode>,
+{
+    fn from(k: ActiveDimsKernel<K>) -> Self {
+        KernelNode::ActiveDims(Box::new(ActiveDimsKernel {
+            base: k.base.into(),
+            dims: k.dims,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::gaussian::kernel::RBFKernel;
+    use nalgebra::dmatrix;
+
+    #[test]
+    fn only_active_columns_matter() {
+        let kernel =
+            ActiveDimsKernel::new(RBFKernel::new(0.5).unwrap(), vec![1]);
+        let x = dmatrix![0.0, 1.0; 100.0, 1.5];
+        let cov = kernel.covariance(&x, &x);
+        let expected = RBFKernel::new(0.5)
+            .unwrap()
+            .covariance(&dmatrix![1.0; 1.5], &dmatrix![1.0; 1.5]);
+        assert!(cov.relative_eq(&expected, 1E-12, 1E-12));
+
+        let (cov_g, grad) = kernel.covariance_with_gradient(&x).unwrap();
+        let (_, expected_grad) = RBFKernel::new(0.5)
+            .unwrap()
+            .covariance_with_gradient(&dmatrix![1.0; 1.5])
+            .unwrap();
+        assert!(cov_g.relative_eq(&expected, 1E-12, 1E-12));
+        assert!(grad.relative_eq(&expected_grad, 1E-12, 1E-12));
+    }
+}
@@ -0,0 +1,370 @@
+use super::{CovGrad, CovGradError, Kernel, KernelError, KernelNode};
+use nalgebra::base::constraint::{SameNumberOfColumns, ShapeConstraint};
+use nalgebra::base::storage::Storage;
+use nalgebra::{DMatrix, DVector, Dim, Matrix};
+use std::f64;
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+/// Change-point kernel
+///
+/// Switches from kernel `a` to kernel `b` along one input dimension, `d`,
+/// via a sigmoid centered at `location`.
+///
+/// ```math
+///     K(\mathbf{x}, \mathbf{x'}) = (1 - s(x_d))(1 - s(x'_d)) k_a(\mathbf{x}, \mathbf{x'})
+///         + s(x_d) s(x'_d) k_b(\mathbf{x}, \mathbf{x'}),
+///     \quad s(x) = \frac{1}{1 + \exp(-(x - l) / w)}
+/// ```
+///
+/// # Parameters
+/// * `location` - Location of the change point, `l`.
+/// * `width` - Width of the transition, `w`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct ChangePointKernel<A, B>
+where
+    A: Kernel,
+    B: Kernel,
+{
+    a: A,
+    b: B,
+    location: f64,
+    width: f64,
+    dim: usize,
+}
+
+impl<A, B> ChangePointKernel<A, B>
+where
+    A: Kernel,
+    B: Kernel,
+{
+    /// Create a new change-point kernel over the first input dimension
+    pub fn new(
+        a: A,
+        b: B,
+        location: f64,
+        width: f64,
+    ) -> Result<Self, KernelError> {
+        if !location.is_finite() {
+            Err(KernelError::ParameterOutOfBounds {
+                name: "location".to_string(),
+                given: location,
+                bounds: (f64::NEG_INFINITY, f64::INFINITY),
+            })
+        } else if width <= 0.0 || !width.is_finite() {
+            Err(KernelError::ParameterOutOfBounds {
+                name: "width".to_string(),
+                given: width,
+                bounds: (0.0, f64::INFINITY),
+            })
+        } else {
+            Ok(Self {
+                a,
+                b,
+                location,
+                width,
+                dim: 0,
+            })
+        }
+    }
+
+    /// Create a new `ChangePointKernel` without checking parameters
+    pub fn new_unchecked(a: A, b: B, location: f64, width: f64) -> Self {
+        Self {
+            a,
+            b,
+            location,
+            width,
+            dim: 0,
+        }
+    }
+
+    /// Switch along input dimension `dim` instead of the first
+    pub fn with_dim(mut self, dim: usize) -> Self {
+        self.dim = dim;
+        self
+    }
+
+    /// Location of the change point
+    pub fn location(&self) -> f64 {
+        self.location
+    }
+
+    /// Width of the transition between the two kernels
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    /// Input dimension along which the kernels switch
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    /// Sigmoid weight of kernel `b` for each row of `x`
+    fn sigmoid<R, C, S>(&self, x: &Matrix<f64, R, C, S>) -> DVector<f64>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        DVector::from_fn(x.nrows(), |i, _| {
+            let z = (x[(i, self.dim)] - self.location) / self.width;
+            1.0 / (1.0 + (-z).exp())
+        })
+    }
+}
+
+impl<A, B> Kernel for ChangePointKernel<A, B>
+where
+    A: Kernel,
+    B: Kernel,
+{
+    fn n_parameters(&self) -> usize {
+        self.a.n_parameters() + self.b.n_parameters() + 2
+    }
+
+    fn covariance<R1, R2, C1, C2, S1, S2>(
+        &self,
+        x1: &Matrix<f64, R1, C1, S1>,
+        x2: &Matrix<f64, R2, C2, S2>,
+    ) -> DMatrix<f64>
+    where
+        R1: Dim,
+        R2: Dim,
+        C1: Dim,
+        C2: Dim,
+        S1: Storage<f64, R1, C1>,
+        S2: Storage<f64, R2, C2>,
+        ShapeConstraint: SameNumberOfColumns<C1, C2>,
+    {
+        let cov_a = self.a.covariance(x1, x2);
+        let cov_b = self.b.covariance(x1, x2);
+        let s1 = self.sigmoid(x1);
+        let s2 = self.sigmoid(x2);
+
+        DMatrix::from_fn(x1.nrows(), x2.nrows(), |i, j| {
+            ((1.0 - s1[i]) * (1.0 - s2[j]))
+                .mul_add(cov_a[(i, j)], s1[i] * s2[j] * cov_b[(i, j)])
+        })
+    }
+
+    fn is_stationary(&self) -> bool {
+        false
+    }
+
+    fn diag<R, C, S>(&self, x: &Matrix<f64, R, C, S>) -> DVector<f64>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        let diag_a = self.a.diag(x);
+        let diag_b = self.b.diag(x);
+        let s = self.sigmoid(x);
+        DVector::from_fn(x.nrows(), |i, _| {
+            (1.0 - s[i])
+                .powi(2)
+                .mul_add(diag_a[i], s[i].powi(2) * diag_b[i])
+        })
+    }
+
+    /// Parameters of `a`, parameters of `b`, the location, and
+    /// `ln(width)`. The location is not log-scaled.
+    fn parameters(&self) -> DVector<f64> {
+        let a = self.a.parameters();
+        let b = self.b.parameters();
+        DVector::from_iterator(
+            self.n_parameters(),
+            a.iter()
+                .chain(b.iter())
+                .copied()
+                .chain([self.location, self.width.ln()]),
+        )
+    }
+
+    fn reparameterize(&self, params: &[f64]) -> Result<Self, KernelError> {
+        use std::cmp::Ordering;
+        let n = self.n_parameters();
+        match params.len().cmp(&n) {
+            Ordering::Less => {
+                Err(KernelError::MissingParameters(n - params.len()))
+            }
+            Ordering::Greater => {
+                Err(KernelError::ExtraniousParameters(params.len() - n))
+            }
+            Ordering::Equal => {
+                let (a_params, rest) = params.split_at(self.a.n_parameters());
+                let (b_params, rest) = rest.split_at(self.b.n_parameters());
+                let a = self.a.reparameterize(a_params)?;
+                let b = self.b.reparameterize(b_params)?;
+                Self::new(a, b, rest[0], rest[1].exp())
+                    .map(|k| k.with_dim(self.dim))
+            }
+        }
+    }
+
+    fn covariance_with_gradient<R, C, S>(
+        &self,
+        x: &Matrix<f64, R, C, S>,
+    ) -> Result<(DMatrix<f64>, CovGrad), CovGradError>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        let n = x.nrows();
+        let (cov_a, grad_a) = self.a.covariance_with_gradient(x)?;
+        let (cov_b, grad_b) = self.b.covariance_with_gradient(x)?;
+        let s = self.sigmoid(x);
+
+        let weight_a =
+            DMatrix::from_fn(n, n, |i, j| (1.0 - s[i]) * (1.0 - s[j]));
+        let weight_b = DMatrix::from_fn(n, n, |i, j| s[i] * s[j]);
+        let cov =
+            cov_a.component_mul(&weight_a) + cov_b.component_mul(&weight_b);
+
+        // ds/dl = -s(1 - s) / w and ds/d(ln w) = -s(1 - s) z
+        let ds_dl = s.map(|si| -si * (1.0 - si) / self.width);
+        let ds_dlnw = DVector::from_fn(n, |i, _| {
+            let z = (x[(i, self.dim)] - self.location) / self.width;
+            -s[i] * (1.0 - s[i]) * z
+        });
+        let sigmoid_grad = |ds: &DVector<f64>| {
+            DMatrix::from_fn(n, n, |i, j| {
+                let d_weight_a =
+                    (-ds[i]).mul_add(1.0 - s[j], -(1.0 - s[i]) * ds[j]);
+                let d_weight_b = ds[i].mul_add(s[j], s[i] * ds[j]);
+                d_weight_a.mul_add(cov_a[(i, j)], d_weight_b * cov_b[(i, j)])
+            })
+        };
+
+        let grad = grad_a
+            .component_mul(&weight_a)?
+            .concat_cols(&grad_b.component_mul(&weight_b)?)?
+            .concat_cols(&CovGrad::new(&[
+                sigmoid_grad(&ds_dl),
+                sigmoid_grad(&ds_dlnw),
+            ])?)?;
+
+        Ok((cov, grad))
+    }
+}
+
+impl<A, B, C> std::ops::Mul<C> for ChangePointKernel<A, B>
+where
+    A: Kernel,
+    B: Kernel,
+    C: Kernel,
+{
+    type Output = super::ProductKernel<Self, C>;
+
+    fn mul(self, rhs: C) -> Self::Output {
+        super::ProductKernel::new(self, rhs)
+    }
+}
+
+impl<A, B, C> std::ops::Add<C> for ChangePointKernel<A, B>
+where
+    A: Kernel,
+    B: Kernel,
+    C: Kernel,
+{
+    type Output = super::AddKernel<Self, C>;
+
+    fn add(self, rhs: C) -> Self::Output {
+        super::AddKernel::new(self, rhs)
+    }
+}
+
+impl<A, B> From<ChangePointKernel<A, B>> for KernelNode
+where
+    A: Kernel + Into<KernelNode>,
+    B: Kernel + Into<KernelNode>,
+{
+    fn from(k: ChangePointKernel<A, B>) -> Self {
+        KernelNode::ChangePoint(Box::new(ChangePointKernel {
+            a: k.a.into(),
+            b: k.b.into(),
+            location: k.location,
+            width: k.width,
+            dim: k.dim,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::gaussian::kernel::{ConstantKernel, RBFKernel};
+    use nalgebra::dmatrix;
+
+    fn kernel() -> ChangePointKernel<RBFKernel, ConstantKernel> {
+        ChangePointKernel::new(
+            RBFKernel::new(0.8).unwrap(),
+            ConstantKernel::new(2.0).unwrap(),
+            0.5,
+            0.3,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn far_from_change_point_uses_one_kernel() {
+        let k = kernel();
+        let x = dmatrix![-10.0; -9.5; 10.0; 11.0];
+        let cov = k.covariance(&x, &x);
+        let rbf = RBFKernel::new(0.8).unwrap().covariance(&x, &x);
+
+        assert::close(cov[(0, 1)], rbf[(0, 1)], 1E-8);
+        assert::close(cov[(2, 3)], 2.0, 1E-8);
+        assert::close(cov[(0, 3)], 0.0, 1E-8);
+        assert!(k.diag(&x).relative_eq(&cov.diagonal(), 1E-12, 1E-12));
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        let a = RBFKernel::default();
+        let b = RBFKernel::default();
+        assert!(ChangePointKernel::new(a.clone(), b.clone(), 0.0, 0.0).is_err());
+        assert!(ChangePointKernel::new(a, b, f64::NAN, 1.0).is_err());
+    }
+
+    #[test]
+    fn reparameterize_keeps_dim() {
+        let x = dmatrix![0.0, 1.0; 2.0, -1.0];
+        let k = kernel().with_dim(1);
+        let k2 = k.reparameterize(k.parameters().as_slice()).unwrap();
+        assert_eq!(k2.dim(), 1);
+        assert!(k2.covariance(&x, &x).relative_eq(
+            &k.covariance(&x, &x),
+            1E-10,
+            1E-10
+        ));
+    }
+
+    #[test]
+    fn gradient_matches_finite_differences() -> Result<(), KernelError> {
+        let k = kernel();
+        let x = dmatrix![-0.5; 0.2; 0.6; 1.4];
+        let (cov, grad) = k.covariance_with_gradient(&x)?;
+        assert!(cov.relative_eq(&k.covariance(&x, &x), 1E-10, 1E-10));
+
+        let params = k.parameters();
+        assert_eq!(params.len(), 4);
+        let h = 1E-6;
+        for p in 0..params.len() {
+            let mut up = params.clone();
+            up[p] += h;
+            let mut down = params.clone();
+            down[p] -= h;
+            let fd = (k.reparameterize(up.as_slice())?.covariance(&x, &x)
+                - k.reparameterize(down.as_slice())?.covariance(&x, &x))
+                / (2.0 * h);
+            assert!(fd.relative_eq(&grad[p], 1E-5, 1E-5));
+        }
+        Ok(())
+    }
+}
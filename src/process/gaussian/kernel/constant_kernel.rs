@@ -32,6 +32,11 @@ impl ConstantKernel {
     pub fn new_unchecked(scale: f64) -> Self {
         Self { scale }
     }
+
+    /// The constant value of the kernel
+    pub fn value(&self) -> f64 {
+        self.scale
+    }
 }
 
 impl Default for ConstantKernel {
@@ -0,0 +1,537 @@
+use super::{CovGrad, CovGradError, Kernel, KernelError};
+use nalgebra::base::constraint::{SameNumberOfColumns, ShapeConstraint};
+use nalgebra::base::storage::Storage;
+use nalgebra::{DMatrix, DVector, Dim, Matrix};
+use std::f64;
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+/// The default bound on the entries of `W`, relative to the largest initial
+/// entry (or one, if that is smaller)
+const W_BOUND_SCALE: f64 = 10.0;
+
+fn default_w_bound(w: &DMatrix<f64>) -> f64 {
+    W_BOUND_SCALE * w.amax().max(1.0)
+}
+
+/// Intrinsic coregionalization model (ICM) kernel for multi-output GPs
+///
+/// The last column of the input holds the (integer-valued) task index; the
+/// remaining columns are passed to the base kernel.
+///
+/// ```math
+///     K((\mathbf{x}, s), (\mathbf{x'}, t)) = B_{st} k(\mathbf{x}, \mathbf{x'}),
+///     \quad B = W W^T + \mathrm{diag}(\kappa)
+/// ```
+///
+/// # Parameters
+/// * `base` - Kernel over the non-task inputs.
+/// * `w` - `n_tasks` × `rank` factor of the task covariance.
+/// * `kappa` - Per-task independent variance.
+///
+/// The entries of `W` are bounded in magnitude by `w_bound`, which is ten
+/// times the largest initial entry by default (see
+/// [`CoregionalKernel::with_w_bound`]). Without a bound, fitting tasks that
+/// are (nearly) perfectly correlated sends `W` off to infinity.
+///
+/// # Panics
+/// The covariance functions panic if a task index is not less than the
+/// number of tasks.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct CoregionalKernel<K>
+where
+    K: Kernel,
+{
+    base: K,
+    w: DMatrix<f64>,
+    kappa: DVector<f64>,
+    w_bound: f64,
+}
+
+impl<K> CoregionalKernel<K>
+where
+    K: Kernel,
+{
+    /// Create a new coregionalization kernel
+    ///
+    /// `w` must have one row per task and `kappa` one entry per task.
+    pub fn new(
+        base: K,
+        w: DMatrix<f64>,
+        kappa: DVector<f64>,
+    ) -> Result<Self, KernelError> {
+        use std::cmp::Ordering;
+        match kappa.nrows().cmp(&w.nrows()) {
+            Ordering::Less => {
+                return Err(KernelError::MissingParameters(
+                    w.nrows() - kappa.nrows(),
+                ))
+            }
+            Ordering::Greater => {
+                return Err(KernelError::ExtraniousParameters(
+                    kappa.nrows() - w.nrows(),
+                ))
+            }
+            Ordering::Equal => (),
+        }
+
+        if let Some(k) = kappa.iter().find(|&&k| k <= 0.0) {
+            Err(KernelError::ParameterOutOfBounds {
+                name: "kappa".to_string(),
+                given: *k,
+                bounds: (0.0, f64::INFINITY),
+            })
+        } else {
+            let w_bound = default_w_bound(&w);
+            Ok(Self {
+                base,
+                w,
+                kappa,
+                w_bound,
+            })
+        }
+    }
+
+    /// Create a new `CoregionalKernel` without checking parameters
+    pub fn new_unchecked(
+        base: K,
+        w: DMatrix<f64>,
+        kappa: DVector<f64>,
+    ) -> Self {
+        let w_bound = default_w_bound(&w);
+        Self {
+            base,
+            w,
+            kappa,
+            w_bound,
+        }
+    }
+
+    /// Create a kernel for `n_tasks` tasks with a rank `rank` task
+    /// covariance. `W` is initialized to all ones and `kappa` to one.
+    pub fn with_rank(base: K, n_tasks: usize, rank: usize) -> Self {
+        Self {
+            base,
+            w: DMatrix::from_element(n_tasks, rank, 1.0),
+            kappa: DVector::from_element(n_tasks, 1.0),
+            w_bound: W_BOUND_SCALE,
+        }
+    }
+
+    /// Set the bound on the magnitude of the entries of `W`
+    ///
+    /// The bound must be greater than the magnitude of every entry of `W`.
+    pub fn with_w_bound(self, w_bound: f64) -> Result<Self, KernelError> {
+        let w_max = self.w.amax();
+        if w_bound > w_max && w_bound.is_finite() {
+            Ok(Self { w_bound, ..self })
+        } else {
+            Err(KernelError::ParameterOutOfBounds {
+                name: "w_bound".to_string(),
+                given: w_bound,
+                bounds: (w_max, f64::INFINITY),
+            })
+        }
+    }
+
+    /// The bound on the magnitude of the entries of `W`
+    pub fn w_bound(&self) -> f64 {
+        self.w_bound
+    }
+
+    /// The base kernel
+    pub fn base(&self) -> &K {
+        &self.base
+    }
+
+    /// Number of tasks (outputs)
+    pub fn n_tasks(&self) -> usize {
+        self.w.nrows()
+    }
+
+    /// Rank of the low-rank part of the task covariance
+    pub fn rank(&self) -> usize {
+        self.w.ncols()
+    }
+
+    /// The task covariance matrix, `B = W W' + diag(kappa)`
+    pub fn task_covariance(&self) -> DMatrix<f64> {
+        &self.w * self.w.transpose() + DMatrix::from_diagonal(&self.kappa)
+    }
+
+    /// Append a task-index column to `x` so that every row belongs to `task`
+    pub fn with_task(x: &DMatrix<f64>, task: usize) -> DMatrix<f64> {
+        let n = x.ncols();
+        x.clone().insert_column(n, task as f64)
+    }
+
+    /// Stack per-task inputs and targets into a single training set suitable
+    /// for a `GaussianProcess` with a `CoregionalKernel`.
+    ///
+    /// Entry `t` of `xs` and `ys` holds the inputs and targets of task `t`.
+    ///
+    /// # Panics
+    /// If `xs` and `ys` differ in length, or the inputs of different tasks
+    /// have a different number of columns.
+    pub fn stack_tasks(
+        xs: &[DMatrix<f64>],
+        ys: &[DVector<f64>],
+    ) -> (DMatrix<f64>, DVector<f64>) {
+        assert_eq!(xs.len(), ys.len(), "need targets for every task");
+        let d = xs.first().map(|x| x.ncols()).unwrap_or(0);
+        let n: usize = xs.iter().map(|x| x.nrows()).sum();
+
+        let mut x_all = DMatrix::zeros(n, d + 1);
+        let mut y_all = DVector::zeros(n);
+        let mut row = 0;
+        for (task, (x, y)) in xs.iter().zip(ys.iter()).enumerate() {
+            assert_eq!(x.ncols(), d, "all tasks must have the same inputs");
+            for i in 0..x.nrows() {
+                x_all.view_mut((row, 0), (1, d)).copy_from(&x.row(i));
+                x_all[(row, d)] = task as f64;
+                y_all[row] = y[i];
+                row += 1;
+            }
+        }
+        (x_all, y_all)
+    }
+
+    // Lives outside of `Kernel::covariance` so that the shape constraint on
+    // that method's generics does not leak into this call.
+    fn base_covariance(
+        &self,
+        x1: &DMatrix<f64>,
+        x2: &DMatrix<f64>,
+    ) -> DMatrix<f64> {
+        self.base.covariance(x1, x2)
+    }
+
+    /// The inputs without the task-index column
+    fn inputs<R, C, S>(x: &Matrix<f64, R, C, S>) -> DMatrix<f64>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        DMatrix::from_fn(x.nrows(), x.ncols() - 1, |i, j| x[(i, j)])
+    }
+
+    fn tasks<R, C, S>(x: &Matrix<f64, R, C, S>) -> Vec<usize>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        let last = x.ncols() - 1;
+        (0..x.nrows())
+            .map(|i| x[(i, last)].round() as usize)
+            .collect()
+    }
+}
+
+impl<K> Kernel for CoregionalKernel<K>
+where
+    K: Kernel,
+{
+    fn n_parameters(&self) -> usize {
+        self.base.n_parameters() + self.w.len() + self.kappa.len()
+    }
+
+    fn covariance<R1, R2, C1, C2, S1, S2>(
+        &self,
+        x1: &Matrix<f64, R1, C1, S1>,
+        x2: &Matrix<f64, R2, C2, S2>,
+    ) -> DMatrix<f64>
+    where
+        R1: Dim,
+        R2: Dim,
+        C1: Dim,
+        C2: Dim,
+        S1: Storage<f64, R1, C1>,
+        S2: Storage<f64, R2, C2>,
+        ShapeConstraint: SameNumberOfColumns<C1, C2>,
+    {
+        let b = self.task_covariance();
+        let t1 = Self::tasks(x1);
+        let t2 = Self::tasks(x2);
+
+        let mut cov =
+            self.base_covariance(&Self::inputs(x1), &Self::inputs(x2));
+        for i in 0..cov.nrows() {
+            for j in 0..cov.ncols() {
+                cov[(i, j)] *= b[(t1[i], t2[j])];
+            }
+        }
+        cov
+    }
+
+    fn is_stationary(&self) -> bool {
+        false
+    }
+
+    fn diag<R, C, S>(&self, x: &Matrix<f64, R, C, S>) -> DVector<f64>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        let b = self.task_covariance();
+        let mut diag = self.base.diag(&Self::inputs(x));
+        Self::tasks(x)
+            .iter()
+            .enumerate()
+            .for_each(|(i, &t)| diag[i] *= b[(t, t)]);
+        diag
+    }
+
+    /// Base kernel parameters, followed by `w_bound * atanh(w / w_bound)`
+    /// for the entries of `W` in column-major order, followed by
+    /// `ln(kappa)`. Since `W` may be negative, its entries are not
+    /// log-scaled; this scaling keeps them within the bound while leaving
+    /// small entries almost unchanged.
+    fn parameters(&self) -> DVector<f64> {
+        let base = self.base.parameters();
+        // `tanh` rounds to one far from zero, so keep `atanh` finite
+        let max_ratio = 1.0 - f64::EPSILON;
+        let w_param = |w: &f64| {
+            let ratio = (w / self.w_bound).clamp(-max_ratio, max_ratio);
+            self.w_bound * ratio.atanh()
+        };
+        DVector::from_iterator(
+            self.n_parameters(),
+            base.iter()
+                .copied()
+                .chain(self.w.iter().map(w_param))
+                .chain(self.kappa.iter().map(|k| k.ln())),
+        )
+    }
+
+    fn reparameterize(&self, params: &[f64]) -> Result<Self, KernelError> {
+        use std::cmp::Ordering;
+        let n = self.n_parameters();
+        match params.len().cmp(&n) {
+            Ordering::Less => {
+                Err(KernelError::MissingParameters(n - params.len()))
+            }
+            Ordering::Greater => {
+                Err(KernelError::ExtraniousParameters(params.len() - n))
+            }
+            Ordering::Equal => {
+                let (base_params, rest) =
+                    params.split_at(self.base.n_parameters());
+                let (w_params, kappa_params) = rest.split_at(self.w.len());
+                let base = self.base.reparameterize(base_params)?;
+                let w = DMatrix::from_iterator(
+                    self.w.nrows(),
+                    self.w.ncols(),
+                    w_params
+                        .iter()
+                        .map(|p| self.w_bound * (p / self.w_bound).tanh()),
+                );
+                let kappa = DVector::from_iterator(
+                    self.kappa.len(),
+                    kappa_params.iter().map(|k| k.exp()),
+                );
+                Self::new(base, w, kappa).map(|k| Self {
+                    w_bound: self.w_bound,
+                    ..k
+                })
+            }
+        }
+    }
+
+    fn covariance_with_gradient<R, C, S>(
+        &self,
+        x: &Matrix<f64, R, C, S>,
+    ) -> Result<(DMatrix<f64>, CovGrad), CovGradError>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        let n = x.nrows();
+        let b = self.task_covariance();
+        let tasks = Self::tasks(x);
+
+        let (base_cov, base_grad) =
+            self.base.covariance_with_gradient(&Self::inputs(x))?;
+
+        let b_mat = DMatrix::from_fn(n, n, |i, j| b[(tasks[i], tasks[j])]);
+        let cov = base_cov.component_mul(&b_mat);
+
+        // d B_st / d W_ur = δ_su W_tr + δ_tu W_sr, and
+        // and d W_ur / d p_ur = 1 - (W_ur / w_bound)^2
+        let w_grads = (0..self.w.ncols())
+            .flat_map(|r| (0..self.w.nrows()).map(move |u| (u, r)));
+        let mut slices: Vec<DMatrix<f64>> = w_grads
+            .map(|(u, r)| {
+                let ratio = self.w[(u, r)] / self.w_bound;
+                let dw = ratio.mul_add(-ratio, 1.0);
+                DMatrix::from_fn(n, n, |i, j| {
+                    let (s, t) = (tasks[i], tasks[j]);
+                    let mut db = 0.0;
+                    if s == u {
+                        db += self.w[(t, r)];
+                    }
+                    if t == u {
+                        db += self.w[(s, r)];
+                    }
+                    db * dw * base_cov[(i, j)]
+                })
+            })
+            .collect();
+
+        // d B_st / d ln(kappa_u) = δ_su δ_tu kappa_u
+        slices.extend((0..self.kappa.len()).map(|u| {
+            DMatrix::from_fn(n, n, |i, j| {
+                if tasks[i] == u && tasks[j] == u {
+                    self.kappa[u] * base_cov[(i, j)]
+                } else {
+                    0.0
+                }
+            })
+        }));
+
+        let grad = base_grad
+            .component_mul(&b_mat)?
+            .concat_cols(&CovGrad::new(&slices)?)?;
+
+        Ok((cov, grad))
+    }
+}
+
+impl<K, B> std::ops::Mul<B> for CoregionalKernel<K>
+where
+    K: Kernel,
+    B: Kernel,
+{
+    type Output = super::ProductKernel<Self, B>;
+
+    fn mul(self, rhs: B) -> Self::Output {
+        super::ProductKernel::new(self, rhs)
+    }
+}
+
+impl<K, B> std::ops::Add<B> for CoregionalKernel<K>
+where
+    K: Kernel,
+    B: Kernel,
+{
+    type Output = super::AddKernel<Self, B>;
+
+    fn add(self, rhs: B) -> Self::Output {
+        super::AddKernel::new(self, rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::gaussian::kernel::RBFKernel;
+    use nalgebra::dmatrix;
+
+    fn kernel() -> CoregionalKernel<RBFKernel> {
+        CoregionalKernel::new(
+            RBFKernel::new(1.5).unwrap(),
+            dmatrix![1.0; -0.5],
+            DVector::from_column_slice(&[0.2, 0.3]),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn task_covariance() {
+        let b = kernel().task_covariance();
+        let expected = dmatrix![1.2, -0.5; -0.5, 0.55];
+        assert!(b.relative_eq(&expected, 1E-10, 1E-10));
+    }
+
+    #[test]
+    fn covariance_scales_base_by_task_covariance() {
+        let k = kernel();
+        let x = dmatrix![0.0, 0.0; 1.0, 1.0; 0.5, 0.0];
+        let cov = k.covariance(&x, &x);
+        let base = RBFKernel::new(1.5).unwrap();
+        let base_cov = base.covariance(&x.column(0), &x.column(0));
+        let b = k.task_covariance();
+
+        assert::close(cov[(0, 0)], b[(0, 0)], 1E-10);
+        assert::close(cov[(1, 1)], b[(1, 1)], 1E-10);
+        assert::close(cov[(0, 1)], b[(0, 1)] * base_cov[(0, 1)], 1E-10);
+        assert::close(cov[(0, 2)], b[(0, 0)] * base_cov[(0, 2)], 1E-10);
+        assert!(k.diag(&x).relative_eq(&cov.diagonal(), 1E-10, 1E-10));
+    }
+
+    #[test]
+    fn reparameterize_round_trip() {
+        let k = kernel();
+        let params = k.parameters();
+        assert_eq!(params.len(), 5);
+        let k2 = k.reparameterize(params.as_slice()).unwrap();
+        assert!(k2.task_covariance().relative_eq(
+            &k.task_covariance(),
+            1E-10,
+            1E-10
+        ));
+        assert!(k.reparameterize(&[0.0; 4]).is_err());
+        assert!(k.reparameterize(&[0.0; 6]).is_err());
+    }
+
+    #[test]
+    fn w_is_bounded() {
+        let k = kernel();
+        assert::close(k.w_bound(), 10.0, 1E-12);
+
+        let params = [0.0, 1E6, -1E6, 0.0, 0.0];
+        let k2 = k.reparameterize(&params).unwrap();
+        let b = k2.task_covariance();
+        assert!(b[(0, 0)] <= 100.0 + 1.0 && b[(0, 1)] >= -100.0);
+        assert!(k2.parameters().iter().all(|p| p.is_finite()));
+
+        let k = k.with_w_bound(2.0).unwrap();
+        assert::close(k.w_bound(), 2.0, 1E-12);
+        assert!(k.clone().with_w_bound(0.5).is_err());
+        assert!(k.with_w_bound(f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn gradient_matches_finite_differences() -> Result<(), KernelError> {
+        let k = kernel();
+        let x = dmatrix![0.0, 0.0; 1.0, 1.0; 0.5, 0.0; -0.3, 1.0];
+        let (cov, grad) = k.covariance_with_gradient(&x)?;
+        assert!(cov.relative_eq(&k.covariance(&x, &x), 1E-10, 1E-10));
+
+        let params = k.parameters();
+        let h = 1E-6;
+        for p in 0..params.len() {
+            let mut up = params.clone();
+            up[p] += h;
+            let mut down = params.clone();
+            down[p] -= h;
+            let cov_up = k.reparameterize(up.as_slice())?.covariance(&x, &x);
+            let cov_down =
+                k.reparameterize(down.as_slice())?.covariance(&x, &x);
+            let fd = (cov_up - cov_down) / (2.0 * h);
+            assert!(fd.relative_eq(&grad[p], 1E-5, 1E-5));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn stack_tasks() {
+        let xs = vec![dmatrix![0.0; 1.0], dmatrix![2.0]];
+        let ys = vec![
+            DVector::from_column_slice(&[1.0, 2.0]),
+            DVector::from_column_slice(&[3.0]),
+        ];
+        let (x, y) = CoregionalKernel::<RBFKernel>::stack_tasks(&xs, &ys);
+        assert_eq!(x, dmatrix![0.0, 0.0; 1.0, 0.0; 2.0, 1.0]);
+        assert_eq!(y, DVector::from_column_slice(&[1.0, 2.0, 3.0]));
+
+        let xt = CoregionalKernel::<RBFKernel>::with_task(&xs[1], 1);
+        assert_eq!(xt, dmatrix![2.0, 1.0]);
+    }
+}
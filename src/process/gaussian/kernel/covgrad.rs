@@ -120,6 +120,16 @@ impl CovGrad {
         }
     }
 
+    /// The number of parameters, one slice each
+    pub fn len(&self) -> usize {
+        self.slices.len()
+    }
+
+    /// True if there are no parameters
+    pub fn is_empty(&self) -> bool {
+        self.slices.is_empty()
+    }
+
     /// Create a new cov-grad with all zeros
     pub fn zeros(n: usize, m: usize) -> Self {
         Self {
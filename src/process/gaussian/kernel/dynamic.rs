@@ -0,0 +1,332 @@
+use super::{
+    ActiveDimsKernel, AddKernel, ChangePointKernel, ConstantKernel,
+    CoregionalKernel, CovGrad, CovGradError, ExpSineSquaredKernel,
+    HammingKernel, Kernel, KernelError, KernelNode, MaternKernel,
+    ProductKernel, RBFKernel, RationalQuadratic, SEardKernel,
+    SpectralMixtureKernel, WarpedKernel, WhiteKernel,
+};
+use nalgebra::base::constraint::{SameNumberOfColumns, ShapeConstraint};
+use nalgebra::base::storage::Storage;
+use nalgebra::{DMatrix, DVector, Dim, Matrix};
+use std::any::Any;
+
+/// An object-safe version of [`Kernel`]
+///
+/// `Kernel` has generic methods, so it cannot be made into a trait object.
+/// Every `Kernel` implements `DynKernel`, which works on `DMatrix` inputs
+/// instead, and `Box<dyn DynKernel>` implements `Kernel`. A boxed kernel can
+/// therefore be chosen at run time and used anywhere a `Kernel` is expected,
+/// including in a [`GaussianProcess`](crate::process::gaussian::GaussianProcess).
+///
+/// The methods are prefixed with `dyn_` so that they never shadow the
+/// `Kernel` methods of the same name. A `Box<dyn DynKernel>` is itself a
+/// `DynKernel`, so dereference it first to reach the boxed kernel, as in
+/// `(*boxed).as_any()`.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::{DMatrix, DVector};
+/// use rv::process::gaussian::kernel::*;
+/// use rv::process::gaussian::{GaussianProcess, NoiseModel};
+///
+/// fn kernel_from_config(name: &str) -> Box<dyn DynKernel> {
+///     match name {
+///         "rbf" => RBFKernel::new(1.0).unwrap().into(),
+///         "periodic" => periodic(1.0, 2.0, 1.0).unwrap().into(),
+///         _ => MaternKernel::new(2.5, 1.0).unwrap().into(),
+///     }
+/// }
+///
+/// let x = DMatrix::from_column_slice(4, 1, &[0.0, 1.0, 2.0, 3.0]);
+/// let y = DVector::from_column_slice(&[0.0, 0.8, 0.9, 0.1]);
+///
+/// let kernel = kernel_from_config("periodic");
+/// assert_eq!(kernel.n_parameters(), 3);
+///
+/// let gp = GaussianProcess::train(kernel, x, y, NoiseModel::Uniform(0.1));
+/// assert!(gp.is_ok());
+/// ```
+pub trait DynKernel: std::fmt::Debug + Send + Sync {
+    /// The number of parameters used in this kernel
+    fn dyn_n_parameters(&self) -> usize;
+
+    /// The covariance matrix between the rows of `x1` and `x2`
+    fn dyn_covariance(
+        &self,
+        x1: &DMatrix<f64>,
+        x2: &DMatrix<f64>,
+    ) -> DMatrix<f64>;
+
+    /// Reports if the kernel is stationary
+    fn dyn_is_stationary(&self) -> bool;
+
+    /// The diagonal of the kernel(x, x)
+    fn dyn_diag(&self, x: &DMatrix<f64>) -> DVector<f64>;
+
+    /// The log-scale parameter vector
+    fn dyn_parameters(&self) -> DVector<f64>;
+
+    /// A new kernel of the same type with the given log-scale parameters
+    fn dyn_reparameterize(
+        &self,
+        params: &[f64],
+    ) -> Result<Box<dyn DynKernel>, KernelError>;
+
+    /// Covariance and gradient with respect to the log-scale parameters
+    fn dyn_covariance_with_gradient(
+        &self,
+        x: &DMatrix<f64>,
+    ) -> Result<(DMatrix<f64>, CovGrad), CovGradError>;
+
+    /// Clone the kernel into a new box
+    fn clone_box(&self) -> Box<dyn DynKernel>;
+
+    /// Whether `other` is a kernel of the same type with the same parameters
+    fn dyn_eq(&self, other: &dyn DynKernel) -> bool;
+
+    /// The kernel as `Any`, for downcasting to its concrete type
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<K> DynKernel for K
+where
+    K: Kernel + Send + Sync + 'static,
+{
+    fn dyn_n_parameters(&self) -> usize {
+        self.n_parameters()
+    }
+
+    fn dyn_covariance(
+        &self,
+        x1: &DMatrix<f64>,
+        x2: &DMatrix<f64>,
+    ) -> DMatrix<f64> {
+        self.covariance(x1, x2)
+    }
+
+    fn dyn_is_stationary(&self) -> bool {
+        self.is_stationary()
+    }
+
+    fn dyn_diag(&self, x: &DMatrix<f64>) -> DVector<f64> {
+        self.diag(x)
+    }
+
+    fn dyn_parameters(&self) -> DVector<f64> {
+        self.parameters()
+    }
+
+    fn dyn_reparameterize(
+        &self,
+        params: &[f64],
+    ) -> Result<Box<dyn DynKernel>, KernelError> {
+        self.reparameterize(params)
+            .map(|k| Box::new(k) as Box<dyn DynKernel>)
+    }
+
+    fn dyn_covariance_with_gradient(
+        &self,
+        x: &DMatrix<f64>,
+    ) -> Result<(DMatrix<f64>, CovGrad), CovGradError> {
+        self.covariance_with_gradient(x)
+    }
+
+    fn clone_box(&self) -> Box<dyn DynKernel> {
+        Box::new(self.clone())
+    }
+
+    fn dyn_eq(&self, other: &dyn DynKernel) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<K>()
+            .is_some_and(|other| self == other)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Copy a matrix of any storage into a `DMatrix`
+fn to_dmatrix<R, C, S>(x: &Matrix<f64, R, C, S>) -> DMatrix<f64>
+where
+    R: Dim,
+    C: Dim,
+    S: Storage<f64, R, C>,
+{
+    DMatrix::from_iterator(x.nrows(), x.ncols(), x.iter().copied())
+}
+
+impl Clone for Box<dyn DynKernel> {
+    fn clone(&self) -> Self {
+        (**self).clone_box()
+    }
+}
+
+impl PartialEq for dyn DynKernel {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+// The inner kernel is reached with `**self` throughout, because the box is
+// itself a `DynKernel` and `self.dyn_*` would call back into this impl.
+impl Kernel for Box<dyn DynKernel> {
+    fn n_parameters(&self) -> usize {
+        (**self).dyn_n_parameters()
+    }
+
+    fn covariance<R1, R2, C1, C2, S1, S2>(
+        &self,
+        x1: &Matrix<f64, R1, C1, S1>,
+        x2: &Matrix<f64, R2, C2, S2>,
+    ) -> DMatrix<f64>
+    where
+        R1: Dim,
+        R2: Dim,
+        C1: Dim,
+        C2: Dim,
+        S1: Storage<f64, R1, C1>,
+        S2: Storage<f64, R2, C2>,
+        ShapeConstraint: SameNumberOfColumns<C1, C2>,
+    {
+        (**self).dyn_covariance(&to_dmatrix(x1), &to_dmatrix(x2))
+    }
+
+    fn is_stationary(&self) -> bool {
+        (**self).dyn_is_stationary()
+    }
+
+    fn diag<R, C, S>(&self, x: &Matrix<f64, R, C, S>) -> DVector<f64>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        (**self).dyn_diag(&to_dmatrix(x))
+    }
+
+    fn parameters(&self) -> DVector<f64> {
+        (**self).dyn_parameters()
+    }
+
+    fn reparameterize(&self, params: &[f64]) -> Result<Self, KernelError> {
+        (**self).dyn_reparameterize(params)
+    }
+
+    fn covariance_with_gradient<R, C, S>(
+        &self,
+        x: &Matrix<f64, R, C, S>,
+    ) -> Result<(DMatrix<f64>, CovGrad), CovGradError>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        (**self).dyn_covariance_with_gradient(&to_dmatrix(x))
+    }
+}
+
+macro_rules! impl_from_kernel {
+    ($($type: ty),*) => {
+        $(impl From<$type> for Box<dyn DynKernel> {
+            fn from(k: $type) -> Self {
+                Box::new(k)
+            }
+        })*
+    };
+}
+
+impl_from_kernel!(
+    ConstantKernel,
+    WhiteKernel,
+    RBFKernel,
+    RationalQuadratic,
+    ExpSineSquaredKernel,
+    MaternKernel,
+    SEardKernel,
+    HammingKernel,
+    SpectralMixtureKernel,
+    KernelNode
+);
+
+macro_rules! impl_from_generic_kernel {
+    ($($type: ident<$($param: ident),+>),*) => {
+        $(impl<$($param),+> From<$type<$($param),+>> for Box<dyn DynKernel>
+        where
+            $($param: Kernel + Send + Sync + 'static),+
+        {
+            fn from(k: $type<$($param),+>) -> Self {
+                Box::new(k)
+            }
+        })*
+    };
+}
+
+impl_from_generic_kernel!(
+    AddKernel<A, B>,
+    ProductKernel<A, B>,
+    ChangePointKernel<A, B>,
+    ActiveDimsKernel<K>,
+    WarpedKernel<K>,
+    CoregionalKernel<K>
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xs() -> DMatrix<f64> {
+        DMatrix::from_row_slice(4, 1, &[-1.0, 0.2, 0.9, 2.5])
+    }
+
+    #[test]
+    fn boxed_kernel_matches_concrete() {
+        let kernel = ConstantKernel::new(2.0).unwrap()
+            * RBFKernel::new(1.5).unwrap()
+            + WhiteKernel::new(0.1).unwrap();
+        let boxed: Box<dyn DynKernel> = kernel.clone().into();
+        let x = xs();
+
+        assert_eq!(boxed.n_parameters(), kernel.n_parameters());
+        assert_eq!(boxed.is_stationary(), kernel.is_stationary());
+        assert_eq!(boxed.parameters(), kernel.parameters());
+        assert_eq!(boxed.covariance(&x, &x), kernel.covariance(&x, &x));
+        assert_eq!(boxed.diag(&x), kernel.diag(&x));
+
+        let (cov, grad) = boxed.covariance_with_gradient(&x).unwrap();
+        let (cov_k, grad_k) = kernel.covariance_with_gradient(&x).unwrap();
+        assert_eq!(cov, cov_k);
+        assert!(grad.relative_eq(&grad_k, 0.0, 0.0));
+
+        // Views of other storage are copied in
+        let row = x.rows(1, 2);
+        assert_eq!(boxed.covariance(&row, &x), kernel.covariance(&row, &x));
+    }
+
+    #[test]
+    fn boxed_kernel_reparameterize_keeps_type() {
+        let boxed: Box<dyn DynKernel> = RBFKernel::new(1.0).unwrap().into();
+        let (new, rest) =
+            boxed.consume_parameters([2.0_f64.ln(), 5.0]).unwrap();
+        assert_eq!(rest.collect::<Vec<_>>(), vec![5.0]);
+        assert_eq!(
+            (*new).as_any().downcast_ref::<RBFKernel>(),
+            Some(&RBFKernel::new(2.0).unwrap())
+        );
+        assert!(boxed.reparameterize(&[]).is_err());
+    }
+
+    #[test]
+    fn boxed_kernel_equality() {
+        let a: Box<dyn DynKernel> = RBFKernel::new(1.0).unwrap().into();
+        let b: Box<dyn DynKernel> = RBFKernel::new(1.0).unwrap().into();
+        let c: Box<dyn DynKernel> = RBFKernel::new(2.0).unwrap().into();
+        let d: Box<dyn DynKernel> = ConstantKernel::new(1.0).unwrap().into();
+        assert!(a == b);
+        assert!(a == a.clone());
+        assert!(a != c);
+        assert!(a != d);
+    }
+}
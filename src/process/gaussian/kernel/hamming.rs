@@ -0,0 +1,212 @@
+use super::{CovGrad, CovGradError, Kernel, KernelError};
+use nalgebra::base::constraint::{SameNumberOfColumns, ShapeConstraint};
+use nalgebra::base::storage::Storage;
+use nalgebra::{DMatrix, DVector, Dim, Matrix};
+use std::f64;
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+/// Hamming kernel over categorical inputs
+///
+/// Each input column holds an integer category code. The covariance decays
+/// with the number of columns in which the categories differ, weighted by a
+/// length scale for each column.
+///
+/// ```math
+///     K(\mathbf{x}, \mathbf{x'}) = \exp\left(-\sum_k \frac{[x_k \ne x'_k]}{l_k}\right)
+/// ```
+///
+/// Combine with [`ActiveDimsKernel`](super::ActiveDimsKernel) to model inputs
+/// with both continuous and categorical columns.
+///
+/// # Parameters
+/// * `l` - Length scale for each dimension.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct HammingKernel {
+    length_scale: DVector<f64>,
+}
+
+/// Whether two category codes differ
+#[inline]
+fn differ(a: f64, b: f64) -> bool {
+    (a - b).abs() >= 0.5
+}
+
+impl HammingKernel {
+    /// Create a new Hamming kernel with the given length scales
+    pub fn new(length_scale: DVector<f64>) -> Result<Self, KernelError> {
+        match length_scale.iter().find(|&&l| l <= 0.0 || !l.is_finite()) {
+            Some(&given) => Err(KernelError::ParameterOutOfBounds {
+                name: "length_scale".to_string(),
+                given,
+                bounds: (0.0, f64::INFINITY),
+            }),
+            None => Ok(Self { length_scale }),
+        }
+    }
+
+    /// Create a new HammingKernel without checking parameters
+    pub fn new_unchecked(length_scale: DVector<f64>) -> Self {
+        Self { length_scale }
+    }
+
+    /// Create a Hamming kernel over `n_dims` columns with unit length scales
+    pub fn with_dims(n_dims: usize) -> Self {
+        Self {
+            length_scale: DVector::from_element(n_dims, 1.0),
+        }
+    }
+
+    /// Length scale for each dimension
+    pub fn length_scale(&self) -> &DVector<f64> {
+        &self.length_scale
+    }
+}
+
+impl Kernel for HammingKernel {
+    fn n_parameters(&self) -> usize {
+        self.length_scale.nrows()
+    }
+
+    fn covariance<R1, R2, C1, C2, S1, S2>(
+        &self,
+        x1: &Matrix<f64, R1, C1, S1>,
+        x2: &Matrix<f64, R2, C2, S2>,
+    ) -> DMatrix<f64>
+    where
+        R1: Dim,
+        R2: Dim,
+        C1: Dim,
+        C2: Dim,
+        S1: Storage<f64, R1, C1>,
+        S2: Storage<f64, R2, C2>,
+        ShapeConstraint: SameNumberOfColumns<C1, C2>,
+    {
+        DMatrix::from_fn(x1.nrows(), x2.nrows(), |i, j| {
+            let d: f64 = self
+                .length_scale
+                .iter()
+                .enumerate()
+                .filter(|&(k, _)| differ(x1[(i, k)], x2[(j, k)]))
+                .map(|(_, l)| l.recip())
+                .sum();
+            (-d).exp()
+        })
+    }
+
+    fn is_stationary(&self) -> bool {
+        true
+    }
+
+    fn diag<R, C, S>(&self, x: &Matrix<f64, R, C, S>) -> DVector<f64>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        DVector::repeat(x.nrows(), 1.0)
+    }
+
+    fn parameters(&self) -> DVector<f64> {
+        self.length_scale.map(|l| l.ln())
+    }
+
+    fn reparameterize(&self, params: &[f64]) -> Result<Self, KernelError> {
+        use std::cmp::Ordering;
+        let n = self.n_parameters();
+        match params.len().cmp(&n) {
+            Ordering::Less => {
+                Err(KernelError::MissingParameters(n - params.len()))
+            }
+            Ordering::Greater => {
+                Err(KernelError::ExtraniousParameters(params.len() - n))
+            }
+            Ordering::Equal => Self::new(DVector::from_iterator(
+                n,
+                params.iter().map(|p| p.exp()),
+            )),
+        }
+    }
+
+    fn covariance_with_gradient<R, C, S>(
+        &self,
+        x: &Matrix<f64, R, C, S>,
+    ) -> Result<(DMatrix<f64>, CovGrad), CovGradError>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        let n = x.nrows();
+        let cov = self.covariance(x, x);
+        let mut grad = CovGrad::zeros(n, self.n_parameters());
+
+        // d/d(ln l_k) exp(-[x_k != x'_k] / l_k) = [x_k != x'_k] / l_k * K
+        for i in 0..n {
+            for j in 0..i {
+                for (k, l) in self.length_scale.iter().enumerate() {
+                    if differ(x[(i, k)], x[(j, k)]) {
+                        let g = cov[(i, j)] / l;
+                        grad[(i, j, k)] = g;
+                        grad[(j, i, k)] = g;
+                    }
+                }
+            }
+        }
+        Ok((cov, grad))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::dmatrix;
+
+    #[test]
+    fn covariance_counts_mismatches() {
+        let kernel =
+            HammingKernel::new(DVector::from_column_slice(&[1.0, 2.0]))
+                .unwrap();
+        let x = dmatrix![0.0, 1.0; 0.0, 2.0; 1.0, 2.0];
+        let cov = kernel.covariance(&x, &x);
+
+        assert::close(cov[(0, 0)], 1.0, 1E-12);
+        assert::close(cov[(0, 1)], (-0.5_f64).exp(), 1E-12);
+        assert::close(cov[(1, 2)], (-1.0_f64).exp(), 1E-12);
+        assert::close(cov[(0, 2)], (-1.5_f64).exp(), 1E-12);
+        assert!(kernel.diag(&x).relative_eq(&cov.diagonal(), 1E-12, 1E-12));
+    }
+
+    #[test]
+    fn invalid_length_scale() {
+        assert!(HammingKernel::new(DVector::from_column_slice(&[1.0, 0.0]))
+            .is_err());
+    }
+
+    #[test]
+    fn gradient_matches_finite_differences() -> Result<(), KernelError> {
+        let kernel =
+            HammingKernel::new(DVector::from_column_slice(&[0.7, 3.0]))
+                .unwrap();
+        let x = dmatrix![0.0, 1.0; 2.0, 1.0; 2.0, 0.0; 0.0, 0.0];
+        let (cov, grad) = kernel.covariance_with_gradient(&x)?;
+        assert!(cov.relative_eq(&kernel.covariance(&x, &x), 1E-12, 1E-12));
+
+        let params = kernel.parameters();
+        let h = 1E-6;
+        for p in 0..params.len() {
+            let mut up = params.clone();
+            up[p] += h;
+            let mut down = params.clone();
+            down[p] -= h;
+            let fd = (kernel.reparameterize(up.as_slice())?.covariance(&x, &x)
+                - kernel.reparameterize(down.as_slice())?.covariance(&x, &x))
+                / (2.0 * h);
+            assert!(fd.relative_eq(&grad[p], 1E-6, 1E-6));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use super::Kernel;
+use nalgebra::{DMatrix, DVector};
+use special::Error as _;
+use std::f64::consts::{PI, SQRT_2};
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+/// Product measure over kernel inputs
+///
+/// Used as the integration measure in
+/// [`BayesianQuadrature`](crate::process::gaussian::BayesianQuadrature).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum QuadratureMeasure {
+    /// Independent normal distribution in each dimension
+    Gaussian {
+        /// Mean of each dimension
+        mean: DVector<f64>,
+        /// Standard deviation of each dimension
+        std: DVector<f64>,
+    },
+    /// Uniform distribution over an axis-aligned box
+    Uniform {
+        /// Lower bound of each dimension
+        lower: DVector<f64>,
+        /// Upper bound of each dimension
+        upper: DVector<f64>,
+    },
+}
+
+impl QuadratureMeasure {
+    /// Number of input dimensions
+    pub fn n_dims(&self) -> usize {
+        match self {
+            Self::Gaussian { mean, .. } => mean.nrows(),
+            Self::Uniform { lower, .. } => lower.nrows(),
+        }
+    }
+}
+
+/// Kernel whose integrals against a [`QuadratureMeasure`] have a closed form
+pub trait IntegrableKernel: Kernel {
+    /// The kernel mean, `∫ k(x, x') dπ(x')`, at each row `x` of `xs`
+    fn kernel_mean(
+        &self,
+        xs: &DMatrix<f64>,
+        measure: &QuadratureMeasure,
+    ) -> DVector<f64>;
+
+    /// The double integral `∫∫ k(x, x') dπ(x) dπ(x')`
+    fn kernel_double_integral(&self, measure: &QuadratureMeasure) -> f64;
+}
+
+/// Kernel mean of `exp(-(x - x')² / 2l²)` in one dimension of `measure`
+fn se_kernel_mean_1d(
+    x: f64,
+    l: f64,
+    measure: &QuadratureMeasure,
+    dim: usize,
+) -> f64 {
+    match measure {
+        QuadratureMeasure::Gaussian { mean, std } => {
+            let s2 = l.mul_add(l, std[dim] * std[dim]);
+            let d = x - mean[dim];
+            l / s2.sqrt() * (-0.5 * d * d / s2).exp()
+        }
+        QuadratureMeasure::Uniform { lower, upper } => {
+            let (a, b) = (lower[dim], upper[dim]);
+            let z = SQRT_2 * l;
+            l * (PI / 2.0).sqrt() / (b - a)
+                * (((b - x) / z).error() - ((a - x) / z).error())
+        }
+    }
+}
+
+/// Double integral of `exp(-(x - x')² / 2l²)` in one dimension of `measure`
+fn se_double_integral_1d(
+    l: f64,
+    measure: &QuadratureMeasure,
+    dim: usize,
+) -> f64 {
+    match measure {
+        QuadratureMeasure::Gaussian { std, .. } => {
+            l / (2.0 * std[dim]).mul_add(std[dim], l * l).sqrt()
+        }
+        QuadratureMeasure::Uniform { lower, upper } => {
+            let w = upper[dim] - lower[dim];
+            let r = w / l;
+            let a = (2.0 * PI).sqrt() * r * (r / SQRT_2).error();
+            let b = 2.0 * (1.0 - (-0.5 * r * r).exp());
+            (a - b) / (r * r)
+        }
+    }
+}
+
+/// Kernel mean of a squared exponential kernel with length scale `l(d)` in
+/// dimension `d`
+pub(crate) fn se_kernel_mean<L>(
+    xs: &DMatrix<f64>,
+    length_scale: L,
+    measure: &QuadratureMeasure,
+) -> DVector<f64>
+where
+    L: Fn(usize) -> f64,
+{
+    assert_eq!(
+        xs.ncols(),
+        measure.n_dims(),
+        "inputs and measure must have the same number of dimensions"
+    );
+    DVector::from_fn(xs.nrows(), |i, _| {
+        (0..xs.ncols())
+            .map(|d| se_kernel_mean_1d(xs[(i, d)], length_scale(d), measure, d))
+            .product()
+    })
+}
+
+/// Double integral of a squared exponential kernel with length scale `l(d)`
+/// in dimension `d`
+pub(crate) fn se_double_integral<L>(
+    length_scale: L,
+    measure: &QuadratureMeasure,
+) -> f64
+where
+    L: Fn(usize) -> f64,
+{
+    (0..measure.n_dims())
+        .map(|d| se_double_integral_1d(length_scale(d), measure, d))
+        .product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::misc::gauss_legendre_quadrature;
+    use crate::process::gaussian::kernel::RBFKernel;
+    use nalgebra::dvector;
+
+    fn uniform() -> QuadratureMeasure {
+        QuadratureMeasure::Uniform {
+            lower: dvector![-1.0],
+            upper: dvector![2.0],
+        }
+    }
+
+    #[test]
+    fn uniform_kernel_mean_matches_quadrature() {
+        let kernel = RBFKernel::new(0.7).unwrap();
+        let xs = DMatrix::from_column_slice(3, 1, &[-1.5, 0.2, 1.9]);
+        let z = kernel.kernel_mean(&xs, &uniform());
+        for (i, x) in xs.iter().enumerate() {
+            let quad = gauss_legendre_quadrature(
+                |y| (-0.5 * (x - y).powi(2) / 0.49).exp() / 3.0,
+                16,
+                (-1.0, 2.0),
+            );
+            assert::close(z[i], quad, 1E-8);
+        }
+    }
+
+    #[test]
+    fn uniform_double_integral_matches_quadrature() {
+        let kernel = RBFKernel::new(0.7).unwrap();
+        let measure = uniform();
+        let quad = gauss_legendre_quadrature(
+            |x| {
+                let xs = DMatrix::from_column_slice(1, 1, &[x]);
+                kernel.kernel_mean(&xs, &measure)[0] / 3.0
+            },
+            16,
+            (-1.0, 2.0),
+        );
+        assert::close(kernel.kernel_double_integral(&measure), quad, 1E-8);
+    }
+
+    #[test]
+    fn gaussian_kernel_mean_matches_quadrature() {
+        let kernel = RBFKernel::new(0.5).unwrap();
+        let measure = QuadratureMeasure::Gaussian {
+            mean: dvector![0.3],
+            std: dvector![0.4],
+        };
+        let pdf = |y: f64| {
+            (-0.5 * ((y - 0.3) / 0.4).powi(2)).exp() / (0.4 * (2.0 * PI).sqrt())
+        };
+        let x = 0.9;
+        let quad = (0..20)
+            .map(|k| {
+                let a = 0.4_f64.mul_add(k as f64, -4.0);
+                gauss_legendre_quadrature(
+                    |y| (-0.5 * (x - y).powi(2) / 0.25).exp() * pdf(y),
+                    16,
+                    (a, a + 0.4),
+                )
+            })
+            .sum::<f64>();
+        let xs = DMatrix::from_column_slice(1, 1, &[x]);
+        assert::close(kernel.kernel_mean(&xs, &measure)[0], quad, 1E-8);
+
+        // ∫∫ k = E[k(X, X')] for independent X, X', here l / √(l² + 2s²)
+        let expected = 0.5 / 0.57_f64.sqrt();
+        assert::close(kernel.kernel_double_integral(&measure), expected, 1E-12);
+    }
+}
@@ -1,10 +1,14 @@
 use crate::misc::bessel::bessel_ikv_temme;
 
-use super::{e2_norm, CovGrad, CovGradError, Kernel, KernelError};
+use super::{
+    e2_norm, CovGrad, CovGradError, Kernel, KernelError, SpectralKernel,
+};
 use nalgebra::base::constraint::{SameNumberOfColumns, ShapeConstraint};
 use nalgebra::base::storage::Storage;
 use nalgebra::{dvector, DMatrix, DVector, Dim, Matrix};
 use peroxide::prelude::gamma;
+use rand::Rng;
+use rand_distr::{ChiSquared, StandardNormal};
 use std::f64;
 
 #[cfg(feature = "serde1")]
@@ -206,6 +210,28 @@ impl Kernel for MaternKernel {
         Ok((cov, grad))
     }
 }
+
+impl SpectralKernel for MaternKernel {
+    /// The spectral density is a multivariate Student's t with `2ν` degrees
+    /// of freedom and scale `1 / l`.
+    fn sample_frequencies<R: Rng>(
+        &self,
+        n_dims: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> DMatrix<f64> {
+        let chi2 = ChiSquared::new(2.0 * self.nu).unwrap();
+        let mut freqs = DMatrix::from_fn(n_dims, n, |_, _| {
+            rng.sample::<f64, _>(StandardNormal) / self.length_scale
+        });
+        freqs.column_iter_mut().for_each(|mut col| {
+            let u: f64 = rng.sample(chi2);
+            col *= (2.0 * self.nu / u).sqrt();
+        });
+        freqs
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -32,6 +32,28 @@ mod seard;
 pub use self::seard::*;
 mod matern;
 pub use self::matern::*;
+mod coregional;
+pub use self::coregional::*;
+mod warped;
+pub use self::warped::*;
+mod change_point;
+pub use self::change_point::*;
+mod hamming;
+pub use self::hamming::*;
+mod active_dims;
+pub use self::active_dims::*;
+mod spectral;
+pub use self::spectral::*;
+mod integrable;
+pub use self::integrable::*;
+mod spectral_mixture;
+pub use self::spectral_mixture::*;
+mod node;
+pub use self::node::*;
+mod dynamic;
+pub use self::dynamic::*;
+mod presets;
+pub use self::presets::*;
 
 /// Kernel Function
 pub trait Kernel: std::fmt::Debug + Clone + PartialEq {
@@ -204,3 +226,7 @@ impl_mul_add!(ExpSineSquaredKernel);
 impl_mul_add!(RationalQuadratic);
 impl_mul_add!(WhiteKernel);
 impl_mul_add!(MaternKernel);
+impl_mul_add!(HammingKernel);
+impl_mul_add!(SpectralMixtureKernel);
+impl_mul_add!(KernelNode);
+impl_mul_add!(Box<dyn DynKernel>);
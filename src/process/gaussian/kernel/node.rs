@@ -0,0 +1,439 @@
+use super::{
+    ActiveDimsKernel, ChangePointKernel, ConstantKernel, CovGrad, CovGradError,
+    ExpSineSquaredKernel, HammingKernel, Kernel, KernelError, MaternKernel,
+    RBFKernel, RationalQuadratic, SEardKernel, SpectralMixtureKernel,
+    WarpedKernel, WhiteKernel,
+};
+use nalgebra::base::constraint::{SameNumberOfColumns, ShapeConstraint};
+use nalgebra::base::storage::Storage;
+use nalgebra::{DMatrix, DVector, Dim, Matrix};
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+/// A kernel tree whose shape is decided at run time
+///
+/// Composing kernels with `+` and `*` builds nested generic types like
+/// `AddKernel<ProductKernel<ConstantKernel, RBFKernel>, WhiteKernel>`,
+/// which must be known at compile time. A `KernelNode` holds the same tree
+/// as a tagged enum, so kernels of different shapes can be stored together,
+/// built from configuration, and serialized. Any composed kernel converts
+/// into a `KernelNode` with `into()`, and nested sums and products are
+/// flattened.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::DMatrix;
+/// use rv::process::gaussian::kernel::*;
+///
+/// let kernel = ConstantKernel::new(2.0).unwrap() * RBFKernel::new(1.5).unwrap()
+///     + WhiteKernel::new(0.1).unwrap();
+/// let node: KernelNode = kernel.clone().into();
+///
+/// let x = DMatrix::from_column_slice(3, 1, &[0.0, 0.5, 2.0]);
+/// assert_eq!(node.covariance(&x, &x), kernel.covariance(&x, &x));
+/// assert_eq!(node.parameters(), kernel.parameters());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum KernelNode {
+    Constant(ConstantKernel),
+    White(WhiteKernel),
+    Rbf(RBFKernel),
+    RationalQuadratic(RationalQuadratic),
+    ExpSineSquared(ExpSineSquaredKernel),
+    Matern(MaternKernel),
+    SEard(SEardKernel),
+    Hamming(HammingKernel),
+    SpectralMixture(SpectralMixtureKernel),
+    /// Sum of the kernels. The empty sum is zero.
+    Sum(Vec<KernelNode>),
+    /// Product of the kernels. The empty product is one.
+    Product(Vec<KernelNode>),
+    ActiveDims(Box<ActiveDimsKernel<KernelNode>>),
+    Warped(Box<WarpedKernel<KernelNode>>),
+    ChangePoint(Box<ChangePointKernel<KernelNode, KernelNode>>),
+}
+
+/// Apply `$f` to the kernel in a non-composite node, or evaluate `$sum` or
+/// `$prod` on the children of a sum or product
+macro_rules! dispatch {
+    ($self: expr, $k: ident => $f: expr, $ks: ident => sum: $sum: expr, prod: $prod: expr) => {
+        match $self {
+            KernelNode::Constant($k) => $f,
+            KernelNode::White($k) => $f,
+            KernelNode::Rbf($k) => $f,
+            KernelNode::RationalQuadratic($k) => $f,
+            KernelNode::ExpSineSquared($k) => $f,
+            KernelNode::Matern($k) => $f,
+            KernelNode::SEard($k) => $f,
+            KernelNode::Hamming($k) => $f,
+            KernelNode::SpectralMixture($k) => $f,
+            KernelNode::ActiveDims($k) => $f,
+            KernelNode::Warped($k) => $f,
+            KernelNode::ChangePoint($k) => $f,
+            KernelNode::Sum($ks) => $sum,
+            KernelNode::Product($ks) => $prod,
+        }
+    };
+}
+
+/// Rebuild a non-composite node from its reparameterized kernel
+macro_rules! reparameterize_leaf {
+    ($self: expr, $params: expr, $($variant: ident),*) => {
+        match $self {
+            $(KernelNode::$variant(k) => {
+                k.reparameterize($params).map(KernelNode::$variant)
+            })*
+            KernelNode::ActiveDims(k) => k
+                .reparameterize($params)
+                .map(|k| KernelNode::ActiveDims(Box::new(k))),
+            KernelNode::Warped(k) => k
+                .reparameterize($params)
+                .map(|k| KernelNode::Warped(Box::new(k))),
+            KernelNode::ChangePoint(k) => k
+                .reparameterize($params)
+                .map(|k| KernelNode::ChangePoint(Box::new(k))),
+            KernelNode::Sum(_) | KernelNode::Product(_) => unreachable!(),
+        }
+    };
+}
+
+impl KernelNode {
+    /// The sum of `nodes`, with nested sums flattened
+    pub fn sum(nodes: Vec<KernelNode>) -> Self {
+        Self::Sum(
+            nodes
+                .into_iter()
+                .flat_map(|node| match node {
+                    Self::Sum(children) => children,
+                    other => vec![other],
+                })
+                .collect(),
+        )
+    }
+
+    /// The product of `nodes`, with nested products flattened
+    pub fn product(nodes: Vec<KernelNode>) -> Self {
+        Self::Product(
+            nodes
+                .into_iter()
+                .flat_map(|node| match node {
+                    Self::Product(children) => children,
+                    other => vec![other],
+                })
+                .collect(),
+        )
+    }
+
+    /// Split `params` into the parameters of each child and reparameterize
+    /// them in turn
+    fn reparameterize_children(
+        children: &[KernelNode],
+        params: &[f64],
+    ) -> Result<Vec<KernelNode>, KernelError> {
+        let n: usize = children.iter().map(|k| k.n_parameters()).sum();
+        if params.len() < n {
+            return Err(KernelError::MissingParameters(n - params.len()));
+        } else if params.len() > n {
+            return Err(KernelError::ExtraniousParameters(params.len() - n));
+        }
+        let mut rest = params;
+        children
+            .iter()
+            .map(|k| {
+                let (head, tail) = rest.split_at(k.n_parameters());
+                rest = tail;
+                k.reparameterize(head)
+            })
+            .collect()
+    }
+}
+
+impl Kernel for KernelNode {
+    fn n_parameters(&self) -> usize {
+        dispatch!(self, k => k.n_parameters(), ks =>
+            sum: ks.iter().map(|k| k.n_parameters()).sum(),
+            prod: ks.iter().map(|k| k.n_parameters()).sum()
+        )
+    }
+
+    fn covariance<R1, R2, C1, C2, S1, S2>(
+        &self,
+        x1: &Matrix<f64, R1, C1, S1>,
+        x2: &Matrix<f64, R2, C2, S2>,
+    ) -> DMatrix<f64>
+    where
+        R1: Dim,
+        R2: Dim,
+        C1: Dim,
+        C2: Dim,
+        S1: Storage<f64, R1, C1>,
+        S2: Storage<f64, R2, C2>,
+        ShapeConstraint: SameNumberOfColumns<C1, C2>,
+    {
+        let (m, n) = (x1.nrows(), x2.nrows());
+        dispatch!(self, k => k.covariance(x1, x2), ks =>
+            sum: ks.iter().fold(DMatrix::zeros(m, n), |acc, k| {
+                acc + k.covariance(x1, x2)
+            }),
+            prod: ks.iter().fold(DMatrix::repeat(m, n, 1.0), |acc, k| {
+                acc.component_mul(&k.covariance(x1, x2))
+            })
+        )
+    }
+
+    fn is_stationary(&self) -> bool {
+        dispatch!(self, k => k.is_stationary(), ks =>
+            sum: ks.iter().all(|k| k.is_stationary()),
+            prod: ks.iter().all(|k| k.is_stationary())
+        )
+    }
+
+    fn diag<R, C, S>(&self, x: &Matrix<f64, R, C, S>) -> DVector<f64>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        let n = x.nrows();
+        dispatch!(self, k => k.diag(x), ks =>
+            sum: ks.iter().fold(DVector::zeros(n), |acc, k| acc + k.diag(x)),
+            prod: ks.iter().fold(DVector::repeat(n, 1.0), |acc, k| {
+                acc.component_mul(&k.diag(x))
+            })
+        )
+    }
+
+    fn parameters(&self) -> DVector<f64> {
+        let concat = |ks: &[KernelNode]| {
+            DVector::from_iterator(
+                self.n_parameters(),
+                ks.iter().flat_map(|k| {
+                    k.parameters().into_iter().copied().collect::<Vec<f64>>()
+                }),
+            )
+        };
+        dispatch!(self, k => k.parameters(), ks =>
+            sum: concat(ks),
+            prod: concat(ks)
+        )
+    }
+
+    fn reparameterize(&self, params: &[f64]) -> Result<Self, KernelError> {
+        match self {
+            Self::Sum(ks) => {
+                Self::reparameterize_children(ks, params).map(Self::Sum)
+            }
+            Self::Product(ks) => {
+                Self::reparameterize_children(ks, params).map(Self::Product)
+            }
+            _ => reparameterize_leaf!(
+                self,
+                params,
+                Constant,
+                White,
+                Rbf,
+                RationalQuadratic,
+                ExpSineSquared,
+                Matern,
+                SEard,
+                Hamming,
+                SpectralMixture
+            ),
+        }
+    }
+
+    fn covariance_with_gradient<R, C, S>(
+        &self,
+        x: &Matrix<f64, R, C, S>,
+    ) -> Result<(DMatrix<f64>, CovGrad), CovGradError>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        let n = x.nrows();
+        let children = |ks: &[KernelNode]| {
+            ks.iter()
+                .map(|k| k.covariance_with_gradient(x))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let concat = |grads: Vec<CovGrad>| {
+            grads.into_iter().try_fold(CovGrad::zeros(n, 0), |acc, g| {
+                if acc.is_empty() {
+                    Ok(g)
+                } else if g.is_empty() {
+                    Ok(acc)
+                } else {
+                    acc.concat_cols(&g)
+                }
+            })
+        };
+
+        dispatch!(self, k => k.covariance_with_gradient(x), ks =>
+            sum: {
+                let (covs, grads): (Vec<_>, Vec<_>) =
+                    children(ks)?.into_iter().unzip();
+                let cov = covs
+                    .into_iter()
+                    .fold(DMatrix::zeros(n, n), |acc, c| acc + c);
+                Ok((cov, concat(grads)?))
+            },
+            prod: {
+                let (covs, grads): (Vec<_>, Vec<_>) =
+                    children(ks)?.into_iter().unzip();
+                // The gradient of each factor times the product of the rest
+                let grads = grads
+                    .iter()
+                    .enumerate()
+                    .map(|(i, g)| {
+                        let others = covs
+                            .iter()
+                            .enumerate()
+                            .filter(|&(j, _)| j != i)
+                            .fold(DMatrix::repeat(n, n, 1.0), |acc, (_, c)| {
+                                acc.component_mul(c)
+                            });
+                        if g.is_empty() {
+                            Ok(g.clone())
+                        } else {
+                            g.component_mul(&others)
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let cov = covs
+                    .iter()
+                    .fold(DMatrix::repeat(n, n, 1.0), |acc, c| {
+                        acc.component_mul(c)
+                    });
+                Ok((cov, concat(grads)?))
+            }
+        )
+    }
+}
+
+macro_rules! impl_from_leaf {
+    ($($type: ty => $variant: ident),*) => {
+        $(impl From<$type> for KernelNode {
+            fn from(k: $type) -> Self {
+                KernelNode::$variant(k)
+            }
+        })*
+    };
+}
+
+impl_from_leaf!(
+    ConstantKernel => Constant,
+    WhiteKernel => White,
+    RBFKernel => Rbf,
+    RationalQuadratic => RationalQuadratic,
+    ExpSineSquaredKernel => ExpSineSquared,
+    MaternKernel => Matern,
+    SEardKernel => SEard,
+    HammingKernel => Hamming,
+    SpectralMixtureKernel => SpectralMixture
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xs() -> DMatrix<f64> {
+        DMatrix::from_row_slice(4, 1, &[-1.0, 0.2, 0.9, 2.5])
+    }
+
+    fn composed() -> impl Kernel + Into<KernelNode> {
+        ConstantKernel::new(1.5).unwrap()
+            * RBFKernel::new(0.8).unwrap()
+            * ExpSineSquaredKernel::new(1.2, 2.0).unwrap()
+            + WhiteKernel::new(0.3).unwrap()
+            + RationalQuadratic::new(0.7, 2.0).unwrap()
+    }
+
+    #[test]
+    fn conversion_flattens_and_preserves_kernel() {
+        let kernel = composed();
+        let node: KernelNode = kernel.clone().into();
+        match &node {
+            KernelNode::Sum(ks) => {
+                assert_eq!(ks.len(), 3);
+                assert!(
+                    matches!(&ks[0], KernelNode::Product(fs) if fs.len() == 3)
+                );
+            }
+            _ => panic!("expected a sum"),
+        }
+
+        let x = xs();
+        assert!(node.covariance(&x, &x).relative_eq(
+            &kernel.covariance(&x, &x),
+            1E-12,
+            1E-12
+        ));
+        assert_eq!(node.diag(&x), kernel.diag(&x));
+        assert_eq!(node.parameters(), kernel.parameters());
+        assert_eq!(node.n_parameters(), kernel.n_parameters());
+        assert!(node.is_stationary());
+    }
+
+    #[test]
+    fn gradient_matches_composed_kernel() {
+        let kernel = composed();
+        let node: KernelNode = kernel.clone().into();
+        let x = xs();
+        let (cov, grad) = node.covariance_with_gradient(&x).unwrap();
+        let (cov_k, grad_k) = kernel.covariance_with_gradient(&x).unwrap();
+        assert!(cov.relative_eq(&cov_k, 1E-12, 1E-12));
+        assert!(grad.relative_eq(&grad_k, 1E-12, 1E-12));
+    }
+
+    #[test]
+    fn reparameterize_round_trips() {
+        let node: KernelNode = composed().into();
+        let params = node.parameters().map(|p| p + 0.1);
+        let node2 = node.reparameterize(params.as_slice()).unwrap();
+        assert::close(node2.parameters().as_slice(), params.as_slice(), 1E-12);
+        assert!(matches!(
+            node.reparameterize(&params.as_slice()[1..]),
+            Err(KernelError::MissingParameters(1))
+        ));
+    }
+
+    #[test]
+    fn wrappers_convert() {
+        let base =
+            RBFKernel::new(1.0).unwrap() + WhiteKernel::new(0.1).unwrap();
+        let kernel = ActiveDimsKernel::new(base, vec![1]);
+        let node: KernelNode = kernel.clone().into();
+        let x = DMatrix::from_row_slice(2, 2, &[0.0, 1.0, 5.0, 2.0]);
+        assert!(node.covariance(&x, &x).relative_eq(
+            &kernel.covariance(&x, &x),
+            1E-12,
+            1E-12
+        ));
+        assert_eq!(node.parameters(), kernel.parameters());
+    }
+
+    #[test]
+    fn empty_sum_and_product() {
+        let x = xs();
+        assert_eq!(
+            KernelNode::sum(vec![]).covariance(&x, &x),
+            DMatrix::zeros(4, 4)
+        );
+        assert_eq!(
+            KernelNode::product(vec![]).diag(&x),
+            DVector::repeat(4, 1.0)
+        );
+        let (_, grad) = KernelNode::product(vec![
+            KernelNode::product(vec![]),
+            RBFKernel::default().into(),
+        ])
+        .covariance_with_gradient(&x)
+        .unwrap();
+        assert_eq!(grad.len(), 1);
+    }
+}
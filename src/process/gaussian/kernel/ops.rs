@@ -1,7 +1,11 @@
-use super::{CovGrad, CovGradError, Kernel, KernelError};
+use super::{
+    ConstantKernel, CovGrad, CovGradError, IntegrableKernel, Kernel,
+    KernelError, KernelNode, QuadratureMeasure, SpectralKernel,
+};
 use nalgebra::base::constraint::{SameNumberOfColumns, ShapeConstraint};
 use nalgebra::base::storage::Storage;
 use nalgebra::{DMatrix, DVector, Dim, Matrix};
+use rand::Rng;
 use std::f64;
 
 #[cfg(feature = "serde1")]
@@ -272,6 +276,61 @@ where
     }
 }
 
+impl<K> SpectralKernel for ProductKernel<ConstantKernel, K>
+where
+    K: SpectralKernel,
+{
+    fn variance(&self) -> f64 {
+        self.a.value() * self.b.variance()
+    }
+
+    fn sample_frequencies<R: Rng>(
+        &self,
+        n_dims: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> DMatrix<f64> {
+        self.b.sample_frequencies(n_dims, n, rng)
+    }
+}
+
+impl<K> IntegrableKernel for ProductKernel<ConstantKernel, K>
+where
+    K: IntegrableKernel,
+{
+    fn kernel_mean(
+        &self,
+        xs: &DMatrix<f64>,
+        measure: &QuadratureMeasure,
+    ) -> DVector<f64> {
+        self.b.kernel_mean(xs, measure) * self.a.value()
+    }
+
+    fn kernel_double_integral(&self, measure: &QuadratureMeasure) -> f64 {
+        self.a.value() * self.b.kernel_double_integral(measure)
+    }
+}
+
+impl<A, B> From<AddKernel<A, B>> for KernelNode
+where
+    A: Kernel + Into<KernelNode>,
+    B: Kernel + Into<KernelNode>,
+{
+    fn from(k: AddKernel<A, B>) -> Self {
+        KernelNode::sum(vec![k.a.into(), k.b.into()])
+    }
+}
+
+impl<A, B> From<ProductKernel<A, B>> for KernelNode
+where
+    A: Kernel + Into<KernelNode>,
+    B: Kernel + Into<KernelNode>,
+{
+    fn from(k: ProductKernel<A, B>) -> Self {
+        KernelNode::product(vec![k.a.into(), k.b.into()])
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::process::gaussian::kernel::{
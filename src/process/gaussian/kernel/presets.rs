@@ -0,0 +1,119 @@
+use super::{
+    ConstantKernel, ExpSineSquaredKernel, KernelError, KernelNode,
+    ProductKernel, RBFKernel,
+};
+
+/// A scaled periodic kernel, `σ² k_per(x, x')`
+pub type PeriodicKernel = ProductKernel<ConstantKernel, ExpSineSquaredKernel>;
+
+/// A scaled periodic kernel whose pattern drifts, `σ² k_rbf(x, x') k_per(x,
+/// x')`
+pub type LocallyPeriodicKernel = ProductKernel<
+    ProductKernel<ConstantKernel, RBFKernel>,
+    ExpSineSquaredKernel,
+>;
+
+/// A periodic kernel with the given `variance`, `period`, and
+/// `periodic_length_scale`, which sets how smooth each period is
+pub fn periodic(
+    variance: f64,
+    period: f64,
+    periodic_length_scale: f64,
+) -> Result<PeriodicKernel, KernelError> {
+    Ok(ConstantKernel::new(variance)?
+        * ExpSineSquaredKernel::new(periodic_length_scale, period)?)
+}
+
+/// A locally periodic kernel: a periodic kernel multiplied by an RBF kernel
+/// so that the repeating pattern can change over distances of about
+/// `length_scale`
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::DMatrix;
+/// use rv::process::gaussian::kernel::{locally_periodic, Kernel};
+///
+/// let kernel = locally_periodic(1.0, 10.0, 2.0, 1.0).unwrap();
+/// let x = DMatrix::from_column_slice(3, 1, &[0.0, 2.0, 20.0]);
+/// let cov = kernel.covariance(&x, &x);
+///
+/// // A full period apart is correlated nearby, but not far away
+/// assert!(cov[(0, 1)] > 0.9);
+/// assert!(cov[(0, 2)] < 0.2);
+/// ```
+pub fn locally_periodic(
+    variance: f64,
+    length_scale: f64,
+    period: f64,
+    periodic_length_scale: f64,
+) -> Result<LocallyPeriodicKernel, KernelError> {
+    Ok(ConstantKernel::new(variance)?
+        * RBFKernel::new(length_scale)?
+        * ExpSineSquaredKernel::new(periodic_length_scale, period)?)
+}
+
+/// A quasi-periodic kernel: the sum of a unit-variance locally periodic
+/// kernel for each of `periods`, sharing `length_scale` and
+/// `periodic_length_scale` as starting values for optimization
+///
+/// The number of terms is only known at run time, so the sum is returned as
+/// a [`KernelNode`].
+pub fn quasi_periodic(
+    periods: &[f64],
+    length_scale: f64,
+    periodic_length_scale: f64,
+) -> Result<KernelNode, KernelError> {
+    if periods.is_empty() {
+        return Err(KernelError::MissingParameters(1));
+    }
+    periods
+        .iter()
+        .map(|&period| {
+            locally_periodic(1.0, length_scale, period, periodic_length_scale)
+                .map(KernelNode::from)
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(KernelNode::sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::gaussian::kernel::Kernel;
+    use nalgebra::DMatrix;
+
+    #[test]
+    fn periodic_repeats() {
+        let kernel = periodic(2.0, 3.0, 1.0).unwrap();
+        let x = DMatrix::from_column_slice(3, 1, &[0.0, 3.0, 30.0]);
+        let cov = kernel.covariance(&x, &x);
+        assert::close(cov[(0, 1)], 2.0, 1E-12);
+        assert::close(cov[(0, 2)], 2.0, 1E-9);
+    }
+
+    #[test]
+    fn locally_periodic_decays() {
+        let kernel = locally_periodic(2.0, 5.0, 3.0, 1.0).unwrap();
+        let x = DMatrix::from_column_slice(2, 1, &[0.0, 6.0]);
+        let cov = kernel.covariance(&x, &x);
+        // Two full periods apart, so only the RBF envelope remains
+        assert::close(cov[(0, 1)], 2.0 * (-0.5 * 36.0 / 25.0_f64).exp(), 1E-9);
+    }
+
+    #[test]
+    fn quasi_periodic_sums_terms() {
+        let node = quasi_periodic(&[2.0, 7.0], 10.0, 1.0).unwrap();
+        assert_eq!(node.n_parameters(), 2 * 4);
+        let x = DMatrix::from_column_slice(2, 1, &[0.0, 14.0]);
+        let expected = locally_periodic(1.0, 10.0, 2.0, 1.0)
+            .unwrap()
+            .covariance(&x, &x)
+            + locally_periodic(1.0, 10.0, 7.0, 1.0)
+                .unwrap()
+                .covariance(&x, &x);
+        assert!(node.covariance(&x, &x).relative_eq(&expected, 1E-12, 1E-12));
+        assert!(quasi_periodic(&[], 1.0, 1.0).is_err());
+        assert!(quasi_periodic(&[-1.0], 1.0, 1.0).is_err());
+    }
+}
@@ -1,7 +1,12 @@
-use super::{e2_norm, CovGrad, CovGradError, Kernel, KernelError};
+use super::{
+    e2_norm, se_double_integral, se_kernel_mean, CovGrad, CovGradError,
+    IntegrableKernel, Kernel, KernelError, QuadratureMeasure, SpectralKernel,
+};
 use nalgebra::base::constraint::{SameNumberOfColumns, ShapeConstraint};
 use nalgebra::base::storage::Storage;
 use nalgebra::{dvector, DMatrix, DVector, Dim, Matrix};
+use rand::Rng;
+use rand_distr::StandardNormal;
 use std::f64;
 
 #[cfg(feature = "serde1")]
@@ -145,6 +150,33 @@ impl Kernel for RBFKernel {
     }
 }
 
+impl SpectralKernel for RBFKernel {
+    fn sample_frequencies<R: Rng>(
+        &self,
+        n_dims: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> DMatrix<f64> {
+        DMatrix::from_fn(n_dims, n, |_, _| {
+            rng.sample::<f64, _>(StandardNormal) / self.length_scale
+        })
+    }
+}
+
+impl IntegrableKernel for RBFKernel {
+    fn kernel_mean(
+        &self,
+        xs: &DMatrix<f64>,
+        measure: &QuadratureMeasure,
+    ) -> DVector<f64> {
+        se_kernel_mean(xs, |_| self.length_scale, measure)
+    }
+
+    fn kernel_double_integral(&self, measure: &QuadratureMeasure) -> f64 {
+        se_double_integral(|_| self.length_scale, measure)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,7 +1,12 @@
-use super::{CovGrad, CovGradError, Kernel, KernelError};
+use super::{
+    se_double_integral, se_kernel_mean, CovGrad, CovGradError,
+    IntegrableKernel, Kernel, KernelError, QuadratureMeasure, SpectralKernel,
+};
 use nalgebra::base::constraint::{SameNumberOfColumns, ShapeConstraint};
 use nalgebra::base::storage::Storage;
 use nalgebra::{DMatrix, DVector, Dim, Matrix};
+use rand::Rng;
+use rand_distr::StandardNormal;
 use std::f64;
 
 #[cfg(feature = "serde1")]
@@ -170,3 +175,51 @@ impl Kernel for SEardKernel {
         self.length_scale.nrows()
     }
 }
+
+impl SpectralKernel for SEardKernel {
+    /// # Panics
+    /// If `n_dims` does not match the number of length scales
+    fn sample_frequencies<R: Rng>(
+        &self,
+        n_dims: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> DMatrix<f64> {
+        assert_eq!(
+            n_dims,
+            self.length_scale.nrows(),
+            "one length scale is required per dimension"
+        );
+        DMatrix::from_fn(n_dims, n, |i, _| {
+            rng.sample::<f64, _>(StandardNormal) / self.length_scale[i]
+        })
+    }
+}
+
+impl IntegrableKernel for SEardKernel {
+    /// # Panics
+    /// If the measure does not have one dimension per length scale
+    fn kernel_mean(
+        &self,
+        xs: &DMatrix<f64>,
+        measure: &QuadratureMeasure,
+    ) -> DVector<f64> {
+        assert_eq!(
+            measure.n_dims(),
+            self.length_scale.nrows(),
+            "one length scale is required per dimension"
+        );
+        se_kernel_mean(xs, |d| self.length_scale[d], measure)
+    }
+
+    /// # Panics
+    /// If the measure does not have one dimension per length scale
+    fn kernel_double_integral(&self, measure: &QuadratureMeasure) -> f64 {
+        assert_eq!(
+            measure.n_dims(),
+            self.length_scale.nrows(),
+            "one length scale is required per dimension"
+        );
+        se_double_integral(|d| self.length_scale[d], measure)
+    }
+}
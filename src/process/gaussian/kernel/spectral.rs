@@ -0,0 +1,27 @@
+use super::Kernel;
+use nalgebra::DMatrix;
+use rand::Rng;
+
+/// Stationary kernel whose spectral density can be sampled
+///
+/// By Bochner's theorem a stationary kernel is the Fourier transform of a
+/// non-negative measure. Scaled to a probability distribution, samples from
+/// this measure give a Monte Carlo approximation of the kernel, which is
+/// what [`RandomFourierFeatures`](crate::process::gaussian::RandomFourierFeatures)
+/// builds on.
+pub trait SpectralKernel: Kernel {
+    /// Variance of the kernel, `k(x, x)`
+    fn variance(&self) -> f64 {
+        1.0
+    }
+
+    /// Draw `n` frequencies for `n_dims`-dimensional inputs from the
+    /// normalized spectral density. The frequencies are the columns of the
+    /// returned `n_dims` by `n` matrix.
+    fn sample_frequencies<R: Rng>(
+        &self,
+        n_dims: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> DMatrix<f64>;
+}
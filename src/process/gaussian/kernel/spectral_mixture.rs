@@ -0,0 +1,394 @@
+use super::{CovGrad, CovGradError, Kernel, KernelError, SpectralKernel};
+use nalgebra::base::constraint::{SameNumberOfColumns, ShapeConstraint};
+use nalgebra::base::storage::Storage;
+use nalgebra::{DMatrix, DVector, Dim, Matrix, RowDVector};
+use rand::Rng;
+use rand_distr::StandardNormal;
+use std::f64;
+use std::f64::consts::PI;
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+/// Spectral mixture kernel (Wilson & Adams, 2013)
+///
+/// The spectral density is a mixture of Gaussians at frequencies `±μ_q`
+/// with variances `v_q`, which gives
+///
+/// ```math
+///     k(\mathbf{x}, \mathbf{x'}) = \sum_q w_q \exp\left(-2\pi^2 v_q \|\boldsymbol{\tau}\|^2\right) \prod_p \cos(2\pi \mu_q \tau_p)
+/// ```
+///
+/// where `τ = x - x'`. With enough components it can approximate any
+/// stationary kernel, and the fitted means give the dominant periods,
+/// `1 / μ_q`, of the data.
+///
+/// # Parameters
+/// * `weights` - Variance contributed by each component.
+/// * `means` - Mean frequency of each component.
+/// * `variances` - Variance of the frequency of each component.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct SpectralMixtureKernel {
+    weights: DVector<f64>,
+    means: DVector<f64>,
+    variances: DVector<f64>,
+}
+
+fn check_positive(name: &str, xs: &DVector<f64>) -> Result<(), KernelError> {
+    match xs.iter().find(|&&x| x <= 0.0 || !x.is_finite()) {
+        Some(&given) => Err(KernelError::ParameterOutOfBounds {
+            name: name.to_string(),
+            given,
+            bounds: (0.0, f64::INFINITY),
+        }),
+        None => Ok(()),
+    }
+}
+
+impl SpectralMixtureKernel {
+    /// Create a new spectral mixture kernel with one component per entry of
+    /// `weights`, `means`, and `variances`
+    pub fn new(
+        weights: DVector<f64>,
+        means: DVector<f64>,
+        variances: DVector<f64>,
+    ) -> Result<Self, KernelError> {
+        let q = weights.len();
+        if q == 0 {
+            return Err(KernelError::MissingParameters(3));
+        } else if means.len() != q {
+            return Err(KernelError::ParameterOutOfBounds {
+                name: "means.len()".to_string(),
+                given: means.len() as f64,
+                bounds: (q as f64, q as f64),
+            });
+        } else if variances.len() != q {
+            return Err(KernelError::ParameterOutOfBounds {
+                name: "variances.len()".to_string(),
+                given: variances.len() as f64,
+                bounds: (q as f64, q as f64),
+            });
+        }
+        check_positive("weights", &weights)?;
+        check_positive("means", &means)?;
+        check_positive("variances", &variances)?;
+        Ok(Self {
+            weights,
+            means,
+            variances,
+        })
+    }
+
+    /// Create a new SpectralMixtureKernel without checking the parameters
+    pub fn new_unchecked(
+        weights: DVector<f64>,
+        means: DVector<f64>,
+        variances: DVector<f64>,
+    ) -> Self {
+        Self {
+            weights,
+            means,
+            variances,
+        }
+    }
+
+    /// The number of mixture components
+    pub fn n_components(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Variance contributed by each component
+    pub fn weights(&self) -> &DVector<f64> {
+        &self.weights
+    }
+
+    /// Mean frequency of each component
+    pub fn means(&self) -> &DVector<f64> {
+        &self.means
+    }
+
+    /// Variance of the frequency of each component
+    pub fn variances(&self) -> &DVector<f64> {
+        &self.variances
+    }
+
+    /// The contribution of component `q` at offset `tau`
+    fn component(&self, q: usize, tau: &RowDVector<f64>) -> f64 {
+        let envelope =
+            (-2.0 * PI * PI * self.variances[q] * tau.norm_squared()).exp();
+        let wave: f64 = tau
+            .iter()
+            .map(|t| (2.0 * PI * self.means[q] * t).cos())
+            .product();
+        self.weights[q] * envelope * wave
+    }
+}
+
+impl Kernel for SpectralMixtureKernel {
+    fn n_parameters(&self) -> usize {
+        3 * self.n_components()
+    }
+
+    fn covariance<R1, R2, C1, C2, S1, S2>(
+        &self,
+        x1: &Matrix<f64, R1, C1, S1>,
+        x2: &Matrix<f64, R2, C2, S2>,
+    ) -> DMatrix<f64>
+    where
+        R1: Dim,
+        R2: Dim,
+        C1: Dim,
+        C2: Dim,
+        S1: Storage<f64, R1, C1>,
+        S2: Storage<f64, R2, C2>,
+        ShapeConstraint: SameNumberOfColumns<C1, C2>,
+    {
+        DMatrix::from_fn(x1.nrows(), x2.nrows(), |i, j| {
+            let tau = RowDVector::from_iterator(
+                x1.ncols(),
+                x1.row(i).iter().zip(x2.row(j).iter()).map(|(a, b)| a - b),
+            );
+            (0..self.n_components())
+                .map(|q| self.component(q, &tau))
+                .sum()
+        })
+    }
+
+    fn is_stationary(&self) -> bool {
+        true
+    }
+
+    fn diag<R, C, S>(&self, x: &Matrix<f64, R, C, S>) -> DVector<f64>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        DVector::repeat(x.nrows(), self.weights.sum())
+    }
+
+    /// The log weights, then the log means, then the log variances
+    fn parameters(&self) -> DVector<f64> {
+        DVector::from_iterator(
+            self.n_parameters(),
+            self.weights
+                .iter()
+                .chain(self.means.iter())
+                .chain(self.variances.iter())
+                .map(|x| x.ln()),
+        )
+    }
+
+    fn reparameterize(&self, params: &[f64]) -> Result<Self, KernelError> {
+        let n = self.n_parameters();
+        if params.len() < n {
+            return Err(KernelError::MissingParameters(n - params.len()));
+        } else if params.len() > n {
+            return Err(KernelError::ExtraniousParameters(params.len() - n));
+        }
+        let q = self.n_components();
+        let block = |k: usize| {
+            DVector::from_iterator(
+                q,
+                params[k * q..(k + 1) * q].iter().map(|p| p.exp()),
+            )
+        };
+        Self::new(block(0), block(1), block(2))
+    }
+
+    fn covariance_with_gradient<R, C, S>(
+        &self,
+        x: &Matrix<f64, R, C, S>,
+    ) -> Result<(DMatrix<f64>, CovGrad), CovGradError>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        let n = x.nrows();
+        let q = self.n_components();
+        let mut cov = DMatrix::zeros(n, n);
+        let mut grad = CovGrad::zeros(n, 3 * q);
+
+        for i in 0..n {
+            for j in 0..=i {
+                let tau = RowDVector::from_iterator(
+                    x.ncols(),
+                    x.row(i).iter().zip(x.row(j).iter()).map(|(a, b)| a - b),
+                );
+                let t2 = tau.norm_squared();
+                for k in 0..q {
+                    let (w, mu, v) =
+                        (self.weights[k], self.means[k], self.variances[k]);
+                    let envelope = (-2.0 * PI * PI * v * t2).exp();
+                    let cosines: Vec<f64> =
+                        tau.iter().map(|t| (2.0 * PI * mu * t).cos()).collect();
+                    let wave: f64 = cosines.iter().product();
+                    let term = w * envelope * wave;
+
+                    // d wave / d ln(mu), one cosine differentiated at a time
+                    let dwave: f64 = tau
+                        .iter()
+                        .enumerate()
+                        .map(|(p, t)| {
+                            let others: f64 = cosines
+                                .iter()
+                                .enumerate()
+                                .filter(|&(r, _)| r != p)
+                                .map(|(_, c)| c)
+                                .product();
+                            -2.0 * PI
+                                * mu
+                                * t
+                                * (2.0 * PI * mu * t).sin()
+                                * others
+                        })
+                        .sum();
+
+                    let d_w = term;
+                    let d_mu = w * envelope * dwave;
+                    let d_v = -2.0 * PI * PI * v * t2 * term;
+
+                    cov[(i, j)] += term;
+                    grad[(i, j, k)] = d_w;
+                    grad[(i, j, q + k)] = d_mu;
+                    grad[(i, j, 2 * q + k)] = d_v;
+                    if i != j {
+                        cov[(j, i)] += term;
+                        grad[(j, i, k)] = d_w;
+                        grad[(j, i, q + k)] = d_mu;
+                        grad[(j, i, 2 * q + k)] = d_v;
+                    }
+                }
+            }
+        }
+
+        Ok((cov, grad))
+    }
+}
+
+impl SpectralKernel for SpectralMixtureKernel {
+    fn variance(&self) -> f64 {
+        self.weights.sum()
+    }
+
+    fn sample_frequencies<R: Rng>(
+        &self,
+        n_dims: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> DMatrix<f64> {
+        let total = self.weights.sum();
+        let mut freqs = DMatrix::zeros(n_dims, n);
+        for mut col in freqs.column_iter_mut() {
+            // Choose a component in proportion to its weight
+            let mut u = rng.gen::<f64>() * total;
+            let q = self
+                .weights
+                .iter()
+                .position(|&w| {
+                    u -= w;
+                    u <= 0.0
+                })
+                .unwrap_or(self.n_components() - 1);
+            let sd = self.variances[q].sqrt();
+            col.iter_mut().for_each(|f| {
+                let sign = if rng.gen::<bool>() { 1.0 } else { -1.0 };
+                let z: f64 = rng.sample(StandardNormal);
+                *f = 2.0 * PI * sd.mul_add(z, sign * self.means[q]);
+            });
+        }
+        freqs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    fn kernel() -> SpectralMixtureKernel {
+        SpectralMixtureKernel::new(
+            DVector::from_column_slice(&[1.5, 0.5]),
+            DVector::from_column_slice(&[0.2, 0.9]),
+            DVector::from_column_slice(&[0.05, 0.3]),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn new_validates_parameters() {
+        let v = |xs: &[f64]| DVector::from_column_slice(xs);
+        assert!(
+            SpectralMixtureKernel::new(v(&[1.0]), v(&[1.0]), v(&[1.0])).is_ok()
+        );
+        assert!(SpectralMixtureKernel::new(v(&[]), v(&[]), v(&[])).is_err());
+        assert!(SpectralMixtureKernel::new(
+            v(&[1.0]),
+            v(&[1.0, 2.0]),
+            v(&[1.0])
+        )
+        .is_err());
+        assert!(SpectralMixtureKernel::new(v(&[1.0]), v(&[0.0]), v(&[1.0]))
+            .is_err());
+    }
+
+    #[test]
+    fn one_dimensional_closed_form() {
+        let k = kernel();
+        let x = DMatrix::from_column_slice(2, 1, &[0.3, 1.1]);
+        let tau: f64 = 0.3 - 1.1;
+        let expected: f64 = [(1.5, 0.2, 0.05), (0.5, 0.9, 0.3)]
+            .iter()
+            .map(|&(w, mu, v): &(f64, f64, f64)| {
+                w * (-2.0 * PI * PI * v * tau * tau).exp()
+                    * (2.0 * PI * mu * tau).cos()
+            })
+            .sum();
+        let cov = k.covariance(&x, &x);
+        assert::close(cov[(0, 1)], expected, 1E-12);
+        assert::close(cov[(0, 0)], 2.0, 1E-12);
+        assert::close(k.diag(&x).as_slice(), &[2.0, 2.0], 1E-12);
+    }
+
+    #[test]
+    fn gradient_matches_finite_differences() {
+        let k = kernel();
+        let x =
+            DMatrix::from_row_slice(3, 2, &[0.1, 0.4, -0.7, 1.2, 1.5, -0.3]);
+        let (cov, grad) = k.covariance_with_gradient(&x).unwrap();
+        assert!(cov.relative_eq(&k.covariance(&x, &x), 1E-12, 1E-12));
+
+        let params = k.parameters();
+        let h = 1E-6;
+        for p in 0..params.len() {
+            let mut hi = params.clone();
+            let mut lo = params.clone();
+            hi[p] += h;
+            lo[p] -= h;
+            let fd = (k
+                .reparameterize(hi.as_slice())
+                .unwrap()
+                .covariance(&x, &x)
+                - k.reparameterize(lo.as_slice()).unwrap().covariance(&x, &x))
+                / (2.0 * h);
+            assert!(grad[p].relative_eq(&fd, 1E-6, 1E-6), "parameter {}", p);
+        }
+    }
+
+    #[test]
+    fn sampled_frequencies_approximate_kernel() {
+        let k = kernel();
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x5EC7);
+        let freqs = k.sample_frequencies(2, 200_000, &mut rng);
+        let tau = DVector::from_column_slice(&[0.4, -0.2]);
+        let approx = k.variance()
+            * freqs.column_iter().map(|w| w.dot(&tau).cos()).sum::<f64>()
+            / 200_000.0;
+        let x = DMatrix::from_row_slice(2, 2, &[0.4, -0.2, 0.0, 0.0]);
+        assert::close(approx, k.covariance(&x, &x)[(0, 1)], 1E-2);
+    }
+}
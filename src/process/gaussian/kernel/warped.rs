@@ -0,0 +1,342 @@
+use super::{CovGrad, CovGradError, Kernel, KernelError, KernelNode};
+use nalgebra::base::constraint::{SameNumberOfColumns, ShapeConstraint};
+use nalgebra::base::storage::Storage;
+use nalgebra::{DMatrix, DVector, Dim, Matrix};
+use std::f64;
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+/// Step used for the central-difference gradient of the warping parameters
+const WARP_GRAD_STEP: f64 = 1E-6;
+
+/// Kernel applied to inputs warped by a Kumaraswamy CDF
+///
+/// Each input dimension, which should lie in [0, 1], is transformed by the
+/// monotone function
+///
+/// ```math
+///     w(x) = 1 - (1 - x^a)^b
+/// ```
+///
+/// before being passed to the base kernel. Inputs outside of [0, 1] are
+/// clamped.
+///
+/// The gradient with respect to the base kernel parameters is exact; the
+/// gradient with respect to the warping parameters is computed by central
+/// differences.
+///
+/// # Parameters
+/// * `base` - Kernel over the warped inputs.
+/// * `a` - First shape parameter for each dimension.
+/// * `b` - Second shape parameter for each dimension.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct WarpedKernel<K>
+where
+    K: Kernel,
+{
+    base: K,
+    a: DVector<f64>,
+    b: DVector<f64>,
+}
+
+impl<K> WarpedKernel<K>
+where
+    K: Kernel,
+{
+    /// Create a new warped kernel
+    pub fn new(
+        base: K,
+        a: DVector<f64>,
+        b: DVector<f64>,
+    ) -> Result<Self, KernelError> {
+        if a.nrows() != b.nrows() {
+            return Err(KernelError::MissingParameters(
+                a.nrows().max(b.nrows()) - a.nrows().min(b.nrows()),
+            ));
+        }
+        for (name, xs) in [("a", &a), ("b", &b)] {
+            if let Some(x) = xs.iter().find(|&&x| x <= 0.0 || !x.is_finite()) {
+                return Err(KernelError::ParameterOutOfBounds {
+                    name: name.to_string(),
+                    given: *x,
+                    bounds: (0.0, f64::INFINITY),
+                });
+            }
+        }
+        Ok(Self { base, a, b })
+    }
+
+    /// Create a new `WarpedKernel` without checking parameters
+    pub fn new_unchecked(base: K, a: DVector<f64>, b: DVector<f64>) -> Self {
+        Self { base, a, b }
+    }
+
+    /// Create a warped kernel over `n_dims` dimensions whose warping is the
+    /// identity, i.e., `a = b = 1`.
+    pub fn identity(base: K, n_dims: usize) -> Self {
+        Self {
+            base,
+            a: DVector::from_element(n_dims, 1.0),
+            b: DVector::from_element(n_dims, 1.0),
+        }
+    }
+
+    /// The base kernel
+    pub fn base(&self) -> &K {
+        &self.base
+    }
+
+    /// Apply the input warping to the rows of `x`
+    pub fn warp<R, C, S>(&self, x: &Matrix<f64, R, C, S>) -> DMatrix<f64>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        DMatrix::from_fn(x.nrows(), x.ncols(), |i, j| {
+            let xij = x[(i, j)].clamp(0.0, 1.0);
+            1.0 - (1.0 - xij.powf(self.a[j])).powf(self.b[j])
+        })
+    }
+
+    fn warp_parameters(&self) -> Vec<f64> {
+        self.a.iter().chain(self.b.iter()).map(|x| x.ln()).collect()
+    }
+
+    fn with_warp_parameters(&self, params: &[f64]) -> Self {
+        let n = self.a.nrows();
+        Self {
+            base: self.base.clone(),
+            a: DVector::from_iterator(n, params[..n].iter().map(|x| x.exp())),
+            b: DVector::from_iterator(n, params[n..].iter().map(|x| x.exp())),
+        }
+    }
+
+    // Lives outside of `Kernel::covariance` so that the shape constraint on
+    // that method's generics does not leak into this call.
+    fn base_covariance(
+        &self,
+        x1: &DMatrix<f64>,
+        x2: &DMatrix<f64>,
+    ) -> DMatrix<f64> {
+        self.base.covariance(x1, x2)
+    }
+}
+
+impl<K> Kernel for WarpedKernel<K>
+where
+    K: Kernel,
+{
+    fn n_parameters(&self) -> usize {
+        self.base.n_parameters() + 2 * self.a.nrows()
+    }
+
+    fn covariance<R1, R2, C1, C2, S1, S2>(
+        &self,
+        x1: &Matrix<f64, R1, C1, S1>,
+        x2: &Matrix<f64, R2, C2, S2>,
+    ) -> DMatrix<f64>
+    where
+        R1: Dim,
+        R2: Dim,
+        C1: Dim,
+        C2: Dim,
+        S1: Storage<f64, R1, C1>,
+        S2: Storage<f64, R2, C2>,
+        ShapeConstraint: SameNumberOfColumns<C1, C2>,
+    {
+        self.base_covariance(&self.warp(x1), &self.warp(x2))
+    }
+
+    fn is_stationary(&self) -> bool {
+        false
+    }
+
+    fn diag<R, C, S>(&self, x: &Matrix<f64, R, C, S>) -> DVector<f64>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        self.base.diag(&self.warp(x))
+    }
+
+    /// Base kernel parameters followed by `ln(a)` and `ln(b)`
+    fn parameters(&self) -> DVector<f64> {
+        let base = self.base.parameters();
+        DVector::from_iterator(
+            self.n_parameters(),
+            base.iter().copied().chain(self.warp_parameters()),
+        )
+    }
+
+    fn reparameterize(&self, params: &[f64]) -> Result<Self, KernelError> {
+        use std::cmp::Ordering;
+        let n = self.n_parameters();
+        match params.len().cmp(&n) {
+            Ordering::Less => {
+                Err(KernelError::MissingParameters(n - params.len()))
+            }
+            Ordering::Greater => {
+                Err(KernelError::ExtraniousParameters(params.len() - n))
+            }
+            Ordering::Equal => {
+                let (base_params, warp_params) =
+                    params.split_at(self.base.n_parameters());
+                let base = self.base.reparameterize(base_params)?;
+                let warped = self.with_warp_parameters(warp_params);
+                Self::new(base, warped.a, warped.b)
+            }
+        }
+    }
+
+    fn covariance_with_gradient<R, C, S>(
+        &self,
+        x: &Matrix<f64, R, C, S>,
+    ) -> Result<(DMatrix<f64>, CovGrad), CovGradError>
+    where
+        R: Dim,
+        C: Dim,
+        S: Storage<f64, R, C>,
+    {
+        let (cov, base_grad) =
+            self.base.covariance_with_gradient(&self.warp(x))?;
+
+        let warp_params = self.warp_parameters();
+        let warp_grads: Vec<DMatrix<f64>> = (0..warp_params.len())
+            .map(|k| {
+                let mut up = warp_params.clone();
+                up[k] += WARP_GRAD_STEP;
+                let mut down = warp_params.clone();
+                down[k] -= WARP_GRAD_STEP;
+
+                let k_up = self.with_warp_parameters(&up);
+                let k_down = self.with_warp_parameters(&down);
+                let x_up = k_up.warp(x);
+                let x_down = k_down.warp(x);
+                (self.base_covariance(&x_up, &x_up)
+                    - self.base_covariance(&x_down, &x_down))
+                    / (2.0 * WARP_GRAD_STEP)
+            })
+            .collect();
+
+        let grad = base_grad.concat_cols(&CovGrad::new(&warp_grads)?)?;
+        Ok((cov, grad))
+    }
+}
+
+impl<K, B> std::ops::Mul<B> for WarpedKernel<K>
+where
+    K: Kernel,
+    B: Kernel,
+{
+    type Output = super::ProductKernel<Self, B>;
+
+    fn mul(self, rhs: B) -> Self::Output {
+        super::ProductKernel::new(self, rhs)
+    }
+}
+
+impl<K, B> std::ops::Add<B> for WarpedKernel<K>
+where
+    K: Kernel,
+    B: Kernel,
+{
+    type Output = super::AddKernel<Self, B>;
+
+    fn add(self, rhs: B) -> Self::Output {
+        super::AddKernel::new(self, rhs)
+    }
+}
+
+impl<K> From<WarpedKernel<K>> for KernelNode
+where
+    K: Kernel + Into<KernelNode>,
+{
+    fn from(k: WarpedKernel<K>) -> Self {
+        KernelNode::Warped(Box::new(WarpedKernel {
+            base: k.base.into(),
+            a: k.a,
+            b: k.b,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::gaussian::kernel::RBFKernel;
+    use nalgebra::dmatrix;
+
+    #[test]
+    fn identity_warp_matches_base() {
+        let base = RBFKernel::new(0.3).unwrap();
+        let kernel = WarpedKernel::identity(base.clone(), 1);
+        let x = dmatrix![0.0; 0.2; 0.5; 0.9];
+        assert!(kernel.covariance(&x, &x).relative_eq(
+            &base.covariance(&x, &x),
+            1E-12,
+            1E-12
+        ));
+    }
+
+    #[test]
+    fn warp_is_monotone_and_fixes_endpoints() {
+        let kernel = WarpedKernel::new(
+            RBFKernel::default(),
+            DVector::from_element(1, 2.5),
+            DVector::from_element(1, 0.7),
+        )
+        .unwrap();
+        let x = dmatrix![0.0; 0.1; 0.4; 0.8; 1.0];
+        let w = kernel.warp(&x);
+        assert::close(w[0], 0.0, 1E-12);
+        assert::close(w[4], 1.0, 1E-12);
+        assert!((1..5).all(|i| w[i] > w[i - 1]));
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        let base = RBFKernel::default();
+        let one = DVector::from_element(1, 1.0);
+        assert!(WarpedKernel::new(
+            base.clone(),
+            DVector::from_element(1, 0.0),
+            one.clone()
+        )
+        .is_err());
+        assert!(WarpedKernel::new(base, one, DVector::from_element(2, 1.0))
+            .is_err());
+    }
+
+    #[test]
+    fn gradient_matches_finite_differences() -> Result<(), KernelError> {
+        let kernel = WarpedKernel::new(
+            RBFKernel::new(0.4).unwrap(),
+            DVector::from_column_slice(&[1.5, 0.8]),
+            DVector::from_column_slice(&[0.6, 2.0]),
+        )
+        .unwrap();
+        let x = dmatrix![0.1, 0.9; 0.3, 0.2; 0.75, 0.5];
+        let (cov, grad) = kernel.covariance_with_gradient(&x)?;
+        assert!(cov.relative_eq(&kernel.covariance(&x, &x), 1E-10, 1E-10));
+
+        let params = kernel.parameters();
+        assert_eq!(params.len(), 5);
+        let h = 1E-5;
+        for p in 0..params.len() {
+            let mut up = params.clone();
+            up[p] += h;
+            let mut down = params.clone();
+            down[p] -= h;
+            let fd = (kernel.reparameterize(up.as_slice())?.covariance(&x, &x)
+                - kernel.reparameterize(down.as_slice())?.covariance(&x, &x))
+                / (2.0 * h);
+            assert!(fd.relative_eq(&grad[p], 1E-5, 1E-5));
+        }
+        Ok(())
+    }
+}
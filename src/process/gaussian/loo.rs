@@ -0,0 +1,393 @@
+//! Exact leave-one-out predictive densities for Gaussian processes
+//!
+//! The leave-one-out (LOO) predictive of each training output follows from
+//! the inverse covariance of the full training set without refitting
+//! (Sundararajan & Keerthi, 2001; GPML §5.4.2). The sum of their log
+//! densities, the LOO pseudo-likelihood, is an alternative to the marginal
+//! likelihood for choosing hyperparameters that is less sensitive to a
+//! misspecified model.
+use argmin::solver::{linesearch::MoreThuenteLineSearch, quasinewton::LBFGS};
+use nalgebra::linalg::Cholesky;
+use nalgebra::{DMatrix, DVector};
+use rand::Rng;
+
+use super::kernel::Kernel;
+use super::{GaussianProcess, GaussianProcessError, GaussianProcessPrediction};
+use crate::consts::HALF_LN_2PI;
+use crate::model::{anomaly_scores_from_ln_f, AnomalyError};
+use crate::process::{RandomProcess, RandomProcessMle};
+
+/// The LOO log predictive density of each training output from the dual
+/// coefficients, `α = K⁻¹y`, and the diagonal of `K⁻¹`
+fn ln_loo_terms<'a>(
+    alpha: &'a DVector<f64>,
+    k_inv_diag: &'a DVector<f64>,
+) -> impl Iterator<Item = f64> + 'a {
+    // GPML Equation 5.10, with y_i - μ_i = α_i / [K⁻¹]_ii and
+    // σ_i² = 1 / [K⁻¹]_ii
+    alpha
+        .iter()
+        .zip(k_inv_diag.iter())
+        .map(|(&a, &kii)| 0.5_f64.mul_add(kii.ln() - a * a / kii, -HALF_LN_2PI))
+}
+
+/// The LOO log pseudo-likelihood from the dual coefficients, `α = K⁻¹y`,
+/// and the diagonal of `K⁻¹`
+fn ln_loo_from_parts(alpha: &DVector<f64>, k_inv_diag: &DVector<f64>) -> f64 {
+    ln_loo_terms(alpha, k_inv_diag).sum()
+}
+
+impl<K> GaussianProcess<K>
+where
+    K: Kernel,
+{
+    /// The leave-one-out predictive means and variances of the training
+    /// outputs
+    ///
+    /// Entry `i` is the predictive of `y_i` from the model trained on every
+    /// other point, with the same hyperparameters. The variances include the
+    /// observation noise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nalgebra::{DMatrix, DVector};
+    /// use rv::process::gaussian::kernel::RBFKernel;
+    /// use rv::process::gaussian::{GaussianProcess, NoiseModel};
+    ///
+    /// let xs = DMatrix::from_fn(20, 1, |i, _| i as f64 / 4.0);
+    /// let ys: DVector<f64> = xs.column(0).map(|x| x.sin());
+    ///
+    /// let gp = GaussianProcess::train(
+    ///     RBFKernel::new(1.0).unwrap(),
+    ///     xs,
+    ///     ys.clone(),
+    ///     NoiseModel::Uniform(0.1),
+    /// )
+    /// .unwrap();
+    ///
+    /// let (means, vars) = gp.loo_predictive();
+    /// assert!((means - ys).amax() < 0.1);
+    /// assert!(vars.iter().all(|&v| v > 0.01));
+    /// ```
+    pub fn loo_predictive(&self) -> (DVector<f64>, DVector<f64>) {
+        let k_inv_diag = self.k_inv.diagonal();
+        let means = DVector::from_iterator(
+            self.y_train.len(),
+            self.y_train
+                .iter()
+                .zip(self.alpha.iter().zip(k_inv_diag.iter()))
+                .map(|(&y, (&a, &kii))| y - a / kii),
+        );
+        let vars = k_inv_diag.map(f64::recip);
+        (means, vars)
+    }
+
+    /// The LOO log pseudo-likelihood, the sum of the log LOO predictive
+    /// densities of the training outputs
+    pub fn ln_loo(&self) -> f64 {
+        ln_loo_from_parts(&self.alpha, &self.k_inv.diagonal())
+    }
+
+    /// Anomaly scores of the training outputs from their LOO predictive
+    /// densities, normalized by the `quantile` of the negative log densities
+    ///
+    /// Outputs that the rest of the data cannot explain score highly. See
+    /// [`AnomalyScorer`](crate::model::AnomalyScorer) for the score and
+    /// [`TailThreshold`](crate::model::TailThreshold) for choosing a cutoff.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use nalgebra::{DMatrix, DVector};
+    /// use rv::process::gaussian::kernel::RBFKernel;
+    /// use rv::process::gaussian::{GaussianProcess, NoiseModel};
+    ///
+    /// let xs = DMatrix::from_fn(20, 1, |i, _| i as f64 / 4.0);
+    /// let mut ys: DVector<f64> = xs.column(0).map(|x| x.sin());
+    /// ys[7] += 2.0;
+    ///
+    /// let gp = GaussianProcess::train(
+    ///     RBFKernel::new(1.0).unwrap(),
+    ///     xs,
+    ///     ys,
+    ///     NoiseModel::Uniform(0.2),
+    /// )
+    /// .unwrap();
+    ///
+    /// let scores = gp.loo_anomaly_scores(0.5).unwrap();
+    /// assert_eq!(scores.argmax().0, 7);
+    /// ```
+    pub fn loo_anomaly_scores(
+        &self,
+        quantile: f64,
+    ) -> Result<DVector<f64>, AnomalyError> {
+        let k_inv_diag = self.k_inv.diagonal();
+        let ln_f: Vec<f64> = ln_loo_terms(&self.alpha, &k_inv_diag).collect();
+        anomaly_scores_from_ln_f(&ln_f, quantile).map(DVector::from)
+    }
+
+    /// The LOO log pseudo-likelihood and its gradient with respect to the
+    /// (log-scaled) kernel parameters, `parameters`
+    pub fn ln_loo_with_params(
+        &self,
+        parameters: &DVector<f64>,
+    ) -> Result<(f64, DVector<f64>), GaussianProcessError> {
+        let kernel = self
+            .kernel
+            .reparameterize(&parameters.iter().copied().collect::<Vec<f64>>())
+            .map_err(GaussianProcessError::KernelError)?;
+
+        let (k, k_grad) = kernel
+            .covariance_with_gradient(&self.x_train)
+            .map_err(|e| GaussianProcessError::KernelError(e.into()))?;
+        let k = self
+            .noise_model
+            .add_noise_to_kernel(&k)
+            .map_err(GaussianProcessError::MisshapenNoiseModel)?;
+
+        let k_chol = Cholesky::new(k)
+            .ok_or(GaussianProcessError::NotPositiveSemiDefinite)?;
+        let k_inv = k_chol.inverse();
+        let alpha = k_chol.solve(&self.y_train);
+        let k_inv_diag = k_inv.diagonal();
+
+        let ln_loo = ln_loo_from_parts(&alpha, &k_inv_diag);
+
+        // GPML Equation 5.13, with Z_j = K⁻¹ ∂K/∂θ_j
+        let grad: Vec<f64> = (0..parameters.len())
+            .map(|j| {
+                let z: DMatrix<f64> = &k_inv * &k_grad[j];
+                let z_alpha = &z * &alpha;
+                (0..alpha.len())
+                    .map(|i| {
+                        let kii = k_inv_diag[i];
+                        let z_kinv_ii =
+                            z.row(i).transpose().dot(&k_inv.column(i));
+                        let a = alpha[i];
+                        (0.5 * (1.0 + a * a / kii))
+                            .mul_add(-z_kinv_ii, a * z_alpha[i])
+                            / kii
+                    })
+                    .sum()
+            })
+            .collect();
+
+        Ok((ln_loo, DVector::from(grad)))
+    }
+
+    /// Choose the kernel parameters that maximize the LOO pseudo-likelihood
+    ///
+    /// The counterpart of [`RandomProcessMle::optimize`], which maximizes the
+    /// marginal likelihood.
+    ///
+    /// # Arguments
+    /// - `max_iters` - Maximum number of iterations per optimization run
+    /// - `random_reinits` - Number of times to retry with random initialization
+    /// - `rng` - Random number generator for random initialization
+    pub fn optimize_loo<R: Rng>(
+        self,
+        max_iters: u64,
+        random_reinits: usize,
+        rng: &mut R,
+    ) -> Result<Self, argmin::core::Error> {
+        LooObjective(self)
+            .optimize(max_iters, random_reinits, rng)
+            .map(|obj| obj.0)
+    }
+}
+
+/// A [`GaussianProcess`] whose "marginal likelihood" is the LOO
+/// pseudo-likelihood, so that it can be optimized by
+/// [`RandomProcessMle::optimize`]
+#[derive(Clone, Debug)]
+struct LooObjective<K: Kernel>(GaussianProcess<K>);
+
+impl<K> RandomProcess<f64> for LooObjective<K>
+where
+    K: Kernel,
+{
+    type Index = DVector<f64>;
+    type SampleFunction = GaussianProcessPrediction<K>;
+    type Error = GaussianProcessError;
+
+    fn sample_function(&self, indices: &[Self::Index]) -> Self::SampleFunction {
+        self.0.sample_function(indices)
+    }
+
+    fn ln_m(&self) -> f64 {
+        self.0.ln_loo()
+    }
+
+    fn ln_m_with_params(
+        &self,
+        parameter: &DVector<f64>,
+    ) -> Result<(f64, DVector<f64>), GaussianProcessError> {
+        self.0.ln_loo_with_params(parameter)
+    }
+
+    fn parameters(&self) -> DVector<f64> {
+        self.0.parameters()
+    }
+
+    fn set_parameters(
+        self,
+        parameters: &DVector<f64>,
+    ) -> Result<Self, GaussianProcessError> {
+        self.0.set_parameters(parameters).map(LooObjective)
+    }
+}
+
+impl<K> RandomProcessMle<f64> for LooObjective<K>
+where
+    K: Kernel,
+{
+    type Solver = LBFGS<
+        MoreThuenteLineSearch<DVector<f64>, DVector<f64>, f64>,
+        DVector<f64>,
+        DVector<f64>,
+        f64,
+    >;
+
+    fn generate_solver() -> Self::Solver {
+        GaussianProcess::<K>::generate_solver()
+    }
+
+    fn random_params<R: Rng>(&self, rng: &mut R) -> DVector<f64> {
+        self.0.random_params(rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::gaussian::kernel::{
+        ConstantKernel, ProductKernel, RBFKernel,
+    };
+    use crate::process::gaussian::NoiseModel;
+    use crate::traits::{Mean, Variance};
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    fn data() -> (DMatrix<f64>, DVector<f64>) {
+        let xs = DMatrix::from_column_slice(
+            7,
+            1,
+            &[-4.0, -3.0, -2.0, -1.0, 0.5, 1.0, 2.5],
+        );
+        let ys =
+            DVector::from_column_slice(&[0.8, 0.1, -0.9, -0.8, 0.4, 0.9, 0.5]);
+        (xs, ys)
+    }
+
+    const NOISE: f64 = 0.2;
+
+    fn gp() -> GaussianProcess<ProductKernel<ConstantKernel, RBFKernel>> {
+        let (xs, ys) = data();
+        let kernel =
+            ConstantKernel::new(1.5).unwrap() * RBFKernel::new(1.2).unwrap();
+        GaussianProcess::train(kernel, xs, ys, NoiseModel::Uniform(NOISE))
+            .unwrap()
+    }
+
+    #[test]
+    fn loo_predictive_matches_refitting() {
+        let gp = gp();
+        let (xs, ys) = data();
+        let (means, vars) = gp.loo_predictive();
+
+        for i in 0..ys.len() {
+            let x_rest = xs.clone().remove_row(i);
+            let y_rest = ys.clone().remove_row(i);
+            let gp_i = GaussianProcess::train(
+                gp.kernel().clone(),
+                x_rest,
+                y_rest,
+                NoiseModel::Uniform(NOISE),
+            )
+            .unwrap();
+            let pred = gp_i.sample_function(&[xs.row(i).transpose()]);
+            let mean = pred.mean().unwrap()[0];
+            let var = NOISE.mul_add(NOISE, pred.variance().unwrap()[0]);
+            assert::close(means[i], mean, 1E-10);
+            assert::close(vars[i], var, 1E-10);
+        }
+    }
+
+    #[test]
+    fn ln_loo_is_sum_of_predictive_densities() {
+        use crate::dist::Gaussian;
+        use crate::traits::Rv;
+
+        let gp = gp();
+        let (means, vars) = gp.loo_predictive();
+        let expected: f64 = (0..means.len())
+            .map(|i| {
+                Gaussian::new(means[i], vars[i].sqrt())
+                    .unwrap()
+                    .ln_f(&gp.y_train[i])
+            })
+            .sum();
+        assert::close(gp.ln_loo(), expected, 1E-10);
+
+        let (ln_loo, _) =
+            gp.ln_loo_with_params(&gp.kernel().parameters()).unwrap();
+        assert::close(ln_loo, expected, 1E-10);
+    }
+
+    #[test]
+    fn ln_loo_gradient_matches_finite_differences() {
+        let gp = gp();
+        let params = gp.kernel().parameters();
+        let (_, grad) = gp.ln_loo_with_params(&params).unwrap();
+
+        let h = 1E-6;
+        for j in 0..params.len() {
+            let mut hi = params.clone();
+            let mut lo = params.clone();
+            hi[j] += h;
+            lo[j] -= h;
+            let fd = (gp.ln_loo_with_params(&hi).unwrap().0
+                - gp.ln_loo_with_params(&lo).unwrap().0)
+                / (2.0 * h);
+            assert::close(grad[j], fd, 1E-6);
+        }
+    }
+
+    #[test]
+    fn optimize_loo_improves_objective() {
+        let gp = gp();
+        let ln_loo_0 = gp.ln_loo();
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x100);
+        let opt = gp.optimize_loo(100, 5, &mut rng).unwrap();
+        assert!(opt.ln_loo() > ln_loo_0);
+
+        let (_, grad) =
+            opt.ln_loo_with_params(&opt.kernel().parameters()).unwrap();
+        assert!(grad.amax() < 1E-3);
+    }
+
+    #[test]
+    fn loo_anomaly_scores_use_loo_predictives() {
+        use crate::dist::Gaussian;
+        use crate::traits::Rv;
+
+        let gp = gp();
+        let (_, ys) = data();
+        let (means, vars) = gp.loo_predictive();
+        let ln_f: Vec<f64> = (0..ys.len())
+            .map(|i| {
+                Gaussian::new(means[i], vars[i].sqrt())
+                    .unwrap()
+                    .ln_f(&ys[i])
+            })
+            .collect();
+
+        let scores = gp.loo_anomaly_scores(1.0).unwrap();
+        let max_nlpd = ln_f.iter().map(|lf| -lf).fold(f64::MIN, f64::max);
+        for (score, lf) in scores.iter().zip(ln_f.iter()) {
+            assert::close(*score, -lf - max_nlpd, 1E-10);
+        }
+        assert!(gp.loo_anomaly_scores(-0.1).is_err());
+    }
+}
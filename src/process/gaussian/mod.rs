@@ -6,17 +6,39 @@ use nalgebra::{DMatrix, DVector, Dyn};
 use rand::Rng;
 #[cfg(feature = "serde1")]
 use serde::{Deserialize, Serialize};
-use std::cell::OnceCell;
+use std::sync::OnceLock;
 
 use crate::dist::MvGaussian;
 use crate::{consts::HALF_LN_2PI, traits::Mean, traits::Rv, traits::Variance};
 
 pub mod kernel;
-use kernel::{Kernel, KernelError};
+use kernel::{CoregionalKernel, Kernel, KernelError};
 
 mod noise_model;
 pub use self::noise_model::NoiseModel;
 
+mod classifier;
+pub use self::classifier::GaussianProcessClassifier;
+
+mod heteroscedastic;
+pub use self::heteroscedastic::HeteroscedasticGaussianProcess;
+
+mod loo;
+
+mod quadrature;
+pub use self::quadrature::BayesianQuadrature;
+
+mod rff;
+pub use self::rff::{
+    rff_error_curve, RandomFourierFeatures, RffDiagnostic, RffRegression,
+};
+
+mod sparse;
+pub use self::sparse::{
+    compare_sparse_strategies, kmeans_inducing, SparseComparison,
+    SparseGaussianProcess, SparseStrategy,
+};
+
 use super::{RandomProcess, RandomProcessMle};
 
 #[inline]
@@ -36,6 +58,8 @@ pub enum GaussianProcessError {
     KernelError(KernelError),
     /// The given noise model does not match the training data
     MisshapenNoiseModel(String),
+    /// The optimizer failed to converge to a solution
+    OptimizationFailed(String),
 }
 
 impl std::error::Error for GaussianProcessError {}
@@ -49,6 +73,9 @@ impl std::fmt::Display for GaussianProcessError {
                 writeln!(f, "Noise model error: {}", msg)
             }
             Self::KernelError(e) => writeln!(f, "Error from kernel: {}", e),
+            Self::OptimizationFailed(msg) => {
+                writeln!(f, "Optimization failed: {}", msg)
+            }
         }
     }
 }
@@ -137,6 +164,36 @@ where
     pub fn kernel(&self) -> &K {
         &(self.kernel)
     }
+
+    /// Create the posterior prediction at the rows of `xs`
+    fn predict(&self, xs: DMatrix<f64>) -> GaussianProcessPrediction<K> {
+        let k_trans = self.kernel.covariance(&xs, &self.x_train);
+        let y_mean = &k_trans * &self.alpha;
+        GaussianProcessPrediction {
+            gp: self.clone(),
+            y_mean,
+            k_trans,
+            xs,
+            cov: OnceLock::new(),
+            dist: OnceLock::new(),
+        }
+    }
+}
+
+impl<K> GaussianProcess<CoregionalKernel<K>>
+where
+    K: Kernel,
+{
+    /// Predict the output of a single task at the rows of `xs`
+    ///
+    /// `xs` should not contain the task-index column; it is appended here.
+    pub fn predict_task(
+        &self,
+        xs: &DMatrix<f64>,
+        task: usize,
+    ) -> GaussianProcessPrediction<CoregionalKernel<K>> {
+        self.predict(CoregionalKernel::<K>::with_task(xs, task))
+    }
 }
 
 impl<K> RandomProcess<f64> for GaussianProcess<K>
@@ -154,21 +211,12 @@ where
         let n = indicies.len();
         let m = indicies.get(0).map(|i| i.len()).unwrap_or(0);
 
-        let indicies: DMatrix<f64> = DMatrix::from_iterator(
+        let indicies: DMatrix<f64> = DMatrix::from_row_iterator(
             n,
             m,
             indicies.iter().flat_map(|i| i.iter().cloned()),
         );
-        let k_trans = self.kernel.covariance(&indicies, &self.x_train);
-        let y_mean = &k_trans * &self.alpha;
-        GaussianProcessPrediction {
-            gp: self.clone(),
-            y_mean,
-            k_trans,
-            xs: indicies,
-            cov: OnceCell::new(),
-            dist: OnceCell::new(),
-        }
+        self.predict(indicies)
     }
 
     fn ln_m(&self) -> f64 {
@@ -296,9 +344,9 @@ where
     /// Values to predict `f(x)` against.
     xs: DMatrix<f64>,
     /// Covariance matrix
-    cov: OnceCell<DMatrix<f64>>,
+    cov: OnceLock<DMatrix<f64>>,
     /// Output Distribution
-    dist: OnceCell<MvGaussian>,
+    dist: OnceLock<MvGaussian>,
 }
 
 impl<K> GaussianProcessPrediction<K>
@@ -815,4 +863,79 @@ mod tests {
         assert!(gp.k_chol().l().relative_eq(&expected_k_chol, 1E-7, 1E-7));
         Ok(())
     }
+
+    #[test]
+    fn sample_function_indices_are_rows() {
+        let x_train = DMatrix::from_row_slice(
+            4,
+            2,
+            &[0.0, 0.0, 1.0, 0.5, 2.0, 3.0, 4.0, 4.5],
+        );
+        let y_train = dvector![0.0, 1.0, -1.0, 2.0];
+        let gp = GaussianProcess::train(
+            RBFKernel::default(),
+            x_train,
+            y_train,
+            NoiseModel::default(),
+        )
+        .unwrap();
+
+        let indices =
+            [dvector![0.0, 1.0], dvector![2.0, 3.0], dvector![4.0, 5.0]];
+        let xs = DMatrix::from_row_slice(3, 2, &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+        let mean = gp.sample_function(&indices).mean().unwrap();
+        let expected = gp.predict(xs).mean().unwrap();
+        assert!(mean.relative_eq(&expected, 1E-12, 1E-12));
+    }
+
+    #[test]
+    fn coregional_gp_shares_information_across_tasks() {
+        // Task 1 is the negation of task 0 but is only observed on the left
+        let x0: DMatrix<f64> =
+            DMatrix::from_column_slice(6, 1, &[-3., -2., -1., 0., 1., 2.]);
+        let x1: DMatrix<f64> =
+            DMatrix::from_column_slice(3, 1, &[-3., -2., -1.]);
+        let y0: DVector<f64> = x0.map(|x| x.sin()).column(0).into();
+        let y1: DVector<f64> = x1.map(|x| -x.sin()).column(0).into();
+
+        let (x_train, y_train) =
+            CoregionalKernel::<RBFKernel>::stack_tasks(&[x0, x1], &[y0, y1]);
+
+        let kernel = CoregionalKernel::new(
+            RBFKernel::default(),
+            DMatrix::from_column_slice(2, 1, &[1.0, -1.0]),
+            DVector::from_element(2, 1E-3),
+        )
+        .unwrap();
+        let gp = GaussianProcess::train(
+            kernel,
+            x_train,
+            y_train,
+            NoiseModel::Uniform(1E-4),
+        )
+        .unwrap();
+
+        let xs = DMatrix::from_column_slice(2, 1, &[1.0, 2.0]);
+        let mean_0 = gp.predict_task(&xs, 0).mean().unwrap();
+        let mean_1 = gp.predict_task(&xs, 1).mean().unwrap();
+
+        assert::close(mean_0[0], 1.0_f64.sin(), 1E-4);
+        assert::close(mean_1[0], -(1.0_f64.sin()), 1E-2);
+        assert::close(mean_1[1], -(2.0_f64.sin()), 1E-2);
+
+        let std_1 = gp.predict_task(&xs, 1).std();
+        assert!(std_1.iter().all(|s| s.is_finite()));
+
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xABCD);
+        let ln_m_start = gp.ln_m();
+        let gp = gp.optimize(50, 2, &mut rng).expect("Failed to optimize");
+        assert!(gp.ln_m() >= ln_m_start);
+
+        // The tasks stay anti-correlated and `W` stays within its bound
+        let b = gp.kernel().task_covariance();
+        assert!(b[(0, 1)] < 0.0);
+        assert!(b[(0, 1)].abs() <= gp.kernel().w_bound().powi(2));
+        let mean_1 = gp.predict_task(&xs, 1).mean().unwrap();
+        assert::close(mean_1[0], -(1.0_f64.sin()), 1E-2);
+    }
 }
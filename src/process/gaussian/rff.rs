@@ -0,0 +1,391 @@
+//! Random Fourier feature approximations of stationary kernels
+use nalgebra::linalg::Cholesky;
+use nalgebra::{DMatrix, DVector, Dyn};
+use rand::Rng;
+use std::f64::consts::PI;
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use super::kernel::SpectralKernel;
+use super::{GaussianProcessError, NoiseModel};
+
+/// Explicit feature map, `φ`, whose inner products approximate a stationary
+/// kernel, `k(x, x') ≈ φ(x)ᵀφ(x')`.
+///
+/// Each of the `D` features is `sqrt(2σ²/D) cos(ωᵀx + b)` where `ω` is drawn
+/// from the kernel's spectral density and `b` is uniform on [0, 2π).
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::DMatrix;
+/// use rand::SeedableRng;
+/// use rand_xoshiro::Xoshiro256Plus;
+/// use rv::process::gaussian::kernel::{Kernel, RBFKernel};
+/// use rv::process::gaussian::RandomFourierFeatures;
+///
+/// let mut rng = Xoshiro256Plus::seed_from_u64(0x1234);
+/// let kernel = RBFKernel::new(1.5).unwrap();
+/// let rff = RandomFourierFeatures::new(&kernel, 1, 5_000, &mut rng);
+///
+/// let x = DMatrix::from_column_slice(4, 1, &[0.0, 0.5, 1.0, 3.0]);
+/// let approx = rff.approx_covariance(&x, &x);
+/// let exact = kernel.covariance(&x, &x);
+/// assert!((approx - exact).abs().max() < 0.1);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct RandomFourierFeatures {
+    /// Frequencies, one per column
+    frequencies: DMatrix<f64>,
+    /// Phase offsets
+    offsets: DVector<f64>,
+    /// Scale applied to every feature, `sqrt(2σ²/D)`
+    scale: f64,
+}
+
+impl RandomFourierFeatures {
+    /// Draw `n_features` random features approximating `kernel` over
+    /// `n_dims`-dimensional inputs
+    pub fn new<K, R>(
+        kernel: &K,
+        n_dims: usize,
+        n_features: usize,
+        rng: &mut R,
+    ) -> Self
+    where
+        K: SpectralKernel,
+        R: Rng,
+    {
+        let frequencies = kernel.sample_frequencies(n_dims, n_features, rng);
+        let offsets =
+            DVector::from_fn(n_features, |_, _| rng.gen::<f64>() * 2.0 * PI);
+        let scale = (2.0 * kernel.variance() / n_features as f64).sqrt();
+        Self {
+            frequencies,
+            offsets,
+            scale,
+        }
+    }
+
+    /// Number of features
+    pub fn n_features(&self) -> usize {
+        self.offsets.nrows()
+    }
+
+    /// Dimension of the inputs
+    pub fn n_dims(&self) -> usize {
+        self.frequencies.nrows()
+    }
+
+    /// Feature matrix with one row per row of `x`
+    pub fn features(&self, x: &DMatrix<f64>) -> DMatrix<f64> {
+        let mut phi = x * &self.frequencies;
+        phi.row_iter_mut().for_each(|mut row| {
+            row.iter_mut()
+                .zip(self.offsets.iter())
+                .for_each(|(z, b)| *z = self.scale * (*z + b).cos());
+        });
+        phi
+    }
+
+    /// Approximate kernel covariance between the rows of `x1` and `x2`
+    pub fn approx_covariance(
+        &self,
+        x1: &DMatrix<f64>,
+        x2: &DMatrix<f64>,
+    ) -> DMatrix<f64> {
+        self.features(x1) * self.features(x2).transpose()
+    }
+}
+
+/// Error of a random Fourier feature approximation on a set of inputs
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct RffDiagnostic {
+    /// Number of features used
+    pub n_features: usize,
+    /// Frobenius norm of the error relative to that of the exact covariance
+    pub relative_error: f64,
+    /// Largest absolute error of any covariance entry
+    pub max_abs_error: f64,
+}
+
+/// Measure how the approximation error on the rows of `x` decreases as the
+/// number of features grows.
+///
+/// One fresh set of features is drawn for each entry of `n_features`.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::DMatrix;
+/// use rand::SeedableRng;
+/// use rand_xoshiro::Xoshiro256Plus;
+/// use rv::process::gaussian::kernel::MaternKernel;
+/// use rv::process::gaussian::rff_error_curve;
+///
+/// let mut rng = Xoshiro256Plus::seed_from_u64(0x1234);
+/// let kernel = MaternKernel::new(1.5, 1.0).unwrap();
+/// let x = DMatrix::from_fn(20, 2, |i, j| (i * (j + 1)) as f64 / 10.0);
+///
+/// let curve = rff_error_curve(&kernel, &x, &[10, 10_000], &mut rng);
+/// assert!(curve[1].relative_error < curve[0].relative_error);
+/// ```
+pub fn rff_error_curve<K, R>(
+    kernel: &K,
+    x: &DMatrix<f64>,
+    n_features: &[usize],
+    rng: &mut R,
+) -> Vec<RffDiagnostic>
+where
+    K: SpectralKernel,
+    R: Rng,
+{
+    let exact = kernel.covariance(x, x);
+    let exact_norm = exact.norm();
+    n_features
+        .iter()
+        .map(|&n| {
+            let rff = RandomFourierFeatures::new(kernel, x.ncols(), n, rng);
+            let err = rff.approx_covariance(x, x) - &exact;
+            RffDiagnostic {
+                n_features: n,
+                relative_error: err.norm() / exact_norm,
+                max_abs_error: err.abs().max(),
+            }
+        })
+        .collect()
+}
+
+/// Bayesian linear regression on random Fourier features
+///
+/// This is an approximate Gaussian process whose training cost is linear in
+/// the number of data and cubic in the number of features, rather than cubic
+/// in the number of data. The weights have a standard normal prior, so the
+/// implied prior on functions has covariance `φ(x)ᵀφ(x')`.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::{DMatrix, DVector};
+/// use rand::SeedableRng;
+/// use rand_xoshiro::Xoshiro256Plus;
+/// use rv::process::gaussian::kernel::RBFKernel;
+/// use rv::process::gaussian::{
+///     NoiseModel, RandomFourierFeatures, RffRegression,
+/// };
+///
+/// let mut rng = Xoshiro256Plus::seed_from_u64(0x1234);
+/// let xs = DMatrix::from_fn(200, 1, |i, _| i as f64 / 20.0);
+/// let ys = xs.column(0).map(|x| x.sin());
+///
+/// let features = RandomFourierFeatures::new(
+///     &RBFKernel::new(1.0).unwrap(),
+///     1,
+///     300,
+///     &mut rng,
+/// );
+/// let model =
+///     RffRegression::fit(features, &xs, &ys, NoiseModel::Uniform(0.05))
+///         .unwrap();
+///
+/// let x_test = DMatrix::from_column_slice(1, 1, &[2.0]);
+/// assert!((model.mean(&x_test)[0] - 2.0_f64.sin()).abs() < 0.05);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct RffRegression {
+    features: RandomFourierFeatures,
+    /// Posterior mean of the weights
+    weights: DVector<f64>,
+    /// Cholesky decomposition of the posterior precision of the weights
+    precision_chol: Cholesky<f64, Dyn>,
+}
+
+impl RffRegression {
+    /// Fit the weight posterior given observations `y` at the rows of `x`
+    pub fn fit(
+        features: RandomFourierFeatures,
+        x: &DMatrix<f64>,
+        y: &DVector<f64>,
+        noise_model: NoiseModel,
+    ) -> Result<Self, GaussianProcessError> {
+        // Per-point noise variances, matching `NoiseModel::add_noise_to_kernel`
+        let noise_var = match noise_model {
+            NoiseModel::Uniform(sigma) => {
+                DVector::from_element(x.nrows(), sigma * sigma)
+            }
+            NoiseModel::PerPoint(var) if var.nrows() == x.nrows() => var,
+            NoiseModel::PerPoint(var) => {
+                return Err(GaussianProcessError::MisshapenNoiseModel(format!(
+                    "Per point noise must be the same size as y \
+                         (expected: {}, got: {})",
+                    x.nrows(),
+                    var.nrows()
+                )))
+            }
+        };
+
+        let phi = features.features(x);
+        let mut phi_weighted = phi.clone();
+        phi_weighted
+            .row_iter_mut()
+            .zip(noise_var.iter())
+            .for_each(|(mut row, v)| row /= *v);
+
+        let n = features.n_features();
+        let precision =
+            phi.transpose() * &phi_weighted + DMatrix::identity(n, n);
+        let precision_chol = Cholesky::new(precision)
+            .ok_or(GaussianProcessError::NotPositiveSemiDefinite)?;
+        let weights = precision_chol.solve(&(phi_weighted.transpose() * y));
+
+        Ok(Self {
+            features,
+            weights,
+            precision_chol,
+        })
+    }
+
+    /// The feature map
+    pub fn features(&self) -> &RandomFourierFeatures {
+        &self.features
+    }
+
+    /// Posterior mean of the feature weights
+    pub fn weights(&self) -> &DVector<f64> {
+        &self.weights
+    }
+
+    /// Posterior mean of the function at the rows of `xs`
+    pub fn mean(&self, xs: &DMatrix<f64>) -> DVector<f64> {
+        self.features.features(xs) * &self.weights
+    }
+
+    /// Posterior covariance of the function at the rows of `xs`
+    pub fn cov(&self, xs: &DMatrix<f64>) -> DMatrix<f64> {
+        let phi = self.features.features(xs);
+        let solved = self.precision_chol.solve(&phi.transpose());
+        phi * solved
+    }
+
+    /// Posterior standard deviation of the function at the rows of `xs`
+    pub fn std(&self, xs: &DMatrix<f64>) -> DVector<f64> {
+        let phi = self.features.features(xs);
+        let solved = self.precision_chol.solve(&phi.transpose());
+        DVector::from_fn(xs.nrows(), |i, _| {
+            phi.row(i)
+                .transpose()
+                .dot(&solved.column(i))
+                .max(0.0)
+                .sqrt()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::gaussian::kernel::{
+        ConstantKernel, Kernel, MaternKernel, RBFKernel, SEardKernel,
+    };
+    use crate::process::gaussian::GaussianProcess;
+    use crate::process::RandomProcess;
+    use crate::traits::Mean;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    fn inputs() -> DMatrix<f64> {
+        DMatrix::from_fn(15, 2, |i, j| ((i + 3 * j) % 7) as f64 / 3.0)
+    }
+
+    #[test]
+    fn rbf_approximation_converges() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xABCD);
+        let kernel = RBFKernel::new(1.2).unwrap();
+        let curve =
+            rff_error_curve(&kernel, &inputs(), &[10, 20_000], &mut rng);
+        assert!(curve[0].relative_error > curve[1].relative_error);
+        assert!(curve[1].max_abs_error < 0.05);
+    }
+
+    #[test]
+    fn seard_and_matern_approximations() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xABCD);
+        let x = inputs();
+
+        let seard =
+            SEardKernel::new(DVector::from_column_slice(&[0.8, 2.0])).unwrap();
+        let curve = rff_error_curve(&seard, &x, &[20_000], &mut rng);
+        assert!(curve[0].max_abs_error < 0.05);
+
+        let matern = MaternKernel::new(2.5, 1.0).unwrap();
+        let curve = rff_error_curve(&matern, &x, &[20_000], &mut rng);
+        assert!(curve[0].max_abs_error < 0.05);
+    }
+
+    #[test]
+    fn scaled_kernel_variance() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xABCD);
+        let kernel =
+            ConstantKernel::new(3.0).unwrap() * RBFKernel::new(1.0).unwrap();
+        let x = inputs();
+        let rff = RandomFourierFeatures::new(&kernel, 2, 20_000, &mut rng);
+        let err = rff.approx_covariance(&x, &x) - kernel.covariance(&x, &x);
+        assert!(err.abs().max() < 0.15);
+    }
+
+    #[test]
+    fn regression_matches_exact_gp() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xABCD);
+        let kernel = RBFKernel::new(1.0).unwrap();
+        let xs = DMatrix::from_fn(30, 1, |i, _| i as f64 / 5.0);
+        let ys = xs.column(0).map(|x| x.cos());
+        let x_test = DMatrix::from_column_slice(3, 1, &[0.5, 2.2, 4.1]);
+
+        let gp = GaussianProcess::train(
+            kernel.clone(),
+            xs.clone(),
+            ys.clone(),
+            NoiseModel::Uniform(0.1),
+        )
+        .unwrap();
+        let indices: Vec<DVector<f64>> = x_test
+            .iter()
+            .map(|&x| DVector::from_element(1, x))
+            .collect();
+        let gp_pred = gp.sample_function(&indices);
+        let gp_mean = gp_pred.mean().unwrap();
+
+        let features = RandomFourierFeatures::new(&kernel, 1, 400, &mut rng);
+        let model =
+            RffRegression::fit(features, &xs, &ys, NoiseModel::Uniform(0.1))
+                .unwrap();
+        let mean = model.mean(&x_test);
+        assert!((mean - &gp_mean).abs().max() < 0.05);
+
+        let std = model.std(&x_test);
+        let cov = model.cov(&x_test);
+        assert!(std.relative_eq(&cov.diagonal().map(f64::sqrt), 1E-8, 1E-8));
+        assert!(gp_pred.std().relative_eq(&std, 0.05, 0.05));
+    }
+
+    #[test]
+    fn regression_rejects_misshapen_noise() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xABCD);
+        let kernel = RBFKernel::new(1.0).unwrap();
+        let features = RandomFourierFeatures::new(&kernel, 1, 10, &mut rng);
+        let xs = DMatrix::zeros(4, 1);
+        let ys = DVector::zeros(4);
+        let noise = NoiseModel::PerPoint(DVector::from_element(3, 0.1));
+        assert!(matches!(
+            RffRegression::fit(features, &xs, &ys, noise),
+            Err(GaussianProcessError::MisshapenNoiseModel(_))
+        ));
+    }
+}
@@ -0,0 +1,703 @@
+//! Sparse Gaussian process approximations based on inducing points
+use nalgebra::linalg::Cholesky;
+use nalgebra::{DMatrix, DVector, Dyn};
+use rand::Rng;
+
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use super::kernel::Kernel;
+use super::{GaussianProcessError, NoiseModel};
+use crate::consts::HALF_LN_2PI;
+use crate::dist::MvGaussian;
+
+/// Jitter, relative to the largest prior variance, added to the diagonal of
+/// the inducing point covariance
+const INDUCING_JITTER: f64 = 1E-8;
+
+/// Maximum number of Lloyd iterations in [`kmeans_inducing`]
+const MAX_KMEANS_ITERS: usize = 100;
+
+/// Approximation used by a [`SparseGaussianProcess`]
+///
+/// Each approximation replaces the prior covariance of the training outputs,
+/// `K_ff`, with the low-rank `Q_ff = K_fu K_uu⁻¹ K_uf` given by the inducing
+/// inputs, `u`. They differ in how they treat the diagonal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum SparseStrategy {
+    /// Deterministic training conditional: `Q_ff + σ²I`
+    Dtc,
+    /// Fully independent training conditional:
+    /// `Q_ff + diag(K_ff - Q_ff) + σ²I`
+    Fitc,
+    /// Variational free energy (Titsias, 2009): the DTC posterior, with the
+    /// marginal likelihood replaced by the lower bound
+    /// `ln N(y | 0, Q_ff + σ²I) - tr(K_ff - Q_ff) / 2σ²` on the exact one
+    Vfe,
+}
+
+/// Gaussian process approximated through a set of inducing inputs
+///
+/// Training costs `O(nm²)` for `n` training points and `m` inducing points
+/// rather than the `O(n³)` of the exact
+/// [`GaussianProcess`](super::GaussianProcess).
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::{DMatrix, DVector};
+/// use rv::process::gaussian::kernel::RBFKernel;
+/// use rv::process::gaussian::{
+///     NoiseModel, SparseGaussianProcess, SparseStrategy,
+/// };
+///
+/// let xs = DMatrix::from_fn(100, 1, |i, _| i as f64 / 10.0);
+/// let ys = xs.column(0).map(|x| x.sin());
+/// let inducing = DMatrix::from_fn(15, 1, |i, _| i as f64 * 10.0 / 14.0);
+///
+/// let gp = SparseGaussianProcess::train(
+///     RBFKernel::new(1.0).unwrap(),
+///     xs,
+///     ys,
+///     inducing,
+///     NoiseModel::Uniform(0.1),
+///     SparseStrategy::Fitc,
+/// )
+/// .unwrap();
+///
+/// let x_test = DMatrix::from_column_slice(1, 1, &[2.5]);
+/// assert!((gp.mean(&x_test)[0] - 2.5_f64.sin()).abs() < 0.05);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct SparseGaussianProcess<K>
+where
+    K: Kernel,
+{
+    /// Covariance Kernel
+    kernel: K,
+    /// Approximation used
+    strategy: SparseStrategy,
+    /// Inducing inputs, one per row
+    inducing: DMatrix<f64>,
+    /// Cholesky decomposition of K_uu
+    kuu_chol: Cholesky<f64, Dyn>,
+    /// Cholesky decomposition of K_uu + K_uf Λ⁻¹ K_fu
+    sigma_inv_chol: Cholesky<f64, Dyn>,
+    /// (K_uu + K_uf Λ⁻¹ K_fu)⁻¹ K_uf Λ⁻¹ y
+    weights: DVector<f64>,
+    /// Log marginal likelihood of the training data
+    ln_m: f64,
+}
+
+impl<K> SparseGaussianProcess<K>
+where
+    K: Kernel,
+{
+    /// Train a sparse Gaussian process on the given data points
+    ///
+    /// # Arguments
+    /// * `kernel` - Kernel to use to determine covariance
+    /// * `x_train` - Values to use for input into `f`
+    /// * `y_train` - Known values for `f(x)`
+    /// * `inducing` - Inducing inputs, one per row
+    /// * `noise_model` - Noise model to use for fitting
+    /// * `strategy` - Sparse approximation to use
+    pub fn train(
+        kernel: K,
+        x_train: DMatrix<f64>,
+        y_train: DVector<f64>,
+        inducing: DMatrix<f64>,
+        noise_model: NoiseModel,
+        strategy: SparseStrategy,
+    ) -> Result<Self, GaussianProcessError> {
+        let n = x_train.nrows();
+        let m = inducing.nrows();
+
+        // Per-point noise variances, matching `NoiseModel::add_noise_to_kernel`
+        let mut lambda = match noise_model {
+            NoiseModel::Uniform(sigma) => {
+                DVector::from_element(n, sigma * sigma)
+            }
+            NoiseModel::PerPoint(var) if var.nrows() == n => var,
+            NoiseModel::PerPoint(var) => {
+                return Err(GaussianProcessError::MisshapenNoiseModel(format!(
+                    "Per point noise must be the same size a y_train \
+                         (expected: {}, got: {})",
+                    n,
+                    var.nrows()
+                )))
+            }
+        };
+
+        let mut kuu = kernel.covariance(&inducing, &inducing);
+        let jitter = INDUCING_JITTER * kuu.diagonal().max().max(1.0);
+        (0..m).for_each(|i| kuu[(i, i)] += jitter);
+        let kuu_chol = Cholesky::new(kuu.clone())
+            .ok_or(GaussianProcessError::NotPositiveSemiDefinite)?;
+        let kuf = kernel.covariance(&inducing, &x_train);
+
+        // Σ (K_ff - Q_ff)_ii / λ_i, subtracted from the VFE bound
+        let mut trace_penalty = 0.0;
+        if strategy != SparseStrategy::Dtc {
+            let kff_diag = kernel.diag(&x_train);
+            let v = kuu_chol
+                .l()
+                .solve_lower_triangular(&kuf)
+                .ok_or(GaussianProcessError::NotPositiveSemiDefinite)?;
+            for (i, l) in lambda.iter_mut().enumerate() {
+                let resid = (kff_diag[i] - v.column(i).norm_squared()).max(0.0);
+                if strategy == SparseStrategy::Fitc {
+                    *l += resid;
+                } else {
+                    trace_penalty += resid / *l;
+                }
+            }
+        }
+
+        let mut kuf_scaled = kuf.clone();
+        kuf_scaled
+            .column_iter_mut()
+            .zip(lambda.iter())
+            .for_each(|(mut col, l)| col /= *l);
+
+        let sigma_inv = &kuu + &kuf_scaled * kuf.transpose();
+        let sigma_inv_chol = Cholesky::new(sigma_inv)
+            .ok_or(GaussianProcessError::NotPositiveSemiDefinite)?;
+        let b = &kuf_scaled * &y_train;
+        let weights = sigma_inv_chol.solve(&b);
+
+        // Marginal likelihood N(y | 0, Q_ff + Λ) via the matrix determinant
+        // lemma and the Woodbury identity.
+        let half_ln_det_sigma_inv =
+            sigma_inv_chol.l_dirty().diagonal().map(|x| x.ln()).sum();
+        let half_ln_det_kuu =
+            kuu_chol.l_dirty().diagonal().map(|x| x.ln()).sum();
+        let ln_det = 2.0_f64.mul_add(
+            half_ln_det_sigma_inv - half_ln_det_kuu,
+            lambda.map(|l| l.ln()).sum(),
+        );
+        let quad = y_train
+            .iter()
+            .zip(lambda.iter())
+            .map(|(y, l)| y * y / l)
+            .sum::<f64>()
+            - b.dot(&weights);
+        let ln_m = (n as f64)
+            .mul_add(-HALF_LN_2PI, -0.5 * (quad + ln_det + trace_penalty));
+
+        Ok(Self {
+            kernel,
+            strategy,
+            inducing,
+            kuu_chol,
+            sigma_inv_chol,
+            weights,
+            ln_m,
+        })
+    }
+
+    /// Return the kernel being used in this GP
+    pub fn kernel(&self) -> &K {
+        &self.kernel
+    }
+
+    /// Return the sparse approximation used
+    pub fn strategy(&self) -> SparseStrategy {
+        self.strategy
+    }
+
+    /// Return the inducing inputs
+    pub fn inducing(&self) -> &DMatrix<f64> {
+        &self.inducing
+    }
+
+    /// Log marginal likelihood of the training data under the approximation
+    ///
+    /// For [`SparseStrategy::Vfe`] this is a lower bound on the log marginal
+    /// likelihood of the exact GP.
+    pub fn ln_m(&self) -> f64 {
+        self.ln_m
+    }
+
+    /// Posterior mean of the function at the rows of `xs`
+    pub fn mean(&self, xs: &DMatrix<f64>) -> DVector<f64> {
+        self.kernel.covariance(xs, &self.inducing) * &self.weights
+    }
+
+    /// Posterior covariance of the function at the rows of `xs`
+    pub fn cov(&self, xs: &DMatrix<f64>) -> DMatrix<f64> {
+        let kus = self.kernel.covariance(&self.inducing, xs);
+        let kss = self.kernel.covariance(xs, xs);
+        kss - kus.transpose() * self.kuu_chol.solve(&kus)
+            + kus.transpose() * self.sigma_inv_chol.solve(&kus)
+    }
+
+    /// Posterior variance of the function at the rows of `xs`
+    pub fn variance(&self, xs: &DMatrix<f64>) -> DVector<f64> {
+        let kus = self.kernel.covariance(&self.inducing, xs);
+        let a = self.kuu_chol.solve(&kus);
+        let b = self.sigma_inv_chol.solve(&kus);
+        let kss = self.kernel.diag(xs);
+        DVector::from_fn(xs.nrows(), |i, _| {
+            let k = kus.column(i);
+            (kss[i] - k.dot(&a.column(i)) + k.dot(&b.column(i))).max(0.0)
+        })
+    }
+
+    /// Posterior standard deviation of the function at the rows of `xs`
+    pub fn std(&self, xs: &DMatrix<f64>) -> DVector<f64> {
+        self.variance(xs).map(f64::sqrt)
+    }
+
+    /// Joint posterior distribution of the function at the rows of `xs`
+    pub fn predict(&self, xs: &DMatrix<f64>) -> MvGaussian {
+        MvGaussian::new_unchecked(self.mean(xs), self.cov(xs))
+    }
+}
+
+/// Choose `n_inducing` inducing inputs as the k-means cluster centers of the
+/// rows of `xs`
+///
+/// Centers are initialized by k-means++ and refined by Lloyd's algorithm
+/// until the assignments stop changing. If `n_inducing` is at least the
+/// number of rows, the rows themselves are returned.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::DMatrix;
+/// use rv::process::gaussian::kernel::RBFKernel;
+/// use rv::process::gaussian::{
+///     kmeans_inducing, NoiseModel, SparseGaussianProcess, SparseStrategy,
+/// };
+///
+/// let mut rng = rand::thread_rng();
+/// let xs = DMatrix::from_fn(200, 1, |i, _| i as f64 / 20.0);
+/// let ys = xs.column(0).map(|x| x.sin());
+/// let inducing = kmeans_inducing(&xs, 20, &mut rng);
+/// assert_eq!(inducing.nrows(), 20);
+///
+/// let gp = SparseGaussianProcess::train(
+///     RBFKernel::new(1.0).unwrap(),
+///     xs,
+///     ys,
+///     inducing,
+///     NoiseModel::Uniform(0.1),
+///     SparseStrategy::Vfe,
+/// )
+/// .unwrap();
+///
+/// let x_test = DMatrix::from_column_slice(1, 1, &[4.2]);
+/// assert!((gp.mean(&x_test)[0] - 4.2_f64.sin()).abs() < 0.05);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `n_inducing` is zero or `xs` has no rows.
+pub fn kmeans_inducing<R: Rng>(
+    xs: &DMatrix<f64>,
+    n_inducing: usize,
+    rng: &mut R,
+) -> DMatrix<f64> {
+    assert!(n_inducing > 0, "at least one inducing point is required");
+    assert!(xs.nrows() > 0, "cannot choose inducing points without data");
+
+    let n = xs.nrows();
+    if n_inducing >= n {
+        return xs.clone();
+    }
+
+    let sq_dist = |i: usize, centers: &DMatrix<f64>, k: usize| {
+        (xs.row(i) - centers.row(k)).norm_squared()
+    };
+
+    // k-means++: each new center is a data point chosen with probability
+    // proportional to its squared distance to the nearest existing center
+    let mut centers = DMatrix::zeros(n_inducing, xs.ncols());
+    centers.set_row(0, &xs.row(rng.gen_range(0..n)));
+    let mut min_dist: Vec<f64> =
+        (0..n).map(|i| sq_dist(i, &centers, 0)).collect();
+    for k in 1..n_inducing {
+        let total: f64 = min_dist.iter().sum();
+        let ix = if total > 0.0 {
+            let mut u = rng.gen::<f64>() * total;
+            min_dist
+                .iter()
+                .position(|&d| {
+                    u -= d;
+                    u <= 0.0
+                })
+                .unwrap_or(n - 1)
+        } else {
+            rng.gen_range(0..n)
+        };
+        centers.set_row(k, &xs.row(ix));
+        min_dist.iter_mut().enumerate().for_each(|(i, d)| {
+            *d = d.min(sq_dist(i, &centers, k));
+        });
+    }
+
+    // Lloyd's algorithm
+    let mut assignment = vec![usize::MAX; n];
+    for _ in 0..MAX_KMEANS_ITERS {
+        let mut changed = false;
+        for (i, a) in assignment.iter_mut().enumerate() {
+            let nearest = (0..n_inducing)
+                .map(|k| (k, sq_dist(i, &centers, k)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(k, _)| k)
+                .unwrap();
+            if *a != nearest {
+                *a = nearest;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+
+        let mut sums = DMatrix::zeros(n_inducing, xs.ncols());
+        let mut counts = vec![0_usize; n_inducing];
+        assignment.iter().enumerate().for_each(|(i, &k)| {
+            let mut row = sums.row_mut(k);
+            row += xs.row(i);
+            counts[k] += 1;
+        });
+        // Empty clusters keep their previous center
+        counts.iter().enumerate().filter(|(_, &c)| c > 0).for_each(
+            |(k, &c)| {
+                centers.set_row(k, &(sums.row(k) / c as f64));
+            },
+        );
+    }
+
+    centers
+}
+
+/// Held-out performance of one sparse approximation
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct SparseComparison {
+    /// Approximation used
+    pub strategy: SparseStrategy,
+    /// Root mean squared error of the predictive mean
+    pub rmse: f64,
+    /// Mean negative log predictive density of the test outputs
+    pub nlpd: f64,
+    /// Log marginal likelihood of the training data
+    pub ln_m: f64,
+}
+
+/// Train one sparse GP per strategy and score each on held-out data
+///
+/// The predictive density of each test output includes the observation
+/// noise: `σ²` for `NoiseModel::Uniform(σ)`, and the mean of the per-point
+/// variances for `NoiseModel::PerPoint`.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::{DMatrix, DVector};
+/// use rv::process::gaussian::kernel::RBFKernel;
+/// use rv::process::gaussian::{
+///     compare_sparse_strategies, NoiseModel, SparseStrategy,
+/// };
+///
+/// let f = |x: f64| (2.0 * x).sin();
+/// let xs = DMatrix::from_fn(80, 1, |i, _| i as f64 / 8.0);
+/// let ys = xs.column(0).map(f);
+/// let x_test = DMatrix::from_fn(20, 1, |i, _| 0.2 + i as f64 / 2.5);
+/// let y_test = x_test.column(0).map(f);
+/// let inducing = DMatrix::from_fn(12, 1, |i, _| i as f64 * 10.0 / 11.0);
+///
+/// let results = compare_sparse_strategies(
+///     &RBFKernel::new(0.7).unwrap(),
+///     &xs,
+///     &ys,
+///     &inducing,
+///     &NoiseModel::Uniform(0.05),
+///     &x_test,
+///     &y_test,
+///     &[SparseStrategy::Dtc, SparseStrategy::Fitc],
+/// )
+/// .unwrap();
+///
+/// assert_eq!(results.len(), 2);
+/// assert!(results.iter().all(|r| r.rmse < 0.1));
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn compare_sparse_strategies<K>(
+    kernel: &K,
+    x_train: &DMatrix<f64>,
+    y_train: &DVector<f64>,
+    inducing: &DMatrix<f64>,
+    noise_model: &NoiseModel,
+    x_test: &DMatrix<f64>,
+    y_test: &DVector<f64>,
+    strategies: &[SparseStrategy],
+) -> Result<Vec<SparseComparison>, GaussianProcessError>
+where
+    K: Kernel,
+{
+    let noise_var = match noise_model {
+        NoiseModel::Uniform(sigma) => sigma * sigma,
+        NoiseModel::PerPoint(var) => var.mean(),
+    };
+    let n_test = y_test.nrows() as f64;
+
+    strategies
+        .iter()
+        .map(|&strategy| {
+            let gp = SparseGaussianProcess::train(
+                kernel.clone(),
+                x_train.clone(),
+                y_train.clone(),
+                inducing.clone(),
+                noise_model.clone(),
+                strategy,
+            )?;
+            let mean = gp.mean(x_test);
+            let var = gp.variance(x_test);
+
+            let (sse, nlpd) = mean
+                .iter()
+                .zip(var.iter())
+                .zip(y_test.iter())
+                .fold((0.0, 0.0), |(sse, nlpd), ((mu, v), y)| {
+                    let r = y - mu;
+                    let s2 = v + noise_var;
+                    (
+                        r.mul_add(r, sse),
+                        0.5_f64.mul_add(r * r / s2 + s2.ln(), nlpd)
+                            + HALF_LN_2PI,
+                    )
+                });
+
+            Ok(SparseComparison {
+                strategy,
+                rmse: (sse / n_test).sqrt(),
+                nlpd: nlpd / n_test,
+                ln_m: gp.ln_m(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::gaussian::kernel::RBFKernel;
+    use crate::process::gaussian::GaussianProcess;
+    use crate::process::RandomProcess;
+    use crate::traits::{Mean, Rv};
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    fn data() -> (DMatrix<f64>, DVector<f64>) {
+        let xs = DMatrix::from_fn(40, 1, |i, _| i as f64 / 4.0);
+        let ys = xs
+            .column(0)
+            .map(|x| 0.1_f64.mul_add((3.0 * x).cos(), x.sin()));
+        (xs, ys)
+    }
+
+    #[test]
+    fn inducing_at_training_inputs_is_exact() {
+        let (xs, ys) = data();
+        let kernel = RBFKernel::new(1.3).unwrap();
+        let noise = NoiseModel::Uniform(0.2);
+        let gp = GaussianProcess::train(
+            kernel.clone(),
+            xs.clone(),
+            ys.clone(),
+            noise.clone(),
+        )
+        .unwrap();
+        let x_test = DMatrix::from_column_slice(3, 1, &[0.3, 4.4, 8.1]);
+
+        for strategy in [
+            SparseStrategy::Dtc,
+            SparseStrategy::Fitc,
+            SparseStrategy::Vfe,
+        ] {
+            let sparse = SparseGaussianProcess::train(
+                kernel.clone(),
+                xs.clone(),
+                ys.clone(),
+                xs.clone(),
+                noise.clone(),
+                strategy,
+            )
+            .unwrap();
+            assert::close(sparse.ln_m(), gp.ln_m(), 1E-4);
+
+            let indices: Vec<DVector<f64>> = x_test
+                .iter()
+                .map(|&x| DVector::from_element(1, x))
+                .collect();
+            let pred = gp.sample_function(&indices);
+            assert!(sparse.mean(&x_test).relative_eq(
+                &pred.mean().unwrap(),
+                1E-4,
+                1E-4
+            ));
+            assert!(sparse.cov(&x_test).relative_eq(pred.cov(), 1E-4, 1E-4));
+        }
+    }
+
+    #[test]
+    fn variance_matches_cov_diagonal() {
+        let (xs, ys) = data();
+        let inducing = DMatrix::from_fn(6, 1, |i, _| i as f64 * 2.0);
+        let x_test = DMatrix::from_column_slice(4, 1, &[0.1, 3.3, 5.9, 20.0]);
+        for strategy in [SparseStrategy::Dtc, SparseStrategy::Fitc] {
+            let gp = SparseGaussianProcess::train(
+                RBFKernel::new(1.0).unwrap(),
+                xs.clone(),
+                ys.clone(),
+                inducing.clone(),
+                NoiseModel::Uniform(0.1),
+                strategy,
+            )
+            .unwrap();
+            let var = gp.variance(&x_test);
+            assert!(var.relative_eq(&gp.cov(&x_test).diagonal(), 1E-8, 1E-8));
+            // Far from the data the prior variance is recovered
+            assert::close(var[3], 1.0, 1E-3);
+        }
+    }
+
+    #[test]
+    fn fitc_and_dtc_differ_with_few_inducing_points() {
+        let (xs, ys) = data();
+        let inducing = DMatrix::from_column_slice(3, 1, &[1.0, 5.0, 9.0]);
+        let train = |strategy| {
+            SparseGaussianProcess::train(
+                RBFKernel::new(1.0).unwrap(),
+                xs.clone(),
+                ys.clone(),
+                inducing.clone(),
+                NoiseModel::Uniform(0.1),
+                strategy,
+            )
+            .unwrap()
+        };
+        let dtc = train(SparseStrategy::Dtc);
+        let fitc = train(SparseStrategy::Fitc);
+        assert!((dtc.ln_m() - fitc.ln_m()).abs() > 1E-3);
+        assert!(dtc.ln_m().is_finite() && fitc.ln_m().is_finite());
+    }
+
+    #[test]
+    fn misshapen_noise_model() {
+        let (xs, ys) = data();
+        let res = SparseGaussianProcess::train(
+            RBFKernel::default(),
+            xs.clone(),
+            ys,
+            xs,
+            NoiseModel::PerPoint(DVector::from_element(3, 0.1)),
+            SparseStrategy::Dtc,
+        );
+        assert!(matches!(
+            res,
+            Err(GaussianProcessError::MisshapenNoiseModel(_))
+        ));
+    }
+
+    #[test]
+    fn vfe_bounds_exact_marginal_likelihood() {
+        let (xs, ys) = data();
+        let kernel = RBFKernel::new(1.0).unwrap();
+        let noise = NoiseModel::Uniform(0.1);
+        let exact = GaussianProcess::train(
+            kernel.clone(),
+            xs.clone(),
+            ys.clone(),
+            noise.clone(),
+        )
+        .unwrap();
+        let train = |strategy, m: usize| {
+            let inducing =
+                DMatrix::from_fn(m, 1, |i, _| i as f64 * 10.0 / (m - 1) as f64);
+            SparseGaussianProcess::train(
+                kernel.clone(),
+                xs.clone(),
+                ys.clone(),
+                inducing,
+                noise.clone(),
+                strategy,
+            )
+            .unwrap()
+        };
+
+        let mut prev = f64::NEG_INFINITY;
+        for m in [3, 5, 8, 12] {
+            let vfe = train(SparseStrategy::Vfe, m);
+            let dtc = train(SparseStrategy::Dtc, m);
+            assert!(vfe.ln_m() <= exact.ln_m());
+            assert!(vfe.ln_m() <= dtc.ln_m());
+            // The bound tightens as inducing points are added
+            assert!(vfe.ln_m() > prev);
+            prev = vfe.ln_m();
+
+            // VFE and DTC share a posterior
+            let x_test = DMatrix::from_column_slice(2, 1, &[1.1, 7.7]);
+            assert!(vfe.mean(&x_test).relative_eq(
+                &dtc.mean(&x_test),
+                1E-12,
+                1E-12
+            ));
+        }
+    }
+
+    #[test]
+    fn predict_matches_mean_and_cov() {
+        let (xs, ys) = data();
+        let inducing = DMatrix::from_fn(6, 1, |i, _| i as f64 * 2.0);
+        let gp = SparseGaussianProcess::train(
+            RBFKernel::new(1.0).unwrap(),
+            xs,
+            ys,
+            inducing,
+            NoiseModel::Uniform(0.1),
+            SparseStrategy::Fitc,
+        )
+        .unwrap();
+        let x_test = DMatrix::from_column_slice(3, 1, &[0.5, 2.5, 9.0]);
+        let pred = gp.predict(&x_test);
+        assert_eq!(pred.mu(), &gp.mean(&x_test));
+        assert_eq!(pred.cov(), &gp.cov(&x_test));
+        assert!(pred.ln_f(&gp.mean(&x_test)).is_finite());
+    }
+
+    #[test]
+    fn kmeans_inducing_finds_clusters() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x123);
+        let centers = [[-5.0, 0.0], [0.0, 5.0], [5.0, 0.0]];
+        let xs = DMatrix::from_fn(90, 2, |i, j| {
+            0.1_f64.mul_add(rng.gen::<f64>(), centers[i % 3][j])
+        });
+        let inducing = kmeans_inducing(&xs, 3, &mut rng);
+        assert_eq!(inducing.shape(), (3, 2));
+        for c in centers {
+            let nearest = inducing
+                .row_iter()
+                .map(|r| (r[0] - c[0]).hypot(r[1] - c[1]))
+                .fold(f64::INFINITY, f64::min);
+            assert!(nearest < 0.1);
+        }
+    }
+
+    #[test]
+    fn kmeans_inducing_returns_data_when_few_rows() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x123);
+        let xs = DMatrix::from_column_slice(3, 1, &[1.0, 2.0, 3.0]);
+        assert_eq!(kmeans_inducing(&xs, 5, &mut rng), xs);
+    }
+}
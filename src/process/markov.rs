@@ -0,0 +1,682 @@
+//! Gauss-Markov processes: Wiener, Ornstein-Uhlenbeck, and random walks
+//!
+//! Each process moves between observation times by an affine Gaussian
+//! transition, `x₁ | x₀ ~ N(a x₀ + c, v)`, so paths can be simulated,
+//! scored, and bridged between fixed end points exactly, with no
+//! discretization error.
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use rand::Rng;
+use rand_distr::StandardNormal;
+use std::fmt;
+use std::fmt::Debug;
+use std::ops::Sub;
+
+use crate::consts::HALF_LN_2PI;
+use crate::dist::Gaussian;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum MarkovProcessError {
+    /// The drift or long-run mean is infinite or NaN
+    MeanNotFinite { mean: f64 },
+    /// The volatility is less than or equal to zero
+    SigmaTooLow { sigma: f64 },
+    /// The volatility is infinite or NaN
+    SigmaNotFinite { sigma: f64 },
+    /// The mean-reversion rate is less than or equal to zero
+    RateTooLow { rate: f64 },
+    /// The mean-reversion rate is infinite or NaN
+    RateNotFinite { rate: f64 },
+    /// A time is before the start of the path
+    TimeBeforeStart {
+        /// Index of the time
+        ix: usize,
+    },
+    /// A time is not after the one before it
+    TimesNotIncreasing {
+        /// Index of the time
+        ix: usize,
+    },
+    /// A time is after the end of a bridge
+    TimeAfterEnd {
+        /// Index of the time
+        ix: usize,
+    },
+    /// The end of a bridge is not after its start
+    EmptyBridge,
+    /// There are a different number of times and states
+    LengthMismatch {
+        /// The number of times
+        n_times: usize,
+        /// The number of states
+        n_xs: usize,
+    },
+}
+
+/// A one-dimensional Markov process with affine Gaussian transitions
+///
+/// Implementors give the transition moments; simulation, path densities,
+/// and bridges follow from them.
+pub trait MarkovProcess {
+    /// The type of time, `f64` for continuous time and `usize` for discrete
+    type Time: Copy + PartialOrd + Sub<Output = Self::Time> + Debug;
+
+    /// The time and state the process starts from
+    fn start(&self) -> (Self::Time, f64);
+
+    /// The moments `(a, c, v)` of the transition over `dt`, such that
+    /// `x₁ | x₀ ~ N(a x₀ + c, v)`
+    fn transition_moments(&self, dt: Self::Time) -> (f64, f64, f64);
+
+    /// The distribution of the state `dt` after being at `x0`, or `None`
+    /// if the transition is deterministic (`dt` is zero)
+    fn transition(&self, x0: f64, dt: Self::Time) -> Option<Gaussian> {
+        let (a, c, v) = self.transition_moments(dt);
+        if v > 0.0 {
+            Some(Gaussian::new_unchecked(a.mul_add(x0, c), v.sqrt()))
+        } else {
+            None
+        }
+    }
+
+    /// The log density of moving from `x0` to `x1` in `dt`
+    fn ln_f_transition(&self, x0: f64, x1: f64, dt: Self::Time) -> f64 {
+        let (a, c, v) = self.transition_moments(dt);
+        let r = x1 - a.mul_add(x0, c);
+        if v > 0.0 {
+            (-0.5 * r * r)
+                .mul_add(v.recip(), (-0.5_f64).mul_add(v.ln(), -HALF_LN_2PI))
+        } else if r == 0.0 {
+            f64::INFINITY
+        } else {
+            f64::NEG_INFINITY
+        }
+    }
+
+    /// The log density of visiting states `xs` at `times`, given the start
+    fn ln_f_path(
+        &self,
+        times: &[Self::Time],
+        xs: &[f64],
+    ) -> Result<f64, MarkovProcessError> {
+        if times.len() != xs.len() {
+            return Err(MarkovProcessError::LengthMismatch {
+                n_times: times.len(),
+                n_xs: xs.len(),
+            });
+        }
+        let start = self.start();
+        check_times(start.0, times, None)?;
+        let (_, _, ln_f) = times.iter().zip(xs.iter()).fold(
+            (start.0, start.1, 0.0),
+            |(t0, x0, ln_f), (&t1, &x1)| {
+                (t1, x1, ln_f + self.ln_f_transition(x0, x1, t1 - t0))
+            },
+        );
+        Ok(ln_f)
+    }
+
+    /// Simulate the states at `times`, which must be increasing and not
+    /// before the start
+    fn simulate<R: Rng>(
+        &self,
+        times: &[Self::Time],
+        rng: &mut R,
+    ) -> Result<Vec<f64>, MarkovProcessError> {
+        let (mut t0, mut x0) = self.start();
+        check_times(t0, times, None)?;
+        Ok(times
+            .iter()
+            .map(|&t1| {
+                let (a, c, v) = self.transition_moments(t1 - t0);
+                let z: f64 = rng.sample(StandardNormal);
+                x0 = v.sqrt().mul_add(z, a.mul_add(x0, c));
+                t0 = t1;
+                x0
+            })
+            .collect())
+    }
+
+    /// Simulate the states at `times` given the process is at `from.1` at
+    /// time `from.0` and at `to.1` at time `to.0`
+    ///
+    /// For the Wiener process this is the Brownian bridge. The draws are
+    /// exact: each state is drawn from its distribution given the previous
+    /// state and the end point.
+    fn bridge<R: Rng>(
+        &self,
+        from: (Self::Time, f64),
+        to: (Self::Time, f64),
+        times: &[Self::Time],
+        rng: &mut R,
+    ) -> Result<Vec<f64>, MarkovProcessError> {
+        let (mut t0, mut x0) = from;
+        let (t_end, x_end) = to;
+        if t_end <= t0 {
+            return Err(MarkovProcessError::EmptyBridge);
+        }
+        check_times(t0, times, Some(t_end))?;
+        Ok(times
+            .iter()
+            .map(|&t1| {
+                let (a1, c1, v1) = self.transition_moments(t1 - t0);
+                let (a2, c2, v2) = self.transition_moments(t_end - t1);
+                let prior_mean = a1.mul_add(x0, c1);
+                x0 = if v1 <= 0.0 {
+                    prior_mean
+                } else if v2 <= 0.0 {
+                    (x_end - c2) / a2
+                } else {
+                    let precision = (a2 * a2).mul_add(v2.recip(), v1.recip());
+                    let mean = (a2 * (x_end - c2))
+                        .mul_add(v2.recip(), prior_mean / v1)
+                        / precision;
+                    let z: f64 = rng.sample(StandardNormal);
+                    z.mul_add(precision.recip().sqrt(), mean)
+                };
+                t0 = t1;
+                x0
+            })
+            .collect())
+    }
+}
+
+/// Check that `times` are increasing, no earlier than `start`, and no
+/// later than `end`
+fn check_times<T: Copy + PartialOrd>(
+    start: T,
+    times: &[T],
+    end: Option<T>,
+) -> Result<(), MarkovProcessError> {
+    use std::cmp::Ordering::{Equal, Greater, Less};
+    let mut prev: Option<T> = None;
+    for (ix, &t) in times.iter().enumerate() {
+        // Incomparable (NaN) times are rejected
+        if !matches!(t.partial_cmp(&start), Some(Greater | Equal)) {
+            return Err(MarkovProcessError::TimeBeforeStart { ix });
+        }
+        if prev.is_some_and(|p| !matches!(t.partial_cmp(&p), Some(Greater))) {
+            return Err(MarkovProcessError::TimesNotIncreasing { ix });
+        }
+        if end.is_some_and(|e| !matches!(t.partial_cmp(&e), Some(Less | Equal)))
+        {
+            return Err(MarkovProcessError::TimeAfterEnd { ix });
+        }
+        prev = Some(t);
+    }
+    Ok(())
+}
+
+fn check_mean(mean: f64) -> Result<(), MarkovProcessError> {
+    if mean.is_finite() {
+        Ok(())
+    } else {
+        Err(MarkovProcessError::MeanNotFinite { mean })
+    }
+}
+
+fn check_sigma(sigma: f64) -> Result<(), MarkovProcessError> {
+    if sigma <= 0.0 {
+        Err(MarkovProcessError::SigmaTooLow { sigma })
+    } else if !sigma.is_finite() {
+        Err(MarkovProcessError::SigmaNotFinite { sigma })
+    } else {
+        Ok(())
+    }
+}
+
+/// Wiener process (Brownian motion) with drift,
+/// `dX = μ dt + σ dW`
+///
+/// # Example
+///
+/// Pin a path at both ends with a Brownian bridge
+///
+/// ```
+/// use rv::process::markov::{MarkovProcess, WienerProcess};
+///
+/// let mut rng = rand::thread_rng();
+/// let wiener = WienerProcess::standard();
+///
+/// let times: Vec<f64> = (1..=10).map(|i| i as f64 / 10.0).collect();
+/// let path = wiener.simulate(&times, &mut rng).unwrap();
+/// assert_eq!(path.len(), 10);
+///
+/// let bridge = wiener
+///     .bridge((0.0, 0.0), (1.0, 2.0), &times, &mut rng)
+///     .unwrap();
+/// assert_eq!(bridge[9], 2.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct WienerProcess {
+    /// Drift per unit time
+    drift: f64,
+    /// Volatility
+    sigma: f64,
+    /// Start time
+    t0: f64,
+    /// Start state
+    x0: f64,
+}
+
+impl WienerProcess {
+    /// Create a Wiener process with `drift` and volatility `sigma`, starting
+    /// at zero at time zero
+    pub fn new(drift: f64, sigma: f64) -> Result<Self, MarkovProcessError> {
+        check_mean(drift)?;
+        check_sigma(sigma)?;
+        Ok(Self::new_unchecked(drift, sigma))
+    }
+
+    /// Create a Wiener process without checking the parameters
+    #[inline]
+    pub fn new_unchecked(drift: f64, sigma: f64) -> Self {
+        WienerProcess {
+            drift,
+            sigma,
+            t0: 0.0,
+            x0: 0.0,
+        }
+    }
+
+    /// Standard Brownian motion: no drift and unit volatility
+    #[inline]
+    pub fn standard() -> Self {
+        Self::new_unchecked(0.0, 1.0)
+    }
+
+    /// Start from `x0` at time `t0`
+    ///
+    /// # Panics
+    /// If `t0` or `x0` is not finite
+    #[must_use]
+    pub fn with_start(mut self, t0: f64, x0: f64) -> Self {
+        assert!(t0.is_finite() && x0.is_finite(), "start must be finite");
+        self.t0 = t0;
+        self.x0 = x0;
+        self
+    }
+
+    /// Drift per unit time
+    #[inline]
+    pub fn drift(&self) -> f64 {
+        self.drift
+    }
+
+    /// Volatility
+    #[inline]
+    pub fn sigma(&self) -> f64 {
+        self.sigma
+    }
+}
+
+impl MarkovProcess for WienerProcess {
+    type Time = f64;
+
+    fn start(&self) -> (f64, f64) {
+        (self.t0, self.x0)
+    }
+
+    fn transition_moments(&self, dt: f64) -> (f64, f64, f64) {
+        (1.0, self.drift * dt, self.sigma * self.sigma * dt)
+    }
+}
+
+/// Ornstein-Uhlenbeck process, `dX = θ (μ - X) dt + σ dW`
+///
+/// Reverts to the mean `μ` at rate `θ`; its stationary distribution is
+/// `N(μ, σ² / 2θ)`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct OrnsteinUhlenbeck {
+    /// Mean-reversion rate
+    rate: f64,
+    /// Long-run mean
+    mean: f64,
+    /// Volatility
+    sigma: f64,
+    /// Start time
+    t0: f64,
+    /// Start state
+    x0: f64,
+}
+
+impl OrnsteinUhlenbeck {
+    /// Create an Ornstein-Uhlenbeck process reverting to `mean` at `rate`
+    /// with volatility `sigma`, starting at `mean` at time zero
+    pub fn new(
+        rate: f64,
+        mean: f64,
+        sigma: f64,
+    ) -> Result<Self, MarkovProcessError> {
+        if rate <= 0.0 {
+            return Err(MarkovProcessError::RateTooLow { rate });
+        }
+        if !rate.is_finite() {
+            return Err(MarkovProcessError::RateNotFinite { rate });
+        }
+        check_mean(mean)?;
+        check_sigma(sigma)?;
+        Ok(Self::new_unchecked(rate, mean, sigma))
+    }
+
+    /// Create an Ornstein-Uhlenbeck process without checking the
+    /// parameters
+    #[inline]
+    pub fn new_unchecked(rate: f64, mean: f64, sigma: f64) -> Self {
+        OrnsteinUhlenbeck {
+            rate,
+            mean,
+            sigma,
+            t0: 0.0,
+            x0: mean,
+        }
+    }
+
+    /// Start from `x0` at time `t0`
+    ///
+    /// # Panics
+    /// If `t0` or `x0` is not finite
+    #[must_use]
+    pub fn with_start(mut self, t0: f64, x0: f64) -> Self {
+        assert!(t0.is_finite() && x0.is_finite(), "start must be finite");
+        self.t0 = t0;
+        self.x0 = x0;
+        self
+    }
+
+    /// Mean-reversion rate
+    #[inline]
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Long-run mean
+    #[inline]
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Volatility
+    #[inline]
+    pub fn sigma(&self) -> f64 {
+        self.sigma
+    }
+
+    /// The stationary distribution, `N(μ, σ² / 2θ)`
+    pub fn stationary(&self) -> Gaussian {
+        Gaussian::new_unchecked(
+            self.mean,
+            self.sigma / (2.0 * self.rate).sqrt(),
+        )
+    }
+}
+
+impl MarkovProcess for OrnsteinUhlenbeck {
+    type Time = f64;
+
+    fn start(&self) -> (f64, f64) {
+        (self.t0, self.x0)
+    }
+
+    fn transition_moments(&self, dt: f64) -> (f64, f64, f64) {
+        let a = (-self.rate * dt).exp();
+        // 1 - a² without cancellation for small dt
+        let one_m_a2 = -(-2.0 * self.rate * dt).exp_m1();
+        (
+            a,
+            self.mean * (-(-self.rate * dt).exp_m1()),
+            self.sigma * self.sigma * one_m_a2 / (2.0 * self.rate),
+        )
+    }
+}
+
+/// Gaussian random walk in discrete time,
+/// `Xₙ₊₁ = Xₙ + μ + σ εₙ` with `εₙ ~ N(0, 1)`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct RandomWalk {
+    /// Mean step
+    drift: f64,
+    /// Step standard deviation
+    sigma: f64,
+    /// Start step
+    t0: usize,
+    /// Start state
+    x0: f64,
+}
+
+impl RandomWalk {
+    /// Create a random walk with mean step `drift` and step standard
+    /// deviation `sigma`, starting at zero at step zero
+    pub fn new(drift: f64, sigma: f64) -> Result<Self, MarkovProcessError> {
+        check_mean(drift)?;
+        check_sigma(sigma)?;
+        Ok(Self::new_unchecked(drift, sigma))
+    }
+
+    /// Create a random walk without checking the parameters
+    #[inline]
+    pub fn new_unchecked(drift: f64, sigma: f64) -> Self {
+        RandomWalk {
+            drift,
+            sigma,
+            t0: 0,
+            x0: 0.0,
+        }
+    }
+
+    /// Start from `x0` at step `t0`
+    ///
+    /// # Panics
+    /// If `x0` is not finite
+    #[must_use]
+    pub fn with_start(mut self, t0: usize, x0: f64) -> Self {
+        assert!(x0.is_finite(), "start must be finite");
+        self.t0 = t0;
+        self.x0 = x0;
+        self
+    }
+
+    /// Mean step
+    #[inline]
+    pub fn drift(&self) -> f64 {
+        self.drift
+    }
+
+    /// Step standard deviation
+    #[inline]
+    pub fn sigma(&self) -> f64 {
+        self.sigma
+    }
+}
+
+impl MarkovProcess for RandomWalk {
+    type Time = usize;
+
+    fn start(&self) -> (usize, f64) {
+        (self.t0, self.x0)
+    }
+
+    fn transition_moments(&self, dt: usize) -> (f64, f64, f64) {
+        let n = dt as f64;
+        (1.0, self.drift * n, self.sigma * self.sigma * n)
+    }
+}
+
+impl std::error::Error for MarkovProcessError {}
+
+impl fmt::Display for MarkovProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MeanNotFinite { mean } => {
+                write!(f, "non-finite mean: {}", mean)
+            }
+            Self::SigmaTooLow { sigma } => {
+                write!(f, "sigma ({}) must be greater than zero", sigma)
+            }
+            Self::SigmaNotFinite { sigma } => {
+                write!(f, "non-finite sigma: {}", sigma)
+            }
+            Self::RateTooLow { rate } => {
+                write!(f, "rate ({}) must be greater than zero", rate)
+            }
+            Self::RateNotFinite { rate } => {
+                write!(f, "non-finite rate: {}", rate)
+            }
+            Self::TimeBeforeStart { ix } => {
+                write!(f, "time {} is before the start", ix)
+            }
+            Self::TimesNotIncreasing { ix } => {
+                write!(f, "time {} is not after the previous time", ix)
+            }
+            Self::TimeAfterEnd { ix } => {
+                write!(f, "time {} is after the end of the bridge", ix)
+            }
+            Self::EmptyBridge => {
+                write!(f, "the bridge must end after it starts")
+            }
+            Self::LengthMismatch { n_times, n_xs } => {
+                write!(f, "{} times but {} states were given", n_times, n_xs)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    fn mean_var(xs: &[f64]) -> (f64, f64) {
+        let n = xs.len() as f64;
+        let mean = xs.iter().sum::<f64>() / n;
+        let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+        (mean, var)
+    }
+
+    #[test]
+    fn wiener_marginals() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x3E);
+        let wiener = WienerProcess::new(0.5, 2.0).unwrap().with_start(1.0, 3.0);
+        let ends: Vec<f64> = (0..20_000)
+            .map(|_| wiener.simulate(&[1.5, 2.0, 3.0], &mut rng).unwrap()[2])
+            .collect();
+        let (mean, var) = mean_var(&ends);
+        // x(3) ~ N(3 + 0.5 × 2, 4 × 2)
+        assert::close(mean, 4.0, 0.05);
+        assert::close(var, 8.0, 0.2);
+    }
+
+    #[test]
+    fn ou_transition_limits() {
+        let ou = OrnsteinUhlenbeck::new(2.0, 1.0, 0.5).unwrap();
+        // Long transitions forget the start
+        let far = ou.transition(-10.0, 100.0).unwrap();
+        assert::close(far.mu(), 1.0, 1E-12);
+        assert::close(far.sigma(), ou.stationary().sigma(), 1E-12);
+        // Short transitions look like Brownian motion
+        let (a, _, v) = ou.transition_moments(1E-10);
+        assert::close(a, 1.0, 1E-9);
+        assert::close(v / 1E-10, 0.25, 1E-8);
+        // Zero time is a point mass
+        assert!(ou.transition(0.3, 0.0).is_none());
+        assert_eq!(ou.ln_f_transition(0.3, 0.3, 0.0), f64::INFINITY);
+        assert_eq!(ou.ln_f_transition(0.3, 0.4, 0.0), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn ln_f_path_is_sum_of_transitions() {
+        let walk = RandomWalk::new(0.1, 1.5).unwrap().with_start(2, 1.0);
+        let times = [3, 5, 6];
+        let xs = [1.2, 0.4, 2.0];
+        let expected = Gaussian::new(1.1, 1.5).unwrap().ln_f(&1.2)
+            + Gaussian::new(1.4, 1.5 * 2_f64.sqrt()).unwrap().ln_f(&0.4)
+            + Gaussian::new(0.5, 1.5).unwrap().ln_f(&2.0);
+        assert::close(walk.ln_f_path(&times, &xs).unwrap(), expected, 1E-12);
+        assert_eq!(
+            walk.ln_f_path(&times, &xs[..2]),
+            Err(MarkovProcessError::LengthMismatch {
+                n_times: 3,
+                n_xs: 2
+            })
+        );
+    }
+
+    #[test]
+    fn brownian_bridge_moments() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x3F);
+        let wiener = WienerProcess::new(3.0, 1.0).unwrap();
+        let mids: Vec<f64> = (0..20_000)
+            .map(|_| {
+                wiener
+                    .bridge((0.0, 0.0), (2.0, 1.0), &[0.5, 1.0], &mut rng)
+                    .unwrap()[1]
+            })
+            .collect();
+        // The bridge ignores the drift: x(1) ~ N(1/2, 1 × 1 / 2)
+        let (mean, var) = mean_var(&mids);
+        assert::close(mean, 0.5, 0.02);
+        assert::close(var, 0.5, 0.02);
+    }
+
+    #[test]
+    fn ou_bridge_matches_conditional() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x40);
+        let ou = OrnsteinUhlenbeck::new(1.0, 0.0, 1.0).unwrap();
+        let mids: Vec<f64> = (0..20_000)
+            .map(|_| {
+                ou.bridge((0.0, 2.0), (2.0, 2.0), &[1.0], &mut rng).unwrap()[0]
+            })
+            .collect();
+        // Symmetric about the midpoint: mean 2·2e⁻¹ / (1 + e⁻²),
+        // variance v / (1 + e⁻²) with v = (1 - e⁻²) / 2
+        let e2 = (-2.0_f64).exp();
+        let (mean, var) = mean_var(&mids);
+        assert::close(mean, 4.0 * (-1.0_f64).exp() / (1.0 + e2), 0.02);
+        assert::close(var, 0.5 * (1.0 - e2) / (1.0 + e2), 0.02);
+    }
+
+    #[test]
+    fn bad_times_are_rejected() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x41);
+        let wiener = WienerProcess::standard().with_start(1.0, 0.0);
+        assert_eq!(
+            wiener.simulate(&[0.5, 2.0], &mut rng),
+            Err(MarkovProcessError::TimeBeforeStart { ix: 0 })
+        );
+        assert_eq!(
+            wiener.simulate(&[2.0, 2.0], &mut rng),
+            Err(MarkovProcessError::TimesNotIncreasing { ix: 1 })
+        );
+        assert_eq!(
+            wiener.simulate(&[2.0, f64::NAN], &mut rng),
+            Err(MarkovProcessError::TimeBeforeStart { ix: 1 })
+        );
+        assert_eq!(
+            wiener.bridge((0.0, 0.0), (1.0, 0.0), &[0.5, 1.5], &mut rng),
+            Err(MarkovProcessError::TimeAfterEnd { ix: 1 })
+        );
+        assert_eq!(
+            wiener.bridge((1.0, 0.0), (1.0, 0.0), &[], &mut rng),
+            Err(MarkovProcessError::EmptyBridge)
+        );
+        assert_eq!(
+            WienerProcess::new(0.0, 0.0),
+            Err(MarkovProcessError::SigmaTooLow { sigma: 0.0 })
+        );
+        assert_eq!(
+            OrnsteinUhlenbeck::new(-1.0, 0.0, 1.0),
+            Err(MarkovProcessError::RateTooLow { rate: -1.0 })
+        );
+    }
+}
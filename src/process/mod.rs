@@ -10,6 +10,8 @@ use rand::Rng;
 use crate::traits::Rv;
 
 pub mod gaussian;
+pub mod markov;
+pub mod student;
 
 /// A representation of a generic random process
 pub trait RandomProcess<X>
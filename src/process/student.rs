@@ -0,0 +1,660 @@
+//! Student-t processes
+use argmin::solver::{linesearch::MoreThuenteLineSearch, quasinewton::LBFGS};
+use nalgebra::linalg::Cholesky;
+use nalgebra::{DMatrix, DVector, Dyn};
+use rand::Rng;
+use rand_distr::ChiSquared;
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+use special::Gamma as _;
+use std::sync::OnceLock;
+
+use super::gaussian::kernel::{Kernel, KernelError};
+use super::gaussian::{GaussianProcessError, NoiseModel};
+use super::{RandomProcess, RandomProcessMle};
+use crate::consts::LN_PI;
+use crate::traits::{Mean, Rv, Variance};
+
+/// Errors from [`StudentTProcess`]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum StudentTProcessError {
+    /// The degrees of freedom are less than or equal to two
+    DofTooLow { nu: f64 },
+    /// The degrees of freedom are infinite or NaN
+    DofNotFinite { nu: f64 },
+    /// Error shared with Gaussian processes
+    Gp(GaussianProcessError),
+}
+
+impl std::error::Error for StudentTProcessError {}
+
+impl std::fmt::Display for StudentTProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DofTooLow { nu } => {
+                write!(f, "degrees of freedom ({}) must exceed two", nu)
+            }
+            Self::DofNotFinite { nu } => {
+                write!(f, "non-finite degrees of freedom: {}", nu)
+            }
+            Self::Gp(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<GaussianProcessError> for StudentTProcessError {
+    fn from(e: GaussianProcessError) -> Self {
+        Self::Gp(e)
+    }
+}
+
+impl From<KernelError> for StudentTProcessError {
+    fn from(e: KernelError) -> Self {
+        Self::Gp(GaussianProcessError::KernelError(e))
+    }
+}
+
+/// Student-t process regression (Shah, Wilson & Ghahramani, 2014)
+///
+/// A Student-t process with `ν > 2` degrees of freedom has the same mean and
+/// covariance as the Gaussian process with kernel `k`, but its finite
+/// dimensional distributions are multivariate Student's t. The predictive
+/// mean is the same as the GP's, while the predictive covariance grows with
+/// how poorly the kernel explains the training data, and the predictive
+/// tails are heavier. As `ν → ∞` it becomes a Gaussian process.
+///
+/// The observation noise is part of the covariance, so it is Student's t
+/// too. The parameters are those of the kernel followed by `ln(ν - 2)`.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::{DMatrix, DVector};
+/// use rv::process::gaussian::kernel::RBFKernel;
+/// use rv::process::gaussian::NoiseModel;
+/// use rv::process::student::StudentTProcess;
+/// use rv::process::RandomProcessMle;
+/// use rv::traits::*;
+///
+/// let xs = DMatrix::from_fn(20, 1, |i, _| i as f64 / 4.0);
+/// let ys: DVector<f64> = xs.column(0).map(|x| x.sin());
+///
+/// let tp = StudentTProcess::fit(
+///     RBFKernel::new(1.0).unwrap(),
+///     5.0,
+///     xs,
+///     ys,
+///     NoiseModel::Uniform(0.1),
+/// )
+/// .unwrap();
+///
+/// let mut rng = rand::thread_rng();
+/// let tp = tp.optimize(100, 2, &mut rng).unwrap();
+///
+/// let x_test = DMatrix::from_column_slice(2, 1, &[1.0, 10.0]);
+/// let pred = tp.predict(&x_test);
+/// let mean = pred.mean().unwrap();
+/// assert!((mean[0] - 1.0_f64.sin()).abs() < 0.1);
+///
+/// // Far from the data the prediction is uncertain
+/// let var = pred.variance().unwrap();
+/// assert!(var[1] > 10.0 * var[0]);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct StudentTProcess<K>
+where
+    K: Kernel,
+{
+    /// Covariance kernel
+    kernel: K,
+    /// Degrees of freedom
+    nu: f64,
+    /// Noise added to the training covariance
+    noise_model: NoiseModel,
+    /// x values used in training
+    x_train: DMatrix<f64>,
+    /// y values used in training
+    y_train: DVector<f64>,
+    /// Cholesky decomposition of the training covariance, including noise
+    k_chol: Cholesky<f64, Dyn>,
+    /// K⁻¹ y
+    alpha: DVector<f64>,
+    /// yᵀ K⁻¹ y
+    beta: f64,
+}
+
+fn validate_nu(nu: f64) -> Result<(), StudentTProcessError> {
+    if !nu.is_finite() {
+        Err(StudentTProcessError::DofNotFinite { nu })
+    } else if nu <= 2.0 {
+        Err(StudentTProcessError::DofTooLow { nu })
+    } else {
+        Ok(())
+    }
+}
+
+/// Log density of a multivariate Student's t with `nu` degrees of freedom
+/// and covariance with log determinant `ln_det`, at a point with squared
+/// Mahalanobis distance `maha` under the covariance
+fn mvt_ln_f(nu: f64, n: f64, ln_det: f64, maha: f64) -> f64 {
+    let half_nu_n = 0.5 * (nu + n);
+    let ln_norm = half_nu_n.ln_gamma().0 - (0.5 * nu).ln_gamma().0;
+    let ln_spread = n.mul_add((nu - 2.0).ln() + LN_PI, ln_det);
+    (-half_nu_n).mul_add(
+        (maha / (nu - 2.0)).ln_1p(),
+        0.5_f64.mul_add(-ln_spread, ln_norm),
+    )
+}
+
+/// Log determinant from a Cholesky factor
+fn chol_ln_det(chol: &Cholesky<f64, Dyn>) -> f64 {
+    2.0 * chol.l_dirty().diagonal().map(|x| x.ln()).sum()
+}
+
+impl<K> StudentTProcess<K>
+where
+    K: Kernel,
+{
+    /// Fit a Student-t process to the given data points
+    ///
+    /// # Arguments
+    /// * `kernel` - Kernel to use to determine covariance
+    /// * `nu` - Degrees of freedom, greater than two
+    /// * `x_train` - Values to use for input into `f`
+    /// * `y_train` - Known values for `f(x)`
+    /// * `noise_model` - Noise model to use for fitting
+    pub fn fit(
+        kernel: K,
+        nu: f64,
+        x_train: DMatrix<f64>,
+        y_train: DVector<f64>,
+        noise_model: NoiseModel,
+    ) -> Result<Self, StudentTProcessError> {
+        validate_nu(nu)?;
+        if y_train.nrows() != x_train.nrows() {
+            return Err(GaussianProcessError::MisshapenNoiseModel(format!(
+                "y_train must have one value per row of x_train \
+                     (expected: {}, got: {})",
+                x_train.nrows(),
+                y_train.nrows()
+            ))
+            .into());
+        }
+        let k = noise_model
+            .add_noise_to_kernel(&kernel.covariance(&x_train, &x_train))
+            .map_err(GaussianProcessError::MisshapenNoiseModel)?;
+        let k_chol = Cholesky::new(k)
+            .ok_or(GaussianProcessError::NotPositiveSemiDefinite)?;
+        let alpha = k_chol.solve(&y_train);
+        let beta = y_train.dot(&alpha);
+
+        Ok(StudentTProcess {
+            kernel,
+            nu,
+            noise_model,
+            x_train,
+            y_train,
+            k_chol,
+            alpha,
+            beta,
+        })
+    }
+
+    /// The kernel being used in this process
+    #[inline]
+    pub fn kernel(&self) -> &K {
+        &self.kernel
+    }
+
+    /// The degrees of freedom
+    #[inline]
+    pub fn nu(&self) -> f64 {
+        self.nu
+    }
+
+    /// The noise model added to the training covariance
+    #[inline]
+    pub fn noise_model(&self) -> &NoiseModel {
+        &self.noise_model
+    }
+
+    /// The posterior prediction of `f` at the rows of `xs`
+    pub fn predict(&self, xs: &DMatrix<f64>) -> StudentTProcessPrediction {
+        let n = self.y_train.len() as f64;
+        let k_trans = self.kernel.covariance(xs, &self.x_train);
+        let mean = &k_trans * &self.alpha;
+
+        let v = self.k_chol.solve(&k_trans.transpose());
+        let cov_gp = self.kernel.covariance(xs, xs) - &k_trans * v;
+        // Shah et al. (2014) Equation 9
+        let scale = (self.nu + self.beta - 2.0) / (self.nu + n - 2.0);
+
+        StudentTProcessPrediction {
+            mean,
+            cov: cov_gp * scale,
+            nu: self.nu + n,
+            chol: OnceLock::new(),
+        }
+    }
+}
+
+impl<K> RandomProcess<f64> for StudentTProcess<K>
+where
+    K: Kernel,
+{
+    type Index = DVector<f64>;
+    type SampleFunction = StudentTProcessPrediction;
+    type Error = StudentTProcessError;
+
+    fn sample_function(&self, indices: &[Self::Index]) -> Self::SampleFunction {
+        let n = indices.len();
+        let m = indices.first().map(|i| i.len()).unwrap_or(0);
+        let xs = DMatrix::from_row_iterator(
+            n,
+            m,
+            indices.iter().flat_map(|i| i.iter().copied()),
+        );
+        self.predict(&xs)
+    }
+
+    fn ln_m(&self) -> f64 {
+        // Shah et al. (2014) Equation 10
+        mvt_ln_f(
+            self.nu,
+            self.y_train.len() as f64,
+            chol_ln_det(&self.k_chol),
+            self.beta,
+        )
+    }
+
+    fn ln_m_with_params(
+        &self,
+        parameter: &DVector<f64>,
+    ) -> Result<(f64, DVector<f64>), StudentTProcessError> {
+        let n_kernel = parameter.len().saturating_sub(1);
+        let kernel = self
+            .kernel
+            .reparameterize(&parameter.as_slice()[..n_kernel])?;
+        let nu = 2.0 + parameter[n_kernel].exp();
+        validate_nu(nu)?;
+
+        let (k, k_grad) = kernel
+            .covariance_with_gradient(&self.x_train)
+            .map_err(|e| StudentTProcessError::from(KernelError::from(e)))?;
+        let k = self
+            .noise_model
+            .add_noise_to_kernel(&k)
+            .map_err(GaussianProcessError::MisshapenNoiseModel)?;
+        let k_chol = Cholesky::new(k)
+            .ok_or(GaussianProcessError::NotPositiveSemiDefinite)?;
+        let alpha = k_chol.solve(&self.y_train);
+        let beta = self.y_train.dot(&alpha);
+        let n = self.y_train.len() as f64;
+
+        let ln_m = mvt_ln_f(nu, n, chol_ln_det(&k_chol), beta);
+
+        // ½ tr((c ααᵀ - K⁻¹) ∂K/∂θ) with c = (ν + n) / (ν + β - 2)
+        let c = (nu + n) / (nu + beta - 2.0);
+        let w = &alpha * alpha.transpose() * c - k_chol.inverse();
+        let mut grad: Vec<f64> = (0..n_kernel)
+            .map(|j| 0.5 * w.component_mul(&k_grad[j]).sum())
+            .collect();
+
+        // Derivative with respect to ν, times dν/d ln(ν - 2) = ν - 2
+        let d_nu = 0.5_f64.mul_add(
+            (0.5 * (nu + n)).digamma()
+                - (0.5 * nu).digamma()
+                - n / (nu - 2.0)
+                - (beta / (nu - 2.0)).ln_1p(),
+            0.5 * (nu + n) * beta / ((nu - 2.0) * (nu - 2.0 + beta)),
+        );
+        grad.push(d_nu * (nu - 2.0));
+
+        Ok((ln_m, DVector::from(grad)))
+    }
+
+    fn parameters(&self) -> DVector<f64> {
+        let kernel_params = self.kernel.parameters();
+        let n = kernel_params.len();
+        DVector::from_iterator(
+            n + 1,
+            kernel_params
+                .iter()
+                .copied()
+                .chain(std::iter::once((self.nu - 2.0).ln())),
+        )
+    }
+
+    fn set_parameters(
+        self,
+        parameters: &DVector<f64>,
+    ) -> Result<Self, StudentTProcessError> {
+        let (kernel, mut leftovers) =
+            self.kernel.consume_parameters(parameters.iter().copied())?;
+        let ln_nu =
+            leftovers.next().ok_or(KernelError::MissingParameters(1))?;
+        let n_extra = leftovers.count();
+        if n_extra > 0 {
+            return Err(KernelError::ExtraniousParameters(n_extra).into());
+        }
+
+        Self::fit(
+            kernel,
+            2.0 + ln_nu.exp(),
+            self.x_train,
+            self.y_train,
+            self.noise_model,
+        )
+    }
+}
+
+impl<K> RandomProcessMle<f64> for StudentTProcess<K>
+where
+    K: Kernel,
+{
+    type Solver = LBFGS<
+        MoreThuenteLineSearch<DVector<f64>, DVector<f64>, f64>,
+        DVector<f64>,
+        DVector<f64>,
+        f64,
+    >;
+
+    fn generate_solver() -> Self::Solver {
+        let linesearch = MoreThuenteLineSearch::new();
+        LBFGS::new(linesearch, 10)
+    }
+
+    fn random_params<R: Rng>(&self, rng: &mut R) -> DVector<f64> {
+        let n = self.parameters().len();
+        DVector::from_iterator(n, (0..n).map(|_| rng.gen_range(-5.0..5.0)))
+    }
+}
+
+/// Multivariate Student's t prediction from a [`StudentTProcess`]
+///
+/// It is parameterized by its covariance, so the scale matrix is
+/// `(ν - 2) / ν` times [`cov`](StudentTProcessPrediction::cov).
+#[derive(Clone, Debug)]
+pub struct StudentTProcessPrediction {
+    mean: DVector<f64>,
+    cov: DMatrix<f64>,
+    /// Degrees of freedom, the process's plus the number of training points
+    nu: f64,
+    chol: OnceLock<Cholesky<f64, Dyn>>,
+}
+
+impl StudentTProcessPrediction {
+    /// The predictive covariance
+    #[inline]
+    pub fn cov(&self) -> &DMatrix<f64> {
+        &self.cov
+    }
+
+    /// The predictive standard deviation at each point
+    pub fn std(&self) -> DVector<f64> {
+        self.cov.diagonal().map(f64::sqrt)
+    }
+
+    /// The degrees of freedom of the prediction
+    #[inline]
+    pub fn nu(&self) -> f64 {
+        self.nu
+    }
+
+    fn chol(&self) -> &Cholesky<f64, Dyn> {
+        self.chol.get_or_init(|| {
+            Cholesky::new(self.cov.clone())
+                .expect("predictive covariance is not positive definite")
+        })
+    }
+}
+
+impl Rv<DVector<f64>> for StudentTProcessPrediction {
+    fn ln_f(&self, x: &DVector<f64>) -> f64 {
+        let chol = self.chol();
+        let z = chol.l_dirty().solve_lower_triangular(&(x - &self.mean));
+        let maha = z.map_or(f64::INFINITY, |z| z.norm_squared());
+        mvt_ln_f(self.nu, x.len() as f64, chol_ln_det(chol), maha)
+    }
+
+    fn draw<R: Rng>(&self, rng: &mut R) -> DVector<f64> {
+        let dims = self.mean.len();
+        let norm = rand_distr::StandardNormal;
+        let z = DVector::from_fn(dims, |_, _| rng.sample(norm));
+        let chi2: f64 = rng.sample(ChiSquared::new(self.nu).unwrap());
+        let scale = ((self.nu - 2.0) / chi2).sqrt();
+        &self.mean + self.chol().l() * z * scale
+    }
+}
+
+impl Mean<DVector<f64>> for StudentTProcessPrediction {
+    fn mean(&self) -> Option<DVector<f64>> {
+        Some(self.mean.clone())
+    }
+}
+
+impl Variance<DVector<f64>> for StudentTProcessPrediction {
+    fn variance(&self) -> Option<DVector<f64>> {
+        Some(self.cov.diagonal())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::StudentsT;
+    use crate::process::gaussian::kernel::{ConstantKernel, RBFKernel};
+    use crate::process::gaussian::GaussianProcess;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    const NOISE: f64 = 0.1;
+
+    fn data() -> (DMatrix<f64>, DVector<f64>) {
+        let xs = DMatrix::from_column_slice(
+            7,
+            1,
+            &[-4.0, -3.0, -2.0, -1.0, 0.5, 1.0, 2.5],
+        );
+        let ys =
+            DVector::from_column_slice(&[0.8, 0.1, -0.9, -0.8, 0.4, 0.9, 0.5]);
+        (xs, ys)
+    }
+
+    fn kernel() -> impl Kernel {
+        ConstantKernel::new(1.5).unwrap() * RBFKernel::new(1.2).unwrap()
+    }
+
+    #[test]
+    fn prediction_scales_gp_covariance() {
+        let (xs, ys) = data();
+        let nu = 4.0;
+        let tp = StudentTProcess::fit(
+            kernel(),
+            nu,
+            xs.clone(),
+            ys.clone(),
+            NoiseModel::Uniform(NOISE),
+        )
+        .unwrap();
+        let gp = GaussianProcess::train(
+            kernel(),
+            xs,
+            ys,
+            NoiseModel::Uniform(NOISE),
+        )
+        .unwrap();
+
+        let x_test =
+            vec![DVector::from_element(1, 0.0), DVector::from_element(1, 5.0)];
+        let tp_pred = tp.sample_function(&x_test);
+        let gp_pred = gp.sample_function(&x_test);
+        assert::close(
+            tp_pred.mean().unwrap().as_slice(),
+            gp_pred.mean().unwrap().as_slice(),
+            1E-10,
+        );
+
+        let n = 7.0;
+        let beta = tp.beta;
+        let scale = (nu + beta - 2.0) / (nu + n - 2.0);
+        assert::close(
+            tp_pred.variance().unwrap().as_slice(),
+            (gp_pred.variance().unwrap() * scale).as_slice(),
+            1E-10,
+        );
+        assert_eq!(tp_pred.nu(), nu + n);
+    }
+
+    #[test]
+    fn large_dof_approaches_gaussian_process() {
+        let (xs, ys) = data();
+        let tp = StudentTProcess::fit(
+            kernel(),
+            1E9,
+            xs.clone(),
+            ys.clone(),
+            NoiseModel::Uniform(NOISE),
+        )
+        .unwrap();
+        let gp = GaussianProcess::train(
+            kernel(),
+            xs,
+            ys,
+            NoiseModel::Uniform(NOISE),
+        )
+        .unwrap();
+        assert::close(tp.ln_m(), gp.ln_m(), 1E-6);
+    }
+
+    #[test]
+    fn one_dimensional_prediction_is_students_t() {
+        let pred = StudentTProcessPrediction {
+            mean: DVector::from_element(1, 1.5),
+            cov: DMatrix::from_element(1, 1, 4.0),
+            nu: 5.0,
+            chol: OnceLock::new(),
+        };
+        // Covariance 4 means scale² = 4 (ν - 2) / ν
+        let s = (4.0 * 3.0 / 5.0_f64).sqrt();
+        let t = StudentsT::new(5.0).unwrap();
+        for x in [-2.0, 1.5, 3.0, 10.0] {
+            let expected = t.ln_f(&((x - 1.5) / s)) - s.ln();
+            assert::close(
+                pred.ln_f(&DVector::from_element(1, x)),
+                expected,
+                1E-10,
+            );
+        }
+    }
+
+    #[test]
+    fn draws_have_predicted_variance() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x57);
+        let pred = StudentTProcessPrediction {
+            mean: DVector::from_column_slice(&[1.0, -1.0]),
+            cov: DMatrix::from_row_slice(2, 2, &[2.0, 0.5, 0.5, 1.0]),
+            nu: 12.0,
+            chol: OnceLock::new(),
+        };
+        let n = 100_000;
+        let draws: Vec<DVector<f64>> = pred.sample(n, &mut rng);
+        let mean =
+            draws.iter().fold(DVector::zeros(2), |acc, d| acc + d) / n as f64;
+        let cov = draws.iter().fold(DMatrix::zeros(2, 2), |acc, d| {
+            let c = d - &mean;
+            acc + &c * c.transpose()
+        }) / n as f64;
+        assert::close(mean.as_slice(), &[1.0, -1.0], 0.02);
+        assert::close(cov.as_slice(), &[2.0, 0.5, 0.5, 1.0], 0.05);
+    }
+
+    #[test]
+    fn ln_m_gradient_matches_finite_differences() {
+        let (xs, ys) = data();
+        let tp = StudentTProcess::fit(
+            kernel(),
+            3.5,
+            xs,
+            ys,
+            NoiseModel::Uniform(NOISE),
+        )
+        .unwrap();
+        let params = tp.parameters();
+        assert_eq!(params.len(), 3);
+
+        let (ln_m, grad) = tp.ln_m_with_params(&params).unwrap();
+        assert::close(ln_m, tp.ln_m(), 1E-10);
+
+        let h = 1E-6;
+        for j in 0..params.len() {
+            let mut hi = params.clone();
+            let mut lo = params.clone();
+            hi[j] += h;
+            lo[j] -= h;
+            let fd = (tp.ln_m_with_params(&hi).unwrap().0
+                - tp.ln_m_with_params(&lo).unwrap().0)
+                / (2.0 * h);
+            assert::close(grad[j], fd, 1E-6);
+        }
+    }
+
+    #[test]
+    fn optimize_improves_ln_m() {
+        let (xs, ys) = data();
+        let tp = StudentTProcess::fit(
+            kernel(),
+            5.0,
+            xs,
+            ys,
+            NoiseModel::Uniform(NOISE),
+        )
+        .unwrap();
+        let ln_m_0 = tp.ln_m();
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x58);
+        let opt = tp.optimize(100, 2, &mut rng).unwrap();
+        assert!(opt.ln_m() > ln_m_0);
+        assert!(opt.nu() > 2.0);
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        let (xs, ys) = data();
+        assert!(matches!(
+            StudentTProcess::fit(
+                kernel(),
+                2.0,
+                xs.clone(),
+                ys.clone(),
+                NoiseModel::Uniform(NOISE)
+            ),
+            Err(StudentTProcessError::DofTooLow { .. })
+        ));
+        assert!(matches!(
+            StudentTProcess::fit(
+                kernel(),
+                f64::INFINITY,
+                xs.clone(),
+                ys,
+                NoiseModel::Uniform(NOISE)
+            ),
+            Err(StudentTProcessError::DofNotFinite { .. })
+        ));
+        assert!(matches!(
+            StudentTProcess::fit(
+                kernel(),
+                4.0,
+                xs,
+                DVector::zeros(3),
+                NoiseModel::Uniform(NOISE)
+            ),
+            Err(StudentTProcessError::Gp(
+                GaussianProcessError::MisshapenNoiseModel(_)
+            ))
+        ));
+    }
+}
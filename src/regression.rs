@@ -0,0 +1,4 @@
+//! Regression models with priors on their coefficients
+mod glm;
+
+pub use glm::{Glm, GlmError, GlmFamily};
@@ -0,0 +1,532 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::consts::LN_2PI;
+use crate::dist::{Gaussian, MvGaussian};
+use crate::misc::ln_fact;
+use crate::traits::*;
+use nalgebra::{DMatrix, DVector};
+use std::f64::consts::PI;
+use std::fmt;
+
+/// Maximum number of Newton-Raphson iterations
+const MAX_ITER: usize = 100;
+/// Stop when no coefficient moves by more than this
+const STEP_TOL: f64 = 1E-10;
+/// Maximum number of times a Newton step is halved to improve the objective
+const MAX_HALVINGS: usize = 30;
+
+/// The response distribution of a [`Glm`], each with its canonical link
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum GlmFamily {
+    /// Bernoulli responses, 0 or 1, with the logit link
+    Logistic,
+    /// Count responses with the log link
+    Poisson,
+}
+
+impl GlmFamily {
+    /// The mean response given the linear predictor, `eta`
+    fn mean(self, eta: f64) -> f64 {
+        match self {
+            Self::Logistic => 1.0 / (1.0 + (-eta).exp()),
+            Self::Poisson => eta.exp(),
+        }
+    }
+
+    /// The log likelihood of `y` given the linear predictor
+    fn ln_f(self, y: f64, eta: f64) -> f64 {
+        match self {
+            // y η - ln(1 + exp(η)), stable for large |η|
+            Self::Logistic => {
+                y.mul_add(eta, -eta.max(0.0)) - (-eta.abs()).exp().ln_1p()
+            }
+            Self::Poisson => y.mul_add(eta, -eta.exp()) - ln_fact(y as usize),
+        }
+    }
+
+    /// The variance of the response given its mean, which is the negative
+    /// second derivative of the log likelihood for canonical links
+    fn variance(self, mean: f64) -> f64 {
+        match self {
+            Self::Logistic => mean * (1.0 - mean),
+            Self::Poisson => mean,
+        }
+    }
+
+    fn supports(self, y: f64) -> bool {
+        match self {
+            Self::Logistic => y == 0.0 || y == 1.0,
+            Self::Poisson => y >= 0.0 && y.fract() == 0.0 && y.is_finite(),
+        }
+    }
+}
+
+/// Bayesian generalized linear model with a Gaussian prior on the
+/// coefficients and a Laplace approximation to the posterior
+///
+/// The response, y, of covariates x has mean g⁻¹(xᵀβ) for the link g of the
+/// [`GlmFamily`]. The coefficients, β, are fit to their maximum a posteriori
+/// (MAP) value by Newton-Raphson, and the posterior is approximated by the
+/// Gaussian centered there with the inverse of the negative Hessian of the
+/// log posterior as its covariance. Include a constant covariate for an
+/// intercept.
+///
+/// # Example
+///
+/// ```
+/// use nalgebra::{DMatrix, DVector};
+/// use rv::dist::MvGaussian;
+/// use rv::regression::{Glm, GlmFamily};
+///
+/// // Pass rates against hours of study, with an intercept
+/// let hours = [0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.5, 4.0, 4.5, 5.0];
+/// let passed = [0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0];
+/// let xs: Vec<DVector<f64>> = hours
+///     .iter()
+///     .map(|h| DVector::from_vec(vec![1.0, *h]))
+///     .collect();
+///
+/// let prior = MvGaussian::new(
+///     DVector::zeros(2),
+///     DMatrix::from_diagonal_element(2, 2, 10.0),
+/// )
+/// .unwrap();
+/// let glm = Glm::fit(GlmFamily::Logistic, &prior, &xs, &passed).unwrap();
+///
+/// // More study, better odds
+/// assert!(glm.coefficients()[1] > 0.0);
+/// let p = glm.predict_mean(&DVector::from_vec(vec![1.0, 4.0]));
+/// assert!(p > 0.5 && p < 1.0);
+///
+/// // The Laplace posterior is an MvGaussian
+/// assert_eq!(glm.posterior().ndims(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub struct Glm {
+    family: GlmFamily,
+    /// Laplace approximation to the posterior of the coefficients, centered
+    /// at the MAP
+    posterior: MvGaussian,
+    /// Laplace approximation to the log marginal likelihood
+    ln_evidence: f64,
+    /// Number of Newton-Raphson iterations taken
+    n_iter: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum GlmError {
+    /// There are no observations to fit to
+    NoData,
+    /// The number of covariate vectors and responses differ
+    LengthMismatch { n_xs: usize, n_ys: usize },
+    /// A covariate vector does not have one entry per coefficient
+    DimensionMismatch { dims: usize, expected: usize },
+    /// A covariate is infinite or NaN
+    CovariateNotFinite { ix: usize },
+    /// A response is not 0 or 1 for logistic regression, or is not a
+    /// nonnegative whole number for Poisson regression
+    InvalidResponse { ix: usize, y: f64 },
+    /// Newton-Raphson did not converge, e.g., because a coefficient runs
+    /// off to infinity under a very wide prior
+    NotConverged { n_iter: usize },
+}
+
+/// The design matrix, one row per observation
+fn design_matrix(xs: &[DVector<f64>], dims: usize) -> DMatrix<f64> {
+    DMatrix::from_fn(xs.len(), dims, |i, j| xs[i][j])
+}
+
+impl Glm {
+    /// Fit the MAP coefficients and their Laplace posterior
+    ///
+    /// # Arguments
+    /// - family: the response distribution and link
+    /// - prior: the Gaussian prior on the coefficients
+    /// - xs: the covariates of each observation
+    /// - ys: the responses, as 0 or 1 for logistic regression and as counts
+    ///   for Poisson regression
+    pub fn fit(
+        family: GlmFamily,
+        prior: &MvGaussian,
+        xs: &[DVector<f64>],
+        ys: &[f64],
+    ) -> Result<Self, GlmError> {
+        let dims = prior.ndims();
+        if xs.is_empty() {
+            return Err(GlmError::NoData);
+        } else if xs.len() != ys.len() {
+            return Err(GlmError::LengthMismatch {
+                n_xs: xs.len(),
+                n_ys: ys.len(),
+            });
+        }
+        for (ix, (x, &y)) in xs.iter().zip(ys.iter()).enumerate() {
+            if x.len() != dims {
+                return Err(GlmError::DimensionMismatch {
+                    dims: x.len(),
+                    expected: dims,
+                });
+            } else if x.iter().any(|xi| !xi.is_finite()) {
+                return Err(GlmError::CovariateNotFinite { ix });
+            } else if !family.supports(y) {
+                return Err(GlmError::InvalidResponse { ix, y });
+            }
+        }
+
+        let design = design_matrix(xs, dims);
+        let y = DVector::from_column_slice(ys);
+        let prior_precision = prior
+            .cov()
+            .clone()
+            .cholesky()
+            .expect("the prior covariance must be positive definite")
+            .inverse();
+        let ln_post = |beta: &DVector<f64>| {
+            let eta = &design * beta;
+            eta.iter()
+                .zip(y.iter())
+                .map(|(&eta, &y)| family.ln_f(y, eta))
+                .sum::<f64>()
+                + prior.ln_f(beta)
+        };
+
+        let mut beta = prior.mu().clone();
+        let mut ln_post_beta = ln_post(&beta);
+        for n_iter in 1..=MAX_ITER {
+            let (grad, neg_hess) = gradient_and_neg_hessian(
+                family,
+                &design,
+                &y,
+                &beta,
+                prior.mu(),
+                &prior_precision,
+            );
+            let chol = neg_hess
+                .cholesky()
+                .expect("the negative Hessian is positive definite");
+            let mut step = chol.solve(&grad);
+
+            // Halve the step until the log posterior does not decrease
+            let mut candidate = &beta + &step;
+            let mut ln_post_candidate = ln_post(&candidate);
+            for _ in 0..MAX_HALVINGS {
+                if ln_post_candidate >= ln_post_beta {
+                    break;
+                }
+                step /= 2.0;
+                candidate = &beta + &step;
+                ln_post_candidate = ln_post(&candidate);
+            }
+            beta = candidate;
+            ln_post_beta = ln_post_candidate;
+
+            if step.amax() < STEP_TOL {
+                let (_, neg_hess) = gradient_and_neg_hessian(
+                    family,
+                    &design,
+                    &y,
+                    &beta,
+                    prior.mu(),
+                    &prior_precision,
+                );
+                let chol = neg_hess
+                    .cholesky()
+                    .expect("the negative Hessian is positive definite");
+                let ln_det: f64 = chol
+                    .l_dirty()
+                    .diagonal()
+                    .iter()
+                    .map(|d| 2.0 * d.ln())
+                    .sum();
+                let ln_evidence = (0.5 * dims as f64)
+                    .mul_add(LN_2PI, 0.5_f64.mul_add(-ln_det, ln_post_beta));
+                let posterior = MvGaussian::new_unchecked(beta, chol.inverse());
+                return Ok(Glm {
+                    family,
+                    posterior,
+                    ln_evidence,
+                    n_iter,
+                });
+            }
+        }
+        Err(GlmError::NotConverged { n_iter: MAX_ITER })
+    }
+
+    /// The response distribution and link
+    #[inline]
+    pub fn family(&self) -> GlmFamily {
+        self.family
+    }
+
+    /// The MAP coefficients
+    #[inline]
+    pub fn coefficients(&self) -> &DVector<f64> {
+        self.posterior.mu()
+    }
+
+    /// The Laplace approximation to the posterior of the coefficients
+    #[inline]
+    pub fn posterior(&self) -> &MvGaussian {
+        &self.posterior
+    }
+
+    /// The Laplace approximation to the log marginal likelihood of the
+    /// responses, for comparing covariates or priors
+    #[inline]
+    pub fn ln_evidence(&self) -> f64 {
+        self.ln_evidence
+    }
+
+    /// The number of Newton-Raphson iterations taken to fit
+    #[inline]
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+
+    /// The approximate posterior of the linear predictor, xᵀβ, at the
+    /// covariates `x`
+    pub fn linear_predictor(&self, x: &DVector<f64>) -> Gaussian {
+        let mean = self.coefficients().dot(x);
+        let var = (self.posterior.cov() * x).dot(x);
+        Gaussian::new_unchecked(mean, var.sqrt())
+    }
+
+    /// The mean response at the covariates `x`, averaged over the
+    /// approximate posterior.
+    ///
+    /// For logistic regression this is the probit approximation,
+    /// σ(μ / √(1 + πs²/8)), and for Poisson regression it is exact,
+    /// exp(μ + s²/2), where μ and s² are the mean and variance of the linear
+    /// predictor.
+    pub fn predict_mean(&self, x: &DVector<f64>) -> f64 {
+        let eta = self.linear_predictor(x);
+        let (mu, var) = (eta.mu(), eta.sigma().powi(2));
+        match self.family {
+            GlmFamily::Logistic => {
+                let kappa = (PI / 8.0).mul_add(var, 1.0).sqrt().recip();
+                self.family.mean(kappa * mu)
+            }
+            GlmFamily::Poisson => 0.5_f64.mul_add(var, mu).exp(),
+        }
+    }
+
+    /// The mean response at the covariates `x` under the MAP coefficients
+    pub fn predict_map(&self, x: &DVector<f64>) -> f64 {
+        self.family.mean(self.coefficients().dot(x))
+    }
+}
+
+/// The gradient of the log posterior and its negative Hessian,
+/// Xᵀ(y - μ) - Λ(β - m) and XᵀWX + Λ
+fn gradient_and_neg_hessian(
+    family: GlmFamily,
+    design: &DMatrix<f64>,
+    y: &DVector<f64>,
+    beta: &DVector<f64>,
+    prior_mean: &DVector<f64>,
+    prior_precision: &DMatrix<f64>,
+) -> (DVector<f64>, DMatrix<f64>) {
+    let mean = (design * beta).map(|eta| family.mean(eta));
+    let grad = design.transpose() * (y - &mean)
+        - prior_precision * (beta - prior_mean);
+
+    let mut weighted = design.clone();
+    weighted
+        .row_iter_mut()
+        .zip(mean.iter())
+        .for_each(|(mut row, &m)| row *= family.variance(m));
+    let neg_hess = design.transpose() * weighted + prior_precision;
+    (grad, neg_hess)
+}
+
+impl std::error::Error for GlmError {}
+
+impl fmt::Display for GlmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoData => write!(f, "there are no observations to fit"),
+            Self::LengthMismatch { n_xs, n_ys } => write!(
+                f,
+                "there are {} covariate vectors but {} responses",
+                n_xs, n_ys
+            ),
+            Self::DimensionMismatch { dims, expected } => write!(
+                f,
+                "covariates have {} dimensions, but the prior has {}",
+                dims, expected
+            ),
+            Self::CovariateNotFinite { ix } => {
+                write!(f, "the covariates of observation {} are not finite", ix)
+            }
+            Self::InvalidResponse { ix, y } => {
+                write!(f, "invalid response {} at observation {}", y, ix)
+            }
+            Self::NotConverged { n_iter } => write!(
+                f,
+                "Newton-Raphson did not converge in {} iterations",
+                n_iter
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Bernoulli, Poisson};
+    use crate::misc::adaptive_gauss_legendre_quadrature;
+    use rand::{Rng, SeedableRng};
+    use rand_xoshiro::Xoshiro256Plus;
+
+    fn wide_prior(dims: usize) -> MvGaussian {
+        MvGaussian::new(
+            DVector::zeros(dims),
+            DMatrix::from_diagonal_element(dims, dims, 100.0),
+        )
+        .unwrap()
+    }
+
+    fn covariates<R: Rng>(n: usize, rng: &mut R) -> Vec<DVector<f64>> {
+        (0..n)
+            .map(|_| {
+                DVector::from_vec(vec![
+                    1.0,
+                    rng.gen::<f64>().mul_add(2.0, -1.0),
+                ])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn logistic_recovers_coefficients() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x93);
+        let beta = DVector::from_vec(vec![-0.5, 2.0]);
+        let xs = covariates(5_000, &mut rng);
+        let ys: Vec<f64> = xs
+            .iter()
+            .map(|x| {
+                let p = GlmFamily::Logistic.mean(beta.dot(x));
+                let y: bool = Bernoulli::new(p).unwrap().draw(&mut rng);
+                if y {
+                    1.0
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        let glm =
+            Glm::fit(GlmFamily::Logistic, &wide_prior(2), &xs, &ys).unwrap();
+        let sd = glm.posterior().cov().diagonal().map(f64::sqrt);
+        for i in 0..2 {
+            assert!((glm.coefficients()[i] - beta[i]).abs() < 3.0 * sd[i]);
+        }
+        assert!(glm.n_iter() < 20);
+    }
+
+    #[test]
+    fn poisson_recovers_coefficients() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x94);
+        let beta = DVector::from_vec(vec![1.0, -0.7]);
+        let xs = covariates(2_000, &mut rng);
+        let ys: Vec<f64> = xs
+            .iter()
+            .map(|x| {
+                let count: u32 =
+                    Poisson::new(beta.dot(x).exp()).unwrap().draw(&mut rng);
+                f64::from(count)
+            })
+            .collect();
+
+        let glm =
+            Glm::fit(GlmFamily::Poisson, &wide_prior(2), &xs, &ys).unwrap();
+        let sd = glm.posterior().cov().diagonal().map(f64::sqrt);
+        for i in 0..2 {
+            assert!((glm.coefficients()[i] - beta[i]).abs() < 3.0 * sd[i]);
+        }
+
+        // The log-normal mean of the response
+        let x = DVector::from_vec(vec![1.0, 0.5]);
+        let eta = glm.linear_predictor(&x);
+        assert::close(
+            glm.predict_mean(&x),
+            (eta.mu() + eta.sigma().powi(2) / 2.0).exp(),
+            1E-12,
+        );
+        assert!(glm.predict_mean(&x) > glm.predict_map(&x));
+    }
+
+    #[test]
+    fn laplace_evidence_near_numeric_integral() {
+        // One coefficient, so the marginal likelihood is a 1D integral
+        let xs: Vec<DVector<f64>> = [0.3, 1.2, -0.5, 2.0, 0.8, -1.1, 1.5]
+            .iter()
+            .map(|&x| DVector::from_vec(vec![x]))
+            .collect();
+        let ys = [1.0, 3.0, 0.0, 6.0, 2.0, 1.0, 4.0];
+        let prior = MvGaussian::new(
+            DVector::from_vec(vec![0.0]),
+            DMatrix::from_element(1, 1, 1.0),
+        )
+        .unwrap();
+        let glm = Glm::fit(GlmFamily::Poisson, &prior, &xs, &ys).unwrap();
+
+        let integrand = |b: f64| {
+            let beta = DVector::from_vec(vec![b]);
+            let ln_lik: f64 = xs
+                .iter()
+                .zip(ys.iter())
+                .map(|(x, &y)| GlmFamily::Poisson.ln_f(y, x.dot(&beta)))
+                .sum();
+            (ln_lik + prior.ln_f(&beta)).exp()
+        };
+        let evidence = adaptive_gauss_legendre_quadrature(
+            integrand,
+            (-5.0, 5.0),
+            1E-14,
+            20,
+        );
+        assert::close(glm.ln_evidence(), evidence.ln(), 0.02);
+    }
+
+    #[test]
+    fn fit_validates_inputs() {
+        let prior = wide_prior(2);
+        let xs = vec![DVector::from_vec(vec![1.0, 0.5]); 2];
+        assert_eq!(
+            Glm::fit(GlmFamily::Logistic, &prior, &[], &[]),
+            Err(GlmError::NoData)
+        );
+        assert_eq!(
+            Glm::fit(GlmFamily::Logistic, &prior, &xs, &[1.0]),
+            Err(GlmError::LengthMismatch { n_xs: 2, n_ys: 1 })
+        );
+        assert_eq!(
+            Glm::fit(GlmFamily::Logistic, &prior, &xs, &[1.0, 0.5]),
+            Err(GlmError::InvalidResponse { ix: 1, y: 0.5 })
+        );
+        assert_eq!(
+            Glm::fit(GlmFamily::Poisson, &prior, &xs, &[1.0, -2.0]),
+            Err(GlmError::InvalidResponse { ix: 1, y: -2.0 })
+        );
+        assert_eq!(
+            Glm::fit(
+                GlmFamily::Poisson,
+                &prior,
+                &[DVector::from_vec(vec![1.0])],
+                &[1.0]
+            ),
+            Err(GlmError::DimensionMismatch {
+                dims: 1,
+                expected: 2
+            })
+        );
+    }
+}
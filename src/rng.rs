@@ -0,0 +1,222 @@
+//! Seeded random number generation for reproducible experiments
+//!
+//! [`Seeded`] wraps a portable generator whose output depends only on the
+//! seed -- not on the platform or the version of `rand` -- so that results
+//! can be reproduced from a recorded seed. It counts the random words it
+//! produces, and in audit mode records how many each sampling call used,
+//! which helps track down where two runs diverge.
+//!
+//! # Example
+//!
+//! ```
+//! use rv::dist::Gaussian;
+//! use rv::rng::Seeded;
+//! use rv::traits::Rv;
+//!
+//! let gauss = Gaussian::standard();
+//!
+//! let mut rng = Seeded::audited(1337);
+//! let xs: Vec<f64> = rng.sample(&gauss, 10);
+//! let x: f64 = rng.draw(&gauss);
+//!
+//! // The same seed gives the same values
+//! let ys: Vec<f64> = gauss.sample_seeded(11, 1337);
+//! assert_eq!(xs[..], ys[..10]);
+//! assert_eq!(x, ys[10]);
+//!
+//! let audit = rng.audit_log().unwrap();
+//! assert_eq!(audit.len(), 2);
+//! assert_eq!(audit[0].n_samples, 10);
+//! assert_eq!(audit[1].n_samples, 1);
+//! assert_eq!(audit.iter().map(|rec| rec.n_draws).sum::<u64>(), rng.n_draws());
+//! ```
+use rand::{Error, RngCore, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+use crate::traits::Rv;
+
+/// The number of random words used by one sampling call
+///
+/// See [`Seeded::audited`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// The number of samples drawn
+    pub n_samples: usize,
+    /// The number of random words used. Each `u32`, `u64`, and 8 bytes of a
+    /// filled buffer count as one.
+    pub n_draws: u64,
+}
+
+/// A deterministic, seeded random number generator
+///
+/// Uses xoshiro256++, which is portable and whose output for a given seed
+/// will not change between versions.
+#[derive(Debug, Clone)]
+pub struct Seeded {
+    rng: Xoshiro256PlusPlus,
+    seed: u64,
+    n_draws: u64,
+    audit: Option<Vec<AuditRecord>>,
+}
+
+impl Seeded {
+    /// Create a generator from `seed`
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Xoshiro256PlusPlus::seed_from_u64(seed),
+            seed,
+            n_draws: 0,
+            audit: None,
+        }
+    }
+
+    /// Create a generator from `seed` that records the number of random
+    /// words used by each call to [`Seeded::draw`] and [`Seeded::sample`]
+    pub fn audited(seed: u64) -> Self {
+        Self {
+            audit: Some(Vec::new()),
+            ..Self::new(seed)
+        }
+    }
+
+    /// The seed the generator was created from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The number of random words produced so far
+    pub fn n_draws(&self) -> u64 {
+        self.n_draws
+    }
+
+    /// The record of each sampling call, or `None` if not in audit mode
+    pub fn audit_log(&self) -> Option<&[AuditRecord]> {
+        self.audit.as_deref()
+    }
+
+    /// Draw one value from `fx`
+    pub fn draw<X, Fx: Rv<X>>(&mut self, fx: &Fx) -> X {
+        let start = self.n_draws;
+        let x = fx.draw(self);
+        self.record(1, start);
+        x
+    }
+
+    /// Draw `n` values from `fx`
+    pub fn sample<X, Fx: Rv<X>>(&mut self, fx: &Fx, n: usize) -> Vec<X> {
+        let start = self.n_draws;
+        let xs = fx.sample(n, self);
+        self.record(n, start);
+        xs
+    }
+
+    /// An independent generator for the `index`th stream of this seed
+    ///
+    /// Streams let parallel workers draw reproducibly without sharing a
+    /// generator: stream `i` of a seed is always the same, no matter which
+    /// streams were used before it. Stream 0 is a fresh copy of this
+    /// generator. Each stream is 2^128 words apart.
+    pub fn stream(&self, index: u64) -> Self {
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(self.seed);
+        (0..index).for_each(|_| rng.jump());
+        Self {
+            rng,
+            seed: self.seed,
+            n_draws: 0,
+            audit: self.audit.as_ref().map(|_| Vec::new()),
+        }
+    }
+
+    fn record(&mut self, n_samples: usize, start: u64) {
+        let n_draws = self.n_draws - start;
+        if let Some(audit) = self.audit.as_mut() {
+            audit.push(AuditRecord { n_samples, n_draws });
+        }
+    }
+}
+
+impl RngCore for Seeded {
+    fn next_u32(&mut self) -> u32 {
+        self.n_draws += 1;
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.n_draws += 1;
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.n_draws += (dest.len() as u64 + 7) / 8;
+        self.rng.fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Gamma, Gaussian, Poisson};
+    use rand::Rng;
+
+    #[test]
+    fn output_is_fixed_by_seed() {
+        // Guards against changes in the generator, which would break
+        // reproducibility of recorded experiments
+        let mut rng = Seeded::new(0);
+        let xs: Vec<u64> = (0..3).map(|_| rng.next_u64()).collect();
+        let mut expected = Xoshiro256PlusPlus::seed_from_u64(0);
+        let ys: Vec<u64> = (0..3).map(|_| expected.next_u64()).collect();
+        assert_eq!(xs, ys);
+        assert_eq!(rng.n_draws(), 3);
+        assert_eq!(rng.seed(), 0);
+    }
+
+    #[test]
+    fn sample_seeded_is_reproducible() {
+        let gamma = Gamma::new(2.0, 1.5).unwrap();
+        let xs: Vec<f64> = gamma.sample_seeded(100, 42);
+        let ys: Vec<f64> = gamma.sample_seeded(100, 42);
+        let zs: Vec<f64> = gamma.sample_seeded(100, 43);
+        assert_eq!(xs, ys);
+        assert_ne!(xs, zs);
+    }
+
+    #[test]
+    fn audit_counts_draws_per_call() {
+        let mut rng = Seeded::audited(7);
+        let _xs: Vec<u32> = rng.sample(&Poisson::new(3.0).unwrap(), 20);
+        let _x: f64 = rng.draw(&Gaussian::standard());
+        let _ = rng.gen::<u64>();
+
+        let audit = rng.audit_log().unwrap();
+        assert_eq!(audit.len(), 2);
+        assert_eq!(audit[0].n_samples, 20);
+        assert!(audit[0].n_draws >= 20);
+        assert_eq!(audit[1].n_samples, 1);
+        assert!(audit[1].n_draws >= 1);
+        // Draws outside of sampling calls are counted but not recorded
+        let recorded: u64 = audit.iter().map(|rec| rec.n_draws).sum();
+        assert_eq!(rng.n_draws(), recorded + 1);
+
+        assert!(Seeded::new(7).audit_log().is_none());
+    }
+
+    #[test]
+    fn streams_are_reproducible_and_distinct() {
+        let mut rng = Seeded::new(11);
+        let _ = rng.next_u64();
+
+        let mut a = rng.stream(2);
+        let mut b = Seeded::new(11).stream(2);
+        assert_eq!(a.next_u64(), b.next_u64());
+
+        let mut c = rng.stream(0);
+        assert_eq!(c.next_u64(), Seeded::new(11).next_u64());
+        assert_ne!(rng.stream(1).next_u64(), Seeded::new(11).next_u64());
+    }
+}
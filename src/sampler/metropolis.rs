@@ -0,0 +1,311 @@
+use crate::traits::Rv;
+use rand::Rng;
+use std::ops::{Add, Mul};
+
+/// How proposals are generated from a proposal distribution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetropolisProposal {
+    /// Propose `x + scale * δ`, where `δ` is drawn from the proposal, which
+    /// must be symmetric about zero
+    RandomWalk,
+    /// Propose a draw from the proposal, independent of the current state.
+    /// The Hastings correction is applied and the scale is not used.
+    Independent,
+}
+
+/// Adaptive scaling of random walk proposals
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Adaptation {
+    /// Acceptance rate to aim for
+    target_rate: f64,
+    /// Number of steps during which the scale is adapted
+    n_steps: usize,
+}
+
+/// Metropolis-Hastings sampler over any [`Rv`] proposal
+///
+/// # Example
+///
+/// Sample from an unnormalized Gaussian with standard deviation 2 using a
+/// random walk with standard normal increments.
+///
+/// ```
+/// use rv::dist::Gaussian;
+/// use rv::sampler::Metropolis;
+///
+/// let mut rng = rand::thread_rng();
+///
+/// let mut sampler = Metropolis::random_walk(
+///     |x: &f64| -x * x / 8.0,
+///     Gaussian::standard(),
+///     0.0,
+/// )
+/// .with_adaptation(0.44, 500);
+///
+/// let xs: Vec<f64> = sampler.iter(&mut rng).skip(500).take(5_000).collect();
+/// let mean = xs.iter().sum::<f64>() / 5_000.0;
+///
+/// assert!(mean.abs() < 0.5);
+/// assert!(sampler.acceptance_rate() > 0.2);
+/// assert!(sampler.scale() > 1.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Metropolis<X, F, Q> {
+    /// Unnormalized log density of the target
+    ln_f: F,
+    /// Proposal distribution
+    proposal: Q,
+    /// How the proposal is used
+    kind: MetropolisProposal,
+    /// Current state
+    x: X,
+    /// Log density of the current state
+    ln_fx: f64,
+    /// Log of the scale applied to random walk increments
+    ln_scale: f64,
+    /// Adaptive scaling, if any
+    adaptation: Option<Adaptation>,
+    /// Number of steps taken
+    n_steps: usize,
+    /// Number of proposals made since the counts were reset
+    n_proposed: usize,
+    /// Number of proposals accepted
+    n_accepted: usize,
+}
+
+impl<X, F, Q> Metropolis<X, F, Q>
+where
+    X: Clone + Add<Output = X> + Mul<f64, Output = X>,
+    F: Fn(&X) -> f64,
+    Q: Rv<X>,
+{
+    /// Create a sampler that proposes with `kind`, starting at `x0`
+    ///
+    /// # Panics
+    /// If `ln_f(x0)` is not finite
+    pub fn new(ln_f: F, proposal: Q, kind: MetropolisProposal, x0: X) -> Self {
+        let ln_fx = ln_f(&x0);
+        assert!(ln_fx.is_finite(), "ln_f(x0) must be finite");
+        Self {
+            ln_f,
+            proposal,
+            kind,
+            x: x0,
+            ln_fx,
+            ln_scale: 0.0,
+            adaptation: None,
+            n_steps: 0,
+            n_proposed: 0,
+            n_accepted: 0,
+        }
+    }
+
+    /// Create a random walk sampler. `proposal` is the distribution of the
+    /// increments and must be symmetric about zero.
+    pub fn random_walk(ln_f: F, proposal: Q, x0: X) -> Self {
+        Self::new(ln_f, proposal, MetropolisProposal::RandomWalk, x0)
+    }
+
+    /// Create an independence sampler
+    pub fn independent(ln_f: F, proposal: Q, x0: X) -> Self {
+        Self::new(ln_f, proposal, MetropolisProposal::Independent, x0)
+    }
+
+    /// Set the scale of the random walk increments
+    ///
+    /// # Panics
+    /// If `scale` is not positive and finite
+    #[must_use]
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        assert!(
+            scale > 0.0 && scale.is_finite(),
+            "scale must be positive and finite"
+        );
+        self.ln_scale = scale.ln();
+        self
+    }
+
+    /// Adapt the scale of the random walk increments toward `target_rate`
+    /// acceptance for the first `n_steps` steps
+    ///
+    /// Adaptation breaks detailed balance, so samples drawn while adapting
+    /// should be discarded. 0.44 is optimal in one dimension and 0.234 in
+    /// many dimensions.
+    ///
+    /// # Panics
+    /// If `target_rate` is not in (0, 1)
+    #[must_use]
+    pub fn with_adaptation(mut self, target_rate: f64, n_steps: usize) -> Self {
+        assert!(
+            0.0 < target_rate && target_rate < 1.0,
+            "target_rate must be in (0, 1)"
+        );
+        self.adaptation = Some(Adaptation {
+            target_rate,
+            n_steps,
+        });
+        self
+    }
+
+    /// The current state
+    pub fn current(&self) -> &X {
+        &self.x
+    }
+
+    /// The log density of the current state
+    pub fn ln_f_current(&self) -> f64 {
+        self.ln_fx
+    }
+
+    /// The current scale of the random walk increments
+    pub fn scale(&self) -> f64 {
+        self.ln_scale.exp()
+    }
+
+    /// The proposal distribution
+    pub fn proposal(&self) -> &Q {
+        &self.proposal
+    }
+
+    /// The number of proposals made
+    pub fn n_proposed(&self) -> usize {
+        self.n_proposed
+    }
+
+    /// The number of proposals accepted
+    pub fn n_accepted(&self) -> usize {
+        self.n_accepted
+    }
+
+    /// The fraction of proposals accepted. `NaN` before the first step.
+    pub fn acceptance_rate(&self) -> f64 {
+        self.n_accepted as f64 / self.n_proposed as f64
+    }
+
+    /// Reset the acceptance counts, e.g., after burn in. Does not restart
+    /// adaptation.
+    pub fn reset_counts(&mut self) {
+        self.n_proposed = 0;
+        self.n_accepted = 0;
+    }
+
+    /// Take one step and return the new state
+    pub fn step<R: Rng>(&mut self, rng: &mut R) -> &X {
+        let (y, ln_q_ratio) = match self.kind {
+            MetropolisProposal::RandomWalk => {
+                let delta: X = self.proposal.draw(rng);
+                (self.x.clone() + delta * self.ln_scale.exp(), 0.0)
+            }
+            MetropolisProposal::Independent => {
+                let y: X = self.proposal.draw(rng);
+                let ratio =
+                    self.proposal.ln_f(&self.x) - self.proposal.ln_f(&y);
+                (y, ratio)
+            }
+        };
+
+        let ln_fy = (self.ln_f)(&y);
+        let ln_alpha = (ln_fy - self.ln_fx + ln_q_ratio).min(0.0);
+        let accepted = rng.gen::<f64>().ln() < ln_alpha;
+        if accepted {
+            self.x = y;
+            self.ln_fx = ln_fy;
+            self.n_accepted += 1;
+        }
+        self.n_proposed += 1;
+        self.n_steps += 1;
+
+        if let Some(adaptation) = self.adaptation {
+            if self.n_steps <= adaptation.n_steps
+                && self.kind == MetropolisProposal::RandomWalk
+            {
+                // Robbins-Monro update of the log scale with a decaying step
+                let gain = (self.n_steps as f64).powf(-0.6);
+                let alpha = if ln_alpha.is_nan() {
+                    0.0
+                } else {
+                    ln_alpha.exp()
+                };
+                self.ln_scale += gain * (alpha - adaptation.target_rate);
+            }
+        }
+
+        &self.x
+    }
+
+    /// An endless iterator of samples
+    pub fn iter<'a, R: Rng>(
+        &'a mut self,
+        rng: &'a mut R,
+    ) -> impl Iterator<Item = X> + 'a {
+        std::iter::repeat_with(move || self.step(rng).clone())
+    }
+
+    /// Draw `n` samples
+    pub fn sample<R: Rng>(&mut self, n: usize, rng: &mut R) -> Vec<X> {
+        self.iter(rng).take(n).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::Gaussian;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    fn ln_target(x: &f64) -> f64 {
+        let g = Gaussian::new(1.0, 0.5).unwrap();
+        g.ln_f(x)
+    }
+
+    #[test]
+    fn random_walk_recovers_moments() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xBEEF);
+        let mut sampler =
+            Metropolis::random_walk(ln_target, Gaussian::standard(), 0.0)
+                .with_scale(0.5);
+        let xs = sampler.sample(10_000, &mut rng);
+
+        let mean = xs.iter().sum::<f64>() / 10_000.0;
+        let var =
+            xs.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / 10_000.0;
+        assert::close(mean, 1.0, 0.05);
+        assert::close(var, 0.25, 0.05);
+        assert_eq!(sampler.n_proposed(), 10_000);
+    }
+
+    #[test]
+    fn independent_applies_hastings_correction() {
+        // The proposal is wider than the target. Without the correction the
+        // samples would follow the proposal.
+        let mut rng = Xoshiro256Plus::seed_from_u64(0xFEED);
+        let mut sampler = Metropolis::independent(
+            ln_target,
+            Gaussian::new(1.0, 1.0).unwrap(),
+            1.0,
+        );
+        let xs = sampler.sample(10_000, &mut rng);
+        let mean = xs.iter().sum::<f64>() / 10_000.0;
+        let var =
+            xs.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / 10_000.0;
+        assert::close(mean, 1.0, 0.05);
+        assert::close(var, 0.25, 0.05);
+    }
+
+    #[test]
+    fn adaptation_moves_toward_target_rate() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x5EED);
+        // The initial scale is far too large
+        let mut sampler =
+            Metropolis::random_walk(ln_target, Gaussian::standard(), 1.0)
+                .with_scale(50.0)
+                .with_adaptation(0.44, 2_000);
+        let _ = sampler.sample(2_000, &mut rng);
+        assert!(sampler.scale() < 5.0);
+
+        sampler.reset_counts();
+        let _ = sampler.sample(4_000, &mut rng);
+        assert::close(sampler.acceptance_rate(), 0.44, 0.1);
+    }
+}
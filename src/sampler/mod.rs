@@ -0,0 +1,6 @@
+//! Markov chain and sequential Monte Carlo samplers
+mod metropolis;
+mod particle;
+
+pub use metropolis::{Metropolis, MetropolisProposal};
+pub use particle::{Conditional, ParticleFilter, ParticleFilterError};
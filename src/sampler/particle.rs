@@ -0,0 +1,421 @@
+#[cfg(feature = "serde1")]
+use serde::{Deserialize, Serialize};
+
+use crate::misc::logsumexp;
+use crate::traits::Rv;
+use rand::Rng;
+use std::fmt;
+
+/// A distribution that depends on the state, such as the transition to the
+/// next state or the likelihood of an observation.
+///
+/// Implemented for closures from the state to a distribution, e.g.
+/// `|x: &f64| Gaussian::new_unchecked(*x, 1.0)`.
+pub trait Conditional<X> {
+    /// The distribution given a state
+    type Dist;
+
+    /// The distribution given the state `x`
+    fn given(&self, x: &X) -> Self::Dist;
+}
+
+impl<X, D, F> Conditional<X> for F
+where
+    F: Fn(&X) -> D,
+{
+    type Dist = D;
+
+    fn given(&self, x: &X) -> D {
+        self(x)
+    }
+}
+
+/// Bootstrap particle filter for state space models with any [`Rv`]
+/// transition and observation distributions
+///
+/// Each step moves every particle by a draw from `transition` given its
+/// state and weights it by the likelihood of the observation under
+/// `observation` given its new state. Particles are resampled systematically
+/// when the effective sample size (ESS) falls below a fraction of the number
+/// of particles. The average likelihood of each observation estimates its
+/// predictive density, which accumulates into an unbiased estimate of the
+/// marginal likelihood of the observations.
+///
+/// # Example
+///
+/// Track a random walk through noisy measurements
+///
+/// ```
+/// use rv::dist::Gaussian;
+/// use rv::sampler::ParticleFilter;
+/// use rv::traits::*;
+///
+/// let mut rng = rand::thread_rng();
+///
+/// let mut pf = ParticleFilter::from_prior(
+///     |x: &f64| Gaussian::new_unchecked(*x, 0.5),
+///     |x: &f64| Gaussian::new_unchecked(*x, 1.0),
+///     &Gaussian::new(0.0, 10.0).unwrap(),
+///     2_000,
+///     &mut rng,
+/// );
+///
+/// for y in [1.2, 1.9, 2.4, 3.1, 3.3] {
+///     pf.step(&y, &mut rng).unwrap();
+/// }
+///
+/// let mean = pf.estimate(|x| *x);
+/// assert!((mean - 3.0).abs() < 1.0);
+/// assert!(pf.ln_marginal().is_finite());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParticleFilter<X, T, O> {
+    /// Distribution of the next state given the current one
+    transition: T,
+    /// Distribution of an observation given the state
+    observation: O,
+    /// Current states
+    particles: Vec<X>,
+    /// Normalized log weights of the particles
+    ln_weights: Vec<f64>,
+    /// Resample when the ESS falls below this fraction of the particles
+    resample_threshold: f64,
+    /// Estimate of the log marginal likelihood of the observations so far
+    ln_marginal: f64,
+    /// ESS after each step, before resampling
+    ess_history: Vec<f64>,
+    /// Number of steps after which the particles were resampled
+    n_resampled: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde1", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "snake_case"))]
+pub enum ParticleFilterError {
+    /// The observation has zero likelihood under every particle. The
+    /// particles are moved but keep their previous weights.
+    ImpossibleObservation,
+}
+
+impl<X, T, O> ParticleFilter<X, T, O>
+where
+    X: Clone,
+    T: Conditional<X>,
+    T::Dist: Rv<X>,
+    O: Conditional<X>,
+{
+    /// Create a filter with equally weighted starting `particles`
+    ///
+    /// # Panics
+    /// If there are no particles
+    pub fn new(transition: T, observation: O, particles: Vec<X>) -> Self {
+        assert!(!particles.is_empty(), "there must be at least one particle");
+        let ln_w = -(particles.len() as f64).ln();
+        Self {
+            transition,
+            observation,
+            ln_weights: vec![ln_w; particles.len()],
+            particles,
+            resample_threshold: 0.5,
+            ln_marginal: 0.0,
+            ess_history: Vec::new(),
+            n_resampled: 0,
+        }
+    }
+
+    /// Create a filter with `n` particles drawn from `prior`
+    ///
+    /// # Panics
+    /// If `n` is zero
+    pub fn from_prior<P, R>(
+        transition: T,
+        observation: O,
+        prior: &P,
+        n: usize,
+        rng: &mut R,
+    ) -> Self
+    where
+        P: Rv<X>,
+        R: Rng,
+    {
+        Self::new(transition, observation, prior.sample(n, rng))
+    }
+
+    /// Resample when the ESS falls below `threshold` times the number of
+    /// particles. 0 never resamples and 1 resamples after every step. The
+    /// default is 0.5.
+    ///
+    /// # Panics
+    /// If `threshold` is not in [0, 1]
+    #[must_use]
+    pub fn with_resample_threshold(mut self, threshold: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&threshold),
+            "threshold must be in [0, 1]"
+        );
+        self.resample_threshold = threshold;
+        self
+    }
+
+    /// The current states of the particles
+    pub fn particles(&self) -> &[X] {
+        &self.particles
+    }
+
+    /// The normalized log weights of the particles
+    pub fn ln_weights(&self) -> &[f64] {
+        &self.ln_weights
+    }
+
+    /// The normalized weights of the particles
+    pub fn weights(&self) -> Vec<f64> {
+        self.ln_weights.iter().map(|w| w.exp()).collect()
+    }
+
+    /// The number of particles
+    pub fn n_particles(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// The effective sample size of the current weights, 1 / Σ w²
+    pub fn ess(&self) -> f64 {
+        ess(&self.ln_weights)
+    }
+
+    /// The ESS after each step, before any resampling
+    pub fn ess_history(&self) -> &[f64] {
+        &self.ess_history
+    }
+
+    /// The number of steps after which the particles were resampled
+    pub fn n_resampled(&self) -> usize {
+        self.n_resampled
+    }
+
+    /// The estimated log marginal likelihood of the observations so far
+    pub fn ln_marginal(&self) -> f64 {
+        self.ln_marginal
+    }
+
+    /// The weighted average of `f` over the particles, the filtering
+    /// estimate of the expectation of `f` of the state
+    pub fn estimate<F>(&self, f: F) -> f64
+    where
+        F: Fn(&X) -> f64,
+    {
+        self.particles
+            .iter()
+            .zip(self.ln_weights.iter())
+            .fold(0.0, |acc, (x, w)| w.exp().mul_add(f(x), acc))
+    }
+
+    /// Move the particles forward one step without an observation
+    pub fn predict<R: Rng>(&mut self, rng: &mut R) {
+        let transition = &self.transition;
+        self.particles
+            .iter_mut()
+            .for_each(|x| *x = transition.given(x).draw(rng));
+    }
+
+    /// Move the particles forward one step and weight them by the
+    /// likelihood of the observation `y`
+    ///
+    /// Returns the estimated log predictive density of `y`, which is added
+    /// to [`ln_marginal`](ParticleFilter::ln_marginal).
+    pub fn step<Y, R>(
+        &mut self,
+        y: &Y,
+        rng: &mut R,
+    ) -> Result<f64, ParticleFilterError>
+    where
+        O::Dist: Rv<Y>,
+        R: Rng,
+    {
+        self.predict(rng);
+
+        let ln_joint: Vec<f64> = self
+            .particles
+            .iter()
+            .zip(self.ln_weights.iter())
+            .map(|(x, w)| w + self.observation.given(x).ln_f(y))
+            .collect();
+        let ln_pred = logsumexp(&ln_joint);
+        if !ln_pred.is_finite() {
+            return Err(ParticleFilterError::ImpossibleObservation);
+        }
+
+        self.ln_weights = ln_joint.iter().map(|w| w - ln_pred).collect();
+        self.ln_marginal += ln_pred;
+
+        let ess = self.ess();
+        self.ess_history.push(ess);
+        if ess < self.resample_threshold * self.n_particles() as f64 {
+            self.resample(rng);
+        }
+
+        Ok(ln_pred)
+    }
+
+    /// Filter a sequence of observations, returning the log predictive
+    /// density of each
+    pub fn filter<Y, R>(
+        &mut self,
+        ys: &[Y],
+        rng: &mut R,
+    ) -> Result<Vec<f64>, ParticleFilterError>
+    where
+        O::Dist: Rv<Y>,
+        R: Rng,
+    {
+        ys.iter().map(|y| self.step(y, rng)).collect()
+    }
+
+    /// Systematically resample the particles and reset the weights
+    pub fn resample<R: Rng>(&mut self, rng: &mut R) {
+        let ixs = systematic_resample(&self.ln_weights, rng);
+        self.particles =
+            ixs.iter().map(|&ix| self.particles[ix].clone()).collect();
+        let ln_w = -(self.n_particles() as f64).ln();
+        self.ln_weights.iter_mut().for_each(|w| *w = ln_w);
+        self.n_resampled += 1;
+    }
+}
+
+/// The effective sample size of normalized log weights
+fn ess(ln_weights: &[f64]) -> f64 {
+    1.0 / ln_weights.iter().map(|w| (2.0 * w).exp()).sum::<f64>()
+}
+
+/// Indices of the particles kept by systematic resampling: one uniform
+/// offset and evenly spaced points through the cumulative weights
+fn systematic_resample<R: Rng>(ln_weights: &[f64], rng: &mut R) -> Vec<usize> {
+    let n = ln_weights.len();
+    let step = 1.0 / n as f64;
+    let mut u = rng.gen::<f64>() * step;
+    let mut cum = 0.0;
+    let mut ixs = Vec::with_capacity(n);
+    for (ix, w) in ln_weights.iter().enumerate() {
+        cum += w.exp();
+        while u < cum && ixs.len() < n {
+            ixs.push(ix);
+            u += step;
+        }
+    }
+    // Rounding can leave the cumulative weight just short of one
+    ixs.resize(n, n - 1);
+    ixs
+}
+
+impl std::error::Error for ParticleFilterError {}
+
+impl fmt::Display for ParticleFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ImpossibleObservation => write!(
+                f,
+                "the observation has zero likelihood under every particle"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Gaussian, Poisson, Uniform};
+    use crate::model::ScalarKalman;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    #[test]
+    fn matches_kalman_filter_on_linear_gaussian_model() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x90);
+        let prior = Gaussian::new(0.0, 2.0).unwrap();
+        let (q, r) = (0.5, 1.0);
+        let ys = [0.8, 1.5, 1.1, 2.6, 3.0, 2.2, 3.9, 4.4];
+
+        // The prior is on the state before the first observation
+        let mut kf = ScalarKalman::new(prior.clone(), r, q).unwrap();
+        let ln_marginal: f64 = ys
+            .iter()
+            .map(|y| {
+                kf.predict(1.0);
+                kf.observe(*y)
+            })
+            .sum();
+
+        let mut pf = ParticleFilter::from_prior(
+            |x: &f64| Gaussian::new_unchecked(*x, q),
+            |x: &f64| Gaussian::new_unchecked(*x, r),
+            &prior,
+            20_000,
+            &mut rng,
+        );
+        let ln_ps = pf.filter(&ys, &mut rng).unwrap();
+
+        assert::close(pf.estimate(|x| *x), kf.state().mu(), 0.05);
+        let mean = pf.estimate(|x| *x);
+        let var = pf.estimate(|x| (x - mean).powi(2));
+        assert::close(var, kf.state().sigma().powi(2), 0.05);
+        assert::close(ln_ps.iter().sum::<f64>(), ln_marginal, 0.05);
+    }
+
+    #[test]
+    fn tracks_poisson_counts_with_log_intensity_state() {
+        // A nonlinear, non-Gaussian model out of the Kalman filter's reach
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x91);
+        let mut pf = ParticleFilter::from_prior(
+            |x: &f64| Gaussian::new_unchecked(*x, 0.1),
+            |x: &f64| Poisson::new_unchecked(x.exp()),
+            &Gaussian::new(0.0, 1.0).unwrap(),
+            5_000,
+            &mut rng,
+        );
+        let counts: Vec<u32> = vec![18, 22, 19, 25, 21, 20, 23, 17, 24, 20];
+        let ln_ps = pf.filter(&counts, &mut rng).unwrap();
+
+        assert_eq!(ln_ps.len(), counts.len());
+        assert::close(pf.ln_marginal(), ln_ps.iter().sum::<f64>(), 1E-12);
+        assert::close(pf.estimate(|x| x.exp()), 21.0, 2.0);
+        assert_eq!(pf.ess_history().len(), counts.len());
+        assert!(pf.n_resampled() > 0);
+    }
+
+    #[test]
+    fn systematic_resampling_keeps_expected_counts() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x92);
+        let weights = [0.5_f64, 0.25, 0.125, 0.125];
+        let ln_weights: Vec<f64> = weights.iter().map(|w| w.ln()).collect();
+        for _ in 0..100 {
+            let ixs = systematic_resample(&ln_weights, &mut rng);
+            assert_eq!(ixs.len(), 4);
+            let counts: Vec<usize> = (0..4)
+                .map(|i| ixs.iter().filter(|&&j| j == i).count())
+                .collect();
+            assert_eq!(counts[0], 2);
+            assert_eq!(counts[1], 1);
+            assert_eq!(counts[2] + counts[3], 1);
+        }
+        assert::close(ess(&ln_weights), 1.0 / 0.34375, 1E-12);
+    }
+
+    #[test]
+    fn impossible_observation_is_an_error() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x93);
+        let mut pf = ParticleFilter::from_prior(
+            |x: &f64| Gaussian::new_unchecked(*x, 0.1),
+            |x: &f64| Uniform::new_unchecked(*x - 0.5, *x + 0.5),
+            &Gaussian::standard(),
+            100,
+            &mut rng,
+        )
+        .with_resample_threshold(1.0);
+        assert_eq!(
+            pf.step(&100.0, &mut rng),
+            Err(ParticleFilterError::ImpossibleObservation)
+        );
+        assert!(pf.step(&0.0, &mut rng).is_ok());
+        assert_eq!(pf.n_resampled(), 1);
+        assert::close(pf.ess(), 100.0, 1E-9);
+    }
+}
@@ -1,6 +1,15 @@
 #[cfg(test)]
 use approx::RelativeEq;
 
+mod properties;
+mod sbc;
+
+pub use properties::{
+    check_cdf_invcdf, check_conjugate_posterior, check_observe_forget,
+    check_sample_moments,
+};
+pub use sbc::{sbc, Sbc};
+
 use crate::traits::Rv;
 use std::collections::BTreeMap;
 
@@ -50,6 +59,21 @@ macro_rules! test_basic_impls {
                 // Make sure Debug is implemented for fx
                 let _s1 = format!("{:?}", fx);
             }
+
+            #[test]
+            fn ln_f_many_agrees_with_ln_f() {
+                let fx = $fx;
+                let xs = vec![$x, $x, $x];
+                let mut out = vec![0.0; xs.len()];
+                fx.ln_f_many(&xs, &mut out);
+
+                let y = fx.ln_f(&xs[0]);
+                for z in out {
+                    assert!(
+                        z == y || (z - y).abs() <= 1E-12 * y.abs().max(1.0)
+                    );
+                }
+            }
         }
     };
 }
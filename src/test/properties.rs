@@ -0,0 +1,292 @@
+//! Checks of the properties every implementation of the rv traits should
+//! have, for testing new distributions
+use crate::data::DataOrSuffStat;
+use crate::traits::*;
+use rand::Rng;
+
+/// Whether `a` and `b` agree within `tol`, relative to their magnitude if
+/// it is above one
+fn close(a: f64, b: f64, tol: f64) -> bool {
+    a == b || (a - b).abs() <= tol * a.abs().max(b.abs()).max(1.0)
+}
+
+/// Check that `cdf(invcdf(p)) = p` for each of `ps` within `tol`
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::Gamma;
+/// use rv::test::check_cdf_invcdf;
+///
+/// let gamma = Gamma::new(2.0, 3.0).unwrap();
+/// assert!(check_cdf_invcdf(&gamma, &[0.01, 0.5, 0.99], 1E-10).is_ok());
+/// ```
+pub fn check_cdf_invcdf<Fx>(fx: &Fx, ps: &[f64], tol: f64) -> Result<(), String>
+where
+    Fx: Cdf<f64> + InverseCdf<f64>,
+{
+    ps.iter().try_for_each(|&p| {
+        let x = fx.invcdf(p);
+        let q = fx.cdf(&x);
+        if (q - p).abs() <= tol {
+            Ok(())
+        } else {
+            Err(format!("cdf(invcdf({})) = cdf({}) = {}", p, x, q))
+        }
+    })
+}
+
+/// Check that the mean and variance of `n` samples agree with the analytic
+/// mean and variance to within `z_max` standard errors.
+///
+/// The standard error of the sample variance is estimated from the sample
+/// fourth central moment, so this needs a finite fourth moment to be
+/// reliable. Moments that are `None` are not checked. With `z_max = 4`, a
+/// correct distribution fails about once in 8,000 checks.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::Laplace;
+/// use rv::test::check_sample_moments;
+///
+/// let mut rng = rand::thread_rng();
+/// let laplace = Laplace::new(1.0, 2.0).unwrap();
+/// assert!(check_sample_moments(&laplace, 10_000, 5.0, &mut rng).is_ok());
+/// ```
+///
+/// # Panics
+/// If `n` is less than two
+pub fn check_sample_moments<Fx, R>(
+    fx: &Fx,
+    n: usize,
+    z_max: f64,
+    rng: &mut R,
+) -> Result<(), String>
+where
+    Fx: Rv<f64> + Mean<f64> + Variance<f64>,
+    R: Rng,
+{
+    assert!(n > 1, "n must be at least two");
+    let xs: Vec<f64> = fx.sample(n, rng);
+    let nf = n as f64;
+    let mean = xs.iter().sum::<f64>() / nf;
+    let central = |k: i32| xs.iter().map(|x| (x - mean).powi(k)).sum::<f64>();
+    let var = central(2) / (nf - 1.0);
+
+    if let Some(mu) = fx.mean() {
+        let z = (mean - mu) / (var / nf).sqrt();
+        if z.abs() > z_max {
+            return Err(format!(
+                "sample mean {} is {} standard errors from the mean {}",
+                mean, z, mu
+            ));
+        }
+    }
+
+    if let Some(sigma2) = fx.variance() {
+        let m4 = central(4) / nf;
+        let z = (var - sigma2) / ((m4 - var * var) / nf).sqrt();
+        if z.abs() > z_max {
+            return Err(format!(
+                "sample variance {} is {} standard errors from the variance \
+                 {}",
+                var, z, sigma2
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check that forgetting data undoes observing it.
+///
+/// Observes every datum in `xs`, forgets the second half, and then the
+/// first half, comparing `fx.ln_f_stat` and `n` against sufficient statistics
+/// that only observed the remaining data, to within `tol`.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::Gaussian;
+/// use rv::test::check_observe_forget;
+///
+/// let gauss = Gaussian::new(1.0, 2.0).unwrap();
+/// let xs = [0.5, -1.2, 3.3, 0.1, 2.2];
+/// assert!(check_observe_forget(&gauss, &xs, 1E-10).is_ok());
+/// ```
+pub fn check_observe_forget<X, Fx>(
+    fx: &Fx,
+    xs: &[X],
+    tol: f64,
+) -> Result<(), String>
+where
+    Fx: HasSuffStat<X>,
+{
+    let compare = |stat: &Fx::Stat, n_kept: usize, when: &str| {
+        let mut expected = fx.empty_suffstat();
+        expected.observe_many(&xs[..n_kept]);
+        let (ln_f, ln_f_expected) =
+            (fx.ln_f_stat(stat), fx.ln_f_stat(&expected));
+        if stat.n() != n_kept {
+            Err(format!("n is {} {}, not {}", stat.n(), when, n_kept))
+        } else if !close(ln_f, ln_f_expected, tol) {
+            Err(format!(
+                "ln_f_stat is {} {}, not {}",
+                ln_f, when, ln_f_expected
+            ))
+        } else {
+            Ok(())
+        }
+    };
+
+    let mut stat = fx.empty_suffstat();
+    stat.observe_many(xs);
+    compare(&stat, xs.len(), "after observing every datum")?;
+
+    let half = xs.len() / 2;
+    xs[half..].iter().for_each(|x| stat.forget(x));
+    compare(&stat, half, "after forgetting the second half")?;
+
+    xs[..half].iter().for_each(|x| stat.forget(x));
+    compare(&stat, 0, "after forgetting every datum")
+}
+
+/// Check that a conjugate posterior is proportional to the prior times the
+/// likelihood, with the marginal likelihood as the constant.
+///
+/// At each of the parameters in `grid`, checks that
+/// `ln π(θ|xs) = ln π(θ) + Σ ln f(x|θ) - ln m(xs)` to within `tol`, which
+/// checks `posterior` and `ln_m` against `ln_f` of the prior and likelihood.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{Beta, Bernoulli};
+/// use rv::test::check_conjugate_posterior;
+///
+/// let prior = Beta::new(2.0, 3.0).unwrap();
+/// let grid: Vec<Bernoulli> = [0.1, 0.4, 0.8]
+///     .iter()
+///     .map(|&p| Bernoulli::new(p).unwrap())
+///     .collect();
+/// let flips = [true, false, true, true];
+/// assert!(check_conjugate_posterior(&prior, &flips, &grid, 1E-10).is_ok());
+/// ```
+pub fn check_conjugate_posterior<X, Fx, Pr>(
+    prior: &Pr,
+    xs: &[X],
+    grid: &[Fx],
+    tol: f64,
+) -> Result<(), String>
+where
+    Fx: Rv<X> + HasSuffStat<X>,
+    Pr: ConjugatePrior<X, Fx>,
+{
+    let data = DataOrSuffStat::Data(xs);
+    let posterior = prior.posterior(&data);
+    let ln_m = prior.ln_m(&data);
+    grid.iter().enumerate().try_for_each(|(ix, fx)| {
+        let ln_post = posterior.ln_f(fx);
+        let ln_lik: f64 = xs.iter().map(|x| fx.ln_f(x)).sum();
+        let expected = prior.ln_f(fx) + ln_lik - ln_m;
+        if close(ln_post, expected, tol) {
+            Ok(())
+        } else {
+            Err(format!(
+                "the log posterior at grid point {} is {}, but the log prior \
+                 plus the log likelihood minus ln_m is {}",
+                ix, ln_post, expected
+            ))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dist::{Gamma, Gaussian, NormalGamma, Poisson};
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    #[test]
+    fn correct_implementations_pass() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x99);
+        let gamma = Gamma::new(3.0, 0.5).unwrap();
+        assert!(check_cdf_invcdf(&gamma, &[0.001, 0.3, 0.9], 1E-10).is_ok());
+        assert!(check_sample_moments(&gamma, 10_000, 4.0, &mut rng).is_ok());
+
+        let counts: Vec<u32> = vec![3, 0, 7, 2, 2, 5];
+        let poisson = Poisson::new(2.0).unwrap();
+        assert!(check_observe_forget(&poisson, &counts, 1E-10).is_ok());
+        let grid: Vec<Poisson> = [0.5, 2.0, 6.0]
+            .iter()
+            .map(|&rate| Poisson::new(rate).unwrap())
+            .collect();
+        assert!(check_conjugate_posterior(&gamma, &counts, &grid, 1E-9).is_ok());
+
+        let ng = NormalGamma::new(0.5, 2.0, 3.0, 1.5).unwrap();
+        let xs = [0.3, -0.4, 1.9, 0.7];
+        let grid: Vec<Gaussian> = [(0.0, 1.0), (1.0, 0.5), (-0.5, 2.0)]
+            .iter()
+            .map(|&(mu, sigma)| Gaussian::new(mu, sigma).unwrap())
+            .collect();
+        assert!(check_conjugate_posterior(&ng, &xs, &grid, 1E-9).is_ok());
+    }
+
+    /// Samples and densities of one distribution with the moments and
+    /// quantiles of another
+    struct Mislabeled(Gamma, Gamma);
+
+    impl Rv<f64> for Mislabeled {
+        fn ln_f(&self, x: &f64) -> f64 {
+            self.0.ln_f(x)
+        }
+
+        fn draw<R: Rng>(&self, rng: &mut R) -> f64 {
+            self.0.draw(rng)
+        }
+    }
+
+    impl Support<f64> for Mislabeled {
+        fn supports(&self, x: &f64) -> bool {
+            self.0.supports(x)
+        }
+    }
+
+    impl Cdf<f64> for Mislabeled {
+        fn cdf(&self, x: &f64) -> f64 {
+            self.0.cdf(x)
+        }
+    }
+
+    impl InverseCdf<f64> for Mislabeled {
+        fn invcdf(&self, p: f64) -> f64 {
+            self.1.invcdf(p)
+        }
+    }
+
+    impl Mean<f64> for Mislabeled {
+        fn mean(&self) -> Option<f64> {
+            self.1.mean()
+        }
+    }
+
+    impl Variance<f64> for Mislabeled {
+        fn variance(&self) -> Option<f64> {
+            self.1.variance()
+        }
+    }
+
+    #[test]
+    fn wrong_implementations_fail() {
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x9a);
+        let mislabeled = Mislabeled(
+            Gamma::new(3.0, 0.5).unwrap(),
+            Gamma::new(3.3, 0.5).unwrap(),
+        );
+        assert!(check_cdf_invcdf(&mislabeled, &[0.5], 1E-6).is_err());
+        assert!(
+            check_sample_moments(&mislabeled, 10_000, 4.0, &mut rng).is_err()
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Simulation-based calibration of posterior inference
+use crate::misc::x2_test_weighted;
+use crate::traits::Rv;
+use rand::Rng;
+
+/// The results of simulation-based calibration
+///
+/// See [`sbc`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sbc {
+    /// The number of posterior draws per simulation, so each rank is in
+    /// `0..=n_draws`
+    pub n_draws: usize,
+    /// For each statistic, the rank of its prior value among its posterior
+    /// values in each simulation
+    pub ranks: Vec<Vec<usize>>,
+    /// For each statistic, the number of ranks in each bin
+    pub histograms: Vec<Vec<u32>>,
+    /// For each statistic, the Χ<sup>2</sup> statistic of its histogram
+    /// against the uniform
+    pub x2_stats: Vec<f64>,
+    /// For each statistic, the p-value of the Χ<sup>2</sup> test
+    pub p_values: Vec<f64>,
+}
+
+impl Sbc {
+    /// The smallest p-value over the statistics. Compare it against a level
+    /// divided by the number of statistics to account for testing each.
+    pub fn min_p_value(&self) -> f64 {
+        self.p_values.iter().copied().fold(1.0, f64::min)
+    }
+}
+
+/// Simulation-based calibration (SBC) of a posterior inference procedure
+///
+/// Each simulation draws parameters from the prior, simulates data from
+/// them, and draws from the posterior given the data. If the posterior draws
+/// are correct, the rank of each prior statistic among the posterior ones is
+/// uniform on `0..=n_draws` (Talts et al., 2018). Skewed, peaked, or U-shaped
+/// rank histograms show biased, overdispersed, or overconfident inference.
+/// Ties, as in discrete parameters, are broken at random.
+///
+/// # Arguments
+/// - prior: The prior on the parameters
+/// - simulate: Simulates a data set given the parameters
+/// - posterior: Draws from the posterior given a data set. Must return the
+///   same, nonzero number of draws each time. Draws from an MCMC sampler
+///   should be thinned to be close to independent.
+/// - stats: The scalar statistics of the parameters to rank, e.g., each
+///   parameter
+/// - n_sims: The number of simulations
+/// - n_bins: The number of bins of the rank histograms
+/// - rng: The random number generator
+///
+/// # Example
+///
+/// Calibrate a conjugate Beta-Bernoulli posterior
+///
+/// ```
+/// use rv::data::DataOrSuffStat;
+/// use rv::dist::{Bernoulli, Beta};
+/// use rv::test::sbc;
+/// use rv::traits::*;
+///
+/// let mut rng = rand::thread_rng();
+/// let prior = Beta::new(2.0, 3.0).unwrap();
+///
+/// let result = sbc(
+///     &prior,
+///     |fx: &Bernoulli, rng| fx.sample(20, rng),
+///     |xs: &[bool], rng| {
+///         let post = prior.posterior(&DataOrSuffStat::<bool, Bernoulli>::from(xs));
+///         post.sample(19, rng)
+///     },
+///     |fx: &Bernoulli| vec![fx.p()],
+///     500,
+///     10,
+///     &mut rng,
+/// );
+///
+/// assert_eq!(result.histograms[0].iter().sum::<u32>(), 500);
+/// assert!(result.min_p_value() > 1E-4);
+/// ```
+///
+/// # Panics
+/// If `n_sims` or `n_bins` is zero, if `posterior` returns no draws or a
+/// different number of draws than before, if `n_bins` exceeds the number of
+/// possible ranks, or if `stats` returns a different number of statistics
+/// than before
+pub fn sbc<Pr, Fx, X, S, P, T, R>(
+    prior: &Pr,
+    simulate: S,
+    posterior: P,
+    stats: T,
+    n_sims: usize,
+    n_bins: usize,
+    rng: &mut R,
+) -> Sbc
+where
+    Pr: Rv<Fx>,
+    S: Fn(&Fx, &mut R) -> Vec<X>,
+    P: Fn(&[X], &mut R) -> Vec<Fx>,
+    T: Fn(&Fx) -> Vec<f64>,
+    R: Rng,
+{
+    assert!(n_sims > 0, "n_sims must be greater than zero");
+    assert!(n_bins > 0, "n_bins must be greater than zero");
+
+    let mut n_draws: Option<usize> = None;
+    let mut ranks: Vec<Vec<usize>> = Vec::new();
+    for _ in 0..n_sims {
+        let fx: Fx = prior.draw(rng);
+        let xs = simulate(&fx, rng);
+        let draws = posterior(&xs, rng);
+        assert!(!draws.is_empty(), "posterior must return draws");
+        assert_eq!(
+            *n_draws.get_or_insert(draws.len()),
+            draws.len(),
+            "posterior must return the same number of draws each time"
+        );
+
+        let truth = stats(&fx);
+        if ranks.is_empty() {
+            ranks = vec![Vec::with_capacity(n_sims); truth.len()];
+        }
+        assert_eq!(
+            truth.len(),
+            ranks.len(),
+            "stats must return the same number of statistics each time"
+        );
+
+        let draw_stats: Vec<Vec<f64>> = draws.iter().map(&stats).collect();
+        truth.iter().enumerate().for_each(|(k, &t)| {
+            let (n_less, n_equal) =
+                draw_stats.iter().fold((0, 0), |(less, equal), s| {
+                    if s[k] < t {
+                        (less + 1, equal)
+                    } else if s[k] == t {
+                        (less, equal + 1)
+                    } else {
+                        (less, equal)
+                    }
+                });
+            ranks[k].push(n_less + rng.gen_range(0..=n_equal));
+        });
+    }
+
+    let n_draws = n_draws.expect("there was at least one simulation");
+    let n_ranks = n_draws + 1;
+    assert!(
+        n_bins <= n_ranks,
+        "n_bins must not exceed the number of possible ranks"
+    );
+
+    // Bins may hold different numbers of ranks if n_bins does not divide
+    // n_ranks, so each has its own expected proportion
+    let bin = |rank: usize| rank * n_bins / n_ranks;
+    let ps: Vec<f64> = (0..n_ranks).fold(vec![0.0; n_bins], |mut ps, r| {
+        ps[bin(r)] += 1.0 / n_ranks as f64;
+        ps
+    });
+
+    let histograms: Vec<Vec<u32>> = ranks
+        .iter()
+        .map(|stat_ranks| {
+            stat_ranks.iter().fold(vec![0_u32; n_bins], |mut hist, &r| {
+                hist[bin(r)] += 1;
+                hist
+            })
+        })
+        .collect();
+
+    let (x2_stats, p_values) = histograms
+        .iter()
+        .map(|hist| {
+            let f_obs: Vec<f64> = hist.iter().map(|&c| f64::from(c)).collect();
+            x2_test_weighted(&f_obs, &ps)
+        })
+        .unzip();
+
+    Sbc {
+        n_draws,
+        ranks,
+        histograms,
+        x2_stats,
+        p_values,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DataOrSuffStat;
+    use crate::dist::{Gamma, Gaussian, Poisson};
+    use crate::traits::ConjugatePrior;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256Plus;
+
+    fn gamma_poisson_sbc(data_weight: usize, seed: u64) -> Sbc {
+        let mut rng = Xoshiro256Plus::seed_from_u64(seed);
+        let prior = Gamma::new(3.0, 1.0).unwrap();
+        sbc(
+            &prior,
+            |fx: &Poisson, rng| fx.sample(10, rng),
+            |xs: &[u32], rng| {
+                // Counting each datum `data_weight` times
+                let xs: Vec<u32> = xs
+                    .iter()
+                    .flat_map(|&x| std::iter::repeat(x).take(data_weight))
+                    .collect();
+                let stat = DataOrSuffStat::<u32, Poisson>::from(&xs);
+                prior.posterior(&stat).sample(49, rng)
+            },
+            |fx: &Poisson| vec![fx.rate()],
+            1_000,
+            10,
+            &mut rng,
+        )
+    }
+
+    #[test]
+    fn calibrated_posterior_has_uniform_ranks() {
+        let result = gamma_poisson_sbc(1, 0x98);
+        assert_eq!(result.n_draws, 49);
+        assert_eq!(result.ranks[0].len(), 1_000);
+        assert!(result.ranks[0].iter().all(|&r| r <= 49));
+        assert!(result.min_p_value() > 1E-3);
+    }
+
+    #[test]
+    fn overconfident_posterior_has_u_shaped_ranks() {
+        let result = gamma_poisson_sbc(4, 0x99);
+        assert!(result.min_p_value() < 1E-6);
+        let hist = &result.histograms[0];
+        assert!(hist[0] > hist[5] && hist[9] > hist[5]);
+    }
+
+    #[test]
+    fn uneven_bins_and_ties() {
+        // With no data, the prior is the posterior. Ranking a constant
+        // statistic exercises the tie breaking.
+        let mut rng = Xoshiro256Plus::seed_from_u64(0x9a);
+        let prior = Gaussian::standard();
+        let result = sbc(
+            &prior,
+            |_: &f64, _| Vec::<f64>::new(),
+            |_: &[f64], rng| prior.sample(9, rng),
+            |&x: &f64| vec![x, 1.0],
+            500,
+            3,
+            &mut rng,
+        );
+        assert_eq!(result.histograms.len(), 2);
+        assert!(result
+            .histograms
+            .iter()
+            .all(|hist| hist.iter().sum::<u32>() == 500));
+        assert!(result.min_p_value() > 1E-3);
+    }
+}
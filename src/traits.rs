@@ -38,6 +38,38 @@ pub trait Rv<X> {
     /// ```
     fn ln_f(&self, x: &X) -> f64;
 
+    /// Log probability function evaluated at each of `xs`, written to `out`
+    ///
+    /// Implementors can override this to compute normalizing constants once
+    /// for the whole batch rather than once per point.
+    ///
+    /// # Panics
+    ///
+    /// If `xs` and `out` have different lengths
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Gaussian;
+    /// use rv::traits::Rv;
+    ///
+    /// let g = Gaussian::new(1.0, 2.0).unwrap();
+    /// let xs: Vec<f64> = vec![-1.0, 0.0, 3.5];
+    /// let mut out = vec![0.0; xs.len()];
+    ///
+    /// g.ln_f_many(&xs, &mut out);
+    ///
+    /// for (x, ln_f) in xs.iter().zip(out.iter()) {
+    ///     assert!((g.ln_f(x) - ln_f).abs() < 1E-12);
+    /// }
+    /// ```
+    fn ln_f_many(&self, xs: &[X], out: &mut [f64]) {
+        assert_eq!(xs.len(), out.len(), "xs and out must be the same length");
+        xs.iter()
+            .zip(out.iter_mut())
+            .for_each(|(x, y)| *y = self.ln_f(x));
+    }
+
     /// Single draw from the `Rv`
     ///
     /// # Example
@@ -114,6 +146,137 @@ pub trait Rv<X> {
     ) -> Box<dyn Iterator<Item = X> + 'r> {
         Box::new(std::iter::repeat_with(move || self.draw(&mut rng)))
     }
+
+    /// Multiple draws of the `Rv` using a deterministic generator seeded
+    /// with `seed`
+    ///
+    /// The same seed always gives the same draws. See [`crate::rng::Seeded`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Gaussian;
+    /// use rv::traits::Rv;
+    ///
+    /// let gauss = Gaussian::standard();
+    /// let xs: Vec<f64> = gauss.sample_seeded(10, 1337);
+    /// let ys: Vec<f64> = gauss.sample_seeded(10, 1337);
+    ///
+    /// assert_eq!(xs, ys);
+    /// ```
+    fn sample_seeded(&self, n: usize, seed: u64) -> Vec<X> {
+        self.sample(n, &mut crate::rng::Seeded::new(seed))
+    }
+}
+
+/// Random variables whose sampler can fail to accept a proposal
+///
+/// Rejection samplers loop until a proposal is accepted, which may take
+/// arbitrarily long for extreme parameters. `try_draw` stops after
+/// `max_proposals` proposals and then does what `policy` says.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{DrawPolicy, VonMises};
+/// use rv::traits::{Support, TryDraw};
+///
+/// let vm = VonMises::new(1.0, 2.0).unwrap();
+/// let mut rng = rand::thread_rng();
+///
+/// let x: f64 = vm.try_draw(100, DrawPolicy::Error, &mut rng).unwrap();
+/// assert!(vm.supports(&x));
+///
+/// // With no proposals allowed, only the fallback can produce a draw
+/// let res: Result<f64, _> = vm.try_draw(0, DrawPolicy::Error, &mut rng);
+/// assert!(res.is_err());
+/// let x: f64 = vm.try_draw(0, DrawPolicy::Fallback, &mut rng).unwrap();
+/// assert!(vm.supports(&x));
+/// ```
+pub trait TryDraw<X>: Rv<X> {
+    /// Single draw of the `Rv` using at most `max_proposals` proposals
+    /// before falling back to `policy`
+    fn try_draw<R: Rng>(
+        &self,
+        max_proposals: usize,
+        policy: crate::dist::DrawPolicy,
+        rng: &mut R,
+    ) -> Result<X, crate::dist::DrawError>;
+}
+
+/// Explicit control over lazily cached quantities
+///
+/// Expensive quantities such as log normalizers and Cholesky factors are
+/// computed the first time they are needed and reused after. `precompute`
+/// fills every cache up front, so that, e.g., a server can warm its
+/// distributions at startup rather than paying for it on the first request.
+/// `invalidate` empties the caches. Setters already invalidate the caches
+/// they affect, so `invalidate` is never needed for correctness.
+///
+/// # Thread safety
+///
+/// Caches are stored in `OnceLock`s, so a distribution may be shared between
+/// threads by reference, and `precompute`, or any method that fills a cache,
+/// may be called from any of them. Each cache is computed at most once;
+/// threads that need a cache while another thread is computing it wait for
+/// the result.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::Gaussian;
+/// use rv::traits::{CacheControl, Rv};
+///
+/// let mut gauss = Gaussian::new(1.0, 2.0).unwrap();
+/// gauss.precompute();
+///
+/// let ln_f = gauss.ln_f(&0.5_f64);
+/// gauss.invalidate();
+/// assert_eq!(gauss.ln_f(&0.5_f64), ln_f);
+/// ```
+pub trait CacheControl {
+    /// Compute and store every lazily cached quantity
+    fn precompute(&self);
+
+    /// Empty the caches. They are recomputed on next use.
+    fn invalidate(&mut self);
+}
+
+/// Affine transformations of location-scale families
+///
+/// If X is in a location-scale family, so is cX + d. `shift` and `rescale`
+/// return the distribution of the transformed variable, so that parameters
+/// need not be transformed by hand. A negative factor reflects the
+/// distribution.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{Gaussian, Uniform};
+/// use rv::traits::Affine;
+///
+/// let gauss = Gaussian::new(1.0, 2.0).unwrap();
+/// assert_eq!(gauss.affine(3.0, -1.0).unwrap(), Gaussian::new(2.0, 6.0).unwrap());
+///
+/// let unif = Uniform::new(1.0, 2.0).unwrap();
+/// assert_eq!(unif.rescale(-2.0).unwrap(), Uniform::new(-4.0, -2.0).unwrap());
+/// assert!(unif.rescale(0.0).is_err());
+/// ```
+pub trait Affine: Sized {
+    /// The distribution of X + `shift`
+    fn shift(&self, shift: f64) -> Result<Self, crate::dist::AffineError>;
+
+    /// The distribution of `scale` X
+    fn rescale(&self, scale: f64) -> Result<Self, crate::dist::AffineError>;
+
+    /// The distribution of `scale` X + `shift`
+    fn affine(
+        &self,
+        scale: f64,
+        shift: f64,
+    ) -> Result<Self, crate::dist::AffineError> {
+        self.rescale(scale)?.shift(shift)
+    }
 }
 
 /// Identifies the support of the Rv
@@ -214,6 +377,30 @@ pub trait ContinuousDistr<X>: Rv<X> + Support<X> {
             std::f64::NEG_INFINITY
         }
     }
+
+    /// The PDF at `x`, taking `x` by value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Gaussian;
+    /// use rv::traits::ContinuousDistr;
+    ///
+    /// let g = Gaussian::standard();
+    /// assert_eq!(g.pdf_at(0.5), g.pdf(&0.5_f64));
+    ///
+    /// let fs: Vec<f64> =
+    ///     [-1.0, 0.0, 1.0].into_iter().map(|x| g.pdf_at(x)).collect();
+    /// assert_eq!(fs[0], fs[2]);
+    /// ```
+    fn pdf_at(&self, x: X) -> f64 {
+        self.pdf(&x)
+    }
+
+    /// The log PDF at `x`, taking `x` by value
+    fn ln_pdf_at(&self, x: X) -> f64 {
+        self.ln_pdf(&x)
+    }
 }
 
 /// Has a cumulative distribution function (CDF)
@@ -238,6 +425,76 @@ pub trait Cdf<X>: Rv<X> {
     fn sf(&self, x: &X) -> f64 {
         1.0 - self.cdf(x)
     }
+
+    /// The CDF at `x`, taking `x` by value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Gaussian;
+    /// use rv::traits::Cdf;
+    ///
+    /// let g = Gaussian::new(1.0, 1.5).unwrap();
+    /// assert!((g.cdf_at(1.0) - 0.5).abs() < 1E-12);
+    /// assert_eq!(g.sf_at(2.0), g.sf(&2.0_f64));
+    /// ```
+    fn cdf_at(&self, x: X) -> f64 {
+        self.cdf(&x)
+    }
+
+    /// The survival function at `x`, taking `x` by value
+    fn sf_at(&self, x: X) -> f64 {
+        self.sf(&x)
+    }
+
+    /// Probability integral transform of the observation `x`, `CDF(x)`.
+    ///
+    /// If `x` was drawn from a continuous distribution, the PIT is uniformly
+    /// distributed on [0, 1], so the PITs of observations under their
+    /// forecast distributions measure calibration. See
+    /// [`pit_uniformity`](crate::misc::pit_uniformity).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Gaussian;
+    /// use rv::traits::Cdf;
+    ///
+    /// let forecast = Gaussian::new(1.0, 2.0).unwrap();
+    /// assert!((forecast.pit(&1.0_f64) - 0.5).abs() < 1E-12);
+    /// ```
+    fn pit(&self, x: &X) -> f64 {
+        self.cdf(x)
+    }
+
+    /// Randomized probability integral transform for discrete distributions.
+    ///
+    /// The PIT of a discrete variable is not uniform, so the transform is
+    /// drawn uniformly from [CDF(x-), CDF(x)], where CDF(x-) = CDF(x) - P(x).
+    /// Under a calibrated forecast the result is uniform on [0, 1].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Poisson;
+    /// use rv::traits::{Cdf, DiscreteDistr};
+    ///
+    /// let forecast = Poisson::new(2.0).unwrap();
+    /// let mut rng = rand::thread_rng();
+    ///
+    /// let u = forecast.randomized_pit(&2_u32, &mut rng);
+    /// let lower = forecast.cdf(&1_u32);
+    /// let upper = forecast.cdf(&2_u32);
+    /// assert!(lower - 1E-12 <= u && u <= upper + 1E-12);
+    /// ```
+    fn randomized_pit<R: Rng>(&self, x: &X, rng: &mut R) -> f64
+    where
+        Self: DiscreteDistr<X>,
+    {
+        let upper = self.cdf(x);
+        let u: f64 = rng.gen();
+        (-u).mul_add(self.pmf(x), upper).clamp(0.0, 1.0)
+    }
 }
 
 /// Has an inverse-CDF / quantile function
@@ -290,6 +547,202 @@ pub trait InverseCdf<X>: Rv<X> + Support<X> {
     }
 }
 
+/// Tail risk measures of a loss distribution, where larger values are worse.
+///
+/// The defaults work from the quantile function. Distributions with closed
+/// forms override them.
+pub trait RiskMeasure: InverseCdf<f64> {
+    /// Value at risk (VaR) at level `p`: the loss exceeded with probability
+    /// at most `1 - p`, i.e. the `p` quantile
+    ///
+    /// # Panics
+    /// If `p` is not in (0, 1)
+    fn value_at_risk(&self, p: f64) -> f64 {
+        assert!(0.0 < p && p < 1.0, "p must be in (0, 1)");
+        self.invcdf(p)
+    }
+
+    /// Expected shortfall at level `p`, also called tail value at risk
+    /// (TVaR) or conditional value at risk (CVaR): the average loss over the
+    /// worst `1 - p` of outcomes
+    ///
+    /// The default averages the quantile function over (p, 1) by
+    /// quadrature, ignoring the outermost 1E-15 of the tail, which cannot be
+    /// resolved in `f64`. It is infinite if the tail has no mean.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::{Beta, Gaussian};
+    /// use rv::traits::*;
+    ///
+    /// let returns = Gaussian::new(0.0, 1.0).unwrap();
+    /// assert::close(returns.value_at_risk(0.975), 1.959_963_984_540_054, 1E-9);
+    /// assert::close(returns.expected_shortfall(0.975), 2.337_802_792_201_415, 1E-9);
+    ///
+    /// // Numeric for distributions without a closed form
+    /// let loss_rate = Beta::new(2.0, 8.0).unwrap();
+    /// let es = loss_rate.expected_shortfall(0.99);
+    /// assert!(es > loss_rate.value_at_risk(0.99) && es < 1.0);
+    /// ```
+    ///
+    /// # Panics
+    /// If `p` is not in (0, 1)
+    fn expected_shortfall(&self, p: f64) -> f64 {
+        assert!(0.0 < p && p < 1.0, "p must be in (0, 1)");
+        crate::dist::quantile_tail_mean(self, p)
+    }
+
+    /// The distortion risk measure: the mean of the loss with its tail
+    /// probabilities distorted by `distortion`, found by quadrature of the
+    /// quantile function. See [`Distorted`](crate::dist::Distorted) for the
+    /// distorted distribution itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::{Distortion, Gaussian};
+    /// use rv::traits::*;
+    ///
+    /// // The Wang transform loads a Gaussian loss by lambda sigma
+    /// let loss = Gaussian::new(100.0, 20.0).unwrap();
+    /// let wang = Distortion::wang(0.25).unwrap();
+    /// assert::close(loss.distortion_risk(&wang), 105.0, 1E-6);
+    /// ```
+    fn distortion_risk(&self, distortion: &crate::dist::Distortion) -> f64 {
+        crate::dist::distorted_mean(self, distortion)
+    }
+}
+
+/// Highest density interval (HDI): the shortest interval containing a given
+/// probability mass.
+///
+/// Unlike the equal-tailed [`InverseCdf::interval`], the HDI of a skewed
+/// distribution puts the same density at both ends. Both are credible
+/// intervals when the distribution is a posterior.
+///
+/// Provided for continuous distributions over `f64` with an [`InverseCdf`],
+/// by minimizing the width of the interval over the probability in its lower
+/// tail, and for discrete distributions over `u32` with a [`Mean`], by
+/// growing the interval outward from the mode one value at a time. Both
+/// assume the distribution is unimodal.
+pub trait Hdi<X> {
+    /// The shortest interval containing `mass` of the probability.
+    ///
+    /// For discrete distributions, the interval contains at least `mass`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::{Gamma, Poisson};
+    /// use rv::traits::*;
+    ///
+    /// let gamma = Gamma::new(2.0, 1.0).unwrap();
+    /// let (a, b) = gamma.hdi(0.9);
+    ///
+    /// // The interval contains 90% of the mass and has equal density at
+    /// // both ends
+    /// assert!((gamma.cdf(&b) - gamma.cdf(&a) - 0.9).abs() < 1E-8);
+    /// assert!((gamma.pdf(&a) - gamma.pdf(&b)).abs() < 1E-6);
+    ///
+    /// // It is shorter than the equal-tailed interval
+    /// let (c, d): (f64, f64) = gamma.interval(0.9);
+    /// assert!(b - a < d - c);
+    ///
+    /// let poisson = Poisson::new(3.5).unwrap();
+    /// let (lower, upper) = poisson.hdi(0.9);
+    /// assert_eq!((lower, upper), (1, 6));
+    /// ```
+    ///
+    /// # Panics
+    /// If `mass` is not in (0, 1)
+    fn hdi(&self, mass: f64) -> (X, X);
+}
+
+impl<Fx> Hdi<f64> for Fx
+where
+    Fx: InverseCdf<f64>,
+{
+    fn hdi(&self, mass: f64) -> (f64, f64) {
+        assert!(0.0 < mass && mass < 1.0, "mass must be in (0, 1)");
+
+        // Golden-section search for the lower tail probability of the
+        // narrowest interval. The ends of the search interval are never
+        // evaluated, so quantiles at 0 and 1 are not needed.
+        let inv_phi = (5.0_f64.sqrt() - 1.0) / 2.0;
+        let width = |p: f64| self.invcdf(p + mass) - self.invcdf(p);
+        let (mut a, mut b) = (0.0, 1.0 - mass);
+        let mut c = inv_phi.mul_add(a - b, b);
+        let mut d = inv_phi.mul_add(b - a, a);
+        let (mut w_c, mut w_d) = (width(c), width(d));
+        for _ in 0..200 {
+            if b - a <= 1E-14 {
+                break;
+            }
+            if w_c < w_d {
+                b = d;
+                d = c;
+                w_d = w_c;
+                c = inv_phi.mul_add(a - b, b);
+                w_c = width(c);
+            } else {
+                a = c;
+                c = d;
+                w_c = w_d;
+                d = inv_phi.mul_add(b - a, a);
+                w_d = width(d);
+            }
+        }
+        let p = (a + b) / 2.0;
+        (self.invcdf(p), self.invcdf(p + mass))
+    }
+}
+
+impl<Fx> Hdi<u32> for Fx
+where
+    Fx: DiscreteDistr<u32> + Mean<f64>,
+{
+    fn hdi(&self, mass: f64) -> (u32, u32) {
+        assert!(0.0 < mass && mass < 1.0, "mass must be in (0, 1)");
+
+        // Climb from the mean to the mode
+        let mean = self.mean().map_or(0.0, |m| m.round());
+        let mut mode = if mean.is_finite() {
+            mean.clamp(0.0, f64::from(u32::MAX)) as u32
+        } else {
+            0
+        };
+        let pmf = |x: u32| self.f(&x);
+        while mode < u32::MAX && pmf(mode + 1) > pmf(mode) {
+            mode += 1;
+        }
+        while mode > 0 && pmf(mode - 1) > pmf(mode) {
+            mode -= 1;
+        }
+
+        let (mut lower, mut upper) = (mode, mode);
+        let mut total = pmf(mode);
+        while total < mass {
+            let f_lower = if lower > 0 { pmf(lower - 1) } else { 0.0 };
+            let f_upper = if upper < u32::MAX {
+                pmf(upper + 1)
+            } else {
+                0.0
+            };
+            if f_lower <= 0.0 && f_upper <= 0.0 {
+                break;
+            } else if f_lower >= f_upper {
+                lower -= 1;
+                total += f_lower;
+            } else {
+                upper += 1;
+                total += f_upper;
+            }
+        }
+        (lower, upper)
+    }
+}
+
 /// Is a discrete probability distribution
 pub trait DiscreteDistr<X>: Rv<X> + Support<X> {
     /// Probability mass function (PMF) at `x`
@@ -337,6 +790,26 @@ pub trait DiscreteDistr<X>: Rv<X> + Support<X> {
             std::f64::NEG_INFINITY
         }
     }
+
+    /// The PMF at `x`, taking `x` by value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Poisson;
+    /// use rv::traits::DiscreteDistr;
+    ///
+    /// let pois = Poisson::new(2.0).unwrap();
+    /// assert_eq!(pois.pmf_at(3_u32), pois.pmf(&3_u32));
+    /// ```
+    fn pmf_at(&self, x: X) -> f64 {
+        self.pmf(&x)
+    }
+
+    /// The log PMF at `x`, taking `x` by value
+    fn ln_pmf_at(&self, x: X) -> f64 {
+        self.ln_pmf(&x)
+    }
 }
 
 /// Defines the distribution mean
@@ -424,6 +897,203 @@ pub trait KlDivergence {
     }
 }
 
+/// Jensen-Shannon divergences
+///
+/// JS(P|Q) = (KL(P|M) + KL(Q|M)) / 2, where M = (P + Q) / 2. Unlike the KL
+/// divergence, it is symmetric and bounded by ln 2.
+///
+/// Distributions without a closed form can use
+/// [`QuadDivergence::js_quad`] or [`McDivergence::js_mc`].
+pub trait JsDivergence {
+    /// The JS divergence, JS(P|Q), between this distribution, P, and another,
+    /// Q, in nats
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Categorical;
+    /// use rv::traits::JsDivergence;
+    ///
+    /// let c1 = Categorical::new(&[0.5, 0.5, 0.0]).unwrap();
+    /// let c2 = Categorical::new(&[0.0, 0.0, 1.0]).unwrap();
+    ///
+    /// // Disjoint supports have the largest divergence, where KL is infinite
+    /// assert!((c1.js(&c2) - 2.0_f64.ln()).abs() < 1E-12);
+    /// assert_eq!(c1.js(&c1), 0.0);
+    /// ```
+    fn js(&self, other: &Self) -> f64;
+}
+
+/// Hellinger distances
+///
+/// H(P, Q) = sqrt(1 - BC(P, Q)), where BC is the Bhattacharyya coefficient,
+/// ∫ sqrt(p(x) q(x)) dx. It is a metric bounded by 1.
+///
+/// Distributions without a closed form can use
+/// [`QuadDivergence::hellinger_quad`] or [`McDivergence::hellinger_mc`].
+pub trait HellingerDistance {
+    /// The Hellinger distance between this distribution and another
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Gaussian;
+    /// use rv::traits::HellingerDistance;
+    ///
+    /// let g1 = Gaussian::new(0.0, 1.0).unwrap();
+    /// let g2 = Gaussian::new(1.0, 1.0).unwrap();
+    ///
+    /// let h = g1.hellinger(&g2);
+    /// assert!((h - (1.0 - (-0.125_f64).exp()).sqrt()).abs() < 1E-12);
+    /// assert_eq!(h, g2.hellinger(&g1));
+    /// ```
+    fn hellinger(&self, other: &Self) -> f64;
+}
+
+/// JS divergence and Hellinger distance by numerical integration
+///
+/// Provided for every continuous distribution with [`QuadBounds`], so that
+/// any two such distributions, of the same type or not, can be compared.
+/// Prefer [`JsDivergence`] and [`HellingerDistance`] where they exist.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{Gaussian, Mixture};
+/// use rv::traits::{HellingerDistance, QuadDivergence};
+///
+/// let g1 = Gaussian::new(0.0, 1.0).unwrap();
+/// let g2 = Gaussian::new(1.0, 2.0).unwrap();
+/// assert!((g1.hellinger_quad(&g2) - g1.hellinger(&g2)).abs() < 1E-8);
+///
+/// let mm = Mixture::uniform(vec![g1.clone(), g2.clone()]).unwrap();
+/// assert!(g1.js_quad(&mm) < g1.js_quad(&g2));
+/// ```
+pub trait QuadDivergence: ContinuousDistr<f64> + QuadBounds {
+    /// The JS divergence, in nats, by adaptive quadrature over the union of
+    /// the quad bounds
+    fn js_quad<Q>(&self, other: &Q) -> f64
+    where
+        Q: ContinuousDistr<f64> + QuadBounds,
+    {
+        // p ln(2p / (p + q)), which is zero where p is
+        fn term(p: f64, q: f64) -> f64 {
+            if p > 0.0 {
+                p * (2.0 * p / (p + q)).ln()
+            } else {
+                0.0
+            }
+        }
+
+        let integrand = |x: f64| {
+            let p = self.pdf(&x);
+            let q = other.pdf(&x);
+            0.5 * (term(p, q) + term(q, p))
+        };
+        quad_union(self, other, integrand).clamp(0.0, std::f64::consts::LN_2)
+    }
+
+    /// The Hellinger distance by adaptive quadrature over the union of the
+    /// quad bounds
+    fn hellinger_quad<Q>(&self, other: &Q) -> f64
+    where
+        Q: ContinuousDistr<f64> + QuadBounds,
+    {
+        let bc = quad_union(self, other, |x: f64| {
+            (self.pdf(&x) * other.pdf(&x)).sqrt()
+        });
+        (1.0 - bc).max(0.0).sqrt()
+    }
+}
+
+impl<Fx> QuadDivergence for Fx where Fx: ContinuousDistr<f64> + QuadBounds {}
+
+fn quad_union<P, Q, F>(p: &P, q: &Q, f: F) -> f64
+where
+    P: QuadBounds + ?Sized,
+    Q: QuadBounds + ?Sized,
+    F: Fn(f64) -> f64,
+{
+    use crate::misc::adaptive_gauss_legendre_quadrature;
+
+    let (lower_p, upper_p) = p.quad_bounds();
+    let (lower_q, upper_q) = q.quad_bounds();
+    let bounds = (lower_p.min(lower_q), upper_p.max(upper_q));
+    adaptive_gauss_legendre_quadrature(f, bounds, 1E-12, 20)
+}
+
+/// JS divergence and Hellinger distance by Monte Carlo
+///
+/// Provided for every distribution, including multivariate and discrete
+/// ones. The estimates have standard errors of order 1 / sqrt(`n`). Prefer
+/// [`JsDivergence`], [`HellingerDistance`] or [`QuadDivergence`] where they
+/// exist.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::Gaussian;
+/// use rv::traits::{McDivergence, QuadDivergence};
+///
+/// let mut rng = rand::thread_rng();
+/// let g1 = Gaussian::new(0.0, 1.0).unwrap();
+/// let g2 = Gaussian::new(1.0, 2.0).unwrap();
+///
+/// // The type of the data must be named if it cannot be inferred
+/// let js_mc = McDivergence::<f64>::js_mc(&g1, &g2, 100_000, &mut rng);
+/// assert!((js_mc - g1.js_quad(&g2)).abs() < 0.01);
+/// ```
+pub trait McDivergence<X>: Rv<X> {
+    /// Monte Carlo estimate of the JS divergence, in nats, from `n` draws
+    /// from each distribution
+    fn js_mc<Q, R>(&self, other: &Q, n: usize, rng: &mut R) -> f64
+    where
+        Q: Rv<X>,
+        R: Rng,
+    {
+        // ln(2 f / (f + g)) = ln 2 + ln f - ln(f + g)
+        fn ln_ratio(ln_f: f64, ln_g: f64) -> f64 {
+            let ln_sum = if ln_g > ln_f {
+                ln_g + (ln_f - ln_g).exp().ln_1p()
+            } else {
+                ln_f + (ln_g - ln_f).exp().ln_1p()
+            };
+            std::f64::consts::LN_2 + ln_f - ln_sum
+        }
+
+        let kl_pm: f64 = self
+            .sample(n, rng)
+            .iter()
+            .map(|x| ln_ratio(self.ln_f(x), other.ln_f(x)))
+            .sum();
+        let kl_qm: f64 = other
+            .sample(n, rng)
+            .iter()
+            .map(|x| ln_ratio(other.ln_f(x), self.ln_f(x)))
+            .sum();
+        (0.5 * (kl_pm + kl_qm) / n as f64).clamp(0.0, std::f64::consts::LN_2)
+    }
+
+    /// Monte Carlo estimate of the Hellinger distance from `n` draws from
+    /// this distribution
+    fn hellinger_mc<Q, R>(&self, other: &Q, n: usize, rng: &mut R) -> f64
+    where
+        Q: Rv<X>,
+        R: Rng,
+    {
+        // BC = E_p[sqrt(q(x) / p(x))]
+        let bc = self
+            .sample(n, rng)
+            .iter()
+            .map(|x| (0.5 * (other.ln_f(x) - self.ln_f(x))).exp())
+            .sum::<f64>()
+            / n as f64;
+        (1.0 - bc).max(0.0).sqrt()
+    }
+}
+
+impl<X, Fx> McDivergence<X> for Fx where Fx: Rv<X> {}
+
 /// The data for this distribution can be summarized by a statistic
 pub trait HasSuffStat<X>: Rv<X> {
     type Stat: SuffStat<X>;
@@ -505,6 +1175,31 @@ pub trait SuffStat<X> {
     fn forget_many(&mut self, xs: &[X]) {
         xs.iter().for_each(|x| self.forget(x));
     }
+
+    /// Assimilate `count` observations of the datum `x`, e.g., from a
+    /// histogram, without expanding them into raw observations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::data::PoissonSuffStat;
+    /// use rv::traits::SuffStat;
+    ///
+    /// let mut stat = PoissonSuffStat::new();
+    /// stat.observe_count(&3_u32, 1_000);
+    /// stat.observe_count(&4_u32, 500);
+    ///
+    /// assert_eq!(stat.n(), 1_500);
+    /// assert_eq!(stat.sum(), 5_000.0);
+    /// ```
+    fn observe_count(&mut self, x: &X, count: usize) {
+        (0..count).for_each(|_| self.observe(x));
+    }
+
+    /// Forget `count` observations of the datum `x`
+    fn forget_count(&mut self, x: &X, count: usize) {
+        (0..count).for_each(|_| self.forget(x));
+    }
 }
 
 /// A prior on `Fx` that induces a posterior that is the same form as the prior
@@ -622,6 +1317,60 @@ where
         self.ln_m_with_cache(&cache, x)
     }
 
+    /// Log marginal likelihood of data that arrive in batches
+    ///
+    /// Returns the log marginal likelihood of all the data seen so far after
+    /// each batch. Only a sufficient statistic is kept between batches, so
+    /// memory does not grow with the data. `fx` supplies the empty
+    /// sufficient statistic.
+    ///
+    /// # Example
+    ///
+    /// An online Bayes factor comparing a prior that expects a fair coin to
+    /// one that expects heads
+    ///
+    /// ```
+    /// use rv::data::DataOrSuffStat;
+    /// use rv::dist::{Bernoulli, Beta};
+    /// use rv::traits::*;
+    ///
+    /// let fair = Beta::new(20.0, 20.0).unwrap();
+    /// let heads = Beta::new(8.0, 2.0).unwrap();
+    /// let fx = Bernoulli::uniform();
+    ///
+    /// let flips = vec![true, true, false, true, true, true, true, false];
+    /// let batches: Vec<&[bool]> = flips.chunks(3).collect();
+    ///
+    /// let ln_m_fair = fair.ln_m_stream(&fx, batches.iter().copied());
+    /// let ln_m_heads = heads.ln_m_stream(&fx, batches.iter().copied());
+    ///
+    /// // The last partial marginal covers all the data
+    /// let ln_m: f64 = fair.ln_m(&DataOrSuffStat::Data(&flips));
+    /// assert::close(ln_m_fair[2], ln_m, 1E-12);
+    ///
+    /// let ln_bf: Vec<f64> = ln_m_heads
+    ///     .iter()
+    ///     .zip(ln_m_fair.iter())
+    ///     .map(|(h, f)| h - f)
+    ///     .collect();
+    /// assert!(ln_bf[2] > 0.0);
+    /// ```
+    fn ln_m_stream<'a, I>(&self, fx: &Fx, batches: I) -> Vec<f64>
+    where
+        I: IntoIterator<Item = &'a [X]>,
+        X: 'a,
+    {
+        let cache = self.ln_m_cache();
+        let mut stat = fx.empty_suffstat();
+        batches
+            .into_iter()
+            .map(|batch| {
+                stat.observe_many(batch);
+                self.ln_m_with_cache(&cache, &DataOrSuffStat::SuffStat(&stat))
+            })
+            .collect()
+    }
+
     /// Compute the cache for the Log posterior predictive of y given x.
     ///
     /// The cache should encompass all information about `x`.
@@ -636,6 +1385,93 @@ where
         self.ln_pp_with_cache(&cache, y)
     }
 
+    /// Log posterior predictive of each of `ys` given x
+    ///
+    /// Each y is evaluated on its own against the same posterior, which is
+    /// computed once. The sum of the results is **not** the log predictive
+    /// of seeing all of `ys`, because seeing one y changes the posterior for
+    /// the next. Use [`ln_pp_joint`](ConjugatePrior::ln_pp_joint) for that.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::data::DataOrSuffStat;
+    /// use rv::dist::{Gamma, Poisson};
+    /// use rv::traits::*;
+    ///
+    /// let prior = Gamma::new(2.0, 1.0).unwrap();
+    /// let xs: Vec<u32> = vec![1, 4, 2];
+    /// let data: DataOrSuffStat<u32, Poisson> = DataOrSuffStat::Data(&xs);
+    ///
+    /// let ys: Vec<u32> = vec![0, 2, 5];
+    /// let ln_pps = prior.ln_pp_many(&ys, &data);
+    ///
+    /// assert_eq!(ln_pps[1], prior.ln_pp(&2, &data));
+    /// ```
+    fn ln_pp_many(&self, ys: &[X], x: &DataOrSuffStat<X, Fx>) -> Vec<f64> {
+        let cache = self.ln_pp_cache(x);
+        ys.iter()
+            .map(|y| self.ln_pp_with_cache(&cache, y))
+            .collect()
+    }
+
+    /// Joint log posterior predictive of all of `ys` given x
+    ///
+    /// This is ln p(y<sub>1</sub>, ..., y<sub>m</sub> | x), which updates
+    /// the posterior with each y in turn, and equals
+    /// ln m(x, y<sub>1</sub>, ..., y<sub>m</sub>) - ln m(x). The ys are
+    /// exchangeable, so their order does not matter. `fx` supplies the empty
+    /// sufficient statistic.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::data::{DataOrSuffStat, PoissonSuffStat};
+    /// use rv::dist::{Gamma, Poisson};
+    /// use rv::traits::*;
+    ///
+    /// let prior = Gamma::new(2.0, 1.0).unwrap();
+    /// let fx = Poisson::new(1.0).unwrap();
+    /// let xs: Vec<u32> = vec![1, 4, 2];
+    /// let data: DataOrSuffStat<u32, Poisson> = DataOrSuffStat::Data(&xs);
+    /// let ys: Vec<u32> = vec![0, 2, 5];
+    ///
+    /// let ln_joint = prior.ln_pp_joint(&fx, &ys, &data);
+    ///
+    /// // Predict each y from the data and the ys before it
+    /// let mut stat = PoissonSuffStat::new();
+    /// stat.observe_many(&xs);
+    /// let mut ln_seq = 0.0;
+    /// for y in ys.iter() {
+    ///     ln_seq += prior.ln_pp(y, &DataOrSuffStat::SuffStat(&stat));
+    ///     stat.observe(y);
+    /// }
+    /// assert::close(ln_joint, ln_seq, 1E-10);
+    ///
+    /// // Treating the ys as independent given x is not the same
+    /// let ln_indep: f64 = prior.ln_pp_many(&ys, &data).iter().sum();
+    /// assert!((ln_joint - ln_indep).abs() > 1E-3);
+    /// ```
+    fn ln_pp_joint(&self, fx: &Fx, ys: &[X], x: &DataOrSuffStat<X, Fx>) -> f64
+    where
+        Fx::Stat: Clone,
+    {
+        let cache = self.ln_m_cache();
+        let mut stat = match x {
+            DataOrSuffStat::SuffStat(stat) => (*stat).clone(),
+            DataOrSuffStat::Data(xs) => {
+                let mut stat = fx.empty_suffstat();
+                stat.observe_many(xs);
+                stat
+            }
+            DataOrSuffStat::None => fx.empty_suffstat(),
+        };
+        let ln_m_x =
+            self.ln_m_with_cache(&cache, &DataOrSuffStat::SuffStat(&stat));
+        stat.observe_many(ys);
+        self.ln_m_with_cache(&cache, &DataOrSuffStat::SuffStat(&stat)) - ln_m_x
+    }
+
     /// Marginal likelihood of x
     fn m(&self, x: &DataOrSuffStat<X, Fx>) -> f64 {
         self.ln_m(x).exp()
@@ -647,7 +1483,208 @@ where
     }
 }
 
+/// Conditional distributions of joint distributions
+///
+/// `E` is the evidence, usually the values of some of the variables as
+/// `(index, value)` pairs. The conditional distribution is over the
+/// remaining variables, in their original order, so conditionals can be
+/// conditioned again or plugged in anywhere the joint type can.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::{BivariateGaussian, Gaussian};
+/// use rv::traits::Condition;
+///
+/// let bvg = BivariateGaussian::new((0.0, 0.0), (1.0, 1.0), 0.5).unwrap();
+///
+/// // Y | X = 1
+/// let y_given_x: Gaussian = bvg.condition(&(0, 1.0)).unwrap();
+/// assert::close(y_given_x.mu(), 0.5, 1E-12);
+/// assert::close(y_given_x.sigma(), 0.75_f64.sqrt(), 1E-12);
+/// ```
+pub trait Condition<E: ?Sized> {
+    /// The distribution of the remaining variables
+    type Conditional;
+
+    /// The distribution of the remaining variables given `evidence`
+    fn condition(
+        &self,
+        evidence: &E,
+    ) -> Result<Self::Conditional, crate::dist::ConditionError>;
+}
+
+/// Marginal distributions of joint distributions
+///
+/// `ixs` selects the variables to keep, in the order they should appear in
+/// the marginal. Every other variable is integrated out.
+///
+/// # Example
+///
+/// ```
+/// use rv::dist::Dirichlet;
+/// use rv::traits::Marginalize;
+///
+/// let dir = Dirichlet::new(vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+///
+/// // The marginal of (x_2, x_0), with the rest lumped into a final component
+/// let marginal = dir.marginalize(&[2, 0]).unwrap();
+/// assert_eq!(marginal.alphas(), &vec![3.0, 1.0, 6.0]);
+/// ```
+pub trait Marginalize {
+    /// The distribution of the selected variables
+    type Marginal;
+
+    /// The distribution of the variables at `ixs`
+    fn marginalize(
+        &self,
+        ixs: &[usize],
+    ) -> Result<Self::Marginal, crate::dist::MarginalizeError>;
+}
+
 /// Get the quad bounds of a univariate real distribution
 pub trait QuadBounds {
     fn quad_bounds(&self) -> (f64, f64);
 }
+
+/// CDF and inverse CDF by numerical integration of the PDF
+///
+/// Provided for every continuous distribution with [`QuadBounds`], so
+/// distributions without a closed-form CDF, such as mixtures, still have
+/// approximate ones. Prefer [`Cdf`] and [`InverseCdf`] where they exist.
+pub trait QuadCdf: ContinuousDistr<f64> + QuadBounds {
+    /// The CDF at `x` by adaptive quadrature of the PDF over the quad
+    /// bounds
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::{Gaussian, Mixture};
+    /// use rv::traits::{Cdf, QuadCdf};
+    ///
+    /// let g = Gaussian::new(1.0, 2.0).unwrap();
+    /// assert!((g.cdf_quad(&2.5) - g.cdf(&2.5)).abs() < 1E-10);
+    ///
+    /// let mm = Mixture::uniform(vec![
+    ///     Gaussian::new(-2.0, 0.5).unwrap(),
+    ///     Gaussian::new(2.0, 1.0).unwrap(),
+    /// ])
+    /// .unwrap();
+    /// assert!((mm.cdf_quad(&0.0) - 0.5).abs() < 0.05);
+    /// ```
+    fn cdf_quad(&self, x: &f64) -> f64 {
+        use crate::misc::adaptive_gauss_legendre_quadrature;
+
+        let (lower, upper) = self.quad_bounds();
+        if *x <= lower {
+            return 0.0;
+        } else if *x >= upper {
+            return 1.0;
+        }
+
+        // Integrate over the shorter tail to limit the error
+        let pdf = |y: f64| self.pdf(&y);
+        let p = if *x - lower <= upper - *x {
+            adaptive_gauss_legendre_quadrature(pdf, (lower, *x), 1E-12, 20)
+        } else {
+            1.0 - adaptive_gauss_legendre_quadrature(
+                pdf,
+                (*x, upper),
+                1E-12,
+                20,
+            )
+        };
+        p.clamp(0.0, 1.0)
+    }
+
+    /// The inverse CDF at `p` by bisection on [`cdf_quad`](QuadCdf::cdf_quad)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::Gaussian;
+    /// use rv::traits::{InverseCdf, QuadCdf};
+    ///
+    /// let g = Gaussian::standard();
+    /// let x: f64 = g.invcdf(0.9);
+    /// assert!((g.invcdf_quad(0.9) - x).abs() < 1E-8);
+    /// ```
+    ///
+    /// # Panics
+    /// If `p` is not in [0, 1]
+    fn invcdf_quad(&self, p: f64) -> f64 {
+        assert!((0.0..=1.0).contains(&p), "p must be in [0, 1]");
+        let (mut lower, mut upper) = self.quad_bounds();
+        for _ in 0..100 {
+            let mid = (lower + upper) / 2.0;
+            if self.cdf_quad(&mid) < p {
+                lower = mid;
+            } else {
+                upper = mid;
+            }
+            if upper - lower <= 1E-12 * mid.abs().max(1.0) {
+                break;
+            }
+        }
+        (lower + upper) / 2.0
+    }
+}
+
+impl<Fx> QuadCdf for Fx where Fx: ContinuousDistr<f64> + QuadBounds {}
+
+/// A family of univariate real distributions that other distributions can be
+/// projected onto with [`Project`]
+pub trait ProjectionFamily: ContinuousDistr<f64> + Sized {
+    /// The member of the family with the given mean and variance, if there
+    /// is one
+    fn from_mean_var(mean: f64, var: f64) -> Option<Self>;
+
+    /// The parameters mapped to the real line, e.g., by taking the log of
+    /// positive parameters
+    fn unconstrained_params(&self) -> Vec<f64>;
+
+    /// The member of the family with the given unconstrained parameters, if
+    /// they are valid
+    fn from_unconstrained_params(params: &[f64]) -> Option<Self>;
+}
+
+/// Approximation of a distribution by the closest member of a tractable
+/// family, e.g., to collapse a mixture or compound inside expectation
+/// propagation
+///
+/// Provided for every continuous distribution with [`QuadBounds`]. The
+/// moments of the distribution and the divergences are found by quadrature
+/// over the quad bounds.
+pub trait Project: ContinuousDistr<f64> + QuadBounds {
+    /// The member of the family `D` closest to this distribution by
+    /// `method`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rv::dist::{Gamma, Gaussian, Mixture, ProjectionMethod};
+    /// use rv::traits::*;
+    ///
+    /// let mm = Mixture::uniform(vec![
+    ///     Gaussian::new(-3.0, 1.0).unwrap(),
+    ///     Gaussian::new(1.0, 0.5).unwrap(),
+    /// ])
+    /// .unwrap();
+    ///
+    /// // The Gaussian with the mean and variance of the mixture
+    /// let g: Gaussian = mm.project_to(ProjectionMethod::MomentMatch).unwrap();
+    /// let mean: f64 = mm.mean().unwrap();
+    /// assert!((g.mu() - mean).abs() < 1E-8);
+    ///
+    /// // A Gamma cannot match a mean below zero
+    /// assert!(mm.project_to::<Gamma>(ProjectionMethod::MomentMatch).is_err());
+    /// ```
+    fn project_to<D: ProjectionFamily>(
+        &self,
+        method: crate::dist::ProjectionMethod,
+    ) -> Result<D, crate::dist::ProjectionError> {
+        crate::dist::project(self, method)
+    }
+}
+
+impl<Fx> Project for Fx where Fx: ContinuousDistr<f64> + QuadBounds {}
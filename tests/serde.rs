@@ -0,0 +1,300 @@
+#![cfg(feature = "serde1")]
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256Plus;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use rv::data::*;
+use rv::dist::*;
+use rv::model::{
+    AnomalyScorer, ComponentSelection, DpMixture, DpMixtureSlice, DpmInference,
+    FitAutoOptions, FittedGmm, LatentClassModel, ScalarKalman,
+};
+use rv::traits::*;
+use rv::ConjugateModel;
+
+fn round_trip<T>(x: &T)
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let json = serde_json::to_string(x).unwrap();
+    let y: T = serde_json::from_str(&json).unwrap();
+    assert_eq!(&y, x, "json: {}", json);
+
+    let yaml = serde_yaml::to_string(x).unwrap();
+    let y: T = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(&y, x, "yaml: {}", yaml);
+}
+
+macro_rules! round_trip_all {
+    ($($x: expr),+ $(,)?) => {
+        $(round_trip(&$x);)+
+    };
+}
+
+#[test]
+fn univariate_distributions() {
+    round_trip_all!(
+        Bernoulli::new(0.3).unwrap(),
+        Beta::new(2.0, 3.0).unwrap(),
+        BetaBinomial::new(10, 0.5, 1.5).unwrap(),
+        Binomial::new(12, 0.4).unwrap(),
+        Categorical::new(&[0.2, 0.3, 0.5]).unwrap(),
+        Cauchy::new(0.5, 2.0).unwrap(),
+        ChiSquared::new(3.5).unwrap(),
+        CompetingRisks::new(vec![vec![0.01, 0.2], vec![0.02, 0.1]]).unwrap(),
+        CompoundPoisson::new(2.0, Exponential::new(1.0).unwrap(), 0.5, 16)
+            .unwrap(),
+        DiscreteSurvival::new(vec![0.2, 0.1, 0.05]).unwrap(),
+        DiscreteUniform::new(2_u32, 9).unwrap(),
+        Distorted::new(
+            Gamma::new(2.0, 1.0).unwrap(),
+            Distortion::wang(0.5).unwrap()
+        )
+        .unwrap(),
+        Dpd::new(vec![0.4, 0.3], 0.3).unwrap(),
+        Exponential::new(1.5).unwrap(),
+        Gamma::new(2.0, 3.0).unwrap(),
+        Gaussian::new(-1.0, 0.5).unwrap(),
+        Geometric::new(0.25).unwrap(),
+        Gev::new(0.0, 1.0, 0.2).unwrap(),
+        InvChiSquared::new(3.0).unwrap(),
+        InvGamma::new(3.0, 2.0).unwrap(),
+        InvGaussian::new(1.0, 2.0).unwrap(),
+        Kumaraswamy::new(2.0, 3.0).unwrap(),
+        Laplace::new(1.0, 0.5).unwrap(),
+        LogNormal::new(0.0, 0.7).unwrap(),
+        LogUniform::new(1E-3, 10.0).unwrap(),
+        LogitNormal::new(-0.5, 1.2).unwrap(),
+        Lomax::new(2.0, 1.5).unwrap(),
+        NegBinomial::new(3.0, 0.4).unwrap(),
+        Pareto::new(2.0, 1.0).unwrap(),
+        Poisson::new(4.5).unwrap(),
+        RecordTime::k_record(3, 2).unwrap(),
+        RecordValue::new(Exponential::new(1.5).unwrap(), 2).unwrap(),
+        RecordWaitingTime::new(2).unwrap(),
+        ScaledBeta::new(2.0, 3.0, -1.0, 4.0).unwrap(),
+        ScaledInvChiSquared::new(3.0, 1.5).unwrap(),
+        Skellam::new(2.0, 3.0).unwrap(),
+        StudentsT::new(4.0).unwrap(),
+        Uniform::new(-1.0, 2.0).unwrap(),
+        VonMises::new(1.0, 2.0).unwrap(),
+        Weibull::new(1.5, 2.0).unwrap(),
+        ZeroInflated::new(0.2, Poisson::new(3.0).unwrap()).unwrap(),
+    );
+}
+
+#[test]
+fn multivariate_and_prior_distributions() {
+    round_trip_all!(
+        BivariateGaussian::new((0.0, 1.0), (1.0, 2.0), 0.3).unwrap(),
+        Crp::new(1.5, 10).unwrap(),
+        Dirichlet::new(vec![1.0, 2.0, 3.0]).unwrap(),
+        SymmetricDirichlet::new(0.5, 4).unwrap(),
+        GammaRatePrior::new(Gamma::new(2.0, 1.0).unwrap(), 3.0).unwrap(),
+        JointCategorical::new(vec![2, 2], &[0.1, 0.2, 0.3, 0.4]).unwrap(),
+        MarkovChain::new(&[0.5, 0.5], &[vec![0.9, 0.1], vec![0.2, 0.8]])
+            .unwrap()
+            .with_draw_len(10),
+        MarkovChainPrior::symmetric(1.0, 3).unwrap(),
+        NormalGamma::new(0.0, 1.0, 2.0, 3.0).unwrap(),
+        NormalInvChiSquared::new(0.0, 1.0, 2.0, 3.0).unwrap(),
+        NormalInvGamma::new(0.0, 1.0, 2.0, 3.0).unwrap(),
+        StickBreaking::new(2.0, 5).unwrap(),
+    );
+}
+
+#[cfg(feature = "arraydist")]
+#[test]
+fn matrix_distributions() {
+    use nalgebra::{DMatrix, DVector};
+
+    let mu = DVector::from_column_slice(&[0.0, 1.0]);
+    let cov = DMatrix::from_row_slice(2, 2, &[2.0, 0.5, 0.5, 1.0]);
+    round_trip_all!(
+        MvGaussian::new(mu.clone(), cov.clone()).unwrap(),
+        MvGaussianDiag::new(
+            mu.clone(),
+            DVector::from_column_slice(&[1.0, 0.5])
+        )
+        .unwrap(),
+        NormalInvWishart::new(mu.clone(), 1.0, 3, cov.clone()).unwrap(),
+        InvWishart::new(cov, 3).unwrap(),
+    );
+
+    let loadings = DMatrix::from_column_slice(2, 1, &[1.0, 0.5]);
+    round_trip(
+        &rv::model::FactorModel::new(mu, loadings, DVector::repeat(2, 0.1))
+            .unwrap(),
+    );
+}
+
+#[test]
+fn wrapper_distributions() {
+    let mm = Mixture::new(
+        vec![0.3, 0.7],
+        vec![Gaussian::standard(), Gaussian::new(2.0, 0.5).unwrap()],
+    )
+    .unwrap();
+    round_trip(&mm);
+
+    // Nested mixtures
+    round_trip(&Mixture::uniform(vec![mm.clone(), mm]).unwrap());
+
+    let labels = LabelMap::new(["a", "b"]).unwrap();
+    round_trip(&LabeledCategorical::new(labels, &[0.4, 0.6]).unwrap());
+
+    let shared = SharedRv::new(Gamma::new(2.0, 1.0).unwrap());
+    let json = serde_json::to_string(&shared).unwrap();
+    let loaded: SharedRv<Gamma> = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.inner(), shared.inner());
+}
+
+#[test]
+fn suffstats() {
+    let mut rng = Xoshiro256Plus::seed_from_u64(0x5E);
+    let xs: Vec<f64> = Gamma::new(2.0, 1.0).unwrap().sample(50, &mut rng);
+    let counts: Vec<u32> = Poisson::new(3.0).unwrap().sample(50, &mut rng);
+    let flips: Vec<bool> = Bernoulli::new(0.3).unwrap().sample(50, &mut rng);
+
+    macro_rules! observed {
+        ($stat: expr, $xs: expr) => {{
+            let mut stat = $stat;
+            stat.observe_many($xs);
+            stat
+        }};
+    }
+
+    let probs: Vec<f64> = xs.iter().map(|x| x / (1.0 + x)).collect();
+    let cats: Vec<usize> = counts.iter().map(|&k| k as usize % 3).collect();
+    let trials: Vec<(u64, u64)> =
+        counts.iter().map(|&k| (u64::from(k), 10)).collect();
+
+    round_trip_all!(
+        observed!(BernoulliSuffStat::new(), &flips),
+        observed!(BetaSuffStat::new(), &probs),
+        observed!(BinomialSuffStat::new(), &trials),
+        observed!(CategoricalSuffStat::new(3), &cats),
+        observed!(ExponentialSuffStat::new(), &xs),
+        observed!(GammaSuffStat::new(), &xs),
+        observed!(GaussianSuffStat::new(), &xs),
+        observed!(GeometricSuffStat::new(), &counts),
+        observed!(InvGammaSuffStat::new(), &xs),
+        observed!(InvGaussianSuffStat::new(), &xs),
+        observed!(MarkovChainSuffStat::new(3), std::slice::from_ref(&cats)),
+        observed!(ParetoSuffStat::new(), &xs),
+        observed!(PoissonSuffStat::new(), &counts),
+        observed!(UniformSuffStat::new(), &xs),
+        observed!(ZeroInflatedSuffStat::new(PoissonSuffStat::new()), &counts),
+    );
+
+    round_trip(&Partition::from_z(vec![0, 1, 0, 2, 1]).unwrap());
+}
+
+#[test]
+fn models() {
+    let mut rng = Xoshiro256Plus::seed_from_u64(0x5E);
+    let counts: Vec<u32> = Poisson::new(3.0).unwrap().sample(40, &mut rng);
+
+    let prior = Arc::new(Gamma::new(1.0, 1.0).unwrap());
+    let mut model = ConjugateModel::<u32, Poisson, Gamma>::new(
+        &Poisson::new(1.0).unwrap(),
+        prior,
+    );
+    counts.iter().for_each(|k| model.observe(k));
+    round_trip(&model);
+
+    for inference in [
+        DpmInference::Svi {
+            truncation: 5,
+            batch_size: 10,
+            n_steps: 20,
+        },
+        DpmInference::Slice { n_iters: 20 },
+    ] {
+        let mut dpm: DpMixture<u32, Poisson, Gamma> =
+            DpMixture::new(1.0, Gamma::new(1.0, 0.1).unwrap(), inference)
+                .unwrap();
+        round_trip(&dpm);
+        dpm.fit(&counts, &mut rng);
+        round_trip(&dpm);
+    }
+
+    let mut slice: DpMixtureSlice<u32, Poisson, Gamma> =
+        DpMixtureSlice::new(1.0, Gamma::new(1.0, 0.1).unwrap()).unwrap();
+    slice.run(&counts, 10, &mut rng);
+    round_trip(&slice);
+
+    let mut kf = ScalarKalman::new(Gaussian::standard(), 0.5, 0.1).unwrap();
+    kf.observe(0.3);
+    round_trip(&kf);
+
+    let responses: Vec<Vec<bool>> = (0..20)
+        .map(|i| vec![i % 2 == 0, i % 3 == 0, i % 2 == 0])
+        .collect();
+    round_trip(&LatentClassModel::fit(&responses, 2, &mut rng).unwrap());
+
+    let xs = [-1.2, -0.8, -1.0, 2.1, 1.9, 2.4];
+    round_trip(&FittedGmm::fit(&xs, 2, &mut rng).unwrap());
+    round_trip(
+        &FitAutoOptions::new(ComponentSelection::DirichletProcess {
+            alpha: 0.5,
+        })
+        .with_max_components(4),
+    );
+
+    let scorer = AnomalyScorer::new(Gaussian::standard(), &xs, 0.5).unwrap();
+    round_trip(&scorer);
+    let scores: Vec<f64> = Gaussian::standard().sample(200, &mut rng);
+    round_trip(&scorer.calibrate(&scores, 0.1).unwrap());
+}
+
+#[cfg(feature = "process")]
+#[test]
+fn kernels() {
+    use nalgebra::DVector;
+    use rv::process::gaussian::kernel::*;
+
+    let rbf = RBFKernel::new(1.5).unwrap();
+    let constant = ConstantKernel::new(2.0).unwrap();
+    let white = WhiteKernel::new(0.1).unwrap();
+    let periodic = ExpSineSquaredKernel::new(1.0, 2.0).unwrap();
+
+    round_trip_all!(
+        rbf.clone(),
+        constant.clone() * rbf.clone(),
+        constant.clone() * rbf.clone() + white.clone(),
+        (constant.clone() * periodic.clone()) * rbf.clone() + white.clone(),
+    );
+
+    let spectral = SpectralMixtureKernel::new(
+        DVector::from_column_slice(&[1.0, 0.5]),
+        DVector::from_column_slice(&[0.2, 1.1]),
+        DVector::from_column_slice(&[0.01, 0.1]),
+    )
+    .unwrap();
+    let change = ChangePointKernel::new(rbf.clone(), periodic, 0.0, 1.0)
+        .unwrap()
+        .with_dim(1);
+    round_trip_all!(
+        KernelNode::from(constant * rbf + white),
+        KernelNode::from(spectral),
+        KernelNode::from(ActiveDimsKernel::new(change, vec![0, 2])),
+        quasi_periodic(&[1.0, 7.0], 10.0, 1.0).unwrap(),
+    );
+}
+
+#[cfg(feature = "process")]
+#[test]
+fn markov_processes() {
+    use rv::process::markov::*;
+
+    round_trip_all!(
+        WienerProcess::new(0.5, 2.0).unwrap().with_start(1.0, -1.0),
+        OrnsteinUhlenbeck::new(2.0, 1.0, 0.5).unwrap(),
+        RandomWalk::new(0.1, 1.0).unwrap().with_start(3, 2.0),
+    );
+}